@@ -0,0 +1,26 @@
+-- Full-text index over chunk contents (keyword half of hybrid search)
+-- `chunk_text` holds each chunk's location and original text; `chunks_fts` indexes it as
+-- an external-content table, so the text is stored once. The indexed terms are not the
+-- raw text but indexer::tokenize::code_terms of it (identifiers plus their
+-- camelCase/snake_case parts); rows leave the index through the FTS5 'delete' command
+-- with those same terms.
+CREATE TABLE IF NOT EXISTS chunk_text (
+    id INTEGER PRIMARY KEY,
+    chunk_id TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    line_start INTEGER NOT NULL,
+    line_end INTEGER NOT NULL,
+    body TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_chunk_text_file_path ON chunk_text(file_path);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
+    body,
+    content = 'chunk_text',
+    content_rowid = 'id',
+    tokenize = "unicode61 tokenchars '_$'"
+);
+
+-- Existing indices are filled from their LanceDB chunks on the next sync
+-- (indexer::pipeline::backfill_chunk_text)
@@ -0,0 +1,16 @@
+-- Implicit relevance feedback: which returned files agents actually opened after a search
+
+-- Paths returned by search-type tool calls (JSON array), so later reads can be matched to them
+ALTER TABLE audit_log ADD COLUMN result_paths TEXT;
+
+-- Decayed click counts per (normalized query, file); updated_at is the time of the last decay
+CREATE TABLE IF NOT EXISTS search_feedback (
+    query          TEXT    NOT NULL,
+    file_path      TEXT    NOT NULL,
+    score          REAL    NOT NULL DEFAULT 0,
+    updated_at     INTEGER NOT NULL,
+    last_search_id INTEGER,
+    PRIMARY KEY (query, file_path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_feedback_file ON search_feedback(file_path);
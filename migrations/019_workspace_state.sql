@@ -0,0 +1,30 @@
+-- Agent workspace states and TODO notes that survive across sessions
+-- (docs/features/21-workspace-state.md)
+
+CREATE TABLE IF NOT EXISTS workspace_states (
+    state_id      TEXT    PRIMARY KEY,
+    task          TEXT,
+    metadata      TEXT    NOT NULL DEFAULT '{}', -- free-form JSON: plan, progress, next steps
+    touched_files TEXT    NOT NULL DEFAULT '[]', -- JSON array of project-relative paths
+    transactions  TEXT    NOT NULL DEFAULT '[]', -- JSON array of open transactions with staged operations
+    created_at    INTEGER NOT NULL,
+    updated_at    INTEGER NOT NULL
+);
+
+-- Notes anchored to a file and optionally a symbol. `line` (1-based) and `anchor_text`
+-- are the last known position; the symbol name is what anchors are re-resolved by.
+CREATE TABLE IF NOT EXISTS todo_notes (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_path   TEXT    NOT NULL,
+    symbol_name TEXT,
+    line        INTEGER,
+    anchor_text TEXT,
+    text        TEXT    NOT NULL,
+    priority    TEXT    NOT NULL DEFAULT 'medium',
+    state_id    TEXT,
+    created_at  INTEGER NOT NULL,
+    resolved_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_notes_file ON todo_notes(file_path);
+CREATE INDEX IF NOT EXISTS idx_todo_notes_state ON todo_notes(state_id);
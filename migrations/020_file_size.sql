@@ -0,0 +1,4 @@
+-- File size next to last_modified, so startup reconciliation can trust an
+-- unchanged (mtime, size) pair without re-hashing the file.
+-- -1 means unknown: rows written before this column, or by single-file reindexing.
+ALTER TABLE files ADD COLUMN size INTEGER NOT NULL DEFAULT -1;
@@ -0,0 +1,75 @@
+-- Database schema model replayed from the project's SQL migrations, and the
+-- places in code that reference tables and columns. Lines are 1-based.
+
+-- Tables and columns a migration dropped or renamed are kept with the
+-- location of that migration (`dropped_file`/`dropped_line`).
+CREATE TABLE IF NOT EXISTS db_tables (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    name         TEXT    NOT NULL,
+    file_path    TEXT    NOT NULL,
+    line         INTEGER NOT NULL,
+    dropped_file TEXT,
+    dropped_line INTEGER,
+    renamed_to   TEXT
+);
+
+CREATE TABLE IF NOT EXISTS db_columns (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id     INTEGER NOT NULL,
+    name         TEXT    NOT NULL,
+    data_type    TEXT    NOT NULL DEFAULT '',
+    not_null     INTEGER NOT NULL DEFAULT 0,
+    primary_key  INTEGER NOT NULL DEFAULT 0,
+    position     INTEGER NOT NULL,
+    file_path    TEXT    NOT NULL,
+    line         INTEGER NOT NULL,
+    dropped_file TEXT,
+    dropped_line INTEGER,
+    renamed_to   TEXT,
+    FOREIGN KEY(table_id) REFERENCES db_tables(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS db_indexes (
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id  INTEGER NOT NULL,
+    name      TEXT    NOT NULL,
+    columns   TEXT    NOT NULL, -- JSON array
+    is_unique INTEGER NOT NULL DEFAULT 0,
+    file_path TEXT    NOT NULL,
+    line      INTEGER NOT NULL,
+    FOREIGN KEY(table_id) REFERENCES db_tables(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS db_foreign_keys (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id    INTEGER NOT NULL,
+    columns     TEXT    NOT NULL, -- JSON array
+    ref_table   TEXT    NOT NULL,
+    ref_columns TEXT    NOT NULL, -- JSON array
+    file_path   TEXT    NOT NULL,
+    line        INTEGER NOT NULL,
+    FOREIGN KEY(table_id) REFERENCES db_tables(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_db_columns_table ON db_columns(table_id);
+CREATE INDEX IF NOT EXISTS idx_db_indexes_table ON db_indexes(table_id);
+CREATE INDEX IF NOT EXISTS idx_db_foreign_keys_table ON db_foreign_keys(table_id);
+
+-- Written per file by the indexer, independent of the schema model: `table_name`
+-- lowercased and without the default schema, `column_name` empty for
+-- table-level usages (ORM entity mappings). `ambiguous` marks an unqualified
+-- column of a multi-table query, recorded once per candidate table.
+CREATE TABLE IF NOT EXISTS column_usages (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_id     INTEGER NOT NULL,
+    table_name  TEXT    NOT NULL,
+    column_name TEXT    NOT NULL,
+    line        INTEGER NOT NULL,
+    kind        TEXT    NOT NULL, -- 'query', 'entity', 'dsl'
+    ambiguous   INTEGER NOT NULL DEFAULT 0,
+    context     TEXT,
+    FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_column_usages_file ON column_usages(file_id);
+CREATE INDEX IF NOT EXISTS idx_column_usages_column ON column_usages(column_name);
@@ -0,0 +1,23 @@
+-- API endpoints from every source: routes registered in framework code and
+-- operations declared by OpenAPI specs, GraphQL schemas and .proto services.
+-- Rows are replaced per file, so the old UNIQUE(method, path) cannot hold: a
+-- spec and its implementation declare the same route. Nothing wrote this table
+-- before, so it is recreated rather than migrated.
+DROP TABLE IF EXISTS api_endpoints;
+
+CREATE TABLE api_endpoints (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    method        TEXT    NOT NULL, -- 'GET', ...; 'QUERY'/'MUTATION'/'SUBSCRIPTION'; 'RPC'
+    path          TEXT    NOT NULL, -- '/api/users/{id}', GraphQL field, '/pkg.Service/Method'
+    source        TEXT    NOT NULL DEFAULT 'code', -- 'code', 'openapi', 'graphql', 'proto'
+    handler       TEXT,             -- handler function, operationId, field or rpc name
+    request_type  TEXT,
+    response_type TEXT,
+    file_id       INTEGER NOT NULL,
+    line          INTEGER,          -- 0-based, like symbol lines
+    FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_endpoints_path ON api_endpoints(path);
+CREATE INDEX IF NOT EXISTS idx_api_endpoints_file ON api_endpoints(file_id);
+CREATE INDEX IF NOT EXISTS idx_frontend_calls_file ON frontend_api_calls(file_id);
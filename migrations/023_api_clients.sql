@@ -0,0 +1,21 @@
+-- Frontend calls made through imported clients and wrappers. The base URL of
+-- such a call is only known in the file that defines the client, so the
+-- client name and the module it was imported from are stored with the call
+-- and resolved against api_bases at query time.
+ALTER TABLE frontend_api_calls ADD COLUMN client TEXT;
+ALTER TABLE frontend_api_calls ADD COLUMN client_source TEXT;
+ALTER TABLE frontend_api_calls ADD COLUMN needs_client INTEGER NOT NULL DEFAULT 0; -- plain call of an unknown function
+
+-- URL prefixes added by client instances (`axios.create({ baseURL })`),
+-- wrapper functions and URL constants, per defining file.
+CREATE TABLE IF NOT EXISTS api_bases (
+    id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    name     TEXT    NOT NULL,
+    base_url TEXT    NOT NULL,
+    file_id  INTEGER NOT NULL,
+    line     INTEGER,          -- 0-based
+    FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_bases_name ON api_bases(name);
+CREATE INDEX IF NOT EXISTS idx_api_bases_file ON api_bases(file_id);
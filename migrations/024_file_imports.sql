@@ -0,0 +1,16 @@
+-- File -> file import graph, resolved at index time with the same rules as
+-- context bundles (relative paths, crate:: paths, tsconfig aliases, Python
+-- packages). Targets are stored by path so an edge survives the target being
+-- indexed later or reindexed under a new id; imports of packages are not
+-- stored here (see dependency_usage).
+CREATE TABLE IF NOT EXISTS file_imports (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_id     INTEGER NOT NULL,
+    target_path TEXT    NOT NULL,
+    line        INTEGER NOT NULL, -- 0-based
+    specifier   TEXT    NOT NULL, -- import path as written
+    FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_imports_file ON file_imports(file_id);
+CREATE INDEX IF NOT EXISTS idx_file_imports_target ON file_imports(target_path);
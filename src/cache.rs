@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::hash::Hash;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::RwLock;
 
 /// LRU cache with size-based eviction
@@ -201,12 +201,25 @@ impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
     }
 }
 
+/// Mined terms kept before the map is cleared and refilled
+const MAX_MINED_TERMS: usize = 1024;
+
+/// Query-expansion data of a project, kept apart from the LRU layers
+#[derive(Default)]
+struct SynonymCache {
+    /// Synonym groups and the `synonyms.toml` modification time they were loaded at
+    groups: Option<(Option<SystemTime>, Arc<Vec<Vec<String>>>)>,
+    /// Co-occurring symbols mined per query term
+    mined: HashMap<String, Vec<String>>,
+}
+
 /// Cache manager with multiple cache layers
 pub struct CacheManager {
     file_cache: Arc<RwLock<LruCache<String, String>>>,
     symbol_cache: Arc<RwLock<LruCache<String, String>>>,
     symbol_cache_rkyv: Arc<RwLock<LruCache<String, AlignedVec>>>,
     search_cache: Arc<RwLock<LruCache<String, String>>>,
+    synonyms: Arc<RwLock<SynonymCache>>,
 
     file_ttl: Duration,
     symbol_ttl: Duration,
@@ -227,6 +240,7 @@ impl CacheManager {
             symbol_cache_rkyv: Arc::new(RwLock::new(LruCache::new(50 * 1024 * 1024))),
             // 20 MB for search
             search_cache: Arc::new(RwLock::new(LruCache::new(20 * 1024 * 1024))),
+            synonyms: Arc::new(RwLock::new(SynonymCache::default())),
 
             file_ttl: Duration::from_secs(300),   // 5 minutes
             symbol_ttl: Duration::from_secs(600), // 10 minutes
@@ -290,6 +304,35 @@ impl CacheManager {
         // Also invalidate related caches
         let mut symbol_cache = self.symbol_cache.write().await;
         symbol_cache.invalidate_prefix(&format!("file:{}", path));
+        symbol_cache.invalidate_prefix(&format!("bundle:{}:", path));
+    }
+
+    // Context bundle cache operations (kept with symbols, keyed by bundled file)
+    pub async fn get_bundle(&self, path: &str, options: &str) -> Option<String> {
+        self.get_symbols(&format!("bundle:{}:{}", path, options))
+            .await
+    }
+
+    pub async fn put_bundle(&self, path: &str, options: &str, data: String) {
+        self.put_symbols(format!("bundle:{}:{}", path, options), data)
+            .await;
+    }
+
+    /// Drop cached bundles of `paths`, e.g. the importers of a changed file
+    pub async fn invalidate_bundles(&self, paths: &[String]) {
+        if paths.is_empty() {
+            return;
+        }
+        let mut symbol_cache = self.symbol_cache.write().await;
+        for path in paths {
+            symbol_cache.invalidate_prefix(&format!("bundle:{}:", path));
+        }
+    }
+
+    /// Drop every cached bundle, e.g. after a sync reindexed many files
+    pub async fn invalidate_all_bundles(&self) {
+        let mut symbol_cache = self.symbol_cache.write().await;
+        symbol_cache.invalidate_prefix("bundle:");
     }
 
     // Symbol cache operations
@@ -362,6 +405,52 @@ impl CacheManager {
         cache.clear();
     }
 
+    // Synonym cache operations
+    /// Synonym groups, if they were loaded while `synonyms.toml` had modification time `stamp`
+    pub async fn get_synonym_groups(
+        &self,
+        stamp: Option<SystemTime>,
+    ) -> Option<Arc<Vec<Vec<String>>>> {
+        let cache = self.synonyms.read().await;
+        cache
+            .groups
+            .as_ref()
+            .filter(|(loaded_at, _)| *loaded_at == stamp)
+            .map(|(_, groups)| groups.clone())
+    }
+
+    pub async fn put_synonym_groups(
+        &self,
+        stamp: Option<SystemTime>,
+        groups: Vec<Vec<String>>,
+    ) -> Arc<Vec<Vec<String>>> {
+        let groups = Arc::new(groups);
+        self.synonyms.write().await.groups = Some((stamp, groups.clone()));
+        groups
+    }
+
+    /// Drop the loaded synonym groups, e.g. after a `synonyms` rule was added
+    pub async fn invalidate_synonym_groups(&self) {
+        self.synonyms.write().await.groups = None;
+    }
+
+    pub async fn get_mined_synonyms(&self, term: &str) -> Option<Vec<String>> {
+        self.synonyms.read().await.mined.get(term).cloned()
+    }
+
+    pub async fn put_mined_synonyms(&self, term: String, names: Vec<String>) {
+        let mut cache = self.synonyms.write().await;
+        if cache.mined.len() >= MAX_MINED_TERMS {
+            cache.mined.clear();
+        }
+        cache.mined.insert(term, names);
+    }
+
+    /// Drop mined synonyms, e.g. after a sync changed which symbols co-occur
+    pub async fn invalidate_mined_synonyms(&self) {
+        self.synonyms.write().await.mined.clear();
+    }
+
     // Statistics
     pub async fn get_stats(&self) -> CacheStats {
         // Evict expired entries first
@@ -555,3 +644,34 @@ impl CacheStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bundle_invalidation() {
+        let cache = CacheManager::new();
+        for path in ["src/a.rs", "src/ab.rs", "src/b.rs"] {
+            cache
+                .put_bundle(path, "2:false", format!("bundle of {}", path))
+                .await;
+        }
+        assert_eq!(
+            cache.get_bundle("src/a.rs", "2:false").await.as_deref(),
+            Some("bundle of src/a.rs")
+        );
+
+        // Only the named file, not others sharing its prefix
+        cache.invalidate_bundles(&["src/a.rs".to_string()]).await;
+        assert!(cache.get_bundle("src/a.rs", "2:false").await.is_none());
+        assert!(cache.get_bundle("src/ab.rs", "2:false").await.is_some());
+
+        cache.invalidate_file("src/ab.rs").await;
+        assert!(cache.get_bundle("src/ab.rs", "2:false").await.is_none());
+        assert!(cache.get_bundle("src/b.rs", "2:false").await.is_some());
+
+        cache.invalidate_all_bundles().await;
+        assert!(cache.get_bundle("src/b.rs", "2:false").await.is_none());
+    }
+}
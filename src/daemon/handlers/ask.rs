@@ -0,0 +1,495 @@
+//! `ask` — retrieval-augmented answers about the codebase.
+//!
+//! Retrieves with `search` (grouped by file) plus a `context_bundle` of the best file,
+//! packs numbered snippets into a token budget, asks the chat endpoint configured in
+//! `[ask]` of config.toml and checks every `path:line` citation in the answer against
+//! the index and the files on disk. Line numbers in the prompt and citations are 1-based.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde_json::{json, Value};
+
+use super::common::{index_path, ToolContext};
+use crate::error::GoferError;
+use crate::indexer::budget::count_tokens;
+use crate::indexer::watcher::{load_config, AskConfig};
+
+const SYSTEM_PROMPT: &str = "You answer questions about a codebase using only the code provided. \
+Cite every claim with the file path and 1-based line numbers exactly as shown, e.g. `src/lib.rs:42` \
+or `src/lib.rs:40-55`. If the code provided does not answer the question, say so.";
+
+/// `path:line` or `path:start-end`; the path must contain an extension
+fn citation_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"([A-Za-z0-9_.@\-/]+\.[A-Za-z0-9]+):(\d+)(?:-(\d+))?").expect("valid regex")
+    })
+}
+
+/// A retrieved code range placed into the prompt.
+#[derive(Debug, Clone)]
+struct Snippet {
+    path: String,
+    /// 1-based, inclusive
+    line_start: usize,
+    line_end: usize,
+    text: String,
+}
+
+pub async fn tool_ask(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let question = args.get("question").and_then(|v| v.as_str()).unwrap_or("");
+    if question.trim().is_empty() {
+        return Err(GoferError::InvalidParams("Question is required".into()).into());
+    }
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(6) as usize;
+    let include_bundle = args
+        .get("include_dependencies")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let mut config = load_config(&ctx.root_path.join(".gofer")).ask;
+    if let Some(model) = args.get("model").and_then(|v| v.as_str()) {
+        config.model = model.to_string();
+    }
+    if let Some(budget) = args.get("max_context_tokens").and_then(|v| v.as_u64()) {
+        config.max_context_tokens = budget as usize;
+    }
+
+    // 1. Retrieve: one merged range per file region
+    let search = super::search::tool_search(
+        json!({
+            "query": question,
+            "limit": limit,
+            "group_by_file": true,
+            "include_context": false,
+            "exact_symbol_shortcut": false,
+        }),
+        ctx,
+    )
+    .await?;
+    let mut snippets = Vec::new();
+    for entry in search
+        .get("results")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+    {
+        if let Some(snippet) = load_snippet(entry, ctx).await {
+            snippets.push(snippet);
+        }
+    }
+
+    // 2. Signatures of what the best file imports
+    let mut dependencies: Vec<(String, String)> = Vec::new();
+    if include_bundle {
+        if let Some(top) = snippets.first() {
+            let bundle = super::files::tool_context_bundle(
+                json!({ "file": top.path, "depth": 1, "skeleton_deps_only": true }),
+                ctx,
+            )
+            .await;
+            if let Ok(bundle) = bundle {
+                for dep in bundle
+                    .get("dependencies")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    let path = dep.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                    let content = dep.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                    if !path.is_empty() && !content.is_empty() {
+                        dependencies.push((index_path(&ctx.root_path, path), content.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    // 3. Budget the prompt
+    let (context, used) = build_context(&snippets, &dependencies, config.max_context_tokens);
+    if used.is_empty() {
+        return Ok(json!({
+            "question": question,
+            "answer": "No relevant code found in the index for this question.",
+            "citations": [],
+            "sources": []
+        }));
+    }
+    let user_prompt = format!("{}\n\nQuestion: {}", context, question);
+
+    // 4. Ask
+    let answer = chat(&config, SYSTEM_PROMPT, &user_prompt).await?;
+
+    // 5. Verify citations
+    let mut citations = Vec::new();
+    for (cited, path, start, end) in extract_citations(&answer) {
+        let key = index_path(&ctx.root_path, &path);
+        let indexed = ctx.sqlite.get_file(&key).await.ok().flatten().is_some();
+        let line_count = match project_file(&ctx.root_path, &key).await {
+            Some(file) => match tokio::fs::read_to_string(file).await {
+                Ok(content) => content.lines().count(),
+                Err(_) => 0,
+            },
+            None => 0,
+        };
+        let in_range = start >= 1 && start <= end && end <= line_count;
+        let in_context = used
+            .iter()
+            .any(|s| s.path == key && start <= s.line_end && end >= s.line_start);
+        citations.push(json!({
+            "citation": cited,
+            "path": key,
+            "line_start": start,
+            "line_end": end,
+            "verified": indexed && in_range,
+            "in_context": in_context,
+        }));
+    }
+    let unverified = citations
+        .iter()
+        .filter(|c| c["verified"] != json!(true))
+        .count();
+
+    Ok(json!({
+        "question": question,
+        "answer": answer,
+        "model": config.model,
+        "citations": citations,
+        "unverified_citations": unverified,
+        "sources": used
+            .iter()
+            .map(|s| format!("{}:{}-{}", s.path, s.line_start, s.line_end))
+            .collect::<Vec<_>>(),
+        "context_tokens_estimate": count_tokens(&user_prompt),
+    }))
+}
+
+/// The file a cited `key` names, if it resolves to one under `root`. Citations come
+/// from model output, so absolute paths, `..` and symlinks may point anywhere.
+async fn project_file(root: &Path, key: &str) -> Option<PathBuf> {
+    let root = tokio::fs::canonicalize(root).await.ok()?;
+    let file = tokio::fs::canonicalize(root.join(key)).await.ok()?;
+    file.starts_with(&root).then_some(file)
+}
+
+/// Turn a grouped `search` result (`path:start-end ...\ncontent`) into a snippet with the
+/// real file lines, so line numbers in the prompt match the file on disk.
+async fn load_snippet(entry: &str, ctx: &ToolContext) -> Option<Snippet> {
+    let header = entry.lines().next()?;
+    let location = header.split_whitespace().next()?;
+    let (path, range) = location.rsplit_once(':')?;
+    let (start, end) = match range.split_once('-') {
+        Some((s, e)) => (s.parse::<usize>().ok()?, e.parse::<usize>().ok()?),
+        None => {
+            let line = range.parse::<usize>().ok()?;
+            (line, line)
+        }
+    };
+
+    let key = index_path(&ctx.root_path, path);
+    let content = tokio::fs::read_to_string(ctx.root_path.join(&key))
+        .await
+        .ok()?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return None;
+    }
+    // Index rows are 0-based
+    let first = start.min(lines.len() - 1);
+    let last = end.max(start).min(lines.len() - 1);
+    Some(Snippet {
+        path: key,
+        line_start: first + 1,
+        line_end: last + 1,
+        text: lines[first..=last].join("\n"),
+    })
+}
+
+/// Pack numbered snippets (then dependency signatures) until `budget` tokens are used.
+/// A snippet that doesn't fit whole is cut at a line boundary.
+fn build_context(
+    snippets: &[Snippet],
+    dependencies: &[(String, String)],
+    budget: usize,
+) -> (String, Vec<Snippet>) {
+    let mut out = String::new();
+    let mut used = Vec::new();
+    let mut remaining = budget;
+
+    for snippet in snippets {
+        let header = format!(
+            "### {}:{}-{}\n```\n",
+            snippet.path, snippet.line_start, snippet.line_end
+        );
+        let overhead = count_tokens(&header) + 2;
+        if remaining <= overhead {
+            break;
+        }
+        let mut body = String::new();
+        // Running total: each line is tokenized once, not the whole body per line
+        let mut body_tokens = 0;
+        let mut last_line = snippet.line_start;
+        for (offset, line) in snippet.text.lines().enumerate() {
+            let numbered = format!("{:>5} | {}\n", snippet.line_start + offset, line);
+            let line_tokens = count_tokens(&numbered);
+            if body_tokens + line_tokens + overhead > remaining {
+                break;
+            }
+            body_tokens += line_tokens;
+            body.push_str(&numbered);
+            last_line = snippet.line_start + offset;
+        }
+        if body.is_empty() {
+            break;
+        }
+        let header = format!(
+            "### {}:{}-{}\n```\n",
+            snippet.path, snippet.line_start, last_line
+        );
+        let block = format!("{}{}```\n\n", header, body);
+        remaining = remaining.saturating_sub(count_tokens(&block));
+        out.push_str(&block);
+        used.push(Snippet {
+            line_end: last_line,
+            ..snippet.clone()
+        });
+    }
+
+    for (path, content) in dependencies {
+        let block = format!(
+            "### {} (signatures only)\n```\n{}\n```\n\n",
+            path,
+            content.trim()
+        );
+        let cost = count_tokens(&block);
+        if cost > remaining {
+            continue;
+        }
+        remaining -= cost;
+        out.push_str(&block);
+    }
+
+    (out.trim_end().to_string(), used)
+}
+
+/// `(as written, path, start, end)` for every `path:line[-end]` in `answer`.
+fn extract_citations(answer: &str) -> Vec<(String, String, usize, usize)> {
+    let mut seen = std::collections::HashSet::new();
+    citation_re()
+        .captures_iter(answer)
+        .filter_map(|cap| {
+            let whole = cap.get(0)?.as_str().to_string();
+            let path = cap.get(1)?.as_str().trim_start_matches("./").to_string();
+            let start: usize = cap.get(2)?.as_str().parse().ok()?;
+            let end: usize = cap
+                .get(3)
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(start);
+            seen.insert(whole.clone())
+                .then_some((whole, path, start, end))
+        })
+        .collect()
+}
+
+/// One non-streaming chat completion against the configured endpoint.
+async fn chat(config: &AskConfig, system: &str, user: &str) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .build()?;
+    let base = config.endpoint.trim_end_matches('/');
+    let messages = json!([
+        { "role": "system", "content": system },
+        { "role": "user", "content": user },
+    ]);
+
+    let (url, body) = match config.api.as_str() {
+        "ollama" => (
+            format!("{}/api/chat", base),
+            json!({
+                "model": config.model,
+                "messages": messages,
+                "stream": false,
+                "options": {
+                    "temperature": config.temperature,
+                    "num_predict": config.max_answer_tokens,
+                },
+            }),
+        ),
+        _ => (
+            format!("{}/chat/completions", base),
+            json!({
+                "model": config.model,
+                "messages": messages,
+                "stream": false,
+                "temperature": config.temperature,
+                "max_tokens": config.max_answer_tokens,
+            }),
+        ),
+    };
+
+    let mut request = client.post(&url).json(&body);
+    if let Some(key) = config
+        .api_key_env
+        .as_deref()
+        .and_then(|var| std::env::var(var).ok())
+    {
+        request = request.bearer_auth(key);
+    }
+
+    let resp = request
+        .send()
+        .await
+        .with_context(|| format!("Chat endpoint {} unreachable", url))?;
+    if !resp.status().is_success() {
+        bail!("Chat endpoint returned status {}", resp.status());
+    }
+    let value: Value = resp.json().await?;
+
+    let content = value
+        .pointer("/choices/0/message/content")
+        .or_else(|| value.pointer("/message/content"))
+        .and_then(|v| v.as_str())
+        .context("Chat response has no message content")?;
+    Ok(content.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one HTTP request with `body`, returning the request it received.
+    async fn stub_server(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(head_end) = text.find("\r\n\r\n") {
+                    let length = text[..head_end]
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= head_end + 4 + length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (format!("http://{}", addr), handle)
+    }
+
+    #[tokio::test]
+    async fn test_chat_openai_compatible() {
+        let (url, server) = stub_server(
+            r#"{"choices":[{"message":{"role":"assistant","content":" See src/cache.rs:12-20. "}}]}"#,
+        )
+        .await;
+        let config = AskConfig {
+            endpoint: format!("{}/v1", url),
+            model: "stub-model".to_string(),
+            ..AskConfig::default()
+        };
+
+        let answer = chat(&config, "system", "question").await.unwrap();
+        assert_eq!(answer, "See src/cache.rs:12-20.");
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /v1/chat/completions"));
+        assert!(request.contains("\"model\":\"stub-model\""));
+    }
+
+    #[tokio::test]
+    async fn test_chat_ollama_native() {
+        let (url, server) =
+            stub_server(r#"{"message":{"role":"assistant","content":"ok"},"done":true}"#).await;
+        let config = AskConfig {
+            api: "ollama".to_string(),
+            endpoint: url,
+            ..AskConfig::default()
+        };
+
+        assert_eq!(chat(&config, "system", "question").await.unwrap(), "ok");
+        assert!(server.await.unwrap().starts_with("POST /api/chat"));
+    }
+
+    #[test]
+    fn test_extract_citations() {
+        let cites = extract_citations(
+            "Entries are evicted in `src/cache.rs:97-130` (see src/cache.rs:97-130 and ./src/main.rs:5).",
+        );
+        let parsed: Vec<(&str, usize, usize)> = cites
+            .iter()
+            .map(|(_, p, s, e)| (p.as_str(), *s, *e))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![("src/cache.rs", 97, 130), ("src/main.rs", 5, 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_project_file_stays_under_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        let outside = dir.path().join("secret.rs");
+        std::fs::write(&outside, "key\n").unwrap();
+
+        assert!(project_file(&root, "src/lib.rs").await.is_some());
+        assert!(project_file(&root, "src/../src/lib.rs").await.is_some());
+        assert!(project_file(&root, "../secret.rs").await.is_none());
+        assert!(project_file(&root, outside.to_str().unwrap())
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn test_build_context_respects_budget() {
+        let snippet = |path: &str, n: usize| Snippet {
+            path: path.to_string(),
+            line_start: 10,
+            line_end: 10 + n - 1,
+            text: (0..n)
+                .map(|i| format!("let value_{} = compute();", i))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        let snippets = vec![snippet("src/a.rs", 5), snippet("src/b.rs", 200)];
+
+        let (context, used) = build_context(&snippets, &[], 200);
+        assert!(count_tokens(&context) <= 200);
+        assert_eq!(used[0].line_end, 14);
+        assert!(context.contains("   10 | let value_0"));
+        // The second snippet is cut at a line boundary, with the header range matching
+        assert_eq!(used.len(), 2);
+        assert!(used[1].line_end < 209);
+        assert!(context.contains(&format!("### src/b.rs:10-{}", used[1].line_end)));
+    }
+}
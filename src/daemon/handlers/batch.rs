@@ -1,6 +1,6 @@
 use super::common::ToolContext;
 use super::files::{
-    tool_read_file, tool_read_function_context, tool_read_types_only, tool_skeleton,
+    max_tokens_arg, tool_read_file, tool_read_function_context, tool_read_types_only, tool_skeleton,
 };
 use super::search::tool_search;
 use super::symbols::{tool_get_references, tool_get_symbols};
@@ -42,6 +42,23 @@ pub async fn tool_batch_operations(args: Value, ctx: &ToolContext) -> Result<Val
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
 
+    // A batch budget is split evenly across the read operations
+    let budgeted = operations
+        .iter()
+        .filter(|op| is_budgeted(op.get("type").and_then(|v| v.as_str()).unwrap_or("")))
+        .count();
+    let per_operation = max_tokens_arg(&args)
+        .filter(|_| budgeted > 0)
+        .map(|max| max / budgeted);
+    let budgeted_operations: Vec<Value>;
+    let operations = match per_operation {
+        Some(share) => {
+            budgeted_operations = operations.iter().map(|op| with_budget(op, share)).collect();
+            &budgeted_operations
+        }
+        None => operations,
+    };
+
     let start = Instant::now();
     let results;
 
@@ -58,9 +75,12 @@ pub async fn tool_batch_operations(args: Value, ctx: &ToolContext) -> Result<Val
                 let sem = Arc::clone(&semaphore);
 
                 tokio::spawn(async move {
-                    let _permit = match sem.acquire().await {
-                        Ok(p) => p,
-                        Err(e) => return Err(anyhow::anyhow!("Semaphore closed: {}", e)),
+                    let _permit = tokio::select! {
+                        permit = sem.acquire() => match permit {
+                            Ok(p) => p,
+                            Err(e) => return Err(anyhow::anyhow!("Semaphore closed: {}", e)),
+                        },
+                        _ = ctx.cancel.cancelled() => return Ok(cancelled_result(idx, &op)),
                     };
                     execute_single_operation(idx, op, &ctx).await
                 })
@@ -70,7 +90,13 @@ pub async fn tool_batch_operations(args: Value, ctx: &ToolContext) -> Result<Val
         // Await all tasks
         let mut batch_results = Vec::new();
         for task in tasks {
-            match task.await {
+            let outcome = task.await;
+            ctx.progress.report(
+                batch_results.len() + 1,
+                Some(operations.len()),
+                "batch_operations",
+            );
+            match outcome {
                 Ok(Ok(result)) => batch_results.push(result),
                 Ok(Err(e)) => {
                     if !continue_on_error {
@@ -102,12 +128,42 @@ pub async fn tool_batch_operations(args: Value, ctx: &ToolContext) -> Result<Val
         "total_operations": operations.len(),
         "successful": successful,
         "failed": failed,
+        "cancelled": ctx.cancel.is_cancelled(),
         "parallel": parallel,
         "total_duration_ms": total_duration_ms,
+        "max_tokens_per_operation": per_operation,
         "results": results
     }))
 }
 
+/// Read operations that accept `max_tokens`
+fn is_budgeted(op_type: &str) -> bool {
+    matches!(
+        op_type,
+        "read_file" | "skeleton" | "read_function_context" | "read_types_only"
+    )
+}
+
+/// Cap a read operation's own `max_tokens` at its share of the batch budget.
+fn with_budget(operation: &Value, share: usize) -> Value {
+    let op_type = operation.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    if !is_budgeted(op_type) {
+        return operation.clone();
+    }
+    let mut operation = operation.clone();
+    let params = operation
+        .as_object_mut()
+        .map(|o| o.entry("params").or_insert_with(|| json!({})));
+    if let Some(Value::Object(params)) = params {
+        let own = params
+            .get("max_tokens")
+            .and_then(|v| v.as_u64())
+            .map_or(share, |n| (n as usize).min(share));
+        params.insert("max_tokens".into(), json!(own));
+    }
+    operation
+}
+
 /// Execute a single operation (helper for batch_operations)
 async fn execute_single_operation(
     idx: usize,
@@ -180,7 +236,12 @@ async fn execute_sequential(
     let mut results = Vec::new();
 
     for (idx, operation) in operations.iter().enumerate() {
+        if ctx.cancel.is_cancelled() {
+            break;
+        }
         let result = execute_single_operation(idx, operation.clone(), ctx).await?;
+        ctx.progress
+            .report(idx + 1, Some(operations.len()), "batch_operations");
 
         let success = result["success"].as_bool().unwrap_or(false);
         results.push(result);
@@ -193,6 +254,17 @@ async fn execute_sequential(
     Ok(results)
 }
 
+/// Result for an operation skipped because the request was cancelled
+fn cancelled_result(idx: usize, operation: &Value) -> Value {
+    json!({
+        "index": idx,
+        "type": operation.get("type").and_then(|v| v.as_str()).unwrap_or("unknown"),
+        "success": false,
+        "error": "cancelled",
+        "duration_ms": 0
+    })
+}
+
 /// Create error result for failed operations
 fn create_error_result(error: anyhow::Error) -> Value {
     json!({
@@ -1,13 +1,27 @@
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::process::Output;
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
 
 use crate::cache::CacheManager;
+use crate::daemon::state::SyncProgress;
 use crate::error_recovery::CircuitBreaker;
+use crate::indexer::api_calls::resolve_api_calls;
+use crate::indexer::watcher::{load_config, FeedbackConfig};
 use crate::indexer::EmbedderPool;
+use crate::ipc::protocol::DaemonNotification;
 use crate::languages::{rust_analyzer::RustAnalyzer, LanguageService};
+use crate::models::FrontendApiCallInfo;
 use crate::storage::{LanceStorage, SqliteStorage};
 
+/// How often long-running steps without a natural count report elapsed time.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+
 /// Context for executing tools — Arc-wrapped resources for cloning across async tasks.
 #[derive(Clone)]
 pub struct ToolContext {
@@ -22,6 +36,84 @@ pub struct ToolContext {
     pub rust_analyzer: Arc<RwLock<Option<Arc<RustAnalyzer>>>>,
     /// Language-specific services (Vue, TypeScript, Python, etc.)
     pub language_services: Arc<Vec<Box<dyn LanguageService>>>,
+    /// Cancelled when the client sends `notifications/cancelled` for this request
+    /// or disconnects
+    pub cancel: CancellationToken,
+    /// `notifications/progress` for the request's `progressToken`
+    pub progress: ProgressReporter,
+    /// The project's `[feedback]` settings
+    pub feedback: FeedbackConfig,
+}
+
+/// Sends MCP `notifications/progress` for a request's `progressToken`.
+/// Does nothing when the client did not ask for progress.
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    target: Option<(Value, mpsc::Sender<String>)>,
+}
+
+impl ProgressReporter {
+    /// Reporter for `token` writing to a connection's outgoing queue.
+    pub fn new(token: Option<Value>, tx: Option<mpsc::Sender<String>>) -> Self {
+        Self {
+            target: token.zip(tx),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Send one progress update. Never blocks: updates are dropped if the
+    /// connection is backed up.
+    pub fn report(&self, progress: usize, total: Option<usize>, message: &str) {
+        let Some((token, tx)) = &self.target else {
+            return;
+        };
+        let notif = DaemonNotification::progress(token, progress, total, message);
+        if let Ok(msg) = serde_json::to_string(&notif) {
+            let _ = tx.try_send(msg);
+        }
+    }
+
+    /// Await `fut`, reporting elapsed seconds under `label` while it runs.
+    pub async fn while_running<F: Future>(&self, label: &str, fut: F) -> F::Output {
+        tokio::pin!(fut);
+        if !self.is_active() {
+            return fut.await;
+        }
+        let start = Instant::now();
+        let mut ticks = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            tokio::select! {
+                out = &mut fut => return out,
+                _ = ticks.tick() => {
+                    let secs = start.elapsed().as_secs() as usize;
+                    self.report(secs, None, &format!("{} ({}s)", label, secs));
+                }
+            }
+        }
+    }
+
+    /// Relay an indexing [`SyncProgress`] until `done` is cancelled.
+    pub async fn relay_sync(&self, sync: Arc<SyncProgress>, done: CancellationToken) {
+        if !self.is_active() {
+            return;
+        }
+        let mut ticks = tokio::time::interval(Duration::from_millis(500));
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = done.cancelled() => break,
+            }
+            let snap = sync.snapshot();
+            if !snap.active {
+                continue;
+            }
+            let stage = sync.stage.lock().await.clone();
+            self.report(snap.files_parsed, Some(snap.files_total), &stage);
+        }
+    }
 }
 
 impl ToolContext {
@@ -55,6 +147,51 @@ impl ToolContext {
 
         Ok(ra)
     }
+
+    /// Run a child process to completion with progress heartbeats. The child is
+    /// killed when the request is cancelled (`Ok(None)`) or the future is dropped,
+    /// e.g. by a surrounding timeout.
+    pub async fn run_command(
+        &self,
+        cmd: &mut tokio::process::Command,
+        label: &str,
+    ) -> std::io::Result<Option<Output>> {
+        cmd.kill_on_drop(true);
+        tokio::select! {
+            out = self.progress.while_running(label, cmd.output()) => out.map(Some),
+            _ = self.cancel.cancelled() => Ok(None),
+        }
+    }
+}
+
+/// Tool context for handler tests: `root` as the project, an empty index in the
+/// returned directory and no embedding model.
+#[cfg(test)]
+pub(crate) async fn test_context(root: &Path) -> (ToolContext, tempfile::TempDir) {
+    let index = tempfile::tempdir().unwrap();
+    let sqlite = SqliteStorage::new(index.path().join("graph.db").to_str().unwrap())
+        .await
+        .unwrap();
+    sqlite.migrate().await.unwrap();
+    let lance = LanceStorage::new(index.path().join("lancedb").to_str().unwrap(), 384)
+        .await
+        .unwrap();
+    let breaker = || Arc::new(CircuitBreaker::new(5, 2, Duration::from_secs(30)));
+    let ctx = ToolContext {
+        sqlite: Arc::new(sqlite),
+        lance: Arc::new(Mutex::new(lance)),
+        embedder: Arc::new(EmbedderPool::unloaded()),
+        root_path: Arc::new(root.to_path_buf()),
+        cache: Arc::new(CacheManager::new()),
+        embedding_circuit: breaker(),
+        vector_circuit: breaker(),
+        rust_analyzer: Arc::new(RwLock::new(None)),
+        language_services: Arc::new(Vec::new()),
+        cancel: CancellationToken::new(),
+        progress: ProgressReporter::default(),
+        feedback: FeedbackConfig::default(),
+    };
+    (ctx, index)
 }
 
 /// Резолвинг пути: если путь относительный, превращает в абсолютный через root_path.
@@ -87,6 +224,11 @@ pub fn make_relative(root: &Path, abs_path: &str) -> String {
         .to_string()
 }
 
+/// Ключ пути в индексе: пути в `files.path` и Lance хранятся относительно root.
+pub fn index_path(root: &Path, file: &str) -> String {
+    make_relative(root, &resolve_path(root, file))
+}
+
 /// make_relative для PathBuf
 pub fn make_relative_pathbuf(root: &Path, abs_path: &Path) -> String {
     abs_path
@@ -96,3 +238,13 @@ pub fn make_relative_pathbuf(root: &Path, abs_path: &Path) -> String {
         .map(|s| s.to_string())
         .unwrap_or_else(|| abs_path.to_string_lossy().to_string())
 }
+
+/// Frontend API calls with the base URL of the client they go through. Calls
+/// of functions that are neither a known client nor listed in
+/// `[api] wrappers` are dropped.
+pub async fn frontend_api_calls(ctx: &ToolContext) -> anyhow::Result<Vec<FrontendApiCallInfo>> {
+    let calls = ctx.sqlite.get_frontend_api_calls().await?;
+    let bases = ctx.sqlite.get_api_bases().await?;
+    let wrappers = load_config(&ctx.root_path.join(".gofer")).api.wrappers;
+    Ok(resolve_api_calls(calls, &bases, &wrappers))
+}
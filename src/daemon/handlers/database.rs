@@ -0,0 +1,527 @@
+//! Database schema tools
+//!
+//! Implements:
+//! - db_schema: tables, columns, indexes and foreign keys replayed from the
+//!   project's SQL migrations, with dropped and renamed objects
+//! - column_usages: code that reads or writes a table/column (queries in string
+//!   literals, ORM entity fields, Diesel DSL), flagging usages of columns a
+//!   later migration dropped or renamed
+
+use std::collections::HashMap;
+
+use super::common::ToolContext;
+use crate::error::GoferError;
+use crate::indexer::column_usages::{table_names_for_type, KIND_ENTITY};
+use crate::indexer::db_schema::{table_key, ColumnModel, Location, SchemaModel, TableModel};
+use crate::indexer::parser::normalize_field;
+use crate::storage::ColumnUsageRow;
+use anyhow::Result;
+use serde_json::{json, Value};
+
+const DEFAULT_LIMIT: usize = 200;
+
+pub async fn tool_db_schema(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let table = args.get("table").and_then(|v| v.as_str());
+    let include_dropped = args
+        .get("include_dropped")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let model = ctx.sqlite.get_db_schema().await?;
+    if model.tables.is_empty() {
+        return Ok(json!({
+            "tables": [],
+            "message": "No schema found. Tables are read from .sql files under migrations/, db/, schema/ or sql/ directories and from schema.sql."
+        }));
+    }
+
+    let tables: Vec<&TableModel> = match table {
+        Some(name) => {
+            let key = table_key(name);
+            let found: Vec<_> = model
+                .tables
+                .iter()
+                .filter(|t| table_key(&t.name) == key)
+                .collect();
+            if found.is_empty() {
+                return Err(
+                    GoferError::InvalidParams(format!("Table not in schema: {}", name)).into(),
+                );
+            }
+            found
+        }
+        None => model
+            .tables
+            .iter()
+            .filter(|t| t.dropped.is_none())
+            .collect(),
+    };
+
+    let detailed = table.is_some();
+    let tables_out: Vec<Value> = tables
+        .iter()
+        .map(|t| {
+            let mut out = json!({
+                "name": t.name,
+                "defined": format_location(&t.defined),
+                "columns": t
+                    .columns
+                    .iter()
+                    .filter(|c| c.dropped.is_none())
+                    .map(|c| format_column(c, detailed))
+                    .collect::<Vec<_>>(),
+            });
+            if !t.indexes.is_empty() {
+                out["indexes"] = json!(t
+                    .indexes
+                    .iter()
+                    .map(|i| format!(
+                        "{}{} ({})",
+                        i.name,
+                        if i.unique { " UNIQUE" } else { "" },
+                        i.columns.join(", ")
+                    ))
+                    .collect::<Vec<_>>());
+            }
+            if !t.foreign_keys.is_empty() {
+                out["foreign_keys"] = json!(t
+                    .foreign_keys
+                    .iter()
+                    .map(|fk| format!(
+                        "({}) -> {}({})",
+                        fk.columns.join(", "),
+                        fk.ref_table,
+                        fk.ref_columns.join(", ")
+                    ))
+                    .collect::<Vec<_>>());
+            }
+            if let Some(dropped) = &t.dropped {
+                out["status"] = json!(gone("table", t.renamed_to.as_deref(), dropped));
+            }
+            out
+        })
+        .collect();
+
+    let mut result = json!({ "tables": tables_out });
+    if include_dropped {
+        let tables_in_scope: Vec<&TableModel> = match table {
+            Some(_) => tables,
+            None => model.tables.iter().collect(),
+        };
+        let dropped = dropped_objects(&tables_in_scope);
+        if !dropped.is_empty() {
+            result["dropped"] = json!(dropped);
+        }
+    }
+    Ok(result)
+}
+
+pub async fn tool_column_usages(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let table = args
+        .get("table")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty());
+    let column = args
+        .get("column")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty());
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(DEFAULT_LIMIT);
+
+    let model = ctx.sqlite.get_db_schema().await?;
+
+    let Some(table) = table else {
+        return match column {
+            Some(column) => column_everywhere(ctx, &model, column, limit).await,
+            None => stale_usages(ctx, &model, limit).await,
+        };
+    };
+
+    let key = table_key(table);
+    // Code may still use a name the table had before a rename
+    let mut keys = vec![key.clone()];
+    for old in &model.tables {
+        if old
+            .renamed_to
+            .as_deref()
+            .is_some_and(|to| table_key(to) == key)
+        {
+            keys.push(table_key(&old.name));
+        }
+    }
+    let rows = ctx.sqlite.get_column_usages(&keys).await?;
+
+    let mut usages = Vec::new();
+    let mut stale = 0usize;
+    for row in &rows {
+        if column.is_some_and(|c| !row.column_name.eq_ignore_ascii_case(c)) {
+            continue;
+        }
+        if row.kind == KIND_ENTITY {
+            continue;
+        }
+        let status = ColumnStatus::of(&model, &row.table_name, &row.column_name);
+        if row.ambiguous && !status.known() {
+            continue;
+        }
+        let note = status.note();
+        stale += usize::from(status.is_stale());
+        usages.push(format_usage(row, note.as_deref()));
+    }
+
+    let fields = entity_fields(ctx, &model, &rows, &key, column).await?;
+
+    let mut result = json!({
+        "table": table,
+        "total": usages.len(),
+        "stale": stale,
+        "usages": usages.into_iter().take(limit).collect::<Vec<_>>(),
+    });
+    if let Some(column) = column {
+        let status = ColumnStatus::of(&model, &key, column);
+        result["column"] = json!(column);
+        result["status"] = json!(status.note().unwrap_or_else(|| match status {
+            ColumnStatus::Unknown => "not in schema".to_string(),
+            ColumnStatus::NoTable => "table not in schema".to_string(),
+            _ => "live".to_string(),
+        }));
+    }
+    if !fields.is_empty() {
+        result["fields"] = json!(fields);
+    }
+    Ok(result)
+}
+
+/// Usages of `column` in any table that has or had it.
+async fn column_everywhere(
+    ctx: &ToolContext,
+    model: &SchemaModel,
+    column: &str,
+    limit: usize,
+) -> Result<Value> {
+    let tables: Vec<String> = model
+        .tables
+        .iter()
+        .filter(|t| {
+            t.columns
+                .iter()
+                .any(|c| c.name.eq_ignore_ascii_case(column))
+        })
+        .map(|t| table_key(&t.name))
+        .collect();
+    if tables.is_empty() {
+        return Err(GoferError::InvalidParams(format!(
+            "No table in the schema has a column named {}",
+            column
+        ))
+        .into());
+    }
+    let rows = ctx.sqlite.get_column_usages(&tables).await?;
+    let mut stale = 0usize;
+    let usages: Vec<String> = rows
+        .iter()
+        .filter(|r| r.column_name.eq_ignore_ascii_case(column))
+        .filter_map(|r| {
+            let status = ColumnStatus::of(model, &r.table_name, &r.column_name);
+            if r.ambiguous && !status.known() {
+                return None;
+            }
+            stale += usize::from(status.is_stale());
+            Some(format_usage(r, status.note().as_deref()))
+        })
+        .collect();
+
+    Ok(json!({
+        "column": column,
+        "tables": tables,
+        "total": usages.len(),
+        "stale": stale,
+        "usages": usages.into_iter().take(limit).collect::<Vec<_>>(),
+    }))
+}
+
+/// Project-wide: code using columns or tables that migrations dropped or renamed.
+async fn stale_usages(ctx: &ToolContext, model: &SchemaModel, limit: usize) -> Result<Value> {
+    let rows = ctx.sqlite.get_column_usages(&[]).await?;
+
+    // An unqualified column of a join is fine if any candidate table has it
+    let mut resolved: HashMap<(&str, i64, String), bool> = HashMap::new();
+    for row in rows.iter().filter(|r| r.ambiguous) {
+        let live = matches!(
+            ColumnStatus::of(model, &row.table_name, &row.column_name),
+            ColumnStatus::Live
+        );
+        *resolved
+            .entry((
+                row.file_path.as_str(),
+                row.line,
+                row.column_name.to_ascii_lowercase(),
+            ))
+            .or_default() |= live;
+    }
+
+    let mut reported = std::collections::HashSet::new();
+    let mut stale = Vec::new();
+    for row in &rows {
+        if row.kind == KIND_ENTITY && !row.column_name.is_empty() {
+            continue;
+        }
+        let status = ColumnStatus::of(model, &row.table_name, &row.column_name);
+        if !status.is_stale() {
+            continue;
+        }
+        let key = (
+            row.file_path.as_str(),
+            row.line,
+            row.column_name.to_ascii_lowercase(),
+        );
+        if row.ambiguous && resolved.get(&key).copied().unwrap_or(false) {
+            continue;
+        }
+        if reported.insert(key) {
+            stale.push(format_usage(row, status.note().as_deref()));
+        }
+    }
+
+    Ok(json!({
+        "checked": rows.len(),
+        "total": stale.len(),
+        "stale_usages": stale.into_iter().take(limit).collect::<Vec<_>>(),
+        "hint": "Pass table (and column) to list every usage of one table or column.",
+    }))
+}
+
+/// Fields of the types mapped to the table (explicitly or by name) that match
+/// its columns, as `path:line Type.field`.
+async fn entity_fields(
+    ctx: &ToolContext,
+    model: &SchemaModel,
+    rows: &[ColumnUsageRow],
+    key: &str,
+    column: Option<&str>,
+) -> Result<Vec<String>> {
+    let Some(table) = model.table(key) else {
+        return Ok(Vec::new());
+    };
+    let explicit: Vec<String> = rows
+        .iter()
+        .filter(|r| r.kind == KIND_ENTITY && r.table_name == key)
+        .filter_map(|r| r.context.clone())
+        .collect();
+    let mut names = explicit.clone();
+    names.extend(type_names_for_table(key));
+    let types = ctx.sqlite.get_type_fields_by_names(&names).await?;
+
+    let wanted: Vec<&ColumnModel> = table
+        .columns
+        .iter()
+        .filter(|c| c.dropped.is_none())
+        .filter(|c| column.is_none_or(|name| c.name.eq_ignore_ascii_case(name)))
+        .collect();
+    let mut fields = Vec::new();
+    for t in types {
+        let mapped = explicit.iter().any(|e| e == &t.type_name)
+            || table_names_for_type(&t.type_name).iter().any(|n| n == key);
+        if !mapped {
+            continue;
+        }
+        let names: Vec<String> = serde_json::from_str(&t.fields_json).unwrap_or_default();
+        for field in names {
+            let normalized = normalize_field(&field);
+            if wanted
+                .iter()
+                .any(|c| normalize_field(&c.name) == normalized)
+            {
+                fields.push(format!(
+                    "{}:{} {}.{}",
+                    t.file_path, t.line, t.type_name, field
+                ));
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// Type names an ORM model of `table` is likely to have: `order_items` →
+/// `OrderItem`, `OrderItems`, `OrderItemRow`, ...
+fn type_names_for_table(table: &str) -> Vec<String> {
+    let camel: String = table
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    let singular = if let Some(stem) = camel.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if let Some(stem) = camel
+        .strip_suffix("ses")
+        .or_else(|| camel.strip_suffix("xes"))
+    {
+        format!("{}{}", stem, &camel[stem.len()..stem.len() + 1])
+    } else {
+        camel.strip_suffix('s').unwrap_or(&camel).to_string()
+    };
+    let mut names = vec![camel.clone(), singular.clone()];
+    for suffix in ["Row", "Record", "Model", "Entity"] {
+        names.push(format!("{}{}", singular, suffix));
+    }
+    names.dedup();
+    names
+}
+
+/// Where a (table, column) pair stands in the schema model.
+enum ColumnStatus<'a> {
+    Live,
+    Dropped(&'a ColumnModel),
+    TableGone(&'a TableModel),
+    /// The table exists but never had the column
+    Unknown,
+    /// No migration created the table
+    NoTable,
+}
+
+impl<'a> ColumnStatus<'a> {
+    fn of(model: &'a SchemaModel, table: &str, column: &str) -> Self {
+        if let Some(t) = model.table(table) {
+            if column.is_empty() || t.column(column).is_some() {
+                return Self::Live;
+            }
+            return t
+                .columns
+                .iter()
+                .rev()
+                .find(|c| c.name.eq_ignore_ascii_case(column))
+                .map(Self::Dropped)
+                .unwrap_or(Self::Unknown);
+        }
+        let key = table_key(table);
+        model
+            .tables
+            .iter()
+            .rev()
+            .find(|t| table_key(&t.name) == key)
+            .map(Self::TableGone)
+            .unwrap_or(Self::NoTable)
+    }
+
+    fn known(&self) -> bool {
+        !matches!(self, Self::Unknown | Self::NoTable)
+    }
+
+    fn is_stale(&self) -> bool {
+        matches!(self, Self::Dropped(_) | Self::TableGone(_))
+    }
+
+    fn note(&self) -> Option<String> {
+        match self {
+            Self::Dropped(c) => c
+                .dropped
+                .as_ref()
+                .map(|at| gone("column", c.renamed_to.as_deref(), at)),
+            Self::TableGone(t) => t
+                .dropped
+                .as_ref()
+                .map(|at| gone("table", t.renamed_to.as_deref(), at)),
+            _ => None,
+        }
+    }
+}
+
+fn gone(what: &str, renamed_to: Option<&str>, at: &Location) -> String {
+    match renamed_to {
+        Some(to) => format!("{} renamed to {} in {}", what, to, format_location(at)),
+        None => format!("{} dropped in {}", what, format_location(at)),
+    }
+}
+
+fn dropped_objects(tables: &[&TableModel]) -> Vec<String> {
+    let mut out = Vec::new();
+    for t in tables {
+        if let Some(at) = &t.dropped {
+            out.push(format!(
+                "{}: {}",
+                t.name,
+                gone("table", t.renamed_to.as_deref(), at)
+            ));
+            continue;
+        }
+        for c in &t.columns {
+            if let Some(at) = &c.dropped {
+                out.push(format!(
+                    "{}.{}: {}",
+                    t.name,
+                    c.name,
+                    gone("column", c.renamed_to.as_deref(), at)
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn format_location(at: &Location) -> String {
+    format!("{}:{}", at.file, at.line)
+}
+
+fn format_column(c: &ColumnModel, with_location: bool) -> String {
+    let mut out = c.name.clone();
+    if !c.data_type.is_empty() {
+        out.push(' ');
+        out.push_str(&c.data_type);
+    }
+    if c.primary_key {
+        out.push_str(" PK");
+    } else if c.not_null {
+        out.push_str(" NOT NULL");
+    }
+    if with_location {
+        out.push_str(&format!(" ({})", format_location(&c.defined)));
+    }
+    out
+}
+
+fn format_usage(row: &ColumnUsageRow, note: Option<&str>) -> String {
+    let target = if row.column_name.is_empty() {
+        row.table_name.clone()
+    } else {
+        format!("{}.{}", row.table_name, row.column_name)
+    };
+    let mut out = format!("{}:{} {} {}", row.file_path, row.line, row.kind, target);
+    if let Some(note) = note {
+        out.push_str(&format!(" [STALE: {}]", note));
+    }
+    if let Some(context) = row.context.as_deref().filter(|c| !c.is_empty()) {
+        out.push_str(" — ");
+        out.push_str(context);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_names_for_table() {
+        assert_eq!(
+            type_names_for_table("order_items"),
+            vec![
+                "OrderItems",
+                "OrderItem",
+                "OrderItemRow",
+                "OrderItemRecord",
+                "OrderItemModel",
+                "OrderItemEntity"
+            ]
+        );
+        assert_eq!(type_names_for_table("categories")[1], "Category");
+        assert_eq!(type_names_for_table("addresses")[1], "Address");
+    }
+}
@@ -1,4 +1,4 @@
-use super::common::{make_relative, resolve_path, ToolContext};
+use super::common::{index_path, make_relative, ToolContext};
 use crate::error::GoferError;
 use anyhow::Result;
 use serde_json::{json, Value};
@@ -13,7 +13,7 @@ pub async fn tool_get_errors(args: Value, ctx: &ToolContext) -> Result<Value> {
         .unwrap_or(200)
         .min(500) as u32;
 
-    let resolved_file = file.map(|f| resolve_path(&ctx.root_path, f));
+    let resolved_file = file.map(|f| index_path(&ctx.root_path, f));
     let errors = &ctx
         .sqlite
         .get_errors(resolved_file.as_deref(), severity, offset, limit)
@@ -58,12 +58,19 @@ pub async fn tool_get_errors(args: Value, ctx: &ToolContext) -> Result<Value> {
 pub async fn tool_run_diagnostics(ctx: &ToolContext) -> Result<Value> {
     use crate::indexer::diagnostics;
 
-    let result = diagnostics::run_diagnostics(&ctx.root_path, &ctx.sqlite).await?;
+    let result = ctx
+        .progress
+        .while_running(
+            "Running cargo check / tsc",
+            diagnostics::run_diagnostics(&ctx.root_path, &ctx.sqlite, &ctx.cancel),
+        )
+        .await?;
 
     Ok(json!({
         "cargo": { "errors": result.cargo_errors, "warnings": result.cargo_warnings },
         "tsc": { "errors": result.tsc_errors, "warnings": result.tsc_warnings },
-        "total": { "errors": result.total_errors, "warnings": result.total_warnings }
+        "total": { "errors": result.total_errors, "warnings": result.total_warnings },
+        "cancelled": result.cancelled
     }))
 }
 
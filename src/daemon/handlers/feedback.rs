@@ -0,0 +1,269 @@
+//! Implicit relevance feedback from the audit log.
+//!
+//! Search-type calls log the paths they returned (`audit_log.result_paths`). When a
+//! `read_file` or `skeleton` of one of those paths follows within the configured window,
+//! the (query, file) pair gets a click in `search_feedback`. Scores decay with a
+//! half-life, and `smart_file_selection` turns them into a bounded multiplicative boost.
+//! `[feedback] enabled = false` in config.toml turns both halves off.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::common::index_path;
+use crate::indexer::tokenize::split_identifier;
+use crate::indexer::watcher::FeedbackConfig;
+use crate::storage::SqliteStorage;
+
+/// Tools whose results can be clicked
+pub const SEARCH_TOOLS: &[&str] = &["search", "search_by_purpose", "smart_file_selection"];
+/// Tools whose call on a returned path counts as a click
+pub const FOLLOW_UP_TOOLS: &[&str] = &["read_file", "skeleton"];
+
+/// Largest boost feedback can give (score × 1.25)
+const MAX_BOOST: f32 = 0.25;
+/// Clicks from other queries count this much towards a file's boost
+const OTHER_QUERY_WEIGHT: f64 = 0.2;
+
+/// Order-independent key for a query: lowercase identifier parts, sorted and deduplicated.
+pub fn normalize_query(query: &str) -> String {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|w| !w.is_empty())
+        .flat_map(split_identifier)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms.join(" ")
+}
+
+/// Files returned by a search-type tool, as a JSON array for `audit_log.result_paths`.
+pub fn result_paths(tool: &str, result: &Value) -> Option<String> {
+    if !SEARCH_TOOLS.contains(&tool) {
+        return None;
+    }
+    serde_json::to_string(&listed_paths(result)?).ok()
+}
+
+/// Unique file paths of a search result's `results` (or `files`) entries, in order.
+pub fn listed_paths(result: &Value) -> Option<Vec<String>> {
+    let entries = result
+        .get("results")
+        .or_else(|| result.get("files"))
+        .and_then(|v| v.as_array())?;
+
+    let mut paths: Vec<String> = Vec::new();
+    for entry in entries.iter().filter_map(|e| e.as_str()) {
+        // "path:line ..." (search) or "path [score=..] ..." (file rankings)
+        let first = entry.split_whitespace().next().unwrap_or("");
+        let path = match first.rsplit_once(':') {
+            Some((p, loc)) if loc.chars().all(|c| c.is_ascii_digit() || c == '-') => p,
+            _ => first,
+        };
+        if !path.is_empty() && !paths.iter().any(|p| p == path) {
+            paths.push(path.to_string());
+        }
+    }
+    Some(paths)
+}
+
+/// Credit the most recent search that returned the file a follow-up tool just opened.
+pub async fn record_follow_up(
+    sqlite: &SqliteStorage,
+    root: &Path,
+    config: &FeedbackConfig,
+    tool: &str,
+    args: &Value,
+) {
+    if !config.enabled || !FOLLOW_UP_TOOLS.contains(&tool) {
+        return;
+    }
+    let Some(file) = args.get("file").and_then(|v| v.as_str()) else {
+        return;
+    };
+
+    let path = index_path(root, file);
+    let now = chrono::Utc::now().timestamp();
+    let since = now - config.window_secs as i64;
+
+    let search = match sqlite.find_search_returning(&path, since).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::debug!("Feedback lookup failed for {}: {}", path, e);
+            return;
+        }
+    };
+    let (search_id, args_json) = search;
+    let query = args_json
+        .and_then(|a| serde_json::from_str::<Value>(&a).ok())
+        .and_then(|a| a.get("query").and_then(|q| q.as_str()).map(normalize_query))
+        .unwrap_or_default();
+    if query.is_empty() {
+        return;
+    }
+
+    let half_life = config.half_life_days * 86_400.0;
+    if let Err(e) = sqlite
+        .record_search_click(&query, &path, search_id, half_life, now)
+        .await
+    {
+        tracing::debug!("Failed to record click on {}: {}", path, e);
+    }
+}
+
+/// Multiplicative boost (1.0 ..= 1 + MAX_BOOST) per candidate file for `query`.
+///
+/// Clicks for the same normalized query count fully, clicks from other queries at
+/// [`OTHER_QUERY_WEIGHT`]; the sum saturates so no file can run away with the ranking.
+pub async fn learned_boosts(
+    sqlite: &SqliteStorage,
+    config: &FeedbackConfig,
+    query: &str,
+    files: &[String],
+) -> HashMap<String, f32> {
+    if !config.enabled {
+        return HashMap::new();
+    }
+
+    let rows = match sqlite.get_search_feedback(files).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::debug!("Failed to load search feedback: {}", e);
+            return HashMap::new();
+        }
+    };
+
+    let key = normalize_query(query);
+    let now = chrono::Utc::now().timestamp();
+    let half_life = config.half_life_days * 86_400.0;
+
+    let mut clicks: HashMap<String, f64> = HashMap::new();
+    for row in &rows {
+        let weight = if row.query == key {
+            1.0
+        } else {
+            OTHER_QUERY_WEIGHT
+        };
+        *clicks.entry(row.file_path.clone()).or_default() += weight * row.decayed(now, half_life);
+    }
+
+    clicks
+        .into_iter()
+        .map(|(file, c)| (file, 1.0 + MAX_BOOST * (1.0 - (-c).exp()) as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn storage() -> (SqliteStorage, tempfile::TempDir) {
+        let temp = tempfile::tempdir().unwrap();
+        let sqlite = SqliteStorage::new(temp.path().join("graph.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.migrate().await.unwrap();
+        (sqlite, temp)
+    }
+
+    fn scores(rows: &[crate::storage::SearchFeedbackRow]) -> Vec<(&str, &str, f64)> {
+        let mut scores: Vec<_> = rows
+            .iter()
+            .map(|r| (r.query.as_str(), r.file_path.as_str(), r.score))
+            .collect();
+        scores.sort_by(|a, b| a.1.cmp(b.1));
+        scores
+    }
+
+    #[tokio::test]
+    async fn test_follow_up_read_records_click() {
+        let (sqlite, _temp) = storage().await;
+        let root = Path::new("/work/app");
+        let config = FeedbackConfig::default();
+        sqlite
+            .log_tool_call(
+                "search",
+                Some(r#"{"query":"loadConfig"}"#),
+                5,
+                true,
+                None,
+                Some(r#"["src/config.rs","src/main.rs"]"#),
+            )
+            .await
+            .unwrap();
+
+        // Absolute and relative paths name the same indexed file
+        let read = |file: &str| json!({ "file": file });
+        record_follow_up(&sqlite, root, &config, "read_file", &read("src/config.rs")).await;
+        record_follow_up(
+            &sqlite,
+            root,
+            &config,
+            "skeleton",
+            &read("/work/app/src/main.rs"),
+        )
+        .await;
+        // Not a follow-up tool, not a returned path, or feedback turned off
+        record_follow_up(&sqlite, root, &config, "write_file", &read("src/config.rs")).await;
+        record_follow_up(&sqlite, root, &config, "read_file", &read("src/other.rs")).await;
+        let disabled = FeedbackConfig {
+            enabled: false,
+            ..FeedbackConfig::default()
+        };
+        record_follow_up(&sqlite, root, &disabled, "read_file", &read("src/main.rs")).await;
+
+        let files = ["src/config.rs", "src/main.rs", "src/other.rs"].map(String::from);
+        let rows = sqlite.get_search_feedback(&files).await.unwrap();
+        assert_eq!(
+            scores(&rows),
+            vec![
+                ("config load", "src/config.rs", 1.0),
+                ("config load", "src/main.rs", 1.0)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_learned_boosts_decay() {
+        let (sqlite, _temp) = storage().await;
+        let config = FeedbackConfig::default();
+        let half_life = config.half_life_days * 86_400.0;
+        let now = chrono::Utc::now().timestamp();
+        let month_ago = now - 30 * 86_400;
+        for (i, (query, file, at)) in [
+            ("config load", "src/fresh.rs", now),
+            ("config load", "src/stale.rs", month_ago),
+            ("db pool", "src/other_query.rs", now),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            sqlite
+                .record_search_click(query, file, i as i64, half_life, at)
+                .await
+                .unwrap();
+        }
+
+        let files = ["src/fresh.rs", "src/stale.rs", "src/other_query.rs"].map(String::from);
+        let boosts = learned_boosts(&sqlite, &config, "load config", &files).await;
+        let (fresh, stale, other) = (
+            boosts["src/fresh.rs"],
+            boosts["src/stale.rs"],
+            boosts["src/other_query.rs"],
+        );
+        assert!(fresh > stale && stale > 1.0);
+        assert!(fresh > other && other > 1.0);
+        assert!(fresh <= 1.0 + MAX_BOOST);
+
+        let disabled = FeedbackConfig {
+            enabled: false,
+            ..FeedbackConfig::default()
+        };
+        assert!(learned_boosts(&sqlite, &disabled, "load config", &files)
+            .await
+            .is_empty());
+    }
+}
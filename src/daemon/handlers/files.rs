@@ -1,7 +1,7 @@
-use super::common::{make_relative, resolve_path, ToolContext};
+use super::common::{index_path, make_relative, resolve_path, ToolContext};
 use crate::error::GoferError;
+use crate::indexer::budget::{self, Detail};
 use crate::indexer::parser::core::SupportedLanguage;
-use crate::storage::SqliteStorage;
 use anyhow::Result;
 use serde_json::{json, Value};
 use std::collections::HashSet;
@@ -12,6 +12,7 @@ pub async fn tool_read_file(args: Value, ctx: &ToolContext) -> Result<Value> {
     let file = args.get("file").and_then(|v| v.as_str()).unwrap_or("");
     let start_line = args.get("start_line").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
     let end_line = args.get("end_line").and_then(|v| v.as_u64());
+    let max_tokens = max_tokens_arg(&args);
 
     if file.is_empty() {
         return Err(GoferError::InvalidParams("File path is required".into()).into());
@@ -46,13 +47,14 @@ pub async fn tool_read_file(args: Value, ctx: &ToolContext) -> Result<Value> {
 
             if start < end {
                 let content = lines[start..end].join("\n");
-                return Ok(json!({
-                    "file": file,
-                    "content": content,
-                    "start_line": start + 1,
-                    "end_line": end,
-                    "total_lines": total_lines
-                }));
+                return Ok(read_file_result(
+                    file,
+                    content,
+                    start,
+                    end,
+                    total_lines,
+                    max_tokens,
+                ));
             }
         }
     }
@@ -80,13 +82,57 @@ pub async fn tool_read_file(args: Value, ctx: &ToolContext) -> Result<Value> {
         String::new()
     };
 
-    Ok(json!({
+    Ok(read_file_result(
+        file,
+        result_content,
+        start,
+        end,
+        total_lines,
+        max_tokens,
+    ))
+}
+
+/// `max_tokens` argument shared by the read tools
+pub fn max_tokens_arg(args: &Value) -> Option<usize> {
+    args.get("max_tokens")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+}
+
+/// Response of `read_file`; with a budget the range degrades towards signatures.
+fn read_file_result(
+    file: &str,
+    content: String,
+    start: usize,
+    end: usize,
+    total_lines: usize,
+    max_tokens: Option<usize>,
+) -> Value {
+    let Some(max_tokens) = max_tokens else {
+        return json!({
+            "file": file,
+            "content": content,
+            "start_line": start + 1,
+            "end_line": end,
+            "total_lines": total_lines
+        });
+    };
+
+    let ext = std::path::Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    let fitted = budget::fit(&content, ext, max_tokens, Detail::Full, start + 1);
+    json!({
         "file": file,
-        "content": result_content,
+        "content": fitted.content,
         "start_line": start + 1,
         "end_line": end,
-        "total_lines": total_lines
-    }))
+        "total_lines": total_lines,
+        "detail": fitted.detail,
+        "tokens": fitted.tokens,
+        "truncated": fitted.truncated
+    })
 }
 
 pub async fn tool_file_exists(args: Value, ctx: &ToolContext) -> Result<Value> {
@@ -121,6 +167,7 @@ pub async fn tool_skeleton(args: Value, ctx: &ToolContext) -> Result<Value> {
         .get("include_tests")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let max_tokens = max_tokens_arg(&args);
 
     let file_path = &ctx.root_path.join(file);
     if !file_path.exists() {
@@ -154,6 +201,18 @@ pub async fn tool_skeleton(args: Value, ctx: &ToolContext) -> Result<Value> {
         skeleton = filter_test_items(&skeleton, language);
     }
 
+    // Over budget: fall back to signatures, then names
+    let mut detail = Detail::Skeleton;
+    let mut truncated = false;
+    if let Some(max_tokens) = max_tokens {
+        if budget::count_tokens(&skeleton) > max_tokens {
+            let fitted = budget::fit(&original_content, ext, max_tokens, Detail::Signatures, 1);
+            skeleton = fitted.content;
+            detail = fitted.detail;
+            truncated = fitted.truncated;
+        }
+    }
+
     let skeleton_lines = skeleton.lines().count();
     let skeleton_chars = skeleton.len();
     let reduction_percent = if original_chars > 0 {
@@ -169,13 +228,16 @@ pub async fn tool_skeleton(args: Value, ctx: &ToolContext) -> Result<Value> {
         "file_path": file,
         "language": language,
         "skeleton_content": skeleton,
+        "detail": detail,
+        "truncated": truncated,
         "stats": {
             "original_lines": original_lines,
             "original_chars": original_chars,
             "skeleton_lines": skeleton_lines,
             "skeleton_chars": skeleton_chars,
             "reduction_percent": format!("{:.1}", reduction_percent),
-            "items_kept": items
+            "items_kept": items,
+            "tokens": budget::count_tokens(&skeleton)
         }
     }))
 }
@@ -195,6 +257,7 @@ pub async fn tool_read_function_context(args: Value, ctx: &ToolContext) -> Resul
         .get("include_callees")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let max_tokens = max_tokens_arg(&args);
 
     if file.is_empty() || function.is_empty() {
         return Err(GoferError::InvalidParams("File and function name are required".into()).into());
@@ -311,7 +374,7 @@ pub async fn tool_read_function_context(args: Value, ctx: &ToolContext) -> Resul
     // Now call async functions
     let mut types = Vec::new();
     if include_types {
-        types = resolve_types(type_names, &ctx.sqlite, file).await?;
+        types = resolve_types(type_names, ctx, &index_path(&ctx.root_path, file)).await?;
     }
 
     let mut imports: Vec<String> = Vec::new();
@@ -324,15 +387,51 @@ pub async fn tool_read_function_context(args: Value, ctx: &ToolContext) -> Resul
         callees = resolve_callees(callee_names, &content, &lang).await?;
     }
 
+    let Some(max_tokens) = max_tokens else {
+        return Ok(json!({
+            "file": file,
+            "function": function,
+            "code": function_code,
+            "start_line": start_line,
+            "end_line": end_line,
+            "referenced_types": types,
+            "imports": imports,
+            "callees": callees
+        }));
+    };
+
+    // The function itself comes first; types and callees fill what is left
+    let fitted = budget::fit(&function_code, ext, max_tokens, Detail::Full, start_line);
+    let mut remaining = max_tokens.saturating_sub(fitted.tokens);
+    let mut omitted = 0;
+    let mut take = |items: Vec<String>| -> Vec<String> {
+        let mut kept = Vec::new();
+        for item in items {
+            let cost = budget::count_tokens(&item);
+            if cost <= remaining {
+                remaining -= cost;
+                kept.push(item);
+            } else {
+                omitted += 1;
+            }
+        }
+        kept
+    };
+    let types = take(types);
+    let callees = take(callees);
+
     Ok(json!({
         "file": file,
         "function": function,
-        "code": function_code,
+        "code": fitted.content,
         "start_line": start_line,
         "end_line": end_line,
         "referenced_types": types,
         "imports": imports,
-        "callees": callees
+        "callees": callees,
+        "detail": fitted.detail,
+        "tokens": max_tokens - remaining,
+        "omitted": omitted
     }))
 }
 
@@ -343,6 +442,7 @@ pub async fn tool_read_types_only(args: Value, ctx: &ToolContext) -> Result<Valu
         .get("include_docs")
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
+    let max_tokens = max_tokens_arg(&args);
 
     if file.is_empty() {
         return Err(GoferError::InvalidParams("File path is required".into()).into());
@@ -382,9 +482,22 @@ pub async fn tool_read_types_only(args: Value, ctx: &ToolContext) -> Result<Valu
         })
         .collect();
 
+    let types_content = filtered_lines.join("\n");
+    let Some(max_tokens) = max_tokens else {
+        return Ok(json!({
+            "file": file,
+            "types_content": types_content
+        }));
+    };
+
+    // Types are already a skeleton; degrade to one line per type, then names
+    let fitted = budget::fit(&types_content, ext, max_tokens, Detail::Skeleton, 1);
     Ok(json!({
         "file": file,
-        "types_content": filtered_lines.join("\n")
+        "types_content": fitted.content,
+        "detail": fitted.detail,
+        "tokens": fitted.tokens,
+        "truncated": fitted.truncated
     }))
 }
 
@@ -399,6 +512,7 @@ pub async fn tool_context_bundle(args: Value, ctx: &ToolContext) -> Result<Value
         .get("skeleton_deps_only")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let max_tokens = max_tokens_arg(&args);
 
     if file.is_empty() {
         return Err(GoferError::InvalidParams("File path is required".into()).into());
@@ -409,17 +523,46 @@ pub async fn tool_context_bundle(args: Value, ctx: &ToolContext) -> Result<Value
         return Err(GoferError::InvalidParams(format!("File not found: {}", file)).into());
     }
 
-    let bundle = tokio::task::spawn_blocking({
-        let file_path = file_path.clone();
-        move || {
-            let mut bundle = crate::indexer::context::create_bundle(&file_path, depth);
-            if skeleton {
-                crate::indexer::context::skeletonize_bundle(&mut bundle);
-            } else if skeleton_deps_only {
-                crate::indexer::context::skeletonize_deps_only(&mut bundle);
+    // Cached until the file or anything it imports is reindexed; the file's
+    // mtime covers edits the watcher has not picked up yet
+    let cache_key = index_path(&ctx.root_path, file);
+    let mtime = std::fs::metadata(file_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let cache_options = format!(
+        "{}:{}:{}:{}:{:?}",
+        mtime, depth, skeleton, skeleton_deps_only, max_tokens
+    );
+    let cacheable = depth <= crate::indexer::context::MAX_CACHED_BUNDLE_DEPTH;
+    if cacheable {
+        if let Some(cached) = ctx.cache.get_bundle(&cache_key, &cache_options).await {
+            if let Ok(result) = serde_json::from_str(&cached) {
+                return Ok(result);
             }
-            bundle
         }
+    }
+
+    let mut bundle = tokio::task::spawn_blocking({
+        let root = ctx.root_path.clone();
+        let file_path = file_path.clone();
+        move || crate::indexer::context::create_bundle(&root, &file_path, depth)
+    })
+    .await?;
+    // Re-exported items are found through the symbol index
+    crate::indexer::context::resolve_from_index(&mut bundle, &ctx.sqlite, &ctx.root_path).await;
+    crate::indexer::context::order_by_relevance(&mut bundle, &ctx.sqlite, &ctx.root_path).await;
+
+    let (bundle, details) = tokio::task::spawn_blocking(move || {
+        if skeleton {
+            crate::indexer::context::skeletonize_bundle(&mut bundle);
+        } else if skeleton_deps_only {
+            crate::indexer::context::skeletonize_deps_only(&mut bundle);
+        }
+        let details = max_tokens.map(|max| crate::indexer::context::fit_bundle(&mut bundle, max));
+        (bundle, details)
     })
     .await?;
 
@@ -431,18 +574,43 @@ pub async fn tool_context_bundle(args: Value, ctx: &ToolContext) -> Result<Value
         "full"
     };
 
-    Ok(json!({
-        "file": file,
-        "mode": mode,
-        "total_lines": bundle.total_lines,
-        "total_tokens_estimate": bundle.total_tokens_estimate,
-        "main_content": bundle.main_content,
-        "dependencies": bundle.dependencies.iter().map(|dep| json!({
-            "path": dep.path,
-            "depth": dep.depth,
-            "content": dep.content
-        })).collect::<Vec<_>>()
-    }))
+    let result = match details {
+        None => json!({
+            "file": file,
+            "mode": mode,
+            "total_lines": bundle.total_lines,
+            "total_tokens_estimate": bundle.total_tokens_estimate,
+            "main_content": bundle.main_content,
+            "dependencies": bundle.dependencies.iter().map(|dep| json!({
+                "path": dep.path,
+                "depth": dep.depth,
+                "reason": dep.reason,
+                "content": dep.content
+            })).collect::<Vec<_>>()
+        }),
+        Some((main_detail, dep_details)) => json!({
+            "file": file,
+            "mode": mode,
+            "total_lines": bundle.total_lines,
+            "total_tokens_estimate": bundle.total_tokens_estimate,
+            "main_detail": main_detail,
+            "main_content": bundle.main_content,
+            "dependencies": bundle.dependencies.iter().zip(dep_details).map(|(dep, detail)| json!({
+                "path": dep.path,
+                "depth": dep.depth,
+                "reason": dep.reason,
+                "detail": detail,
+                "content": dep.content
+            })).collect::<Vec<_>>()
+        }),
+    };
+
+    if cacheable {
+        ctx.cache
+            .put_bundle(&cache_key, &cache_options, result.to_string())
+            .await;
+    }
+    Ok(result)
 }
 
 pub async fn tool_find_files(args: Value, ctx: &ToolContext) -> Result<Value> {
@@ -655,9 +823,10 @@ fn collect_type_names(
 
 async fn resolve_types(
     type_names: HashSet<String>,
-    sqlite: &SqliteStorage,
+    ctx: &ToolContext,
     file_path: &str,
 ) -> Result<Vec<String>> {
+    let sqlite = &ctx.sqlite;
     if type_names.is_empty() {
         return Ok(Vec::new());
     }
@@ -673,7 +842,7 @@ async fn resolve_types(
                         || symbol.kind == crate::models::chunk::SymbolKind::Interface
                         || symbol.kind == crate::models::chunk::SymbolKind::TypeAlias)
                 {
-                    let type_file_path = std::path::PathBuf::from(&file_info.path);
+                    let type_file_path = ctx.root_path.join(&file_info.path);
                     if let Ok(file_content) = tokio::fs::read_to_string(&type_file_path).await {
                         let lines: Vec<&str> = file_content.lines().collect();
                         if symbol.line_start > 0 && symbol.line_end as usize <= lines.len() {
@@ -917,3 +1086,68 @@ fn find_function_in_file(
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::handlers::common::test_context;
+    use std::time::{Duration, SystemTime};
+
+    /// Write `content` with its own mtime, so consecutive edits never share one
+    fn write(path: &std::path::Path, content: &str, secs: u64) {
+        std::fs::write(path, content).unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    }
+
+    fn dependency_content(bundle: &Value) -> String {
+        bundle["dependencies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|d| d["content"].as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_context_bundle_cache_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.ts"), dir.path().join("b.ts"));
+        write(&a, "import { b } from './b';\nexport const a = b;\n", 0);
+        write(&b, "export const b = 1;\n", 0);
+        let (ctx, _index) = test_context(dir.path()).await;
+        let args = json!({ "file": "a.ts", "depth": 1 });
+
+        let first = tool_context_bundle(args.clone(), &ctx).await.unwrap();
+        assert!(first["main_content"]
+            .as_str()
+            .unwrap()
+            .contains("export const a = b"));
+        assert!(dependency_content(&first).contains("b = 1"));
+
+        // Served from the cache until something invalidates it
+        write(&b, "export const b = 2;\n", 1);
+        let cached = tool_context_bundle(args.clone(), &ctx).await.unwrap();
+        assert_eq!(cached, first);
+
+        // Editing the file itself is seen before the watcher reindexes it
+        write(&a, "import { b } from './b';\nexport const a = b + 1;\n", 2);
+        let edited = tool_context_bundle(args.clone(), &ctx).await.unwrap();
+        assert!(edited["main_content"]
+            .as_str()
+            .unwrap()
+            .contains("export const a = b + 1"));
+        assert!(dependency_content(&edited).contains("b = 2"));
+
+        // A full sync drops every bundle
+        write(&b, "export const b = 3;\n", 3);
+        ctx.cache.invalidate_all_bundles().await;
+        let synced = tool_context_bundle(args, &ctx).await.unwrap();
+        assert!(dependency_content(&synced).contains("b = 3"));
+    }
+}
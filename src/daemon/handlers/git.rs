@@ -1,6 +1,8 @@
 use super::common::ToolContext;
+use super::rules;
 use crate::error::GoferError;
 use crate::indexer::git::GitRepo;
+use crate::indexer::rules::Severity;
 use anyhow::Result;
 use serde_json::{json, Value};
 
@@ -120,10 +122,19 @@ pub async fn tool_verify_patch(args: Value, ctx: &ToolContext) -> Result<Value>
 
     let result = crate::indexer::diagnostics::verify_patch(&ctx.root_path, file, content).await?;
 
+    // Conventions from .gofer/rules.toml; a broken error-level rule fails the patch
+    let violations = rules::check_content(ctx, file, content).await?;
+    let status = if violations.iter().any(|v| v.severity == Severity::Error) {
+        "error"
+    } else {
+        result.status.as_str()
+    };
+
     Ok(json!({
         "file": file,
-        "status": result.status,
+        "status": status,
         "summary": result.summary,
+        "rule_violations": violations.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
         "diagnostics": result.diagnostics.iter().map(|d| {
             let col = d.column.map(|c| format!(":{}", c)).unwrap_or_default();
             let code = d.code.as_deref().map(|c| format!("{}: ", c)).unwrap_or_default();
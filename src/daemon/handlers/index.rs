@@ -1,7 +1,13 @@
-use super::common::ToolContext;
+use std::sync::Arc;
+
+use super::common::{index_path, ToolContext};
+use crate::daemon::state::SyncProgress;
 use crate::error::GoferError;
+use crate::indexer::service::IndexerService;
+use crate::indexer::watcher::load_config;
 use anyhow::Result;
 use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
 
 pub async fn tool_get_index_status(ctx: &ToolContext) -> Result<Value> {
     use std::time::Instant;
@@ -560,6 +566,7 @@ pub async fn tool_validate_index(ctx: &ToolContext) -> Result<Value> {
 
 pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value> {
     let scope = args.get("scope").and_then(|v| v.as_str()).unwrap_or("file");
+    let wait = args.get("wait").and_then(|v| v.as_bool()).unwrap_or(false);
 
     match scope {
         "file" => {
@@ -569,6 +576,7 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
                 .ok_or_else(|| anyhow::anyhow!("path required for file scope"))?;
 
             // Mark file as pending for reindexing
+            let key = index_path(&ctx.root_path, path);
             let result = sqlx::query(
                 r#"
                 UPDATE files
@@ -577,7 +585,7 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
                 WHERE path = ?
                 "#,
             )
-            .bind(path)
+            .bind(&key)
             .execute(ctx.sqlite.pool())
             .await?;
 
@@ -591,6 +599,10 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
                 }));
             }
 
+            if wait {
+                return reindex_paths(vec![key], ctx).await;
+            }
+
             Ok(json!({
                 "status": "queued",
                 "scope": "file",
@@ -606,7 +618,7 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow::anyhow!("path required for directory scope"))?;
 
-            let pattern = format!("{}%", path);
+            let pattern = format!("{}%", index_path(&ctx.root_path, path));
             let result = sqlx::query(
                 r#"
                 UPDATE files
@@ -621,6 +633,15 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
 
             let updated = result.rows_affected();
 
+            if wait {
+                let paths: Vec<String> =
+                    sqlx::query_scalar("SELECT path FROM files WHERE path LIKE ?")
+                        .bind(&pattern)
+                        .fetch_all(ctx.sqlite.pool())
+                        .await?;
+                return reindex_paths(paths, ctx).await;
+            }
+
             Ok(json!({
                 "status": "queued",
                 "scope": "directory",
@@ -659,6 +680,10 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
             .execute(ctx.sqlite.pool())
             .await?;
 
+            if wait {
+                return reindex_project(ctx).await;
+            }
+
             Ok(json!({
                 "status": "queued",
                 "scope": "project",
@@ -671,6 +696,104 @@ pub async fn tool_force_reindex(args: Value, ctx: &ToolContext) -> Result<Value>
     }
 }
 
+fn indexer_for(ctx: &ToolContext, workers: usize) -> IndexerService {
+    IndexerService::new(
+        ctx.root_path.as_ref().clone(),
+        ctx.sqlite.as_ref().clone(),
+        Arc::clone(&ctx.lance),
+        Arc::clone(&ctx.embedder),
+        workers,
+    )
+    .with_cache(Arc::clone(&ctx.cache))
+}
+
+fn completion_status(ctx: &ToolContext) -> &'static str {
+    if ctx.cancel.is_cancelled() {
+        "cancelled"
+    } else {
+        "completed"
+    }
+}
+
+/// Reindex files one by one, reporting each as progress. Cancellation stops
+/// between files.
+async fn reindex_paths(paths: Vec<String>, ctx: &ToolContext) -> Result<Value> {
+    let indexer = indexer_for(ctx, 1);
+    let total = paths.len();
+    let mut reindexed = 0;
+    let mut failed = Vec::new();
+
+    for (i, path) in paths.iter().enumerate() {
+        if ctx.cancel.is_cancelled() {
+            break;
+        }
+        // Forget the stored hash so unchanged content is not skipped
+        sqlx::query("UPDATE files SET content_hash = '' WHERE path = ?")
+            .bind(path)
+            .execute(ctx.sqlite.pool())
+            .await?;
+        match indexer.index_file(&ctx.root_path.join(path)).await {
+            Ok(()) => reindexed += 1,
+            Err(e) => failed.push(json!({ "path": path, "error": e.to_string() })),
+        }
+        ctx.progress.report(i + 1, Some(total), path);
+    }
+
+    Ok(json!({
+        "status": completion_status(ctx),
+        "files_total": total,
+        "files_reindexed": reindexed,
+        "failed": failed
+    }))
+}
+
+/// Full pipeline sync over the whole project, relaying its [`SyncProgress`].
+async fn reindex_project(ctx: &ToolContext) -> Result<Value> {
+    // Forget stored hashes and mtimes so the pipeline skips nothing
+    sqlx::query("UPDATE files SET content_hash = '', last_modified = 0")
+        .execute(ctx.sqlite.pool())
+        .await?;
+    // ...and walk the whole tree rather than only files git reports as changed
+    sqlx::query("DELETE FROM index_metadata WHERE key = ?")
+        .bind(crate::indexer::reconcile::SYNCED_HEAD_KEY)
+        .execute(ctx.sqlite.pool())
+        .await?;
+
+    let config = load_config(&ctx.root_path.join(".gofer"));
+    let indexer = indexer_for(ctx, config.indexer.parallel_workers.unwrap_or(4));
+
+    let sync = Arc::new(SyncProgress::new());
+    let relay_done = CancellationToken::new();
+    let relay = {
+        let progress = ctx.progress.clone();
+        let sync = Arc::clone(&sync);
+        let done = relay_done.clone();
+        tokio::spawn(async move { progress.relay_sync(sync, done).await })
+    };
+
+    let result = indexer
+        .full_sync(
+            &ctx.root_path,
+            &config.indexer.ignore_patterns(),
+            Some(Arc::clone(&sync)),
+            None,
+            ctx.cancel.child_token(),
+        )
+        .await;
+    relay_done.cancel();
+    let _ = relay.await;
+    result?;
+
+    let snap = sync.snapshot();
+    Ok(json!({
+        "status": completion_status(ctx),
+        "scope": "project",
+        "files_total": snap.files_total,
+        "files_parsed": snap.files_parsed,
+        "files_written": snap.files_written
+    }))
+}
+
 pub async fn tool_get_cache_stats(ctx: &ToolContext) -> Result<Value> {
     let stats = ctx.cache.get_stats().await;
     Ok(serde_json::to_value(stats)?)
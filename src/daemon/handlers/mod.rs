@@ -1,14 +1,20 @@
+pub mod ask;
 pub mod batch;
 pub mod cas_buffer;
 pub mod code_quality;
 pub mod common;
+pub mod database;
 pub mod diagnostics;
+pub mod feedback;
 pub mod file_ops;
 pub mod files;
 pub mod git;
 pub mod index;
 pub mod lang_tools;
 pub mod project;
+pub mod query;
+pub mod rerank;
+pub mod rules;
 pub mod rust_analyzer;
 pub mod rust_analyzer_extended;
 pub mod sandbox;
@@ -16,3 +22,4 @@ pub mod search;
 pub mod symbols;
 pub mod transactions;
 pub mod trash;
+pub mod workspace;
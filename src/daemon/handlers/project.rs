@@ -1,6 +1,7 @@
-use super::common::{make_relative, resolve_path, ToolContext};
+use super::common::{frontend_api_calls, index_path, make_relative, ToolContext};
 use crate::error::GoferError;
-use crate::models::Rule;
+use crate::indexer::api_contracts::{contract_handler_names, contract_report, SOURCE_CODE};
+use crate::models::{Rule, SymbolWithPath};
 use anyhow::Result;
 use serde_json::{json, Value};
 use walkdir::{DirEntry, WalkDir};
@@ -102,6 +103,66 @@ pub async fn tool_dependency_impact(args: Value, ctx: &ToolContext) -> Result<Va
     }))
 }
 
+pub async fn tool_get_imports(args: Value, ctx: &ToolContext) -> Result<Value> {
+    import_graph(args, ctx, false).await
+}
+
+pub async fn tool_get_importers(args: Value, ctx: &ToolContext) -> Result<Value> {
+    import_graph(args, ctx, true).await
+}
+
+/// Files `file` imports (or that import it, when `reverse`) from the import
+/// graph, up to `depth` hops: `src/a.rs:3 -> src/b.rs (depth 1)`.
+async fn import_graph(args: Value, ctx: &ToolContext, reverse: bool) -> Result<Value> {
+    let file = args.get("file").and_then(|v| v.as_str()).unwrap_or("");
+    let depth = args
+        .get("depth")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1)
+        .clamp(1, 10) as u32;
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(200);
+
+    if file.is_empty() {
+        return Err(GoferError::InvalidParams("File path is required".into()).into());
+    }
+    let path = index_path(&ctx.root_path, file);
+    if ctx.sqlite.get_file(&path).await?.is_none() {
+        return Err(GoferError::InvalidParams(format!("File not indexed: {}", file)).into());
+    }
+
+    let edges = ctx.sqlite.get_import_closure(&path, depth, reverse).await?;
+    let mut files: Vec<&str> = edges
+        .iter()
+        .map(|(e, _)| {
+            if reverse {
+                e.from_path.as_str()
+            } else {
+                e.to_path.as_str()
+            }
+        })
+        .filter(|f| *f != path)
+        .collect();
+    files.sort();
+    files.dedup();
+
+    Ok(json!({
+        "file": path,
+        "total_files": files.len(),
+        "edges": edges.iter().take(limit).map(|(e, hop)| format!(
+            "{}:{} -> {} (depth {})",
+            e.from_path,
+            e.line + 1,
+            e.to_path,
+            hop
+        )).collect::<Vec<_>>(),
+        "truncated": edges.len() > limit,
+    }))
+}
+
 pub async fn tool_domain_stats(ctx: &ToolContext) -> Result<Value> {
     let stats = &ctx.sqlite.get_domain_stats().await?;
 
@@ -117,30 +178,109 @@ pub async fn tool_domain_stats(ctx: &ToolContext) -> Result<Value> {
 
 pub async fn tool_get_api_routes(args: Value, ctx: &ToolContext) -> Result<Value> {
     let side = args.get("side").and_then(|v| v.as_str());
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(200);
 
-    let mut backend_routes = Vec::new();
-    let mut frontend_calls = Vec::new();
+    let endpoints = ctx.sqlite.get_api_endpoints().await?;
+    let calls = frontend_api_calls(ctx).await?;
+    let mut result = json!({});
 
     if side.is_none() || side == Some("backend") {
-        let endpoints = &ctx.sqlite.get_api_endpoints().await?;
-        backend_routes = endpoints
+        // Handlers link to their definition: `GET /api/users src/main.rs:12 list -> src/users.rs:40`
+        let mut handlers: Vec<String> = endpoints
+            .iter()
+            .filter(|ep| ep.source == SOURCE_CODE)
+            .filter_map(|ep| ep.handler.clone())
+            .collect();
+        handlers.sort();
+        handlers.dedup();
+        let definitions = ctx.sqlite.get_functions_by_names(&handlers).await?;
+
+        let backend_routes: Vec<String> = endpoints
             .iter()
-            .map(|ep| format!("{} {}", ep.method, ep.path))
+            .filter(|ep| ep.source == SOURCE_CODE)
+            .map(|ep| {
+                let mut route = format!(
+                    "{} {} {}:{} {}",
+                    ep.method,
+                    ep.path,
+                    ep.file_path,
+                    ep.line.unwrap_or(0) + 1,
+                    ep.handler.as_deref().unwrap_or("")
+                )
+                .trim_end()
+                .to_string();
+                let definition = ep
+                    .handler
+                    .as_deref()
+                    .and_then(|h| handler_definition(&definitions, h, &ep.file_path));
+                if let Some(f) = definition {
+                    route.push_str(&format!(" -> {}:{}", f.file_path, f.line + 1));
+                }
+                route
+            })
             .collect();
+        result["backend"] = json!(truncate(backend_routes, limit));
     }
 
     if side.is_none() || side == Some("frontend") {
-        let calls = &ctx.sqlite.get_frontend_api_calls().await?;
-        frontend_calls = calls
+        let frontend_calls: Vec<String> = calls
             .iter()
-            .map(|call| format!("{} {}", call.method.as_deref().unwrap_or("GET"), call.path))
+            .map(|call| {
+                format!(
+                    "{} {} {}:{}",
+                    call.method.as_deref().unwrap_or("GET"),
+                    call.path,
+                    call.file_path,
+                    call.line.unwrap_or(0) + 1
+                )
+            })
             .collect();
+        result["frontend"] = json!(truncate(frontend_calls, limit));
     }
 
-    Ok(json!({
-        "backend": backend_routes,
-        "frontend": frontend_calls
-    }))
+    // Spec endpoints (OpenAPI, GraphQL, proto) with their implementation and drift
+    if side.is_none() || side == Some("contract") {
+        let names = contract_handler_names(&endpoints);
+        let functions = ctx.sqlite.get_functions_by_names(&names).await?;
+        let report = contract_report(&endpoints, &calls, &functions);
+        if !report.contracts.is_empty() {
+            result["contracts"] = json!(truncate(report.contracts, limit));
+            result["drift"] = json!({
+                "not_implemented": truncate(report.not_implemented, limit),
+                "not_in_spec": truncate(report.not_in_spec, limit),
+            });
+        } else if side == Some("contract") {
+            result["message"] = json!(
+                "No API contracts indexed. OpenAPI/Swagger (.yaml/.json), GraphQL schemas and .proto files are read when indexed."
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+/// Function named `handler`, preferring one in the file that registers it
+fn handler_definition<'a>(
+    functions: &'a [SymbolWithPath],
+    handler: &str,
+    file: &str,
+) -> Option<&'a SymbolWithPath> {
+    let mut named = functions.iter().filter(|f| f.name == handler);
+    let first = named.clone().next();
+    named.find(|f| f.file_path == file).or(first)
+}
+
+fn truncate(mut items: Vec<String>, limit: usize) -> Vec<String> {
+    if items.len() > limit {
+        let more = items.len() - limit;
+        items.truncate(limit);
+        items.push(format!("... {} more", more));
+    }
+    items
 }
 
 pub async fn tool_get_summary(args: Value, ctx: &ToolContext) -> Result<Value> {
@@ -152,7 +292,7 @@ pub async fn tool_get_summary(args: Value, ctx: &ToolContext) -> Result<Value> {
 
     match &ctx
         .sqlite
-        .get_summary_by_path(&resolve_path(&ctx.root_path, file))
+        .get_summary_by_path(&index_path(&ctx.root_path, file))
         .await?
     {
         Some(summary) => Ok(json!({
@@ -199,7 +339,7 @@ pub async fn tool_get_vue_tree(args: Value, ctx: &ToolContext) -> Result<Value>
 
     match &ctx
         .sqlite
-        .get_vue_tree(&resolve_path(&ctx.root_path, file))
+        .get_vue_tree(&index_path(&ctx.root_path, file))
         .await?
     {
         Some(tree) => Ok(json!({
@@ -235,6 +375,9 @@ pub async fn tool_add_rule(args: Value, ctx: &ToolContext) -> Result<Value> {
     };
 
     ctx.sqlite.upsert_rules(&[r], "mcp_tool").await?;
+    if category.eq_ignore_ascii_case("synonyms") {
+        ctx.cache.invalidate_synonym_groups().await;
+    }
 
     Ok(json!({
         "status": "success",
@@ -251,7 +394,7 @@ pub async fn tool_mark_golden_sample(args: Value, ctx: &ToolContext) -> Result<V
         return Err(GoferError::InvalidParams("File path is required".into()).into());
     }
 
-    let file_path = resolve_path(&ctx.root_path, file);
+    let file_path = index_path(&ctx.root_path, file);
 
     // Find file_id
     if let Some(file) = ctx.sqlite.get_file(&file_path).await? {
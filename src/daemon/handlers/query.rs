@@ -0,0 +1,335 @@
+//! Query understanding for `search`: identifier splitting, synonym expansion and
+//! exact-symbol detection.
+//!
+//! Synonyms come from three places, merged in this order:
+//! 1. `.gofer/synonyms.toml` — user-maintained groups of interchangeable terms;
+//! 2. `rules` entries with category `synonyms` (`auth: verify_token, session`);
+//! 3. symbols that co-occur in the index with the query term (see
+//!    [`SqliteStorage::co_occurring_symbols`](crate::storage::SqliteStorage::co_occurring_symbols)).
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use super::common::ToolContext;
+use crate::indexer::tokenize::split_identifier;
+
+/// Upper bound on expansion terms added to a single query
+const MAX_EXPANSIONS: usize = 8;
+/// Co-occurring symbols mined per query term
+const MINED_PER_TERM: i32 = 3;
+/// Terms shorter than this are too ambiguous to expand
+const MIN_EXPANDABLE_LEN: usize = 3;
+
+/// Result of analysing a search query.
+#[derive(Debug, Clone, Default)]
+pub struct QueryPlan {
+    /// Lowercased words of the query with identifiers split (`getUserById` → get, user, by, id)
+    pub terms: Vec<String>,
+    /// Synonyms and related identifiers, not including `terms`
+    pub expansions: Vec<String>,
+    /// Set when the whole query is a compound identifier that names an indexed symbol
+    pub exact_symbol: Option<String>,
+}
+
+impl QueryPlan {
+    /// Text for keyword (BM25) retrieval: the original query plus split terms and expansions.
+    pub fn keyword_query(&self, original: &str) -> String {
+        let mut parts = vec![original.to_string()];
+        parts.extend(self.terms.iter().cloned());
+        parts.extend(self.expansions.iter().cloned());
+        parts.join(" ")
+    }
+}
+
+/// `.gofer/synonyms.toml`:
+///
+/// ```toml
+/// groups = [
+///   ["auth", "authentication", "verify_token", "login"],
+///   ["db", "database", "repo"],
+/// ]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct SynonymsFile {
+    #[serde(default)]
+    groups: Vec<Vec<String>>,
+}
+
+/// Analyse `query` against the project index.
+pub async fn analyze_query(query: &str, ctx: &ToolContext) -> QueryPlan {
+    let words: Vec<&str> = query
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':' || c == '$'))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut terms: Vec<String> = Vec::new();
+    for word in &words {
+        for part in word.split("::").flat_map(split_identifier) {
+            if !terms.contains(&part) {
+                terms.push(part);
+            }
+        }
+    }
+
+    let exact_symbol = detect_exact_symbol(query, ctx).await;
+
+    let mut seen: HashSet<String> = terms.iter().cloned().collect();
+    seen.extend(words.iter().map(|w| w.to_lowercase()));
+    let mut expansions: Vec<String> = Vec::new();
+    let mut push = |term: &str, expansions: &mut Vec<String>| {
+        let key = term.to_lowercase();
+        if expansions.len() < MAX_EXPANSIONS && seen.insert(key) {
+            expansions.push(term.to_string());
+        }
+    };
+
+    // Whole words first so `verify_token` matches a group entry, then their parts
+    let mut lookup: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    lookup.extend(terms.iter().cloned());
+
+    let groups = synonym_groups(ctx).await;
+    for term in &lookup {
+        for group in groups.iter().filter(|g| g.contains(term)) {
+            for synonym in group {
+                push(synonym, &mut expansions);
+            }
+        }
+    }
+
+    if exact_symbol.is_none() {
+        for term in terms.iter().filter(|t| t.len() >= MIN_EXPANDABLE_LEN) {
+            for name in mined_synonyms(term, ctx).await {
+                push(&name, &mut expansions);
+            }
+        }
+    }
+
+    QueryPlan {
+        terms,
+        expansions,
+        exact_symbol,
+    }
+}
+
+/// Co-occurring symbols of `term`, cached per project until a sync clears them.
+async fn mined_synonyms(term: &str, ctx: &ToolContext) -> Vec<String> {
+    if let Some(cached) = ctx.cache.get_mined_synonyms(term).await {
+        return cached;
+    }
+    match ctx.sqlite.co_occurring_symbols(term, MINED_PER_TERM).await {
+        Ok(names) => {
+            ctx.cache
+                .put_mined_synonyms(term.to_string(), names.clone())
+                .await;
+            names
+        }
+        Err(e) => {
+            tracing::debug!("Synonym mining failed for {}: {}", term, e);
+            Vec::new()
+        }
+    }
+}
+
+/// A query that is one compound identifier (`getUserById`, `verify_token`, `auth::Claims`)
+/// and exists verbatim in the symbol table is a lookup, not a search.
+async fn detect_exact_symbol(query: &str, ctx: &ToolContext) -> Option<String> {
+    let candidate = query.trim();
+    if candidate.is_empty()
+        || candidate.contains(char::is_whitespace)
+        || !candidate
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '$')
+    {
+        return None;
+    }
+
+    let name = candidate.rsplit("::").next().unwrap_or(candidate);
+    let compound = candidate.contains("::") || split_identifier(name).len() > 1;
+    if !compound {
+        return None;
+    }
+
+    match ctx.sqlite.get_symbol_by_name(name).await {
+        Ok(symbols) if !symbols.is_empty() => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Synonym groups of the project, reloaded once `.gofer/synonyms.toml` changes
+/// (adding a `synonyms` rule drops them as well).
+async fn synonym_groups(ctx: &ToolContext) -> Arc<Vec<Vec<String>>> {
+    let path = ctx.root_path.join(".gofer").join("synonyms.toml");
+    let stamp = tokio::fs::metadata(&path)
+        .await
+        .and_then(|m| m.modified())
+        .ok();
+    if let Some(groups) = ctx.cache.get_synonym_groups(stamp).await {
+        return groups;
+    }
+    let groups = load_synonym_groups(&path, ctx).await;
+    ctx.cache.put_synonym_groups(stamp, groups).await
+}
+
+/// Lowercased synonym groups from the synonyms file at `path` and `synonyms` rules.
+async fn load_synonym_groups(path: &Path, ctx: &ToolContext) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+
+    if let Ok(content) = tokio::fs::read_to_string(path).await {
+        match toml::from_str::<SynonymsFile>(&content) {
+            Ok(file) => groups.extend(file.groups),
+            Err(e) => tracing::warn!("Invalid {}: {}", path.display(), e),
+        }
+    }
+
+    if let Ok(rules) = ctx.sqlite.get_rules().await {
+        groups.extend(
+            rules
+                .iter()
+                .filter(|r| r.category.eq_ignore_ascii_case("synonyms"))
+                .filter_map(|r| parse_synonym_rule(&r.rule)),
+        );
+    }
+
+    groups
+        .into_iter()
+        .map(|g| g.into_iter().map(|t| t.trim().to_lowercase()).collect())
+        .collect()
+}
+
+/// `auth: verify_token, session` or `auth = verify_token, session` → one group.
+fn parse_synonym_rule(rule: &str) -> Option<Vec<String>> {
+    let (head, tail) = rule.split_once(':').or_else(|| rule.split_once('='))?;
+    let mut group = vec![head.trim().to_string()];
+    group.extend(
+        tail.split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty()),
+    );
+    (group.len() > 1 && !group[0].is_empty()).then_some(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::handlers::common::test_context;
+    use crate::models::chunk::{Symbol, SymbolKind};
+
+    async fn index_symbols(ctx: &ToolContext, path: &str, names: &[&str]) {
+        let file_id = ctx.sqlite.upsert_file(path, 1, "h").await.unwrap();
+        let symbols: Vec<Symbol> = names
+            .iter()
+            .map(|name| Symbol {
+                id: 0,
+                file_id,
+                name: name.to_string(),
+                kind: SymbolKind::Function,
+                line_start: 1,
+                line_end: 2,
+                signature: None,
+            })
+            .collect();
+        ctx.sqlite.insert_symbols(file_id, &symbols).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exact_symbol_and_split_terms() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+        index_symbols(&ctx, "src/users.rs", &["getUserById"]).await;
+
+        let plan = analyze_query("getUserById", &ctx).await;
+        assert_eq!(plan.terms, vec!["get", "user", "by", "id"]);
+        assert_eq!(plan.exact_symbol.as_deref(), Some("getUserById"));
+        assert_eq!(
+            plan.keyword_query("getUserById"),
+            "getUserById get user by id"
+        );
+
+        // Compound but not indexed, or indexed but not compound: a search
+        assert_eq!(analyze_query("getOrderById", &ctx).await.exact_symbol, None);
+        assert_eq!(analyze_query("user lookup", &ctx).await.exact_symbol, None);
+    }
+
+    #[tokio::test]
+    async fn test_synonym_groups_expand_query() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".gofer")).unwrap();
+        std::fs::write(
+            dir.path().join(".gofer/synonyms.toml"),
+            "groups = [[\"auth\", \"Authentication\", \"verify_token\"], [\"db\", \"database\"]]\n",
+        )
+        .unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+
+        let plan = analyze_query("checkAuth", &ctx).await;
+        assert_eq!(plan.terms, vec!["check", "auth"]);
+        assert_eq!(plan.expansions, vec!["authentication", "verify_token"]);
+    }
+
+    #[tokio::test]
+    async fn test_synonym_groups_reload_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gofer/synonyms.toml");
+        std::fs::create_dir_all(dir.path().join(".gofer")).unwrap();
+        std::fs::write(&path, "groups = [[\"auth\", \"login\"]]\n").unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+        assert_eq!(analyze_query("auth", &ctx).await.expansions, vec!["login"]);
+
+        std::fs::write(&path, "groups = [[\"auth\", \"session\"]]\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(
+            analyze_query("auth", &ctx).await.expansions,
+            vec!["session"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mined_synonyms_are_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+        index_symbols(&ctx, "src/auth/session.rs", &["verify_token", "log_event"]).await;
+        index_symbols(&ctx, "src/db/pool.rs", &["log_event"]).await;
+
+        let plan = analyze_query("auth", &ctx).await;
+        assert_eq!(plan.expansions, vec!["verify_token", "log_event"]);
+
+        // Served from the cache until a sync clears it; search results don't share it
+        index_symbols(&ctx, "src/auth/login.rs", &["hash_password"]).await;
+        ctx.cache.invalidate_all_searches().await;
+        assert_eq!(
+            analyze_query("auth", &ctx).await.expansions,
+            plan.expansions
+        );
+        ctx.cache.invalidate_mined_synonyms().await;
+        assert_eq!(
+            analyze_query("auth", &ctx).await.expansions,
+            vec!["verify_token", "hash_password", "log_event"]
+        );
+    }
+
+    #[test]
+    fn test_parse_synonym_rule() {
+        assert_eq!(
+            parse_synonym_rule("auth: verify_token, session"),
+            Some(vec![
+                "auth".to_string(),
+                "verify_token".to_string(),
+                "session".to_string()
+            ])
+        );
+        assert_eq!(
+            parse_synonym_rule("db = database"),
+            Some(vec!["db".to_string(), "database".to_string()])
+        );
+        assert_eq!(parse_synonym_rule("auth:"), None);
+        assert_eq!(parse_synonym_rule("no separator"), None);
+    }
+}
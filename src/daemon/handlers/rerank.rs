@@ -0,0 +1,281 @@
+//! Result diversification for `search`: maximal marginal relevance over fused hits,
+//! a per-file cap and merging of adjacent chunks into one range per file.
+//!
+//! Fused retrieval tends to return several overlapping chunks of the same function
+//! (vector chunk, symbol row and BM25 chunk at slightly different lines) or the same
+//! code copied into generated modules. MMR trades a little relevance for coverage:
+//! each pick maximises `λ·relevance − (1−λ)·max_similarity_to_already_picked`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::search::FusedHit;
+
+/// Chunks separated by at most this many lines are merged by [`group_by_file`]
+const ADJACENT_GAP: u32 = 2;
+
+/// Greedy MMR selection of up to `limit` hits.
+///
+/// `lambda` = 1.0 is pure relevance order, lower values favour diversity.
+/// `max_per_file` = 0 disables the per-file cap.
+pub fn mmr_rerank(
+    hits: Vec<FusedHit>,
+    lambda: f64,
+    max_per_file: usize,
+    limit: usize,
+) -> Vec<FusedHit> {
+    let max_rrf = hits.iter().map(|h| h.rrf_score).fold(0.0, f64::max);
+    let tokens: Vec<HashSet<String>> = hits.iter().map(|h| content_tokens(&h.content)).collect();
+
+    let mut remaining: Vec<usize> = (0..hits.len()).collect();
+    let mut picked: Vec<usize> = Vec::new();
+    let mut per_file: HashMap<&str, usize> = HashMap::new();
+
+    while picked.len() < limit {
+        let mut best: Option<(usize, f64)> = None;
+        for (pos, &i) in remaining.iter().enumerate() {
+            let relevance = if max_rrf > 0.0 {
+                hits[i].rrf_score / max_rrf
+            } else {
+                0.0
+            };
+            let redundancy = picked
+                .iter()
+                .map(|&j| similarity(&hits[i], &tokens[i], &hits[j], &tokens[j]))
+                .fold(0.0, f64::max);
+            let mmr = lambda * relevance - (1.0 - lambda) * redundancy;
+            if best.is_none_or(|(_, s)| mmr > s) {
+                best = Some((pos, mmr));
+            }
+        }
+        let Some((pos, _)) = best else { break };
+        let i = remaining.remove(pos);
+
+        let count = per_file.entry(hits[i].file_path.as_str()).or_insert(0);
+        if max_per_file > 0 && *count >= max_per_file {
+            continue;
+        }
+        *count += 1;
+        picked.push(i);
+    }
+
+    let mut slots: Vec<Option<FusedHit>> = hits.into_iter().map(Some).collect();
+    picked.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+/// Hits of one file with overlapping/adjacent line ranges merged.
+pub struct FileGroup {
+    pub file_path: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    /// Best score among the merged hits
+    pub score: f32,
+    /// Symbol names of the merged hits, in rank order
+    pub symbols: Vec<String>,
+    /// Stitched content; `...` marks lines no hit covered
+    pub content: String,
+}
+
+/// Merge scored hits into per-file ranges. Groups keep the rank of their best hit.
+pub fn group_by_file(hits: Vec<(f32, FusedHit)>) -> Vec<FileGroup> {
+    let mut order: Vec<String> = Vec::new();
+    let mut by_file: HashMap<String, Vec<(f32, FusedHit)>> = HashMap::new();
+    for (score, hit) in hits {
+        if !by_file.contains_key(&hit.file_path) {
+            order.push(hit.file_path.clone());
+        }
+        by_file
+            .entry(hit.file_path.clone())
+            .or_default()
+            .push((score, hit));
+    }
+
+    let mut groups: Vec<FileGroup> = Vec::new();
+    for file in order {
+        let mut file_hits = by_file.remove(&file).unwrap_or_default();
+        file_hits.sort_by_key(|(_, h)| h.line_start);
+
+        let mut current: Vec<(f32, FusedHit)> = Vec::new();
+        for (score, hit) in file_hits {
+            let end = current.iter().map(|(_, h)| h.line_end).max().unwrap_or(0);
+            if !current.is_empty() && hit.line_start > end + ADJACENT_GAP {
+                groups.push(merge_range(std::mem::take(&mut current)));
+            }
+            current.push((score, hit));
+        }
+        if !current.is_empty() {
+            groups.push(merge_range(current));
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    groups
+}
+
+fn merge_range(hits: Vec<(f32, FusedHit)>) -> FileGroup {
+    let line_start = hits.iter().map(|(_, h)| h.line_start).min().unwrap_or(0);
+    let line_end = hits
+        .iter()
+        .map(|(_, h)| h.line_end)
+        .max()
+        .unwrap_or(line_start);
+    let score = hits.iter().map(|(s, _)| *s).fold(0.0, f32::max);
+
+    let mut ranked: Vec<&(f32, FusedHit)> = hits.iter().collect();
+    ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut symbols: Vec<String> = Vec::new();
+    for (_, hit) in ranked {
+        if let Some(name) = &hit.matched_symbol {
+            if !symbols.contains(name) {
+                symbols.push(name.clone());
+            }
+        }
+    }
+
+    // First writer wins per line: chunks agree on overlapping lines
+    let mut lines: BTreeMap<u32, &str> = BTreeMap::new();
+    for (_, hit) in &hits {
+        for (offset, line) in hit.content.lines().enumerate() {
+            lines.entry(hit.line_start + offset as u32).or_insert(line);
+        }
+    }
+    let mut content = String::new();
+    let mut prev: Option<u32> = None;
+    for (&n, line) in &lines {
+        if prev.is_some_and(|p| n > p + 1) {
+            content.push_str("...\n");
+        }
+        content.push_str(line);
+        content.push('\n');
+        prev = Some(n);
+    }
+
+    FileGroup {
+        file_path: hits[0].1.file_path.clone(),
+        line_start,
+        line_end,
+        score,
+        symbols,
+        content: content.trim_end().to_string(),
+    }
+}
+
+/// Similarity of two hits: token Jaccard, or line overlap for ranges in the same file.
+fn similarity(a: &FusedHit, ta: &HashSet<String>, b: &FusedHit, tb: &HashSet<String>) -> f64 {
+    let mut sim = jaccard(ta, tb);
+    if a.file_path == b.file_path {
+        let overlap_start = a.line_start.max(b.line_start);
+        let overlap_end = a.line_end.min(b.line_end);
+        if overlap_end >= overlap_start {
+            let overlap = (overlap_end - overlap_start + 1) as f64;
+            let shorter = (a.line_end - a.line_start + 1).min(b.line_end - b.line_start + 1) as f64;
+            sim = sim.max(overlap / shorter);
+        }
+    }
+    sim
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let inter = a.intersection(b).count() as f64;
+    inter / ((a.len() + b.len()) as f64 - inter)
+}
+
+fn content_tokens(content: &str) -> HashSet<String> {
+    content
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| w.len() > 1)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(file: &str, start: u32, end: u32, content: &str, rrf: f64) -> FusedHit {
+        FusedHit {
+            file_path: file.to_string(),
+            line_start: start,
+            line_end: end,
+            content: content.to_string(),
+            rrf_score: rrf,
+            vector_score: None,
+            matched_symbol: None,
+            symbol_kind: None,
+        }
+    }
+
+    #[test]
+    fn test_mmr_prefers_distinct_code_and_caps_files() {
+        let hits = vec![
+            hit(
+                "gen/a.rs",
+                1,
+                5,
+                "fn load_user(id: u64) -> User { db.get(id) }",
+                1.0,
+            ),
+            hit(
+                "gen/b.rs",
+                1,
+                5,
+                "fn load_user(id: u64) -> User { db.get(id) }",
+                0.95,
+            ),
+            hit(
+                "src/auth.rs",
+                10,
+                20,
+                "fn verify_token(token: &str) -> Claims",
+                0.9,
+            ),
+            hit(
+                "src/auth.rs",
+                30,
+                40,
+                "fn refresh_session(session: Session)",
+                0.85,
+            ),
+            hit("src/auth.rs", 50, 60, "fn revoke(token: Token)", 0.8),
+        ];
+
+        // The copy in gen/b.rs ranks behind distinct code; the third auth.rs hit is capped
+        let picked = mmr_rerank(hits, 0.7, 2, 4);
+        let files: Vec<&str> = picked.iter().map(|h| h.file_path.as_str()).collect();
+        assert_eq!(
+            files,
+            vec!["gen/a.rs", "src/auth.rs", "src/auth.rs", "gen/b.rs"]
+        );
+    }
+
+    #[test]
+    fn test_group_by_file_merges_adjacent_ranges() {
+        let hits = vec![
+            (1.0, hit("src/a.rs", 10, 12, "fn a() {\n    x();\n}", 1.0)),
+            (0.8, hit("src/a.rs", 13, 14, "fn b() {\n}", 0.8)),
+            (0.6, hit("src/a.rs", 40, 41, "fn c() {\n}", 0.6)),
+            (0.9, hit("src/b.rs", 1, 1, "use a;", 0.9)),
+        ];
+
+        let groups = group_by_file(hits);
+        let ranges: Vec<(&str, u32, u32)> = groups
+            .iter()
+            .map(|g| (g.file_path.as_str(), g.line_start, g.line_end))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("src/a.rs", 10, 14),
+                ("src/b.rs", 1, 1),
+                ("src/a.rs", 40, 41)
+            ]
+        );
+        assert_eq!(groups[0].content, "fn a() {\n    x();\n}\nfn b() {\n}");
+    }
+}
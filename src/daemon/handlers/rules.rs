@@ -0,0 +1,332 @@
+//! `check_rules`: conventions from `.gofer/rules.toml` checked against the
+//! indexed files, and the same checks on edited content before it is written
+//! (`verify_patch`, `commit_transaction`). `architecture_check`: layering
+//! violations and module coupling of the import graph.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use super::common::{index_path, ToolContext};
+use crate::error::GoferError;
+use crate::indexer::architecture::{coupling, GraphFile};
+use crate::indexer::context::ImportResolver;
+use crate::indexer::domains::{detect_domain, DomainConfig};
+use crate::indexer::parser::SupportedLanguage;
+use crate::indexer::rules::{
+    check_file, load_rules, parse_imports, Domains, FileFacts, RulesFile, Severity, Violation,
+};
+use crate::indexer::watcher::load_config;
+
+pub async fn tool_check_rules(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+    let errors_only = args.get("severity").and_then(|v| v.as_str()) == Some("error");
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(200);
+
+    let rules = project_rules(ctx)?;
+    if rules.is_empty() {
+        return Ok(json!({
+            "violations": [],
+            "message": "No rules defined. Add [[imports]], [[naming]], [[banned]], [[limits]] or [architecture] entries to .gofer/rules.toml."
+        }));
+    }
+
+    // Symbols come from the index; content and imports from disk
+    let files = code_files(ctx, path).await?;
+    let mut indexed = Vec::with_capacity(files.len());
+    for file in files {
+        let symbols = match ctx.sqlite.get_file(&file).await? {
+            Some(f) => ctx.sqlite.get_file_symbols(f.id).await?,
+            None => continue,
+        };
+        indexed.push((file, symbols));
+    }
+    // Imports may leave `path`, so domains are loaded for every indexed file
+    let all_files = if path.is_empty() {
+        indexed.iter().map(|(file, _)| file.clone()).collect()
+    } else {
+        code_files(ctx, "").await?
+    };
+    let domains = Domains {
+        config: domain_config(ctx),
+        stored: ctx.sqlite.get_file_domains(&all_files).await?,
+    };
+    let root = ctx.root_path.as_ref().clone();
+    let checked = indexed.len();
+    let mut violations = tokio::task::spawn_blocking(move || {
+        let resolver = ImportResolver::new(&root);
+        let mut violations = Vec::new();
+        for (file, symbols) in indexed {
+            let Ok(content) = std::fs::read_to_string(root.join(&file)) else {
+                continue;
+            };
+            let facts = FileFacts::parse(
+                &root,
+                &resolver,
+                &domains.config,
+                &file,
+                content,
+                Some(symbols),
+            );
+            if let Some(facts) = facts {
+                violations.extend(check_file(&rules, &facts, &domains));
+            }
+        }
+        violations
+    })
+    .await?;
+    if errors_only {
+        violations.retain(|v| v.severity == Severity::Error);
+    }
+
+    let errors = violations
+        .iter()
+        .filter(|v| v.severity == Severity::Error)
+        .count();
+    let mut files_with_violations: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+    files_with_violations.dedup();
+    let summary = format!(
+        "{} errors, {} warnings in {} of {} files",
+        errors,
+        violations.len() - errors,
+        files_with_violations.len(),
+        checked
+    );
+    let mut lines: Vec<String> = violations.iter().map(Violation::to_string).collect();
+    if lines.len() > limit {
+        let more = lines.len() - limit;
+        lines.truncate(limit);
+        lines.push(format!("... {} more", more));
+    }
+    Ok(json!({
+        "summary": summary,
+        "violations": lines,
+    }))
+}
+
+pub async fn tool_architecture_check(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+    let depth = args
+        .get("depth")
+        .and_then(|v| v.as_u64())
+        .map(|d| d as usize)
+        .unwrap_or(2);
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| l as usize)
+        .unwrap_or(50);
+
+    let rules = project_rules(ctx)?;
+    let layered = !rules.architecture.is_empty();
+    let files = code_files(ctx, path).await?;
+    // Targets outside `path` keep their indexed domain
+    let all_files = if path.is_empty() {
+        files.clone()
+    } else {
+        code_files(ctx, "").await?
+    };
+    let stored = ctx.sqlite.get_file_domains(&all_files).await?;
+    let config = domain_config(ctx);
+    let root = ctx.root_path.as_ref().clone();
+    let (modules, cycles, violations) = tokio::task::spawn_blocking(move || {
+        let resolver = ImportResolver::new(&root);
+        let mut graph = Vec::with_capacity(files.len());
+        for file in files {
+            let Ok(content) = std::fs::read_to_string(root.join(&file)) else {
+                continue;
+            };
+            let Some(imports) = parse_imports(&root, &resolver, &file, &content) else {
+                continue;
+            };
+            let domain = detect_domain(&file, &content, &config).0.as_str();
+            graph.push(GraphFile {
+                path: file,
+                domain: domain.to_string(),
+                imports,
+            });
+        }
+        let mut domains = Domains { config, stored };
+        domains
+            .stored
+            .extend(graph.iter().map(|f| (f.path.clone(), f.domain.clone())));
+        let mut violations = Vec::new();
+        for file in &graph {
+            for import in &file.imports {
+                for target in &import.targets {
+                    if let Some(why) = rules.architecture.check_edge(
+                        &file.path,
+                        &file.domain,
+                        target,
+                        domains.of(target),
+                    ) {
+                        violations.push(format!(
+                            "{}:{} -> {} ({})",
+                            file.path,
+                            import.line + 1,
+                            target,
+                            why
+                        ));
+                    }
+                }
+            }
+        }
+        let (modules, cycles) = coupling(&rules.architecture, &graph, depth);
+        (modules, cycles, violations)
+    })
+    .await?;
+
+    let summary = format!(
+        "{} layering violations, {} modules, {} cycles",
+        violations.len(),
+        modules.len(),
+        cycles.len()
+    );
+    let mut result = json!({
+        "summary": summary,
+        "violations": violations.iter().take(limit).collect::<Vec<_>>(),
+        "modules": modules.iter().take(limit).map(|m| format!(
+            "{}: {} files, fan-in {}, fan-out {}",
+            m.name, m.files, m.fan_in, m.fan_out
+        )).collect::<Vec<_>>(),
+        "cycles": cycles.iter().map(|c| json!({
+            "modules": c.modules.join(" <-> "),
+            "edges": c.edges,
+        })).collect::<Vec<_>>(),
+    });
+    if !layered {
+        result["message"] = json!(
+            "No layers declared. Add [architecture] layers/forbid to .gofer/rules.toml to check import edges."
+        );
+    }
+    Ok(result)
+}
+
+/// Rules `content` would break if written to `file`; empty when the project
+/// has no rules or the file is not code.
+pub async fn check_content(ctx: &ToolContext, file: &str, content: &str) -> Result<Vec<Violation>> {
+    let rules = project_rules(ctx)?;
+    let path = index_path(&ctx.root_path, file);
+    if rules.is_empty() || !is_code(&path) {
+        return Ok(Vec::new());
+    }
+    let root = ctx.root_path.as_ref().clone();
+    let content = content.to_string();
+    let config = domain_config(ctx);
+    let (facts, config) = tokio::task::spawn_blocking(move || {
+        let resolver = ImportResolver::new(&root);
+        let facts = FileFacts::parse(&root, &resolver, &config, &path, content, None);
+        (facts, config)
+    })
+    .await?;
+    let Some(facts) = facts else {
+        return Ok(Vec::new());
+    };
+    let targets: Vec<String> = facts.targets().cloned().collect();
+    let domains = Domains {
+        config,
+        stored: ctx.sqlite.get_file_domains(&targets).await?,
+    };
+    Ok(check_file(&rules, &facts, &domains))
+}
+
+/// Domain detection with the project's `[domains]` overrides
+fn domain_config(ctx: &ToolContext) -> DomainConfig {
+    load_config(&ctx.root_path.join(".gofer")).domain_config()
+}
+
+fn project_rules(ctx: &ToolContext) -> Result<RulesFile> {
+    load_rules(&ctx.root_path).map_err(|e| GoferError::InvalidParams(e.to_string()).into())
+}
+
+/// Indexed code files under `path` (all when empty), sorted
+async fn code_files(ctx: &ToolContext, path: &str) -> Result<Vec<String>> {
+    let prefix = if path.is_empty() {
+        String::new()
+    } else {
+        index_path(&ctx.root_path, path)
+    };
+    let mut files: Vec<String> = ctx
+        .sqlite
+        .get_file_stats()
+        .await?
+        .into_keys()
+        .filter(|p| prefix.is_empty() || *p == prefix || p.starts_with(&format!("{}/", prefix)))
+        .filter(|p| is_code(p))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn is_code(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(SupportedLanguage::from_extension)
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::handlers::common::test_context;
+
+    #[tokio::test]
+    async fn test_architecture_check_uses_project_domains() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".gofer")).unwrap();
+        std::fs::write(
+            root.join(".gofer/config.toml"),
+            "[domains]\nfrontend_paths = [\"portal/\"]\nrs_paths = [\"core/\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join(".gofer/rules.toml"),
+            "[architecture]\nforbid = [\"frontend -> backend\"]\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("portal")).unwrap();
+        std::fs::create_dir_all(root.join("core")).unwrap();
+        std::fs::write(
+            root.join("portal/app.ts"),
+            "import { query } from '../core/db';\nexport const app = query;\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("core/db.ts"), "export const query = 1;\n").unwrap();
+
+        let (ctx, _index) = test_context(root).await;
+        ctx.sqlite
+            .upsert_file("portal/app.ts", 0, "a")
+            .await
+            .unwrap();
+        let db = ctx.sqlite.upsert_file("core/db.ts", 0, "b").await.unwrap();
+
+        // Both domains come from `[domains]`; neither path is a default
+        let all = tool_architecture_check(json!({}), &ctx).await.unwrap();
+        assert_eq!(
+            all["violations"],
+            json!(["portal/app.ts:1 -> core/db.ts (frontend must not import backend)"])
+        );
+
+        // A target outside `path` keeps its indexed domain
+        ctx.sqlite
+            .update_file_domain(db, "backend", &[])
+            .await
+            .unwrap();
+        std::fs::write(
+            root.join(".gofer/config.toml"),
+            "[domains]\nfrontend_paths = [\"portal/\"]\n",
+        )
+        .unwrap();
+        let scoped = tool_architecture_check(json!({ "path": "portal" }), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(scoped["violations"], all["violations"]);
+    }
+}
@@ -180,12 +180,12 @@ pub async fn tool_run_all_tests(args: Value, ctx: &ToolContext) -> Result<Value>
 
     // Check for Cargo.toml (Rust)
     if project_root.join("Cargo.toml").exists() {
-        return run_cargo_tests(project_root, filter, timeout_seconds).await;
+        return run_cargo_tests(ctx, filter, timeout_seconds).await;
     }
 
     // Check for package.json (Node.js)
     if project_root.join("package.json").exists() {
-        return run_npm_tests(project_root, filter, timeout_seconds).await;
+        return run_npm_tests(ctx, filter, timeout_seconds).await;
     }
 
     // Check for pytest (Python)
@@ -193,7 +193,7 @@ pub async fn tool_run_all_tests(args: Value, ctx: &ToolContext) -> Result<Value>
         || project_root.join("pyproject.toml").exists()
         || project_root.join("tests").exists()
     {
-        return run_pytest_tests(project_root, filter, timeout_seconds).await;
+        return run_pytest_tests(ctx, filter, timeout_seconds).await;
     }
 
     Err(GoferError::InvalidParams("No test framework detected in project".into()).into())
@@ -673,10 +673,11 @@ async fn run_javascript_test(
 
 #[allow(dead_code)]
 async fn run_cargo_tests(
-    project_root: &Path,
+    ctx: &ToolContext,
     filter: Option<&str>,
     timeout_secs: u64,
 ) -> Result<Value> {
+    let project_root = ctx.root_path.as_path();
     let mut cmd = Command::new("cargo");
     cmd.arg("test")
         .current_dir(project_root)
@@ -688,10 +689,15 @@ async fn run_cargo_tests(
     }
 
     let start = std::time::Instant::now();
-    let execute_future = cmd.output();
+    let execute_future = ctx.run_command(&mut cmd, "cargo test");
 
     let output = match timeout(Duration::from_secs(timeout_secs), execute_future).await {
-        Ok(Ok(output)) => output,
+        Ok(Ok(Some(output))) => output,
+        Ok(Ok(None)) => {
+            return Ok(json!({
+                "status": "cancelled",
+            }))
+        }
         Ok(Err(e)) => {
             return Ok(json!({
                 "status": "error",
@@ -716,10 +722,11 @@ async fn run_cargo_tests(
 
 #[allow(dead_code)]
 async fn run_npm_tests(
-    project_root: &Path,
+    ctx: &ToolContext,
     filter: Option<&str>,
     timeout_secs: u64,
 ) -> Result<Value> {
+    let project_root = ctx.root_path.as_path();
     let mut cmd = Command::new("npm");
     cmd.arg("test")
         .current_dir(project_root)
@@ -731,10 +738,15 @@ async fn run_npm_tests(
     }
 
     let start = std::time::Instant::now();
-    let execute_future = cmd.output();
+    let execute_future = ctx.run_command(&mut cmd, "npm test");
 
     let output = match timeout(Duration::from_secs(timeout_secs), execute_future).await {
-        Ok(Ok(output)) => output,
+        Ok(Ok(Some(output))) => output,
+        Ok(Ok(None)) => {
+            return Ok(json!({
+                "status": "cancelled",
+            }))
+        }
         Ok(Err(e)) => {
             return Ok(json!({
                 "status": "error",
@@ -759,10 +771,11 @@ async fn run_npm_tests(
 
 #[allow(dead_code)]
 async fn run_pytest_tests(
-    project_root: &Path,
+    ctx: &ToolContext,
     filter: Option<&str>,
     timeout_secs: u64,
 ) -> Result<Value> {
+    let project_root = ctx.root_path.as_path();
     let mut cmd = Command::new("pytest");
     cmd.arg("-v")
         .current_dir(project_root)
@@ -774,10 +787,15 @@ async fn run_pytest_tests(
     }
 
     let start = std::time::Instant::now();
-    let execute_future = cmd.output();
+    let execute_future = ctx.run_command(&mut cmd, "pytest");
 
     let output = match timeout(Duration::from_secs(timeout_secs), execute_future).await {
-        Ok(Ok(output)) => output,
+        Ok(Ok(Some(output))) => output,
+        Ok(Ok(None)) => {
+            return Ok(json!({
+                "status": "cancelled",
+            }))
+        }
         Ok(Err(e)) => {
             return Ok(json!({
                 "status": "error",
@@ -1,15 +1,20 @@
-use super::common::{make_relative, resolve_path, ToolContext};
+use super::common::{frontend_api_calls, index_path, make_relative, resolve_path, ToolContext};
 use crate::error::GoferError;
+use crate::indexer::api_contracts::{client_path, same_route, SOURCE_CODE};
 use crate::models::chunk::SymbolKind;
 use anyhow::Result;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Default RRF constant; larger values flatten the contribution of top ranks
+const RRF_K: f64 = 60.0;
+
 /// Fused search hit from vector + FTS results
 pub struct FusedHit {
     pub file_path: String,
     pub line_start: u32,
+    pub line_end: u32,
     pub content: String,
     pub rrf_score: f64,
     pub vector_score: Option<f32>,
@@ -41,6 +46,20 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         .get("include_context")
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
+    // Result diversification: MMR weight, per-file cap, merged per-file ranges
+    let diversity = args
+        .get("diversity")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.3)
+        .clamp(0.0, 1.0);
+    let max_per_file = args
+        .get("max_per_file")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(3) as usize;
+    let group_by_file = args
+        .get("group_by_file")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
 
     // Extract path filter for use in vector and FTS search
     let path_filter = args.get("path").and_then(|v| v.as_str());
@@ -49,14 +68,35 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         return Err(GoferError::InvalidParams("Query is required".into()).into());
     }
 
+    // Key on every argument: tuned or differently formatted calls must not share entries
+    let cache_key = args.to_string();
+
     // NEW: Feature 008 - Check cache first
-    if let Some(cached_json) = ctx.cache.get_search(query, limit).await {
+    if let Some(cached_json) = ctx.cache.get_search(&cache_key, limit).await {
         // Parse cached JSON back to Value
         if let Ok(cached_result) = serde_json::from_str::<Value>(&cached_json) {
             return Ok(cached_result);
         }
     }
 
+    // Query understanding: split identifiers, expand synonyms, detect exact symbol names
+    let plan = super::query::analyze_query(query, ctx).await;
+    let exact_shortcut = args
+        .get("exact_symbol_shortcut")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+        && path_filter.is_none()
+        && args.get("glob").is_none();
+    if let (Some(symbol), true) = (&plan.exact_symbol, exact_shortcut) {
+        let mut result =
+            super::symbols::tool_search_symbols(json!({ "query": symbol, "limit": limit }), ctx)
+                .await?;
+        if let Some(obj) = result.as_object_mut() {
+            obj.insert("mode".to_string(), json!("exact_symbol"));
+        }
+        return Ok(result);
+    }
+
     // Feature 016: Track warnings for degraded mode
     let mut warnings: Vec<String> = Vec::new();
     let mut degraded = false;
@@ -72,7 +112,7 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         })
         .await;
 
-    let path_filter_abs = path_filter.map(|p| resolve_path(&ctx.root_path, p));
+    let path_filter_key = path_filter.map(|p| index_path(&ctx.root_path, p));
 
     let vector_results = match embedding_result {
         Ok(embedding) => {
@@ -82,7 +122,7 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
                 .call(|| async {
                     let lance = ctx.lance.lock().await;
                     lance
-                        .search_with_filter(&embedding, limit * 2, path_filter_abs.as_deref())
+                        .search_with_filter(&embedding, limit * 2, path_filter_key.as_deref())
                         .await
                         .map_err(|e| anyhow::anyhow!(e))
                 })
@@ -116,7 +156,7 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         .join(" OR ");
     let fts_results = match ctx
         .sqlite
-        .search_symbols_with_path_filter(&fts_query, (limit * 2) as i32, path_filter_abs.as_deref())
+        .search_symbols_with_path_filter(&fts_query, (limit * 2) as i32, path_filter_key.as_deref())
         .await
     {
         Ok(r) => r,
@@ -126,15 +166,48 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         }
     };
 
-    // 3. RRF fusion (k=60)
-    const K: f64 = 60.0;
+    // 2b. BM25 over chunk bodies — reaches identifiers, strings and comments inside functions
+    let chunk_text_results = match ctx
+        .sqlite
+        .search_chunk_text(
+            &plan.keyword_query(query),
+            (limit * 2) as i32,
+            path_filter_key.as_deref(),
+        )
+        .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!("Chunk FTS search failed (continuing without it): {}", e);
+            Vec::new()
+        }
+    };
+
+    // 2c. Symbol names for expansion terms (synonyms, co-occurring identifiers)
+    let mut expansion_results = Vec::new();
+    for term in &plan.expansions {
+        if let Ok(hits) = ctx
+            .sqlite
+            .search_symbols_with_path_filter(term, limit as i32, path_filter_key.as_deref())
+            .await
+        {
+            expansion_results.extend(hits);
+        }
+    }
+
+    // 3. RRF fusion (k=60 unless tuned via `rrf_k`)
+    let k = args
+        .get("rrf_k")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(RRF_K)
+        .max(0.0);
 
     let mut scores: HashMap<(String, u32), FusedHit> = HashMap::new();
 
     // Vector results contribute
     for (rank, hit) in vector_results.iter().enumerate() {
         let key = (hit.file_path.clone(), hit.line_start);
-        let rrf = 1.0 / (K + rank as f64 + 1.0);
+        let rrf = 1.0 / (k + rank as f64 + 1.0);
         scores
             .entry(key)
             .and_modify(|h| {
@@ -146,6 +219,7 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
             .or_insert(FusedHit {
                 file_path: hit.file_path.clone(),
                 line_start: hit.line_start,
+                line_end: hit.line_end,
                 content: hit.content.clone(),
                 rrf_score: rrf,
                 vector_score: Some(hit.score),
@@ -157,7 +231,7 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
     // FTS results contribute
     for (rank, sym) in fts_results.iter().enumerate() {
         let key = (sym.file_path.clone(), sym.line as u32);
-        let rrf = 1.0 / (K + rank as f64 + 1.0);
+        let rrf = 1.0 / (k + rank as f64 + 1.0);
         let content = sym.signature.as_deref().unwrap_or(&sym.name).to_string();
         scores
             .entry(key)
@@ -171,6 +245,53 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
             .or_insert(FusedHit {
                 file_path: sym.file_path.clone(),
                 line_start: sym.line as u32,
+                line_end: sym.end_line.max(sym.line) as u32,
+                content,
+                rrf_score: rrf,
+                vector_score: None,
+                matched_symbol: Some(sym.name.clone()),
+                symbol_kind: Some(sym.kind),
+            });
+    }
+
+    // Chunk body matches contribute
+    for (rank, chunk) in chunk_text_results.iter().enumerate() {
+        let key = (chunk.file_path.clone(), chunk.line_start as u32);
+        let rrf = 1.0 / (k + rank as f64 + 1.0);
+        scores
+            .entry(key)
+            .and_modify(|h| h.rrf_score += rrf)
+            .or_insert(FusedHit {
+                file_path: chunk.file_path.clone(),
+                line_start: chunk.line_start as u32,
+                line_end: chunk.line_end as u32,
+                content: chunk.content.clone(),
+                rrf_score: rrf,
+                vector_score: None,
+                matched_symbol: None,
+                symbol_kind: None,
+            });
+    }
+
+    // Expansion matches contribute at half weight — they are guesses, not the user's words
+    const EXPANSION_WEIGHT: f64 = 0.5;
+    for (rank, sym) in expansion_results.iter().enumerate() {
+        let key = (sym.file_path.clone(), sym.line as u32);
+        let rrf = EXPANSION_WEIGHT / (k + rank as f64 + 1.0);
+        let content = sym.signature.as_deref().unwrap_or(&sym.name).to_string();
+        scores
+            .entry(key)
+            .and_modify(|h| {
+                h.rrf_score += rrf;
+                if h.matched_symbol.is_none() {
+                    h.matched_symbol = Some(sym.name.clone());
+                    h.symbol_kind = Some(sym.kind);
+                }
+            })
+            .or_insert(FusedHit {
+                file_path: sym.file_path.clone(),
+                line_start: sym.line as u32,
+                line_end: sym.end_line.max(sym.line) as u32,
                 content,
                 rrf_score: rrf,
                 vector_score: None,
@@ -215,76 +336,115 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         fused
     };
 
+    // Diversify: drop near-duplicates and overlapping chunks, cap hits per file
+    let fused = super::rerank::mmr_rerank(fused, 1.0 - diversity, max_per_file, limit);
+
     // NEW: Normalize scores and filter by min_score
     let max_rrf = fused.first().map(|h| h.rrf_score).unwrap_or(1.0);
-    let enhanced_results: Vec<(f32, Value)> = fused
-        .into_iter()
-        .map(|hit| {
-            // Normalize RRF score to 0.0-1.0
-            let normalized_score = if max_rrf > 0.0 {
-                (hit.rrf_score / max_rrf) as f32
-            } else {
-                0.0
-            };
+    let normalize = |hit: &FusedHit| -> f32 {
+        if max_rrf > 0.0 {
+            (hit.rrf_score / max_rrf) as f32
+        } else {
+            0.0
+        }
+    };
 
-            // Determine match reason
-            let match_reason = determine_match_reason(&hit, query);
+    let results: Vec<Value> = if group_by_file {
+        let scored: Vec<(f32, FusedHit)> = fused
+            .into_iter()
+            .map(|hit| (normalize(&hit), hit))
+            .filter(|(score, _)| *score >= min_score)
+            .collect();
+        super::rerank::group_by_file(scored)
+            .into_iter()
+            .map(|group| {
+                let mut parts = vec![format!(
+                    "{}:{}-{}",
+                    make_relative(&ctx.root_path, &group.file_path),
+                    group.line_start,
+                    group.line_end
+                )];
+                if include_scores {
+                    parts.push(format!("[score={:.3}]", group.score));
+                }
+                if include_context && !group.symbols.is_empty() {
+                    parts.push(format!("(ctx:{})", group.symbols.join(", ")));
+                }
+                let content = if preview_mode {
+                    generate_preview(&group.content, 3).unwrap_or_default()
+                } else {
+                    group.content
+                };
+                json!(format!("{}\n{}", parts.join(" "), content))
+            })
+            .collect()
+    } else {
+        let enhanced_results: Vec<(f32, Value)> = fused
+            .into_iter()
+            .map(|hit| {
+                // Normalize RRF score to 0.0-1.0
+                let normalized_score = normalize(&hit);
 
-            // Generate preview if requested
-            let preview = if preview_mode {
-                generate_preview(&hit.content, 3)
-            } else {
-                None
-            };
+                // Determine match reason
+                let match_reason = determine_match_reason(&hit, query);
 
-            // Get context (symbol name)
-            let context = if include_context {
-                hit.matched_symbol.clone().or_else(|| {
-                    // Try to extract function/class name from content
-                    extract_context_from_content(&hit.content)
-                })
-            } else {
-                None
-            };
+                // Generate preview if requested
+                let preview = if preview_mode {
+                    generate_preview(&hit.content, 3)
+                } else {
+                    None
+                };
 
-            let default_content = hit.content.trim().to_string();
-            let content_str = if preview_mode {
-                preview.as_ref().unwrap_or(&default_content)
-            } else {
-                &default_content
-            };
+                // Get context (symbol name)
+                let context = if include_context {
+                    hit.matched_symbol.clone().or_else(|| {
+                        // Try to extract function/class name from content
+                        extract_context_from_content(&hit.content)
+                    })
+                } else {
+                    None
+                };
 
-            let mut parts = vec![format!(
-                "{}:{}",
-                make_relative(&ctx.root_path, &hit.file_path),
-                hit.line_start
-            )];
+                let default_content = hit.content.trim().to_string();
+                let content_str = if preview_mode {
+                    preview.as_ref().unwrap_or(&default_content)
+                } else {
+                    &default_content
+                };
 
-            if include_scores {
-                parts.push(format!("[score={:.3}]", normalized_score));
-            }
-            if include_scores || preview_mode {
-                if let Some(reason) = &match_reason {
-                    parts.push(format!("(reason:{})", reason));
+                let mut parts = vec![format!(
+                    "{}:{}",
+                    make_relative(&ctx.root_path, &hit.file_path),
+                    hit.line_start
+                )];
+
+                if include_scores {
+                    parts.push(format!("[score={:.3}]", normalized_score));
+                }
+                if include_scores || preview_mode {
+                    if let Some(reason) = &match_reason {
+                        parts.push(format!("(reason:{})", reason));
+                    }
+                }
+                if let Some(ctx_val) = context {
+                    parts.push(format!("(ctx:{})", ctx_val));
                 }
-            }
-            if let Some(ctx_val) = context {
-                parts.push(format!("(ctx:{})", ctx_val));
-            }
 
-            let result = json!(format!("{}\n{}", parts.join(" "), content_str));
+                let result = json!(format!("{}\n{}", parts.join(" "), content_str));
 
-            (normalized_score, result)
-        })
-        .filter(|(score, _)| *score >= min_score)
-        .collect::<Vec<_>>();
+                (normalized_score, result)
+            })
+            .filter(|(score, _)| *score >= min_score)
+            .collect::<Vec<_>>();
 
-    // Sort by score descending
-    let mut enhanced_results = enhanced_results;
-    enhanced_results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-    enhanced_results.truncate(limit);
+        // Sort by score descending
+        let mut enhanced_results = enhanced_results;
+        enhanced_results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        enhanced_results.truncate(limit);
+
+        enhanced_results.into_iter().map(|(_, r)| r).collect()
+    };
 
-    let results: Vec<Value> = enhanced_results.into_iter().map(|(_, r)| r).collect();
     let search_time_ms = search_start.elapsed().as_millis();
 
     // 6. Structured output with degraded mode info (Feature 016)
@@ -295,6 +455,12 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
         "search_time_ms": search_time_ms
     });
 
+    if !plan.expansions.is_empty() {
+        if let Some(obj) = final_result.as_object_mut() {
+            obj.insert("expanded_terms".to_string(), json!(plan.expansions));
+        }
+    }
+
     // Add degraded mode information if applicable
     if degraded {
         if let Some(obj) = final_result.as_object_mut() {
@@ -306,9 +472,7 @@ pub async fn tool_search(args: Value, ctx: &ToolContext) -> Result<Value> {
     // NEW: Feature 008 - Store in cache (only if not degraded for best quality)
     if !degraded {
         if let Ok(result_json) = serde_json::to_string(&final_result) {
-            ctx.cache
-                .put_search(query.to_string(), limit, result_json)
-                .await;
+            ctx.cache.put_search(cache_key, limit, result_json).await;
         }
     }
 
@@ -333,32 +497,33 @@ pub async fn tool_cross_stack_search(args: Value, ctx: &ToolContext) -> Result<V
         return Ok(search_result);
     }
 
-    // Collect unique file paths from the structured search results
-    let result_files: Vec<String> = search_result
-        .get("results")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|r| {
-                    r.get("file_path")
-                        .and_then(|v| v.as_str())
-                        .map(String::from)
-                })
-                .collect()
-        })
-        .unwrap_or_default();
+    let links = cross_stack_links(ctx, &search_result).await;
 
+    // Merge links into the search result
+    let mut result = search_result;
+    result["cross_stack_links"] = json!(links);
+    Ok(result)
+}
+
+/// Cross-stack links and API call → route links touching the files of a search result
+async fn cross_stack_links(ctx: &ToolContext, search_result: &Value) -> Vec<Value> {
+    // "path:line ..." hits, or the files of an exact-symbol match
+    let mut result_files = super::feedback::listed_paths(search_result).unwrap_or_default();
+    if let Some(symbols) = search_result.get("symbols").and_then(|v| v.as_object()) {
+        result_files.extend(symbols.keys().cloned());
+    }
+
+    let mut links = Vec::new();
     if result_files.is_empty() {
-        return Ok(search_result);
+        return links;
     }
 
     // Look up cross-stack links for each file
     let mut seen_links = std::collections::HashSet::new();
-    let mut links = Vec::new();
 
     for file_path in &result_files {
-        let abs_path = resolve_path(&ctx.root_path, file_path);
-        let file_links = match ctx.sqlite.get_cross_stack_links_for_file(&abs_path).await {
+        let rel_path = index_path(&ctx.root_path, file_path);
+        let file_links = match ctx.sqlite.get_cross_stack_links_for_file(&rel_path).await {
             Ok(l) => l,
             Err(e) => {
                 tracing::debug!("Failed to get cross-stack links for {}: {}", file_path, e);
@@ -382,10 +547,58 @@ pub async fn tool_cross_stack_search(args: Value, ctx: &ToolContext) -> Result<V
         }
     }
 
-    // Merge links into the search result
-    let mut result = search_result;
-    result["cross_stack_links"] = json!(links);
-    Ok(result)
+    // HTTP calls made by the result files and the routes they reach, both ways
+    let api_links = match api_call_links(ctx, &result_files).await {
+        Ok(l) => l,
+        Err(e) => {
+            tracing::debug!("Failed to link API calls: {}", e);
+            Vec::new()
+        }
+    };
+    for link in api_links {
+        if seen_links.insert(link.clone()) {
+            links.push(json!(link));
+        }
+    }
+    links
+}
+
+/// `web/api.ts:12 GET /api/users/${id} -> src/users.rs:40 show_user (api)` for
+/// every call made from, or route served by, one of `files`
+async fn api_call_links(ctx: &ToolContext, files: &[String]) -> Result<Vec<String>> {
+    let files: std::collections::HashSet<String> = files
+        .iter()
+        .map(|f| index_path(&ctx.root_path, f))
+        .collect();
+    let endpoints = ctx.sqlite.get_api_endpoints().await?;
+    let calls = frontend_api_calls(ctx).await?;
+
+    let mut links = Vec::new();
+    for endpoint in endpoints.iter().filter(|e| e.source == SOURCE_CODE) {
+        // Express `app.all` and friends accept every method
+        let method = (endpoint.method != "ALL").then_some(endpoint.method.as_str());
+        for call in &calls {
+            if !files.contains(&call.file_path) && !files.contains(&endpoint.file_path) {
+                continue;
+            }
+            let Some(path) = client_path(&call.path) else {
+                continue;
+            };
+            if same_route(method, &endpoint.path, call.method.as_deref(), &path) {
+                links.push(format!(
+                    "{}:{} {} {} -> {}:{} {} (api)",
+                    call.file_path,
+                    call.line.unwrap_or(0) + 1,
+                    call.method.as_deref().unwrap_or("GET"),
+                    call.path,
+                    endpoint.file_path,
+                    endpoint.line.unwrap_or(0) + 1,
+                    endpoint.handler.as_deref().unwrap_or(&endpoint.path)
+                ));
+            }
+        }
+    }
+    Ok(links)
 }
 
 pub async fn tool_search_by_purpose(args: Value, ctx: &ToolContext) -> Result<Value> {
@@ -487,8 +700,14 @@ pub async fn tool_smart_file_selection(args: Value, ctx: &ToolContext) -> Result
         return Err(GoferError::InvalidParams("Query is required".into()).into());
     }
 
+    // Explicit weights replace the adaptive ones (used by `gofer eval` to compare configs)
+    let weights_override: Option<ScoringWeights> = args
+        .get("weights")
+        .and_then(|w| serde_json::from_value(w.clone()).ok());
+
     // Check cache first (using get_search as a substitute for get_file_selection)
-    if let Some(cached_json) = ctx.cache.get_search(query, limit).await {
+    let cache_key = format!("smart_file_selection\u{0}{}", args);
+    if let Some(cached_json) = ctx.cache.get_search(&cache_key, limit).await {
         if let Ok(cached_result) = serde_json::from_str::<Value>(&cached_json) {
             return Ok(cached_result);
         }
@@ -516,6 +735,18 @@ pub async fn tool_smart_file_selection(args: Value, ctx: &ToolContext) -> Result
             .push(hit.content.clone());
     }
 
+    // Learned boosts from files agents opened after earlier searches
+    let use_feedback = args
+        .get("use_feedback")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let feedback_boosts = if use_feedback {
+        let files: Vec<String> = file_scores.keys().cloned().collect();
+        super::feedback::learned_boosts(&ctx.sqlite, &ctx.feedback, query, &files).await
+    } else {
+        HashMap::new()
+    };
+
     // 3. Get file metadata and symbols
     let mut candidates = Vec::new();
     let total_candidates = file_scores.len();
@@ -536,7 +767,7 @@ pub async fn tool_smart_file_selection(args: Value, ctx: &ToolContext) -> Result
             .unwrap_or_default();
 
         // Get file metadata for scoring
-        let file_metadata = get_file_metadata(&file_path).await;
+        let file_metadata = get_file_metadata(&resolve_path(&ctx.root_path, &file_path)).await;
 
         // Calculate component scores with v2 algorithm
         let path_score = calculate_path_score_v2(query, &file_path);
@@ -548,13 +779,18 @@ pub async fn tool_smart_file_selection(args: Value, ctx: &ToolContext) -> Result
         };
 
         // Calculate score with adaptive weights, recency, and size
+        let weights = weights_override
+            .clone()
+            .unwrap_or_else(|| calculate_adaptive_weights(query));
+        let feedback_boost = feedback_boosts.get(&file_path).copied().unwrap_or(1.0);
         let (final_score, scoring_details) = calculate_relevance_score_v2(
-            query,
+            weights,
             &file_metadata,
             vector_score,
             path_score,
             symbol_score,
             summary_score,
+            feedback_boost,
         );
 
         // Generate reasoning
@@ -616,9 +852,7 @@ pub async fn tool_smart_file_selection(args: Value, ctx: &ToolContext) -> Result
 
     // Store in cache (using put_search as a substitute for put_file_selection)
     if let Ok(result_json) = serde_json::to_string(&result) {
-        ctx.cache
-            .put_search(query.to_string(), limit, result_json)
-            .await;
+        ctx.cache.put_search(cache_key, limit, result_json).await;
     }
 
     Ok(result)
@@ -804,7 +1038,7 @@ async fn get_file_metadata(path: &str) -> FileMetadata {
 }
 
 /// Scoring weights for different query types
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Deserialize)]
 struct ScoringWeights {
     vector: f32,
     path: f32,
@@ -819,21 +1053,22 @@ struct ScoringDetails {
     base_score: f32,
     recency_boost: f32,
     size_penalty: f32,
+    feedback_boost: f32,
     confidence: f32,
     weights: ScoringWeights,
 }
 
 /// Calculate relevance score with adaptive weights v2
 fn calculate_relevance_score_v2(
-    query: &str,
+    weights: ScoringWeights,
     file_metadata: &FileMetadata,
     vector_score: f32,
     path_score: f32,
     symbol_score: f32,
     summary_score: f32,
+    feedback_boost: f32,
 ) -> (f32, ScoringDetails) {
-    // 1. Determine query type and adjust weights
-    let weights = calculate_adaptive_weights(query);
+    // 1. Weights are chosen by the caller (adaptive per query type, or overridden)
 
     // 2. Calculate recency boost
     let recency_boost = calculate_recency_boost(file_metadata.last_modified);
@@ -847,8 +1082,8 @@ fn calculate_relevance_score_v2(
         + symbol_score * weights.symbols
         + summary_score * weights.summary;
 
-    // 5. Apply modifiers
-    let final_score = base_score * recency_boost * size_penalty;
+    // 5. Apply modifiers (feedback_boost: learned from follow-up reads, 1.0 = no signal)
+    let final_score = base_score * recency_boost * size_penalty * feedback_boost;
 
     // 6. Calculate confidence
     let confidence = calculate_confidence(vector_score, path_score, symbol_score);
@@ -857,6 +1092,7 @@ fn calculate_relevance_score_v2(
         base_score,
         recency_boost,
         size_penalty,
+        feedback_boost,
         confidence,
         weights,
     };
@@ -1005,6 +1241,11 @@ fn generate_selection_reason_v2(
         reasons.push("recently modified".to_string());
     }
 
+    // Implicit feedback
+    if details.feedback_boost > 1.05 {
+        reasons.push("often opened after similar searches".to_string());
+    }
+
     // Confidence
     if details.confidence > 0.8 {
         reasons.push("high confidence".to_string());
@@ -1253,3 +1494,71 @@ fn generate_selection_reason(
         reasons.join(", ")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::handlers::common::test_context;
+    use crate::indexer::domains::{ParsedApiCall, ParsedEndpoint};
+
+    #[tokio::test]
+    async fn test_cross_stack_links_wrapper_call_to_route() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let (ctx, _index) = test_context(root).await;
+
+        let web = ctx.sqlite.upsert_file("web/api.ts", 0, "a").await.unwrap();
+        let call = ParsedApiCall {
+            method: Some("GET".into()),
+            path: "/api/users/${id}".into(),
+            path_pattern: "/api/users/:param".into(),
+            line: 11,
+            client: Some("apiFetch".into()),
+            client_source: Some("./http".into()),
+            needs_client: true,
+            ..Default::default()
+        };
+        ctx.sqlite
+            .replace_frontend_api_calls(web, &[call])
+            .await
+            .unwrap();
+        let server = ctx
+            .sqlite
+            .upsert_file("src/users.rs", 0, "b")
+            .await
+            .unwrap();
+        let endpoint = ParsedEndpoint {
+            method: "GET".into(),
+            path: "/api/users/:id".into(),
+            handler: Some("show_user".into()),
+            request_type: None,
+            response_type: None,
+            line: 39,
+        };
+        ctx.sqlite
+            .replace_api_endpoints(server, SOURCE_CODE, &[endpoint])
+            .await
+            .unwrap();
+
+        // Search hits are "path:line ..." strings
+        let search_result = json!({
+            "results": ["web/api.ts:12 (ctx:loadUser)\nreturn apiFetch(`/api/users/${id}`);"]
+        });
+
+        // `apiFetch` is defined nowhere the indexer could see, so it is not an API call yet
+        assert!(cross_stack_links(&ctx, &search_result).await.is_empty());
+
+        std::fs::create_dir_all(root.join(".gofer")).unwrap();
+        std::fs::write(
+            root.join(".gofer/config.toml"),
+            "[api]\nwrappers = [\"apiFetch\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            cross_stack_links(&ctx, &search_result).await,
+            vec![json!(
+                "web/api.ts:12 GET /api/users/${id} -> src/users.rs:40 show_user (api)"
+            )]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::common::{make_relative, resolve_path, ToolContext};
+use super::common::{index_path, make_relative, ToolContext};
 use crate::error::GoferError;
 use crate::models::chunk::SymbolWithPath;
 use anyhow::Result;
@@ -81,7 +81,7 @@ pub async fn tool_get_symbols(args: Value, ctx: &ToolContext) -> Result<Value> {
         }
     }
 
-    let resolved_path = file_filter.map(|f| resolve_path(&ctx.root_path, f));
+    let resolved_path = file_filter.map(|f| index_path(&ctx.root_path, f));
     let file_filter_resolved = resolved_path.as_deref();
 
     let symbols = &ctx
@@ -232,11 +232,11 @@ pub async fn tool_symbol_exists(args: Value, ctx: &ToolContext) -> Result<Value>
     }
 
     let exists = if let Some(f) = file {
-        let abs_path = resolve_path(&ctx.root_path, f);
+        let rel_path = index_path(&ctx.root_path, f);
         // Check if symbol exists in specific file
         let symbols = ctx
             .sqlite
-            .get_symbols(Some(&abs_path), None, 0, 1000)
+            .get_symbols(Some(&rel_path), None, 0, 1000)
             .await?;
         symbols.iter().any(|s| s.name == symbol)
     } else {
@@ -260,9 +260,9 @@ pub async fn tool_is_exported(args: Value, ctx: &ToolContext) -> Result<Value> {
     }
 
     let matches = if let Some(f) = file {
-        let abs_path = resolve_path(&ctx.root_path, f);
+        let rel_path = index_path(&ctx.root_path, f);
         ctx.sqlite
-            .get_symbols(Some(&abs_path), None, 0, 1000)
+            .get_symbols(Some(&rel_path), None, 0, 1000)
             .await?
             .into_iter()
             .filter(|s| s.name == symbol)
@@ -303,9 +303,9 @@ pub async fn tool_has_documentation(args: Value, ctx: &ToolContext) -> Result<Va
     }
 
     let matches = if let Some(f) = file {
-        let abs_path = resolve_path(&ctx.root_path, f);
+        let rel_path = index_path(&ctx.root_path, f);
         ctx.sqlite
-            .get_symbols(Some(&abs_path), None, 0, 1000)
+            .get_symbols(Some(&rel_path), None, 0, 1000)
             .await?
             .into_iter()
             .filter(|s| s.name == symbol)
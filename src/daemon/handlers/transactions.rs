@@ -4,19 +4,22 @@
 //! Implements:
 //! - begin_transaction - начать транзакцию
 //! - add_operation - добавить операцию в транзакцию
-//! - commit_transaction - атомарно применить все операции
+//! - commit_transaction - атомарно применить все операции и проверить .gofer/rules.toml
 //! - rollback_transaction - откатить транзакцию
 //! - list_transactions - показать активные транзакции
 
 use super::common::{resolve_path_buf, ToolContext};
 use super::file_ops;
+use super::rules;
 use super::trash;
 use crate::error::GoferError;
+use crate::indexer::rules::Severity;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -92,6 +95,8 @@ pub struct Transaction {
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub snapshots: Vec<FileSnapshot>,
+    /// Root of the project the transaction was started in
+    pub project_root: PathBuf,
 }
 
 // Global transaction storage
@@ -101,7 +106,7 @@ lazy_static::lazy_static! {
 }
 
 /// Begin a new transaction
-pub async fn tool_begin_transaction(args: Value, _ctx: &ToolContext) -> Result<Value> {
+pub async fn tool_begin_transaction(args: Value, ctx: &ToolContext) -> Result<Value> {
     let transaction_id = args
         .get("transaction_id")
         .and_then(|v| v.as_str())
@@ -126,6 +131,7 @@ pub async fn tool_begin_transaction(args: Value, _ctx: &ToolContext) -> Result<V
         started_at: Utc::now(),
         completed_at: None,
         snapshots: Vec::new(),
+        project_root: ctx.root_path.to_path_buf(),
     };
 
     transactions.insert(transaction_id.clone(), transaction);
@@ -215,7 +221,29 @@ pub async fn tool_commit_transaction(args: Value, ctx: &ToolContext) -> Result<V
         return Err(GoferError::InvalidParams("Transaction has no operations".into()).into());
     }
 
-    // Step 1: Create snapshots of all affected files
+    // Step 1: Check the content the operations would write against
+    // .gofer/rules.toml, before anything touches the disk
+    let enforce_rules = args
+        .get("enforce_rules")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let staged = staged_contents(&transaction.operations, ctx).await;
+    let mut violations = Vec::new();
+    for (path, content) in &staged {
+        violations.extend(rules::check_content(ctx, path, content).await?);
+    }
+    let rule_violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+
+    if enforce_rules && violations.iter().any(|v| v.severity == Severity::Error) {
+        return Ok(json!({
+            "transaction_id": transaction_id,
+            "status": "rejected",
+            "rule_violations": rule_violations,
+            "action": "Nothing was written; the transaction stays active. Fix the operations or commit with enforce_rules=false",
+        }));
+    }
+
+    // Step 2: Create snapshots of all affected files
     let mut snapshots = Vec::new();
     for op_record in &transaction.operations {
         if let Some(snapshot) = create_snapshot(&op_record.operation, ctx).await? {
@@ -225,7 +253,7 @@ pub async fn tool_commit_transaction(args: Value, ctx: &ToolContext) -> Result<V
 
     transaction.snapshots = snapshots;
 
-    // Step 2: Apply all operations
+    // Step 3: Apply all operations
     let mut files_changed = Vec::new();
     let mut operations_applied = 0;
 
@@ -262,6 +290,8 @@ pub async fn tool_commit_transaction(args: Value, ctx: &ToolContext) -> Result<V
             }
         }
     }
+    files_changed.sort();
+    files_changed.dedup();
 
     // Success - clear snapshots
     transaction.snapshots.clear();
@@ -273,6 +303,7 @@ pub async fn tool_commit_transaction(args: Value, ctx: &ToolContext) -> Result<V
         "status": "committed",
         "operations_applied": operations_applied,
         "files_changed": files_changed,
+        "rule_violations": rule_violations,
         "committed_at": Utc::now().to_rfc3339(),
     }))
 }
@@ -336,6 +367,67 @@ pub async fn tool_list_transactions(_args: Value, _ctx: &ToolContext) -> Result<
     }))
 }
 
+/// Open transaction with its staged operations, as saved in a workspace state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTransaction {
+    pub transaction_id: String,
+    pub operations: Vec<Operation>,
+}
+
+/// Snapshot of the active transactions of one project (for `save_workspace_state`)
+pub async fn active_transactions(project_root: &Path) -> Vec<SavedTransaction> {
+    let transactions = TRANSACTIONS.read().await;
+    let mut active: Vec<SavedTransaction> = transactions
+        .values()
+        .filter(|tx| matches!(tx.status, TransactionStatus::Active))
+        .filter(|tx| tx.project_root == project_root)
+        .map(|tx| SavedTransaction {
+            transaction_id: tx.transaction_id.clone(),
+            operations: tx.operations.iter().map(|r| r.operation.clone()).collect(),
+        })
+        .collect();
+    active.sort_by(|a, b| a.transaction_id.cmp(&b.transaction_id));
+    active
+}
+
+/// Re-open a saved transaction of the project with its operations staged again.
+/// Returns false if a transaction with that id already exists in this session.
+pub async fn restore_transaction(saved: &SavedTransaction, project_root: &Path) -> bool {
+    let mut transactions = TRANSACTIONS.write().await;
+    if transactions.contains_key(&saved.transaction_id) {
+        return false;
+    }
+    let operations = saved
+        .operations
+        .iter()
+        .enumerate()
+        .map(|(i, op)| OperationRecord {
+            operation_id: format!("op_{:03}", i + 1),
+            operation: op.clone(),
+            status: "staged".to_string(),
+            validation_result: None,
+        })
+        .collect();
+    transactions.insert(
+        saved.transaction_id.clone(),
+        Transaction {
+            transaction_id: saved.transaction_id.clone(),
+            operations,
+            status: TransactionStatus::Active,
+            started_at: Utc::now(),
+            completed_at: None,
+            snapshots: Vec::new(),
+            project_root: project_root.to_path_buf(),
+        },
+    );
+    true
+}
+
+#[cfg(test)]
+pub(super) async fn forget_transaction(transaction_id: &str) {
+    TRANSACTIONS.write().await.remove(transaction_id);
+}
+
 // Helper functions
 
 fn parse_operation(data: &Value) -> Result<Operation> {
@@ -597,7 +689,97 @@ async fn apply_operation(operation: &Operation, ctx: &ToolContext) -> Result<Val
     }
 }
 
-fn extract_path_from_operation(operation: &Operation) -> Option<String> {
+/// Content of every file the operations leave behind, computed without
+/// writing: each operation sees the result of the ones before it.
+async fn staged_contents(
+    operations: &[OperationRecord],
+    ctx: &ToolContext,
+) -> BTreeMap<String, String> {
+    // path -> content, `None` once deleted or moved away
+    let mut files: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let mut written = BTreeSet::new();
+    for record in operations {
+        match &record.operation {
+            Operation::PatchFile {
+                path,
+                search_string,
+                replace_string,
+                occurrence,
+            } => {
+                let Some(content) = current_content(&mut files, path, ctx).await else {
+                    continue;
+                };
+                let patched = match occurrence {
+                    0 => content.replace(search_string.as_str(), replace_string),
+                    n => match content.match_indices(search_string.as_str()).nth(n - 1) {
+                        Some((at, _)) => format!(
+                            "{}{}{}",
+                            &content[..at],
+                            replace_string,
+                            &content[at + search_string.len()..]
+                        ),
+                        None => content,
+                    },
+                };
+                files.insert(path.clone(), Some(patched));
+                written.insert(path.clone());
+            }
+            Operation::WriteFile { path, content, .. } => {
+                files.insert(path.clone(), Some(content.clone()));
+                written.insert(path.clone());
+            }
+            Operation::AppendToFile {
+                path,
+                content,
+                newline_before,
+            } => {
+                let mut existing = current_content(&mut files, path, ctx)
+                    .await
+                    .unwrap_or_default();
+                if *newline_before && !existing.ends_with('\n') {
+                    existing.push('\n');
+                }
+                existing.push_str(content);
+                files.insert(path.clone(), Some(existing));
+                written.insert(path.clone());
+            }
+            Operation::DeleteSafe { path, .. } => {
+                files.insert(path.clone(), None);
+            }
+            Operation::MoveFile {
+                source,
+                destination,
+                ..
+            } => {
+                let content = current_content(&mut files, source, ctx).await;
+                files.insert(source.clone(), None);
+                files.insert(destination.clone(), content);
+                written.insert(destination.clone());
+            }
+            Operation::CreateDirectory { .. } => {}
+        }
+    }
+    files
+        .into_iter()
+        .filter(|(path, _)| written.contains(path))
+        .filter_map(|(path, content)| Some((path, content?)))
+        .collect()
+}
+
+async fn current_content(
+    files: &mut BTreeMap<String, Option<String>>,
+    path: &str,
+    ctx: &ToolContext,
+) -> Option<String> {
+    if let Some(content) = files.get(path) {
+        return content.clone();
+    }
+    tokio::fs::read_to_string(resolve_path_buf(&ctx.root_path, path))
+        .await
+        .ok()
+}
+
+pub fn extract_path_from_operation(operation: &Operation) -> Option<String> {
     match operation {
         Operation::PatchFile { path, .. }
         | Operation::WriteFile { path, .. }
@@ -620,3 +802,153 @@ fn format_duration(duration: chrono::Duration) -> String {
         format!("{}d ago", seconds / 86400)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::handlers::common::test_context;
+
+    const RULES: &str = r#"
+[[banned]]
+name = "no-dbg"
+pattern = 'dbg!'
+"#;
+
+    async fn staged(ctx: &ToolContext, id: &str, operations: Vec<Value>) {
+        tool_begin_transaction(json!({ "transaction_id": id }), ctx)
+            .await
+            .unwrap();
+        for operation in operations {
+            tool_add_operation(json!({ "transaction_id": id, "operation": operation }), ctx)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_rejects_rule_violations_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".gofer")).unwrap();
+        std::fs::write(dir.path().join(".gofer/rules.toml"), RULES).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+
+        staged(
+            &ctx,
+            "tx_rules_reject",
+            vec![
+                json!({ "type": "write_file", "params": { "path": "new.rs", "content": "fn b() {}\n" } }),
+                json!({ "type": "patch_file", "params": {
+                    "path": "lib.rs",
+                    "search_string": "{}",
+                    "replace_string": "{ dbg!(1); }"
+                } }),
+            ],
+        )
+        .await;
+        let result = tool_commit_transaction(json!({ "transaction_id": "tx_rules_reject" }), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "rejected");
+        assert_eq!(
+            result["rule_violations"],
+            json!(["lib.rs:1 [error] no-dbg: `dbg!` is banned"])
+        );
+        // Nothing reached the disk, so the watcher had nothing to index
+        assert!(!dir.path().join("new.rs").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+            "fn a() {}\n"
+        );
+
+        // Still active: it can be committed with the rules waived
+        let result = tool_commit_transaction(
+            json!({ "transaction_id": "tx_rules_reject", "enforce_rules": false }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["status"], "committed");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+            "fn a() { dbg!(1); }\n"
+        );
+        forget_transaction("tx_rules_reject").await;
+    }
+
+    #[tokio::test]
+    async fn test_commit_fails_on_invalid_rules_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".gofer")).unwrap();
+        std::fs::write(
+            dir.path().join(".gofer/rules.toml"),
+            "[[banned]]\npattern = '('\n",
+        )
+        .unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+
+        staged(
+            &ctx,
+            "tx_rules_invalid",
+            vec![json!({ "type": "write_file", "params": { "path": "new.rs", "content": "fn b() {}\n" } })],
+        )
+        .await;
+        let err = tool_commit_transaction(json!({ "transaction_id": "tx_rules_invalid" }), &ctx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("rules.toml"));
+        assert!(!dir.path().join("new.rs").exists());
+        forget_transaction("tx_rules_invalid").await;
+    }
+
+    #[tokio::test]
+    async fn test_staged_contents_chain_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "x x x\n").unwrap();
+        std::fs::write(dir.path().join("old.rs"), "moved\n").unwrap();
+        let (ctx, _index) = test_context(dir.path()).await;
+
+        let record = |operation| OperationRecord {
+            operation_id: String::new(),
+            operation,
+            status: "staged".to_string(),
+            validation_result: None,
+        };
+        let operations = vec![
+            record(Operation::PatchFile {
+                path: "a.rs".to_string(),
+                search_string: "x".to_string(),
+                replace_string: "y".to_string(),
+                occurrence: 2,
+            }),
+            record(Operation::AppendToFile {
+                path: "a.rs".to_string(),
+                content: "z".to_string(),
+                newline_before: true,
+            }),
+            record(Operation::MoveFile {
+                source: "old.rs".to_string(),
+                destination: "new.rs".to_string(),
+                overwrite: false,
+            }),
+            record(Operation::WriteFile {
+                path: "gone.rs".to_string(),
+                content: "tmp".to_string(),
+                create_dirs: false,
+            }),
+            record(Operation::DeleteSafe {
+                path: "gone.rs".to_string(),
+                reason: None,
+                tags: Vec::new(),
+            }),
+        ];
+        let staged = staged_contents(&operations, &ctx).await;
+        assert_eq!(
+            staged.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("a.rs".to_string(), "x y x\nz".to_string()),
+                ("new.rs".to_string(), "moved\n".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,519 @@
+//! Workspace state & TODO notes (docs/features/21-workspace-state.md)
+//!
+//! Implements:
+//! - save_workspace_state / load_workspace_state / list_workspace_states / delete_workspace_state
+//! - add_todo_note / list_todo_notes / resolve_todo_note
+//!
+//! States live in the project SQLite DB so a new agent session can pick up the plan,
+//! touched files and open transactions of one that died. Notes are anchored to a file
+//! and, where possible, a symbol; on every read the anchor is re-resolved through the
+//! symbol index by name, so notes follow their code through edits and moves.
+
+use super::common::{index_path, ToolContext};
+use super::transactions::{self, SavedTransaction};
+use crate::error::GoferError;
+use crate::models::SymbolKind;
+use crate::storage::{TodoFilter, TodoNoteRow, WorkspaceStateRow};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+const PRIORITIES: &[&str] = &["low", "medium", "high"];
+
+/// Save (or overwrite) a workspace state
+pub async fn tool_save_workspace_state(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let state_id = required_str(&args, "state_id")?;
+    let task = args.get("task").and_then(|v| v.as_str());
+    let metadata = args.get("metadata").cloned().unwrap_or_else(|| json!({}));
+    if !metadata.is_object() {
+        return Err(GoferError::InvalidParams("metadata must be an object".into()).into());
+    }
+    let include_transactions = args
+        .get("include_transactions")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let mut touched_files: Vec<String> = args
+        .get("touched_files")
+        .and_then(|v| v.as_array())
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|f| f.as_str())
+                .map(|f| index_path(&ctx.root_path, f))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Open transactions are saved with their staged operations; their files count as touched
+    let saved = if include_transactions {
+        transactions::active_transactions(&ctx.root_path).await
+    } else {
+        Vec::new()
+    };
+    for op in saved.iter().flat_map(|tx| &tx.operations) {
+        if let Some(path) = transactions::extract_path_from_operation(op) {
+            touched_files.push(index_path(&ctx.root_path, &path));
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    touched_files.retain(|f| seen.insert(f.clone()));
+
+    let metadata_json = serde_json::to_string(&metadata)?;
+    let touched_json = serde_json::to_string(&touched_files)?;
+    let transactions_json = serde_json::to_string(&saved)?;
+    let now = Utc::now().timestamp();
+
+    ctx.sqlite
+        .save_workspace_state(
+            state_id,
+            task,
+            &metadata_json,
+            &touched_json,
+            &transactions_json,
+            now,
+        )
+        .await?;
+
+    Ok(json!({
+        "state_id": state_id,
+        "saved_at": format_time(now),
+        "touched_files": touched_files,
+        "transactions": saved.iter().map(|tx| &tx.transaction_id).collect::<Vec<_>>(),
+        "size_bytes": metadata_json.len() + touched_json.len() + transactions_json.len(),
+    }))
+}
+
+/// Load a workspace state, re-open its transactions and list its open notes
+pub async fn tool_load_workspace_state(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let state_id = required_str(&args, "state_id")?;
+    let restore = args
+        .get("restore_transactions")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let state = ctx
+        .sqlite
+        .get_workspace_state(state_id)
+        .await?
+        .ok_or_else(|| {
+            GoferError::InvalidParams(format!("Workspace state not found: {}", state_id))
+        })?;
+
+    let saved: Vec<SavedTransaction> =
+        serde_json::from_str(&state.transactions).unwrap_or_default();
+    let mut transactions_out = Vec::new();
+    for tx in &saved {
+        let restored = restore && transactions::restore_transaction(tx, &ctx.root_path).await;
+        transactions_out.push(json!({
+            "transaction_id": tx.transaction_id,
+            "operations": tx.operations.len(),
+            "restored": restored,
+        }));
+    }
+
+    let filter = TodoFilter {
+        state_id: Some(state_id.to_string()),
+        ..Default::default()
+    };
+    let notes = ctx.sqlite.list_todo_notes(&filter).await?;
+    let mut todos = Vec::new();
+    for note in notes {
+        todos.push(reanchor(note, ctx).await);
+    }
+
+    let mut out = state_json(&state);
+    out["transactions"] = json!(transactions_out);
+    out["todos"] = json!(todos);
+    Ok(out)
+}
+
+/// List saved workspace states, most recent first
+pub async fn tool_list_workspace_states(_args: Value, ctx: &ToolContext) -> Result<Value> {
+    let states = ctx.sqlite.list_workspace_states().await?;
+    let list: Vec<Value> = states
+        .iter()
+        .map(|state| {
+            let metadata: Value = serde_json::from_str(&state.metadata).unwrap_or_default();
+            let touched: Vec<String> =
+                serde_json::from_str(&state.touched_files).unwrap_or_default();
+            let saved: Vec<SavedTransaction> =
+                serde_json::from_str(&state.transactions).unwrap_or_default();
+            json!({
+                "state_id": state.state_id,
+                "task": state.task,
+                "progress": metadata.get("progress"),
+                "touched_files": touched.len(),
+                "open_transactions": saved.len(),
+                "updated_at": format_time(state.updated_at),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "states": list,
+        "total": list.len(),
+    }))
+}
+
+/// Delete a workspace state (its notes stay, detached)
+pub async fn tool_delete_workspace_state(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let state_id = required_str(&args, "state_id")?;
+    if !ctx.sqlite.delete_workspace_state(state_id).await? {
+        return Err(
+            GoferError::InvalidParams(format!("Workspace state not found: {}", state_id)).into(),
+        );
+    }
+    Ok(json!({
+        "state_id": state_id,
+        "status": "deleted",
+    }))
+}
+
+/// Attach a TODO note to a file, a symbol, or the symbol enclosing a line
+pub async fn tool_add_todo_note(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let file = required_str(&args, "file")?;
+    let text = required_str(&args, "text")?;
+    let priority = args
+        .get("priority")
+        .and_then(|v| v.as_str())
+        .unwrap_or("medium");
+    if !PRIORITIES.contains(&priority) {
+        return Err(GoferError::InvalidParams(format!(
+            "priority must be one of {}",
+            PRIORITIES.join(", ")
+        ))
+        .into());
+    }
+    let state_id = args.get("state_id").and_then(|v| v.as_str());
+    let symbol = args.get("symbol").and_then(|v| v.as_str());
+    let line = args.get("line").and_then(|v| v.as_i64());
+
+    let path = index_path(&ctx.root_path, file);
+    if !ctx.root_path.join(&path).exists() {
+        return Err(GoferError::InvalidParams(format!("File not found: {}", file)).into());
+    }
+
+    // Anchor to a symbol: the named one, or the innermost one around `line`
+    let (symbol_name, line) = match (symbol, line) {
+        (Some(name), _) => {
+            let sym = ctx
+                .sqlite
+                .find_symbol_by_name_and_file(name, &path)
+                .await?
+                .ok_or_else(|| {
+                    GoferError::InvalidParams(format!("Symbol '{}' not found in {}", name, file))
+                })?;
+            let line = line.unwrap_or(sym.line_start as i64 + 1);
+            (Some(name.to_string()), Some(line))
+        }
+        (None, Some(line)) => {
+            let enclosing = ctx
+                .sqlite
+                .find_symbol_at_line(&path, (line - 1) as i32)
+                .await?
+                .filter(|s| s.kind != SymbolKind::LocalVar)
+                .map(|s| s.name);
+            (enclosing, Some(line))
+        }
+        (None, None) => (None, None),
+    };
+
+    let anchor_text = match line {
+        Some(line) => read_line(ctx, &path, line).await,
+        None => None,
+    };
+    let now = Utc::now().timestamp();
+    let id = ctx
+        .sqlite
+        .add_todo_note(
+            &path,
+            symbol_name.as_deref(),
+            line,
+            anchor_text.as_deref(),
+            text,
+            priority,
+            state_id,
+            now,
+        )
+        .await?;
+
+    Ok(json!({
+        "todo_id": id,
+        "file": path,
+        "symbol": symbol_name,
+        "line": line,
+        "text": text,
+        "priority": priority,
+        "state_id": state_id,
+    }))
+}
+
+/// List TODO notes with anchors re-resolved against the current code
+pub async fn tool_list_todo_notes(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let filter = TodoFilter {
+        file_path: args
+            .get("file")
+            .and_then(|v| v.as_str())
+            .map(|f| index_path(&ctx.root_path, f)),
+        priority: args
+            .get("priority")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        state_id: args
+            .get("state_id")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        include_resolved: args
+            .get("include_resolved")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
+    let notes = ctx.sqlite.list_todo_notes(&filter).await?;
+    let mut todos = Vec::new();
+    for note in notes {
+        todos.push(reanchor(note, ctx).await);
+    }
+
+    Ok(json!({
+        "total": todos.len(),
+        "todos": todos,
+    }))
+}
+
+/// Mark a TODO note as done
+pub async fn tool_resolve_todo_note(args: Value, ctx: &ToolContext) -> Result<Value> {
+    let id = args
+        .get("todo_id")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(|| GoferError::InvalidParams("todo_id is required".into()))?;
+    let now = Utc::now().timestamp();
+    if !ctx.sqlite.resolve_todo_note(id, now).await? {
+        return Err(GoferError::InvalidParams(format!(
+            "TODO note {} not found or already resolved",
+            id
+        ))
+        .into());
+    }
+    Ok(json!({
+        "todo_id": id,
+        "status": "resolved",
+        "resolved_at": format_time(now),
+    }))
+}
+
+// Helper functions
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| GoferError::InvalidParams(format!("{} is required", key)).into())
+}
+
+fn format_time(ts: i64) -> String {
+    DateTime::<Utc>::from_timestamp(ts, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default()
+}
+
+fn state_json(state: &WorkspaceStateRow) -> Value {
+    json!({
+        "state_id": state.state_id,
+        "task": state.task,
+        "metadata": serde_json::from_str::<Value>(&state.metadata).unwrap_or_default(),
+        "touched_files": serde_json::from_str::<Value>(&state.touched_files).unwrap_or_default(),
+        "created_at": format_time(state.created_at),
+        "saved_at": format_time(state.updated_at),
+    })
+}
+
+async fn read_line(ctx: &ToolContext, path: &str, line: i64) -> Option<String> {
+    let content = tokio::fs::read_to_string(ctx.root_path.join(path))
+        .await
+        .ok()?;
+    content
+        .lines()
+        .nth((line - 1).max(0) as usize)
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+}
+
+/// Re-resolve a note's anchor and persist it if it moved.
+///
+/// The symbol is looked up by name, first in the note's file and then project-wide
+/// (a unique match means the code moved to another file). Inside the symbol, or the
+/// whole file for notes without one, the line with the saved anchor text closest to
+/// the old position wins; a symbol whose anchor line is gone falls back to its start.
+async fn reanchor(note: TodoNoteRow, ctx: &ToolContext) -> Value {
+    let mut file = note.file_path.clone();
+    let mut window: Option<(i64, i64)> = None;
+
+    if let Some(name) = &note.symbol_name {
+        match ctx.sqlite.find_symbol_by_name_and_file(name, &file).await {
+            Ok(Some(sym)) => window = Some((sym.line_start as i64 + 1, sym.line_end as i64 + 1)),
+            _ => {
+                if let Some((moved_to, start, end)) = find_moved_symbol(name, ctx).await {
+                    file = moved_to;
+                    window = Some((start, end));
+                }
+            }
+        }
+    }
+
+    let content = tokio::fs::read_to_string(ctx.root_path.join(&file))
+        .await
+        .unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+    let near = note.line.or(window.map(|w| w.0)).unwrap_or(1);
+    let (lo, hi) = window.unwrap_or((1, lines.len() as i64));
+
+    let by_text = note.anchor_text.as_deref().and_then(|anchor| {
+        (lo..=hi)
+            .filter(|&n| {
+                lines
+                    .get((n - 1).max(0) as usize)
+                    .is_some_and(|l| l.trim() == anchor)
+            })
+            .min_by_key(|&n| (n - near).abs())
+    });
+
+    let (line, anchor) = match (by_text, window) {
+        (Some(n), _) => (note.line.map(|_| n), "ok"),
+        (None, Some((start, _))) => (note.line.map(|_| start), "ok"),
+        (None, None) if note.line.is_none() && !lines.is_empty() => (None, "ok"),
+        (None, None) => (note.line, "lost"),
+    };
+    let anchor = if anchor == "ok" && (file != note.file_path || line != note.line) {
+        "moved"
+    } else {
+        anchor
+    };
+
+    if anchor == "moved" {
+        let text = match line {
+            Some(n) => read_line(ctx, &file, n).await,
+            None => None,
+        };
+        let text = text.or(note.anchor_text.clone());
+        if let Err(e) = ctx
+            .sqlite
+            .update_todo_anchor(note.id, &file, line, text.as_deref())
+            .await
+        {
+            tracing::debug!("Failed to update anchor of TODO {}: {}", note.id, e);
+        }
+    }
+
+    json!({
+        "todo_id": note.id,
+        "file": file,
+        "symbol": note.symbol_name,
+        "line": line,
+        "text": note.text,
+        "priority": note.priority,
+        "state_id": note.state_id,
+        "created_at": format_time(note.created_at),
+        "resolved_at": note.resolved_at.map(format_time),
+        "anchor": anchor,
+    })
+}
+
+/// The file and 1-based range of `name` if exactly one file defines it.
+async fn find_moved_symbol(name: &str, ctx: &ToolContext) -> Option<(String, i64, i64)> {
+    let symbols = ctx.sqlite.get_symbol_by_name(name).await.ok()?;
+    let mut found: Option<(i64, i64, i64)> = None;
+    for sym in symbols.iter().filter(|s| s.kind != SymbolKind::LocalVar) {
+        match found {
+            Some((file_id, _, _)) if file_id != sym.file_id => return None,
+            Some(_) => {}
+            None => {
+                found = Some((
+                    sym.file_id,
+                    sym.line_start as i64 + 1,
+                    sym.line_end as i64 + 1,
+                ))
+            }
+        }
+    }
+    let (file_id, start, end) = found?;
+    let file = ctx.sqlite.get_file_by_id(file_id).await.ok()??;
+    Some((file.path, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::handlers::transactions::Operation;
+
+    fn write_op(path: &str) -> Operation {
+        Operation::WriteFile {
+            path: path.to_string(),
+            content: "fn main() {}\n".to_string(),
+            create_dirs: false,
+        }
+    }
+
+    fn saved(id: &str, path: &str) -> SavedTransaction {
+        SavedTransaction {
+            transaction_id: id.to_string(),
+            operations: vec![write_op(path)],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transactions_round_trip_through_saved_state() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(
+            transactions::restore_transaction(&saved("tx_ws_round", "src/a.rs"), root.path()).await
+        );
+
+        // What save_workspace_state stores, and load_workspace_state reads back
+        let stored =
+            serde_json::to_string(&transactions::active_transactions(root.path()).await).unwrap();
+        transactions::forget_transaction("tx_ws_round").await;
+        assert!(transactions::active_transactions(root.path())
+            .await
+            .is_empty());
+
+        let loaded: Vec<SavedTransaction> = serde_json::from_str(&stored).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(transactions::restore_transaction(&loaded[0], root.path()).await);
+        assert!(!transactions::restore_transaction(&loaded[0], root.path()).await);
+
+        let active = transactions::active_transactions(root.path()).await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].transaction_id, "tx_ws_round");
+        assert_eq!(
+            transactions::extract_path_from_operation(&active[0].operations[0]).as_deref(),
+            Some("src/a.rs")
+        );
+        transactions::forget_transaction("tx_ws_round").await;
+    }
+
+    #[tokio::test]
+    async fn test_other_project_transactions_not_captured() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        transactions::restore_transaction(&saved("tx_ws_a", "src/a.rs"), a.path()).await;
+        transactions::restore_transaction(&saved("tx_ws_b", "src/b.rs"), b.path()).await;
+
+        let ids = |txs: Vec<SavedTransaction>| {
+            txs.into_iter()
+                .map(|tx| tx.transaction_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(transactions::active_transactions(a.path()).await),
+            vec!["tx_ws_a"]
+        );
+        assert_eq!(
+            ids(transactions::active_transactions(b.path()).await),
+            vec!["tx_ws_b"]
+        );
+
+        transactions::forget_transaction("tx_ws_a").await;
+        transactions::forget_transaction("tx_ws_b").await;
+    }
+}
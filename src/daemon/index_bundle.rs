@@ -0,0 +1,389 @@
+//! Index bundles — portable `.tar.gz` snapshots of a project index.
+//!
+//! A bundle contains the SQLite graph, the LanceDB table and a `manifest.json`
+//! describing the embedding model, schema version and git HEAD the index was built
+//! against. Paths inside the index are relative to the project root, so a bundle can
+//! be imported anywhere; importing verifies compatibility, relativizes bundles from
+//! older versions and leaves the incremental sync to the caller.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::daemon::state::relativize_index_paths;
+use crate::indexer::git::GitRepo;
+use crate::storage::{LanceStorage, SqliteStorage};
+
+/// Bump when the archive layout changes.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const GRAPH_DB: &str = "graph.db";
+const LANCE_DIR: &str = "lancedb";
+
+/// Everything in the index directory that an import replaces
+const INDEX_FILES: [&str; 4] = [GRAPH_DB, "graph.db-wal", "graph.db-shm", LANCE_DIR];
+
+/// Metadata stored alongside the index data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub gofer_version: String,
+    pub created_at: i64,
+    /// Project root at export time (bundles from older versions store paths under it)
+    pub source_root: String,
+    /// Embedding model key (`model:dimension`), must match on import
+    pub embedding_model: String,
+    pub schema_version: i64,
+    pub git_head: Option<String>,
+}
+
+/// Outcome of a successful import.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub manifest: BundleManifest,
+    /// HEAD of the target checkout (differs from `manifest.git_head` if the trees diverged)
+    pub current_git_head: Option<String>,
+    /// Legacy absolute paths made relative (bundles from older versions)
+    pub sqlite_paths_relativized: u64,
+    pub lance_paths_relativized: u64,
+}
+
+/// Write a bundle for the index in `index_dir` to `output`.
+///
+/// The caller must hold the project's Lance lock so the table isn't modified mid-copy.
+pub async fn export_index(
+    sqlite: &SqliteStorage,
+    index_dir: &Path,
+    root: &Path,
+    embedding_model: &str,
+    output: &Path,
+) -> Result<BundleManifest> {
+    let git_head = GitRepo::open(root).and_then(|r| r.head_commit());
+    let schema_version = sqlite.schema_version().await?;
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        gofer_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+        source_root: root.to_string_lossy().to_string(),
+        embedding_model: embedding_model.to_string(),
+        schema_version,
+        git_head,
+    };
+
+    // VACUUM INTO gives a consistent copy without stopping writers
+    let staging = tempfile::tempdir_in(index_dir)?;
+    let db_copy = staging.path().join(GRAPH_DB);
+    sqlite.snapshot_to(&db_copy).await?;
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let index_dir = index_dir.to_path_buf();
+    let output = output.to_path_buf();
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::create(&output)
+            .with_context(|| format!("Cannot create bundle {}", output.display()))?;
+        let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+        // Manifest first so readers can inspect it without unpacking the rest
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        tar.append_data(&mut header, MANIFEST_FILE, manifest_json.as_slice())?;
+
+        tar.append_path_with_name(&db_copy, GRAPH_DB)?;
+
+        let lance_dir = index_dir.join(LANCE_DIR);
+        if lance_dir.exists() {
+            tar.append_dir_all(LANCE_DIR, &lance_dir)?;
+        }
+
+        tar.into_inner()?.finish()?;
+        drop(staging);
+        Ok(())
+    })
+    .await??;
+
+    Ok(manifest)
+}
+
+/// Read only the manifest from a bundle.
+pub fn read_manifest(bundle: &Path) -> Result<BundleManifest> {
+    let file = File::open(bundle).with_context(|| format!("Cannot open {}", bundle.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if &*entry.path()? == Path::new(MANIFEST_FILE) {
+            return Ok(serde_json::from_reader(entry)?);
+        }
+    }
+    bail!("{} has no {}", bundle.display(), MANIFEST_FILE)
+}
+
+/// Check that a bundle can be loaded by this binary with the active embedding model.
+pub fn check_compatibility(manifest: &BundleManifest, embedding_model: &str) -> Result<()> {
+    if manifest.format_version != BUNDLE_FORMAT_VERSION {
+        bail!(
+            "Unsupported bundle format {} (expected {})",
+            manifest.format_version,
+            BUNDLE_FORMAT_VERSION
+        );
+    }
+    if manifest.embedding_model != embedding_model {
+        bail!(
+            "Embedding model mismatch: bundle was built with {}, daemon uses {}",
+            manifest.embedding_model,
+            embedding_model
+        );
+    }
+    let latest = SqliteStorage::latest_schema_version();
+    if manifest.schema_version > latest {
+        bail!(
+            "Bundle schema version {} is newer than supported ({}); upgrade gofer",
+            manifest.schema_version,
+            latest
+        );
+    }
+    Ok(())
+}
+
+/// Replace the index in `index_dir` with the contents of `bundle` for the checkout at `new_root`.
+///
+/// The project must not be loaded while this runs.
+pub async fn import_index(
+    bundle: &Path,
+    index_dir: &Path,
+    new_root: &Path,
+    embedding_model: &str,
+    vector_dim: usize,
+) -> Result<ImportReport> {
+    let manifest = read_manifest(bundle)?;
+    check_compatibility(&manifest, embedding_model)?;
+
+    tokio::fs::create_dir_all(index_dir).await?;
+    let staging = tempfile::tempdir_in(index_dir)?;
+    let staging_path = staging.path().to_path_buf();
+    let bundle_owned = bundle.to_path_buf();
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let file = File::open(&bundle_owned)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        archive.unpack(&staging_path)?;
+        Ok(())
+    })
+    .await??;
+
+    if !staging.path().join(GRAPH_DB).exists() {
+        bail!("Bundle is missing {}", GRAPH_DB);
+    }
+
+    // Move the live index aside so any failure below can put it back
+    let previous = tempfile::tempdir_in(index_dir)?;
+    if let Err(e) = move_index(index_dir, previous.path()).await {
+        move_index(previous.path(), index_dir).await?;
+        return Err(e);
+    }
+    let installed = install(
+        staging.path(),
+        index_dir,
+        &manifest.source_root,
+        embedding_model,
+        vector_dim,
+    )
+    .await;
+    drop(staging);
+    let (sqlite_paths_relativized, lance_paths_relativized) = match installed {
+        Ok(rebased) => rebased,
+        Err(e) => {
+            // Keep the old index on disk if it can't be moved back
+            let previous = previous.keep();
+            remove_index(index_dir).await?;
+            move_index(&previous, index_dir).await?;
+            tokio::fs::remove_dir(&previous).await?;
+            return Err(e);
+        }
+    };
+
+    let report = ImportReport {
+        manifest,
+        current_git_head: GitRepo::open(new_root).and_then(|r| r.head_commit()),
+        sqlite_paths_relativized,
+        lance_paths_relativized,
+    };
+
+    tracing::info!(
+        "Imported index bundle {:?} into {:?}: {} sqlite / {} lance legacy paths relativized",
+        bundle,
+        new_root,
+        report.sqlite_paths_relativized,
+        report.lance_paths_relativized
+    );
+
+    Ok(report)
+}
+
+/// Move the staged index into the empty `index_dir` and open it; returns the
+/// sqlite and lance paths relativized.
+async fn install(
+    staging: &Path,
+    index_dir: &Path,
+    source_root: &str,
+    embedding_model: &str,
+    vector_dim: usize,
+) -> Result<(u64, u64)> {
+    move_index(staging, index_dir).await?;
+
+    let db_path = index_dir.join(GRAPH_DB);
+    let sqlite = SqliteStorage::new(&db_path.to_string_lossy()).await?;
+    sqlite.migrate().await?;
+    sqlite
+        .set_index_meta("embedding_cache_version", embedding_model)
+        .await?;
+
+    // Bundles written before relative path storage hold absolute paths under source_root
+    let lance = LanceStorage::new(&index_dir.join(LANCE_DIR).to_string_lossy(), vector_dim).await?;
+    let rebased = relativize_index_paths(&sqlite, &lance, source_root).await?;
+    Ok(rebased)
+}
+
+/// Move the index files present in `from` to `to`
+async fn move_index(from: &Path, to: &Path) -> Result<()> {
+    for name in INDEX_FILES {
+        let source: PathBuf = from.join(name);
+        if source.exists() {
+            tokio::fs::rename(&source, to.join(name)).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn remove_index(dir: &Path) -> Result<()> {
+    for name in INDEX_FILES {
+        let path = dir.join(name);
+        if path.is_dir() {
+            tokio::fs::remove_dir_all(&path).await?;
+        } else if path.exists() {
+            tokio::fs::remove_file(&path).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = "test-model:384";
+
+    async fn index_with(dir: &Path, file: &str) -> SqliteStorage {
+        std::fs::create_dir_all(dir).unwrap();
+        let sqlite = SqliteStorage::new(dir.join(GRAPH_DB).to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.migrate().await.unwrap();
+        sqlite.upsert_file(file, 1, "h").await.unwrap();
+        LanceStorage::new(dir.join(LANCE_DIR).to_str().unwrap(), 384)
+            .await
+            .unwrap();
+        sqlite
+    }
+
+    async fn indexed_files(dir: &Path) -> Vec<String> {
+        let sqlite = SqliteStorage::new(dir.join(GRAPH_DB).to_str().unwrap())
+            .await
+            .unwrap();
+        let mut files: Vec<String> = sqlite.get_file_stats().await.unwrap().into_keys().collect();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("project");
+        std::fs::create_dir_all(&root).unwrap();
+        let source = temp.path().join("source");
+        let sqlite = index_with(&source, "src/lib.rs").await;
+
+        let bundle = temp.path().join("out/index.tar.gz");
+        let manifest = export_index(&sqlite, &source, &root, MODEL, &bundle)
+            .await
+            .unwrap();
+        assert_eq!(read_manifest(&bundle).unwrap().embedding_model, MODEL);
+        // Exporting leaves the live index untouched
+        assert_eq!(sqlite.get_index_meta("git_head").await.unwrap(), None);
+
+        let target = temp.path().join("target");
+        drop(index_with(&target, "old.rs").await);
+        let report = import_index(&bundle, &target, &root, MODEL, 384)
+            .await
+            .unwrap();
+        assert_eq!(report.manifest.schema_version, manifest.schema_version);
+        assert_eq!(indexed_files(&target).await, vec!["src/lib.rs".to_string()]);
+        assert!(target.join(LANCE_DIR).exists());
+
+        // Only the index itself is left in the directory
+        let entries: Vec<_> = std::fs::read_dir(&target)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir() && e.file_name() != LANCE_DIR)
+            .collect();
+        assert!(entries.is_empty());
+
+        assert!(import_index(&bundle, &target, &root, "other:768", 768)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_restores_index() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("project");
+        let target = temp.path().join("target");
+        drop(index_with(&target, "old.rs").await);
+
+        // A bundle whose graph.db is not a database fails after the swap
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            gofer_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: 0,
+            source_root: root.to_string_lossy().to_string(),
+            embedding_model: MODEL.to_string(),
+            schema_version: 1,
+            git_head: None,
+        };
+        let bundle = temp.path().join("broken.tar.gz");
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            File::create(&bundle).unwrap(),
+            Compression::default(),
+        ));
+        for (name, data) in [
+            (MANIFEST_FILE, serde_json::to_vec(&manifest).unwrap()),
+            (GRAPH_DB, b"not a database".repeat(100)),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, data.as_slice()).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+
+        assert!(import_index(&bundle, &target, &root, MODEL, 384)
+            .await
+            .is_err());
+        assert_eq!(indexed_files(&target).await, vec!["old.rs".to_string()]);
+        assert!(target.join(LANCE_DIR).exists());
+    }
+}
@@ -1,5 +1,8 @@
 pub mod handlers;
+pub mod index_bundle;
 pub mod metrics_http;
+pub mod prompts;
 pub mod registry;
+pub mod resources;
 pub mod state;
 pub mod tools;
@@ -0,0 +1,416 @@
+//! Project prompt library — MCP prompts defined in `.gofer/prompts/*.toml`.
+//!
+//! One prompt per file; the name defaults to the file stem:
+//!
+//! ```toml
+//! description = "Team review checklist"
+//!
+//! [[arguments]]
+//! name = "file"
+//! description = "File to review"
+//! required = true
+//!
+//! [template]
+//! text = """
+//! Review `{{file}}` against our rules:
+//! {{rules}}
+//!
+//! {{skeleton:{{file}}}}
+//! """
+//! ```
+//!
+//! `{{arg}}` is replaced by the argument, and gofer data placeholders written in
+//! the template are filled in: `{{skeleton:path}}`, `{{errors:path}}`,
+//! `{{rules}}` and `{{golden_samples}}`. Argument values are never expanded. Files are re-read on every request, and a watcher on
+//! `.gofer/` sends `notifications/prompts/list_changed` when they change.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::Result;
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use super::tools::{self, ToolContext};
+
+static ARG_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{(\w+)\}\}").expect("valid regex"));
+static DATA_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{(skeleton|errors):((?:[^{}]|\{\{\w+\}\})+)\}\}|\{\{(rules|golden_samples)\}\}")
+        .expect("valid regex")
+});
+
+/// A prompt loaded from `.gofer/prompts/<name>.toml`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectPrompt {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+    pub template: PromptTemplate,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptTemplate {
+    #[serde(default = "default_role")]
+    pub role: String,
+    pub text: String,
+}
+
+fn default_role() -> String {
+    "user".to_string()
+}
+
+fn prompts_dir(root: &Path) -> PathBuf {
+    root.join(".gofer").join("prompts")
+}
+
+/// Load all project prompts, sorted by name. Invalid files are skipped with a warning.
+pub fn load_prompts(root: &Path) -> Vec<ProjectPrompt> {
+    let Ok(entries) = std::fs::read_dir(prompts_dir(root)) else {
+        return Vec::new();
+    };
+    let mut prompts: Vec<ProjectPrompt> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            match toml::from_str::<ProjectPrompt>(&content) {
+                Ok(mut prompt) => {
+                    if prompt.name.is_empty() {
+                        prompt.name = path.file_stem()?.to_string_lossy().to_string();
+                    }
+                    Some(prompt)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to parse prompt {:?}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts
+}
+
+impl ProjectPrompt {
+    /// Entry for `prompts/list`.
+    pub fn to_list_entry(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "arguments": self.arguments.iter().map(|a| json!({
+                "name": a.name,
+                "description": a.description,
+                "required": a.required,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Render the prompt into MCP messages.
+    pub async fn render(&self, args: &Value, ctx: &ToolContext) -> Result<Vec<Value>> {
+        for arg in self.arguments.iter().filter(|a| a.required) {
+            let present = args
+                .get(&arg.name)
+                .and_then(|v| v.as_str())
+                .is_some_and(|v| !v.is_empty());
+            if !present {
+                return Err(anyhow::anyhow!("'{}' argument is required", arg.name));
+            }
+        }
+
+        let text = fill_data(parse_template(&self.template.text, args), ctx).await;
+        Ok(vec![json!({
+            "role": self.template.role,
+            "content": { "type": "text", "text": text }
+        })])
+    }
+}
+
+/// Replace `{{arg}}` with argument values; unknown names are left alone so data
+/// placeholders such as `{{rules}}` survive.
+fn substitute_args(text: &str, args: &Value) -> String {
+    ARG_PLACEHOLDER
+        .replace_all(text, |caps: &Captures| {
+            match args.get(&caps[1]).and_then(|v| v.as_str()) {
+                Some(value) => value.to_string(),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Piece of a template with its arguments substituted
+#[derive(Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Skeleton(String),
+    Errors(String),
+    Rules,
+    GoldenSamples,
+}
+
+/// Split a template into text and data placeholders. Placeholders are only
+/// taken from the template itself, so an argument value such as `{{rules}}`
+/// stays literal; arguments inside a placeholder (`{{skeleton:{{file}}}}`) are
+/// substituted.
+fn parse_template(text: &str, args: &Value) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut last = 0;
+    for caps in DATA_PLACEHOLDER.captures_iter(text) {
+        let whole = caps.get(0).expect("match");
+        if whole.start() > last {
+            parts.push(Part::Text(substitute_args(
+                &text[last..whole.start()],
+                args,
+            )));
+        }
+        last = whole.end();
+
+        parts.push(match (caps.get(1), caps.get(2), caps.get(3)) {
+            (Some(kind), Some(path), _) => {
+                let path = substitute_args(path.as_str(), args).trim().to_string();
+                match kind.as_str() {
+                    "skeleton" => Part::Skeleton(path),
+                    _ => Part::Errors(path),
+                }
+            }
+            (_, _, Some(name)) if name.as_str() == "rules" => Part::Rules,
+            _ => Part::GoldenSamples,
+        });
+    }
+    if last < text.len() {
+        parts.push(Part::Text(substitute_args(&text[last..], args)));
+    }
+    parts
+}
+
+async fn fill_data(parts: Vec<Part>, ctx: &ToolContext) -> String {
+    let mut out = String::new();
+    for part in parts {
+        let (placeholder, value) = match part {
+            Part::Text(text) => {
+                out.push_str(&text);
+                continue;
+            }
+            Part::Skeleton(path) => {
+                let value = skeleton_text(&path, ctx).await;
+                (format!("{{{{skeleton:{}}}}}", path), value)
+            }
+            Part::Errors(path) => {
+                let value = errors_text(&path, ctx).await;
+                (format!("{{{{errors:{}}}}}", path), value)
+            }
+            Part::Rules => ("{{rules}}".to_string(), rules_text(ctx).await),
+            Part::GoldenSamples => (
+                "{{golden_samples}}".to_string(),
+                golden_samples_text(ctx).await,
+            ),
+        };
+        match value {
+            Ok(v) => out.push_str(&v),
+            Err(e) => out.push_str(&format!("({} unavailable: {})", placeholder, e)),
+        }
+    }
+    out
+}
+
+async fn skeleton_text(path: &str, ctx: &ToolContext) -> Result<String> {
+    let skeleton = tools::dispatch("skeleton", json!({ "file": path }), ctx).await?;
+    Ok(skeleton
+        .get("skeleton_content")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+async fn errors_text(path: &str, ctx: &ToolContext) -> Result<String> {
+    let errors = tools::dispatch("get_errors", json!({ "file": path }), ctx).await?;
+    let lines: Vec<String> = errors
+        .get("errors")
+        .and_then(|v| v.as_object())
+        .map(|files| {
+            files
+                .iter()
+                .flat_map(|(file, errs)| {
+                    errs.as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|e| e.as_str())
+                        .map(move |e| format!("{}:{}", file, e))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(if lines.is_empty() {
+        "No errors.".to_string()
+    } else {
+        lines.join("\n")
+    })
+}
+
+/// Project rules as a markdown list.
+pub async fn rules_text(ctx: &ToolContext) -> Result<String> {
+    let rules = ctx.sqlite.get_rules().await?;
+    Ok(if rules.is_empty() {
+        "No project rules defined.".to_string()
+    } else {
+        rules
+            .iter()
+            .map(|r| format!("- [{}] (p={}) {}", r.category, r.priority, r.rule))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+async fn golden_samples_text(ctx: &ToolContext) -> Result<String> {
+    let samples = ctx.sqlite.get_golden_samples().await?;
+    Ok(if samples.is_empty() {
+        "No golden samples marked.".to_string()
+    } else {
+        samples
+            .iter()
+            .map(|(path, category)| match category {
+                Some(c) => format!("- {} ({})", path, c),
+                None => format!("- {}", path),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+/// Watch `.gofer/prompts/` and broadcast `notifications/prompts/list_changed`.
+pub async fn watch_prompts(
+    root: PathBuf,
+    notify_tx: broadcast::Sender<String>,
+    cancel: CancellationToken,
+) {
+    let gofer_dir = root.join(".gofer");
+    if !gofer_dir.is_dir() {
+        return;
+    }
+    let prompts = prompts_dir(&root);
+
+    tokio::task::spawn_blocking(move || {
+        let (tx, rx) = channel::<DebounceEventResult>();
+        let mut debouncer = match new_debouncer(Duration::from_millis(300), tx) {
+            Ok(d) => d,
+            Err(e) => {
+                tracing::warn!("Failed to create prompt watcher: {}", e);
+                return;
+            }
+        };
+        // Recursive on .gofer so a prompts/ directory created later is picked up
+        if let Err(e) = debouncer
+            .watcher()
+            .watch(&gofer_dir, RecursiveMode::Recursive)
+        {
+            tracing::warn!("Failed to watch {:?}: {}", gofer_dir, e);
+            return;
+        }
+
+        while !cancel.is_cancelled() {
+            match rx.recv_timeout(Duration::from_secs(1)) {
+                Ok(Ok(events)) => {
+                    if events.iter().any(|e| e.path.starts_with(&prompts)) {
+                        tracing::info!("Prompt library changed: {:?}", prompts);
+                        let notif = json!({
+                            "jsonrpc": "2.0",
+                            "method": "notifications/prompts/list_changed"
+                        });
+                        let _ = notify_tx.send(notif.to_string());
+                    }
+                }
+                Ok(Err(e)) => tracing::debug!("Prompt watcher error: {:?}", e),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    })
+    .await
+    .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_prompts_from_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompts = prompts_dir(dir.path());
+        std::fs::create_dir_all(&prompts).unwrap();
+        std::fs::write(
+            prompts.join("team_review.toml"),
+            r#"
+description = "Team review checklist"
+
+[[arguments]]
+name = "file"
+required = true
+
+[template]
+text = "Review {{file}}:\n{{skeleton:{{file}}}}\n{{rules}}"
+"#,
+        )
+        .unwrap();
+        std::fs::write(prompts.join("broken.toml"), "description = ").unwrap();
+        std::fs::write(prompts.join("notes.md"), "# not a prompt").unwrap();
+
+        let loaded = load_prompts(dir.path());
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "team_review");
+        assert_eq!(loaded[0].template.role, "user");
+        assert!(loaded[0].arguments[0].required);
+    }
+
+    #[test]
+    fn test_arguments_substituted_before_data_placeholders() {
+        let text = "Review {{file}}:\n{{skeleton:{{file}}}}\n{{rules}} {{missing}}";
+        let parts = parse_template(text, &json!({ "file": "src/auth.rs" }));
+        assert_eq!(
+            parts,
+            vec![
+                Part::Text("Review src/auth.rs:\n".to_string()),
+                Part::Skeleton("src/auth.rs".to_string()),
+                Part::Text("\n".to_string()),
+                Part::Rules,
+                Part::Text(" {{missing}}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_argument_values_are_not_expanded() {
+        let text = "Review {{file}}: {{note}}";
+        let args = json!({ "file": "{{skeleton:.env}}", "note": "{{rules}}" });
+        assert_eq!(
+            parse_template(text, &args),
+            vec![Part::Text(
+                "Review {{skeleton:.env}}: {{rules}}".to_string()
+            )]
+        );
+        assert_eq!(
+            parse_template("{{errors:{{file}}}}", &json!({ "file": "{{rules}}" })),
+            vec![Part::Errors("{{rules}}".to_string())]
+        );
+    }
+}
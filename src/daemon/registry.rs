@@ -141,6 +141,22 @@ impl RegistryDb {
         Ok(())
     }
 
+    /// Point a project at a new location, keeping its id (and therefore its index).
+    pub async fn update_path(&self, project_id: &str, new_path: &str) -> Result<()> {
+        let name = Path::new(new_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unnamed")
+            .to_string();
+        sqlx::query("UPDATE projects SET path = ?, name = ? WHERE id = ?")
+            .bind(new_path)
+            .bind(&name)
+            .bind(project_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     /// Remove a project from the registry (does NOT delete index files).
     #[allow(dead_code)]
     pub async fn remove(&self, project_id: &str) -> Result<()> {
@@ -0,0 +1,356 @@
+//! MCP resources — templated URIs over the index and per-connection subscriptions.
+//!
+//! Templates (paths are relative to the project root, absolute paths are accepted):
+//! - `file://{path}` — file content
+//! - `symbol://{name}` — definitions of a symbol with their source
+//! - `summary://{path}` — file summary
+//! - `errors://{path}` — active compiler diagnostics for a file
+//!
+//! The indexer reports every file it reindexes; [`forward_updates`] turns that into
+//! `notifications/resources/updated` on `notify_tx` for URIs someone subscribed to, and
+//! each connection only passes on the notifications for its own subscriptions.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc};
+
+use super::handlers::common::{index_path, ToolContext};
+use super::tools;
+use crate::error::GoferError;
+use crate::models::SymbolKind;
+use crate::storage::SqliteStorage;
+
+pub const UPDATED_METHOD: &str = "notifications/resources/updated";
+
+/// A parsed templated resource URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceUri {
+    File(String),
+    Symbol(String),
+    Summary(String),
+    Errors(String),
+}
+
+impl ResourceUri {
+    /// Parse a templated URI, normalising paths to index keys so that
+    /// `file:///abs/root/src/a.rs` and `file://src/a.rs` are the same resource.
+    pub fn parse(uri: &str, root: &Path) -> Option<Self> {
+        let (scheme, rest) = uri.split_once("://")?;
+        if rest.is_empty() {
+            return None;
+        }
+        let path = || index_path(root, rest);
+        match scheme {
+            "file" => Some(Self::File(path())),
+            "symbol" => Some(Self::Symbol(rest.to_string())),
+            "summary" => Some(Self::Summary(path())),
+            "errors" => Some(Self::Errors(path())),
+            _ => None,
+        }
+    }
+
+    /// Canonical form used for subscriptions and notifications.
+    pub fn canonical(&self) -> String {
+        match self {
+            Self::File(p) => format!("file://{}", p),
+            Self::Symbol(n) => format!("symbol://{}", n),
+            Self::Summary(p) => format!("summary://{}", p),
+            Self::Errors(p) => format!("errors://{}", p),
+        }
+    }
+}
+
+/// Entries for `resources/templates/list`.
+pub fn templates() -> Value {
+    json!([
+        {
+            "uriTemplate": "file://{path}",
+            "name": "File",
+            "description": "File content (path relative to the project root)",
+            "mimeType": "text/plain"
+        },
+        {
+            "uriTemplate": "symbol://{name}",
+            "name": "Symbol",
+            "description": "Definitions of a symbol: file, kind, lines, signature and source",
+            "mimeType": "application/json"
+        },
+        {
+            "uriTemplate": "summary://{path}",
+            "name": "File Summary",
+            "description": "Purpose summary of a file",
+            "mimeType": "application/json"
+        },
+        {
+            "uriTemplate": "errors://{path}",
+            "name": "File Errors",
+            "description": "Active compiler errors and warnings for a file",
+            "mimeType": "application/json"
+        }
+    ])
+}
+
+/// Read a templated resource. Returns `(mimeType, text)`.
+pub async fn read(uri: &ResourceUri, ctx: &ToolContext) -> Result<(&'static str, String)> {
+    let value = match uri {
+        ResourceUri::File(path) => {
+            let abs = ctx.root_path.join(path);
+            let inside = abs
+                .canonicalize()
+                .ok()
+                .zip(ctx.root_path.canonicalize().ok())
+                .is_some_and(|(file, root)| file.starts_with(root));
+            if !inside {
+                return Err(GoferError::InvalidParams(format!("File not found: {}", path)).into());
+            }
+            return Ok(("text/plain", tokio::fs::read_to_string(&abs).await?));
+        }
+        ResourceUri::Symbol(name) => read_symbol(name, ctx).await?,
+        ResourceUri::Summary(path) => {
+            tools::dispatch("get_summary", json!({ "file": path }), ctx).await?
+        }
+        ResourceUri::Errors(path) => {
+            tools::dispatch("get_errors", json!({ "file": path }), ctx).await?
+        }
+    };
+    Ok(("application/json", serde_json::to_string_pretty(&value)?))
+}
+
+async fn read_symbol(name: &str, ctx: &ToolContext) -> Result<Value> {
+    let symbols = ctx.sqlite.get_symbol_by_name(name).await?;
+    let mut definitions = Vec::new();
+    for sym in symbols.iter().filter(|s| s.kind != SymbolKind::LocalVar) {
+        let Some(file) = ctx.sqlite.get_file_by_id(sym.file_id).await? else {
+            continue;
+        };
+        let code = tokio::fs::read_to_string(ctx.root_path.join(&file.path))
+            .await
+            .ok()
+            .map(|content| {
+                content
+                    .lines()
+                    .skip(sym.line_start.max(0) as usize)
+                    .take((sym.line_end - sym.line_start).max(0) as usize + 1)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        definitions.push(json!({
+            "file": file.path,
+            "kind": sym.kind.as_str(),
+            "line_start": sym.line_start + 1,
+            "line_end": sym.line_end + 1,
+            "signature": sym.signature,
+            "code": code,
+        }));
+    }
+    if definitions.is_empty() {
+        return Err(GoferError::InvalidParams(format!("Symbol not found: {}", name)).into());
+    }
+    Ok(json!({
+        "name": name,
+        "definitions": definitions,
+    }))
+}
+
+/// Resource subscriptions of all connections.
+///
+/// Keyed by `(project root, canonical URI)`; each subscriber keeps the URI it
+/// subscribed with so notifications echo the client's own spelling.
+#[derive(Default)]
+pub struct ResourceSubscriptions {
+    next_connection: AtomicU64,
+    subs: Mutex<HashMap<(String, String), HashMap<u64, String>>>,
+}
+
+impl ResourceSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate an id for a new connection.
+    pub fn connect(&self) -> u64 {
+        self.next_connection.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn subscribe(&self, conn: u64, project: &str, canonical: &str, uri: &str) {
+        let mut subs = self.subs.lock().unwrap_or_else(|e| e.into_inner());
+        subs.entry((project.to_string(), canonical.to_string()))
+            .or_default()
+            .insert(conn, uri.to_string());
+    }
+
+    pub fn unsubscribe(&self, conn: u64, project: &str, canonical: &str) {
+        let mut subs = self.subs.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (project.to_string(), canonical.to_string());
+        if let Some(conns) = subs.get_mut(&key) {
+            conns.remove(&conn);
+            if conns.is_empty() {
+                subs.remove(&key);
+            }
+        }
+    }
+
+    /// Drop every subscription of a closed connection.
+    pub fn disconnect(&self, conn: u64) {
+        let mut subs = self.subs.lock().unwrap_or_else(|e| e.into_inner());
+        subs.retain(|_, conns| {
+            conns.remove(&conn);
+            !conns.is_empty()
+        });
+    }
+
+    pub fn is_subscribed(&self, project: &str, canonical: &str) -> bool {
+        let subs = self.subs.lock().unwrap_or_else(|e| e.into_inner());
+        subs.contains_key(&(project.to_string(), canonical.to_string()))
+    }
+
+    /// The URI `conn` subscribed with, if it is subscribed to `canonical`.
+    pub fn subscribed_uri(&self, conn: u64, project: &str, canonical: &str) -> Option<String> {
+        let subs = self.subs.lock().unwrap_or_else(|e| e.into_inner());
+        subs.get(&(project.to_string(), canonical.to_string()))
+            .and_then(|conns| conns.get(&conn).cloned())
+    }
+
+    /// Symbol names subscribed to in a project.
+    fn symbols(&self, project: &str) -> HashSet<String> {
+        let subs = self.subs.lock().unwrap_or_else(|e| e.into_inner());
+        subs.keys()
+            .filter(|(p, _)| p == project)
+            .filter_map(|(_, uri)| uri.strip_prefix("symbol://").map(String::from))
+            .collect()
+    }
+}
+
+/// Notification for a canonical URI; `project_path` in `_meta` lets each
+/// connection match it against its own subscriptions.
+pub fn updated_notification(project: &str, canonical: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": UPDATED_METHOD,
+        "params": {
+            "uri": canonical,
+            "_meta": { "project_path": project }
+        }
+    })
+}
+
+/// Rewrite a broadcast `resources/updated` notification for one connection.
+/// Returns `None` if the connection is not subscribed to it.
+pub fn filter_for_connection(
+    msg: &str,
+    conn: u64,
+    subscriptions: &ResourceSubscriptions,
+) -> Option<String> {
+    let notif: Value = serde_json::from_str(msg).ok()?;
+    let params = notif.get("params")?;
+    let canonical = params.get("uri")?.as_str()?;
+    let project = params.get("_meta")?.get("project_path")?.as_str()?;
+    let uri = subscriptions.subscribed_uri(conn, project, canonical)?;
+    Some(
+        json!({
+            "jsonrpc": "2.0",
+            "method": UPDATED_METHOD,
+            "params": { "uri": uri }
+        })
+        .to_string(),
+    )
+}
+
+/// Decide what a connection receives for a broadcast notification: resource
+/// updates only if it subscribed, everything else unchanged.
+pub fn route_notification(
+    msg: String,
+    conn: u64,
+    subscriptions: &ResourceSubscriptions,
+) -> Option<String> {
+    if msg.contains(UPDATED_METHOD) {
+        filter_for_connection(&msg, conn, subscriptions)
+    } else {
+        Some(msg)
+    }
+}
+
+/// Turn reindexed paths reported by the indexer into resource update notifications.
+pub async fn forward_updates(
+    mut rx: mpsc::UnboundedReceiver<String>,
+    project: String,
+    sqlite: SqliteStorage,
+    subscriptions: std::sync::Arc<ResourceSubscriptions>,
+    notify_tx: broadcast::Sender<String>,
+) {
+    while let Some(path) = rx.recv().await {
+        let mut updated: Vec<String> = [
+            ResourceUri::File(path.clone()),
+            ResourceUri::Summary(path.clone()),
+            ResourceUri::Errors(path.clone()),
+        ]
+        .iter()
+        .map(ResourceUri::canonical)
+        .filter(|uri| subscriptions.is_subscribed(&project, uri))
+        .collect();
+
+        for name in subscriptions.symbols(&project) {
+            if let Ok(Some(_)) = sqlite.find_symbol_by_name_and_file(&name, &path).await {
+                updated.push(ResourceUri::Symbol(name).canonical());
+            }
+        }
+
+        for uri in updated {
+            tracing::debug!("Resource updated: {}", uri);
+            let _ = notify_tx.send(updated_notification(&project, &uri).to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalises_paths() {
+        let root = Path::new("/work/app");
+        assert_eq!(
+            ResourceUri::parse("file:///work/app/src/main.rs", root),
+            Some(ResourceUri::File("src/main.rs".into()))
+        );
+        assert_eq!(
+            ResourceUri::parse("errors://src/main.rs", root).map(|u| u.canonical()),
+            Some("errors://src/main.rs".into())
+        );
+        assert_eq!(
+            ResourceUri::parse("symbol://UserService", root),
+            Some(ResourceUri::Symbol("UserService".into()))
+        );
+        assert_eq!(ResourceUri::parse("project://stats", root), None);
+        assert_eq!(ResourceUri::parse("file://", root), None);
+    }
+
+    #[test]
+    fn test_updates_reach_only_subscribers() {
+        let subs = ResourceSubscriptions::new();
+        let (a, b) = (subs.connect(), subs.connect());
+        subs.subscribe(
+            a,
+            "/work/app",
+            "file://src/main.rs",
+            "file:///work/app/src/main.rs",
+        );
+
+        let msg = updated_notification("/work/app", "file://src/main.rs").to_string();
+        let forwarded = filter_for_connection(&msg, a, &subs).unwrap();
+        assert!(forwarded.contains("file:///work/app/src/main.rs"));
+        assert!(!forwarded.contains("_meta"));
+        assert!(filter_for_connection(&msg, b, &subs).is_none());
+
+        let other = updated_notification("/work/other", "file://src/main.rs").to_string();
+        assert!(filter_for_connection(&other, a, &subs).is_none());
+
+        subs.disconnect(a);
+        assert!(!subs.is_subscribed("/work/app", "file://src/main.rs"));
+    }
+}
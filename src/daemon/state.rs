@@ -10,12 +10,15 @@ use anyhow::Result;
 use tokio::sync::{broadcast, mpsc, Mutex, RwLock, Semaphore};
 use tokio_util::sync::CancellationToken;
 
+use super::prompts;
 use super::registry::{ProjectRecord, RegistryDb};
+use super::resources::{self, ResourceSubscriptions};
 use crate::cache::CacheManager;
 use crate::error_recovery::CircuitBreaker; // Feature 016
 use crate::indexer::summarizer::{summary_worker, SummarizerConfig};
 use crate::indexer::{
-    load_config, start_watcher, EmbedderPool, GoferConfig, IndexTask, IndexerService,
+    load_config, start_watcher, EmbedderPool, FeedbackConfig, GoferConfig, IndexTask,
+    IndexerService,
 };
 use crate::languages::LanguageService;
 use crate::resource_limits::ResourceLimits; // Feature 015
@@ -33,7 +36,7 @@ pub struct DaemonState {
     pub projects: RwLock<HashMap<String, Arc<ProjectState>>>,
     /// Daemon start time
     pub started_at: Instant,
-    /// Sync progress (shared with pipeline)
+    /// Full sync progress (shared with pipeline); git syncs report per project
     pub sync_progress: Arc<SyncProgress>,
     /// Cancellation token for graceful shutdown
     pub shutdown_token: CancellationToken,
@@ -49,6 +52,8 @@ pub struct DaemonState {
     pub embedding_circuit: Arc<CircuitBreaker>,
     /// Circuit breaker for vector search (Feature 016)
     pub vector_circuit: Arc<CircuitBreaker>,
+    /// MCP resource subscriptions of all connections
+    pub resource_subscriptions: Arc<ResourceSubscriptions>,
 }
 
 /// Lock-free runtime metrics for the daemon process.
@@ -228,17 +233,15 @@ pub struct ProjectState {
     pub cache: Arc<CacheManager>,
     /// rust-analyzer instance for this project (lazy-loaded)
     pub rust_analyzer: Arc<RwLock<Option<Arc<crate::languages::rust_analyzer::RustAnalyzer>>>>,
+    /// `[feedback]` from the project's config.toml, read on load
+    pub feedback: FeedbackConfig,
+    /// Progress of the watcher's git syncs for this project
+    pub sync_progress: Arc<SyncProgress>,
 }
 
 impl DaemonState {
     /// Create a new DaemonState, loading the global embedder and reranker.
     pub async fn new(gofer_home: PathBuf) -> Result<Self> {
-        let registry_path = gofer_home.join("registry.sqlite");
-        let registry_path_str = registry_path
-            .to_str()
-            .ok_or_else(|| anyhow::anyhow!("Invalid registry path: non-UTF8 characters"))?;
-        let registry = RegistryDb::new(registry_path_str).await?;
-
         // Try to load global config from ~/.gofer/config.toml (not .gofer/config.toml)
         let global_config_path = gofer_home.join("config.toml");
         let config = if global_config_path.exists() {
@@ -252,6 +255,17 @@ impl DaemonState {
         tracing::info!("Loading embedding pool...");
         let embedder = EmbedderPool::with_config(1, &config.embedding)?; // Start with 1 instance, scale up for indexing
 
+        Self::with_embedder(gofer_home, embedder).await
+    }
+
+    /// Create a DaemonState around an already loaded embedding pool.
+    pub(crate) async fn with_embedder(gofer_home: PathBuf, embedder: EmbedderPool) -> Result<Self> {
+        let registry_path = gofer_home.join("registry.sqlite");
+        let registry_path_str = registry_path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid registry path: non-UTF8 characters"))?;
+        let registry = RegistryDb::new(registry_path_str).await?;
+
         let (notify_tx, _) = broadcast::channel::<String>(64);
 
         // Feature 016: Circuit breakers for external services
@@ -283,6 +297,7 @@ impl DaemonState {
             resource_limits: Arc::new(ResourceLimits::default()), // Feature 015
             embedding_circuit,                                    // Feature 016
             vector_circuit,                                       // Feature 016
+            resource_subscriptions: Arc::new(ResourceSubscriptions::new()),
         })
     }
 
@@ -394,6 +409,9 @@ impl DaemonState {
             // Continue anyway - the database may still be usable
         }
 
+        // C5: Indices built before relative path storage keep absolute paths — strip the root once
+        relativize_index_paths(&sqlite, &lance_storage, &record.path).await?;
+
         let lance = Arc::new(Mutex::new(lance_storage));
 
         let (task_tx, task_rx) = mpsc::channel::<IndexTask>(100);
@@ -404,6 +422,7 @@ impl DaemonState {
 
         // Create cache manager for this project
         let cache = Arc::new(CacheManager::new());
+        let sync_progress = Arc::new(SyncProgress::new());
 
         let state = Arc::new(ProjectState {
             id: record.id.clone(),
@@ -417,18 +436,45 @@ impl DaemonState {
             cancel: project_cancel,
             cache: cache.clone(),
             rust_analyzer: Arc::new(RwLock::new(None)),
+            feedback: config.feedback.clone(),
+            sync_progress: sync_progress.clone(),
         });
 
         // Spawn indexer worker — shares lance + embedder pool via Arc
         // Feature 012: Pass cache for invalidation on file changes
         // Use configured parallel workers
-        let indexer =
-            IndexerService::new(sqlite, lance, self.embedder.clone(), workers).with_cache(cache);
+        // Reindexed files feed resources/updated notifications for subscribers
+        let (updates_tx, updates_rx) = mpsc::unbounded_channel::<String>();
+        tokio::spawn(resources::forward_updates(
+            updates_rx,
+            root_path.to_string_lossy().to_string(),
+            sqlite.clone(),
+            self.resource_subscriptions.clone(),
+            self.notify_tx.clone(),
+        ));
+
+        let indexer = IndexerService::new(
+            root_path.clone(),
+            sqlite,
+            lance,
+            self.embedder.clone(),
+            workers,
+        )
+        .with_cache(cache)
+        .with_updates(updates_tx)
+        .with_progress(sync_progress);
 
         tokio::spawn(async move {
             indexer.run(task_rx).await;
         });
 
+        // Hot-reload .gofer/prompts/ for prompts/list_changed
+        tokio::spawn(prompts::watch_prompts(
+            root_path.clone(),
+            self.notify_tx.clone(),
+            state.cancel.clone(),
+        ));
+
         // Store in map
         let mut projects = self.projects.write().await;
         projects.insert(record.id.clone(), state.clone());
@@ -474,17 +520,19 @@ impl DaemonState {
             PathBuf::from(project_path).join(".gofer")
         };
         let config = load_config(&gofer_dir);
-        let ignore_patterns = config.indexer.ignore.clone();
+        let ignore_patterns = config.indexer.ignore_patterns();
         let workers = config.indexer.parallel_workers.unwrap_or(4);
 
         // Full sync using shared lance + embedder pool (no redundant instances)
         // Note: parallel_workers here is for consistency, full_sync uses internal pipeline
         let sync_indexer = IndexerService::new(
+            project.path.clone(),
             project.sqlite.clone(),
             project.lance.clone(),
             self.embedder.clone(),
             workers,
-        );
+        )
+        .with_cache(project.cache.clone());
 
         let root = PathBuf::from(project_path);
 
@@ -527,8 +575,9 @@ impl DaemonState {
         if summarizer_config.enable_llm {
             let cancel = self.shutdown_token.clone();
             let sqlite_sum = project.sqlite.clone();
+            let root_sum = root.clone();
             tokio::spawn(async move {
-                summary_worker(summarizer_config, sqlite_sum, cancel).await;
+                summary_worker(summarizer_config, root_sum, sqlite_sum, cancel).await;
             });
         }
 
@@ -616,6 +665,35 @@ impl DaemonState {
     }
 }
 
+/// index_meta key recording how file paths are stored (`relative` once migrated).
+const PATH_FORMAT_KEY: &str = "path_format";
+
+/// Convert absolute paths under `root` to root-relative ones, once per index.
+/// Returns the number of (sqlite, lance) rows rewritten.
+pub(crate) async fn relativize_index_paths(
+    sqlite: &SqliteStorage,
+    lance: &LanceStorage,
+    root: &str,
+) -> Result<(u64, u64)> {
+    if sqlite.get_index_meta(PATH_FORMAT_KEY).await?.as_deref() == Some("relative") {
+        return Ok((0, 0));
+    }
+
+    let sqlite_rows = sqlite.rebase_paths(root, "").await?;
+    let lance_rows = lance.rebase_paths(root, "").await?;
+    sqlite.set_index_meta(PATH_FORMAT_KEY, "relative").await?;
+
+    if sqlite_rows + lance_rows > 0 {
+        tracing::info!(
+            "Converted index paths under {} to relative ({} sqlite / {} lance rows)",
+            root,
+            sqlite_rows,
+            lance_rows
+        );
+    }
+    Ok((sqlite_rows, lance_rows))
+}
+
 /// Initialize language services for a project.
 fn init_language_services(
     sqlite: &SqliteStorage,
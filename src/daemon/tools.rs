@@ -17,6 +17,8 @@ pub async fn dispatch(name: &str, args: Value, ctx: &ToolContext) -> Result<Valu
         "get_references" => symbols::tool_get_references(args, ctx).await,
         "get_dependencies" => project::tool_get_dependencies(args, ctx).await,
         "dependency_impact" => project::tool_dependency_impact(args, ctx).await,
+        "get_imports" => project::tool_get_imports(args, ctx).await,
+        "get_importers" => project::tool_get_importers(args, ctx).await,
         "get_errors" => diagnostics::tool_get_errors(args, ctx).await,
         "run_diagnostics" => diagnostics::tool_run_diagnostics(ctx).await,
         "get_config_keys" => diagnostics::tool_get_config_keys(ctx).await,
@@ -27,14 +29,19 @@ pub async fn dispatch(name: &str, args: Value, ctx: &ToolContext) -> Result<Valu
         "cross_stack_search" => search::tool_cross_stack_search(args, ctx).await,
         "domain_stats" => project::tool_domain_stats(ctx).await,
         "get_api_routes" => project::tool_get_api_routes(args, ctx).await,
+        "db_schema" => database::tool_db_schema(args, ctx).await,
+        "column_usages" => database::tool_column_usages(args, ctx).await,
         "get_summary" => project::tool_get_summary(args, ctx).await,
         "search_by_purpose" => search::tool_search_by_purpose(args, ctx).await,
+        "ask" => ask::tool_ask(args, ctx).await,
         "skeleton" => files::tool_skeleton(args, ctx).await,
         "verify_patch" => git::tool_verify_patch(args, ctx).await,
         "read_file" => files::tool_read_file(args, ctx).await,
         "project_tree" => project::tool_project_tree(args, ctx).await,
         "search_symbols" => symbols::tool_search_symbols(args, ctx).await,
         "add_rule" => project::tool_add_rule(args, ctx).await,
+        "check_rules" => rules::tool_check_rules(args, ctx).await,
+        "architecture_check" => rules::tool_architecture_check(args, ctx).await,
         "mark_golden_sample" => project::tool_mark_golden_sample(args, ctx).await,
         "run_check" => diagnostics::tool_run_check(args, ctx).await,
         "grep" => files::tool_grep(args, ctx).await,
@@ -73,6 +80,14 @@ pub async fn dispatch(name: &str, args: Value, ctx: &ToolContext) -> Result<Valu
         "list_trash" => trash::tool_list_trash(args, ctx).await,
         "restore" => trash::tool_restore(args, ctx).await,
         "purge_trash" => trash::tool_purge_trash(args, ctx).await,
+        // Workspace state & TODO notes
+        "save_workspace_state" => workspace::tool_save_workspace_state(args, ctx).await,
+        "load_workspace_state" => workspace::tool_load_workspace_state(args, ctx).await,
+        "list_workspace_states" => workspace::tool_list_workspace_states(args, ctx).await,
+        "delete_workspace_state" => workspace::tool_delete_workspace_state(args, ctx).await,
+        "add_todo_note" => workspace::tool_add_todo_note(args, ctx).await,
+        "list_todo_notes" => workspace::tool_list_todo_notes(args, ctx).await,
+        "resolve_todo_note" => workspace::tool_resolve_todo_note(args, ctx).await,
         // Atomic Transactions (Phase 2)
         // Code Quality Tools (Phase 2)
         "format_file" => code_quality::tool_format_file(args, ctx).await,
@@ -135,7 +150,12 @@ pub fn core_tools_list() -> Vec<Value> {
                     "include_scores": { "type": "boolean", "description": "Include relevance scores (0.0-1.0)", "default": false },
                     "preview_mode": { "type": "boolean", "description": "Return short preview (2-3 lines) instead of full content. Saves 80% tokens.", "default": false },
                     "min_score": { "type": "number", "description": "Minimum relevance score to include (0.0-1.0, filters low-quality results)", "default": 0.0 },
-                    "include_context": { "type": "boolean", "description": "Include context (function/class name where match found)", "default": true }
+                    "include_context": { "type": "boolean", "description": "Include context (function/class name where match found)", "default": true },
+                    "exact_symbol_shortcut": { "type": "boolean", "description": "If the query is an exact compound symbol name (e.g. getUserById), return search_symbols results instead", "default": true },
+                    "diversity": { "type": "number", "description": "Trade relevance for variety (0.0 = pure relevance, 1.0 = maximum diversity). Suppresses overlapping chunks and duplicated code.", "default": 0.3 },
+                    "max_per_file": { "type": "integer", "description": "Maximum hits from one file (0 = unlimited)", "default": 3 },
+                    "group_by_file": { "type": "boolean", "description": "Merge overlapping/adjacent hits in a file into one 'path:start-end' result", "default": false },
+                    "rrf_k": { "type": "number", "description": "Reciprocal rank fusion constant (advanced, for tuning)", "default": 60 }
                 },
                 "required": ["query"]
             }
@@ -185,6 +205,32 @@ pub fn core_tools_list() -> Vec<Value> {
                 "required": ["name"]
             }
         }),
+        json!({
+            "name": "get_imports",
+            "description": "List the project files a file imports, from the import graph resolved at index time (relative paths, crate:: paths, tsconfig aliases, Python packages). Returns 'file:line -> target (depth N)' edges; depth > 1 follows imports transitively.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string", "description": "Relative file path" },
+                    "depth": { "type": "integer", "description": "Follow the import graph this many hops (default: 1, max: 10)", "default": 1 },
+                    "limit": { "type": "integer", "description": "Max edges to list (default: 200)", "default": 200 }
+                },
+                "required": ["file"]
+            }
+        }),
+        json!({
+            "name": "get_importers",
+            "description": "List the project files that import a file, from the import graph resolved at index time. Returns 'importer:line -> file (depth N)' edges; depth > 1 finds transitive importers (what a change can affect).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string", "description": "Relative file path" },
+                    "depth": { "type": "integer", "description": "Follow the import graph this many hops (default: 1, max: 10)", "default": 1 },
+                    "limit": { "type": "integer", "description": "Max edges to list (default: 200)", "default": 200 }
+                },
+                "required": ["file"]
+            }
+        }),
         json!({
             "name": "get_errors",
             "description": "Get current compiler errors/warnings from cargo check or tsc. Supports pagination via offset/limit. Returns a token-optimized map clustered by file.",
@@ -229,21 +275,22 @@ pub fn core_tools_list() -> Vec<Value> {
         }),
         json!({
             "name": "context_bundle",
-            "description": "Build a context bundle for a file, resolving its import dependencies recursively. Use skeleton=true to skeletonize everything, or skeleton_deps_only=true to keep main file full but skeletonize dependencies (saves tokens while preserving target context).",
+            "description": "Build a context bundle for a file, resolving its import dependencies recursively. Workspace-internal absolute imports (Rust crate:: paths, TS path aliases, Python package imports) contribute only the imported items' definitions. Use skeleton=true to skeletonize everything, or skeleton_deps_only=true to keep main file full but skeletonize dependencies (saves tokens while preserving target context).",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "file": { "type": "string", "description": "File path to bundle context for" },
                     "depth": { "type": "integer", "description": "How deep to resolve dependencies (default: 2)", "default": 2 },
                     "skeleton": { "type": "boolean", "description": "If true, strip function bodies from ALL files (main + deps)", "default": false },
-                    "skeleton_deps_only": { "type": "boolean", "description": "If true, keep main file full but skeletonize dependencies only", "default": false }
+                    "skeleton_deps_only": { "type": "boolean", "description": "If true, keep main file full but skeletonize dependencies only", "default": false },
+                    "max_tokens": { "type": "integer", "description": "Token budget for the whole bundle. Dependencies degrade first (full → skeleton → signatures → names), the main file last" }
                 },
                 "required": ["file"]
             }
         }),
         json!({
             "name": "cross_stack_search",
-            "description": "Search with cross-stack correlation (find related backend/frontend entities).",
+            "description": "Search with cross-stack correlation (find related backend/frontend entities, and the backend routes that frontend API calls in the results reach).",
             "inputSchema": {
                 "type": "object",
                 "properties": {
@@ -255,11 +302,35 @@ pub fn core_tools_list() -> Vec<Value> {
         }),
         json!({
             "name": "get_api_routes",
-            "description": "List all API routes (backend endpoints and frontend API calls).",
+            "description": "List API routes: backend endpoints, frontend API calls, and endpoints declared by OpenAPI/Swagger specs, GraphQL schemas and .proto services linked to the handler implementing them and to their clients. Reports contract drift: spec endpoints nothing implements, and client calls to paths or fields absent from the spec.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "side": { "type": "string", "description": "Filter by side: backend, frontend, contract (optional)" },
+                    "limit": { "type": "integer", "description": "Max entries per list (default: 200)", "default": 200 }
+                }
+            }
+        }),
+        json!({
+            "name": "db_schema",
+            "description": "Database schema replayed from the project's SQL migrations: tables with columns, indexes and foreign keys, plus columns and tables later migrations dropped or renamed (with the migration file:line).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "table": { "type": "string", "description": "Show one table in detail, including where each column was defined (optional)" },
+                    "include_dropped": { "type": "boolean", "description": "List dropped and renamed columns/tables", "default": true }
+                }
+            }
+        }),
+        json!({
+            "name": "column_usages",
+            "description": "Find code that uses a table or column: SQL in string literals (sqlx, SQLAlchemy text(), TypeORM query builders...), Diesel DSL paths and ORM entity fields. Usages of dropped or renamed columns are marked STALE. Without arguments, lists every stale usage in the project.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
-                    "side": { "type": "string", "description": "Filter by side: backend, frontend (optional)" }
+                    "table": { "type": "string", "description": "Table name (optional)" },
+                    "column": { "type": "string", "description": "Column name; without table, searches every table that has or had it (optional)" },
+                    "limit": { "type": "integer", "description": "Max usages to return (default: 200)", "default": 200 }
                 }
             }
         }),
@@ -274,6 +345,21 @@ pub fn core_tools_list() -> Vec<Value> {
                 "required": ["file"]
             }
         }),
+        json!({
+            "name": "ask",
+            "description": "Answer a natural-language question about the codebase. Gofer retrieves relevant code, asks the chat model configured in [ask] of .gofer/config.toml and returns the answer with file:line citations checked against the index.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "question": { "type": "string", "description": "Question about the code (e.g. 'How are search results cached?')" },
+                    "limit": { "type": "integer", "description": "Code regions to retrieve (default: 6)", "default": 6 },
+                    "include_dependencies": { "type": "boolean", "description": "Add signatures of modules the best match imports", "default": true },
+                    "max_context_tokens": { "type": "integer", "description": "Token budget for retrieved code (default from config: 6000)" },
+                    "model": { "type": "string", "description": "Override the configured chat model" }
+                },
+                "required": ["question"]
+            }
+        }),
         json!({
             "name": "search_by_purpose",
             "description": "Search files by high-level purpose/responsibility. Best for architectural queries like 'authentication', 'billing logic', 'API routes'.",
@@ -305,6 +391,10 @@ pub fn core_tools_list() -> Vec<Value> {
                         "type": "boolean",
                         "default": false,
                         "description": "Include test functions (default: false)"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Token budget. Falls back to signatures, then names only, if the skeleton does not fit"
                     }
                 },
                 "required": ["file"]
@@ -312,7 +402,7 @@ pub fn core_tools_list() -> Vec<Value> {
         }),
         json!({
             "name": "verify_patch",
-            "description": "Verify a code patch by temporarily applying it and running the compiler/linter. Returns diagnostics (errors, warnings) without modifying the file permanently.",
+            "description": "Verify a code patch by temporarily applying it and running the compiler/linter. Returns diagnostics (errors, warnings) without modifying the file permanently, plus violations of .gofer/rules.toml (an error-level violation fails the patch).",
             "inputSchema": {
                 "type": "object",
                 "properties": {
@@ -322,6 +412,30 @@ pub fn core_tools_list() -> Vec<Value> {
                 "required": ["file", "content"]
             }
         }),
+        json!({
+            "name": "check_rules",
+            "description": "Check indexed files against the conventions in .gofer/rules.toml: forbidden imports between domains or directories, naming patterns per symbol kind, banned APIs and max file/function size. Returns 'file:line [severity] rule: message' entries.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Only check this file or directory (optional)" },
+                    "severity": { "type": "string", "description": "Only report 'error' violations (optional)" },
+                    "limit": { "type": "integer", "description": "Max violations to list (default: 200)", "default": 200 }
+                }
+            }
+        }),
+        json!({
+            "name": "architecture_check",
+            "description": "Check import edges against the layers declared under [architecture] in .gofer/rules.toml (e.g. 'api -> service -> repo', forbid 'ui -> db'). Returns violating imports as 'file:line -> target (reason)', fan-in/fan-out per module or directory, and import cycles between them.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Only check files under this directory (optional)" },
+                    "depth": { "type": "integer", "description": "Directory depth grouping files outside declared modules (default: 2)", "default": 2 },
+                    "limit": { "type": "integer", "description": "Max violations and modules to list (default: 50)", "default": 50 }
+                }
+            }
+        }),
         json!({
             "name": "read_file",
             "description": "Read file content with optional line range. Returns the file text with line numbers.",
@@ -330,7 +444,8 @@ pub fn core_tools_list() -> Vec<Value> {
                 "properties": {
                     "file": { "type": "string", "description": "Relative file path" },
                     "start_line": { "type": "integer", "description": "First line to read (1-based, default: 1)", "default": 1 },
-                    "end_line": { "type": "integer", "description": "Last line to read (inclusive, default: end of file)" }
+                    "end_line": { "type": "integer", "description": "Last line to read (inclusive, default: end of file)" },
+                    "max_tokens": { "type": "integer", "description": "Token budget. Content degrades full → skeleton → signatures → names to fit" }
                 },
                 "required": ["file"]
             }
@@ -452,6 +567,11 @@ pub fn core_tools_list() -> Vec<Value> {
                     "path": {
                         "type": "string",
                         "description": "File or directory path (required for file/directory scope)"
+                    },
+                    "wait": {
+                        "type": "boolean",
+                        "description": "Reindex now and return when done instead of only queueing. Reports MCP progress and stops on notifications/cancelled.",
+                        "default": false
                     }
                 }
             }
@@ -510,6 +630,10 @@ pub fn core_tools_list() -> Vec<Value> {
                         "type": "boolean",
                         "default": false,
                         "description": "Include functions called by this function (1 level deep)"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Token budget. The function degrades to fit; referenced types and callees fill what is left"
                     }
                 },
                 "required": ["file", "function"]
@@ -534,6 +658,10 @@ pub fn core_tools_list() -> Vec<Value> {
                         "type": "boolean",
                         "default": true,
                         "description": "Include doc comments"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Token budget. Falls back to one line per type, then names only"
                     }
                 },
                 "required": ["file"]
@@ -558,6 +686,22 @@ pub fn core_tools_list() -> Vec<Value> {
                         "type": "number",
                         "default": 0.3,
                         "description": "Minimum relevance score 0-1 (default: 0.3)"
+                    },
+                    "weights": {
+                        "type": "object",
+                        "description": "Override the adaptive scoring weights (advanced, for tuning)",
+                        "properties": {
+                            "vector": { "type": "number" },
+                            "path": { "type": "number" },
+                            "symbols": { "type": "number" },
+                            "summary": { "type": "number" }
+                        },
+                        "required": ["vector", "path", "symbols", "summary"]
+                    },
+                    "use_feedback": {
+                        "type": "boolean",
+                        "default": true,
+                        "description": "Boost files that were opened after similar searches (disable globally with [feedback] enabled = false)"
                     }
                 },
                 "required": ["query"]
@@ -597,6 +741,10 @@ pub fn core_tools_list() -> Vec<Value> {
                         "type": "boolean",
                         "default": true,
                         "description": "Continue if one operation fails (default: true)"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Token budget split evenly across read operations (read_file, skeleton, read_function_context, read_types_only)"
                     }
                 },
                 "required": ["operations"]
@@ -856,6 +1004,93 @@ pub fn core_tools_list() -> Vec<Value> {
                 }
             }
         }),
+        // Workspace state & TODO notes (resume interrupted sessions)
+        json!({
+            "name": "save_workspace_state",
+            "description": "Save the current task, plan metadata, touched files and open transactions under a state_id so a later session can resume. Saving an existing state_id overwrites it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "state_id": { "type": "string", "description": "Name of the state (e.g., 'refactor-auth-v2')" },
+                    "task": { "type": "string", "description": "What is being worked on" },
+                    "metadata": { "type": "object", "description": "Free-form plan/progress data (e.g., {\"progress\": \"60%\", \"next_steps\": [...]})" },
+                    "touched_files": { "type": "array", "items": { "type": "string" }, "description": "Files modified so far" },
+                    "include_transactions": { "type": "boolean", "description": "Save open transactions and add their files to touched_files", "default": true }
+                },
+                "required": ["state_id"]
+            }
+        }),
+        json!({
+            "name": "load_workspace_state",
+            "description": "Load a saved workspace state: task, metadata, touched files, its open TODO notes (anchors re-resolved) and transactions, which are re-opened unless already active.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "state_id": { "type": "string", "description": "State to load" },
+                    "restore_transactions": { "type": "boolean", "description": "Re-open saved transactions with their staged operations", "default": true }
+                },
+                "required": ["state_id"]
+            }
+        }),
+        json!({
+            "name": "list_workspace_states",
+            "description": "List saved workspace states, most recently updated first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "delete_workspace_state",
+            "description": "Delete a saved workspace state. Its TODO notes are kept.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "state_id": { "type": "string", "description": "State to delete" }
+                },
+                "required": ["state_id"]
+            }
+        }),
+        json!({
+            "name": "add_todo_note",
+            "description": "Attach a TODO note to a file, a symbol, or a line (anchored to its enclosing symbol). Anchors follow the symbol by name through edits and file moves.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string", "description": "File the note belongs to" },
+                    "text": { "type": "string", "description": "Note text" },
+                    "symbol": { "type": "string", "description": "Symbol to anchor to (optional)" },
+                    "line": { "type": "integer", "description": "1-based line (optional)" },
+                    "priority": { "type": "string", "enum": ["low", "medium", "high"], "default": "medium" },
+                    "state_id": { "type": "string", "description": "Workspace state the note belongs to (optional)" }
+                },
+                "required": ["file", "text"]
+            }
+        }),
+        json!({
+            "name": "list_todo_notes",
+            "description": "List TODO notes, highest priority first, with anchors re-resolved against the current code ('ok', 'moved' or 'lost').",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string", "description": "Only notes in this file" },
+                    "priority": { "type": "string", "enum": ["low", "medium", "high"] },
+                    "state_id": { "type": "string", "description": "Only notes of this workspace state" },
+                    "include_resolved": { "type": "boolean", "default": false }
+                }
+            }
+        }),
+        json!({
+            "name": "resolve_todo_note",
+            "description": "Mark a TODO note as done.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "todo_id": { "type": "integer", "description": "ID from add_todo_note" }
+                },
+                "required": ["todo_id"]
+            }
+        }),
         // Atomic Transactions (Phase 2) - safe multi-file operations
         // Code Quality Tools (Phase 2) - formatters and linters
         json!({
@@ -0,0 +1,646 @@
+//! Search quality evaluation (`gofer eval`).
+//!
+//! Runs a golden set of queries with expected files/symbols against the live index
+//! through the daemon and reports recall@k, MRR and nDCG@k per tool. Two configs —
+//! per-tool argument overrides such as `rrf_k`, `diversity` or scoring `weights` —
+//! can be compared on the same set.
+//!
+//! Golden set (YAML or JSON):
+//!
+//! ```yaml
+//! k: 10
+//! queries:
+//!   - query: "circuit breaker pattern implementation"
+//!     files: ["src/error_recovery.rs"]
+//!     symbols: ["CircuitBreaker"]
+//!     tools: [search, smart_file_selection]   # optional, default: all
+//! ```
+//!
+//! Config (YAML or JSON):
+//!
+//! ```yaml
+//! name: no-diversity
+//! search: { diversity: 0.0, max_per_file: 0 }
+//! smart_file_selection: { weights: { vector: 0.5, path: 0.2, symbols: 0.2, summary: 0.1 } }
+//! ```
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::ipc::client::DaemonClient;
+
+/// Default cutoff when neither the golden set nor the CLI sets one
+pub const DEFAULT_K: usize = 10;
+
+/// Ranking tools the harness knows how to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalTool {
+    Search,
+    SearchByPurpose,
+    SmartFileSelection,
+}
+
+impl EvalTool {
+    pub const ALL: [EvalTool; 3] = [
+        EvalTool::Search,
+        EvalTool::SearchByPurpose,
+        EvalTool::SmartFileSelection,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EvalTool::Search => "search",
+            EvalTool::SearchByPurpose => "search_by_purpose",
+            EvalTool::SmartFileSelection => "smart_file_selection",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GoldenSet {
+    #[serde(default)]
+    pub k: Option<usize>,
+    pub queries: Vec<GoldenQuery>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GoldenQuery {
+    pub query: String,
+    /// Expected files, relative to the project root; a trailing `/` matches a directory
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Expected symbol names, matched as whole words in a result
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Tools to run this query against (default: all)
+    #[serde(default)]
+    pub tools: Option<Vec<EvalTool>>,
+}
+
+/// Extra arguments merged into every call of the corresponding tool.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EvalConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub search: Map<String, Value>,
+    #[serde(default)]
+    pub search_by_purpose: Map<String, Value>,
+    #[serde(default)]
+    pub smart_file_selection: Map<String, Value>,
+}
+
+impl EvalConfig {
+    fn overrides(&self, tool: EvalTool) -> &Map<String, Value> {
+        match tool {
+            EvalTool::Search => &self.search,
+            EvalTool::SearchByPurpose => &self.search_by_purpose,
+            EvalTool::SmartFileSelection => &self.smart_file_selection,
+        }
+    }
+
+    fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| "default".to_string())
+    }
+}
+
+/// Metrics for one query against one tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryOutcome {
+    pub query: String,
+    pub tool: EvalTool,
+    pub recall: f64,
+    pub reciprocal_rank: f64,
+    pub ndcg: f64,
+    pub latency_ms: u64,
+    /// Expected files/symbols not found in the top k
+    pub missed: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Mean metrics for one tool over all queries it ran.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSummary {
+    pub tool: EvalTool,
+    pub queries: usize,
+    pub recall_at_k: f64,
+    pub mrr: f64,
+    pub ndcg_at_k: f64,
+    pub mean_latency_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub config: String,
+    pub k: usize,
+    pub summaries: Vec<ToolSummary>,
+    pub outcomes: Vec<QueryOutcome>,
+}
+
+/// Per-query change between two reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryDelta {
+    pub query: String,
+    pub tool: EvalTool,
+    pub reciprocal_rank: (f64, f64),
+    pub recall: (f64, f64),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalDiff {
+    pub baseline: EvalReport,
+    pub candidate: EvalReport,
+    /// Queries whose reciprocal rank or recall changed, largest change first
+    pub changed: Vec<QueryDelta>,
+}
+
+/// Load a golden set or config, picking the format by extension (`.yaml`/`.yml`, else JSON).
+pub fn load_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let is_yaml = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yaml") | Some("yml")
+    );
+    let parsed = if is_yaml {
+        serde_yaml::from_str(&content).map_err(anyhow::Error::from)
+    } else {
+        serde_json::from_str(&content).map_err(anyhow::Error::from)
+    };
+    parsed.with_context(|| format!("Invalid {}", path.display()))
+}
+
+/// Arguments for one eval call. Learned feedback is off unless the config turns it
+/// on, so scores don't depend on how the project has been used.
+fn tool_args(query: &str, config: &EvalConfig, tool: EvalTool, k: usize) -> Value {
+    let mut args = json!({ "query": query, "limit": k, "use_feedback": false });
+    if let Some(obj) = args.as_object_mut() {
+        for (key, value) in config.overrides(tool) {
+            obj.insert(key.clone(), value.clone());
+        }
+    }
+    args
+}
+
+/// Run every golden query against the daemon with `config` applied.
+pub async fn run_eval(
+    client: &mut DaemonClient,
+    project_path: &str,
+    set: &GoldenSet,
+    config: &EvalConfig,
+    k: usize,
+) -> Result<EvalReport> {
+    let mut outcomes = Vec::new();
+
+    for golden in &set.queries {
+        if golden.files.is_empty() && golden.symbols.is_empty() {
+            tracing::warn!("Skipping '{}': no expected files or symbols", golden.query);
+            continue;
+        }
+        let tools = golden
+            .tools
+            .clone()
+            .unwrap_or_else(|| EvalTool::ALL.to_vec());
+
+        for tool in tools {
+            let args = tool_args(&golden.query, config, tool, k);
+
+            let started = Instant::now();
+            let response = client
+                .call(
+                    "tools/call",
+                    json!({
+                        "project_path": project_path,
+                        "name": tool.name(),
+                        "arguments": args,
+                        // Eval searches must not be credited with later reads
+                        "record_feedback": false,
+                    }),
+                )
+                .await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+
+            let ranked = response.and_then(|r| parse_tool_response(tool, &r));
+            outcomes.push(match ranked {
+                Ok(items) => score_query(golden, tool, &items, k, latency_ms),
+                Err(e) => QueryOutcome {
+                    query: golden.query.clone(),
+                    tool,
+                    recall: 0.0,
+                    reciprocal_rank: 0.0,
+                    ndcg: 0.0,
+                    latency_ms,
+                    missed: expected_targets(golden),
+                    error: Some(e.to_string()),
+                },
+            });
+        }
+    }
+
+    Ok(EvalReport {
+        config: config.label(),
+        k,
+        summaries: summarize(&outcomes),
+        outcomes,
+    })
+}
+
+/// Compare two reports of the same golden set.
+pub fn diff_reports(baseline: EvalReport, candidate: EvalReport) -> EvalDiff {
+    let mut changed: Vec<QueryDelta> = Vec::new();
+    for new in &candidate.outcomes {
+        let Some(old) = baseline
+            .outcomes
+            .iter()
+            .find(|o| o.query == new.query && o.tool == new.tool)
+        else {
+            continue;
+        };
+        if (old.reciprocal_rank - new.reciprocal_rank).abs() > f64::EPSILON
+            || (old.recall - new.recall).abs() > f64::EPSILON
+        {
+            changed.push(QueryDelta {
+                query: new.query.clone(),
+                tool: new.tool,
+                reciprocal_rank: (old.reciprocal_rank, new.reciprocal_rank),
+                recall: (old.recall, new.recall),
+            });
+        }
+    }
+    let magnitude = |d: &QueryDelta| {
+        (d.reciprocal_rank.1 - d.reciprocal_rank.0).abs() + (d.recall.1 - d.recall.0).abs()
+    };
+    changed.sort_by(|a, b| {
+        magnitude(b)
+            .partial_cmp(&magnitude(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    EvalDiff {
+        baseline,
+        candidate,
+        changed,
+    }
+}
+
+/// Human-readable report table.
+pub fn format_report(report: &EvalReport) -> String {
+    let mut out = format!("Config: {} (k={})\n", report.config, report.k);
+    out.push_str(&format!(
+        "{:<22} {:>7} {:>10} {:>7} {:>8} {:>9}\n",
+        "tool", "queries", "recall@k", "MRR", "nDCG@k", "avg ms"
+    ));
+    for s in &report.summaries {
+        out.push_str(&format!(
+            "{:<22} {:>7} {:>10.3} {:>7.3} {:>8.3} {:>9.0}\n",
+            s.tool.name(),
+            s.queries,
+            s.recall_at_k,
+            s.mrr,
+            s.ndcg_at_k,
+            s.mean_latency_ms
+        ));
+    }
+
+    let failures: Vec<&QueryOutcome> = report
+        .outcomes
+        .iter()
+        .filter(|o| o.error.is_some() || !o.missed.is_empty())
+        .collect();
+    if !failures.is_empty() {
+        out.push_str("\nMisses:\n");
+        for o in failures {
+            match &o.error {
+                Some(e) => out.push_str(&format!(
+                    "  [{}] {} — error: {}\n",
+                    o.tool.name(),
+                    o.query,
+                    e
+                )),
+                None => out.push_str(&format!(
+                    "  [{}] {} — missing {}\n",
+                    o.tool.name(),
+                    o.query,
+                    o.missed.join(", ")
+                )),
+            }
+        }
+    }
+    out
+}
+
+/// Side-by-side metrics and the queries that moved.
+pub fn format_diff(diff: &EvalDiff) -> String {
+    let mut out = format!(
+        "Baseline: {}  vs  Candidate: {} (k={})\n",
+        diff.baseline.config, diff.candidate.config, diff.candidate.k
+    );
+    out.push_str(&format!(
+        "{:<22} {:>18} {:>18} {:>18}\n",
+        "tool", "recall@k", "MRR", "nDCG@k"
+    ));
+    for new in &diff.candidate.summaries {
+        let Some(old) = diff.baseline.summaries.iter().find(|s| s.tool == new.tool) else {
+            continue;
+        };
+        out.push_str(&format!(
+            "{:<22} {:>18} {:>18} {:>18}\n",
+            new.tool.name(),
+            delta_cell(old.recall_at_k, new.recall_at_k),
+            delta_cell(old.mrr, new.mrr),
+            delta_cell(old.ndcg_at_k, new.ndcg_at_k)
+        ));
+    }
+
+    if !diff.changed.is_empty() {
+        out.push_str("\nChanged queries:\n");
+        for d in &diff.changed {
+            out.push_str(&format!(
+                "  [{}] {} — RR {:.3} → {:.3}, recall {:.3} → {:.3}\n",
+                d.tool.name(),
+                d.query,
+                d.reciprocal_rank.0,
+                d.reciprocal_rank.1,
+                d.recall.0,
+                d.recall.1
+            ));
+        }
+    }
+    out
+}
+
+fn delta_cell(old: f64, new: f64) -> String {
+    format!("{:.3} → {:.3}", old, new)
+}
+
+/// One ranked result: the file it points at and its full text (for symbol matching).
+#[derive(Debug, Clone)]
+struct RankedItem {
+    path: String,
+    text: String,
+}
+
+/// Extract the ranked result list from a `tools/call` response.
+fn parse_tool_response(tool: EvalTool, response: &Value) -> Result<Vec<RankedItem>> {
+    let text = response
+        .pointer("/content/0/text")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if response.get("isError").and_then(|v| v.as_bool()) == Some(true) {
+        anyhow::bail!("{}", text);
+    }
+    let value: Value = serde_json::from_str(text).context("Tool returned non-JSON output")?;
+
+    let entries = |key: &str| -> Vec<String> {
+        value
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let items = match tool {
+        // `search` answers an exact symbol name with `search_symbols` output: { path: [entries] }
+        EvalTool::Search if value.get("mode").and_then(|v| v.as_str()) == Some("exact_symbol") => {
+            value
+                .get("symbols")
+                .and_then(|v| v.as_object())
+                .map(|files| {
+                    files
+                        .iter()
+                        .flat_map(|(path, syms)| {
+                            syms.as_array()
+                                .into_iter()
+                                .flatten()
+                                .map(move |s| RankedItem {
+                                    path: path.clone(),
+                                    text: s.as_str().unwrap_or("").to_string(),
+                                })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        // "path:line[-end] [score] (ctx:..)\ncontent"
+        EvalTool::Search => entries("results")
+            .into_iter()
+            .map(|r| {
+                let location = r.split_whitespace().next().unwrap_or("");
+                let path = location
+                    .rsplit_once(':')
+                    .map(|(p, _)| p)
+                    .unwrap_or(location);
+                RankedItem {
+                    path: path.to_string(),
+                    text: r.clone(),
+                }
+            })
+            .collect(),
+        // "path [score=..] ..."
+        EvalTool::SearchByPurpose | EvalTool::SmartFileSelection => entries("files")
+            .into_iter()
+            .map(|r| RankedItem {
+                path: r.split_whitespace().next().unwrap_or("").to_string(),
+                text: r.clone(),
+            })
+            .collect(),
+    };
+    Ok(items)
+}
+
+fn expected_targets(golden: &GoldenQuery) -> Vec<String> {
+    golden
+        .files
+        .iter()
+        .chain(&golden.symbols)
+        .cloned()
+        .collect()
+}
+
+/// Score one ranked list. Each expected file/symbol is a target; a result is relevant
+/// if it satisfies a target not already satisfied by a higher-ranked result.
+fn score_query(
+    golden: &GoldenQuery,
+    tool: EvalTool,
+    items: &[RankedItem],
+    k: usize,
+    latency_ms: u64,
+) -> QueryOutcome {
+    let targets = expected_targets(golden);
+    let file_count = golden.files.len();
+    let satisfies = |item: &RankedItem, idx: usize| -> bool {
+        if idx < file_count {
+            path_matches(&item.path, &targets[idx])
+        } else {
+            contains_word(&item.text, &targets[idx])
+        }
+    };
+
+    let mut found: HashSet<usize> = HashSet::new();
+    let mut reciprocal_rank = 0.0;
+    let mut dcg = 0.0;
+    for (rank, item) in items.iter().take(k).enumerate() {
+        let hits: Vec<usize> = (0..targets.len()).filter(|&i| satisfies(item, i)).collect();
+        if hits.is_empty() {
+            continue;
+        }
+        if reciprocal_rank == 0.0 {
+            reciprocal_rank = 1.0 / (rank + 1) as f64;
+        }
+        let new_hits = hits.iter().filter(|&&i| found.insert(i)).count();
+        if new_hits > 0 {
+            dcg += 1.0 / ((rank + 2) as f64).log2();
+        }
+    }
+
+    let ideal: f64 = (0..targets.len().min(k))
+        .map(|rank| 1.0 / ((rank + 2) as f64).log2())
+        .sum();
+
+    QueryOutcome {
+        query: golden.query.clone(),
+        tool,
+        recall: found.len() as f64 / targets.len() as f64,
+        reciprocal_rank,
+        ndcg: if ideal > 0.0 { dcg / ideal } else { 0.0 },
+        latency_ms,
+        missed: (0..targets.len())
+            .filter(|i| !found.contains(i))
+            .map(|i| targets[i].clone())
+            .collect(),
+        error: None,
+    }
+}
+
+fn summarize(outcomes: &[QueryOutcome]) -> Vec<ToolSummary> {
+    EvalTool::ALL
+        .iter()
+        .filter_map(|&tool| {
+            let rows: Vec<&QueryOutcome> = outcomes.iter().filter(|o| o.tool == tool).collect();
+            if rows.is_empty() {
+                return None;
+            }
+            let n = rows.len() as f64;
+            let mean = |f: fn(&QueryOutcome) -> f64| rows.iter().map(|o| f(o)).sum::<f64>() / n;
+            Some(ToolSummary {
+                tool,
+                queries: rows.len(),
+                recall_at_k: mean(|o| o.recall),
+                mrr: mean(|o| o.reciprocal_rank),
+                ndcg_at_k: mean(|o| o.ndcg),
+                mean_latency_ms: mean(|o| o.latency_ms as f64),
+            })
+        })
+        .collect()
+}
+
+fn path_matches(path: &str, expected: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    let expected = expected.trim_start_matches("./");
+    if expected.ends_with('/') {
+        return path.starts_with(expected) || path.contains(&format!("/{}", expected));
+    }
+    path == expected || path.ends_with(&format!("/{}", expected))
+}
+
+fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(pos, _)| {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let before = text[..pos].chars().next_back().is_none_or(|c| !is_ident(c));
+        let after = text[pos + word.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !is_ident(c));
+        before && after
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, text: &str) -> RankedItem {
+        RankedItem {
+            path: path.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn golden(files: &[&str], symbols: &[&str]) -> GoldenQuery {
+        GoldenQuery {
+            query: "q".to_string(),
+            files: files.iter().map(|s| s.to_string()).collect(),
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            tools: None,
+        }
+    }
+
+    #[test]
+    fn test_tool_args_disable_feedback() {
+        let config = EvalConfig::default();
+        let args = tool_args("q", &config, EvalTool::SmartFileSelection, 5);
+        assert_eq!(
+            args,
+            json!({ "query": "q", "limit": 5, "use_feedback": false })
+        );
+
+        // A config can still evaluate with feedback on purpose
+        let mut config = EvalConfig::default();
+        config
+            .smart_file_selection
+            .insert("use_feedback".to_string(), json!(true));
+        let args = tool_args("q", &config, EvalTool::SmartFileSelection, 5);
+        assert_eq!(args["use_feedback"], json!(true));
+    }
+
+    #[test]
+    fn test_score_query_metrics() {
+        let g = golden(&["src/cache.rs", "src/storage/"], &["LruCache"]);
+        let items = vec![
+            item("src/main.rs", "fn main()"),
+            item("src/cache.rs", "pub struct LruCache<K, V>"),
+            item("src/cache.rs", "impl LruCache"),
+            item("src/storage/sqlite.rs", "pub struct SqliteStorage"),
+        ];
+
+        let outcome = score_query(&g, EvalTool::Search, &items, 10, 0);
+        assert!((outcome.recall - 1.0).abs() < 1e-9);
+        assert!((outcome.reciprocal_rank - 0.5).abs() < 1e-9);
+        // Relevant at ranks 2 and 4 against an ideal of ranks 1..3
+        let dcg = 1.0 / 3f64.log2() + 1.0 / 5f64.log2();
+        let ideal = 1.0 + 1.0 / 3f64.log2() + 1.0 / 4f64.log2();
+        assert!((outcome.ndcg - dcg / ideal).abs() < 1e-9);
+
+        let cut = score_query(&g, EvalTool::Search, &items, 1, 0);
+        assert_eq!(cut.reciprocal_rank, 0.0);
+        assert_eq!(cut.missed.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_search_results() {
+        let response = json!({
+            "content": [{ "type": "text", "text": json!({
+                "results": [
+                    "src/cache.rs:42 [score=1.000] (ctx:LruCache)\npub struct LruCache",
+                    "src/ipc/server.rs:10-30\nfn handle()"
+                ]
+            }).to_string() }]
+        });
+        let items = parse_tool_response(EvalTool::Search, &response).unwrap();
+        let paths: Vec<&str> = items.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/cache.rs", "src/ipc/server.rs"]);
+        assert!(contains_word(&items[0].text, "LruCache"));
+        assert!(!contains_word("LruCacheEntry", "LruCache"));
+    }
+}
@@ -0,0 +1,887 @@
+//! HTTP calls made by frontend code: `fetch` and friends, axios/ky/ofetch
+//! instances created with a `baseURL`, project wrappers such as
+//! `apiFetch(url)`, generated `createClient` clients, SWR/TanStack Query keys
+//! and Nuxt `useFetch`.
+//!
+//! Each file is parsed once. Module-level constants, client instances and
+//! wrapper functions are recorded as bases so that calls made through them in
+//! other files can be prefixed at query time, following the import that
+//! brought the client into scope. Template literals are rendered with their
+//! interpolations kept as `${expr}`, which `normalize_api_path` turns into
+//! `:param`.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use tree_sitter::{Node, Parser};
+
+use super::domains::{normalize_api_path, ParsedApiCall};
+use super::parser::SupportedLanguage;
+use super::routes::{arguments, collect, line, named, string_value, text, HTTP_METHODS};
+use crate::models::{ApiBaseInfo, FrontendApiCallInfo};
+
+/// Functions called as `f(url, options)` or `f({ url, method })`
+const FETCH_FUNCTIONS: &[&str] = &[
+    "fetch",
+    "$fetch",
+    "ofetch",
+    "ky",
+    "axios",
+    "useFetch",
+    "useLazyFetch",
+];
+
+/// Objects called as `obj.get(url)` or `obj.request({ url, method })` without
+/// being created in the file
+const CLIENT_OBJECTS: &[&str] = &[
+    "axios", "ky", "ofetch", "$fetch", "api", "http", "$http", "$axios", "$api",
+];
+
+/// Hooks whose key is the URL they fetch: `useSWR("/api/user", fetcher)`
+const KEY_HOOKS: &[&str] = &[
+    "useSWR",
+    "useSWRImmutable",
+    "useSWRInfinite",
+    "useSWRMutation",
+];
+
+/// TanStack Query functions taking `{ queryKey }`; a key starting with a URL
+/// is what the default `queryFn` fetches
+const QUERY_HOOKS: &[&str] = &[
+    "useQuery",
+    "useSuspenseQuery",
+    "useInfiniteQuery",
+    "useSuspenseInfiniteQuery",
+    "queryOptions",
+    "infiniteQueryOptions",
+    "prefetchQuery",
+    "fetchQuery",
+    "ensureQueryData",
+];
+
+/// Calls creating a client from `{ baseURL }`-style options
+const CLIENT_FACTORIES: &[&str] = &[
+    "axios.create",
+    "ky.create",
+    "ky.extend",
+    "ofetch.create",
+    "$fetch.create",
+    "createClient",
+    "createFetch",
+];
+
+const BASE_KEYS: &[&str] = &["baseURL", "baseUrl", "prefixUrl", "prefix"];
+
+/// A constant, client instance or wrapper function other files may call
+/// through, with the URL prefix it adds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiBase {
+    pub name: String,
+    pub base_url: String,
+    pub line: u32,
+}
+
+/// API calls of one file and the bases it defines.
+#[derive(Debug, Default)]
+pub struct FileCalls {
+    pub calls: Vec<ParsedApiCall>,
+    pub bases: Vec<ApiBase>,
+}
+
+/// Calls and bases of a TypeScript, JavaScript or Vue file.
+pub fn parse_api_calls(content: &str, language: SupportedLanguage) -> FileCalls {
+    let (script, offset) = match language {
+        SupportedLanguage::TypeScript | SupportedLanguage::JavaScript => (content, 0),
+        SupportedLanguage::Vue => match vue_script(content) {
+            Some(script) => script,
+            None => return FileCalls::default(),
+        },
+        _ => return FileCalls::default(),
+    };
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&language.tree_sitter_language())
+        .is_err()
+    {
+        return FileCalls::default();
+    }
+    let Some(tree) = parser.parse(script, None) else {
+        return FileCalls::default();
+    };
+    let root = tree.root_node();
+
+    let mut module = Module {
+        src: script,
+        imports: HashMap::new(),
+        consts: HashMap::new(),
+        clients: HashMap::new(),
+        wrappers: Vec::new(),
+    };
+    let mut bases = module.definitions(root);
+    for base in &mut bases {
+        base.line += offset;
+    }
+
+    let mut nodes = Vec::new();
+    collect(root, &["call_expression"], &mut nodes);
+    let calls = nodes
+        .into_iter()
+        // The call inside a wrapper is reported where the wrapper is called
+        .filter(|n| !module.wrappers.iter().any(|r| r.contains(&n.start_byte())))
+        .filter_map(|n| {
+            let call = module.call(n)?;
+            module.finish(call, line(n) + offset)
+        })
+        .collect();
+    FileCalls { calls, bases }
+}
+
+/// Prefix calls made through clients defined in other files with their base
+/// URL. A call is matched to a base of the same name in the module it was
+/// imported from. Calls to functions that are neither a known base nor one of
+/// the configured `wrappers` are dropped.
+pub fn resolve_api_calls(
+    calls: Vec<FrontendApiCallInfo>,
+    bases: &[ApiBaseInfo],
+    wrappers: &[String],
+) -> Vec<FrontendApiCallInfo> {
+    calls
+        .into_iter()
+        .filter_map(|mut call| {
+            let Some(client) = call.client.clone() else {
+                return Some(call);
+            };
+            let base = bases.iter().find(|b| {
+                b.name == client
+                    && imports_module(&call.file_path, call.client_source.as_deref(), &b.file_path)
+            });
+            match base {
+                Some(base) => {
+                    call.path = join_url(&base.base_url, &call.path);
+                    call.path_pattern = Some(normalize_api_path(strip_query(&call.path)));
+                    Some(call)
+                }
+                None if !call.needs_client || wrappers.contains(&client) => Some(call),
+                None => None,
+            }
+        })
+        .collect()
+}
+
+/// First `<script>` block of a Vue SFC and the line it starts on
+fn vue_script(content: &str) -> Option<(&str, u32)> {
+    static SCRIPT: OnceLock<Regex> = OnceLock::new();
+    let script = SCRIPT.get_or_init(|| Regex::new(r"(?s)<script[^>]*>(.*?)</script>").unwrap());
+    let body = script.captures(content)?.get(1)?;
+    let offset = content[..body.start()].matches('\n').count() as u32;
+    Some((body.as_str(), offset))
+}
+
+/// Client a call goes through and the prefix it adds
+#[derive(Debug, Clone, Default)]
+struct Client {
+    base: String,
+    method: Option<String>,
+}
+
+/// A recognized call whose URL is not checked yet
+struct Call {
+    path: String,
+    method: Option<String>,
+    /// Imported object or function the call goes through
+    client: Option<String>,
+    /// Only an API call if `client` turns out to be a wrapper
+    needs_client: bool,
+    type_used: Option<String>,
+}
+
+impl Call {
+    fn new(path: String, method: Option<String>) -> Self {
+        Self {
+            path,
+            method,
+            client: None,
+            needs_client: false,
+            type_used: None,
+        }
+    }
+}
+
+struct Module<'s> {
+    src: &'s str,
+    /// Local name -> module specifier
+    imports: HashMap<String, String>,
+    /// Module-level URL constants
+    consts: HashMap<String, String>,
+    clients: HashMap<String, Client>,
+    /// Bodies of wrapper functions
+    wrappers: Vec<Range<usize>>,
+}
+
+impl<'s> Module<'s> {
+    /// Record imports, URL constants, client instances and wrappers of the
+    /// module; returns the ones other files can call through.
+    fn definitions(&mut self, root: Node<'_>) -> Vec<ApiBase> {
+        let mut declarators = Vec::new();
+        let mut functions = Vec::new();
+        for statement in named(root) {
+            let statement = match statement.kind() {
+                "export_statement" => match statement.child_by_field_name("declaration") {
+                    Some(declaration) => declaration,
+                    None => continue,
+                },
+                _ => statement,
+            };
+            match statement.kind() {
+                "import_statement" => self.import(statement),
+                "lexical_declaration" | "variable_declaration" => declarators.extend(
+                    named(statement)
+                        .into_iter()
+                        .filter(|d| d.kind() == "variable_declarator"),
+                ),
+                "function_declaration" => {
+                    if let Some(name) = statement.child_by_field_name("name") {
+                        functions.push((name, statement));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut bases = Vec::new();
+        let declared: Vec<(Node<'_>, Node<'_>)> = declarators
+            .iter()
+            .filter_map(|d| {
+                let name = d.child_by_field_name("name")?;
+                let value = unwrap(d.child_by_field_name("value")?);
+                (name.kind() == "identifier").then_some((name, value))
+            })
+            .collect();
+        for &(name, value) in &declared {
+            if let Some(url) = self.render(value).filter(|u| is_url(u)) {
+                self.define(name, url.clone(), &mut bases);
+                self.consts.insert(text(name, self.src).to_string(), url);
+            }
+        }
+        for &(name, value) in &declared {
+            if let Some(base) = self.client_base(value) {
+                self.define(name, base.clone(), &mut bases);
+                self.clients.insert(
+                    text(name, self.src).to_string(),
+                    Client { base, method: None },
+                );
+            }
+        }
+        let functions = declared
+            .into_iter()
+            .filter(|(_, value)| matches!(value.kind(), "arrow_function" | "function_expression"))
+            .chain(functions);
+        for (name, function) in functions {
+            let Some(client) = self.wrapper(function) else {
+                continue;
+            };
+            if let Some(body) = function.child_by_field_name("body") {
+                self.wrappers.push(body.byte_range());
+            }
+            self.define(name, client.base.clone(), &mut bases);
+            self.clients
+                .insert(text(name, self.src).to_string(), client);
+        }
+        bases
+    }
+
+    fn define(&self, name: Node<'_>, base_url: String, bases: &mut Vec<ApiBase>) {
+        bases.push(ApiBase {
+            name: text(name, self.src).to_string(),
+            base_url,
+            line: line(name),
+        });
+    }
+
+    /// `import api, { http as client } from "./api"`
+    fn import(&mut self, statement: Node<'_>) {
+        let Some(source) = statement
+            .child_by_field_name("source")
+            .and_then(|s| string_value(s, self.src))
+        else {
+            return;
+        };
+        let Some(clause) = named(statement)
+            .into_iter()
+            .find(|n| n.kind() == "import_clause")
+        else {
+            return;
+        };
+        let mut names = Vec::new();
+        for part in named(clause) {
+            match part.kind() {
+                "identifier" => names.push(part),
+                "namespace_import" => {
+                    names.extend(named(part).into_iter().filter(|n| n.kind() == "identifier"))
+                }
+                "named_imports" => names.extend(
+                    named(part)
+                        .into_iter()
+                        .filter(|s| s.kind() == "import_specifier")
+                        .filter_map(|s| {
+                            s.child_by_field_name("alias")
+                                .or_else(|| s.child_by_field_name("name"))
+                        }),
+                ),
+                _ => {}
+            }
+        }
+        for name in names {
+            self.imports
+                .insert(text(name, self.src).to_string(), source.clone());
+        }
+    }
+
+    /// Base URL of `axios.create({ baseURL })`, `new Client({ baseUrl })` or
+    /// `createClient(...)`
+    fn client_base(&self, value: Node<'_>) -> Option<String> {
+        let callee = match value.kind() {
+            "call_expression" => value.child_by_field_name("function")?,
+            "new_expression" => value.child_by_field_name("constructor")?,
+            _ => return None,
+        };
+        let factory = CLIENT_FACTORIES.contains(&text(callee, self.src));
+        if value.kind() == "call_expression" && !factory {
+            return None;
+        }
+        let base = arguments(value)
+            .into_iter()
+            .find(|a| a.kind() == "object")
+            .and_then(|options| {
+                BASE_KEYS
+                    .iter()
+                    .find_map(|k| property(options, k, self.src))
+            });
+        match base {
+            Some(base) => {
+                let url = self.render_part(base);
+                let (_, path) = split_base(&url);
+                Some(path.to_string())
+            }
+            None if factory => Some(String::new()),
+            None => None,
+        }
+    }
+
+    /// A function passing its first parameter as the URL of a recognized call,
+    /// after an optional constant prefix: `(path) => fetch(`${API}${path}`)`
+    fn wrapper(&self, function: Node<'_>) -> Option<Client> {
+        let param = first_param(function, self.src)?;
+        let body = function.child_by_field_name("body")?;
+        let marker = format!("${{{param}}}");
+        let mut calls = Vec::new();
+        collect(body, &["call_expression"], &mut calls);
+        calls.into_iter().find_map(|c| {
+            let call = self.call(c).filter(|c| !c.needs_client)?;
+            let (prefix, _) = call.path.split_once(&marker)?;
+            (!prefix.contains("${")).then(|| Client {
+                base: prefix.to_string(),
+                method: call.method,
+            })
+        })
+    }
+
+    fn call(&self, node: Node<'_>) -> Option<Call> {
+        let function = node.child_by_field_name("function")?;
+        let args = arguments(node);
+        let mut call = match function.kind() {
+            "identifier" => self.function_call(text(function, self.src), &args)?,
+            "member_expression" => {
+                let object = function.child_by_field_name("object")?;
+                let property = text(function.child_by_field_name("property")?, self.src);
+                self.method_call(object, property, &args)?
+            }
+            _ => return None,
+        };
+        call.type_used = node
+            .child_by_field_name("type_arguments")
+            .and_then(|t| named(t).into_iter().next())
+            .map(|t| text(t, self.src).to_string());
+        Some(call)
+    }
+
+    /// `fetch(url)`, `api(url)`, `useSWR(key)`, `useQuery({ queryKey })` or
+    /// `getJson(url)` through an imported function
+    fn function_call(&self, name: &str, args: &[Node<'_>]) -> Option<Call> {
+        let first = args.first().copied()?;
+        if FETCH_FUNCTIONS.contains(&name) || self.clients.contains_key(name) {
+            let client = self.clients.get(name).cloned().unwrap_or_default();
+            let (path, method) = match first.kind() {
+                "object" => self.config(first)?,
+                _ => (self.render_part(first), self.option_method(args.get(1))),
+            };
+            return Some(Call::new(
+                join_url(&client.base, &path),
+                method.or(client.method),
+            ));
+        }
+        if KEY_HOOKS.contains(&name) {
+            return Some(Call::new(self.key(first), Some("GET".to_string())));
+        }
+        if QUERY_HOOKS.contains(&name) {
+            return self.query_key(first);
+        }
+        if !matches!(
+            first.kind(),
+            "string" | "template_string" | "binary_expression"
+        ) {
+            return None;
+        }
+        Some(Call {
+            path: self.render_part(first),
+            method: self.option_method(args.get(1)),
+            client: Some(name.to_string()),
+            needs_client: true,
+            type_used: None,
+        })
+    }
+
+    /// `api.get(url)`, `client.GET(url)`, `http.request({ url })` or
+    /// `queryClient.prefetchQuery({ queryKey })`
+    fn method_call(&self, object: Node<'_>, property: &str, args: &[Node<'_>]) -> Option<Call> {
+        let first = args.first().copied()?;
+        if QUERY_HOOKS.contains(&property) {
+            return self.query_key(first);
+        }
+        let verb = HTTP_METHODS
+            .iter()
+            .find(|m| m.eq_ignore_ascii_case(property))
+            .map(|m| m.to_uppercase());
+        if verb.is_none() && property != "request" {
+            return None;
+        }
+        // `this.http.get`, `nuxtApp.$api.get`
+        let name = match object.kind() {
+            "identifier" => text(object, self.src),
+            "member_expression" => text(object.child_by_field_name("property")?, self.src),
+            _ => return None,
+        };
+        let (base, client) = if let Some(client) = self.clients.get(name) {
+            (client.base.as_str(), None)
+        } else if object.kind() == "identifier" && self.imports.contains_key(name) {
+            ("", Some(name.to_string()))
+        } else if CLIENT_OBJECTS.contains(&name) {
+            ("", None)
+        } else {
+            return None;
+        };
+        let (path, method) = match verb {
+            Some(verb) => (self.render_part(first), Some(verb)),
+            None => self.config(first)?,
+        };
+        Some(Call {
+            client,
+            ..Call::new(join_url(base, &path), method)
+        })
+    }
+
+    /// `{ url, method }` request config
+    fn config(&self, options: Node<'_>) -> Option<(String, Option<String>)> {
+        let url =
+            property(options, "url", self.src).or_else(|| property(options, "path", self.src))?;
+        Some((self.render_part(url), self.option_method(Some(&options))))
+    }
+
+    /// `method` of a `{ method: "POST" }` options object
+    fn option_method(&self, options: Option<&Node<'_>>) -> Option<String> {
+        let options = options.filter(|o| o.kind() == "object")?;
+        let method = property(*options, "method", self.src)?;
+        string_value(method, self.src).map(|m| m.to_uppercase())
+    }
+
+    /// First element of a `["/api/user", id]` key, or the key itself
+    fn key(&self, key: Node<'_>) -> String {
+        let key = match key.kind() {
+            "array" => named(key).into_iter().next().unwrap_or(key),
+            _ => key,
+        };
+        self.render_part(key)
+    }
+
+    fn query_key(&self, options: Node<'_>) -> Option<Call> {
+        let key = match options.kind() {
+            "object" => property(options, "queryKey", self.src)?,
+            _ => options,
+        };
+        Some(Call::new(self.key(key), Some("GET".to_string())))
+    }
+
+    /// Check the URL of a call: a leading unknown `${BASE}` is dropped, and
+    /// resolved later when `BASE` is imported.
+    fn finish(&self, call: Call, line: u32) -> Option<ParsedApiCall> {
+        let (lead, rest) = split_base(&call.path);
+        let (path, client) = match lead {
+            Some(expr) if rest.starts_with('/') => {
+                let client = call
+                    .client
+                    .or_else(|| self.imports.contains_key(expr).then(|| expr.to_string()));
+                (rest.to_string(), client)
+            }
+            Some(_) => return None,
+            None => (call.path, call.client),
+        };
+        if !is_url(&path) {
+            return None;
+        }
+        let client_source = client.as_ref().and_then(|c| self.imports.get(c)).cloned();
+        Some(ParsedApiCall {
+            method: call.method,
+            path_pattern: normalize_api_path(strip_query(&path)),
+            path,
+            type_used: call.type_used,
+            line,
+            client,
+            client_source,
+            needs_client: call.needs_client,
+        })
+    }
+
+    /// Value of a string expression, with unknown parts kept as `${expr}`;
+    /// `None` when nothing of it is known.
+    fn render(&self, node: Node<'_>) -> Option<String> {
+        match node.kind() {
+            "string" => string_value(node, self.src),
+            "template_string" => {
+                let mut out = String::new();
+                let mut at = node.start_byte() + 1;
+                for part in named(node) {
+                    if part.kind() != "template_substitution" {
+                        continue;
+                    }
+                    out.push_str(&self.src[at..part.start_byte()]);
+                    if let Some(expr) = named(part).into_iter().next() {
+                        out.push_str(&self.render_part(expr));
+                    }
+                    at = part.end_byte();
+                }
+                let end = node.end_byte().saturating_sub(1).max(at);
+                out.push_str(&self.src[at..end]);
+                Some(out)
+            }
+            "binary_expression" => {
+                let operator = node.child_by_field_name("operator")?;
+                if text(operator, self.src) != "+" {
+                    return None;
+                }
+                let left = self.render_part(node.child_by_field_name("left")?);
+                let right = self.render_part(node.child_by_field_name("right")?);
+                Some(left + &right)
+            }
+            "identifier" => self.consts.get(text(node, self.src)).cloned(),
+            "parenthesized_expression" | "as_expression" | "non_null_expression" => {
+                self.render(named(node).into_iter().next()?)
+            }
+            _ => None,
+        }
+    }
+
+    fn render_part(&self, node: Node<'_>) -> String {
+        self.render(node)
+            .unwrap_or_else(|| format!("${{{}}}", text(node, self.src)))
+    }
+}
+
+/// Value of `key` in an object literal, `{ key }` shorthand included
+fn property<'t>(object: Node<'t>, key: &str, src: &str) -> Option<Node<'t>> {
+    named(object)
+        .into_iter()
+        .find_map(|pair| match pair.kind() {
+            "pair" => {
+                let name = pair.child_by_field_name("key")?;
+                let name = string_value(name, src).unwrap_or_else(|| text(name, src).to_string());
+                (name == key).then(|| pair.child_by_field_name("value"))?
+            }
+            "shorthand_property_identifier" => (text(pair, src) == key).then_some(pair),
+            _ => None,
+        })
+}
+
+fn first_param(function: Node<'_>, src: &str) -> Option<String> {
+    if let Some(param) = function.child_by_field_name("parameter") {
+        return Some(text(param, src).to_string());
+    }
+    let param = named(function.child_by_field_name("parameters")?)
+        .into_iter()
+        .next()?;
+    let name = match param.kind() {
+        "identifier" => param,
+        _ => param.child_by_field_name("pattern")?,
+    };
+    (name.kind() == "identifier").then(|| text(name, src).to_string())
+}
+
+/// `await x`, `x as T` and `(x)` around a declared value
+fn unwrap(node: Node<'_>) -> Node<'_> {
+    match node.kind() {
+        "await_expression"
+        | "as_expression"
+        | "parenthesized_expression"
+        | "satisfies_expression" => named(node).into_iter().next().map(unwrap).unwrap_or(node),
+        _ => node,
+    }
+}
+
+/// `${API_URL}/users` -> (`API_URL`, `/users`)
+fn split_base(url: &str) -> (Option<&str>, &str) {
+    url.strip_prefix("${")
+        .and_then(|rest| rest.split_once('}'))
+        .map_or((None, url), |(expr, path)| (Some(expr), path))
+}
+
+fn is_url(s: &str) -> bool {
+    s.starts_with('/') || s.starts_with("http://") || s.starts_with("https://")
+}
+
+fn strip_query(path: &str) -> &str {
+    path.split(['?', '#']).next().unwrap_or_default()
+}
+
+/// `/api/` + `/users` -> `/api/users`; `ky`'s `prefixUrl` takes paths
+/// without a leading slash.
+fn join_url(base: &str, path: &str) -> String {
+    if base.is_empty() {
+        return path.to_string();
+    }
+    if path.is_empty() {
+        return base.to_string();
+    }
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// Whether `specifier`, imported in `importer`, refers to `file`. Relative
+/// specifiers are resolved against the importer's directory; aliases such as
+/// `@/` and `~/` match by path suffix. Without a specifier (auto-imports) any
+/// file matches.
+fn imports_module(importer: &str, specifier: Option<&str>, file: &str) -> bool {
+    let Some(specifier) = specifier else {
+        return true;
+    };
+    let file = module_path(file);
+    if specifier.starts_with('.') {
+        let mut parts: Vec<&str> = importer.split('/').collect();
+        parts.pop();
+        for part in specifier.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        return module_path(&parts.join("/")) == file;
+    }
+    let target = match specifier.split_once('/') {
+        Some(("@" | "~" | "~~" | "@@" | "#", rest)) => rest.to_string(),
+        Some(("$lib", rest)) => format!("lib/{rest}"),
+        _ => specifier.to_string(),
+    };
+    let target = module_path(&target);
+    file == target || file.ends_with(&format!("/{target}"))
+}
+
+/// `web/src/api/index.ts` -> `web/src/api`
+fn module_path(path: &str) -> &str {
+    let path = path.trim_start_matches("./");
+    let path = match path.rsplit_once('.') {
+        Some((stem, ext))
+            if matches!(ext, "ts" | "tsx" | "js" | "jsx" | "mjs" | "mts" | "vue")
+                && !stem.ends_with('/') =>
+        {
+            stem
+        }
+        _ => path,
+    };
+    path.strip_suffix("/index").unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(content: &str) -> Vec<(Option<String>, String, String, u32)> {
+        parse_api_calls(content, SupportedLanguage::TypeScript)
+            .calls
+            .into_iter()
+            .map(|c| (c.method, c.path, c.path_pattern, c.line))
+            .collect()
+    }
+
+    fn get(path: &str, pattern: &str, line: u32) -> (Option<String>, String, String, u32) {
+        (
+            Some("GET".to_string()),
+            path.to_string(),
+            pattern.to_string(),
+            line,
+        )
+    }
+
+    #[test]
+    fn test_instances_and_wrappers() {
+        let content = r#"
+import axios from "axios";
+const API = "/api/v1";
+export const api = axios.create({ baseURL: `${import.meta.env.VITE_URL}/api` });
+
+export async function apiFetch<T>(path: string, init?: RequestInit): Promise<T> {
+    const res = await fetch(`${API}${path}`, init);
+    return res.json();
+}
+
+api.get<User>(`/users/${id}`);
+apiFetch("/orders?page=2", { method: "post" });
+fetch(API + "/health");
+axios.request({ url: "/raw", method: "delete" });
+"#;
+        let parsed = parse_api_calls(content, SupportedLanguage::TypeScript);
+        let bases: Vec<(&str, &str)> = parsed
+            .bases
+            .iter()
+            .map(|b| (b.name.as_str(), b.base_url.as_str()))
+            .collect();
+        assert_eq!(
+            bases,
+            vec![("API", "/api/v1"), ("api", "/api"), ("apiFetch", "/api/v1")]
+        );
+        assert_eq!(parsed.calls[0].type_used.as_deref(), Some("User"));
+        assert_eq!(
+            calls(content),
+            vec![
+                get("/api/users/${id}", "/api/users/:param", 10),
+                (
+                    Some("POST".to_string()),
+                    "/api/v1/orders?page=2".to_string(),
+                    "/api/v1/orders".to_string(),
+                    11
+                ),
+                (
+                    None,
+                    "/api/v1/health".to_string(),
+                    "/api/v1/health".to_string(),
+                    12
+                ),
+                (
+                    Some("DELETE".to_string()),
+                    "/raw".to_string(),
+                    "/raw".to_string(),
+                    13
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_hooks_and_generated_clients() {
+        let content = r#"
+const client = createClient<paths>({ baseUrl: "https://api.example.com/v2" });
+const { data } = useQuery({ queryKey: ["/api/todos", id], queryFn });
+queryClient.prefetchQuery({ queryKey: [`/api/todos/${id}`] });
+const { data: user } = useSWR(`/api/users/${id}`, fetcher);
+const { data: post } = await useFetch(`/api/posts/${route.params.id}`);
+client.GET("/pets/{petId}", { params });
+useQuery({ queryKey: ["todos"] });
+"#;
+        assert_eq!(
+            calls(content),
+            vec![
+                get("/api/todos", "/api/todos", 2),
+                get("/api/todos/${id}", "/api/todos/:param", 3),
+                get("/api/users/${id}", "/api/users/:param", 4),
+                (
+                    None,
+                    "/api/posts/${route.params.id}".to_string(),
+                    "/api/posts/:param".to_string(),
+                    5
+                ),
+                get(
+                    "https://api.example.com/v2/pets/{petId}",
+                    "https://api.example.com/v2/pets/:param",
+                    6
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vue_script_lines() {
+        let content = "<template>\n  <div/>\n</template>\n<script setup lang=\"ts\">\nconst { data } = await useFetch('/api/me')\n</script>\n";
+        let parsed = parse_api_calls(content, SupportedLanguage::Vue);
+        assert_eq!(parsed.calls.len(), 1);
+        assert_eq!(parsed.calls[0].path, "/api/me");
+        assert_eq!(parsed.calls[0].line, 4);
+    }
+
+    #[test]
+    fn test_resolve_imported_clients() {
+        let content = r#"
+import { http } from "@/lib/http";
+import { getJson } from "../api/client";
+import { BASE } from "./config";
+
+http.post("/users", body);
+getJson(`/users/${id}`);
+fetch(`${BASE}/status`);
+navigate("/home");
+"#;
+        let parsed = parse_api_calls(content, SupportedLanguage::TypeScript);
+        let stored: Vec<FrontendApiCallInfo> = parsed
+            .calls
+            .into_iter()
+            .map(|c| FrontendApiCallInfo {
+                id: 0,
+                method: c.method,
+                path: c.path,
+                path_pattern: Some(c.path_pattern),
+                file_id: 0,
+                file_path: "web/src/pages/users.ts".to_string(),
+                line: Some(c.line as i32),
+                client: c.client,
+                client_source: c.client_source,
+                needs_client: c.needs_client,
+            })
+            .collect();
+        assert_eq!(stored.len(), 4);
+        let base = |name: &str, base_url: &str, file_path: &str| ApiBaseInfo {
+            name: name.to_string(),
+            base_url: base_url.to_string(),
+            file_path: file_path.to_string(),
+        };
+        let bases = vec![
+            base("http", "/api", "web/src/lib/http.ts"),
+            base("getJson", "/api/v1", "web/src/api/client/index.ts"),
+            base("getJson", "/other", "web/src/other/client.ts"),
+            base("BASE", "https://example.com/svc", "web/src/pages/config.ts"),
+        ];
+
+        let resolved = resolve_api_calls(stored.clone(), &bases, &[]);
+        let paths: Vec<&str> = resolved.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/api/users",
+                "/api/v1/users/${id}",
+                "https://example.com/svc/status"
+            ]
+        );
+        assert_eq!(
+            resolved[1].path_pattern.as_deref(),
+            Some("/api/v1/users/:param")
+        );
+
+        // Configured wrappers keep calls whose definition was not found
+        let resolved = resolve_api_calls(stored, &[], &["navigate".to_string()]);
+        let paths: Vec<&str> = resolved.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["/users", "/status", "/home"]);
+    }
+}
@@ -0,0 +1,888 @@
+//! API contracts: endpoints declared in OpenAPI/Swagger specs, GraphQL schemas
+//! and `.proto` services, next to the routes framework code registers and the
+//! calls clients make.
+//!
+//! Every indexed file contributes endpoints tagged with their source (`code`,
+//! `openapi`, `graphql`, `proto`) and client calls; `get_api_routes` joins the
+//! two sides at query time, so a spec edit never forces re-reading handlers.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::api_calls::{parse_api_calls, ApiBase};
+use super::domains::{
+    normalize_api_path, parse_backend_routes, paths_match, ParsedApiCall, ParsedEndpoint,
+};
+use super::parser::idl;
+use super::parser::{DocumentKind, SourceKind, SupportedLanguage};
+use super::routes::{merge_routes, parse_framework_routes};
+use crate::models::{ApiEndpointInfo, FrontendApiCallInfo, SymbolWithPath};
+
+pub const SOURCE_CODE: &str = "code";
+pub const SOURCE_OPENAPI: &str = "openapi";
+pub const SOURCE_GRAPHQL: &str = "graphql";
+pub const SOURCE_PROTO: &str = "proto";
+
+/// `api_endpoints.method` of a proto rpc (its path is `/package.Service/Method`)
+pub const METHOD_RPC: &str = "RPC";
+
+const OPENAPI_METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "patch", "head", "options", "trace",
+];
+
+/// Endpoints a file declares or implements, and the API calls it makes.
+#[derive(Debug, Default)]
+pub struct FileApi {
+    pub source: &'static str,
+    pub endpoints: Vec<ParsedEndpoint>,
+    pub calls: Vec<ParsedApiCall>,
+    /// Client instances and wrappers other files call through
+    pub bases: Vec<ApiBase>,
+}
+
+/// Endpoints and calls of one indexed file.
+pub fn extract_api(path: &str, content: &str, kind: SourceKind) -> FileApi {
+    match kind {
+        SourceKind::Code(language) => {
+            let ext = Path::new(path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("");
+            // Empty for languages other than TypeScript, JavaScript and Vue
+            let mut client = parse_api_calls(content, language);
+            if matches!(
+                language,
+                SupportedLanguage::TypeScript
+                    | SupportedLanguage::JavaScript
+                    | SupportedLanguage::Vue
+            ) {
+                client.calls.extend(graphql_template_calls(content));
+            }
+            let mut endpoints = parse_backend_routes(content, ext);
+            merge_routes(
+                &mut endpoints,
+                parse_framework_routes(path, content, language),
+            );
+            FileApi {
+                source: SOURCE_CODE,
+                endpoints,
+                calls: client.calls,
+                bases: client.bases,
+            }
+        }
+        SourceKind::Document(DocumentKind::Yaml) if is_openapi(content) => FileApi {
+            source: SOURCE_OPENAPI,
+            endpoints: parse_openapi(content, false),
+            calls: Vec::new(),
+            bases: Vec::new(),
+        },
+        SourceKind::Document(DocumentKind::Json) if is_openapi(content) => FileApi {
+            source: SOURCE_OPENAPI,
+            endpoints: parse_openapi(content, true),
+            calls: Vec::new(),
+            bases: Vec::new(),
+        },
+        SourceKind::Document(DocumentKind::GraphQl) => FileApi {
+            source: SOURCE_GRAPHQL,
+            endpoints: parse_graphql_schema(content),
+            calls: parse_graphql_operations(content, 0),
+            bases: Vec::new(),
+        },
+        SourceKind::Document(DocumentKind::Proto) => FileApi {
+            source: SOURCE_PROTO,
+            endpoints: parse_proto_services(content),
+            calls: Vec::new(),
+            bases: Vec::new(),
+        },
+        SourceKind::Document(_) => FileApi::default(),
+    }
+}
+
+// === OpenAPI / Swagger ===
+
+/// Cheap check before parsing: a top-level `openapi:`/`swagger:` key.
+pub fn is_openapi(content: &str) -> bool {
+    let mut end = content.len().min(4096);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    MARKER
+        .get_or_init(|| Regex::new(r#"(?m)^\s*["']?(openapi|swagger)["']?\s*:"#).unwrap())
+        .is_match(&content[..end])
+}
+
+/// Operations under `paths`, with the server/base path prepended. The
+/// operationId becomes the handler name; `$ref`'d request and response
+/// schemas become the types.
+pub fn parse_openapi(content: &str, is_json: bool) -> Vec<ParsedEndpoint> {
+    let spec: Option<serde_yaml::Value> = if is_json {
+        serde_json::from_str::<serde_json::Value>(content)
+            .ok()
+            .and_then(|v| serde_yaml::to_value(v).ok())
+    } else {
+        serde_yaml::from_str(content).ok()
+    };
+    let Some(spec) = spec else {
+        return Vec::new();
+    };
+    if spec.get("openapi").is_none() && spec.get("swagger").is_none() {
+        return Vec::new();
+    }
+    let Some(paths) = spec.get("paths").and_then(|p| p.as_mapping()) else {
+        return Vec::new();
+    };
+    let base = base_path(&spec);
+    let lines: Vec<&str> = content.lines().collect();
+    let paths_line = key_line(&lines, "paths", 0).unwrap_or(0);
+
+    let mut endpoints = Vec::new();
+    for (path, item) in paths {
+        let Some(path) = path.as_str() else {
+            continue;
+        };
+        let path_line = key_line(&lines, path, paths_line).unwrap_or(paths_line);
+        let Some(item) = item.as_mapping() else {
+            continue;
+        };
+        for (method, operation) in item {
+            let Some(method) = method
+                .as_str()
+                .filter(|m| OPENAPI_METHODS.contains(&m.to_ascii_lowercase().as_str()))
+            else {
+                continue;
+            };
+            let str_field = |key: &str| operation.get(key).and_then(|v| v.as_str());
+            let request_type = operation
+                .get("requestBody")
+                .and_then(|b| b.get("content"))
+                .and_then(first_schema_ref)
+                .or_else(|| {
+                    // Swagger 2: `in: body` parameter
+                    operation
+                        .get("parameters")?
+                        .as_sequence()?
+                        .iter()
+                        .find(|p| p.get("in").and_then(|v| v.as_str()) == Some("body"))
+                        .and_then(|p| p.get("schema"))
+                        .and_then(schema_ref)
+                });
+            let response_type = operation.get("responses").and_then(|responses| {
+                // YAML reads an unquoted `200:` as a number
+                ["200", "201", "202", "2XX", "default"]
+                    .iter()
+                    .filter_map(|code| {
+                        responses.get(*code).or_else(|| {
+                            let number = code.parse::<u64>().ok()?;
+                            responses.get(serde_yaml::Value::from(number))
+                        })
+                    })
+                    .find_map(|r| {
+                        r.get("content")
+                            .and_then(first_schema_ref)
+                            .or_else(|| r.get("schema").and_then(schema_ref))
+                    })
+            });
+            endpoints.push(ParsedEndpoint {
+                method: method.to_ascii_uppercase(),
+                path: format!("{}{}", base, path),
+                handler: str_field("operationId").map(str::to_string),
+                request_type,
+                response_type,
+                line: key_line(&lines, method, path_line).unwrap_or(path_line) as u32,
+            });
+        }
+    }
+    endpoints
+}
+
+/// `basePath` (Swagger 2) or the path of the first `servers` URL (OpenAPI 3),
+/// without a trailing slash.
+fn base_path(spec: &serde_yaml::Value) -> String {
+    let raw = spec
+        .get("basePath")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            spec.get("servers")?
+                .as_sequence()?
+                .first()?
+                .get("url")?
+                .as_str()
+        })
+        .unwrap_or("");
+    let path = match raw.find("://") {
+        Some(scheme) => raw[scheme + 3..]
+            .find('/')
+            .map_or("", |p| &raw[scheme + 3 + p..]),
+        None => raw,
+    };
+    if path.starts_with('/') {
+        path.trim_end_matches('/').to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Schema name of the first media type of a `content` map.
+fn first_schema_ref(content: &serde_yaml::Value) -> Option<String> {
+    content
+        .as_mapping()?
+        .values()
+        .find_map(|media| media.get("schema").and_then(schema_ref))
+}
+
+/// `#/components/schemas/User` → `User`, looking through array `items`.
+fn schema_ref(schema: &serde_yaml::Value) -> Option<String> {
+    let reference = schema
+        .get("$ref")
+        .or_else(|| schema.get("items")?.get("$ref"))?
+        .as_str()?;
+    reference.rsplit('/').next().map(str::to_string)
+}
+
+/// 0-based line of the first `key:` (YAML) or `"key":` (JSON) at or after `from`.
+fn key_line(lines: &[&str], key: &str, from: usize) -> Option<usize> {
+    let quoted = [format!("\"{}\"", key), format!("'{}'", key)];
+    (from..lines.len()).find(|&i| {
+        let line = lines[i].trim_start();
+        let rest = quoted
+            .iter()
+            .find_map(|q| line.strip_prefix(q.as_str()))
+            .or_else(|| line.strip_prefix(key));
+        rest.is_some_and(|r| r.trim_start().starts_with(':'))
+    })
+}
+
+// === GraphQL ===
+
+/// Fields of the root operation types: `QUERY user`, `MUTATION createUser`.
+pub fn parse_graphql_schema(content: &str) -> Vec<ParsedEndpoint> {
+    let blocks = idl::graphql_schema(content);
+    let roots = idl::graphql_root_types(&blocks);
+    let mut endpoints = Vec::new();
+    for block in &blocks {
+        let Some((operation, _)) = roots.iter().find(|(_, name)| *name == block.name) else {
+            continue;
+        };
+        if !matches!(block.keyword.as_str(), "type" | "interface") {
+            continue;
+        }
+        for field in &block.members {
+            endpoints.push(ParsedEndpoint {
+                method: operation.to_ascii_uppercase(),
+                path: field.name.clone(),
+                handler: Some(field.name.clone()),
+                request_type: None,
+                response_type: Some(field.type_name.clone()).filter(|t| !t.is_empty()),
+                line: field.line as u32,
+            });
+        }
+    }
+    endpoints
+}
+
+/// Root fields requested by the operations of a GraphQL document, as calls;
+/// `line_offset` is added to every line.
+fn parse_graphql_operations(content: &str, line_offset: usize) -> Vec<ParsedApiCall> {
+    idl::graphql_operations(content)
+        .into_iter()
+        .flat_map(|op| {
+            let method = op.kind.to_ascii_uppercase();
+            op.fields
+                .into_iter()
+                .map(move |(field, line)| ParsedApiCall {
+                    method: Some(method.clone()),
+                    path_pattern: field.clone(),
+                    path: field,
+                    type_used: None,
+                    line: (line + line_offset) as u32,
+                    ..Default::default()
+                })
+        })
+        .collect()
+}
+
+/// Operations inside `gql`/`graphql` tagged templates of JS/TS code.
+fn graphql_template_calls(content: &str) -> Vec<ParsedApiCall> {
+    if !content.contains("gql") && !content.contains("graphql") {
+        return Vec::new();
+    }
+    static TEMPLATE: OnceLock<Regex> = OnceLock::new();
+    static EXPR: OnceLock<Regex> = OnceLock::new();
+    let template = TEMPLATE.get_or_init(|| Regex::new(r"\b(?:gql|graphql)\s*(?:\(\s*)?`").unwrap());
+    let expr = EXPR.get_or_init(|| Regex::new(r"\$\{[^}]*\}").unwrap());
+    let mut calls = Vec::new();
+    for m in template.find_iter(content) {
+        let body_start = m.end();
+        let Some(len) = content[body_start..].find('`') else {
+            break;
+        };
+        // Interpolated fragments are not part of the operation text
+        let body = expr.replace_all(&content[body_start..body_start + len], "");
+        let line = content[..body_start].matches('\n').count();
+        calls.extend(parse_graphql_operations(&body, line));
+    }
+    calls
+}
+
+// === Protocol Buffers ===
+
+/// One `RPC /package.Service/Method` endpoint per rpc, plus one per
+/// `google.api.http` binding.
+pub fn parse_proto_services(content: &str) -> Vec<ParsedEndpoint> {
+    let file = idl::proto_definitions(content);
+    let package = file.package.map(|p| format!("{}.", p)).unwrap_or_default();
+    let mut endpoints = Vec::new();
+    for service in file.blocks.iter().filter(|b| b.keyword == "service") {
+        for rpc in &service.members {
+            let endpoint = |method: &str, path: String| ParsedEndpoint {
+                method: method.to_string(),
+                path,
+                handler: Some(rpc.name.clone()),
+                request_type: rpc.input.clone(),
+                response_type: Some(rpc.type_name.clone()).filter(|t| !t.is_empty()),
+                line: rpc.line as u32,
+            };
+            endpoints.push(endpoint(
+                METHOD_RPC,
+                format!("/{}{}/{}", package, service.name, rpc.name),
+            ));
+            for (method, path) in &rpc.http {
+                endpoints.push(endpoint(method, path.clone()));
+            }
+        }
+    }
+    endpoints
+}
+
+// === Linking ===
+
+/// Whether an endpoint is a GraphQL field or proto rpc rather than an HTTP route.
+pub fn is_operation(method: &str) -> bool {
+    matches!(method, "QUERY" | "MUTATION" | "SUBSCRIPTION" | METHOD_RPC)
+}
+
+/// Whether two endpoint/call pairs refer to the same route: same method (when
+/// both know it) and matching paths, ignoring query strings, trailing slashes
+/// and parameter syntax (`{id}`, `:id`, `<id>`, `${id}`).
+pub fn same_route(
+    method_a: Option<&str>,
+    path_a: &str,
+    method_b: Option<&str>,
+    path_b: &str,
+) -> bool {
+    if let (Some(a), Some(b)) = (method_a, method_b) {
+        if !a.eq_ignore_ascii_case(b) {
+            return false;
+        }
+    }
+    if is_operation(method_a.unwrap_or("")) || is_operation(method_b.unwrap_or("")) {
+        return path_a == path_b;
+    }
+    paths_match(&route_path(path_a), &route_path(path_b))
+}
+
+/// Like `same_route`, but a client path may carry a prefix the spec leaves to
+/// the server (`/api/v1/users/:id` calls `/users/{id}`). Needs at least two
+/// spec segments so `/` or `/{id}` do not match everything.
+pub fn call_matches(
+    spec_method: &str,
+    spec_path: &str,
+    call_method: Option<&str>,
+    call_path: &str,
+) -> bool {
+    if same_route(Some(spec_method), spec_path, call_method, call_path) {
+        return true;
+    }
+    if is_operation(spec_method) {
+        return false;
+    }
+    let spec = route_path(spec_path);
+    let call = route_path(call_path);
+    let spec_segments = spec.split('/').filter(|s| !s.is_empty()).count();
+    if spec_segments < 2 {
+        return false;
+    }
+    let call_segments: Vec<&str> = call.split('/').filter(|s| !s.is_empty()).collect();
+    if call_segments.len() <= spec_segments {
+        return false;
+    }
+    let tail = format!(
+        "/{}",
+        call_segments[call_segments.len() - spec_segments..].join("/")
+    );
+    same_route(Some(spec_method), &spec, call_method, &tail)
+}
+
+/// Path of a relative client call with query string and trailing slash
+/// removed; `None` for absolute URLs and paths built from a variable, whose
+/// target cannot be known statically.
+pub fn client_path(path: &str) -> Option<String> {
+    path.starts_with('/').then(|| route_path(path))
+}
+
+fn route_path(path: &str) -> String {
+    let path = path.split(['?', '#']).next().unwrap_or("");
+    let normalized = normalize_api_path(path);
+    let trimmed = normalized.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Symbol names that may implement an operation or serve as its generated
+/// client: `GetUser` → `GetUser`, `getUser`, `get_user`; GraphQL fields also
+/// get graphene's `resolve_` form.
+pub fn handler_names(source: &str, name: &str) -> Vec<String> {
+    let snake = to_snake(name);
+    let mut names = vec![name.to_string(), lower_first(name), snake.clone()];
+    let mut upper = name.to_string();
+    if let Some(first) = upper.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    names.push(upper);
+    if source == SOURCE_GRAPHQL {
+        names.push(format!("resolve_{}", snake));
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Spec endpoints linked to their implementation and clients, and the drift
+/// between spec and code.
+#[derive(Debug, Default)]
+pub struct ContractReport {
+    /// `GET /users/{id} [openapi api.yaml:12 getUser] -> src/users.rs:40 get_user; 2 clients`
+    pub contracts: Vec<String>,
+    /// Spec endpoints no route or handler implements
+    pub not_implemented: Vec<String>,
+    /// Client calls to paths or fields the specs do not declare
+    pub not_in_spec: Vec<String>,
+}
+
+/// Handler names to look up for the spec endpoints (see `contract_report`).
+pub fn contract_handler_names(endpoints: &[ApiEndpointInfo]) -> Vec<String> {
+    let mut names: Vec<String> = endpoints
+        .iter()
+        .filter(|e| e.source != SOURCE_CODE)
+        .filter_map(|e| Some(handler_names(&e.source, e.handler.as_deref()?)))
+        .flatten()
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Link every spec endpoint to the code route with the same method and path,
+/// or else to a function named after its operationId, field or rpc; count the
+/// client calls and generated client functions that target it; and report
+/// drift. `functions` are the matches for `contract_handler_names`.
+pub fn contract_report(
+    endpoints: &[ApiEndpointInfo],
+    calls: &[FrontendApiCallInfo],
+    functions: &[SymbolWithPath],
+) -> ContractReport {
+    let (code, specs): (Vec<&ApiEndpointInfo>, Vec<&ApiEndpointInfo>) =
+        endpoints.iter().partition(|e| e.source == SOURCE_CODE);
+    let mut report = ContractReport::default();
+    if specs.is_empty() {
+        return report;
+    }
+
+    for spec in &specs {
+        let names = spec
+            .handler
+            .as_deref()
+            .map(|h| handler_names(&spec.source, h))
+            .unwrap_or_default();
+        let named: Vec<&SymbolWithPath> = functions
+            .iter()
+            .filter(|f| names.contains(&f.name) && f.file_path != spec.file_path)
+            .collect();
+        let route = code.iter().find(|c| {
+            !is_operation(&spec.method)
+                && same_route(Some(&spec.method), &spec.path, Some(&c.method), &c.path)
+        });
+        let implementation = route
+            .map(|c| {
+                let handler = c.handler.as_deref().unwrap_or_default();
+                format!("{} {}", location(&c.file_path, c.line), handler)
+                    .trim_end()
+                    .to_string()
+            })
+            .or_else(|| {
+                named
+                    .iter()
+                    .find(|f| !is_generated_path(&f.file_path))
+                    .map(|f| format!("{}:{} {}", f.file_path, f.line + 1, f.name))
+            });
+
+        let generated_clients = named
+            .iter()
+            .filter(|f| is_generated_path(&f.file_path))
+            .count();
+        let call_clients = calls
+            .iter()
+            .filter(|c| {
+                let path = if is_operation(&spec.method) {
+                    Some(c.path.clone())
+                } else {
+                    client_path(&c.path)
+                };
+                path.is_some_and(|p| {
+                    call_matches(&spec.method, &spec.path, c.method.as_deref(), &p)
+                })
+            })
+            .count();
+
+        let declared = format!(
+            "{} {} [{} {}{}]",
+            spec.method,
+            spec.path,
+            spec.source,
+            location(&spec.file_path, spec.line),
+            spec.handler
+                .as_deref()
+                .map(|h| format!(" {}", h))
+                .unwrap_or_default()
+        );
+        let mut line = format!(
+            "{} -> {}",
+            declared,
+            implementation.as_deref().unwrap_or("NOT IMPLEMENTED")
+        );
+        let clients = generated_clients + call_clients;
+        if clients > 0 {
+            line.push_str(&format!(
+                "; {} client{}",
+                clients,
+                if clients == 1 { "" } else { "s" }
+            ));
+        }
+        report.contracts.push(line);
+
+        // A proto HTTP binding is served by its rpc, which is reported itself
+        let binding = spec.source == SOURCE_PROTO && spec.method != METHOD_RPC;
+        if implementation.is_none() && !binding {
+            report.not_implemented.push(declared);
+        }
+    }
+
+    let http_specs: Vec<&&ApiEndpointInfo> =
+        specs.iter().filter(|e| !is_operation(&e.method)).collect();
+    let graphql_specs: Vec<&&ApiEndpointInfo> = specs
+        .iter()
+        .filter(|e| e.source == SOURCE_GRAPHQL)
+        .collect();
+    for call in calls {
+        let method = call.method.as_deref();
+        let missing = if is_operation(method.unwrap_or("")) {
+            !graphql_specs.is_empty()
+                && !graphql_specs
+                    .iter()
+                    .any(|e| same_route(Some(&e.method), &e.path, method, &call.path))
+        } else {
+            match client_path(&call.path) {
+                Some(path) if !http_specs.is_empty() => !http_specs
+                    .iter()
+                    .any(|e| call_matches(&e.method, &e.path, method, &path)),
+                _ => false,
+            }
+        };
+        if missing {
+            report.not_in_spec.push(format!(
+                "{} {} {}",
+                method.unwrap_or("GET"),
+                call.path,
+                location(&call.file_path, call.line)
+            ));
+        }
+    }
+    report
+}
+
+/// `path:line`, 1-based, from a stored 0-based line.
+fn location(path: &str, line: Option<i32>) -> String {
+    format!("{}:{}", path, line.unwrap_or(0) + 1)
+}
+
+/// Whether a file looks generated from a contract (protoc/grpc output, API
+/// clients from openapi-generator and friends).
+pub fn is_generated_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    [
+        "_pb2.py",
+        "_pb2_grpc.py",
+        ".pb.go",
+        "_grpc.pb.go",
+        "_pb.js",
+        "_pb.ts",
+        "_pb.d.ts",
+        "_grpc_web_pb",
+        ".pb.rs",
+        "__generated__/",
+        "/generated/",
+        "/gen/",
+    ]
+    .iter()
+    .any(|marker| lower.contains(marker))
+        || lower.starts_with("generated/")
+        || lower.starts_with("gen/")
+}
+
+fn lower_first(name: &str) -> String {
+    let mut out = name.to_string();
+    if let Some(first) = out.get_mut(..1) {
+        first.make_ascii_lowercase();
+    }
+    out
+}
+
+fn to_snake(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev_lower =
+                i > 0 && (chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit());
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if i > 0 && (prev_lower || (next_lower && chars[i - 1].is_ascii_uppercase())) {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_yaml_and_json_operations() {
+        let yaml = r#"openapi: 3.0.0
+servers:
+  - url: https://api.example.com/v1/
+paths:
+  /users/{id}:
+    parameters:
+      - name: id
+    get:
+      operationId: getUser
+      responses:
+        200:
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/User'
+    delete:
+      operationId: deleteUser
+"#;
+        let endpoints = parse_openapi(yaml, false);
+        let summary: Vec<_> = endpoints
+            .iter()
+            .map(|e| {
+                (
+                    e.method.as_str(),
+                    e.path.as_str(),
+                    e.handler.as_deref(),
+                    e.response_type.as_deref(),
+                    e.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("GET", "/v1/users/{id}", Some("getUser"), Some("User"), 7),
+                ("DELETE", "/v1/users/{id}", Some("deleteUser"), None, 15),
+            ]
+        );
+
+        let json = r##"{
+  "swagger": "2.0",
+  "basePath": "/api",
+  "paths": {
+    "/orders": {
+      "post": {
+        "parameters": [{"in": "body", "schema": {"$ref": "#/definitions/NewOrder"}}]
+      }
+    }
+  }
+}"##;
+        assert!(is_openapi(json));
+        let endpoints = parse_openapi(json, true);
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].path, "/api/orders");
+        assert_eq!(endpoints[0].request_type.as_deref(), Some("NewOrder"));
+        assert_eq!(endpoints[0].line, 5);
+        assert!(!is_openapi("name: ci\non: push\n"));
+    }
+
+    #[test]
+    fn test_graphql_and_proto_endpoints() {
+        let sdl =
+            "schema { query: Root }\ntype Root {\n  me: User\n}\ntype Query { ignored: Int }\n";
+        let endpoints = parse_graphql_schema(sdl);
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(
+            (endpoints[0].method.as_str(), endpoints[0].path.as_str()),
+            ("QUERY", "me")
+        );
+
+        let proto = "package shop.v1;\nservice Orders {\n  rpc GetOrder(GetOrderRequest) returns (Order) {\n    option (google.api.http) = { get: \"/v1/orders/{id}\" };\n  }\n}\n";
+        let endpoints = parse_proto_services(proto);
+        let routes: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                ("RPC", "/shop.v1.Orders/GetOrder", 2),
+                ("GET", "/v1/orders/{id}", 2)
+            ]
+        );
+        assert_eq!(
+            endpoints[0].request_type.as_deref(),
+            Some("GetOrderRequest")
+        );
+    }
+
+    #[test]
+    fn test_graphql_operations_in_templates() {
+        let ts = "const Q = gql`\n  query Me {\n    me { id }\n  }\n  ${FRAGMENT}\n`;\n";
+        let calls = graphql_template_calls(ts);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].method.as_deref(), Some("QUERY"));
+        assert_eq!(calls[0].path, "me");
+        assert_eq!(calls[0].line, 2);
+    }
+
+    #[test]
+    fn test_contract_report_links_and_drift() {
+        let endpoint =
+            |method: &str, path: &str, source: &str, handler: Option<&str>, file: &str| {
+                ApiEndpointInfo {
+                    id: 0,
+                    method: method.to_string(),
+                    path: path.to_string(),
+                    source: source.to_string(),
+                    handler: handler.map(str::to_string),
+                    request_type: None,
+                    response_type: None,
+                    file_id: 0,
+                    file_path: file.to_string(),
+                    line: Some(0),
+                }
+            };
+        let endpoints = vec![
+            endpoint(
+                "GET",
+                "/users/{id}",
+                SOURCE_OPENAPI,
+                Some("getUser"),
+                "api.yaml",
+            ),
+            endpoint(
+                "DELETE",
+                "/users/{id}",
+                SOURCE_OPENAPI,
+                Some("deleteUser"),
+                "api.yaml",
+            ),
+            endpoint(
+                "GET",
+                "/users/:id",
+                SOURCE_CODE,
+                Some("show_user"),
+                "src/users.rs",
+            ),
+            endpoint(
+                "RPC",
+                "/Users/Ping",
+                SOURCE_PROTO,
+                Some("Ping"),
+                "users.proto",
+            ),
+        ];
+        let call = |method: &str, path: &str| FrontendApiCallInfo {
+            id: 0,
+            method: Some(method.to_string()),
+            path: path.to_string(),
+            path_pattern: None,
+            file_id: 0,
+            file_path: "web/api.ts".to_string(),
+            line: Some(4),
+            client: None,
+            client_source: None,
+            needs_client: false,
+        };
+        let calls = vec![call("GET", "/users/${id}"), call("POST", "/orders")];
+        let functions = vec![SymbolWithPath {
+            id: 0,
+            name: "ping".to_string(),
+            kind: crate::models::SymbolKind::Function,
+            line: 9,
+            end_line: 12,
+            signature: None,
+            file_path: "src/grpc.rs".to_string(),
+        }];
+        assert!(contract_handler_names(&endpoints).contains(&"ping".to_string()));
+
+        let report = contract_report(&endpoints, &calls, &functions);
+        assert_eq!(
+            report.contracts,
+            vec![
+                "GET /users/{id} [openapi api.yaml:1 getUser] -> src/users.rs:1 show_user; 1 client",
+                "DELETE /users/{id} [openapi api.yaml:1 deleteUser] -> NOT IMPLEMENTED",
+                "RPC /Users/Ping [proto users.proto:1 Ping] -> src/grpc.rs:10 ping",
+            ]
+        );
+        assert_eq!(
+            report.not_implemented,
+            vec!["DELETE /users/{id} [openapi api.yaml:1 deleteUser]"]
+        );
+        assert_eq!(report.not_in_spec, vec!["POST /orders web/api.ts:5"]);
+    }
+
+    #[test]
+    fn test_route_matching() {
+        assert!(same_route(
+            Some("GET"),
+            "/users/{id}",
+            Some("get"),
+            "/users/${userId}/"
+        ));
+        assert!(!same_route(Some("POST"), "/users", Some("GET"), "/users"));
+        assert!(same_route(Some("GET"), "/users", None, "/users?page=2"));
+        assert!(call_matches(
+            "GET",
+            "/users/{id}",
+            Some("GET"),
+            "/api/v1/users/7"
+        ));
+        assert!(!call_matches("GET", "/{id}", None, "/api/anything"));
+        assert_eq!(client_path("https://example.com/users"), None);
+        assert_eq!(
+            handler_names(SOURCE_GRAPHQL, "createUser"),
+            vec![
+                "CreateUser",
+                "createUser",
+                "create_user",
+                "resolve_create_user"
+            ]
+        );
+        assert_eq!(to_snake("GetHTTPStatus"), "get_http_status");
+        assert!(is_generated_path("web/src/gen/users_pb.ts"));
+        assert!(!is_generated_path("src/api/users.rs"));
+    }
+}
@@ -0,0 +1,392 @@
+//! Layering between project modules, declared under `[architecture]` in
+//! `.gofer/rules.toml`, and the module coupling of the import graph.
+//!
+//! ```toml
+//! [architecture]
+//! layers = ["api -> service -> repo"]   # a layer may only import the ones after it
+//! forbid = ["ui -> db"]                 # ui must not import db
+//!
+//! [architecture.modules]
+//! api = "src/api"
+//! service = ["src/service", "src/jobs"]
+//! repo = "src/repo/**"
+//! ui = "frontend"                       # a domain works too
+//! db = "src/db"
+//! ```
+//!
+//! A name without a `modules` entry is used as a selector itself (domain,
+//! glob or directory). Like other rules, `[architecture]` takes `message`,
+//! `severity`, `paths` and `exclude`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Deserialize;
+
+use super::rules::{one_or_many, selects, FileImport, Scope};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Architecture {
+    #[serde(flatten)]
+    pub scope: Scope,
+    /// Module name -> domains, globs or directories of its files
+    #[serde(default)]
+    pub modules: BTreeMap<String, Selectors>,
+    /// Chains like `api -> service -> repo`
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub layers: Vec<String>,
+    /// Edges like `ui -> db`
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub forbid: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Selectors(#[serde(deserialize_with = "one_or_many")] pub Vec<String>);
+
+impl Architecture {
+    /// Whether any layering is declared; modules alone only group files.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty() && self.forbid.is_empty()
+    }
+
+    /// First declared module containing `path`
+    pub fn module_of(&self, path: &str, domain: &str) -> Option<&str> {
+        self.modules
+            .iter()
+            .find(|(_, s)| s.0.iter().any(|s| selects(s, path, domain)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn contains(&self, name: &str, path: &str, domain: &str) -> bool {
+        match self.modules.get(name) {
+            Some(selectors) => selectors.0.iter().any(|s| selects(s, path, domain)),
+            None => selects(name, path, domain),
+        }
+    }
+
+    /// Why an import from `from` to `to` breaks the declared layering
+    pub fn check_edge(
+        &self,
+        from: &str,
+        from_domain: &str,
+        to: &str,
+        to_domain: &str,
+    ) -> Option<String> {
+        if !self.scope.applies_to(from) {
+            return None;
+        }
+        for chain in &self.layers {
+            let layers = split_chain(chain);
+            let position = |path: &str, domain: &str| {
+                layers
+                    .iter()
+                    .position(|name| self.contains(name, path, domain))
+            };
+            if let (Some(i), Some(j)) = (position(from, from_domain), position(to, to_domain)) {
+                if i > j {
+                    return Some(format!(
+                        "{} must not import {}; layers: {}",
+                        layers[i],
+                        layers[j],
+                        layers.join(" -> ")
+                    ));
+                }
+            }
+        }
+        for edge in &self.forbid {
+            if let [a, b] = split_chain(edge)[..] {
+                if self.contains(a, from, from_domain)
+                    && self.contains(b, to, to_domain)
+                    && !self.contains(b, from, from_domain)
+                {
+                    return Some(format!("{} must not import {}", a, b));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn split_chain(chain: &str) -> Vec<&str> {
+    chain
+        .split("->")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Fan-in/fan-out of one module or directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coupling {
+    pub name: String,
+    pub files: usize,
+    /// Other modules importing this one
+    pub fan_in: usize,
+    /// Other modules this one imports
+    pub fan_out: usize,
+}
+
+/// Modules that import each other, with one import edge per direction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub modules: Vec<String>,
+    /// `path:line -> target`
+    pub edges: Vec<String>,
+}
+
+/// An indexed file and its resolved imports
+pub struct GraphFile {
+    pub path: String,
+    pub domain: String,
+    pub imports: Vec<FileImport>,
+}
+
+/// Module of a file: its declared module, else its directory cut to `depth`
+/// components.
+pub fn unit_of(architecture: &Architecture, path: &str, domain: &str, depth: usize) -> String {
+    if let Some(module) = architecture.module_of(path, domain) {
+        return module.to_string();
+    }
+    let dirs: Vec<&str> = path.split('/').collect();
+    let dirs = &dirs[..dirs.len() - 1];
+    if dirs.is_empty() {
+        ".".to_string()
+    } else {
+        dirs[..dirs.len().min(depth.max(1))].join("/")
+    }
+}
+
+/// Coupling of every module (most coupled first) and the import cycles
+/// between modules.
+pub fn coupling(
+    architecture: &Architecture,
+    files: &[GraphFile],
+    depth: usize,
+) -> (Vec<Coupling>, Vec<Cycle>) {
+    let domains: HashMap<&str, &str> = files
+        .iter()
+        .map(|f| (f.path.as_str(), f.domain.as_str()))
+        .collect();
+    let unit = |path: &str| {
+        let domain = domains.get(path).copied().unwrap_or("unknown");
+        unit_of(architecture, path, domain, depth)
+    };
+
+    let mut sizes: BTreeMap<String, usize> = BTreeMap::new();
+    // (from, to) -> first import edge
+    let mut edges: BTreeMap<(String, String), String> = BTreeMap::new();
+    for file in files {
+        let from = unit(&file.path);
+        *sizes.entry(from.clone()).or_default() += 1;
+        for import in &file.imports {
+            for target in &import.targets {
+                let to = unit(target);
+                if to != from {
+                    edges.entry((from.clone(), to)).or_insert_with(|| {
+                        format!("{}:{} -> {}", file.path, import.line + 1, target)
+                    });
+                }
+            }
+        }
+    }
+
+    let mut out: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut fan_in: HashMap<&str, usize> = HashMap::new();
+    for (from, to) in edges.keys() {
+        out.entry(from).or_default().insert(to);
+        *fan_in.entry(to).or_default() += 1;
+    }
+    let mut modules: Vec<Coupling> = sizes
+        .iter()
+        .map(|(name, files)| Coupling {
+            name: name.clone(),
+            files: *files,
+            fan_in: fan_in.get(name.as_str()).copied().unwrap_or(0),
+            fan_out: out.get(name.as_str()).map(|s| s.len()).unwrap_or(0),
+        })
+        .collect();
+    modules.sort_by(|a, b| {
+        (b.fan_in + b.fan_out)
+            .cmp(&(a.fan_in + a.fan_out))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let cycles = strongly_connected(&out)
+        .into_iter()
+        .map(|members| {
+            let edges = edges
+                .iter()
+                .filter(|((from, to), _)| {
+                    members.contains(&from.as_str()) && members.contains(&to.as_str())
+                })
+                .map(|(_, edge)| edge.clone())
+                .collect();
+            Cycle {
+                modules: members.into_iter().map(String::from).collect(),
+                edges,
+            }
+        })
+        .collect();
+    (modules, cycles)
+}
+
+/// Tarjan's algorithm; only components with more than one node.
+fn strongly_connected<'a>(graph: &BTreeMap<&'a str, BTreeSet<&'a str>>) -> Vec<Vec<&'a str>> {
+    struct State<'a> {
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        components: Vec<Vec<&'a str>>,
+    }
+
+    fn visit<'a>(
+        node: &'a str,
+        graph: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+        state: &mut State<'a>,
+    ) {
+        let index = state.index.len();
+        state.index.insert(node, index);
+        state.low.insert(node, index);
+        state.stack.push(node);
+        state.on_stack.insert(node);
+        for &next in graph.get(node).into_iter().flatten() {
+            if !state.index.contains_key(next) {
+                visit(next, graph, state);
+                let low = state.low[node].min(state.low[next]);
+                state.low.insert(node, low);
+            } else if state.on_stack.contains(next) {
+                let low = state.low[node].min(state.index[next]);
+                state.low.insert(node, low);
+            }
+        }
+        if state.low[node] == index {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            if component.len() > 1 {
+                component.sort();
+                state.components.push(component);
+            }
+        }
+    }
+
+    let mut state = State {
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        components: Vec::new(),
+    };
+    for &node in graph.keys() {
+        if !state.index.contains_key(node) {
+            visit(node, graph, &mut state);
+        }
+    }
+    state.components.sort();
+    state.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn architecture() -> Architecture {
+        toml::from_str(
+            r#"
+layers = "api -> service -> repo"
+forbid = ["frontend -> src/db"]
+
+[modules]
+api = "src/api"
+service = ["src/service", "src/jobs"]
+repo = "src/repo/**"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn import(line: u32, target: &str) -> FileImport {
+        FileImport {
+            line,
+            specifier: target.to_string(),
+            targets: vec![target.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_check_edge() {
+        let arch = architecture();
+        assert_eq!(
+            arch.check_edge(
+                "src/api/users.rs",
+                "backend",
+                "src/repo/users.rs",
+                "backend"
+            ),
+            None
+        );
+        assert_eq!(
+            arch.check_edge(
+                "src/repo/users.rs",
+                "backend",
+                "src/jobs/sync.rs",
+                "backend"
+            )
+            .as_deref(),
+            Some("repo must not import service; layers: api -> service -> repo")
+        );
+        assert_eq!(
+            arch.check_edge("web/app.ts", "frontend", "src/db/pool.ts", "backend")
+                .as_deref(),
+            Some("frontend must not import src/db")
+        );
+        assert_eq!(
+            arch.check_edge("web/app.ts", "frontend", "web/api.ts", "frontend"),
+            None
+        );
+        assert_eq!(
+            arch.module_of("src/jobs/sync.rs", "backend"),
+            Some("service")
+        );
+    }
+
+    #[test]
+    fn test_coupling() {
+        let arch = architecture();
+        let file = |path: &str, imports| GraphFile {
+            path: path.to_string(),
+            domain: "backend".to_string(),
+            imports,
+        };
+        let files = vec![
+            file("src/api/users.rs", vec![import(2, "src/service/users.rs")]),
+            file("src/service/users.rs", vec![import(0, "src/util/log.rs")]),
+            file("src/util/log.rs", vec![import(4, "src/jobs/flush.rs")]),
+            file("src/jobs/flush.rs", Vec::new()),
+            file("main.rs", vec![import(0, "src/api/users.rs")]),
+        ];
+
+        let (modules, cycles) = coupling(&arch, &files, 2);
+        let service = modules.iter().find(|m| m.name == "service").unwrap();
+        assert_eq!((service.files, service.fan_in, service.fan_out), (2, 2, 1));
+        assert_eq!(modules[0].name, "service");
+        assert_eq!(
+            cycles,
+            vec![Cycle {
+                modules: vec!["service".to_string(), "src/util".to_string()],
+                edges: vec![
+                    "src/service/users.rs:1 -> src/util/log.rs".to_string(),
+                    "src/util/log.rs:5 -> src/jobs/flush.rs".to_string(),
+                ],
+            }]
+        );
+        assert_eq!(unit_of(&arch, "main.rs", "backend", 2), ".");
+        assert_eq!(unit_of(&arch, "src/a/b/c.rs", "backend", 2), "src/a");
+    }
+}
@@ -0,0 +1,291 @@
+//! Token budgeting shared by the read tools.
+//!
+//! Counts use the cl100k BPE (the tokenizer behind most chat models agents run on),
+//! so `max_tokens` means the same thing in `read_file`, `skeleton`, `context_bundle`
+//! and `batch_operations`. Content that does not fit is degraded one level at a
+//! time — full → skeleton → signatures → names — before anything is cut.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tiktoken_rs::CoreBPE;
+
+use crate::indexer::parser::{self, CodeParser, SupportedLanguage};
+use crate::models::SymbolKind;
+
+fn bpe() -> Option<&'static CoreBPE> {
+    static BPE: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    BPE.get_or_init(|| match tiktoken_rs::cl100k_base() {
+        Ok(bpe) => Some(bpe),
+        Err(e) => {
+            tracing::warn!("cl100k tokenizer unavailable, estimating tokens: {}", e);
+            None
+        }
+    })
+    .as_ref()
+}
+
+/// Number of tokens in `text`. Falls back to chars/4 if the BPE failed to load.
+pub fn count_tokens(text: &str) -> usize {
+    match bpe() {
+        Some(bpe) => bpe.encode_ordinary(text).len(),
+        None => text.len().div_ceil(4),
+    }
+}
+
+/// How much of a file survives budgeting, from most to least detailed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Detail {
+    Full,
+    Skeleton,
+    Signatures,
+    Names,
+}
+
+impl Detail {
+    pub fn next(self) -> Option<Detail> {
+        match self {
+            Detail::Full => Some(Detail::Skeleton),
+            Detail::Skeleton => Some(Detail::Signatures),
+            Detail::Signatures => Some(Detail::Names),
+            Detail::Names => None,
+        }
+    }
+}
+
+/// Content rendered at some [`Detail`] level with its token count.
+#[derive(Debug, Clone)]
+pub struct Fitted {
+    pub content: String,
+    pub detail: Detail,
+    pub tokens: usize,
+    /// True when even the names did not fit and lines were cut
+    pub truncated: bool,
+}
+
+/// Render `content` at `detail`. Non-code files only have the full level.
+///
+/// `first_line` is the 1-based file line `content` starts at, so signature line
+/// numbers stay valid for slices of a file.
+pub fn render(content: &str, extension: &str, detail: Detail, first_line: usize) -> String {
+    let Some(lang) = SupportedLanguage::from_extension(extension) else {
+        return content.to_string();
+    };
+    match detail {
+        Detail::Full => content.to_string(),
+        Detail::Skeleton => {
+            parser::generate_skeleton(content, lang).unwrap_or_else(|_| content.to_string())
+        }
+        Detail::Signatures | Detail::Names => {
+            let mut parser = CodeParser::new();
+            let symbols = parser.parse_symbols(content, lang).unwrap_or_default();
+            let lines: Vec<&str> = content.lines().collect();
+            symbols
+                .iter()
+                .filter(|s| s.kind != SymbolKind::LocalVar)
+                .map(|s| {
+                    if detail == Detail::Names {
+                        return format!("{} {}", s.kind.as_str(), s.name);
+                    }
+                    let signature = s.signature.clone().unwrap_or_else(|| {
+                        lines
+                            .get(s.line_start.max(0) as usize)
+                            .map(|l| l.trim().to_string())
+                            .unwrap_or_else(|| s.name.clone())
+                    });
+                    format!(
+                        "{}: {}",
+                        s.line_start.max(0) as usize + first_line,
+                        signature
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+/// Degrade `content` starting at `from` until it fits `max_tokens`.
+pub fn fit(
+    content: &str,
+    extension: &str,
+    max_tokens: usize,
+    from: Detail,
+    first_line: usize,
+) -> Fitted {
+    let mut detail = from;
+    loop {
+        let rendered = render(content, extension, detail, first_line);
+        let tokens = count_tokens(&rendered);
+        if tokens <= max_tokens {
+            return Fitted {
+                content: rendered,
+                detail,
+                tokens,
+                truncated: false,
+            };
+        }
+        match detail.next() {
+            Some(next) if SupportedLanguage::from_extension(extension).is_some() => detail = next,
+            _ => return truncate(&rendered, max_tokens, detail),
+        }
+    }
+}
+
+/// Keep whole lines from the top while they fit.
+fn truncate(content: &str, max_tokens: usize, detail: Detail) -> Fitted {
+    let mut out = String::new();
+    let mut tokens = 0;
+    for line in content.lines() {
+        let cost = count_tokens(line) + 1;
+        if tokens + cost > max_tokens {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+        tokens += cost;
+    }
+    let tokens = count_tokens(&out);
+    Fitted {
+        content: out,
+        detail,
+        tokens,
+        truncated: true,
+    }
+}
+
+/// One file taking part in a shared budget.
+pub struct BudgetItem<'a> {
+    pub content: &'a str,
+    pub extension: &'a str,
+    /// Lower priorities are degraded first
+    pub priority: u32,
+}
+
+/// Fit several files into one budget.
+///
+/// The lowest-priority file with the most tokens is degraded one level at a time
+/// until the total fits; higher-priority files are only touched once every
+/// lower-priority file has reached [`Detail::Names`]. If names alone still exceed
+/// the budget, the remaining allowance is split in priority order and lines are cut.
+pub fn fit_all(items: &[BudgetItem<'_>], max_tokens: usize) -> Vec<Fitted> {
+    let mut fitted: Vec<Fitted> = items
+        .iter()
+        .map(|item| {
+            let content = item.content.to_string();
+            let tokens = count_tokens(&content);
+            Fitted {
+                content,
+                detail: Detail::Full,
+                tokens,
+                truncated: false,
+            }
+        })
+        .collect();
+
+    loop {
+        let total: usize = fitted.iter().map(|f| f.tokens).sum();
+        if total <= max_tokens {
+            return fitted;
+        }
+        let candidate = (0..items.len())
+            .filter(|&i| {
+                fitted[i].detail.next().is_some()
+                    && SupportedLanguage::from_extension(items[i].extension).is_some()
+            })
+            .min_by_key(|&i| (items[i].priority, std::cmp::Reverse(fitted[i].tokens)));
+        let Some(i) = candidate else { break };
+
+        let detail = fitted[i].detail.next().unwrap_or(Detail::Names);
+        let content = render(items[i].content, items[i].extension, detail, 1);
+        let tokens = count_tokens(&content);
+        fitted[i] = Fitted {
+            content,
+            detail,
+            tokens,
+            truncated: false,
+        };
+    }
+
+    // Names everywhere and still over: give the allowance out by priority
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(items[i].priority));
+    let mut left = max_tokens;
+    for i in order {
+        if fitted[i].tokens > left {
+            fitted[i] = truncate(&fitted[i].content, left, fitted[i].detail);
+        }
+        left -= fitted[i].tokens.min(left);
+    }
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = r#"
+/// Loads a user.
+pub fn load_user(id: u64) -> Option<User> {
+    let conn = connect();
+    let row = conn.query("SELECT * FROM users WHERE id = ?", id);
+    row.map(User::from)
+}
+
+pub struct User {
+    pub id: u64,
+    pub name: String,
+}
+"#;
+
+    #[test]
+    fn test_count_tokens_uses_bpe() {
+        assert_eq!(count_tokens(""), 0);
+        assert_eq!(count_tokens("hello world"), 2);
+    }
+
+    #[test]
+    fn test_fit_degrades_progressively() {
+        let full = fit(CODE, "rs", 10_000, Detail::Full, 1);
+        assert_eq!(full.detail, Detail::Full);
+        assert!(!full.truncated);
+
+        let skeleton_tokens = count_tokens(&render(CODE, "rs", Detail::Skeleton, 1));
+        let fitted = fit(CODE, "rs", skeleton_tokens, Detail::Full, 1);
+        assert_eq!(fitted.detail, Detail::Skeleton);
+        assert!(!fitted.content.contains("SELECT"));
+
+        let names = fit(
+            CODE,
+            "rs",
+            count_tokens("function load_user\nstruct User"),
+            Detail::Full,
+            1,
+        );
+        assert_eq!(names.detail, Detail::Names);
+        assert!(names.content.contains("load_user"));
+        assert!(names.tokens <= count_tokens("function load_user\nstruct User"));
+    }
+
+    #[test]
+    fn test_fit_all_degrades_low_priority_first() {
+        let items = [
+            BudgetItem {
+                content: CODE,
+                extension: "rs",
+                priority: 1,
+            },
+            BudgetItem {
+                content: CODE,
+                extension: "rs",
+                priority: 0,
+            },
+        ];
+        let budget = count_tokens(CODE) + count_tokens(&render(CODE, "rs", Detail::Skeleton, 1));
+        let fitted = fit_all(&items, budget);
+        assert_eq!(fitted[0].detail, Detail::Full);
+        assert_eq!(fitted[1].detail, Detail::Skeleton);
+        assert!(fitted.iter().map(|f| f.tokens).sum::<usize>() <= budget);
+    }
+}
@@ -0,0 +1,555 @@
+//! Code references to database tables and columns.
+//!
+//! Three sources, all read without knowing the schema (the schema model is
+//! joined in at query time, so a changed migration does not require
+//! re-reading code):
+//! - SQL in string literals (sqlx `query!`, database/sql, SQLAlchemy `text()`,
+//!   raw TypeORM queries): columns resolved through the query's table aliases
+//! - ORM entity mappings: `#[diesel(table_name = ...)]`, `__tablename__`,
+//!   `@Entity("...")`, GORM `TableName()` — the mapped type's fields are
+//!   matched against the table's columns through `type_fingerprints`
+//! - Diesel DSL paths such as `users::email` or `users::dsl::email`
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::parser::sql;
+use super::parser::SupportedLanguage;
+
+/// How a usage was found
+pub const KIND_QUERY: &str = "query";
+pub const KIND_ENTITY: &str = "entity";
+pub const KIND_DSL: &str = "dsl";
+
+/// Longest query excerpt kept as context
+const CONTEXT_CHARS: usize = 160;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnUsage {
+    pub table: String,
+    /// Empty for entity mappings
+    pub column: String,
+    /// 1-based line
+    pub line: usize,
+    pub kind: &'static str,
+    /// An unqualified column in a query over several tables; it belongs to
+    /// whichever of them has it
+    pub ambiguous: bool,
+    /// Query excerpt, or the mapped type name for entities
+    pub context: String,
+}
+
+/// All table and column references in a source file.
+pub fn extract_column_usages(content: &str, language: SupportedLanguage) -> Vec<ColumnUsage> {
+    let lines = LineIndex::new(content);
+    let mut usages = Vec::new();
+
+    for (offset, text) in string_literals(content, language) {
+        if !looks_like_query(&text) {
+            continue;
+        }
+        let context = excerpt(&text);
+        for reference in sql::query_columns(&text) {
+            let line = lines.line(offset + reference.offset);
+            let ambiguous = reference.tables.len() > 1;
+            for table in reference.tables {
+                usages.push(ColumnUsage {
+                    table,
+                    column: reference.column.clone(),
+                    line,
+                    kind: KIND_QUERY,
+                    ambiguous,
+                    context: context.clone(),
+                });
+            }
+        }
+    }
+
+    for (offset, table, type_name) in entity_mappings(content, language) {
+        usages.push(ColumnUsage {
+            table,
+            column: String::new(),
+            line: lines.line(offset),
+            kind: KIND_ENTITY,
+            ambiguous: false,
+            context: type_name,
+        });
+    }
+
+    if language == SupportedLanguage::Rust && content.contains("diesel") {
+        usages.extend(diesel_paths(content, &lines));
+    }
+
+    usages.sort_by(|a, b| (a.line, &a.table, &a.column).cmp(&(b.line, &b.table, &b.column)));
+    usages.dedup();
+    usages
+}
+
+/// Snake-case forms a type name may take as a table name: `OrderItem` →
+/// `order_item`, `order_items`; `UserRow`/`UserModel`/`UserEntity` → `user`, `users`.
+pub fn table_names_for_type(type_name: &str) -> Vec<String> {
+    let mut base = type_name;
+    for suffix in ["Row", "Record", "Model", "Entity", "Table", "Db", "DB"] {
+        if let Some(stripped) = base.strip_suffix(suffix).filter(|s| !s.is_empty()) {
+            base = stripped;
+            break;
+        }
+    }
+    let mut snake = String::new();
+    for (i, c) in base.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    let plural = if snake.ends_with('y') && !snake.ends_with("ey") {
+        format!("{}ies", &snake[..snake.len() - 1])
+    } else if snake.ends_with('s') || snake.ends_with('x') || snake.ends_with("ch") {
+        format!("{}es", snake)
+    } else {
+        format!("{}s", snake)
+    };
+    vec![snake, plural]
+}
+
+fn looks_like_query(text: &str) -> bool {
+    static QUERY: OnceLock<Regex> = OnceLock::new();
+    QUERY
+        .get_or_init(|| {
+            Regex::new(
+                r"(?is)^\s*(select\s.+\sfrom\s|insert\s+into\s|update\s+\S+\s+set\s|delete\s+from\s|with\s+\w+\s+as\s*\()",
+            )
+            .unwrap()
+        })
+        .is_match(text)
+}
+
+fn excerpt(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.char_indices().nth(CONTEXT_CHARS) {
+        Some((cut, _)) => format!("{}…", &collapsed[..cut]),
+        None => collapsed,
+    }
+}
+
+/// Byte offset → 1-based line.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(content: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        Self { starts }
+    }
+
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+}
+
+/// String literals with the byte offset of their first content character.
+/// Template literal substitutions (`${...}`) become `?` padded to the same
+/// length, so offsets and lines inside the literal stay exact.
+fn string_literals(content: &str, language: SupportedLanguage) -> Vec<(usize, String)> {
+    if language == SupportedLanguage::Vue {
+        // Only <script> blocks; apostrophes in template text are not quotes
+        static SCRIPT: OnceLock<Regex> = OnceLock::new();
+        let script = SCRIPT.get_or_init(|| Regex::new(r"(?s)<script[^>]*>(.*?)</script>").unwrap());
+        return script
+            .captures_iter(content)
+            .filter_map(|caps| caps.get(1))
+            .flat_map(|body| {
+                scan_literals(body.as_str(), SupportedLanguage::TypeScript)
+                    .into_iter()
+                    .map(move |(offset, text)| (offset + body.start(), text))
+            })
+            .collect();
+    }
+    scan_literals(content, language)
+}
+
+fn scan_literals(content: &str, language: SupportedLanguage) -> Vec<(usize, String)> {
+    let bytes = content.as_bytes();
+    let python = language == SupportedLanguage::Python;
+    let mut literals = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match b {
+            b'/' if !python && next == Some(b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if !python && next == Some(b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'#' if python => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            // Rust raw strings: r"..." and r#"..."#
+            b'r' if language == SupportedLanguage::Rust
+                && matches!(next, Some(b'"') | Some(b'#'))
+                && (i == 0 || !is_ident_byte(bytes[i - 1])) =>
+            {
+                let hashes = bytes[i + 1..].iter().take_while(|&&c| c == b'#').count();
+                let open = i + 1 + hashes;
+                if bytes.get(open) != Some(&b'"') {
+                    i += 1;
+                    continue;
+                }
+                let mut closing = String::from("\"");
+                closing.push_str(&"#".repeat(hashes));
+                let start = open + 1;
+                let end = content[start..]
+                    .find(&closing)
+                    .map(|p| p + start)
+                    .unwrap_or(bytes.len());
+                literals.push((start, content[start..end].to_string()));
+                i = end + closing.len();
+            }
+            b'\'' if matches!(language, SupportedLanguage::Rust | SupportedLanguage::Go) => {
+                // Char/rune literal or lifetime; neither holds SQL
+                i += match (next, bytes.get(i + 2)) {
+                    (Some(b'\\'), _) => bytes[i + 2..]
+                        .iter()
+                        .position(|&c| c == b'\'')
+                        .map(|p| p + 3)
+                        .unwrap_or(1),
+                    (_, Some(b'\'')) => 3,
+                    _ => 1,
+                };
+            }
+            b'"' | b'\'' if python && bytes[i..].starts_with(&[b, b, b]) => {
+                let start = i + 3;
+                let closing = [b, b, b];
+                let end = find_unescaped(bytes, start, &closing);
+                literals.push((start, content[start..end].to_string()));
+                i = end + 3;
+            }
+            b'`' if language == SupportedLanguage::Go => {
+                let start = i + 1;
+                let end = content[start..]
+                    .find('`')
+                    .map(|p| p + start)
+                    .unwrap_or(bytes.len());
+                literals.push((start, content[start..end].to_string()));
+                i = end + 1;
+            }
+            b'`' => {
+                let (end, text) = template_literal(content, i + 1);
+                literals.push((i + 1, text));
+                i = end + 1;
+            }
+            b'"' | b'\'' => {
+                let start = i + 1;
+                let end = find_unescaped(bytes, start, &[b]);
+                literals.push((start, content[start..end].to_string()));
+                i = end + 1;
+            }
+            _ => i += 1,
+        }
+    }
+    literals
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Index of the closing delimiter, skipping backslash escapes.
+fn find_unescaped(bytes: &[u8], start: usize, closing: &[u8]) -> usize {
+    let mut j = start;
+    while j < bytes.len() {
+        if bytes[j] == b'\\' {
+            j += 2;
+            continue;
+        }
+        if bytes[j..].starts_with(closing) {
+            return j;
+        }
+        j += 1;
+    }
+    bytes.len()
+}
+
+/// JS/TS template literal starting after the backtick: (index of the closing
+/// backtick, text with substitutions blanked).
+fn template_literal(content: &str, start: usize) -> (usize, String) {
+    let bytes = content.as_bytes();
+    let mut text = String::new();
+    let mut j = start;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => {
+                let end = (j + 2).min(bytes.len());
+                text.push_str(&content[j..end]);
+                j = end;
+            }
+            b'`' => return (j, text),
+            b'$' if bytes.get(j + 1) == Some(&b'{') => {
+                let mut depth = 0;
+                let mut k = j + 1;
+                while k < bytes.len() {
+                    match bytes[k] {
+                        b'{' => depth += 1,
+                        b'}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    k += 1;
+                }
+                let end = (k + 1).min(bytes.len());
+                text.push('?');
+                for c in content[j + 1..end].chars() {
+                    text.push(if c == '\n' { '\n' } else { ' ' });
+                    // Keep byte offsets: pad multi-byte characters
+                    for _ in 1..c.len_utf8() {
+                        text.push(' ');
+                    }
+                }
+                j = end;
+            }
+            _ => {
+                let c = content[j..].chars().next().unwrap_or(' ');
+                text.push(c);
+                j += c.len_utf8().max(1);
+            }
+        }
+    }
+    (bytes.len(), text)
+}
+
+/// `(offset of the type declaration, table, type name)` for ORM entities.
+fn entity_mappings(content: &str, language: SupportedLanguage) -> Vec<(usize, String, String)> {
+    static RUST: OnceLock<Regex> = OnceLock::new();
+    static PYTHON: OnceLock<Regex> = OnceLock::new();
+    static TS: OnceLock<Regex> = OnceLock::new();
+    static GO: OnceLock<Regex> = OnceLock::new();
+    static RUST_TYPE: OnceLock<Regex> = OnceLock::new();
+    static PYTHON_CLASS: OnceLock<Regex> = OnceLock::new();
+    static TS_CLASS: OnceLock<Regex> = OnceLock::new();
+
+    let mut found = Vec::new();
+    match language {
+        SupportedLanguage::Rust => {
+            let attr = RUST.get_or_init(|| {
+                Regex::new(r#"#\[(?:(?:diesel|sea_orm)\(\s*)?table_name\s*=\s*"?([\w.:]+)"?"#)
+                    .unwrap()
+            });
+            let item = RUST_TYPE
+                .get_or_init(|| Regex::new(r"\b(?:struct|enum)\s+([A-Za-z_]\w*)").unwrap());
+            for caps in attr.captures_iter(content) {
+                let after = caps.get(0).map(|m| m.end()).unwrap_or(0);
+                if let Some(decl) = item.captures(&content[after..]) {
+                    let m = decl.get(1).unwrap();
+                    // `crate::schema::users` → `users`
+                    let table = caps[1].rsplit("::").next().unwrap_or_default();
+                    found.push((after + m.start(), table.to_string(), m.as_str().to_string()));
+                }
+            }
+        }
+        SupportedLanguage::Python => {
+            let table = PYTHON.get_or_init(|| {
+                Regex::new(r#"(?m)^\s+__tablename__\s*=\s*["']([\w.]+)["']"#).unwrap()
+            });
+            let class =
+                PYTHON_CLASS.get_or_init(|| Regex::new(r"(?m)^\s*class\s+([A-Za-z_]\w*)").unwrap());
+            for caps in table.captures_iter(content) {
+                let at = caps.get(0).map(|m| m.start()).unwrap_or(0);
+                if let Some(decl) = class.captures_iter(&content[..at]).last() {
+                    let m = decl.get(1).unwrap();
+                    found.push((m.start(), caps[1].to_string(), m.as_str().to_string()));
+                }
+            }
+        }
+        SupportedLanguage::TypeScript | SupportedLanguage::JavaScript | SupportedLanguage::Vue => {
+            let entity = TS.get_or_init(|| {
+                Regex::new(
+                    r#"@Entity\(\s*(?:["'`]([\w.]+)["'`]|\{[^}]*?\bname\s*:\s*["'`]([\w.]+)["'`][^}]*\})?[^)]*\)"#,
+                )
+                .unwrap()
+            });
+            let class = TS_CLASS.get_or_init(|| Regex::new(r"\bclass\s+([A-Za-z_]\w*)").unwrap());
+            for caps in entity.captures_iter(content) {
+                let after = caps.get(0).map(|m| m.end()).unwrap_or(0);
+                let Some(decl) = class.captures(&content[after..]) else {
+                    continue;
+                };
+                let m = decl.get(1).unwrap();
+                let type_name = m.as_str().to_string();
+                let table = caps
+                    .get(1)
+                    .or_else(|| caps.get(2))
+                    .map(|t| t.as_str().to_string())
+                    // `@Entity()` names the table after the class
+                    .unwrap_or_else(|| table_names_for_type(&type_name).remove(0));
+                found.push((after + m.start(), table, type_name));
+            }
+        }
+        SupportedLanguage::Go => {
+            let method = GO.get_or_init(|| {
+                Regex::new(
+                    r#"func\s*\(\s*(?:\w+\s+)?\*?(\w+)\s*\)\s*TableName\(\)\s*string\s*\{\s*return\s*"([\w.]+)""#,
+                )
+                .unwrap()
+            });
+            for caps in method.captures_iter(content) {
+                let m = caps.get(1).unwrap();
+                found.push((m.start(), caps[2].to_string(), m.as_str().to_string()));
+            }
+        }
+    }
+    found
+}
+
+/// Diesel DSL paths: `users::email`, `schema::users::dsl::email`,
+/// `users::table` (a table usage, recorded with an empty column).
+fn diesel_paths(content: &str, lines: &LineIndex) -> Vec<ColumnUsage> {
+    static PATH: OnceLock<Regex> = OnceLock::new();
+    let path =
+        PATH.get_or_init(|| Regex::new(r"\b[a-z_][a-z0-9_]*(?:::[a-z_][a-z0-9_]*)+\b").unwrap());
+    const SKIP: &[&str] = &["crate", "super", "self", "schema", "dsl", "prelude"];
+    const ROOTS: &[&str] = &[
+        "std",
+        "core",
+        "alloc",
+        "diesel",
+        "tokio",
+        "serde",
+        "serde_json",
+        "chrono",
+        "anyhow",
+        "sqlx",
+        "futures",
+        "tracing",
+        "log",
+    ];
+
+    let mut usages = Vec::new();
+    for m in path.find_iter(content) {
+        // Function calls and macro paths are not columns
+        let rest = &content[m.end()..];
+        if rest.starts_with('(') || rest.starts_with('!') || rest.starts_with("::") {
+            continue;
+        }
+        let segments: Vec<&str> = m.as_str().split("::").collect();
+        if ROOTS.contains(&segments[0]) {
+            continue;
+        }
+        let segments: Vec<&str> = segments.into_iter().filter(|s| !SKIP.contains(s)).collect();
+        let [.., table, column] = segments.as_slice() else {
+            continue;
+        };
+        let column = match *column {
+            "table" => "",
+            "star" | "all_columns" => continue,
+            column => column,
+        };
+        usages.push(ColumnUsage {
+            table: table.to_string(),
+            column: column.to_string(),
+            line: lines.line(m.start()),
+            kind: KIND_DSL,
+            ambiguous: false,
+            context: m.as_str().to_string(),
+        });
+    }
+    usages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queries_in_string_literals() {
+        let rust = r##"
+fn load(pool: &Pool) {
+    // "SELECT nothing FROM comments" is ignored
+    let row = sqlx::query_as!(User, r#"
+        SELECT u.id, u.email
+        FROM users u
+        WHERE u.deleted_at IS NULL"#);
+    let label = 'x';
+    sqlx::query("UPDATE users SET last_login = now() WHERE id = $1");
+}
+"##;
+        let usages = extract_column_usages(rust, SupportedLanguage::Rust);
+        let found: Vec<_> = usages
+            .iter()
+            .map(|u| (u.line, u.table.as_str(), u.column.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (5, "users", "email"),
+                (5, "users", "id"),
+                (7, "users", "deleted_at"),
+                (9, "users", "id"),
+                (9, "users", "last_login"),
+            ]
+        );
+        assert!(usages.iter().all(|u| u.kind == KIND_QUERY && !u.ambiguous));
+
+        let ts = "const q = `SELECT name, ${cols} FROM orders o JOIN users u ON u.id = o.user_id WHERE total > ${min}`;";
+        let usages = extract_column_usages(ts, SupportedLanguage::TypeScript);
+        let name: Vec<_> = usages.iter().filter(|u| u.column == "name").collect();
+        assert_eq!(name.len(), 2);
+        assert!(name.iter().all(|u| u.ambiguous));
+        assert!(usages
+            .iter()
+            .any(|u| u.table == "orders" && u.column == "user_id"));
+    }
+
+    #[test]
+    fn test_entity_mappings_and_dsl() {
+        let rust = "#[derive(Queryable)]\n#[diesel(table_name = crate::schema::users)]\npub struct UserRow { id: i32 }\n\
+                    fn f() { users::table.filter(users::dsl::email.eq(x)); }\n";
+        let usages = extract_column_usages(rust, SupportedLanguage::Rust);
+        let found: Vec<_> = usages
+            .iter()
+            .map(|u| (u.line, u.kind, u.table.as_str(), u.column.as_str()))
+            .collect();
+        assert!(found.contains(&(3, KIND_ENTITY, "users", "")));
+        assert!(found.contains(&(4, KIND_DSL, "users", "")));
+        assert!(found.contains(&(4, KIND_DSL, "users", "email")));
+
+        let py = "class User(Base):\n    __tablename__ = 'accounts'\n    id = Column(Integer)\n";
+        let usages = extract_column_usages(py, SupportedLanguage::Python);
+        assert_eq!(usages[0].table, "accounts");
+        assert_eq!(usages[0].context, "User");
+        assert_eq!(usages[0].line, 1);
+
+        let ts = "@Entity()\nexport class OrderItem {\n  @Column() price: number;\n}\n";
+        let usages = extract_column_usages(ts, SupportedLanguage::TypeScript);
+        assert_eq!(usages[0].table, "order_item");
+
+        let go = "func (User) TableName() string { return \"people\" }";
+        let usages = extract_column_usages(go, SupportedLanguage::Go);
+        assert_eq!(
+            (usages[0].table.as_str(), usages[0].context.as_str()),
+            ("people", "User")
+        );
+
+        assert_eq!(
+            table_names_for_type("CategoryModel"),
+            vec!["category", "categories"]
+        );
+    }
+}
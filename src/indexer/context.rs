@@ -2,57 +2,591 @@
 //!
 //! Moved from api/context.rs; no HTTP/axum dependencies.
 
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use crate::indexer::budget::{self, BudgetItem, Detail};
 use crate::indexer::parser::{self, CodeParser, SupportedLanguage};
-use crate::models::{ContextBundle, DependencyFile};
+use crate::languages::typescript::TsImportResolver;
+use crate::languages::{python, rust};
+use crate::models::{
+    ContextBundle, DependencyFile, ImportEdge, ImportInfo, SymbolKind, SymbolWithPath,
+    UnresolvedItem,
+};
+use crate::storage::SqliteStorage;
 
-pub fn create_bundle(main_path: &Path, max_depth: u32) -> ContextBundle {
+/// Deepest `context_bundle` that is cached; the watcher invalidates cached
+/// bundles of importers up to this many hops away.
+pub const MAX_CACHED_BUNDLE_DEPTH: u32 = 4;
+
+pub fn create_bundle(root: &Path, main_path: &Path, max_depth: u32) -> ContextBundle {
     let main_content = std::fs::read_to_string(main_path).unwrap_or_default();
-    let mut dependencies = Vec::new();
-    let mut visited = HashSet::new();
-    visited.insert(main_path.canonicalize().unwrap_or(main_path.to_path_buf()));
+    let mut collector = Collector {
+        resolver: InternalResolver::new(root),
+        dependencies: Vec::new(),
+        visited: HashSet::new(),
+        defined: HashSet::new(),
+        unresolved: Vec::new(),
+        max_depth,
+    };
+    collector
+        .visited
+        .insert(main_path.canonicalize().unwrap_or(main_path.to_path_buf()));
+    collector.follow_imports(main_path, &main_content, 1);
+
+    let mut bundle = ContextBundle {
+        main_file: main_path.to_string_lossy().to_string(),
+        main_content,
+        dependencies: collector.dependencies,
+        markdown: String::new(),
+        total_lines: 0,
+        total_tokens_estimate: 0,
+        unresolved_items: collector.unresolved,
+    };
+    refresh_totals(&mut bundle);
+    bundle
+}
+
+/// Look up items the imported module only re-exports in the symbol index and add
+/// their definitions, preferring matches under the module's directory.
+pub async fn resolve_from_index(bundle: &mut ContextBundle, sqlite: &SqliteStorage, root: &Path) {
+    if bundle.unresolved_items.is_empty() {
+        return;
+    }
+    let mut names: Vec<String> = bundle
+        .unresolved_items
+        .iter()
+        .map(|i| i.name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    let symbols = sqlite
+        .get_definitions_by_names(&names)
+        .await
+        .unwrap_or_default();
+
+    let unresolved = std::mem::take(&mut bundle.unresolved_items);
+    let mut skip: HashSet<String> = bundle.dependencies.iter().map(|d| d.path.clone()).collect();
+    skip.insert(bundle.main_file.clone());
+    let root = root.to_path_buf();
+    let (added, unresolved) = match tokio::task::spawn_blocking({
+        let unresolved = unresolved.clone();
+        move || indexed_definitions(unresolved, &symbols, &root, &skip)
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => (Vec::new(), unresolved),
+    };
+    bundle.unresolved_items = unresolved;
+    if !added.is_empty() {
+        bundle.dependencies.extend(added);
+        refresh_totals(bundle);
+    }
+}
+
+/// Definitions of `items` in the files `symbols` places them in, skipping files
+/// already in the bundle, and the items that stay unresolved.
+fn indexed_definitions(
+    items: Vec<UnresolvedItem>,
+    symbols: &[SymbolWithPath],
+    root: &Path,
+    skip: &HashSet<String>,
+) -> (Vec<DependencyFile>, Vec<UnresolvedItem>) {
+    let mut found: Vec<(PathBuf, Vec<String>, u32)> = Vec::new();
+    let mut unresolved = Vec::new();
+    for item in items {
+        let module_dir = Path::new(&item.module_file)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let candidates: Vec<PathBuf> = symbols
+            .iter()
+            .filter(|s| s.name == item.name)
+            .map(|s| root.join(&s.file_path))
+            .collect();
+        let best = candidates
+            .iter()
+            .find(|p| p.starts_with(&module_dir))
+            .or(candidates.first());
+        let Some(path) = best.cloned() else {
+            unresolved.push(item);
+            continue;
+        };
+        match found.iter_mut().find(|(p, _, _)| *p == path) {
+            Some((_, names, _)) => names.push(item.name),
+            None => found.push((path, vec![item.name], item.depth)),
+        }
+    }
+
+    let mut added = Vec::new();
+    for (path, names, depth) in found {
+        let path_str = path.to_string_lossy().to_string();
+        if skip.contains(&path_str) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let (definitions, defined) =
+            extract_definitions(&content, &extension_of(&path_str), &names);
+        if defined.is_empty() {
+            continue;
+        }
+        added.push(DependencyFile {
+            path: path_str,
+            content: definitions,
+            reason: format!("imports: {} (definitions)", defined.join(", ")),
+            depth,
+        });
+    }
+    (added, unresolved)
+}
 
-    let ext = main_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-    if let Some(language) = SupportedLanguage::from_extension(ext) {
+/// Order dependencies by depth, then by how many bundled files import them,
+/// then by how many files import them project-wide (from the import graph).
+pub async fn order_by_relevance(bundle: &mut ContextBundle, sqlite: &SqliteStorage, root: &Path) {
+    let key = |path: &str| {
+        Path::new(path)
+            .strip_prefix(root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string())
+    };
+    let deps: Vec<String> = bundle.dependencies.iter().map(|d| key(&d.path)).collect();
+    let mut bundled = deps.clone();
+    bundled.push(key(&bundle.main_file));
+
+    let edges: HashSet<(String, String)> = sqlite
+        .get_import_edges(&bundled, false)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| (e.from_path, e.to_path))
+        .collect();
+    let mut in_bundle: HashMap<&str, usize> = HashMap::new();
+    for (_, to) in &edges {
+        *in_bundle.entry(to.as_str()).or_default() += 1;
+    }
+    let fan_in = sqlite.get_importer_counts(&deps).await.unwrap_or_default();
+
+    let mut ranked: Vec<_> = std::mem::take(&mut bundle.dependencies)
+        .into_iter()
+        .zip(&deps)
+        .map(|(dep, path)| {
+            let rank = (
+                dep.depth,
+                Reverse(in_bundle.get(path.as_str()).copied().unwrap_or(0)),
+                Reverse(fan_in.get(path).copied().unwrap_or(0)),
+            );
+            (rank, dep)
+        })
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    bundle.dependencies = ranked.into_iter().map(|(_, dep)| dep).collect();
+}
+
+/// Walks imports and accumulates dependencies for [`create_bundle`].
+struct Collector {
+    resolver: InternalResolver,
+    dependencies: Vec<DependencyFile>,
+    /// Files included whole
+    visited: HashSet<PathBuf>,
+    /// (file, item) pairs included as definitions
+    defined: HashSet<(PathBuf, String)>,
+    unresolved: Vec<UnresolvedItem>,
+    max_depth: u32,
+}
+
+impl Collector {
+    fn follow_imports(&mut self, path: &Path, content: &str, depth: u32) {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let Some(language) = SupportedLanguage::from_extension(ext) else {
+            return;
+        };
         let mut parser = CodeParser::new();
-        let imports = parser.parse_imports(&main_content, language);
-        let base_dir = main_path.parent().unwrap_or(Path::new("."));
+        let imports = parser.parse_imports(content, language);
+        let base_dir = path.parent().unwrap_or(Path::new("."));
 
+        // Items are grouped per file so `use crate::a::{B, C}` yields one dependency
+        let mut item_imports: Vec<(PathBuf, Vec<String>)> = Vec::new();
         for import in imports {
             if import.is_relative {
                 if let Some(resolved) = resolve_import(&import.path, base_dir, language) {
-                    collect_dependency(
-                        &resolved,
-                        &import.items.join(", "),
-                        &mut dependencies,
-                        &mut visited,
-                        max_depth,
-                        1,
-                    );
+                    self.collect_dependency(&resolved, &import.items.join(", "), depth);
+                    continue;
+                }
+            }
+            for internal in self.resolver.resolve(&import, path, language) {
+                if internal.items.is_empty() {
+                    self.collect_dependency(&internal.file, &import.items.join(", "), depth);
+                    continue;
+                }
+                match item_imports.iter_mut().find(|(f, _)| *f == internal.file) {
+                    Some((_, items)) => items.extend(internal.items),
+                    None => item_imports.push((internal.file, internal.items)),
                 }
             }
         }
+        for (file, items) in item_imports {
+            self.collect_definitions(&file, &items, depth);
+        }
     }
 
-    let markdown = generate_markdown(main_path, &main_content, &dependencies);
+    fn collect_dependency(&mut self, path: &Path, reason: &str, depth: u32) {
+        let canonical = path.canonicalize().unwrap_or(path.to_path_buf());
+        if self.visited.contains(&canonical) || depth > self.max_depth {
+            return;
+        }
+        self.visited.insert(canonical);
 
-    let main_lines = main_content.lines().count();
-    let dep_lines: usize = dependencies.iter().map(|d| d.content.lines().count()).sum();
-    let total_lines = main_lines + dep_lines;
-    let total_chars =
-        main_content.len() + dependencies.iter().map(|d| d.content.len()).sum::<usize>();
-    let total_tokens_estimate = total_chars / 4;
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return,
+        };
 
-    ContextBundle {
-        main_file: main_path.to_string_lossy().to_string(),
-        main_content,
-        dependencies,
-        markdown,
-        total_lines,
-        total_tokens_estimate,
+        self.dependencies.push(DependencyFile {
+            path: path.to_string_lossy().to_string(),
+            content: content.clone(),
+            reason: format!("imports: {}", reason),
+            depth,
+        });
+
+        if depth < self.max_depth {
+            self.follow_imports(path, &content, depth + 1);
+        }
+    }
+
+    /// Include only the definitions of `items` from `path`. Items the file does not
+    /// define are left for [`resolve_from_index`].
+    fn collect_definitions(&mut self, path: &Path, items: &[String], depth: u32) {
+        let canonical = path.canonicalize().unwrap_or(path.to_path_buf());
+        if self.visited.contains(&canonical) || depth > self.max_depth {
+            return;
+        }
+        let wanted: Vec<String> = items
+            .iter()
+            .filter(|item| {
+                !self
+                    .defined
+                    .contains(&(canonical.clone(), item.to_string()))
+            })
+            .cloned()
+            .collect();
+        if wanted.is_empty() {
+            return;
+        }
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+
+        let path_str = path.to_string_lossy().to_string();
+        let (definitions, defined) =
+            extract_definitions(&content, &extension_of(&path_str), &wanted);
+        for name in wanted.iter().filter(|n| !defined.contains(n)) {
+            self.unresolved.push(UnresolvedItem {
+                name: name.clone(),
+                module_file: path_str.clone(),
+                depth,
+            });
+        }
+        if defined.is_empty() {
+            return;
+        }
+        for name in &defined {
+            self.defined.insert((canonical.clone(), name.clone()));
+        }
+        self.dependencies.push(DependencyFile {
+            path: path_str,
+            content: definitions,
+            reason: format!("imports: {} (definitions)", defined.join(", ")),
+            depth,
+        });
+    }
+}
+
+/// A workspace-internal import that is not a plain relative path.
+struct InternalImport {
+    file: PathBuf,
+    /// Imported items; empty when the module itself is imported
+    items: Vec<String>,
+}
+
+/// Resolves `crate::` paths, tsconfig aliases and absolute Python imports with the
+/// same logic as `rust_resolve_module_path`, `ts_resolve_import` and
+/// `python_resolve_import`.
+struct InternalResolver {
+    root: PathBuf,
+    ts: TsImportResolver,
+}
+
+impl InternalResolver {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            ts: TsImportResolver::new(root),
+        }
+    }
+
+    fn resolve(
+        &self,
+        import: &ImportInfo,
+        from_file: &Path,
+        language: SupportedLanguage,
+    ) -> Vec<InternalImport> {
+        match language {
+            SupportedLanguage::Rust => self.resolve_rust(&import.path).into_iter().collect(),
+            SupportedLanguage::TypeScript
+            | SupportedLanguage::JavaScript
+            | SupportedLanguage::Vue => {
+                if import.path.starts_with('.') {
+                    return Vec::new();
+                }
+                let Some(file) = self.ts.resolve(&import.path, from_file) else {
+                    return Vec::new();
+                };
+                // Default and namespace imports name the module, not an item in it
+                let named = import.items.iter().all(|i| i != "*" && i != "default");
+                let items = if named {
+                    import.items.clone()
+                } else {
+                    Vec::new()
+                };
+                vec![InternalImport { file, items }]
+            }
+            SupportedLanguage::Python => self.resolve_python(import, from_file),
+            SupportedLanguage::Go => Vec::new(),
+        }
+    }
+
+    /// `crate::a::b` is a module import when it names a module file, otherwise
+    /// `b` is an item of `crate::a`.
+    fn resolve_rust(&self, path: &str) -> Option<InternalImport> {
+        if !path.starts_with("crate::") {
+            return None;
+        }
+        let path = path.trim_end_matches("::*").trim_end_matches("::self");
+        if let Some(file) = rust::resolve_module_path(&self.root, path)
+            .into_iter()
+            .next()
+        {
+            return Some(InternalImport {
+                file,
+                items: Vec::new(),
+            });
+        }
+        let (module, item) = path.rsplit_once("::")?;
+        let file = rust::resolve_module_path(&self.root, module)
+            .into_iter()
+            .next()?;
+        Some(InternalImport {
+            file,
+            items: vec![item.to_string()],
+        })
+    }
+
+    /// `import a.b` and `from a import b` (with `b` a submodule) are module imports;
+    /// `from a import X` imports the item `X` of `a`.
+    fn resolve_python(&self, import: &ImportInfo, from_file: &Path) -> Vec<InternalImport> {
+        if import.is_relative || import.path.is_empty() {
+            return Vec::new();
+        }
+        let Some(file) = python::resolve_project_import(&import.path, from_file, &self.root) else {
+            return Vec::new();
+        };
+        let module_import = import.items.iter().any(|i| i == "*")
+            || import.path.rsplit('.').next() == import.items.first().map(String::as_str);
+        if module_import {
+            return vec![InternalImport {
+                file,
+                items: Vec::new(),
+            }];
+        }
+
+        let mut resolved = Vec::new();
+        let mut items = Vec::new();
+        for item in &import.items {
+            let submodule = format!("{}.{}", import.path, item);
+            match python::resolve_project_import(&submodule, from_file, &self.root) {
+                Some(sub) => resolved.push(InternalImport {
+                    file: sub,
+                    items: Vec::new(),
+                }),
+                None => items.push(item.clone()),
+            }
+        }
+        if !items.is_empty() {
+            resolved.push(InternalImport { file, items });
+        }
+        resolved
+    }
+}
+
+/// Resolves imports to the project files they name, with the same rules as
+/// [`create_bundle`]: relative paths, `crate::` paths, tsconfig aliases and
+/// Python package imports.
+pub struct ImportResolver {
+    root: PathBuf,
+    internal: InternalResolver,
+}
+
+impl ImportResolver {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            internal: InternalResolver::new(root),
+        }
+    }
+
+    /// Files `import` of `from_file` refers to, relative to the root; empty for
+    /// packages and paths that do not exist.
+    pub fn resolve(
+        &self,
+        import: &ImportInfo,
+        from_file: &Path,
+        language: SupportedLanguage,
+    ) -> Vec<String> {
+        let mut files = Vec::new();
+        if import.is_relative {
+            let base_dir = from_file.parent().unwrap_or(Path::new("."));
+            files.extend(resolve_import(&import.path, base_dir, language));
+        }
+        if files.is_empty() {
+            files.extend(
+                self.internal
+                    .resolve(import, from_file, language)
+                    .into_iter()
+                    .map(|i| i.file),
+            );
+        }
+        let mut relative: Vec<String> = files
+            .iter()
+            .filter_map(|f| {
+                let normalized = normalize_path(f);
+                normalized
+                    .strip_prefix(&self.root)
+                    .ok()
+                    .map(|p| p.to_string_lossy().to_string())
+            })
+            .collect();
+        relative.dedup();
+        relative
+    }
+
+    /// Edges from `path` (relative to the root) to the project files its
+    /// imports resolve to.
+    pub fn edges(
+        &self,
+        path: &str,
+        imports: &[ImportInfo],
+        language: SupportedLanguage,
+    ) -> Vec<ImportEdge> {
+        let from_file = self.root.join(path);
+        imports
+            .iter()
+            .flat_map(|import| {
+                self.resolve(import, &from_file, language)
+                    .into_iter()
+                    .filter(|target| target != path)
+                    .map(|target| ImportEdge {
+                        from_path: path.to_string(),
+                        to_path: target,
+                        line: import.line as i32,
+                        specifier: import.path.clone(),
+                    })
+            })
+            .collect()
+    }
+}
+
+/// `src/ui/../db/pool.ts` -> `src/db/pool.ts`, without touching the file system
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Source of the definitions of `names` in `content` (with their doc comments and
+/// attributes), and the names that were found.
+fn extract_definitions(content: &str, extension: &str, names: &[String]) -> (String, Vec<String>) {
+    let Some(language) = SupportedLanguage::from_extension(extension) else {
+        return (String::new(), Vec::new());
+    };
+    let mut parser = CodeParser::new();
+    let symbols = parser.parse_symbols(content, language).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return (String::new(), Vec::new());
     }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut defined: Vec<String> = Vec::new();
+    for symbol in symbols.iter().filter(|s| s.kind != SymbolKind::LocalVar) {
+        if !names.contains(&symbol.name) {
+            continue;
+        }
+        let end = (symbol.line_end.max(symbol.line_start).max(0) as usize).min(lines.len() - 1);
+        let mut start = (symbol.line_start.max(0) as usize).min(end);
+        while start > 0 && is_decoration(lines[start - 1]) {
+            start -= 1;
+        }
+        ranges.push((start, end));
+        if !defined.contains(&symbol.name) {
+            defined.push(symbol.name.clone());
+        }
+    }
+
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    let definitions = merged
+        .iter()
+        .map(|&(start, end)| lines[start..=end].join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (definitions, defined)
+}
+
+/// Doc comments, attributes and decorators that belong to the definition below them
+fn is_decoration(line: &str) -> bool {
+    let t = line.trim_start();
+    ["///", "//!", "#[", "# ", "/**", "* ", "*/", "@"]
+        .iter()
+        .any(|p| t.starts_with(p))
+        || t == "*"
+}
+
+/// Regenerate markdown, line and token totals after the contents changed.
+fn refresh_totals(bundle: &mut ContextBundle) {
+    let main_path = Path::new(&bundle.main_file);
+    bundle.markdown = generate_markdown(main_path, &bundle.main_content, &bundle.dependencies);
+
+    let main_lines = bundle.main_content.lines().count();
+    let dep_lines: usize = bundle
+        .dependencies
+        .iter()
+        .map(|d| d.content.lines().count())
+        .sum();
+    bundle.total_lines = main_lines + dep_lines;
+    bundle.total_tokens_estimate = budget::count_tokens(&bundle.main_content)
+        + bundle
+            .dependencies
+            .iter()
+            .map(|d| budget::count_tokens(&d.content))
+            .sum::<usize>();
 }
 
 fn resolve_import(
@@ -222,57 +756,6 @@ fn resolve_tsconfig_paths(
     None
 }
 
-fn collect_dependency(
-    path: &Path,
-    reason: &str,
-    deps: &mut Vec<DependencyFile>,
-    visited: &mut HashSet<PathBuf>,
-    max_depth: u32,
-    current_depth: u32,
-) {
-    let canonical = path.canonicalize().unwrap_or(path.to_path_buf());
-    if visited.contains(&canonical) || current_depth > max_depth {
-        return;
-    }
-    visited.insert(canonical);
-
-    let content = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-
-    deps.push(DependencyFile {
-        path: path.to_string_lossy().to_string(),
-        content: content.clone(),
-        reason: format!("imports: {}", reason),
-        depth: current_depth,
-    });
-
-    if current_depth < max_depth {
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if let Some(language) = SupportedLanguage::from_extension(ext) {
-            let mut parser = CodeParser::new();
-            let imports = parser.parse_imports(&content, language);
-            let base_dir = path.parent().unwrap_or(Path::new("."));
-
-            for import in imports {
-                if import.is_relative {
-                    if let Some(resolved) = resolve_import(&import.path, base_dir, language) {
-                        collect_dependency(
-                            &resolved,
-                            &import.items.join(", "),
-                            deps,
-                            visited,
-                            max_depth,
-                            current_depth + 1,
-                        );
-                    }
-                }
-            }
-        }
-    }
-}
-
 fn generate_markdown(main_path: &Path, main_content: &str, deps: &[DependencyFile]) -> String {
     let mut md = String::new();
 
@@ -322,23 +805,7 @@ pub fn skeletonize_bundle(bundle: &mut ContextBundle) {
         }
     }
 
-    let main_path = Path::new(&bundle.main_file);
-    bundle.markdown = generate_markdown(main_path, &bundle.main_content, &bundle.dependencies);
-
-    let main_lines = bundle.main_content.lines().count();
-    let dep_lines: usize = bundle
-        .dependencies
-        .iter()
-        .map(|d| d.content.lines().count())
-        .sum();
-    bundle.total_lines = main_lines + dep_lines;
-    let total_chars = bundle.main_content.len()
-        + bundle
-            .dependencies
-            .iter()
-            .map(|d| d.content.len())
-            .sum::<usize>();
-    bundle.total_tokens_estimate = total_chars / 4;
+    refresh_totals(bundle);
 }
 
 /// Skeletonize only dependencies, keeping main_content intact.
@@ -355,23 +822,55 @@ pub fn skeletonize_deps_only(bundle: &mut ContextBundle) {
         }
     }
 
-    let main_path = Path::new(&bundle.main_file);
-    bundle.markdown = generate_markdown(main_path, &bundle.main_content, &bundle.dependencies);
+    refresh_totals(bundle);
+}
 
-    let main_lines = bundle.main_content.lines().count();
-    let dep_lines: usize = bundle
+/// Fit a bundle into `max_tokens`, returning the detail level of the main file and
+/// of each dependency. Deeper dependencies degrade first; the main file goes last.
+pub fn fit_bundle(bundle: &mut ContextBundle, max_tokens: usize) -> (Detail, Vec<Detail>) {
+    let main_ext = extension_of(&bundle.main_file);
+    let mut items = vec![BudgetItem {
+        content: &bundle.main_content,
+        extension: &main_ext,
+        priority: u32::MAX,
+    }];
+    let dep_exts: Vec<String> = bundle
         .dependencies
         .iter()
-        .map(|d| d.content.lines().count())
-        .sum();
-    bundle.total_lines = main_lines + dep_lines;
-    let total_chars = bundle.main_content.len()
-        + bundle
-            .dependencies
-            .iter()
-            .map(|d| d.content.len())
-            .sum::<usize>();
-    bundle.total_tokens_estimate = total_chars / 4;
+        .map(|d| extension_of(&d.path))
+        .collect();
+    let max_depth = bundle
+        .dependencies
+        .iter()
+        .map(|d| d.depth)
+        .max()
+        .unwrap_or(0);
+    for (dep, ext) in bundle.dependencies.iter().zip(&dep_exts) {
+        items.push(BudgetItem {
+            content: &dep.content,
+            extension: ext,
+            priority: max_depth - dep.depth,
+        });
+    }
+
+    let mut fitted = budget::fit_all(&items, max_tokens).into_iter();
+    let main = fitted.next().expect("main file is always budgeted");
+    bundle.main_content = main.content;
+    let mut dep_details = Vec::with_capacity(bundle.dependencies.len());
+    for (dep, f) in bundle.dependencies.iter_mut().zip(fitted) {
+        dep.content = f.content;
+        dep_details.push(f.detail);
+    }
+    refresh_totals(bundle);
+    (main.detail, dep_details)
+}
+
+fn extension_of(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_string()
 }
 
 /// Skeletonize a single file by content and extension.
@@ -381,3 +880,172 @@ pub fn skeletonize_content(content: &str, extension: &str) -> String {
     };
     parser::generate_skeleton(content, lang).unwrap_or_else(|_| content.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_import_includes_only_imported_definitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(
+            src.join("main.rs"),
+            "use crate::models::{User, Role};\n\nfn main() {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            src.join("models.rs"),
+            "/// A user.\npub struct User {\n    pub id: u64,\n}\n\npub fn unrelated() -> u32 {\n    42\n}\n\npub enum Role {\n    Admin,\n}\n",
+        )
+        .unwrap();
+
+        let bundle = create_bundle(dir.path(), &src.join("main.rs"), 2);
+        assert_eq!(bundle.dependencies.len(), 1);
+        let dep = &bundle.dependencies[0];
+        assert!(dep.path.ends_with("models.rs"));
+        assert!(dep.content.starts_with("/// A user.\npub struct User"));
+        assert!(dep.content.contains("pub enum Role"));
+        assert!(!dep.content.contains("unrelated"));
+        assert!(bundle.unresolved_items.is_empty());
+    }
+
+    #[test]
+    fn test_crate_module_import_includes_whole_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("db")).unwrap();
+        std::fs::write(
+            src.join("main.rs"),
+            "use crate::db::pool;\n\nfn main() {}\n",
+        )
+        .unwrap();
+        std::fs::write(src.join("db/mod.rs"), "pub mod pool;\n").unwrap();
+        std::fs::write(
+            src.join("db/pool.rs"),
+            "pub fn connect() {}\n\npub fn close() {}\n",
+        )
+        .unwrap();
+
+        let bundle = create_bundle(dir.path(), &src.join("main.rs"), 1);
+        assert_eq!(bundle.dependencies.len(), 1);
+        assert!(bundle.dependencies[0].path.ends_with("db/pool.rs"));
+        assert!(bundle.dependencies[0].content.contains("pub fn close()"));
+    }
+
+    #[test]
+    fn test_tsconfig_alias_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("utils")).unwrap();
+        std::fs::write(
+            dir.path().join("tsconfig.json"),
+            "{\n  // aliases\n  \"compilerOptions\": { \"baseUrl\": \".\", \"paths\": { \"@/*\": [\"src/*\"] } }\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            src.join("app.ts"),
+            "import { formatDate } from '@/utils/date';\n\nformatDate();\n",
+        )
+        .unwrap();
+        std::fs::write(
+            src.join("utils/date.ts"),
+            "export function formatDate(): string {\n  return '';\n}\n\nexport function parseDate(): number {\n  return 0;\n}\n",
+        )
+        .unwrap();
+
+        let bundle = create_bundle(dir.path(), &src.join("app.ts"), 1);
+        assert_eq!(bundle.dependencies.len(), 1);
+        let dep = &bundle.dependencies[0];
+        assert!(dep.path.ends_with("utils/date.ts"));
+        assert!(dep.content.contains("formatDate"));
+        assert!(!dep.content.contains("parseDate"));
+    }
+
+    #[test]
+    fn test_python_package_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg = dir.path().join("pkg");
+        std::fs::create_dir_all(&pkg).unwrap();
+        std::fs::write(pkg.join("__init__.py"), "").unwrap();
+        std::fs::write(
+            pkg.join("models.py"),
+            "class User:\n    pass\n\n\ndef helper():\n    return 1\n",
+        )
+        .unwrap();
+        std::fs::write(pkg.join("jobs.py"), "def run():\n    pass\n").unwrap();
+        std::fs::write(
+            dir.path().join("app.py"),
+            "from pkg.models import User\nfrom pkg import jobs\n",
+        )
+        .unwrap();
+
+        let bundle = create_bundle(dir.path(), &dir.path().join("app.py"), 1);
+        let dep = |name: &str| {
+            bundle
+                .dependencies
+                .iter()
+                .find(|d| d.path.ends_with(name))
+                .unwrap()
+        };
+        // An item of a module: only its definition
+        assert!(dep("pkg/models.py").content.contains("class User"));
+        assert!(!dep("pkg/models.py").content.contains("helper"));
+        // A submodule imported from its package: the whole file
+        assert!(dep("pkg/jobs.py").content.contains("def run()"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_from_index_prefers_module_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("api")).unwrap();
+        std::fs::create_dir_all(src.join("legacy")).unwrap();
+        std::fs::write(
+            src.join("main.rs"),
+            "use crate::api::Client;\n\nfn main() {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            src.join("api/mod.rs"),
+            "mod client;\npub use self::client::Client;\n",
+        )
+        .unwrap();
+        let client = "pub struct Client {\n    pub url: String,\n}\n\npub fn other() {}\n";
+        std::fs::write(src.join("api/client.rs"), client).unwrap();
+        std::fs::write(src.join("legacy/client.rs"), client).unwrap();
+
+        let index = tempfile::tempdir().unwrap();
+        let sqlite = SqliteStorage::new(index.path().join("graph.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.migrate().await.unwrap();
+        for path in ["src/legacy/client.rs", "src/api/client.rs"] {
+            let file_id = sqlite.upsert_file(path, 1, "h").await.unwrap();
+            let symbol = crate::models::Symbol {
+                id: 0,
+                file_id,
+                name: "Client".to_string(),
+                kind: SymbolKind::Struct,
+                line_start: 0,
+                line_end: 2,
+                signature: None,
+            };
+            sqlite.insert_symbols(file_id, &[symbol]).await.unwrap();
+        }
+
+        let mut bundle = create_bundle(dir.path(), &src.join("main.rs"), 2);
+        assert_eq!(bundle.unresolved_items.len(), 1);
+        resolve_from_index(&mut bundle, &sqlite, dir.path()).await;
+        assert!(bundle.unresolved_items.is_empty());
+        let dep = bundle
+            .dependencies
+            .iter()
+            .find(|d| d.path.ends_with("client.rs"))
+            .unwrap();
+        assert!(dep.path.ends_with("api/client.rs"));
+        assert!(dep.content.starts_with("pub struct Client"));
+        assert!(!dep.content.contains("other"));
+    }
+}
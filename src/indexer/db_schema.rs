@@ -0,0 +1,574 @@
+//! Database schema model built from SQL migrations.
+//!
+//! Migration files are replayed in order (`CREATE`/`ALTER`/`DROP`/`RENAME`)
+//! into tables, columns, indexes and foreign keys. Dropped and renamed
+//! objects are kept with the migration that removed them, so code still
+//! querying a column that went away two migrations ago can be pointed at it.
+
+use std::path::Path;
+
+use super::parser::sql::{self, SqlAlter, SqlColumn, SqlConstraint, SqlDefinition};
+use crate::storage::SqliteStorage;
+
+/// `index_metadata` key: hash of the migration files the stored model was built from
+pub const SCHEMA_SOURCES_KEY: &str = "db_schema_sources";
+
+/// Directories whose `.sql` files are migrations or schema dumps
+const MIGRATION_DIRS: &[&str] = &[
+    "migrations",
+    "migration",
+    "migrate",
+    "db",
+    "schema",
+    "schemas",
+    "sql",
+];
+
+/// Full schema dumps; applied before migrations
+const SCHEMA_FILES: &[&str] = &["schema.sql", "structure.sql"];
+
+/// A place in a migration file (1-based line).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableModel {
+    pub name: String,
+    pub defined: Location,
+    /// Set when a later migration dropped or renamed the table
+    pub dropped: Option<Location>,
+    pub renamed_to: Option<String>,
+    pub columns: Vec<ColumnModel>,
+    pub indexes: Vec<IndexModel>,
+    pub foreign_keys: Vec<ForeignKeyModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnModel {
+    pub name: String,
+    pub data_type: String,
+    pub not_null: bool,
+    pub primary_key: bool,
+    pub defined: Location,
+    pub dropped: Option<Location>,
+    pub renamed_to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexModel {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+    pub defined: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyModel {
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    pub defined: Location,
+}
+
+/// Every table the migrations ever created, live ones first.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaModel {
+    pub tables: Vec<TableModel>,
+}
+
+impl SchemaModel {
+    /// The live table named `name` (case-insensitive, default schema optional).
+    pub fn table(&self, name: &str) -> Option<&TableModel> {
+        let key = table_key(name);
+        self.tables
+            .iter()
+            .find(|t| t.dropped.is_none() && table_key(&t.name) == key)
+    }
+}
+
+impl TableModel {
+    pub fn column(&self, name: &str) -> Option<&ColumnModel> {
+        self.columns
+            .iter()
+            .find(|c| c.dropped.is_none() && c.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Lookup key of a table name: lowercase, without the default schema.
+pub fn table_key(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    for schema in ["public.", "main.", "dbo."] {
+        if let Some(rest) = lower.strip_prefix(schema) {
+            return rest.to_string();
+        }
+    }
+    lower
+}
+
+/// Whether a root-relative path is a migration (or schema dump) to replay.
+/// Down migrations are skipped.
+pub fn is_migration(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    if !lower.ends_with(".sql") {
+        return false;
+    }
+    let mut parts: Vec<&str> = lower.split('/').collect();
+    let file = parts.pop().unwrap_or_default();
+    if file == "down.sql" || file.ends_with(".down.sql") || file.ends_with("_down.sql") {
+        return false;
+    }
+    SCHEMA_FILES.contains(&file) || parts.iter().any(|dir| MIGRATION_DIRS.contains(dir))
+}
+
+/// Sort migration paths into replay order: schema dumps first, then by path,
+/// which follows the numeric or timestamp prefixes migration tools use.
+pub fn sort_migrations(paths: &mut [String]) {
+    paths.sort_by_key(|path| {
+        let file = path.rsplit('/').next().unwrap_or(path).to_ascii_lowercase();
+        (!SCHEMA_FILES.contains(&file.as_str()), path.clone())
+    });
+}
+
+/// Replay migrations, given as `(path, content)` in replay order.
+pub fn build_schema(migrations: &[(String, String)]) -> SchemaModel {
+    let mut builder = Builder::default();
+    for (path, content) in migrations {
+        for statement in sql::split_statements(content) {
+            builder.apply(path, statement.start_line, sql::classify(&statement));
+        }
+    }
+    let mut tables = builder.tables;
+    // Live tables first, each group in creation order
+    tables.sort_by_key(|t| t.dropped.is_some());
+    SchemaModel { tables }
+}
+
+/// Rebuild the stored schema model when the migration files changed since the
+/// last build. Returns the number of live tables, or `None` if nothing changed.
+pub async fn refresh(sqlite: &SqliteStorage, root: &Path) -> anyhow::Result<Option<usize>> {
+    let mut files: Vec<(String, String)> = sqlite
+        .get_files_by_language("sql")
+        .await?
+        .into_iter()
+        .filter(|(path, _)| is_migration(path))
+        .collect();
+    files.sort();
+    let mut hasher = blake3::Hasher::new();
+    for (path, hash) in &files {
+        hasher.update(path.as_bytes());
+        hasher.update(hash.as_bytes());
+    }
+    let sources = hasher.finalize().to_hex().to_string();
+    if sqlite.get_index_meta(SCHEMA_SOURCES_KEY).await?.as_deref() == Some(sources.as_str()) {
+        return Ok(None);
+    }
+
+    let mut paths: Vec<String> = files.into_iter().map(|(path, _)| path).collect();
+    sort_migrations(&mut paths);
+    let mut migrations = Vec::with_capacity(paths.len());
+    for path in paths {
+        match tokio::fs::read_to_string(root.join(&path)).await {
+            Ok(content) => migrations.push((path, content)),
+            Err(e) => tracing::debug!("Schema: cannot read {}: {}", path, e),
+        }
+    }
+
+    let model = build_schema(&migrations);
+    sqlite.replace_db_schema(&model).await?;
+    sqlite.set_index_meta(SCHEMA_SOURCES_KEY, &sources).await?;
+    Ok(Some(
+        model.tables.iter().filter(|t| t.dropped.is_none()).count(),
+    ))
+}
+
+#[derive(Default)]
+struct Builder {
+    tables: Vec<TableModel>,
+}
+
+impl Builder {
+    fn live(&mut self, name: &str) -> Option<usize> {
+        let key = table_key(name);
+        self.tables
+            .iter()
+            .position(|t| t.dropped.is_none() && table_key(&t.name) == key)
+    }
+
+    /// The live table, created on first mention when an `ALTER` or `CREATE
+    /// INDEX` refers to a table the migrations never created.
+    fn live_or_implicit(&mut self, name: &str, at: &Location) -> usize {
+        if let Some(idx) = self.live(name) {
+            return idx;
+        }
+        self.forget(name);
+        self.tables.push(TableModel {
+            name: name.to_string(),
+            defined: at.clone(),
+            dropped: None,
+            renamed_to: None,
+            columns: Vec::new(),
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+        });
+        self.tables.len() - 1
+    }
+
+    /// Drop history of a name that is about to be defined again.
+    fn forget(&mut self, name: &str) {
+        let key = table_key(name);
+        self.tables.retain(|t| table_key(&t.name) != key);
+    }
+
+    fn apply(&mut self, file: &str, statement_line: usize, definition: SqlDefinition) {
+        let at = |line: usize| Location {
+            file: file.to_string(),
+            line: line + 1,
+        };
+        let here = at(statement_line);
+
+        match definition {
+            SqlDefinition::Table {
+                name,
+                columns,
+                constraints,
+                if_not_exists,
+            } => {
+                if if_not_exists && self.live(&name).is_some() {
+                    return;
+                }
+                self.forget(&name);
+                let mut table = TableModel {
+                    name,
+                    defined: here,
+                    dropped: None,
+                    renamed_to: None,
+                    columns: Vec::new(),
+                    indexes: Vec::new(),
+                    foreign_keys: Vec::new(),
+                };
+                for column in &columns {
+                    add_column(&mut table, column, at(column.line));
+                }
+                for constraint in constraints {
+                    add_constraint(&mut table, constraint, &at);
+                }
+                self.tables.push(table);
+            }
+            SqlDefinition::AlterTable { table, actions } => {
+                let mut idx = self.live_or_implicit(&table, &here);
+                for action in actions {
+                    idx = self.alter(idx, action, &here, &at);
+                }
+            }
+            SqlDefinition::DropTable { names } => {
+                for name in names {
+                    if let Some(idx) = self.live(&name) {
+                        self.tables[idx].dropped = Some(here.clone());
+                    }
+                }
+            }
+            SqlDefinition::Index {
+                name,
+                table,
+                columns,
+                unique,
+            } => {
+                if table.is_empty() {
+                    return;
+                }
+                let idx = self.live_or_implicit(&table, &here);
+                let indexes = &mut self.tables[idx].indexes;
+                indexes.retain(|i| !i.name.eq_ignore_ascii_case(&name));
+                indexes.push(IndexModel {
+                    name,
+                    columns,
+                    unique,
+                    defined: here,
+                });
+            }
+            SqlDefinition::DropIndex { names } => {
+                for name in names {
+                    let short = name.rsplit('.').next().unwrap_or(&name).to_string();
+                    for table in self.tables.iter_mut().filter(|t| t.dropped.is_none()) {
+                        table
+                            .indexes
+                            .retain(|i| !i.name.eq_ignore_ascii_case(&short));
+                    }
+                }
+            }
+            SqlDefinition::View { .. } | SqlDefinition::Routine { .. } | SqlDefinition::Other => {}
+        }
+    }
+
+    /// Apply one `ALTER TABLE` action to table `idx`; returns the table's
+    /// index afterwards (a rename moves it).
+    fn alter(
+        &mut self,
+        idx: usize,
+        action: SqlAlter,
+        here: &Location,
+        at: &dyn Fn(usize) -> Location,
+    ) -> usize {
+        let table = &mut self.tables[idx];
+        match action {
+            SqlAlter::AddColumn(column) => {
+                let location = at(column.line);
+                add_column(table, &column, location);
+            }
+            SqlAlter::AddConstraint(constraint) => add_constraint(table, constraint, at),
+            SqlAlter::DropColumn(name) => {
+                if let Some(column) = live_column(table, &name) {
+                    column.dropped = Some(here.clone());
+                }
+                let lower = name.to_ascii_lowercase();
+                let mentions =
+                    |columns: &[String]| columns.iter().any(|c| c.to_ascii_lowercase() == lower);
+                table.indexes.retain(|i| !mentions(&i.columns));
+                table.foreign_keys.retain(|fk| !mentions(&fk.columns));
+            }
+            SqlAlter::RenameColumn { from, to } => {
+                let table_name = table.name.clone();
+                rename_column(table, &from, &to, here);
+                self.rename_references(&table_name, Some((&from, &to)), None);
+            }
+            SqlAlter::SetType { column, data_type } => {
+                if let Some(column) = live_column(table, &column) {
+                    column.data_type = data_type;
+                }
+            }
+            SqlAlter::SetNotNull { column, not_null } => {
+                if let Some(column) = live_column(table, &column) {
+                    column.not_null = not_null;
+                }
+            }
+            SqlAlter::ModifyColumn { from, column } => {
+                if !from.eq_ignore_ascii_case(&column.name) {
+                    let table_name = table.name.clone();
+                    rename_column(table, &from, &column.name, here);
+                    self.rename_references(&table_name, Some((&from, &column.name)), None);
+                }
+                let table = &mut self.tables[idx];
+                if let Some(existing) = live_column(table, &column.name) {
+                    existing.data_type = column.data_type();
+                    existing.not_null = column.is_not_null();
+                    existing.primary_key |= column.is_primary_key();
+                }
+            }
+            SqlAlter::RenameTable(to) => {
+                let old = table.name.clone();
+                self.forget(&to);
+                let idx = self.live(&old).unwrap_or(idx);
+                let mut renamed = self.tables[idx].clone();
+                renamed.name = to.clone();
+                renamed.defined = here.clone();
+                renamed.columns.retain(|c| c.dropped.is_none());
+                let original = &mut self.tables[idx];
+                original.dropped = Some(here.clone());
+                original.renamed_to = Some(to.clone());
+                self.tables.push(renamed);
+                self.rename_references(&old, None, Some(&to));
+                return self.tables.len() - 1;
+            }
+            SqlAlter::Other => {}
+        }
+        idx
+    }
+
+    /// Point foreign keys of live tables at a renamed column or table.
+    fn rename_references(
+        &mut self,
+        table: &str,
+        column: Option<(&str, &str)>,
+        new_table: Option<&str>,
+    ) {
+        let key = table_key(table);
+        for fk in self
+            .tables
+            .iter_mut()
+            .filter(|t| t.dropped.is_none())
+            .flat_map(|t| t.foreign_keys.iter_mut())
+            .filter(|fk| table_key(&fk.ref_table) == key)
+        {
+            if let Some((from, to)) = column {
+                for c in fk.ref_columns.iter_mut() {
+                    if c.eq_ignore_ascii_case(from) {
+                        *c = to.to_string();
+                    }
+                }
+            }
+            if let Some(new_table) = new_table {
+                fk.ref_table = new_table.to_string();
+            }
+        }
+    }
+}
+
+fn live_column<'a>(table: &'a mut TableModel, name: &str) -> Option<&'a mut ColumnModel> {
+    table
+        .columns
+        .iter_mut()
+        .find(|c| c.dropped.is_none() && c.name.eq_ignore_ascii_case(name))
+}
+
+fn add_column(table: &mut TableModel, column: &SqlColumn, defined: Location) {
+    table
+        .columns
+        .retain(|c| !c.name.eq_ignore_ascii_case(&column.name));
+    if let Some(fk) = column.references() {
+        table.foreign_keys.push(ForeignKeyModel {
+            columns: fk.columns,
+            ref_table: fk.ref_table,
+            ref_columns: fk.ref_columns,
+            defined: defined.clone(),
+        });
+    }
+    table.columns.push(ColumnModel {
+        name: column.name.clone(),
+        data_type: column.data_type(),
+        not_null: column.is_not_null(),
+        primary_key: column.is_primary_key(),
+        defined,
+        dropped: None,
+        renamed_to: None,
+    });
+}
+
+fn add_constraint(
+    table: &mut TableModel,
+    constraint: SqlConstraint,
+    at: &dyn Fn(usize) -> Location,
+) {
+    match constraint {
+        SqlConstraint::PrimaryKey(names) => {
+            for name in names {
+                if let Some(column) = live_column(table, &name) {
+                    column.primary_key = true;
+                    column.not_null = true;
+                }
+            }
+        }
+        SqlConstraint::ForeignKey(fk) => table.foreign_keys.push(ForeignKeyModel {
+            defined: at(fk.line),
+            columns: fk.columns,
+            ref_table: fk.ref_table,
+            ref_columns: fk.ref_columns,
+        }),
+    }
+}
+
+/// Keep the old name as a dropped column pointing at the new one.
+fn rename_column(table: &mut TableModel, from: &str, to: &str, here: &Location) {
+    let Some(old) = live_column(table, from) else {
+        return;
+    };
+    let mut renamed = old.clone();
+    old.dropped = Some(here.clone());
+    old.renamed_to = Some(to.to_string());
+    renamed.name = to.to_string();
+    table.columns.retain(|c| !c.name.eq_ignore_ascii_case(to));
+    table.columns.push(renamed);
+
+    for names in table
+        .indexes
+        .iter_mut()
+        .map(|i| &mut i.columns)
+        .chain(table.foreign_keys.iter_mut().map(|fk| &mut fk.columns))
+    {
+        for name in names.iter_mut() {
+            if name.eq_ignore_ascii_case(from) {
+                *name = to.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_paths() {
+        assert!(is_migration("migrations/001_init.sql"));
+        assert!(is_migration("backend/db/migrate/20240101_users.up.sql"));
+        assert!(is_migration("schema.sql"));
+        assert!(!is_migration("migrations/2024-01-01-000000_users/down.sql"));
+        assert!(!is_migration("migrations/002_users.down.sql"));
+        assert!(!is_migration("queries/report.sql"));
+        assert!(!is_migration("migrations/README.md"));
+
+        let mut paths = vec![
+            "migrations/010_b.sql".to_string(),
+            "migrations/002_a.sql".to_string(),
+            "db/schema.sql".to_string(),
+        ];
+        sort_migrations(&mut paths);
+        assert_eq!(
+            paths,
+            vec![
+                "db/schema.sql",
+                "migrations/002_a.sql",
+                "migrations/010_b.sql"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_tracks_drops_and_renames() {
+        let migrations = vec![
+            (
+                "migrations/001_init.sql".to_string(),
+                "CREATE TABLE users (\n\
+                     id INTEGER PRIMARY KEY,\n\
+                     mail TEXT NOT NULL,\n\
+                     nickname TEXT\n\
+                 );\n\
+                 CREATE TABLE posts (id INTEGER PRIMARY KEY, author_id INTEGER REFERENCES users(id));\n\
+                 CREATE INDEX idx_users_mail ON users(mail);\n"
+                    .to_string(),
+            ),
+            (
+                "migrations/002_cleanup.sql".to_string(),
+                "ALTER TABLE users RENAME COLUMN mail TO email;\n\
+                 ALTER TABLE users DROP COLUMN nickname;\n\
+                 ALTER TABLE users RENAME TO accounts;\n\
+                 CREATE TABLE IF NOT EXISTS accounts (id INTEGER);\n"
+                    .to_string(),
+            ),
+        ];
+        let model = build_schema(&migrations);
+
+        assert!(model.table("users").is_none());
+        let accounts = model.table("public.accounts").unwrap();
+        let live: Vec<_> = accounts
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.not_null))
+            .collect();
+        assert_eq!(live, vec![("id", true), ("email", true)]);
+        assert_eq!(accounts.indexes[0].columns, vec!["email"]);
+
+        let posts = model.table("posts").unwrap();
+        assert_eq!(posts.foreign_keys[0].ref_table, "accounts");
+        assert_eq!(posts.columns[1].defined.line, 6);
+
+        let users = model
+            .tables
+            .iter()
+            .find(|t| t.name == "users")
+            .expect("old table kept");
+        assert_eq!(users.renamed_to.as_deref(), Some("accounts"));
+        let mail = users.columns.iter().find(|c| c.name == "mail").unwrap();
+        assert_eq!(mail.renamed_to.as_deref(), Some("email"));
+        let nickname = users.columns.iter().find(|c| c.name == "nickname").unwrap();
+        assert_eq!(
+            nickname.dropped,
+            Some(Location {
+                file: "migrations/002_cleanup.sql".into(),
+                line: 2
+            })
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use serde::Deserialize;
 use std::path::Path;
-use std::process::Command;
+use std::process::Output;
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 use crate::storage::SqliteStorage;
 
@@ -82,10 +84,24 @@ struct TscDiagnostic {
     message: String,
 }
 
+/// Run a checker to completion. The child is killed if `cancel` fires first
+/// (`Ok(None)`).
+async fn output_or_cancel(
+    cmd: &mut Command,
+    cancel: &CancellationToken,
+) -> std::io::Result<Option<Output>> {
+    cmd.kill_on_drop(true);
+    tokio::select! {
+        out = cmd.output() => out.map(Some),
+        _ = cancel.cancelled() => Ok(None),
+    }
+}
+
 /// Run cargo check and collect diagnostics
 pub async fn run_cargo_check(
     root: &Path,
     sqlite: &SqliteStorage,
+    cancel: &CancellationToken,
 ) -> anyhow::Result<(usize, usize)> {
     let cargo_toml = root.join("Cargo.toml");
     if !cargo_toml.exists() {
@@ -94,12 +110,15 @@ pub async fn run_cargo_check(
 
     tracing::info!("Running cargo check...");
 
-    let output = Command::new("cargo")
-        .arg("check")
+    let mut cmd = Command::new("cargo");
+    cmd.arg("check")
         .arg("--message-format=json")
         .arg("--quiet")
-        .current_dir(root)
-        .output()?;
+        .current_dir(root);
+    let Some(output) = output_or_cancel(&mut cmd, cancel).await? else {
+        tracing::info!("cargo check cancelled");
+        return Ok((0, 0));
+    };
 
     // Clear existing errors
     sqlite.clear_active_errors().await?;
@@ -179,7 +198,11 @@ async fn process_cargo_message(
 }
 
 /// Run tsc --noEmit and collect diagnostics
-pub async fn run_tsc_check(root: &Path, sqlite: &SqliteStorage) -> anyhow::Result<(usize, usize)> {
+pub async fn run_tsc_check(
+    root: &Path,
+    sqlite: &SqliteStorage,
+    cancel: &CancellationToken,
+) -> anyhow::Result<(usize, usize)> {
     let tsconfig = root.join("tsconfig.json");
     let package_json = root.join("package.json");
 
@@ -196,20 +219,22 @@ pub async fn run_tsc_check(root: &Path, sqlite: &SqliteStorage) -> anyhow::Resul
 
     tracing::info!("Running TypeScript check...");
 
-    let output = if tsc_cmd == "npx" {
-        Command::new("npx")
-            .args(["tsc", "--noEmit", "--pretty", "false"])
-            .current_dir(root)
-            .output()
+    let mut cmd = if tsc_cmd == "npx" {
+        let mut cmd = Command::new("npx");
+        cmd.arg("tsc");
+        cmd
     } else {
         Command::new(tsc_cmd)
-            .args(["--noEmit", "--pretty", "false"])
-            .current_dir(root)
-            .output()
     };
+    cmd.args(["--noEmit", "--pretty", "false"])
+        .current_dir(root);
 
-    let output = match output {
-        Ok(o) => o,
+    let output = match output_or_cancel(&mut cmd, cancel).await {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            tracing::info!("TypeScript check cancelled");
+            return Ok((0, 0));
+        }
         Err(_) => {
             tracing::warn!("TypeScript compiler not available");
             return Ok((0, 0));
@@ -269,10 +294,12 @@ pub async fn run_tsc_check(root: &Path, sqlite: &SqliteStorage) -> anyhow::Resul
     Ok((error_count, warning_count))
 }
 
-/// Run all available diagnostics (rate-limited)
+/// Run all available diagnostics (rate-limited). Stops at the first checker
+/// that is cancelled; counts gathered so far are returned with `cancelled` set.
 pub async fn run_diagnostics(
     root: &Path,
     sqlite: &SqliteStorage,
+    cancel: &CancellationToken,
 ) -> anyhow::Result<DiagnosticsResult> {
     if !rate_check(&LAST_DIAGNOSTICS, DIAGNOSTICS_COOLDOWN_SECS).await {
         return Ok(DiagnosticsResult {
@@ -282,13 +309,20 @@ pub async fn run_diagnostics(
             cargo_warnings: 0,
             tsc_errors: 0,
             tsc_warnings: 0,
+            cancelled: false,
         });
     }
 
     sqlite.clear_active_errors().await?;
 
-    let (cargo_errors, cargo_warnings) = run_cargo_check(root, sqlite).await.unwrap_or((0, 0));
-    let (tsc_errors, tsc_warnings) = run_tsc_check(root, sqlite).await.unwrap_or((0, 0));
+    let (cargo_errors, cargo_warnings) = run_cargo_check(root, sqlite, cancel)
+        .await
+        .unwrap_or((0, 0));
+    let (tsc_errors, tsc_warnings) = if cancel.is_cancelled() {
+        (0, 0)
+    } else {
+        run_tsc_check(root, sqlite, cancel).await.unwrap_or((0, 0))
+    };
 
     Ok(DiagnosticsResult {
         total_errors: cargo_errors + tsc_errors,
@@ -297,6 +331,7 @@ pub async fn run_diagnostics(
         cargo_warnings,
         tsc_errors,
         tsc_warnings,
+        cancelled: cancel.is_cancelled(),
     })
 }
 
@@ -308,6 +343,7 @@ pub struct DiagnosticsResult {
     pub cargo_warnings: usize,
     pub tsc_errors: usize,
     pub tsc_warnings: usize,
+    pub cancelled: bool,
 }
 
 // === verify_patch: Sandboxed Verification ===
@@ -1,4 +1,5 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::Path;
 
@@ -26,8 +27,9 @@ impl Domain {
     }
 }
 
-/// Domain detection configuration
-#[derive(Debug, Clone, Default)]
+/// Domain detection configuration, `[domains]` in config.toml
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct DomainConfig {
     pub rs_paths: Vec<String>,
     pub py_paths: Vec<String>,
@@ -69,6 +71,24 @@ impl DomainConfig {
             shared_paths: vec!["shared/".into(), "common/".into(), "types/".into()],
         }
     }
+
+    /// The defaults, with each list set in `configured` replacing its default;
+    /// an empty list keeps it.
+    pub fn with_overrides(configured: &DomainConfig) -> Self {
+        let mut config = Self::default_config();
+        for (list, own) in [
+            (&mut config.rs_paths, &configured.rs_paths),
+            (&mut config.py_paths, &configured.py_paths),
+            (&mut config.frontend_paths, &configured.frontend_paths),
+            (&mut config.ops_paths, &configured.ops_paths),
+            (&mut config.shared_paths, &configured.shared_paths),
+        ] {
+            if !own.is_empty() {
+                *list = own.clone();
+            }
+        }
+        config
+    }
 }
 
 /// Detect domain by folder path (Level 1)
@@ -564,7 +584,7 @@ pub fn parse_backend_routes(content: &str, extension: &str) -> Vec<ParsedEndpoin
 }
 
 /// Parsed frontend API call
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[allow(dead_code)]
 pub struct ParsedApiCall {
     pub method: Option<String>,
@@ -572,6 +592,10 @@ pub struct ParsedApiCall {
     pub path_pattern: String, // Normalized with :params
     pub type_used: Option<String>,
     pub line: u32,
+    /// Imported client or function the call goes through
+    pub client: Option<String>,
+    pub client_source: Option<String>,
+    pub needs_client: bool,
 }
 
 /// Parse fetch/axios calls from TypeScript/JavaScript
@@ -600,6 +624,7 @@ pub fn parse_frontend_api_calls(content: &str) -> Vec<ParsedApiCall> {
                 path_pattern: normalize_api_path(path),
                 type_used: None,
                 line: line_num as u32,
+                ..Default::default()
             });
         }
 
@@ -614,6 +639,7 @@ pub fn parse_frontend_api_calls(content: &str) -> Vec<ParsedApiCall> {
                 path_pattern: normalize_api_path(path),
                 type_used: None,
                 line: line_num as u32,
+                ..Default::default()
             });
         }
     }
@@ -621,9 +647,10 @@ pub fn parse_frontend_api_calls(content: &str) -> Vec<ParsedApiCall> {
     calls
 }
 
-/// Normalize API path: /api/users/${id} -> /api/users/:id
+/// Normalize API path: /api/users/${id} -> /api/users/:param. OpenAPI/proto
+/// `{id}` and Flask `<int:id>` parameters are normalized the same way.
 pub fn normalize_api_path(path: &str) -> String {
-    let re = Regex::new(r"\$\{[^}]+\}").unwrap();
+    let re = Regex::new(r"\$\{[^}]+\}|\{[^}]+\}|<[^>]+>").unwrap();
     re.replace_all(path, ":param").to_string()
 }
 
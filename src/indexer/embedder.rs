@@ -293,6 +293,22 @@ impl EmbedderPool {
         })
     }
 
+    /// Pool without a loaded model: embedding fails, everything else works.
+    /// For handler tests that never embed.
+    #[cfg(test)]
+    pub fn unloaded() -> Self {
+        Self {
+            instances: RwLock::new(Vec::new()),
+            semaphore: Arc::new(RwLock::new(Arc::new(Semaphore::new(1)))),
+            pool_size: AtomicUsize::new(1),
+            next_idx: AtomicUsize::new(0),
+            model_dimension: 384,
+            model_name: "BGESmallENV15".to_string(),
+            model: EmbeddingModel::BGESmallENV15,
+            cache_dir: std::env::temp_dir().join("fastembed"),
+        }
+    }
+
     /// Масштабировать пул до `target_size` инстансов (для индексации).
     /// Если текущий размер >= target, ничего не делает.
     pub async fn scale_up(&self, target_size: usize) -> Result<()> {
@@ -47,6 +47,15 @@ impl GitRepo {
         })
     }
 
+    /// Commit id that HEAD currently points to (None for an unborn branch).
+    pub fn head_commit(&self) -> Option<String> {
+        self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|oid| oid.to_string())
+    }
+
     /// Get blame info for a specific line range in a file (cached).
     pub fn blame_lines(&self, file_path: &Path, start_line: u32, end_line: u32) -> Vec<BlameInfo> {
         let key = format!("{}:{}:{}", file_path.display(), start_line, end_line);
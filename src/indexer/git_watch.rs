@@ -0,0 +1,320 @@
+//! Git operation detection for the file watcher.
+//!
+//! A branch switch, rebase or stash pop rewrites many files at once. Instead of
+//! feeding each one to the indexer as its own task, the watcher holds file events
+//! while git holds `index.lock` or rewrites `HEAD`/refs. Once the operation settles
+//! it either replays the held events (nothing git-level changed, e.g. `git add`) or
+//! emits one sync over the files that differ between the old and new trees.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use git2::{Oid, Repository, Tree};
+
+/// Quiet period after the last git event before an operation counts as finished
+pub const SETTLE: Duration = Duration::from_millis(1500);
+/// A rebase stopped on conflicts is flushed after this long, so edits made while
+/// resolving them still get indexed
+const MAX_PAUSE: Duration = Duration::from_secs(60);
+
+/// Entries of the git dir whose changes mark a git operation.
+const OPERATION_MARKERS: &[&str] = &[
+    "HEAD",
+    "HEAD.lock",
+    "ORIG_HEAD",
+    "index.lock",
+    "packed-refs",
+    "refs",
+    "rebase-merge",
+    "rebase-apply",
+    "MERGE_HEAD",
+    "CHERRY_PICK_HEAD",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    head: Option<Oid>,
+    branch: Option<String>,
+    stash: Option<Oid>,
+}
+
+struct Pending {
+    before: Snapshot,
+    started: Instant,
+    last_git_event: Instant,
+    rebase_seen: bool,
+    held: BTreeSet<PathBuf>,
+}
+
+/// What to do once a paused git operation settles.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Flush {
+    /// Nothing git-level changed: process the held file events as usual
+    Replay(Vec<PathBuf>),
+    /// Index everything the operation touched as a single sync
+    Sync { reason: String, paths: Vec<PathBuf> },
+}
+
+/// Tracks `HEAD`, the current branch and the stash of the project's repository.
+pub struct GitWatch {
+    repo: Repository,
+    git_dir: PathBuf,
+    workdir: PathBuf,
+    current: Snapshot,
+    pending: Option<Pending>,
+}
+
+impl GitWatch {
+    /// `None` for projects outside a git repository and for bare repositories.
+    pub fn open(root: &Path) -> Option<Self> {
+        let repo = Repository::discover(root).ok()?;
+        let workdir = repo.workdir()?.to_path_buf();
+        let git_dir = repo.path().to_path_buf();
+        let current = snapshot(&repo);
+        Some(Self {
+            repo,
+            git_dir,
+            workdir,
+            current,
+            pending: None,
+        })
+    }
+
+    /// Directories to watch, with whether to watch them recursively: the git dir
+    /// itself for `HEAD`, `index.lock` and rebase state, and `refs/`.
+    pub fn watch_targets(&self) -> Vec<(PathBuf, bool)> {
+        vec![
+            (self.git_dir.clone(), false),
+            (self.git_dir.join("refs"), true),
+        ]
+    }
+
+    pub fn is_git_path(&self, path: &Path) -> bool {
+        path.starts_with(&self.git_dir)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Record a change inside the git dir; operation markers pause file processing.
+    pub fn observe_git(&mut self, path: &Path) {
+        let Ok(rel) = path.strip_prefix(&self.git_dir) else {
+            return;
+        };
+        let Some(first) = rel.components().next() else {
+            return;
+        };
+        let first = first.as_os_str().to_string_lossy();
+        if !OPERATION_MARKERS.contains(&first.as_ref()) {
+            return;
+        }
+
+        let now = Instant::now();
+        let before = &self.current;
+        let pending = self.pending.get_or_insert_with(|| {
+            tracing::debug!("Git operation started ({}), holding file events", first);
+            Pending {
+                before: before.clone(),
+                started: now,
+                last_git_event: now,
+                rebase_seen: false,
+                held: BTreeSet::new(),
+            }
+        });
+        pending.last_git_event = now;
+        pending.rebase_seen |= first.starts_with("rebase-");
+    }
+
+    /// Hold a worktree change while an operation is in progress.
+    /// Returns `false` if nothing is paused and the event should be handled now.
+    pub fn hold(&mut self, path: &Path) -> bool {
+        match self.pending.as_mut() {
+            Some(pending) => {
+                pending.held.insert(path.to_path_buf());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether the paused operation has finished; if so, resume and say how
+    /// to index what it changed.
+    pub fn poll(&mut self) -> Option<Flush> {
+        let pending = self.pending.as_ref()?;
+        let busy = self.git_dir.join("index.lock").exists() || self.rebase_in_progress();
+        if pending.last_git_event.elapsed() < SETTLE
+            || (busy && pending.started.elapsed() < MAX_PAUSE)
+        {
+            return None;
+        }
+
+        let pending = self.pending.take()?;
+        let after = snapshot(&self.repo);
+        self.current = after.clone();
+
+        if after == pending.before && !pending.rebase_seen {
+            return Some(Flush::Replay(pending.held.into_iter().collect()));
+        }
+
+        let mut paths = pending.held;
+        let old_tree = self.tree(pending.before.head);
+        let new_tree = self.tree(after.head);
+        paths.extend(self.diff_paths(old_tree.as_ref(), new_tree.as_ref()));
+        // Stash push and pop: the stash commit records the worktree changes
+        if after.stash != pending.before.stash {
+            for stash in [pending.before.stash, after.stash].into_iter().flatten() {
+                paths.extend(self.stash_paths(stash));
+            }
+        }
+
+        Some(Flush::Sync {
+            reason: describe(&pending.before, &after, pending.rebase_seen),
+            paths: paths.into_iter().collect(),
+        })
+    }
+
+    fn rebase_in_progress(&self) -> bool {
+        self.git_dir.join("rebase-merge").exists() || self.git_dir.join("rebase-apply").exists()
+    }
+
+    fn tree(&self, commit: Option<Oid>) -> Option<Tree<'_>> {
+        self.repo.find_commit(commit?).ok()?.tree().ok()
+    }
+
+    /// Worktree paths (old and new names) that differ between two trees.
+    fn diff_paths(&self, old: Option<&Tree<'_>>, new: Option<&Tree<'_>>) -> Vec<PathBuf> {
+        let Ok(diff) = self.repo.diff_tree_to_tree(old, new, None) else {
+            return Vec::new();
+        };
+        let mut paths = Vec::new();
+        for delta in diff.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path() {
+                    paths.push(self.workdir.join(path));
+                }
+            }
+        }
+        paths
+    }
+
+    fn stash_paths(&self, stash: Oid) -> Vec<PathBuf> {
+        let Ok(commit) = self.repo.find_commit(stash) else {
+            return Vec::new();
+        };
+        let base = commit.parent(0).ok().and_then(|c| c.tree().ok());
+        let stashed = commit.tree().ok();
+        self.diff_paths(base.as_ref(), stashed.as_ref())
+    }
+}
+
+fn snapshot(repo: &Repository) -> Snapshot {
+    let head = repo.head().ok();
+    Snapshot {
+        head: head.as_ref().and_then(|h| h.target()),
+        branch: head
+            .as_ref()
+            .filter(|h| h.is_branch())
+            .and_then(|h| h.shorthand().map(String::from)),
+        stash: repo.refname_to_id("refs/stash").ok(),
+    }
+}
+
+/// Stage label for `SyncProgress`.
+fn describe(before: &Snapshot, after: &Snapshot, rebase_seen: bool) -> String {
+    if rebase_seen {
+        "git rebase".to_string()
+    } else if before.branch != after.branch {
+        format!(
+            "git checkout {}",
+            after.branch.as_deref().unwrap_or("(detached HEAD)")
+        )
+    } else if before.head != after.head {
+        let short = after
+            .head
+            .map(|oid| oid.to_string()[..8].to_string())
+            .unwrap_or_default();
+        format!("git HEAD moved to {}", short)
+    } else {
+        "git stash".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_all(repo: &Repository, message: &str) -> Oid {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("test", "test@example.com").unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_checkout_becomes_one_sync_of_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+        std::fs::write(root.join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(root.join("b.rs"), "fn b() {}").unwrap();
+        let first = commit_all(&repo, "first");
+        repo.branch("feature", &repo.find_commit(first).unwrap(), false)
+            .unwrap();
+
+        let mut watch = GitWatch::open(&root).unwrap();
+        assert!(!watch.hold(&root.join("a.rs")));
+
+        // Switch to `feature` and change a.rs there
+        repo.set_head("refs/heads/feature").unwrap();
+        std::fs::write(root.join("a.rs"), "fn a2() {}").unwrap();
+        commit_all(&repo, "second");
+
+        let head = watch.git_dir.join("HEAD");
+        watch.observe_git(&head);
+        assert!(watch.hold(&root.join("a.rs")));
+        watch.pending.as_mut().unwrap().last_git_event -= SETTLE;
+
+        match watch.poll() {
+            Some(Flush::Sync { reason, paths }) => {
+                assert_eq!(reason, "git checkout feature");
+                assert_eq!(paths, vec![root.join("a.rs")]);
+            }
+            other => panic!("expected a sync, got {:?}", other),
+        }
+        assert!(!watch.is_paused());
+    }
+
+    #[test]
+    fn test_lock_without_ref_change_replays_held_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+        std::fs::write(root.join("a.rs"), "fn a() {}").unwrap();
+        commit_all(&repo, "first");
+
+        let mut watch = GitWatch::open(&root).unwrap();
+        let git_dir = watch.git_dir.clone();
+        watch.observe_git(&git_dir.join("index.lock"));
+        watch.observe_git(&git_dir.join("objects/ab/cdef"));
+        assert!(watch.hold(&root.join("a.rs")));
+        assert_eq!(watch.poll(), None);
+
+        watch.pending.as_mut().unwrap().last_git_event -= SETTLE;
+        assert_eq!(watch.poll(), Some(Flush::Replay(vec![root.join("a.rs")])));
+    }
+}
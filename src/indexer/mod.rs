@@ -1,13 +1,24 @@
+pub mod api_calls;
+pub mod api_contracts;
+pub mod architecture;
+pub mod budget;
+pub mod column_usages;
 pub mod context;
+pub mod db_schema;
 pub mod diagnostics;
 pub mod domains;
 pub mod embedder;
 pub mod git;
+pub mod git_watch;
 
 pub mod parser;
 pub mod pipeline;
+pub mod reconcile;
+pub mod routes;
+pub mod rules;
 pub mod service;
 pub mod summarizer;
+pub mod tokenize;
 pub mod watcher;
 
 pub use embedder::*;
@@ -7,9 +7,9 @@ use crate::models::{CodeChunk, SymbolKind};
 // === Семантический AST-чанкинг (Smart Chunking) ===
 
 /// Максимальный размер чанка в байтах (~2048 токенов)
-const MAX_CHUNK_BYTES: usize = 8192;
+pub(super) const MAX_CHUNK_BYTES: usize = 8192;
 /// Минимальный размер чанка (не создаём слишком мелкие)
-const MIN_CHUNK_BYTES: usize = 256;
+pub(super) const MIN_CHUNK_BYTES: usize = 256;
 
 /// Семантический чанкинг файла на основе tree-sitter AST.
 /// Уважает границы функций, классов и структур.
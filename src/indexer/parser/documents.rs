@@ -0,0 +1,1236 @@
+//! Symbols and chunks for non-code files: Markdown, TOML, YAML, JSON, SQL,
+//! GraphQL and Protocol Buffers.
+//!
+//! None of these go through tree-sitter. Each reader turns the file into
+//! sections with line ranges (a heading, a config key path, an SQL table);
+//! sections become symbols, and the outermost ones that fit in a chunk become
+//! chunks, so a search hit on a doc lands on the section and not the file.
+
+use std::path::Path;
+
+use super::chunking::{MAX_CHUNK_BYTES, MIN_CHUNK_BYTES};
+use super::core::{ParsedFile, SupportedLanguage};
+use super::idl::{self, IdlBlock};
+use super::sql::{self, SqlAlter, SqlDefinition};
+use crate::models::{CodeChunk, Symbol, SymbolKind};
+
+/// Symbols per file cap, so a large JSON fixture cannot flood the symbol table
+const MAX_SYMBOLS: usize = 2000;
+/// JSON nesting deeper than this is chunked by lines only
+const MAX_JSON_DEPTH: usize = 64;
+
+/// Generated files that match a document extension but are never worth indexing
+const SKIPPED_FILES: &[&str] = &["package-lock.json", "npm-shrinkwrap.json"];
+
+/// Non-code file types indexed alongside source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Markdown,
+    Toml,
+    Yaml,
+    Json,
+    Sql,
+    GraphQl,
+    Proto,
+}
+
+impl DocumentKind {
+    pub const ALL: [DocumentKind; 7] = [
+        DocumentKind::Markdown,
+        DocumentKind::Toml,
+        DocumentKind::Yaml,
+        DocumentKind::Json,
+        DocumentKind::Sql,
+        DocumentKind::GraphQl,
+        DocumentKind::Proto,
+    ];
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if SKIPPED_FILES.contains(&name) || name.ends_with(".min.json") {
+            return None;
+        }
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.extensions().contains(&ext))
+    }
+
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Markdown => &["md", "markdown", "mdx"],
+            Self::Toml => &["toml"],
+            Self::Yaml => &["yml", "yaml"],
+            Self::Json => &["json"],
+            Self::Sql => &["sql"],
+            Self::GraphQl => &["graphql", "graphqls", "gql"],
+            Self::Proto => &["proto"],
+        }
+    }
+
+    /// Value stored in `files.language`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+            Self::Sql => "sql",
+            Self::GraphQl => "graphql",
+            Self::Proto => "proto",
+        }
+    }
+}
+
+/// What an indexed file is: source code parsed with tree-sitter, or a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    Code(SupportedLanguage),
+    Document(DocumentKind),
+}
+
+impl SourceKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        SupportedLanguage::from_extension(ext)
+            .map(Self::Code)
+            .or_else(|| DocumentKind::from_path(path).map(Self::Document))
+    }
+
+    pub fn language(&self) -> Option<SupportedLanguage> {
+        match self {
+            Self::Code(language) => Some(*language),
+            Self::Document(_) => None,
+        }
+    }
+
+    /// Value stored in `files.language`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Code(SupportedLanguage::Rust) => "rust",
+            Self::Code(SupportedLanguage::TypeScript) => "typescript",
+            Self::Code(SupportedLanguage::JavaScript) => "javascript",
+            Self::Code(SupportedLanguage::Python) => "python",
+            Self::Code(SupportedLanguage::Go) => "go",
+            Self::Code(SupportedLanguage::Vue) => "vue",
+            Self::Document(kind) => kind.as_str(),
+        }
+    }
+}
+
+/// Symbols and chunks of a document. Never fails: unreadable structure just
+/// yields line-based chunks without symbols.
+pub fn parse_document(content: &str, file_path: &str, kind: DocumentKind) -> ParsedFile {
+    let lines: Vec<&str> = content.lines().collect();
+    let sections = match kind {
+        DocumentKind::Markdown => markdown_sections(&lines),
+        DocumentKind::Toml => toml_sections(&lines),
+        DocumentKind::Yaml => yaml_sections(&lines),
+        DocumentKind::Json => json_sections(content),
+        DocumentKind::Sql => sql_sections(content),
+        DocumentKind::GraphQl => graphql_sections(content),
+        DocumentKind::Proto => proto_sections(content),
+    };
+
+    let symbols = sections
+        .iter()
+        .take(MAX_SYMBOLS)
+        .map(|s| Symbol {
+            id: 0,
+            file_id: 0,
+            name: s.name.clone(),
+            kind: s.kind,
+            line_start: s.start as i32,
+            line_end: s.end as i32,
+            signature: lines
+                .get(s.start)
+                .map(|l| l.trim().chars().take(200).collect()),
+        })
+        .collect();
+
+    let chunks = Chunker::new(content, file_path, &sections).run();
+
+    ParsedFile {
+        symbols,
+        chunks,
+        refs: Vec::new(),
+        imports: Vec::new(),
+    }
+}
+
+/// A named line range of a document.
+#[derive(Debug, Clone)]
+struct Section {
+    /// Symbol name: heading text, full key path, table or `table.column`
+    name: String,
+    /// Breadcrumb for chunk context: `Guide > Setup` or `services.api.ports`
+    path: String,
+    kind: SymbolKind,
+    /// 0-based, inclusive
+    start: usize,
+    end: usize,
+    parent: Option<usize>,
+    /// Whether the section may become a chunk of its own; columns and similar
+    /// leaf symbols only live inside their parent's chunk
+    chunk: bool,
+}
+
+impl Section {
+    fn new(name: String, path: String, kind: SymbolKind, line: usize) -> Self {
+        Self {
+            name,
+            path,
+            kind,
+            start: line,
+            end: line,
+            parent: None,
+            chunk: true,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Markdown: ATX and setext headings, skipping code fences and front matter
+// ---------------------------------------------------------------------------
+
+fn markdown_sections(lines: &[&str]) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    // (level, section index) of headings still open
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut fence: Option<String> = None;
+    let mut front_matter = lines.first().is_some_and(|l| l.trim_end() == "---");
+    let mut skip_underline = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        if front_matter {
+            if i > 0 && matches!(line.trim_end(), "---" | "...") {
+                front_matter = false;
+            }
+            continue;
+        }
+        if std::mem::take(&mut skip_underline) {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str())
+                && trimmed
+                    .trim_start_matches(marker.as_str())
+                    .trim()
+                    .is_empty()
+            {
+                fence = None;
+            }
+            continue;
+        }
+        if line.len() - trimmed.len() < 4 {
+            if let Some(marker) = fence_marker(trimmed) {
+                fence = Some(marker);
+                continue;
+            }
+        }
+
+        let heading = atx_heading(line).or_else(|| {
+            let underline = lines.get(i + 1)?;
+            let level = setext_level(underline)?;
+            let text = line.trim();
+            let after_blank = i == 0 || lines[i - 1].trim().is_empty();
+            (!text.is_empty() && after_blank && line.len() - trimmed.len() < 4)
+                .then(|| (level, text.to_string()))
+        });
+        let Some((level, text)) = heading else {
+            continue;
+        };
+        skip_underline = atx_heading(line).is_none();
+
+        while let Some(&(open_level, idx)) = open.last() {
+            if open_level < level {
+                break;
+            }
+            sections[idx].end = i.saturating_sub(1).max(sections[idx].start);
+            open.pop();
+        }
+        let parent = open.last().map(|&(_, idx)| idx);
+        let path = match parent {
+            Some(p) => format!("{} > {}", sections[p].path, text),
+            None => text.clone(),
+        };
+        let mut section = Section::new(text, path, SymbolKind::Section, i);
+        section.parent = parent;
+        open.push((level, sections.len()));
+        sections.push(section);
+    }
+
+    let last = lines.len().saturating_sub(1);
+    for (_, idx) in open {
+        sections[idx].end = last;
+    }
+    sections
+}
+
+/// Opening code fence: three or more backticks or tildes.
+fn fence_marker(trimmed: &str) -> Option<String> {
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let run = trimmed.chars().take_while(|c| *c == ch).count();
+    (run >= 3).then(|| ch.to_string().repeat(run))
+}
+
+fn atx_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() >= 4 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    // Optional closing sequence: `## Title ##`
+    let mut text = rest.trim();
+    let without_hashes = text.trim_end_matches('#');
+    if without_hashes.is_empty() || without_hashes.ends_with([' ', '\t']) {
+        text = without_hashes.trim_end();
+    }
+    (!text.is_empty()).then(|| (level, text.to_string()))
+}
+
+fn setext_level(underline: &str) -> Option<usize> {
+    let t = underline.trim();
+    if t.len() >= 2 && t.chars().all(|c| c == '=') {
+        Some(1)
+    } else if t.len() >= 2 && t.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TOML: tables and key paths
+// ---------------------------------------------------------------------------
+
+fn toml_sections(lines: &[&str]) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut table: Option<usize> = None;
+    let mut prefix = String::new();
+    let mut last_content = 0usize;
+    let mut i = 0usize;
+
+    while i < lines.len() {
+        let t = lines[i].trim();
+        if t.is_empty() || t.starts_with('#') {
+            i += 1;
+            continue;
+        }
+
+        if t.starts_with('[') {
+            let array = t.starts_with("[[");
+            let open = if array { 2 } else { 1 };
+            let close = if array { "]]" } else { "]" };
+            if let Some(end) = t.find(close) {
+                if let Some(idx) = table {
+                    sections[idx].end = last_content;
+                }
+                let mut path = toml_key_path(&t[open..end]);
+                if array {
+                    path.push_str("[]");
+                }
+                table = Some(sections.len());
+                sections.push(Section::new(path.clone(), path.clone(), SymbolKind::Key, i));
+                prefix = path;
+                last_content = i;
+                i += 1;
+                continue;
+            }
+        }
+
+        if let Some((key, value)) = split_toml_key(t) {
+            let end = toml_value_end(lines, i, value);
+            let key = toml_key_path(key);
+            let path = if prefix.is_empty() {
+                key
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            let mut section = Section::new(path.clone(), path, SymbolKind::Key, i);
+            section.end = end;
+            section.parent = table;
+            section.chunk = false;
+            sections.push(section);
+            last_content = end;
+            i = end + 1;
+            continue;
+        }
+
+        last_content = i;
+        i += 1;
+    }
+
+    if let Some(idx) = table {
+        sections[idx].end = last_content;
+    }
+    sections
+}
+
+/// `a."b.c" . d` → `a.b.c.d`
+fn toml_key_path(raw: &str) -> String {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in raw.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '.') => parts.push(std::mem::take(&mut current).trim().to_string()),
+            (None, c) => current.push(c),
+        }
+    }
+    parts.push(current.trim().to_string());
+    parts.join(".")
+}
+
+/// Split `key = value` at the first `=` outside quotes.
+fn split_toml_key(line: &str) -> Option<(&str, &str)> {
+    let mut quote: Option<char> = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '=') => {
+                let key = line[..i].trim();
+                return (!key.is_empty()).then(|| (key, line[i + 1..].trim()));
+            }
+            (None, c) if c.is_whitespace() || c.is_alphanumeric() || "._-".contains(c) => {}
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Last line of a value that may continue as a multi-line string, array or
+/// inline table.
+fn toml_value_end(lines: &[&str], start: usize, value: &str) -> usize {
+    let mut state = ValueState::default();
+    state.scan(value);
+    let mut end = start;
+    while !state.done() && end + 1 < lines.len() {
+        end += 1;
+        state.scan(lines[end]);
+    }
+    end
+}
+
+#[derive(Default)]
+struct ValueState {
+    depth: i32,
+    triple: Option<&'static str>,
+}
+
+impl ValueState {
+    fn done(&self) -> bool {
+        self.triple.is_none() && self.depth <= 0
+    }
+
+    fn scan(&mut self, line: &str) {
+        let mut rest = line;
+        while !rest.is_empty() {
+            if let Some(q) = self.triple {
+                match rest.find(q) {
+                    Some(pos) => {
+                        rest = &rest[pos + q.len()..];
+                        self.triple = None;
+                    }
+                    None => return,
+                }
+                continue;
+            }
+            if let Some(q) = ["\"\"\"", "'''"].into_iter().find(|q| rest.starts_with(q)) {
+                self.triple = Some(q);
+                rest = &rest[3..];
+                continue;
+            }
+            let c = rest.chars().next().unwrap_or(' ');
+            match c {
+                '#' => return,
+                '"' | '\'' => {
+                    let body = &rest[1..];
+                    let mut escaped = false;
+                    let close = body.char_indices().find(|&(_, ch)| {
+                        let hit = ch == c && !escaped;
+                        escaped = c == '"' && ch == '\\' && !escaped;
+                        hit
+                    });
+                    match close {
+                        Some((pos, _)) => rest = &body[pos + 1..],
+                        None => return,
+                    }
+                    continue;
+                }
+                '[' | '{' => self.depth += 1,
+                ']' | '}' => self.depth -= 1,
+                _ => {}
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// YAML: indentation-based key paths; `[]` marks sequence items
+// ---------------------------------------------------------------------------
+
+enum YamlFrame {
+    Key {
+        indent: usize,
+        idx: usize,
+        path: String,
+    },
+    Seq {
+        indent: usize,
+        path: String,
+    },
+}
+
+impl YamlFrame {
+    fn indent(&self) -> usize {
+        match self {
+            Self::Key { indent, .. } | Self::Seq { indent, .. } => *indent,
+        }
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            Self::Key { path, .. } | Self::Seq { path, .. } => path,
+        }
+    }
+}
+
+fn yaml_sections(lines: &[&str]) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut stack: Vec<YamlFrame> = Vec::new();
+    let mut block_scalar: Option<usize> = None;
+    let mut last_content = 0usize;
+
+    fn close(
+        stack: &mut Vec<YamlFrame>,
+        sections: &mut [Section],
+        end: usize,
+        keep: impl Fn(&YamlFrame) -> bool,
+    ) {
+        while let Some(frame) = stack.last() {
+            if keep(frame) {
+                break;
+            }
+            if let YamlFrame::Key { idx, .. } = frame {
+                sections[*idx].end = end.max(sections[*idx].start);
+            }
+            stack.pop();
+        }
+    }
+
+    for (i, raw) in lines.iter().enumerate() {
+        let trimmed = raw.trim_start();
+        let indent = raw.len() - trimmed.len();
+
+        if let Some(owner) = block_scalar {
+            if trimmed.is_empty() || indent > owner {
+                if !trimmed.is_empty() {
+                    last_content = i;
+                }
+                continue;
+            }
+            block_scalar = None;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if indent == 0 && (trimmed.starts_with("---") || trimmed.starts_with("...")) {
+            close(&mut stack, &mut sections, last_content, |_| false);
+            continue;
+        }
+
+        // Sequence items, possibly nested on one line: `- - a`
+        let mut col = indent;
+        let mut rest = trimmed;
+        while rest == "-" || rest.starts_with("- ") {
+            close(&mut stack, &mut sections, last_content, |f| {
+                f.indent() < col || (f.indent() == col && matches!(f, YamlFrame::Key { .. }))
+            });
+            let path = format!("{}[]", stack.last().map(|f| f.path()).unwrap_or(""));
+            stack.push(YamlFrame::Seq { indent: col, path });
+            let after = &rest[1..];
+            let item = after.trim_start();
+            col += 1 + after.len() - item.len();
+            rest = item;
+        }
+
+        if let Some((key, value)) = yaml_key(rest) {
+            close(&mut stack, &mut sections, last_content, |f| {
+                f.indent() < col
+            });
+            let parent = stack.iter().rev().find_map(|f| match f {
+                YamlFrame::Key { idx, .. } => Some(*idx),
+                YamlFrame::Seq { .. } => None,
+            });
+            let path = match stack.last() {
+                Some(frame) => format!("{}.{}", frame.path(), key),
+                None => key.to_string(),
+            };
+            let mut section = Section::new(path.clone(), path.clone(), SymbolKind::Key, i);
+            section.parent = parent;
+            stack.push(YamlFrame::Key {
+                indent: col,
+                idx: sections.len(),
+                path,
+            });
+            sections.push(section);
+
+            let value = value.trim();
+            if value.starts_with('|') || value.starts_with('>') {
+                block_scalar = Some(col);
+            }
+        }
+        last_content = i;
+    }
+
+    close(&mut stack, &mut sections, last_content, |_| false);
+    sections
+}
+
+/// `key: value` with a plain or quoted key; `None` for scalars and flow items.
+fn yaml_key(text: &str) -> Option<(&str, &str)> {
+    let first = text.chars().next()?;
+    if first == '"' || first == '\'' {
+        let close = text[1..].find(first)? + 1;
+        let after = text[close + 1..].trim_start();
+        let value = after.strip_prefix(':')?;
+        return (value.is_empty() || value.starts_with([' ', '\t']))
+            .then(|| (&text[1..close], value));
+    }
+    if "{[&*!|>%@`?#".contains(first) {
+        return None;
+    }
+    let bytes = text.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'#' if i > 0 && bytes[i - 1] == b' ' => return None,
+            b':' if i + 1 == bytes.len() || bytes[i + 1] == b' ' || bytes[i + 1] == b'\t' => {
+                let key = text[..i].trim_end();
+                return (!key.is_empty()).then(|| (key, &text[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// JSON (and JSONC): object key paths with line ranges
+// ---------------------------------------------------------------------------
+
+struct JsonScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    sections: Vec<Section>,
+}
+
+fn json_sections(content: &str) -> Vec<Section> {
+    let mut scanner = JsonScanner {
+        bytes: content.as_bytes(),
+        pos: 0,
+        line: 0,
+        sections: Vec::new(),
+    };
+    scanner.skip_ws();
+    match scanner.value("", None, 0) {
+        Some(()) => scanner.sections,
+        None => Vec::new(),
+    }
+}
+
+impl JsonScanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// Whitespace plus `//` and `/* */` comments (tsconfig, VS Code settings)
+    fn skip_ws(&mut self) {
+        while let Some(b) = self.peek() {
+            match b {
+                b'\n' => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                b' ' | b'\t' | b'\r' => self.pos += 1,
+                b'/' if self.bytes.get(self.pos + 1) == Some(&b'/') => {
+                    while self.peek().is_some_and(|b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b'/' if self.bytes.get(self.pos + 1) == Some(&b'*') => {
+                    self.pos += 2;
+                    while self.pos < self.bytes.len()
+                        && !(self.bytes[self.pos] == b'*'
+                            && self.bytes.get(self.pos + 1) == Some(&b'/'))
+                    {
+                        if self.bytes[self.pos] == b'\n' {
+                            self.line += 1;
+                        }
+                        self.pos += 1;
+                    }
+                    self.pos += 2;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn value(&mut self, path: &str, parent: Option<usize>, depth: usize) -> Option<()> {
+        if depth > MAX_JSON_DEPTH {
+            return None;
+        }
+        match self.peek()? {
+            b'{' => self.object(path, parent, depth),
+            b'[' => self.array(path, parent, depth),
+            b'"' => self.string().map(|_| ()),
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|b| {
+                    !matches!(b, b',' | b']' | b'}' | b'\n' | b' ' | b'\t' | b'\r')
+                }) {
+                    self.pos += 1;
+                }
+                (self.pos > start).then_some(())
+            }
+        }
+    }
+
+    fn object(&mut self, path: &str, parent: Option<usize>, depth: usize) -> Option<()> {
+        self.pos += 1;
+        loop {
+            self.skip_ws();
+            match self.peek()? {
+                b'}' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                b',' => {
+                    self.pos += 1;
+                    continue;
+                }
+                b'"' => {}
+                _ => return None,
+            }
+            let line = self.line;
+            let key = self.string()?;
+            self.skip_ws();
+            if self.peek()? != b':' {
+                return None;
+            }
+            self.pos += 1;
+            self.skip_ws();
+
+            let child = if path.is_empty() {
+                key
+            } else {
+                format!("{}.{}", path, key)
+            };
+            let idx = self.sections.len();
+            let mut section = Section::new(child.clone(), child.clone(), SymbolKind::Key, line);
+            section.parent = parent;
+            self.sections.push(section);
+            self.value(&child, Some(idx), depth + 1)?;
+            self.sections[idx].end = self.line;
+        }
+    }
+
+    fn array(&mut self, path: &str, parent: Option<usize>, depth: usize) -> Option<()> {
+        self.pos += 1;
+        let item = format!("{}[]", path);
+        loop {
+            self.skip_ws();
+            match self.peek()? {
+                b']' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                b',' => self.pos += 1,
+                _ => self.value(&item, parent, depth + 1)?,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos + 1;
+        let mut i = start;
+        while i < self.bytes.len() {
+            match self.bytes[i] {
+                b'\\' => i += 2,
+                b'"' => {
+                    self.pos = i + 1;
+                    let raw = String::from_utf8_lossy(&self.bytes[start..i]);
+                    return Some(raw.replace("\\\"", "\"").replace("\\\\", "\\"));
+                }
+                b'\n' => return None,
+                _ => i += 1,
+            }
+        }
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SQL: statements; tables and views with their columns, routines
+// ---------------------------------------------------------------------------
+
+fn sql_sections(content: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    for statement in sql::split_statements(content) {
+        let (name, kind, columns) = match sql::classify(&statement) {
+            SqlDefinition::Table { name, columns, .. } => (name, SymbolKind::Table, columns),
+            SqlDefinition::View { name } => (name, SymbolKind::Table, Vec::new()),
+            SqlDefinition::Routine { name } => (name, SymbolKind::Function, Vec::new()),
+            SqlDefinition::AlterTable { table, actions } => {
+                for action in actions {
+                    let SqlAlter::AddColumn(column) = action else {
+                        continue;
+                    };
+                    let name = format!("{}.{}", table, column.name);
+                    let mut section =
+                        Section::new(name.clone(), name, SymbolKind::Column, column.line);
+                    section.end = statement.end_line;
+                    section.chunk = false;
+                    sections.push(section);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let idx = sections.len();
+        let mut section = Section::new(name.clone(), name.clone(), kind, statement.start_line);
+        section.end = statement.end_line;
+        sections.push(section);
+        for column in columns {
+            let full = format!("{}.{}", name, column.name);
+            let mut section = Section::new(full.clone(), full, SymbolKind::Column, column.line);
+            section.parent = Some(idx);
+            section.chunk = false;
+            sections.push(section);
+        }
+    }
+    sections
+}
+
+// ---------------------------------------------------------------------------
+// GraphQL and Protocol Buffers: types, messages and services; root operation
+// fields and rpcs as `Type.member`
+// ---------------------------------------------------------------------------
+
+fn graphql_sections(content: &str) -> Vec<Section> {
+    let blocks = idl::graphql_schema(content);
+    let roots: Vec<String> = idl::graphql_root_types(&blocks)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    idl_sections(&blocks, |block| {
+        let kind = match block.keyword.as_str() {
+            "interface" => SymbolKind::Interface,
+            "enum" => SymbolKind::Enum,
+            "directive" => return None,
+            _ => SymbolKind::Type,
+        };
+        Some((kind, roots.contains(&block.name)))
+    })
+}
+
+fn proto_sections(content: &str) -> Vec<Section> {
+    let file = idl::proto_definitions(content);
+    idl_sections(&file.blocks, |block| match block.keyword.as_str() {
+        "message" => Some((SymbolKind::Struct, false)),
+        "enum" => Some((SymbolKind::Enum, false)),
+        "service" => Some((SymbolKind::Interface, true)),
+        _ => None,
+    })
+}
+
+/// Sections of IDL blocks; `classify` gives a block's symbol kind and whether
+/// its members (operation fields, rpcs) become symbols too.
+fn idl_sections(
+    blocks: &[IdlBlock],
+    classify: impl Fn(&IdlBlock) -> Option<(SymbolKind, bool)>,
+) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    for block in blocks {
+        let Some((kind, with_members)) = classify(block) else {
+            continue;
+        };
+        let idx = sections.len();
+        let mut section = Section::new(block.name.clone(), block.name.clone(), kind, block.start);
+        section.end = block.end;
+        // Nested proto messages come after their parent
+        section.parent = (0..idx).rev().find(|&p| {
+            sections[p].chunk && sections[p].start < block.start && sections[p].end >= block.end
+        });
+        sections.push(section);
+        if !with_members {
+            continue;
+        }
+        for member in &block.members {
+            let name = format!("{}.{}", block.name, member.name);
+            let mut section = Section::new(name.clone(), name, SymbolKind::Method, member.line);
+            section.parent = Some(idx);
+            section.chunk = false;
+            sections.push(section);
+        }
+    }
+    sections
+}
+
+// ---------------------------------------------------------------------------
+// Chunking: outermost sections that fit, recursing into oversized ones
+// ---------------------------------------------------------------------------
+
+struct Chunker<'a> {
+    content: &'a str,
+    file_path: &'a str,
+    sections: &'a [Section],
+    /// Byte offset of each line start, plus `content.len()`
+    offsets: Vec<usize>,
+    chunks: Vec<CodeChunk>,
+    /// Lines accumulated for the next chunk: (start, end, first section, context)
+    pending: Option<(usize, usize, Option<usize>, Option<usize>)>,
+}
+
+impl<'a> Chunker<'a> {
+    fn new(content: &'a str, file_path: &'a str, sections: &'a [Section]) -> Self {
+        let mut offsets = vec![0];
+        offsets.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        if offsets.last() != Some(&content.len()) {
+            offsets.push(content.len());
+        }
+        Self {
+            content,
+            file_path,
+            sections,
+            offsets,
+            chunks: Vec::new(),
+            pending: None,
+        }
+    }
+
+    fn line_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn bytes(&self, start: usize, end: usize) -> usize {
+        self.offsets[end + 1] - self.offsets[start]
+    }
+
+    fn run(mut self) -> Vec<CodeChunk> {
+        if self.line_count() > 0 {
+            self.range(0, self.line_count() - 1, None);
+            self.flush();
+        }
+        self.chunks
+    }
+
+    /// Lines `start..=end` whose chunkable sections are children of `parent`.
+    fn range(&mut self, start: usize, end: usize, parent: Option<usize>) {
+        let children: Vec<usize> = (0..self.sections.len())
+            .filter(|&i| {
+                let s = &self.sections[i];
+                s.chunk && s.parent == parent && s.start >= start && s.end <= end
+            })
+            .collect();
+
+        let mut cursor = start;
+        for child in children {
+            let section = &self.sections[child];
+            if section.start < cursor {
+                continue;
+            }
+            if section.start > cursor {
+                let first = parent.filter(|&p| self.sections[p].start == cursor);
+                self.unit(cursor, section.start - 1, first, parent);
+            }
+            let (s, e) = (section.start, section.end);
+            if self.bytes(s, e) <= MAX_CHUNK_BYTES {
+                self.unit(s, e, Some(child), parent);
+            } else {
+                self.flush();
+                self.range(s, e, Some(child));
+                self.flush();
+            }
+            cursor = e + 1;
+        }
+        if cursor <= end {
+            let first = parent.filter(|&p| self.sections[p].start == cursor);
+            self.unit(cursor, end, first, parent);
+        }
+    }
+
+    /// Add lines to the pending chunk, merging small neighbours like the code
+    /// chunker does.
+    fn unit(&mut self, start: usize, end: usize, first: Option<usize>, context: Option<usize>) {
+        let size = self.bytes(start, end);
+        if let Some((p_start, p_end, _, p_context)) = self.pending {
+            let pending_size = self.bytes(p_start, p_end);
+            if p_context != context
+                || (pending_size + size > MAX_CHUNK_BYTES && pending_size >= MIN_CHUNK_BYTES)
+            {
+                self.flush();
+            }
+        }
+        if size > MAX_CHUNK_BYTES {
+            self.flush();
+            self.split(start, end, first, context);
+            return;
+        }
+        match self.pending.as_mut() {
+            Some(pending) => {
+                pending.1 = end;
+                pending.2 = pending.2.or(first);
+            }
+            None => self.pending = Some((start, end, first, context)),
+        }
+    }
+
+    /// Oversized leaf: cut by lines, repeating the breadcrumb on every piece.
+    fn split(&mut self, start: usize, end: usize, first: Option<usize>, context: Option<usize>) {
+        let label = first.or(context);
+        let prefix_len = label.map(|i| self.sections[i].path.len() + 14).unwrap_or(0);
+        let limit = MAX_CHUNK_BYTES.saturating_sub(prefix_len).max(1);
+        let mut piece_start = start;
+        for line in start..=end {
+            if line > piece_start && self.bytes(piece_start, line) > limit {
+                self.emit(piece_start, line - 1, first, label);
+                piece_start = line;
+            }
+        }
+        self.emit(piece_start, end, first, label);
+    }
+
+    fn flush(&mut self) {
+        if let Some((start, end, first, context)) = self.pending.take() {
+            self.emit(start, end, first, context);
+        }
+    }
+
+    fn emit(&mut self, start: usize, end: usize, first: Option<usize>, context: Option<usize>) {
+        let text = &self.content[self.offsets[start]..self.offsets[end + 1]];
+        if text.trim().is_empty() {
+            return;
+        }
+        let content = match context {
+            Some(c) => format!("// Context: {}\n{}", self.sections[c].path, text),
+            None => text.to_string(),
+        };
+        let mut scopes = Vec::new();
+        let mut ancestor = context;
+        while let Some(i) = ancestor {
+            scopes.insert(0, self.sections[i].path.clone());
+            ancestor = self.sections[i].parent;
+        }
+        let symbol = first.map(|i| &self.sections[i]);
+        let line_end = end as u32 + 1;
+        self.chunks.push(CodeChunk {
+            id: format!("{}:{}:{}", self.file_path, start, line_end),
+            file_path: self.file_path.to_string(),
+            content,
+            line_start: start as u32,
+            line_end,
+            symbol_name: symbol.map(|s| s.name.clone()),
+            symbol_kind: symbol.map(|s| s.kind),
+            symbol_path: symbol.map(|s| s.path.clone()),
+            scopes,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(parsed: &ParsedFile) -> Vec<(&str, i32, i32)> {
+        parsed
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.line_start, s.line_end))
+            .collect()
+    }
+
+    #[test]
+    fn test_markdown_headings_nest_and_skip_code_fences() {
+        let md = "---\ntitle: x\n---\n# Guide\nintro\n\n## Setup\n```sh\n# not a heading\n```\n\nOther\n-----\ntext\n# Appendix\n";
+        let parsed = parse_document(md, "docs/guide.md", DocumentKind::Markdown);
+        assert_eq!(
+            names(&parsed),
+            vec![
+                ("Guide", 3, 13),
+                ("Setup", 6, 10),
+                ("Other", 11, 13),
+                ("Appendix", 14, 14)
+            ]
+        );
+        // Small file: one chunk covering everything
+        assert_eq!(parsed.chunks.len(), 1);
+        assert_eq!(parsed.symbols[0].kind, SymbolKind::Section);
+    }
+
+    #[test]
+    fn test_toml_tables_and_multiline_values() {
+        let toml = "name = \"x\"\n\n[indexer]\nignore = [\n  \"a\",\n]\nparallel_workers = 4\n\n[[bin]]\n\"quoted.key\" = '''\nmulti\n'''\n";
+        let parsed = parse_document(toml, "config.toml", DocumentKind::Toml);
+        assert_eq!(
+            names(&parsed),
+            vec![
+                ("name", 0, 0),
+                ("indexer", 2, 6),
+                ("indexer.ignore", 3, 5),
+                ("indexer.parallel_workers", 6, 6),
+                ("bin[]", 8, 11),
+                ("bin[].quoted.key", 9, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_key_paths_through_sequences_and_block_scalars() {
+        let yaml = "on: push\njobs:\n  build:\n    steps:\n      - name: Test\n        run: |\n          cargo test\n          a: not a key\n      - uses: actions/checkout@v4\n";
+        let parsed = parse_document(yaml, ".github/workflows/ci.yml", DocumentKind::Yaml);
+        assert_eq!(
+            names(&parsed),
+            vec![
+                ("on", 0, 0),
+                ("jobs", 1, 8),
+                ("jobs.build", 2, 8),
+                ("jobs.build.steps", 3, 8),
+                ("jobs.build.steps[].name", 4, 4),
+                ("jobs.build.steps[].run", 5, 7),
+                ("jobs.build.steps[].uses", 8, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_key_paths_with_comments() {
+        let json = "{\n  // compiler options\n  \"compilerOptions\": {\n    \"strict\": true,\n    \"paths\": {\"@/*\": [\"src/*\"]}\n  },\n  \"include\": [{\"x\": 1}]\n}\n";
+        let parsed = parse_document(json, "tsconfig.json", DocumentKind::Json);
+        assert_eq!(
+            names(&parsed),
+            vec![
+                ("compilerOptions", 2, 5),
+                ("compilerOptions.strict", 3, 3),
+                ("compilerOptions.paths", 4, 4),
+                ("compilerOptions.paths.@/*", 4, 4),
+                ("include", 6, 6),
+                ("include[].x", 6, 6),
+            ]
+        );
+        assert!(parse_document("{ broken", "x.json", DocumentKind::Json)
+            .symbols
+            .is_empty());
+    }
+
+    #[test]
+    fn test_sql_tables_and_columns_become_symbols() {
+        let sql = "CREATE TABLE users (\n  id INTEGER PRIMARY KEY,\n  email TEXT\n);\n\nALTER TABLE users ADD COLUMN name TEXT;\nCREATE INDEX idx ON users(email);\n";
+        let parsed = parse_document(sql, "migrations/001.sql", DocumentKind::Sql);
+        let kinds: Vec<_> = parsed
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("users", SymbolKind::Table),
+                ("users.id", SymbolKind::Column),
+                ("users.email", SymbolKind::Column),
+                ("users.name", SymbolKind::Column),
+            ]
+        );
+        assert_eq!(
+            parsed.symbols[1].signature.as_deref(),
+            Some("id INTEGER PRIMARY KEY,")
+        );
+    }
+
+    #[test]
+    fn test_graphql_and_proto_definitions_become_symbols() {
+        let sdl = "type Query {\n  user(id: ID!): User\n}\n\ntype User {\n  id: ID!\n}\n";
+        let parsed = parse_document(sdl, "schema.graphql", DocumentKind::GraphQl);
+        assert_eq!(
+            names(&parsed),
+            vec![("Query", 0, 2), ("Query.user", 1, 1), ("User", 4, 6)]
+        );
+
+        let proto = "service Users {\n  rpc Get(GetRequest) returns (User);\n}\nmessage User {\n  message Address {}\n}\n";
+        let parsed = parse_document(proto, "users.proto", DocumentKind::Proto);
+        let kinds: Vec<_> = parsed
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("Users", SymbolKind::Interface),
+                ("Users.Get", SymbolKind::Method),
+                ("User", SymbolKind::Struct),
+                ("User.Address", SymbolKind::Struct),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_oversized_sections_split_into_children_with_context() {
+        let mut yaml = String::from("services:\n");
+        for svc in ["api", "web"] {
+            yaml.push_str(&format!("  {}:\n    env:\n", svc));
+            for i in 0..300 {
+                yaml.push_str(&format!("      VAR_{}: value\n", i));
+            }
+        }
+        let parsed = parse_document(&yaml, "compose.yml", DocumentKind::Yaml);
+        assert!(parsed.chunks.len() >= 2);
+        assert!(parsed
+            .chunks
+            .iter()
+            .all(|c| c.content.len() <= MAX_CHUNK_BYTES + 64));
+        let web = parsed
+            .chunks
+            .iter()
+            .find(|c| c.content.contains("web:"))
+            .unwrap();
+        assert!(web.content.starts_with("// Context: services"));
+        assert_eq!(web.scopes, vec!["services".to_string()]);
+    }
+
+    #[test]
+    fn test_document_kind_from_path() {
+        assert_eq!(
+            DocumentKind::from_path(Path::new("docs/adr/0001.md")),
+            Some(DocumentKind::Markdown)
+        );
+        assert_eq!(
+            DocumentKind::from_path(Path::new("ci.yaml")),
+            Some(DocumentKind::Yaml)
+        );
+        assert_eq!(
+            DocumentKind::from_path(Path::new("package-lock.json")),
+            None
+        );
+        assert_eq!(DocumentKind::from_path(Path::new("main.rs")), None);
+    }
+}
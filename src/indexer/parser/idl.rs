@@ -0,0 +1,801 @@
+//! Definition-level reading of GraphQL documents and Protocol Buffers files.
+//!
+//! Not a validating parser: comments (and GraphQL descriptions) are dropped,
+//! the rest is split into identifier, string and punctuation tokens, and the
+//! named blocks — types, messages, services — are picked out by brace depth
+//! together with their members. Both formats are regular enough at that level
+//! that this holds up on real schemas.
+
+/// A named `{ }` block: a GraphQL type or a proto message, enum or service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlBlock {
+    /// `type`, `input`, `interface`, `enum`, `schema`, `message`, `service`, ...
+    pub keyword: String,
+    /// Proto nested messages are dotted: `Outer.Inner`
+    pub name: String,
+    /// Whether the block is a GraphQL `extend type ...`
+    pub extension: bool,
+    /// 0-based lines of the keyword and of the closing brace
+    pub start: usize,
+    pub end: usize,
+    pub members: Vec<IdlMember>,
+}
+
+/// A field, enum value or rpc of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlMember {
+    pub name: String,
+    /// GraphQL field type without list and non-null markers; proto field type
+    /// or rpc response message
+    pub type_name: String,
+    /// Proto rpc request message
+    pub input: Option<String>,
+    /// `google.api.http` bindings of a proto rpc: (`GET`, `/v1/users/{id}`)
+    pub http: Vec<(String, String)>,
+    /// 0-based
+    pub line: usize,
+}
+
+/// A `.proto` file: its package and top-level and nested blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtoFile {
+    pub package: Option<String>,
+    pub blocks: Vec<IdlBlock>,
+}
+
+/// An executable GraphQL operation (`query GetUser { user { id } }`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphqlOperation {
+    /// `query`, `mutation` or `subscription`
+    pub kind: String,
+    pub name: Option<String>,
+    /// Root fields selected, with their 0-based line
+    pub fields: Vec<(String, usize)>,
+}
+
+const GRAPHQL_DEFINITIONS: &[&str] = &[
+    "type",
+    "input",
+    "interface",
+    "enum",
+    "union",
+    "scalar",
+    "schema",
+    "directive",
+];
+const PROTO_BLOCKS: &[&str] = &["message", "enum", "service", "oneof", "extend"];
+/// Proto statements that are neither fields nor blocks
+const PROTO_SKIPPED: &[&str] = &[
+    "option",
+    "reserved",
+    "extensions",
+    "import",
+    "syntax",
+    "edition",
+    "package",
+];
+const HTTP_METHODS: &[&str] = &["get", "put", "post", "delete", "patch"];
+
+// ---------------------------------------------------------------------------
+// GraphQL
+// ---------------------------------------------------------------------------
+
+/// Type system definitions of a GraphQL document; operations are skipped.
+pub fn graphql_schema(content: &str) -> Vec<IdlBlock> {
+    let text = blank_comments(content, Syntax::Graphql);
+    let lines = LineIndex::new(&text);
+    let tokens = tokenize(&text, Syntax::Graphql);
+    let mut blocks = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let start = i;
+        let extension = tokens[i].text == "extend";
+        if extension {
+            i += 1;
+        }
+        let Some(token) = tokens.get(i) else {
+            break;
+        };
+        if !GRAPHQL_DEFINITIONS.contains(&token.text) {
+            // An operation, fragment or stray token: skip it whole
+            i = match token.text {
+                "{" => matching(&tokens, i) + 1,
+                _ => skip_definition(&tokens, i + 1),
+            };
+            continue;
+        }
+        let keyword = token.text;
+        let name = match keyword {
+            "schema" => "schema".to_string(),
+            "directive" => tokens
+                .get(i + 2)
+                .map(|t| format!("@{}", t.text))
+                .unwrap_or_default(),
+            _ => tokens
+                .get(i + 1)
+                .map(|t| t.text.to_string())
+                .unwrap_or_default(),
+        };
+
+        // Header runs to the body's `{` or to the next definition
+        let mut j = i + 1;
+        let mut depth = 0i32;
+        let mut body = None;
+        while j < tokens.len() {
+            match tokens[j].text {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                "{" if depth == 0 => {
+                    body = Some(j);
+                    break;
+                }
+                t if depth == 0 && j > i + 1 && starts_definition(t) => break,
+                _ => {}
+            }
+            j += 1;
+        }
+
+        let mut block = IdlBlock {
+            keyword: keyword.to_string(),
+            name,
+            extension,
+            start: lines.line(tokens[start].offset),
+            end: lines.line(tokens[j.saturating_sub(1).max(start)].offset),
+            members: Vec::new(),
+        };
+        i = j;
+        if let Some(open) = body {
+            let close = matching(&tokens, open);
+            let body = tokens.get(open + 1..close).unwrap_or_default();
+            block.members = graphql_members(body, keyword, &lines);
+            block.end = lines.line(tokens.get(close).map(|t| t.offset).unwrap_or(text.len()));
+            i = close + 1;
+        }
+        if is_name(block.name.trim_start_matches('@')) {
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// Root operation types as (`query`, type name): from a `schema { }` block if
+/// the document has one, else the conventional `Query`, `Mutation` and
+/// `Subscription`.
+pub fn graphql_root_types(blocks: &[IdlBlock]) -> Vec<(String, String)> {
+    let declared: Vec<(String, String)> = blocks
+        .iter()
+        .filter(|b| b.keyword == "schema")
+        .flat_map(|b| &b.members)
+        .filter(|m| !m.type_name.is_empty())
+        .map(|m| (m.name.clone(), m.type_name.clone()))
+        .collect();
+    if !declared.is_empty() {
+        return declared;
+    }
+    ["query", "mutation", "subscription"]
+        .iter()
+        .map(|op| {
+            let mut name = op.to_string();
+            name[..1].make_ascii_uppercase();
+            (op.to_string(), name)
+        })
+        .collect()
+}
+
+/// Executable operations of a GraphQL document (a `.graphql` file of client
+/// queries, or the body of a `gql` template).
+pub fn graphql_operations(content: &str) -> Vec<GraphqlOperation> {
+    let text = blank_comments(content, Syntax::Graphql);
+    let lines = LineIndex::new(&text);
+    let tokens = tokenize(&text, Syntax::Graphql);
+    let mut operations = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let (kind, name) = match tokens[i].text {
+            "{" => ("query", None),
+            t @ ("query" | "mutation" | "subscription") => (
+                t,
+                tokens
+                    .get(i + 1)
+                    .filter(|t| is_name(t.text))
+                    .map(|t| t.text.to_string()),
+            ),
+            _ => {
+                i = skip_definition(&tokens, i + 1);
+                continue;
+            }
+        };
+        let Some(open) = (i..tokens.len()).find(|&j| tokens[j].text == "{") else {
+            break;
+        };
+        let close = matching(&tokens, open);
+        operations.push(GraphqlOperation {
+            kind: kind.to_string(),
+            name,
+            fields: root_fields(tokens.get(open + 1..close).unwrap_or_default(), &lines),
+        });
+        i = close + 1;
+    }
+    operations
+}
+
+/// Fields (or enum values) at the top level of a type body.
+fn graphql_members(body: &[Token<'_>], keyword: &str, lines: &LineIndex) -> Vec<IdlMember> {
+    let mut members = Vec::new();
+    let mut depth = 0i32;
+    for (k, token) in body.iter().enumerate() {
+        match token.text {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth -= 1,
+            t if depth == 0 && is_name(t) => {
+                let prev = k.checked_sub(1).map(|p| body[p].text);
+                if matches!(prev, Some("@" | ":" | "=" | "|")) {
+                    continue;
+                }
+                let next = body.get(k + 1).map(|t| t.text);
+                let is_field = matches!(next, Some("(" | ":"));
+                if !is_field && keyword != "enum" {
+                    continue;
+                }
+                let type_name = if is_field {
+                    field_type(&body[k + 1..])
+                } else {
+                    String::new()
+                };
+                members.push(IdlMember {
+                    name: t.to_string(),
+                    type_name,
+                    input: None,
+                    http: Vec::new(),
+                    line: lines.line(token.offset),
+                });
+            }
+            _ => {}
+        }
+    }
+    members
+}
+
+/// Named type of a field from the tokens after its name: `(args): [User!]!` → `User`.
+fn field_type(tokens: &[Token<'_>]) -> String {
+    let mut k = 0;
+    if tokens.first().map(|t| t.text) == Some("(") {
+        k = matching(tokens, 0) + 1;
+    }
+    if tokens.get(k).map(|t| t.text) != Some(":") {
+        return String::new();
+    }
+    tokens[k + 1..]
+        .iter()
+        .map(|t| t.text)
+        .find(|t| *t != "[")
+        .filter(|t| is_name(t))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Root fields of a selection set, through aliases; fragment spreads and
+/// `__typename` are skipped.
+fn root_fields(selection: &[Token<'_>], lines: &LineIndex) -> Vec<(String, usize)> {
+    let mut fields = Vec::new();
+    let mut depth = 0i32;
+    for (k, token) in selection.iter().enumerate() {
+        match token.text {
+            "(" | "{" => depth += 1,
+            ")" | "}" => depth -= 1,
+            t if depth == 0 && is_name(t) => {
+                let prev = k.checked_sub(1).map(|p| selection[p].text);
+                let next = selection.get(k + 1).map(|t| t.text);
+                if matches!(prev, Some("@" | "." | "on")) || (t == "on" && prev == Some(".")) {
+                    continue;
+                }
+                if next == Some(":") || t.starts_with("__") {
+                    continue;
+                }
+                fields.push((t.to_string(), lines.line(token.offset)));
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// Whether a top-level token opens a new definition.
+fn starts_definition(token: &str) -> bool {
+    token == "extend"
+        || GRAPHQL_DEFINITIONS.contains(&token)
+        || matches!(token, "query" | "mutation" | "subscription" | "fragment")
+}
+
+/// Index past the definition starting before `i`: through its first balanced
+/// `{ }` block, or up to the next definition keyword outside parentheses.
+fn skip_definition(tokens: &[Token<'_>], mut i: usize) -> usize {
+    let mut depth = 0i32;
+    while i < tokens.len() {
+        match tokens[i].text {
+            "{" => return matching(tokens, i) + 1,
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            t if depth <= 0 && starts_definition(t) => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+// ---------------------------------------------------------------------------
+// Protocol Buffers
+// ---------------------------------------------------------------------------
+
+/// Package and blocks of a `.proto` file; nested blocks come after their parent.
+pub fn proto_definitions(content: &str) -> ProtoFile {
+    let text = blank_comments(content, Syntax::Proto);
+    let lines = LineIndex::new(&text);
+    let tokens = tokenize(&text, Syntax::Proto);
+    let mut file = ProtoFile::default();
+    proto_body(&tokens, 0, tokens.len(), "", &lines, &mut file);
+    file
+}
+
+/// Statements of `tokens[from..to]`; returns the members found at this level
+/// and pushes every block to `file`.
+fn proto_body(
+    tokens: &[Token<'_>],
+    from: usize,
+    to: usize,
+    scope: &str,
+    lines: &LineIndex,
+    file: &mut ProtoFile,
+) -> Vec<IdlMember> {
+    let mut members = Vec::new();
+    let mut i = from;
+    while i < to {
+        let word = tokens[i].text;
+        if word == "package" {
+            file.package = tokens.get(i + 1).map(|t| t.text.to_string());
+        }
+        if PROTO_BLOCKS.contains(&word) && tokens.get(i + 2).map(|t| t.text) == Some("{") {
+            let name = tokens[i + 1].text;
+            let open = i + 2;
+            let close = matching(tokens, open).min(to);
+            let full = if scope.is_empty() || word == "oneof" {
+                name.to_string()
+            } else {
+                format!("{}.{}", scope, name)
+            };
+            let index = file.blocks.len();
+            file.blocks.push(IdlBlock {
+                keyword: word.to_string(),
+                name: full.clone(),
+                extension: word == "extend",
+                start: lines.line(tokens[i].offset),
+                end: lines.line(tokens.get(close).map_or(0, |t| t.offset)),
+                members: Vec::new(),
+            });
+            let inner_scope = if word == "oneof" { scope } else { &full };
+            let inner = proto_body(tokens, open + 1, close, inner_scope, lines, file);
+            if word == "oneof" {
+                // oneof fields belong to the enclosing message
+                members.extend(inner.iter().cloned());
+            }
+            file.blocks[index].members = inner;
+            i = close + 1;
+            continue;
+        }
+        if word == "rpc" {
+            let (member, next) = proto_rpc(tokens, i, to, lines);
+            members.extend(member);
+            i = next;
+            continue;
+        }
+
+        // Plain statement up to `;`; a `{` here is an option aggregate
+        let mut end = i;
+        while end < to && tokens[end].text != ";" {
+            if tokens[end].text == "{" {
+                end = matching(tokens, end);
+            }
+            end += 1;
+        }
+        if !PROTO_SKIPPED.contains(&word) {
+            let statement = &tokens[i..end.min(to)];
+            if let Some(eq) = statement.iter().position(|t| t.text == "=") {
+                if eq > 0 && is_name(statement[eq - 1].text) {
+                    let type_name = statement[..eq - 1]
+                        .iter()
+                        .map(|t| t.text)
+                        .rfind(|t| {
+                            is_name(t) && !matches!(*t, "repeated" | "optional" | "required")
+                        })
+                        .unwrap_or_default();
+                    members.push(IdlMember {
+                        name: statement[eq - 1].text.to_string(),
+                        type_name: type_name.to_string(),
+                        input: None,
+                        http: Vec::new(),
+                        line: lines.line(statement[eq - 1].offset),
+                    });
+                }
+            }
+        }
+        i = end + 1;
+    }
+    members
+}
+
+/// `rpc Name (stream? Req) returns (stream? Resp) [{ options }] ;?`
+fn proto_rpc(
+    tokens: &[Token<'_>],
+    start: usize,
+    to: usize,
+    lines: &LineIndex,
+) -> (Option<IdlMember>, usize) {
+    let name = tokens.get(start + 1).map(|t| t.text).unwrap_or_default();
+    let message = |open: usize| -> (String, usize) {
+        if tokens.get(open).map(|t| t.text) != Some("(") {
+            return (String::new(), open);
+        }
+        let close = matching(tokens, open);
+        let name = tokens
+            .get(open + 1..close.min(to))
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.text)
+            .rfind(|t| is_name(t) && *t != "stream")
+            .unwrap_or_default();
+        (name.to_string(), close + 1)
+    };
+    let (input, after_input) = message(start + 2);
+    let mut i = after_input;
+    let mut type_name = String::new();
+    if tokens.get(i).map(|t| t.text) == Some("returns") {
+        let (output, after) = message(i + 1);
+        type_name = output;
+        i = after;
+    }
+
+    let mut http = Vec::new();
+    match tokens.get(i).map(|t| t.text) {
+        Some("{") => {
+            let close = matching(tokens, i).min(to);
+            let body = tokens.get(i + 1..close).unwrap_or_default();
+            for k in 0..body.len().saturating_sub(2) {
+                let method = body[k].text.to_ascii_lowercase();
+                if HTTP_METHODS.contains(&method.as_str()) && body[k + 1].text == ":" {
+                    if let Some(path) = unquote(body[k + 2].text) {
+                        http.push((method.to_ascii_uppercase(), path.to_string()));
+                    }
+                }
+            }
+            i = close + 1;
+            if tokens.get(i).map(|t| t.text) == Some(";") {
+                i += 1;
+            }
+        }
+        Some(";") => i += 1,
+        _ => {}
+    }
+
+    let member = is_name(name).then(|| IdlMember {
+        name: name.to_string(),
+        type_name,
+        input: Some(input).filter(|s| !s.is_empty()),
+        http,
+        line: lines.line(tokens[start].offset),
+    });
+    (member, i)
+}
+
+// ---------------------------------------------------------------------------
+// Tokens
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Graphql,
+    Proto,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+/// Replace comments — and in GraphQL, string descriptions — with spaces,
+/// keeping newlines so offsets still map to lines.
+fn blank_comments(content: &str, syntax: Syntax) -> String {
+    let bytes = content.as_bytes();
+    let mut out = bytes.to_vec();
+    let blank = |out: &mut Vec<u8>, from: usize, to: usize| {
+        for b in &mut out[from..to] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+    };
+    let mut i = 0;
+    while i < bytes.len() {
+        match (syntax, bytes[i]) {
+            (Syntax::Graphql, b'#') => {
+                let end = find_from(content, i, "\n").unwrap_or(bytes.len());
+                blank(&mut out, i, end);
+                i = end;
+            }
+            (Syntax::Graphql, b'"') if content[i..].starts_with("\"\"\"") => {
+                let end = find_from(content, i + 3, "\"\"\"").map_or(bytes.len(), |e| e + 3);
+                blank(&mut out, i, end);
+                i = end;
+            }
+            (_, b'"') | (Syntax::Proto, b'\'') => {
+                let end = string_end(content, i);
+                if syntax == Syntax::Graphql {
+                    blank(&mut out, i, end);
+                }
+                i = end;
+            }
+            (Syntax::Proto, b'/') if content[i..].starts_with("//") => {
+                let end = find_from(content, i, "\n").unwrap_or(bytes.len());
+                blank(&mut out, i, end);
+                i = end;
+            }
+            (Syntax::Proto, b'/') if content[i..].starts_with("/*") => {
+                let end = find_from(content, i + 2, "*/").map_or(bytes.len(), |e| e + 2);
+                blank(&mut out, i, end);
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    // Only ASCII bytes were replaced, and whole characters at that
+    String::from_utf8(out).unwrap_or_else(|_| content.to_string())
+}
+
+fn find_from(content: &str, from: usize, needle: &str) -> Option<usize> {
+    content[from..].find(needle).map(|p| from + p)
+}
+
+/// Offset just past the string literal opening at `start` (or of its line end
+/// when unterminated).
+fn string_end(text: &str, start: usize) -> usize {
+    let bytes = text.as_bytes();
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return i,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    let mut end = i.min(bytes.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    end
+}
+
+/// Names (proto names may be dotted), string literals and single punctuation
+/// characters. Commas are whitespace in GraphQL and noise in proto.
+fn tokenize(text: &str, syntax: Syntax) -> Vec<Token<'_>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b.is_ascii_whitespace() || b == b',' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if b == b'_' || b.is_ascii_alphanumeric() {
+            while i < bytes.len()
+                && (bytes[i] == b'_'
+                    || bytes[i].is_ascii_alphanumeric()
+                    || (syntax == Syntax::Proto && bytes[i] == b'.'))
+            {
+                i += 1;
+            }
+        } else if b == b'"' || b == b'\'' {
+            i = string_end(text, i);
+        } else {
+            i += text[i..].chars().next().map_or(1, char::len_utf8);
+        }
+        tokens.push(Token {
+            text: &text[start..i],
+            offset: start,
+        });
+    }
+    tokens
+}
+
+/// Index of the token closing the bracket opened at `open` (or the last index).
+fn matching(tokens: &[Token<'_>], open: usize) -> usize {
+    let (up, down) = match tokens[open].text {
+        "(" => ("(", ")"),
+        "[" => ("[", "]"),
+        _ => ("{", "}"),
+    };
+    let mut depth = 0i32;
+    for (k, token) in tokens.iter().enumerate().skip(open) {
+        if token.text == up {
+            depth += 1;
+        } else if token.text == down {
+            depth -= 1;
+            if depth == 0 {
+                return k;
+            }
+        }
+    }
+    tokens.len().saturating_sub(1)
+}
+
+fn is_name(token: &str) -> bool {
+    token
+        .chars()
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+}
+
+fn unquote(token: &str) -> Option<&str> {
+    token
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .or_else(|| token.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')))
+}
+
+/// 0-based line of a byte offset.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { starts }
+    }
+
+    fn line(&self, offset: usize) -> usize {
+        self.starts
+            .partition_point(|&s| s <= offset)
+            .saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphql_schema_blocks_and_fields() {
+        let sdl = r#"# Root
+"""
+Queries { not a block }
+"""
+type Query {
+  "The user"
+  user(id: ID!, filter: UserFilter = {active: true}): User
+  users: [User!]! @deprecated(reason: "use search")
+}
+
+extend type Mutation {
+  createUser(
+    input: CreateUserInput!
+  ): User!
+}
+
+enum Role { ADMIN USER @deprecated }
+scalar DateTime
+union Result = User | Error
+"#;
+        let blocks = graphql_schema(sdl);
+        let summary: Vec<_> = blocks
+            .iter()
+            .map(|b| (b.keyword.as_str(), b.name.as_str(), b.start, b.end))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("type", "Query", 4, 8),
+                ("type", "Mutation", 10, 14),
+                ("enum", "Role", 16, 16),
+                ("scalar", "DateTime", 17, 17),
+                ("union", "Result", 18, 18),
+            ]
+        );
+        let fields: Vec<_> = blocks[0]
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.type_name.as_str(), m.line))
+            .collect();
+        assert_eq!(fields, vec![("user", "User", 6), ("users", "User", 7)]);
+        assert!(blocks[1].extension);
+        assert_eq!(blocks[1].members[0].name, "createUser");
+        assert_eq!(blocks[1].members[0].type_name, "User");
+        let roles: Vec<_> = blocks[2].members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(roles, vec!["ADMIN", "USER"]);
+    }
+
+    #[test]
+    fn test_graphql_operations_root_fields() {
+        let doc = "query GetUser($id: ID!) {\n  me: user(id: $id) { name }\n  ...Extra\n  __typename\n}\nfragment Extra on Query { x }\nmutation { createUser(input: {}) { id } }\n{ users { id } }\n";
+        let ops = graphql_operations(doc);
+        assert_eq!(ops.len(), 3);
+        assert_eq!(ops[0].kind, "query");
+        assert_eq!(ops[0].name.as_deref(), Some("GetUser"));
+        assert_eq!(ops[0].fields, vec![("user".to_string(), 1)]);
+        assert_eq!(ops[1].kind, "mutation");
+        assert_eq!(ops[1].fields, vec![("createUser".to_string(), 6)]);
+        assert_eq!(ops[2].fields, vec![("users".to_string(), 7)]);
+    }
+
+    #[test]
+    fn test_proto_services_messages_and_http_bindings() {
+        let proto = r#"syntax = "proto3";
+package acme.users.v1;
+option go_package = "acme/users"; // not a field
+
+/* The user service */
+service UserService {
+  rpc GetUser(GetUserRequest) returns (User) {
+    option (google.api.http) = {
+      get: "/v1/users/{id}"
+      additional_bindings { get: "/v1/me" }
+    };
+  }
+  rpc Watch(stream WatchRequest) returns (stream User);
+}
+
+message User {
+  string id = 1;
+  repeated string emails = 2;
+  message Address { string city = 1; }
+  oneof contact { string phone = 3; }
+}
+"#;
+        let file = proto_definitions(proto);
+        assert_eq!(file.package.as_deref(), Some("acme.users.v1"));
+        let names: Vec<_> = file
+            .blocks
+            .iter()
+            .map(|b| (b.keyword.as_str(), b.name.as_str(), b.start, b.end))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("service", "UserService", 5, 13),
+                ("message", "User", 15, 20),
+                ("message", "User.Address", 18, 18),
+                ("oneof", "contact", 19, 19),
+            ]
+        );
+        let rpcs = &file.blocks[0].members;
+        assert_eq!(rpcs.len(), 2);
+        assert_eq!(rpcs[0].name, "GetUser");
+        assert_eq!(rpcs[0].input.as_deref(), Some("GetUserRequest"));
+        assert_eq!(rpcs[0].type_name, "User");
+        assert_eq!(
+            rpcs[0].http,
+            vec![
+                ("GET".to_string(), "/v1/users/{id}".to_string()),
+                ("GET".to_string(), "/v1/me".to_string())
+            ]
+        );
+        assert_eq!(rpcs[1].input.as_deref(), Some("WatchRequest"));
+        assert_eq!(rpcs[1].line, 12);
+        let fields: Vec<_> = file.blocks[1]
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![("id", "string"), ("emails", "string"), ("phone", "string")]
+        );
+    }
+}
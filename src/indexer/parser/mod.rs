@@ -1,7 +1,10 @@
 pub mod chunking;
 #[allow(unused_imports)]
 pub mod core;
+pub mod documents;
+pub mod idl;
 pub mod skeleton;
+pub mod sql;
 pub mod type_fields;
 
 // Реэкспорт публичного API — потребители не меняются
@@ -9,6 +12,7 @@ pub mod type_fields;
 pub use self::core::{CodeParser, ParsedFile, ParserError, Result, SupportedLanguage};
 #[allow(unused_imports)]
 pub use chunking::smart_chunk_file;
+pub use documents::{parse_document, DocumentKind, SourceKind};
 #[allow(unused_imports)]
 pub use skeleton::generate_skeleton;
 #[allow(unused_imports)]
@@ -0,0 +1,1300 @@
+//! Statement-level SQL reading for migrations and schema files.
+//!
+//! Not a full SQL parser: statements are split on `;` outside strings,
+//! comments, dollar quotes and trigger bodies, then the DDL forms that define
+//! names (tables, columns, views, indexes, routines) are picked out of a token
+//! stream. Anything else is kept as an opaque statement.
+
+/// One statement; `text` has comments blanked out but keeps every newline, so
+/// offsets into it map back to lines.
+#[derive(Debug, Clone)]
+pub struct SqlStatement {
+    /// 0-based line of the first character
+    pub start_line: usize,
+    /// 0-based line of the terminating `;` (or of the last character)
+    pub end_line: usize,
+    pub text: String,
+}
+
+impl SqlStatement {
+    /// 0-based file line of a byte offset into `text`.
+    pub fn line_at(&self, offset: usize) -> usize {
+        self.start_line
+            + self.text[..offset.min(self.text.len())]
+                .matches('\n')
+                .count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlColumn {
+    pub name: String,
+    /// The column definition with whitespace collapsed, e.g. `id INTEGER PRIMARY KEY`
+    pub definition: String,
+    /// 0-based file line
+    pub line: usize,
+}
+
+impl SqlColumn {
+    /// Declared type, e.g. `VARCHAR(255)`; empty when omitted (SQLite allows that)
+    pub fn data_type(&self) -> String {
+        let tokens = tokenize(&self.definition);
+        let mut depth = 0i32;
+        let mut end = 1;
+        for (i, token) in tokens.iter().enumerate().skip(1) {
+            if depth == 0 && COLUMN_CONSTRAINTS.contains(&token.text.to_ascii_uppercase().as_str())
+            {
+                break;
+            }
+            match token.text {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                _ => {}
+            }
+            end = i + 1;
+        }
+        if end <= 1 {
+            return String::new();
+        }
+        let last = &tokens[end - 1];
+        self.definition[tokens[1].offset..last.offset + last.text.len()].to_string()
+    }
+
+    pub fn is_primary_key(&self) -> bool {
+        self.words()
+            .windows(2)
+            .any(|w| w[0] == "PRIMARY" && w[1] == "KEY")
+    }
+
+    pub fn is_not_null(&self) -> bool {
+        self.is_primary_key()
+            || self
+                .words()
+                .windows(2)
+                .any(|w| w[0] == "NOT" && w[1] == "NULL")
+    }
+
+    /// Inline `REFERENCES table (column)`
+    pub fn references(&self) -> Option<SqlForeignKey> {
+        let tokens = tokenize(&self.definition);
+        let pos = tokens
+            .iter()
+            .position(|t| t.text.eq_ignore_ascii_case("references"))?;
+        let (ref_table, after) = qualified_name(&tokens, pos + 1)?;
+        let ref_columns = name_list(&tokens, after)
+            .map(|(names, _)| names)
+            .unwrap_or_default();
+        Some(SqlForeignKey {
+            columns: vec![self.name.clone()],
+            ref_table,
+            ref_columns,
+            line: self.line,
+        })
+    }
+
+    fn words(&self) -> Vec<String> {
+        tokenize(&self.definition)
+            .iter()
+            .map(|t| t.text.to_ascii_uppercase())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlForeignKey {
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    /// 0-based file line
+    pub line: usize,
+}
+
+/// Table-level constraint the schema model keeps track of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlConstraint {
+    PrimaryKey(Vec<String>),
+    ForeignKey(SqlForeignKey),
+}
+
+/// One action of `ALTER TABLE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlAlter {
+    AddColumn(SqlColumn),
+    AddConstraint(SqlConstraint),
+    DropColumn(String),
+    RenameColumn {
+        from: String,
+        to: String,
+    },
+    RenameTable(String),
+    /// `ALTER COLUMN c TYPE t` / `SET DATA TYPE t`
+    SetType {
+        column: String,
+        data_type: String,
+    },
+    /// `ALTER COLUMN c SET NOT NULL` / `DROP NOT NULL`
+    SetNotNull {
+        column: String,
+        not_null: bool,
+    },
+    /// MySQL `MODIFY c def` (`from` is `c`) and `CHANGE old def`
+    ModifyColumn {
+        from: String,
+        column: SqlColumn,
+    },
+    Other,
+}
+
+/// What a statement defines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlDefinition {
+    Table {
+        name: String,
+        columns: Vec<SqlColumn>,
+        constraints: Vec<SqlConstraint>,
+        if_not_exists: bool,
+    },
+    View {
+        name: String,
+    },
+    Index {
+        name: String,
+        table: String,
+        columns: Vec<String>,
+        unique: bool,
+    },
+    /// Function, procedure or trigger
+    Routine {
+        name: String,
+    },
+    AlterTable {
+        table: String,
+        actions: Vec<SqlAlter>,
+    },
+    DropTable {
+        names: Vec<String>,
+    },
+    DropIndex {
+        names: Vec<String>,
+    },
+    Other,
+}
+
+/// A column a query reads or writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlColumnRef {
+    pub column: String,
+    /// The table it belongs to; several when an unqualified name could come
+    /// from any table of a join. Empty when the query names no table.
+    pub tables: Vec<String>,
+    /// Byte offset of the column name in the query text
+    pub offset: usize,
+}
+
+/// Split a script into statements.
+pub fn split_statements(content: &str) -> Vec<SqlStatement> {
+    let bytes = content.as_bytes();
+    let mut statements = Vec::new();
+    let mut text = String::with_capacity(content.len());
+    let mut start: Option<usize> = None;
+    let mut line = 0usize;
+    let mut block_depth = 0i32;
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        match b {
+            b'\n' => {
+                line += 1;
+                if start.is_some() {
+                    text.push('\n');
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                        if start.is_some() {
+                            text.push('\n');
+                        }
+                    }
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                if start.is_some() {
+                    text.push(' ');
+                }
+            }
+            b';' if block_depth <= 0 => {
+                if let Some(first) = start.take() {
+                    text.push(';');
+                    statements.push(SqlStatement {
+                        start_line: first,
+                        end_line: line,
+                        text: std::mem::take(&mut text),
+                    });
+                }
+                block_depth = 0;
+                i += 1;
+            }
+            _ if b.is_ascii_whitespace() && start.is_none() => i += 1,
+            _ => {
+                start.get_or_insert(line);
+                let end = match b {
+                    b'\'' | b'"' | b'`' => quoted_end(bytes, i, b),
+                    b'[' if is_bracket_identifier(bytes, i) => quoted_end(bytes, i, b']'),
+                    b'$' => dollar_quote_end(bytes, i).unwrap_or(i + 1),
+                    _ if is_word_byte(b) => {
+                        let mut end = i;
+                        while end < bytes.len() && is_word_byte(bytes[end]) {
+                            end += 1;
+                        }
+                        // Trigger and routine bodies: `BEGIN ...; ...; END`
+                        let word = &content[i..end];
+                        if word.eq_ignore_ascii_case("begin") || word.eq_ignore_ascii_case("case") {
+                            if is_routine(&text) {
+                                block_depth += 1;
+                            }
+                        } else if word.eq_ignore_ascii_case("end") && block_depth > 0 {
+                            block_depth -= 1;
+                        }
+                        end
+                    }
+                    _ => i + 1,
+                };
+                let end = end.max(i + 1).min(bytes.len());
+                let piece = &content[i..end];
+                line += piece.matches('\n').count();
+                text.push_str(piece);
+                i = end;
+            }
+        }
+    }
+
+    if let Some(first) = start {
+        if !text.trim().is_empty() {
+            statements.push(SqlStatement {
+                start_line: first,
+                end_line: first + text.trim_end().matches('\n').count(),
+                text,
+            });
+        }
+    }
+    statements
+}
+
+/// Recognise the definition a statement makes.
+pub fn classify(statement: &SqlStatement) -> SqlDefinition {
+    let tokens = tokenize(&statement.text);
+    let words: Vec<String> = tokens.iter().map(|t| t.text.to_ascii_uppercase()).collect();
+    let at = |pos: usize, word: &str| words.get(pos).map(|w| w == word).unwrap_or(false);
+    let end = tokens
+        .iter()
+        .rposition(|t| t.text == ";")
+        .unwrap_or(tokens.len());
+
+    if at(0, "CREATE") {
+        let mut pos = 1;
+        let mut unique = false;
+        while let Some(modifier) = words
+            .get(pos)
+            .filter(|w| CREATE_MODIFIERS.contains(&w.as_str()))
+        {
+            unique |= modifier == "UNIQUE";
+            pos += 1;
+        }
+        let object = words.get(pos).cloned().unwrap_or_default();
+        pos += 1;
+        if object == "INDEX" && at(pos, "CONCURRENTLY") {
+            pos += 1;
+        }
+        let if_not_exists = at(pos, "IF");
+        if if_not_exists {
+            pos += if at(pos + 1, "NOT") { 3 } else { 2 };
+        }
+        let Some((name, after)) = qualified_name(&tokens, pos) else {
+            return SqlDefinition::Other;
+        };
+        return match object.as_str() {
+            "TABLE" => {
+                let (columns, constraints) = table_body(statement, &tokens, after);
+                SqlDefinition::Table {
+                    name,
+                    columns,
+                    constraints,
+                    if_not_exists,
+                }
+            }
+            "VIEW" => SqlDefinition::View { name },
+            "INDEX" => {
+                let on = words[after..]
+                    .iter()
+                    .position(|w| w == "ON")
+                    .map(|p| p + after);
+                let (table, mut next) = on
+                    .and_then(|on| qualified_name(&tokens, on + 1))
+                    .unwrap_or_default();
+                if at(next, "USING") {
+                    next += 2;
+                }
+                let columns = name_list(&tokens, next)
+                    .map(|(names, _)| names)
+                    .unwrap_or_default();
+                SqlDefinition::Index {
+                    name,
+                    table,
+                    columns,
+                    unique,
+                }
+            }
+            "FUNCTION" | "PROCEDURE" | "TRIGGER" => SqlDefinition::Routine { name },
+            _ => SqlDefinition::Other,
+        };
+    }
+
+    if at(0, "ALTER") && at(1, "TABLE") {
+        let mut pos = 2;
+        if at(pos, "IF") && at(pos + 1, "EXISTS") {
+            pos += 2;
+        }
+        if at(pos, "ONLY") {
+            pos += 1;
+        }
+        let Some((table, after)) = qualified_name(&tokens, pos) else {
+            return SqlDefinition::Other;
+        };
+        let mut actions = Vec::new();
+        let mut depth = 0i32;
+        let mut start = after;
+        for i in after..=end {
+            match tokens.get(i).map(|t| t.text) {
+                Some("(") => depth += 1,
+                Some(")") => depth -= 1,
+                Some(",") if depth == 0 => {
+                    actions.push(alter_action(statement, &tokens[start..i]));
+                    start = i + 1;
+                }
+                _ if i == end => {
+                    actions.push(alter_action(statement, &tokens[start.min(end)..end]))
+                }
+                _ => {}
+            }
+        }
+        return SqlDefinition::AlterTable { table, actions };
+    }
+
+    // MySQL `RENAME TABLE a TO b`
+    if at(0, "RENAME") && at(1, "TABLE") {
+        if let Some((table, after)) = qualified_name(&tokens, 2) {
+            if at(after, "TO") {
+                if let Some((to, _)) = qualified_name(&tokens, after + 1) {
+                    return SqlDefinition::AlterTable {
+                        table,
+                        actions: vec![SqlAlter::RenameTable(to)],
+                    };
+                }
+            }
+        }
+    }
+
+    if at(0, "DROP") && (at(1, "TABLE") || at(1, "INDEX")) {
+        let mut pos = 2;
+        if at(pos, "CONCURRENTLY") {
+            pos += 1;
+        }
+        if at(pos, "IF") && at(pos + 1, "EXISTS") {
+            pos += 2;
+        }
+        let mut names = Vec::new();
+        while let Some((name, after)) = qualified_name(&tokens, pos) {
+            names.push(name);
+            if tokens.get(after).map(|t| t.text) != Some(",") {
+                break;
+            }
+            pos = after + 1;
+        }
+        return if at(1, "TABLE") {
+            SqlDefinition::DropTable { names }
+        } else {
+            SqlDefinition::DropIndex { names }
+        };
+    }
+
+    SqlDefinition::Other
+}
+
+/// Columns read or written by a DML query (`SELECT`, `INSERT`, `UPDATE`,
+/// `DELETE`). Qualified names are resolved through table aliases; bare names
+/// are attributed to every table the query mentions.
+pub fn query_columns(text: &str) -> Vec<SqlColumnRef> {
+    let tokens = tokenize(text);
+    let words: Vec<String> = tokens.iter().map(|t| t.text.to_ascii_uppercase()).collect();
+    let at = |pos: usize, word: &str| words.get(pos).map(|w| w == word).unwrap_or(false);
+    let is_keyword = |pos: usize| {
+        tokens[pos].text.bytes().next().is_some_and(is_word_byte)
+            && QUERY_KEYWORDS.contains(&words[pos].as_str())
+    };
+
+    // Common table expressions: `name AS (` and `name (a, b) AS (`
+    let mut ctes = std::collections::HashSet::new();
+    for i in 0..tokens.len() {
+        if at(i + 1, "AS") && tokens.get(i + 2).is_some_and(|t| t.text == "(") && !is_keyword(i) {
+            if let Some(name) = identifier(tokens[i].text) {
+                ctes.insert(name.to_ascii_lowercase());
+            }
+        }
+    }
+
+    // Tables and their aliases
+    let mut tables: Vec<String> = Vec::new();
+    let mut aliases: std::collections::HashMap<String, String> = Default::default();
+    let mut consumed = vec![false; tokens.len()];
+    let mut insert_table = None;
+    for i in 0..tokens.len() {
+        if !matches!(words[i].as_str(), "FROM" | "JOIN" | "UPDATE" | "INTO") {
+            continue;
+        }
+        let mut pos = i + 1;
+        loop {
+            if at(pos, "ONLY") {
+                pos += 1;
+            }
+            if pos >= tokens.len() || is_keyword(pos) {
+                break;
+            }
+            let Some((name, after)) = qualified_name(&tokens, pos) else {
+                break;
+            };
+            consumed[pos..after].iter_mut().for_each(|c| *c = true);
+            let key = name.to_ascii_lowercase();
+            let cte = ctes.contains(&key);
+            if !cte && !tables.contains(&name) {
+                tables.push(name.clone());
+            }
+            if words[i] == "INTO" {
+                insert_table.get_or_insert(name.clone());
+            }
+            if let Some(short) = key.rsplit('.').next() {
+                aliases.insert(short.to_string(), name.clone());
+            }
+            aliases.insert(key, name.clone());
+
+            let mut next = after;
+            if at(next, "AS") {
+                next += 1;
+            }
+            if next < tokens.len() && !is_keyword(next) && tokens[next].text != "(" {
+                if let Some(alias) = identifier(tokens[next].text) {
+                    aliases.insert(alias.to_ascii_lowercase(), name.clone());
+                    consumed[next] = true;
+                    next += 1;
+                }
+            }
+            if words[i] == "FROM" && tokens.get(next).is_some_and(|t| t.text == ",") {
+                pos = next + 1;
+                continue;
+            }
+            break;
+        }
+    }
+    if let Some(table) = insert_table {
+        aliases.insert("excluded".into(), table);
+    }
+
+    // Output aliases (`expr AS name`) are not columns when referenced later
+    let outputs: std::collections::HashSet<String> = (1..tokens.len())
+        .filter(|&i| at(i - 1, "AS") && tokens.get(i + 1).map(|t| t.text) != Some("("))
+        .filter_map(|i| identifier(tokens[i].text))
+        .map(|name| name.to_ascii_lowercase())
+        .collect();
+
+    let mut refs = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let prev = i.checked_sub(1).map(|p| tokens[p].text).unwrap_or("");
+        let next = tokens.get(i + 1).map(|t| t.text).unwrap_or("");
+        let skip = consumed[i]
+            || is_keyword(i)
+            || token
+                .text
+                .bytes()
+                .next()
+                .is_some_and(|b| b.is_ascii_digit())
+            || matches!(prev, ":" | "@" | "$" | "?" | ".")
+            || at(i.wrapping_sub(1), "AS")
+            || next == "(";
+        let Some(name) = identifier(token.text).filter(|_| !skip) else {
+            i += 1;
+            continue;
+        };
+        let key = name.to_ascii_lowercase();
+
+        if next == "." {
+            // `alias.column`; `schema.table.column` resolves on the last qualifier
+            let mut j = i;
+            while tokens.get(j + 1).is_some_and(|t| t.text == ".")
+                && tokens.get(j + 3).is_some_and(|t| t.text == ".")
+            {
+                j += 2;
+            }
+            let qualifier = identifier(tokens[j].text)
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if let (Some(table), Some(column)) = (
+                aliases.get(&qualifier).or_else(|| aliases.get(&key)),
+                tokens.get(j + 2).and_then(|t| identifier(t.text)),
+            ) {
+                refs.push(SqlColumnRef {
+                    column,
+                    tables: vec![table.clone()],
+                    offset: tokens[j + 2].offset,
+                });
+            }
+            i = j + 3;
+            continue;
+        }
+
+        if !aliases.contains_key(&key) && !ctes.contains(&key) && !outputs.contains(&key) {
+            refs.push(SqlColumnRef {
+                column: name,
+                tables: tables.clone(),
+                offset: token.offset,
+            });
+        }
+        i += 1;
+    }
+    refs
+}
+
+const CREATE_MODIFIERS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "TEMP",
+    "TEMPORARY",
+    "UNLOGGED",
+    "UNIQUE",
+    "MATERIALIZED",
+    "VIRTUAL",
+    "RECURSIVE",
+    "GLOBAL",
+    "LOCAL",
+    "CONSTRAINT",
+];
+
+/// Entries of a table body that are constraints rather than columns
+const TABLE_CONSTRAINTS: &[&str] = &[
+    "CONSTRAINT",
+    "PRIMARY",
+    "FOREIGN",
+    "UNIQUE",
+    "CHECK",
+    "EXCLUDE",
+    "KEY",
+    "INDEX",
+    "FULLTEXT",
+    "SPATIAL",
+    "LIKE",
+    "PERIOD",
+];
+
+/// Words that end a column's type in its definition
+const COLUMN_CONSTRAINTS: &[&str] = &[
+    "CONSTRAINT",
+    "NOT",
+    "NULL",
+    "PRIMARY",
+    "REFERENCES",
+    "UNIQUE",
+    "CHECK",
+    "DEFAULT",
+    "COLLATE",
+    "GENERATED",
+    "AS",
+    "AUTO_INCREMENT",
+    "AUTOINCREMENT",
+    "IDENTITY",
+    "COMMENT",
+    "ON",
+];
+
+/// Reserved words of DML queries; never column names. Words that commonly are
+/// column names too (`key`, `name`, `type`, `time`) are left out.
+const QUERY_KEYWORDS: &[&str] = &[
+    "ALL",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "AT",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CAST",
+    "COLLATE",
+    "CONFLICT",
+    "CROSS",
+    "CURRENT",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DUPLICATE",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "EXCEPT",
+    "EXISTS",
+    "FALSE",
+    "FETCH",
+    "FILTER",
+    "FIRST",
+    "FOLLOWING",
+    "FOR",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "ILIKE",
+    "IN",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "LAST",
+    "LATERAL",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "LOCKED",
+    "NATURAL",
+    "NEXT",
+    "NOT",
+    "NOTHING",
+    "NOWAIT",
+    "NULL",
+    "NULLS",
+    "OF",
+    "OFFSET",
+    "ON",
+    "ONLY",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "PRECEDING",
+    "RANGE",
+    "RECURSIVE",
+    "REPLACE",
+    "RETURNING",
+    "RIGHT",
+    "ROW",
+    "ROWS",
+    "SELECT",
+    "SET",
+    "SHARE",
+    "SIMILAR",
+    "SKIP",
+    "SOME",
+    "TABLE",
+    "THEN",
+    "TIES",
+    "TO",
+    "TRUE",
+    "UNBOUNDED",
+    "UNION",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+    "ZONE",
+];
+
+#[derive(Debug)]
+struct Token<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let end = if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if matches!(b, b'\'' | b'"' | b'`') {
+            quoted_end(bytes, i, b)
+        } else if b == b'[' && is_bracket_identifier(bytes, i) {
+            quoted_end(bytes, i, b']')
+        } else if b == b'$' {
+            dollar_quote_end(bytes, i).unwrap_or(i + 1)
+        } else if is_word_byte(b) {
+            let mut end = i;
+            while end < bytes.len() && is_word_byte(bytes[end]) {
+                end += 1;
+            }
+            end
+        } else {
+            i + 1
+        };
+        let end = end.max(i + 1).min(bytes.len());
+        tokens.push(Token {
+            text: &text[i..end],
+            offset: i,
+        });
+        i = end;
+    }
+    tokens
+}
+
+/// `schema.name` starting at `pos`; returns the unquoted name and the index after it.
+fn qualified_name(tokens: &[Token<'_>], pos: usize) -> Option<(String, usize)> {
+    let mut parts = vec![identifier(tokens.get(pos)?.text)?];
+    let mut next = pos + 1;
+    while tokens.get(next).is_some_and(|t| t.text == ".") {
+        parts.push(identifier(tokens.get(next + 1)?.text)?);
+        next += 2;
+    }
+    Some((parts.join("."), next))
+}
+
+/// Unquoted identifier, or `None` for punctuation and string literals.
+fn identifier(token: &str) -> Option<String> {
+    let first = token.bytes().next()?;
+    match first {
+        b'"' | b'`' | b'[' => Some(token[1..token.len().saturating_sub(1).max(1)].to_string()),
+        _ if is_word_byte(first) => Some(token.to_string()),
+        _ => None,
+    }
+}
+
+/// Columns and constraints of `CREATE TABLE name (...)`; `after` is the token
+/// after the name.
+fn table_body(
+    statement: &SqlStatement,
+    tokens: &[Token<'_>],
+    after: usize,
+) -> (Vec<SqlColumn>, Vec<SqlConstraint>) {
+    // `CREATE VIRTUAL TABLE t USING fts5(...)` lists columns the same way
+    let Some(open) = tokens[after.min(tokens.len())..]
+        .iter()
+        .position(|t| t.text == "(")
+        .map(|p| p + after)
+    else {
+        return Default::default();
+    };
+    if tokens[after..open]
+        .iter()
+        .any(|t| t.text.eq_ignore_ascii_case("as"))
+    {
+        // CREATE TABLE ... AS SELECT
+        return Default::default();
+    }
+
+    let mut columns = Vec::new();
+    let mut constraints = Vec::new();
+    let mut depth = 0i32;
+    let mut entry_start = open + 1;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token.text {
+            "(" => depth += 1,
+            ")" | "," if depth == 1 => {
+                let entry = &tokens[entry_start..i];
+                if let Some(column) = column_def(statement, entry) {
+                    columns.push(column);
+                } else if let Some(c) = constraint(statement, entry) {
+                    constraints.push(c);
+                }
+                entry_start = i + 1;
+                if token.text == ")" {
+                    break;
+                }
+            }
+            ")" => depth -= 1,
+            _ => {}
+        }
+    }
+    (columns, constraints)
+}
+
+/// `[CONSTRAINT name] PRIMARY KEY (...)` or `[CONSTRAINT name] FOREIGN KEY (...)
+/// REFERENCES table (...)`; other constraints are not modelled.
+fn constraint(statement: &SqlStatement, tokens: &[Token<'_>]) -> Option<SqlConstraint> {
+    let mut pos = 0;
+    if tokens.first()?.text.eq_ignore_ascii_case("constraint") {
+        pos = 2;
+    }
+    let word = |p: usize| tokens.get(p).map(|t| t.text.to_ascii_uppercase());
+    match (word(pos)?.as_str(), word(pos + 1).as_deref()) {
+        ("PRIMARY", Some("KEY")) => {
+            let (columns, _) = name_list(tokens, pos + 2)?;
+            Some(SqlConstraint::PrimaryKey(columns))
+        }
+        ("FOREIGN", Some("KEY")) => {
+            let (columns, after) = name_list(tokens, pos + 2)?;
+            if word(after).as_deref() != Some("REFERENCES") {
+                return None;
+            }
+            let (ref_table, after) = qualified_name(tokens, after + 1)?;
+            let ref_columns = name_list(tokens, after)
+                .map(|(names, _)| names)
+                .unwrap_or_default();
+            Some(SqlConstraint::ForeignKey(SqlForeignKey {
+                columns,
+                ref_table,
+                ref_columns,
+                line: statement.line_at(tokens[0].offset),
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// `(a, b DESC, lower(c))` at `pos`: the leading name of each entry (whole
+/// expressions for computed entries) and the index after `)`.
+fn name_list(tokens: &[Token<'_>], pos: usize) -> Option<(Vec<String>, usize)> {
+    if tokens.get(pos)?.text != "(" {
+        return None;
+    }
+    let mut names = Vec::new();
+    let mut depth = 0i32;
+    let mut entry_start = pos + 1;
+    for (i, token) in tokens.iter().enumerate().skip(pos) {
+        match token.text {
+            "(" => depth += 1,
+            ")" | "," if depth == 1 => {
+                let entry = &tokens[entry_start..i];
+                match entry {
+                    [] => {}
+                    [first, rest @ ..] if rest.first().map(|t| t.text) != Some("(") => {
+                        names.extend(identifier(first.text));
+                    }
+                    _ => names.push(entry.iter().map(|t| t.text).collect()),
+                }
+                entry_start = i + 1;
+                if token.text == ")" {
+                    return Some((names, i + 1));
+                }
+            }
+            ")" => depth -= 1,
+            _ => {}
+        }
+    }
+    Some((names, tokens.len()))
+}
+
+/// One comma-separated action of `ALTER TABLE`.
+fn alter_action(statement: &SqlStatement, tokens: &[Token<'_>]) -> SqlAlter {
+    let words: Vec<String> = tokens.iter().map(|t| t.text.to_ascii_uppercase()).collect();
+    let at = |pos: usize, word: &str| words.get(pos).map(|w| w == word).unwrap_or(false);
+    let name_at = |pos: usize| tokens.get(pos).and_then(|t| identifier(t.text));
+    let mut pos = 1;
+
+    match words.first().map(String::as_str) {
+        Some("ADD") => {
+            if at(pos, "COLUMN") {
+                pos += 1;
+            }
+            if at(pos, "IF") && at(pos + 1, "NOT") {
+                pos += 3;
+            }
+            let rest = &tokens[pos.min(tokens.len())..];
+            if words
+                .get(pos)
+                .is_some_and(|w| TABLE_CONSTRAINTS.contains(&w.as_str()))
+            {
+                return constraint(statement, rest)
+                    .map(SqlAlter::AddConstraint)
+                    .unwrap_or(SqlAlter::Other);
+            }
+            column_def(statement, rest)
+                .map(SqlAlter::AddColumn)
+                .unwrap_or(SqlAlter::Other)
+        }
+        Some("DROP") => {
+            if at(pos, "COLUMN") {
+                pos += 1;
+            } else if words
+                .get(pos)
+                .is_some_and(|w| TABLE_CONSTRAINTS.contains(&w.as_str()) || w == "PARTITION")
+            {
+                return SqlAlter::Other;
+            }
+            if at(pos, "IF") && at(pos + 1, "EXISTS") {
+                pos += 2;
+            }
+            name_at(pos)
+                .map(SqlAlter::DropColumn)
+                .unwrap_or(SqlAlter::Other)
+        }
+        Some("RENAME") => {
+            if at(pos, "TO") || at(pos, "AS") {
+                return qualified_name(tokens, pos + 1)
+                    .map(|(name, _)| SqlAlter::RenameTable(name))
+                    .unwrap_or(SqlAlter::Other);
+            }
+            if at(pos, "COLUMN") {
+                pos += 1;
+            } else if words
+                .get(pos)
+                .is_some_and(|w| TABLE_CONSTRAINTS.contains(&w.as_str()))
+            {
+                return SqlAlter::Other;
+            }
+            match (name_at(pos), at(pos + 1, "TO"), name_at(pos + 2)) {
+                (Some(from), true, Some(to)) => SqlAlter::RenameColumn { from, to },
+                _ => SqlAlter::Other,
+            }
+        }
+        Some("ALTER") => {
+            if at(pos, "COLUMN") {
+                pos += 1;
+            }
+            let Some(column) = name_at(pos) else {
+                return SqlAlter::Other;
+            };
+            let rest = pos + 1;
+            let type_at = if at(rest, "TYPE") {
+                Some(rest + 1)
+            } else if at(rest, "SET") && at(rest + 1, "DATA") && at(rest + 2, "TYPE") {
+                Some(rest + 3)
+            } else {
+                None
+            };
+            if let Some(start) = type_at.filter(|&s| s < tokens.len()) {
+                let end = words[start..]
+                    .iter()
+                    .position(|w| matches!(w.as_str(), "USING" | "COLLATE"))
+                    .map(|p| p + start)
+                    .unwrap_or(tokens.len());
+                let last = &tokens[end.max(start + 1) - 1];
+                let data_type = statement.text[tokens[start].offset..last.offset + last.text.len()]
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                return SqlAlter::SetType { column, data_type };
+            }
+            if at(rest + 1, "NOT") && at(rest + 2, "NULL") {
+                if at(rest, "SET") {
+                    return SqlAlter::SetNotNull {
+                        column,
+                        not_null: true,
+                    };
+                }
+                if at(rest, "DROP") {
+                    return SqlAlter::SetNotNull {
+                        column,
+                        not_null: false,
+                    };
+                }
+            }
+            SqlAlter::Other
+        }
+        Some("MODIFY") => {
+            if at(pos, "COLUMN") {
+                pos += 1;
+            }
+            column_def(statement, &tokens[pos.min(tokens.len())..])
+                .map(|column| SqlAlter::ModifyColumn {
+                    from: column.name.clone(),
+                    column,
+                })
+                .unwrap_or(SqlAlter::Other)
+        }
+        Some("CHANGE") => {
+            if at(pos, "COLUMN") {
+                pos += 1;
+            }
+            match (
+                name_at(pos),
+                column_def(statement, &tokens[(pos + 1).min(tokens.len())..]),
+            ) {
+                (Some(from), Some(column)) => SqlAlter::ModifyColumn { from, column },
+                _ => SqlAlter::Other,
+            }
+        }
+        _ => SqlAlter::Other,
+    }
+}
+
+/// A column definition from its tokens, or `None` for table constraints.
+fn column_def(statement: &SqlStatement, tokens: &[Token<'_>]) -> Option<SqlColumn> {
+    let first = tokens.first()?;
+    if TABLE_CONSTRAINTS.contains(&first.text.to_ascii_uppercase().as_str())
+        // `tokenize = 'porter'` style options of virtual tables
+        || tokens.get(1).is_some_and(|t| t.text == "=")
+    {
+        return None;
+    }
+    let name = identifier(first.text)?;
+    let last = tokens.last()?;
+    let raw = &statement.text[first.offset..last.offset + last.text.len()];
+    Some(SqlColumn {
+        name,
+        definition: raw.split_whitespace().collect::<Vec<_>>().join(" "),
+        line: statement.line_at(first.offset),
+    })
+}
+
+/// Whether the statement text so far opens a trigger or routine body.
+fn is_routine(text: &str) -> bool {
+    let head: Vec<String> = text
+        .split_whitespace()
+        .take(6)
+        .map(|w| w.to_ascii_uppercase())
+        .collect();
+    head.first().is_some_and(|w| w == "CREATE")
+        && head
+            .iter()
+            .any(|w| matches!(w.as_str(), "TRIGGER" | "FUNCTION" | "PROCEDURE"))
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// `[name]` is a quoted identifier (SQL Server, SQLite) unless it looks like an
+/// array subscript or type suffix (`int[]`, `a[1]`).
+fn is_bracket_identifier(bytes: &[u8], i: usize) -> bool {
+    bytes
+        .get(i + 1)
+        .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+}
+
+/// End (exclusive) of a quoted literal or identifier starting at `i`; doubled
+/// quotes are escapes.
+fn quoted_end(bytes: &[u8], i: usize, close: u8) -> usize {
+    let mut j = i + 1;
+    while j < bytes.len() {
+        if bytes[j] == close {
+            if bytes.get(j + 1) == Some(&close) && close != b']' {
+                j += 2;
+                continue;
+            }
+            return j + 1;
+        }
+        j += 1;
+    }
+    bytes.len()
+}
+
+/// End (exclusive) of a PostgreSQL dollar-quoted string (`$$...$$`, `$fn$...$fn$`).
+fn dollar_quote_end(bytes: &[u8], i: usize) -> Option<usize> {
+    let mut j = i + 1;
+    while j < bytes.len() && (bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_') {
+        j += 1;
+    }
+    if bytes.get(j) != Some(&b'$') {
+        return None;
+    }
+    let tag = &bytes[i..=j];
+    let body = j + 1;
+    (body..=bytes.len().saturating_sub(tag.len()))
+        .find(|&k| &bytes[k..k + tag.len()] == tag)
+        .map(|k| k + tag.len())
+        .or(Some(bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_respects_strings_comments_and_trigger_bodies() {
+        let sql = "-- header; not a statement\n\
+                   CREATE TABLE a (x TEXT DEFAULT ';');\n\
+                   /* block; comment */ INSERT INTO a VALUES ('it''s;');\n\
+                   CREATE TRIGGER t AFTER INSERT ON a BEGIN\n\
+                     UPDATE a SET x = 'y';\n\
+                   END;\n\
+                   CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;\n";
+        let statements = split_statements(sql);
+        let lines: Vec<_> = statements
+            .iter()
+            .map(|s| (s.start_line, s.end_line))
+            .collect();
+        assert_eq!(lines, vec![(1, 1), (2, 2), (3, 5), (6, 6)]);
+        assert!(!statements[0].text.contains("header"));
+    }
+
+    #[test]
+    fn test_classify_tables_columns_and_alters() {
+        let sql = "CREATE TABLE IF NOT EXISTS \"users\" (\n\
+                       id INTEGER PRIMARY KEY,\n\
+                       email TEXT NOT NULL UNIQUE,\n\
+                       amount NUMERIC(10, 2),\n\
+                       FOREIGN KEY (id) REFERENCES accounts(id)\n\
+                   );\n\
+                   ALTER TABLE users ADD COLUMN last_login INTEGER;\n\
+                   CREATE UNIQUE INDEX idx_email ON users(email);\n\
+                   CREATE VIRTUAL TABLE docs USING fts5(body, tokenize='porter');\n";
+        let defs: Vec<_> = split_statements(sql).iter().map(classify).collect();
+
+        let SqlDefinition::Table {
+            name,
+            columns,
+            constraints,
+            if_not_exists,
+        } = &defs[0]
+        else {
+            panic!("expected a table, got {:?}", defs[0]);
+        };
+        assert!(*if_not_exists);
+        assert_eq!(name, "users");
+        let names: Vec<_> = columns.iter().map(|c| (c.name.as_str(), c.line)).collect();
+        assert_eq!(names, vec![("id", 1), ("email", 2), ("amount", 3)]);
+        assert_eq!(columns[2].definition, "amount NUMERIC(10, 2)");
+        assert_eq!(columns[2].data_type(), "NUMERIC(10, 2)");
+        assert!(columns[0].is_primary_key() && columns[1].is_not_null());
+        assert_eq!(
+            constraints,
+            &vec![SqlConstraint::ForeignKey(SqlForeignKey {
+                columns: vec!["id".into()],
+                ref_table: "accounts".into(),
+                ref_columns: vec!["id".into()],
+                line: 4,
+            })]
+        );
+
+        assert_eq!(
+            defs[1],
+            SqlDefinition::AlterTable {
+                table: "users".into(),
+                actions: vec![SqlAlter::AddColumn(SqlColumn {
+                    name: "last_login".into(),
+                    definition: "last_login INTEGER".into(),
+                    line: 6,
+                })],
+            }
+        );
+        assert_eq!(
+            defs[2],
+            SqlDefinition::Index {
+                name: "idx_email".into(),
+                table: "users".into(),
+                columns: vec!["email".into()],
+                unique: true,
+            }
+        );
+        let SqlDefinition::Table { name, columns, .. } = &defs[3] else {
+            panic!("expected a virtual table");
+        };
+        assert_eq!(name, "docs");
+        assert_eq!(columns.len(), 1);
+    }
+
+    #[test]
+    fn test_classify_schema_changes() {
+        let sql = "ALTER TABLE users DROP COLUMN IF EXISTS legacy, RENAME COLUMN mail TO email;\n\
+                   ALTER TABLE users ALTER COLUMN age TYPE BIGINT USING age::bigint;\n\
+                   ALTER TABLE orders ADD CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id);\n\
+                   ALTER TABLE accounts RENAME TO users_old;\n\
+                   DROP TABLE IF EXISTS a, public.b CASCADE;\n\
+                   CREATE INDEX idx_lower ON users USING btree (lower(email), created_at DESC);\n\
+                   CREATE TABLE posts (id INT, author_id INT NOT NULL REFERENCES users(id));\n";
+        let defs: Vec<_> = split_statements(sql).iter().map(classify).collect();
+
+        let SqlDefinition::AlterTable { table, actions } = &defs[0] else {
+            panic!("expected an alter, got {:?}", defs[0]);
+        };
+        assert_eq!(table, "users");
+        assert_eq!(
+            actions,
+            &vec![
+                SqlAlter::DropColumn("legacy".into()),
+                SqlAlter::RenameColumn {
+                    from: "mail".into(),
+                    to: "email".into()
+                },
+            ]
+        );
+        let SqlDefinition::AlterTable { actions, .. } = &defs[1] else {
+            panic!("expected an alter");
+        };
+        assert_eq!(
+            actions,
+            &vec![SqlAlter::SetType {
+                column: "age".into(),
+                data_type: "BIGINT".into()
+            }]
+        );
+        let SqlDefinition::AlterTable { actions, .. } = &defs[2] else {
+            panic!("expected an alter");
+        };
+        assert!(matches!(
+            &actions[0],
+            SqlAlter::AddConstraint(SqlConstraint::ForeignKey(fk)) if fk.ref_table == "users" && fk.columns == ["user_id"]
+        ));
+        let SqlDefinition::AlterTable { actions, .. } = &defs[3] else {
+            panic!("expected an alter");
+        };
+        assert_eq!(actions, &vec![SqlAlter::RenameTable("users_old".into())]);
+        assert_eq!(
+            defs[4],
+            SqlDefinition::DropTable {
+                names: vec!["a".into(), "public.b".into()]
+            }
+        );
+        let SqlDefinition::Index { columns, .. } = &defs[5] else {
+            panic!("expected an index");
+        };
+        assert_eq!(
+            columns,
+            &vec!["lower(email)".to_string(), "created_at".into()]
+        );
+        let SqlDefinition::Table { columns, .. } = &defs[6] else {
+            panic!("expected a table");
+        };
+        let fk = columns[1].references().unwrap();
+        assert_eq!((fk.ref_table.as_str(), fk.ref_columns.len()), ("users", 1));
+        assert_eq!(columns[1].data_type(), "INT");
+    }
+
+    #[test]
+    fn test_query_columns_resolve_aliases() {
+        let refs = query_columns(
+            "SELECT u.id, u.email, o.total AS sum FROM users u JOIN orders AS o ON o.user_id = u.id \
+             WHERE status = $1 AND created_at > NOW() ORDER BY sum",
+        );
+        let found: Vec<_> = refs
+            .iter()
+            .map(|r| (r.column.as_str(), r.tables.join(",")))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("id", "users".to_string()),
+                ("email", "users".into()),
+                ("total", "orders".into()),
+                ("user_id", "orders".into()),
+                ("id", "users".into()),
+                ("status", "users,orders".into()),
+                ("created_at", "users,orders".into()),
+            ]
+        );
+
+        let refs = query_columns(
+            "INSERT INTO users (email, name) VALUES (?, ?) ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name",
+        );
+        assert!(refs.iter().all(|r| r.tables == ["users"]));
+        assert_eq!(refs.len(), 5);
+    }
+}
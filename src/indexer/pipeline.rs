@@ -15,13 +15,18 @@ use tokio::task::JoinHandle;
 use tokio::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
+use super::api_contracts::{extract_api, FileApi};
+use super::column_usages::{extract_column_usages, ColumnUsage};
+use super::context::ImportResolver;
+use super::db_schema::table_key;
 use super::domains::{detect_domain, DomainConfig};
 use super::embedder::EmbedderPool;
-use super::parser::{CodeParser, SupportedLanguage};
-use super::watcher::scan_directory;
+use super::parser::{parse_document, CodeParser, SourceKind, SupportedLanguage};
+use super::reconcile::{self, GitCandidates};
+use super::watcher::{filter_scannable, load_config, scan_directory};
 use crate::daemon::state::SyncProgress;
 use crate::models::{CodeChunk, ImportInfo, Symbol, SymbolReference};
-use crate::storage::{LanceStorage, SqliteStorage};
+use crate::storage::{FileStat, LanceStorage, SqliteStorage};
 
 // ---------------------------------------------------------------------------
 // Message types between pipeline stages
@@ -33,7 +38,7 @@ struct ScannedFile {
     content: Arc<String>,
     hash: String,
     modified: i64,
-    language: SupportedLanguage,
+    kind: SourceKind,
 }
 
 /// Parser → Batcher
@@ -41,11 +46,13 @@ struct ParsedDoc {
     path: String,
     hash: String,
     modified: i64,
-    language: SupportedLanguage,
+    kind: SourceKind,
     symbols: Vec<Symbol>,
     chunks: Vec<CodeChunk>,
     refs: Vec<SymbolReference>,
     imports: Vec<ImportInfo>,
+    column_usages: Vec<ColumnUsage>,
+    api: FileApi,
     domain: SmolStr,
     tech_stack: Vec<SmolStr>,
     content: Arc<String>,
@@ -70,10 +77,12 @@ pub struct ParsedFileMetadata {
     pub path: String,
     pub hash: String,
     pub modified: i64,
-    pub language: SupportedLanguage,
+    pub kind: SourceKind,
     pub symbols: Vec<Symbol>,
     pub refs: Vec<SymbolReference>,
     pub imports: Vec<ImportInfo>,
+    pub column_usages: Vec<ColumnUsage>,
+    pub api: FileApi,
     pub domain: SmolStr,
     pub tech_stack: Vec<SmolStr>,
     pub content: Arc<String>,
@@ -91,6 +100,11 @@ const SQLITE_FLUSH_SIZE: usize = 100;
 const BATCH_MAX_CONTENT_BYTES: usize = 512 * 1024; // 512KB max content per batch
 const MAX_FILE_SIZE_BYTES: u64 = 2 * 1024 * 1024; // 2MB max file size for indexing
 
+/// `index_metadata` key: set once every indexed file has its `file_imports` rows
+const IMPORT_GRAPH_KEY: &str = "import_graph";
+/// `index_metadata` key: set once `chunk_text` holds the text of every stored chunk
+const CHUNK_TEXT_KEY: &str = "chunk_text";
+
 /// Run the full indexing pipeline. Returns collected metadata for post-pipeline
 /// phases (cross-stack linking, structural fingerprinting).
 ///
@@ -118,8 +132,14 @@ pub async fn run_pipeline(
         tracing::warn!("Failed to scale up embedder pool: {}", e);
     };
 
-    // Pre-fetch hashes for skip-unchanged logic
-    let existing_hashes = sqlite.get_all_file_hashes().await?;
+    // Pre-fetch hash, mtime and size for skip-unchanged logic
+    let existing = sqlite.get_file_stats().await?;
+    let fresh_index = existing.is_empty();
+
+    // In a git repository only files changed since the last sync are candidates
+    let git = git_candidates(root, extra_ignores, &sqlite, &existing).await;
+    let recorded_state = git.as_ref().map(|g| (g.head.clone(), g.dirty.clone()));
+    let candidates = git.map(|g| g.paths);
 
     // Shared collector for post-pipeline phases
     let collected: Arc<Mutex<Vec<ParsedFileMetadata>>> = Arc::new(Mutex::new(Vec::new()));
@@ -143,7 +163,8 @@ pub async fn run_pipeline(
         scanner_stage(
             root_owned,
             ignores_owned,
-            existing_hashes,
+            existing,
+            candidates,
             scan_tx,
             prog_scanner,
             cancel_scanner,
@@ -152,14 +173,16 @@ pub async fn run_pipeline(
     });
 
     // Parser workers — each gets a clone of the shared receiver
+    let domain_config = Arc::new(load_config(&root.join(".gofer")).domain_config());
     let mut h_parsers: Vec<JoinHandle<anyhow::Result<()>>> = Vec::with_capacity(num_workers);
     for _ in 0..num_workers {
         let rx = scan_rx.clone();
         let tx = parse_tx.clone();
         let prog = progress.clone();
         let cancel_parser = cancel.clone();
+        let domain_config = domain_config.clone();
         h_parsers.push(tokio::spawn(async move {
-            parser_worker(rx, tx, prog, cancel_parser).await
+            parser_worker(rx, tx, domain_config, prog, cancel_parser).await
         }));
     }
     drop(parse_tx); // Only worker clones hold senders now
@@ -193,12 +216,14 @@ pub async fn run_pipeline(
     let prog_writer = progress.clone();
     let lance_compact = lance.clone();
     let cancel_writer = cancel.clone();
+    let resolver = ImportResolver::new(root);
     let h_writer: JoinHandle<anyhow::Result<()>> = tokio::spawn(async move {
         if let Err(ref e) = writer_stage(
             sqlite_clone,
             lance,
             embed_rx,
             collected_clone,
+            resolver,
             prog_writer,
             cancel_writer,
         )
@@ -236,6 +261,12 @@ pub async fn run_pipeline(
 
     tracing::info!("Pipeline complete: {} files processed", metadata.len());
 
+    if !cancel.is_cancelled() {
+        record_synced_state(root, extra_ignores, &sqlite, recorded_state).await;
+        backfill_import_graph(root, &sqlite, fresh_index).await;
+        backfill_chunk_text(&sqlite, &lance_compact, fresh_index).await;
+    }
+
     // Post-pipeline: compact LanceDB fragments to prevent read amplification
     if !metadata.is_empty() {
         let lance_guard = lance_compact.lock().await;
@@ -264,6 +295,186 @@ pub async fn run_pipeline(
     Ok(metadata)
 }
 
+/// Candidate files from git for an incremental startup sync. `None` means scan
+/// the whole tree: not a git repository, no previous sync recorded, or the
+/// ignore patterns or gofer version changed since.
+async fn git_candidates(
+    root: &Path,
+    extra_ignores: &[String],
+    sqlite: &SqliteStorage,
+    existing: &HashMap<String, FileStat>,
+) -> Option<GitCandidates> {
+    let scope = sqlite
+        .get_index_meta(reconcile::SYNCED_SCOPE_KEY)
+        .await
+        .ok()??;
+    if scope != reconcile::scope_fingerprint(extra_ignores) {
+        return None;
+    }
+    let head = sqlite
+        .get_index_meta(reconcile::SYNCED_HEAD_KEY)
+        .await
+        .ok()??;
+    let dirty: Vec<String> = sqlite
+        .get_index_meta(reconcile::SYNCED_DIRTY_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default();
+
+    let root_owned = root.to_path_buf();
+    let mut found = tokio::task::spawn_blocking(move || {
+        reconcile::git_candidates(&root_owned, Some(&head), &dirty)
+    })
+    .await
+    .ok()??;
+
+    // Rows a migration or force_reindex marked for re-parsing
+    found.paths.extend(
+        existing
+            .iter()
+            .filter(|(_, stat)| stat.hash.is_empty())
+            .map(|(path, _)| root.join(path)),
+    );
+    found.paths.sort();
+    found.paths.dedup();
+    tracing::info!(
+        "Scanner: {} candidate files from git since {}",
+        found.paths.len(),
+        found.head
+    );
+    Some(found)
+}
+
+/// Remember the commit and dirty files this sync covered, for the next startup.
+async fn record_synced_state(
+    root: &Path,
+    extra_ignores: &[String],
+    sqlite: &SqliteStorage,
+    state: Option<(String, Vec<String>)>,
+) {
+    let state = match state {
+        Some(state) => Some(state),
+        None => {
+            let root_owned = root.to_path_buf();
+            tokio::task::spawn_blocking(move || reconcile::git_state(&root_owned))
+                .await
+                .ok()
+                .flatten()
+        }
+    };
+    let Some((head, dirty)) = state else {
+        return;
+    };
+    let dirty = serde_json::to_string(&dirty).unwrap_or_else(|_| "[]".to_string());
+    for (key, value) in [
+        (reconcile::SYNCED_HEAD_KEY, head),
+        (reconcile::SYNCED_DIRTY_KEY, dirty),
+        (
+            reconcile::SYNCED_SCOPE_KEY,
+            reconcile::scope_fingerprint(extra_ignores),
+        ),
+    ] {
+        if let Err(e) = sqlite.set_index_meta(key, &value).await {
+            tracing::warn!("Failed to record synced git state: {}", e);
+        }
+    }
+}
+
+/// Resolve the import graph of files indexed before `file_imports` existed.
+/// A sync skips unchanged files, so without this their edges would stay
+/// missing; it runs once per index, and a fresh index is complete already.
+async fn backfill_import_graph(root: &Path, sqlite: &SqliteStorage, fresh_index: bool) {
+    if !fresh_index {
+        if let Ok(Some(_)) = sqlite.get_index_meta(IMPORT_GRAPH_KEY).await {
+            return;
+        }
+        let paths: Vec<String> = match sqlite.get_file_stats().await {
+            Ok(stats) => stats.into_keys().collect(),
+            Err(e) => {
+                tracing::warn!("Import graph backfill skipped: {}", e);
+                return;
+            }
+        };
+        let root_owned = root.to_path_buf();
+        let graph = tokio::task::spawn_blocking(move || {
+            let resolver = ImportResolver::new(&root_owned);
+            let mut parser = CodeParser::new();
+            paths
+                .into_iter()
+                .filter_map(|path| {
+                    let Some(SourceKind::Code(language)) = SourceKind::from_path(Path::new(&path))
+                    else {
+                        return None;
+                    };
+                    let content = std::fs::read_to_string(root_owned.join(&path)).ok()?;
+                    let imports = parser.parse_imports(&content, language);
+                    let edges = resolver.edges(&path, &imports, language);
+                    Some((path, edges))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+
+        for (path, edges) in &graph {
+            let Ok(Some(file)) = sqlite.get_file(path).await else {
+                continue;
+            };
+            if let Err(e) = sqlite.replace_file_imports(file.id, edges).await {
+                tracing::warn!("Import graph backfill failed for {}: {}", path, e);
+                return;
+            }
+        }
+        tracing::info!("Import graph: resolved imports of {} files", graph.len());
+    }
+    if let Err(e) = sqlite.set_index_meta(IMPORT_GRAPH_KEY, "1").await {
+        tracing::warn!("Failed to record import graph backfill: {}", e);
+    }
+}
+
+/// Fill `chunk_text` from the LanceDB chunks of an index built before it
+/// existed, so upgrading needs no reparse or re-embedding. Runs once per index.
+async fn backfill_chunk_text(
+    sqlite: &SqliteStorage,
+    lance: &Arc<Mutex<LanceStorage>>,
+    fresh_index: bool,
+) {
+    if !fresh_index {
+        if let Ok(Some(_)) = sqlite.get_index_meta(CHUNK_TEXT_KEY).await {
+            return;
+        }
+        let chunks = match lance.lock().await.chunks().await {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                tracing::warn!("Chunk text backfill skipped: {}", e);
+                return;
+            }
+        };
+        let mut by_file: HashMap<String, Vec<CodeChunk>> = HashMap::new();
+        for chunk in chunks {
+            by_file
+                .entry(chunk.file_path.clone())
+                .or_default()
+                .push(chunk);
+        }
+        for (path, file_chunks) in &by_file {
+            if let Err(e) = sqlite
+                .replace_chunk_text(&[path.as_str()], file_chunks)
+                .await
+            {
+                tracing::warn!("Chunk text backfill failed for {}: {}", path, e);
+                return;
+            }
+        }
+        tracing::info!("Chunk text: indexed {} files", by_file.len());
+    }
+    if let Err(e) = sqlite.set_index_meta(CHUNK_TEXT_KEY, "1").await {
+        tracing::warn!("Failed to record chunk text backfill: {}", e);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Stage 1: Scanner — I/O bound file discovery
 // ---------------------------------------------------------------------------
@@ -271,12 +482,16 @@ pub async fn run_pipeline(
 async fn scanner_stage(
     root: PathBuf,
     extra_ignores: Vec<String>,
-    existing_hashes: HashMap<String, (String, i64)>,
+    existing: HashMap<String, FileStat>,
+    candidates: Option<Vec<PathBuf>>,
     tx: mpsc::Sender<ScannedFile>,
     progress: Option<Arc<SyncProgress>>,
     cancel: CancellationToken,
 ) -> anyhow::Result<usize> {
-    let files = scan_directory(&root, &extra_ignores);
+    let files = match candidates {
+        Some(paths) => filter_scannable(&root, &extra_ignores, paths),
+        None => scan_directory(&root, &extra_ignores),
+    };
     let total = files.len();
     tracing::info!("Scanner: found {} files", total);
 
@@ -296,11 +511,11 @@ async fn scanner_stage(
             break;
         }
 
-        let path_str = path.to_string_lossy().to_string();
+        // Index keys are relative to the project root so the index survives a move
+        let path_str = relative_key(&root, &path);
 
         // Detect language early to skip unsupported files
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        let Some(language) = SupportedLanguage::from_extension(ext) else {
+        let Some(kind) = SourceKind::from_path(&path) else {
             skipped_unsupported += 1;
             continue;
         };
@@ -334,9 +549,12 @@ async fn scanner_stage(
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0);
 
-        // Fast path: skip if mtime unchanged (avoids file read + hash)
-        if let Some((_, stored_mtime)) = existing_hashes.get(&path_str) {
-            if *stored_mtime == modified {
+        // Fast path: skip if mtime and size unchanged (avoids file read + hash).
+        // Rows written before sizes were stored (-1) compare on mtime alone.
+        if let Some(stored) = existing.get(&path_str) {
+            if stored.modified == modified
+                && (stored.size < 0 || stored.size == metadata.len() as i64)
+            {
                 skipped_unchanged += 1;
                 // Update progress for skipped files too
                 if let Some(ref p) = progress {
@@ -360,8 +578,8 @@ async fn scanner_stage(
         let hash = blake3::hash(content.as_bytes()).to_hex().to_string();
 
         // Skip unchanged (content hash confirms — handles rare mtime-only changes)
-        if let Some((existing_hash, _)) = existing_hashes.get(&path_str) {
-            if existing_hash == &hash {
+        if let Some(stored) = existing.get(&path_str) {
+            if stored.hash == hash {
                 skipped_unchanged += 1;
                 // Update progress for skipped files too
                 if let Some(ref p) = progress {
@@ -377,7 +595,7 @@ async fn scanner_stage(
                 content: Arc::new(content),
                 hash,
                 modified,
-                language,
+                kind,
             })
             .await
             .is_err()
@@ -408,6 +626,14 @@ async fn scanner_stage(
     Ok(sent)
 }
 
+/// Path of `path` relative to `root`, as stored in `files.path` and Lance.
+pub(crate) fn relative_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
 // ---------------------------------------------------------------------------
 // Stage 2: Parser workers — CPU-bound parsing via spawn_blocking
 // ---------------------------------------------------------------------------
@@ -415,6 +641,7 @@ async fn scanner_stage(
 async fn parser_worker(
     rx: Arc<Mutex<mpsc::Receiver<ScannedFile>>>,
     tx: mpsc::Sender<ParsedDoc>,
+    domain_config: Arc<DomainConfig>,
     progress: Option<Arc<SyncProgress>>,
     cancel: CancellationToken,
 ) -> anyhow::Result<()> {
@@ -435,32 +662,45 @@ async fn parser_worker(
         };
 
         // Offload CPU-heavy parsing to blocking thread pool
+        let domain_config = domain_config.clone();
         let parsed = match tokio::task::spawn_blocking(move || -> anyhow::Result<ParsedDoc> {
             let mut parser = CodeParser::new();
             let content_ref = &*scanned.content;
 
             // Single-pass parse: symbols + chunks + refs + imports from one tree
-            let parsed_file = match parser.parse_file(content_ref, &scanned.path, scanned.language)
-            {
-                Ok(pf) => pf,
-                Err(e) => {
-                    tracing::warn!("Parser: failed to parse {}: {}", scanned.path, e);
-                    Default::default()
+            let parsed_file = match scanned.kind {
+                SourceKind::Code(language) => {
+                    match parser.parse_file(content_ref, &scanned.path, language) {
+                        Ok(pf) => pf,
+                        Err(e) => {
+                            tracing::warn!("Parser: failed to parse {}: {}", scanned.path, e);
+                            Default::default()
+                        }
+                    }
                 }
+                SourceKind::Document(kind) => parse_document(content_ref, &scanned.path, kind),
             };
 
-            let domain_config = DomainConfig::default_config();
+            let column_usages = scanned
+                .kind
+                .language()
+                .map(|language| extract_column_usages(content_ref, language))
+                .unwrap_or_default();
+            let api = extract_api(&scanned.path, content_ref, scanned.kind);
+
             let (domain, tech_stack) = detect_domain(&scanned.path, content_ref, &domain_config);
 
             Ok(ParsedDoc {
                 path: scanned.path,
                 hash: scanned.hash,
                 modified: scanned.modified,
-                language: scanned.language,
+                kind: scanned.kind,
                 symbols: parsed_file.symbols,
                 chunks: parsed_file.chunks,
                 refs: parsed_file.refs,
                 imports: parsed_file.imports,
+                column_usages,
+                api,
                 domain: SmolStr::from(domain.as_str()),
                 tech_stack: tech_stack.into_iter().map(SmolStr::from).collect(),
                 content: scanned.content,
@@ -556,10 +796,12 @@ async fn batcher_stage(
                             path: doc.path.clone(),
                             hash: doc.hash,
                             modified: doc.modified,
-                            language: doc.language,
+                            kind: doc.kind,
                             symbols: doc.symbols,
                             refs: doc.refs,
                             imports: doc.imports,
+                            column_usages: doc.column_usages,
+                            api: doc.api,
                             domain: doc.domain,
                             tech_stack: doc.tech_stack,
                             content: doc.content,
@@ -866,6 +1108,7 @@ async fn writer_stage(
     lance: Arc<Mutex<LanceStorage>>,
     mut rx: mpsc::Receiver<EmbeddedBatch>,
     collected: Arc<Mutex<Vec<ParsedFileMetadata>>>,
+    resolver: ImportResolver,
     progress: Option<Arc<SyncProgress>>,
     cancel: tokio_util::sync::CancellationToken,
 ) -> anyhow::Result<()> {
@@ -911,6 +1154,15 @@ async fn writer_stage(
             total_chunks += batch.chunks.len();
         }
 
+        // Keep the chunk full-text index in step with LanceDB (a file's chunks and
+        // metadata always travel in the same batch)
+        if !batch.metadata.is_empty() {
+            let paths: Vec<&str> = batch.metadata.iter().map(|m| m.path.as_str()).collect();
+            if let Err(e) = sqlite.replace_chunk_text(&paths, &batch.chunks).await {
+                tracing::error!("Writer: chunk FTS error: {}", e);
+            }
+        }
+
         // 0. Update progress eagerly!
         let metadata_count = batch.metadata.len();
         if metadata_count > 0 {
@@ -927,7 +1179,7 @@ async fn writer_stage(
         if pending_metadata.len() >= SQLITE_FLUSH_SIZE {
             let count = pending_metadata.len();
             tracing::info!("Writer: flushing {} metadata entries to SQLite", count);
-            flush_sqlite_batch(&sqlite, &mut pending_metadata, &collected).await;
+            flush_sqlite_batch(&sqlite, &mut pending_metadata, &collected, &resolver).await;
             tracing::info!("Writer: {} files written so far", total_files);
         }
     }
@@ -938,7 +1190,7 @@ async fn writer_stage(
     if !pending_metadata.is_empty() {
         let count = pending_metadata.len();
         tracing::info!("Writer: final flush of {} metadata entries", count);
-        flush_sqlite_batch(&sqlite, &mut pending_metadata, &collected).await;
+        flush_sqlite_batch(&sqlite, &mut pending_metadata, &collected, &resolver).await;
     }
 
     // Resolve cross-file references
@@ -964,6 +1216,7 @@ async fn flush_sqlite_batch(
     sqlite: &SqliteStorage,
     pending: &mut Vec<ParsedFileMetadata>,
     collected: &Arc<Mutex<Vec<ParsedFileMetadata>>>,
+    resolver: &ImportResolver,
 ) {
     let batch: Vec<ParsedFileMetadata> = std::mem::take(pending);
 
@@ -987,17 +1240,19 @@ async fn flush_sqlite_batch(
         // 1. Upsert file record
         let file_id_result = sqlx::query_scalar::<_, i64>(
             r#"
-            INSERT INTO files (path, last_modified, content_hash)
-            VALUES (?, ?, ?)
+            INSERT INTO files (path, last_modified, content_hash, size)
+            VALUES (?, ?, ?, ?)
             ON CONFLICT(path) DO UPDATE SET
                 last_modified = excluded.last_modified,
-                content_hash = excluded.content_hash
+                content_hash = excluded.content_hash,
+                size = excluded.size
             RETURNING id
             "#,
         )
         .bind(&file_meta.path)
         .bind(file_meta.modified)
         .bind(&file_meta.hash)
+        .bind(file_meta.content.len() as i64)
         .fetch_one(&mut *tx)
         .await;
 
@@ -1011,14 +1266,7 @@ async fn flush_sqlite_batch(
 
         // 2. Update domain, language, and indexing status
         let tech_json = serde_json::to_string(&file_meta.tech_stack).unwrap_or_default();
-        let language_str = match file_meta.language {
-            SupportedLanguage::Rust => "rust",
-            SupportedLanguage::TypeScript => "typescript",
-            SupportedLanguage::JavaScript => "javascript",
-            SupportedLanguage::Python => "python",
-            SupportedLanguage::Go => "go",
-            SupportedLanguage::Vue => "vue",
-        };
+        let language_str = file_meta.kind.as_str();
         let now = chrono::Utc::now().timestamp();
 
         let _ = sqlx::query(
@@ -1060,42 +1308,44 @@ async fn flush_sqlite_batch(
             .execute(&mut *tx)
             .await;
 
-        let ecosystem = match file_meta.language {
-            SupportedLanguage::Rust => "cargo",
-            SupportedLanguage::TypeScript
-            | SupportedLanguage::JavaScript
-            | SupportedLanguage::Vue => "npm",
-            SupportedLanguage::Python => "pip",
-            SupportedLanguage::Go => "go",
-        };
-
-        for import in &file_meta.imports {
-            if !import.is_relative {
-                let pkg_name = extract_package_name(&import.path, file_meta.language);
-                let items_json = if !import.items.is_empty() {
-                    Some(serde_json::to_string(&import.items).unwrap_or_default())
-                } else {
-                    None
-                };
-                let usage_type = match file_meta.language {
-                    SupportedLanguage::Rust => "use",
-                    _ => "import",
-                };
+        // Documents (Markdown, config, SQL) have no imports
+        if let Some(language) = file_meta.kind.language() {
+            let ecosystem = match language {
+                SupportedLanguage::Rust => "cargo",
+                SupportedLanguage::TypeScript
+                | SupportedLanguage::JavaScript
+                | SupportedLanguage::Vue => "npm",
+                SupportedLanguage::Python => "pip",
+                SupportedLanguage::Go => "go",
+            };
 
-                // Find or create dependency
-                let dep_id: Option<i64> = sqlx::query_scalar(
-                    "SELECT id FROM dependencies WHERE name = ? AND ecosystem = ?",
-                )
-                .bind(&pkg_name)
-                .bind(ecosystem)
-                .fetch_optional(&mut *tx)
-                .await
-                .unwrap_or(None);
+            for import in &file_meta.imports {
+                if !import.is_relative {
+                    let pkg_name = extract_package_name(&import.path, language);
+                    let items_json = if !import.items.is_empty() {
+                        Some(serde_json::to_string(&import.items).unwrap_or_default())
+                    } else {
+                        None
+                    };
+                    let usage_type = match language {
+                        SupportedLanguage::Rust => "use",
+                        _ => "import",
+                    };
 
-                let dep_id = match dep_id {
-                    Some(id) => id,
-                    None => {
-                        let _ = sqlx::query(
+                    // Find or create dependency
+                    let dep_id: Option<i64> = sqlx::query_scalar(
+                        "SELECT id FROM dependencies WHERE name = ? AND ecosystem = ?",
+                    )
+                    .bind(&pkg_name)
+                    .bind(ecosystem)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .unwrap_or(None);
+
+                    let dep_id = match dep_id {
+                        Some(id) => id,
+                        None => {
+                            let _ = sqlx::query(
                             "INSERT OR IGNORE INTO dependencies (name, version, ecosystem, updated_at) VALUES (?, '?', ?, ?)",
                         )
                         .bind(&pkg_name)
@@ -1104,21 +1354,21 @@ async fn flush_sqlite_batch(
                         .execute(&mut *tx)
                         .await;
 
-                        sqlx::query_scalar(
-                            "SELECT id FROM dependencies WHERE name = ? AND ecosystem = ?",
-                        )
-                        .bind(&pkg_name)
-                        .bind(ecosystem)
-                        .fetch_optional(&mut *tx)
-                        .await
-                        .ok()
-                        .flatten()
-                        .unwrap_or(0)
-                    }
-                };
+                            sqlx::query_scalar(
+                                "SELECT id FROM dependencies WHERE name = ? AND ecosystem = ?",
+                            )
+                            .bind(&pkg_name)
+                            .bind(ecosystem)
+                            .fetch_optional(&mut *tx)
+                            .await
+                            .ok()
+                            .flatten()
+                            .unwrap_or(0)
+                        }
+                    };
 
-                if dep_id > 0 {
-                    let _ = sqlx::query(
+                    if dep_id > 0 {
+                        let _ = sqlx::query(
                         r#"
                         INSERT INTO dependency_usage (dependency_id, file_id, line, usage_type, import_path, items)
                         VALUES (?, ?, ?, ?, ?, ?)
@@ -1136,11 +1386,111 @@ async fn flush_sqlite_batch(
                     .bind(items_json.as_deref())
                     .execute(&mut *tx)
                     .await;
+                    }
                 }
             }
         }
 
-        // 5. Insert references per symbol
+        // 5. Replace the file's edges in the import graph
+        let _ = sqlx::query("DELETE FROM file_imports WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await;
+        if let Some(language) = file_meta.kind.language() {
+            let edges = resolver.edges(&file_meta.path, &file_meta.imports, language);
+            for chunk in edges.chunks(100) {
+                let mut builder = sqlx::QueryBuilder::new(
+                    "INSERT INTO file_imports (file_id, target_path, line, specifier) ",
+                );
+                builder.push_values(chunk, |mut b, e| {
+                    b.push_bind(file_id)
+                        .push_bind(&e.to_path)
+                        .push_bind(e.line)
+                        .push_bind(&e.specifier);
+                });
+                let _ = builder.build().execute(&mut *tx).await;
+            }
+        }
+
+        // 6. Replace table/column usages
+        let _ = sqlx::query("DELETE FROM column_usages WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await;
+        for chunk in file_meta.column_usages.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO column_usages (file_id, table_name, column_name, line, kind, ambiguous, context) ",
+            );
+            builder.push_values(chunk, |mut b, u| {
+                b.push_bind(file_id)
+                    .push_bind(table_key(&u.table))
+                    .push_bind(&u.column)
+                    .push_bind(u.line as i64)
+                    .push_bind(u.kind)
+                    .push_bind(u.ambiguous)
+                    .push_bind(&u.context);
+            });
+            let _ = builder.build().execute(&mut *tx).await;
+        }
+
+        // 7. Replace API endpoints, calls and client bases
+        let _ = sqlx::query("DELETE FROM api_endpoints WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await;
+        for chunk in file_meta.api.endpoints.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO api_endpoints (method, path, source, handler, request_type, response_type, file_id, line) ",
+            );
+            builder.push_values(chunk, |mut b, e| {
+                b.push_bind(&e.method)
+                    .push_bind(&e.path)
+                    .push_bind(file_meta.api.source)
+                    .push_bind(&e.handler)
+                    .push_bind(&e.request_type)
+                    .push_bind(&e.response_type)
+                    .push_bind(file_id)
+                    .push_bind(e.line as i32);
+            });
+            let _ = builder.build().execute(&mut *tx).await;
+        }
+        let _ = sqlx::query("DELETE FROM frontend_api_calls WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await;
+        for chunk in file_meta.api.calls.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO frontend_api_calls (method, path, path_pattern, file_id, line, client, client_source, needs_client) ",
+            );
+            builder.push_values(chunk, |mut b, c| {
+                b.push_bind(&c.method)
+                    .push_bind(&c.path)
+                    .push_bind(&c.path_pattern)
+                    .push_bind(file_id)
+                    .push_bind(c.line as i32)
+                    .push_bind(&c.client)
+                    .push_bind(&c.client_source)
+                    .push_bind(c.needs_client);
+            });
+            let _ = builder.build().execute(&mut *tx).await;
+        }
+        let _ = sqlx::query("DELETE FROM api_bases WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await;
+        for chunk in file_meta.api.bases.chunks(100) {
+            let mut builder =
+                sqlx::QueryBuilder::new("INSERT INTO api_bases (name, base_url, file_id, line) ");
+            builder.push_values(chunk, |mut b, base| {
+                b.push_bind(&base.name)
+                    .push_bind(&base.base_url)
+                    .push_bind(file_id)
+                    .push_bind(base.line as i32);
+            });
+            let _ = builder.build().execute(&mut *tx).await;
+        }
+
+        // 8. Insert references per symbol
         // Get the symbols we just inserted to get their IDs
         let stored_symbols: Vec<Symbol> = sqlx::query_as::<_, Symbol>(
             "SELECT id, file_id, name, kind, line_start, line_end, signature FROM symbols WHERE file_id = ?",
@@ -1192,10 +1542,12 @@ async fn flush_sqlite_batch(
                 path: file_meta.path,
                 hash: file_meta.hash,
                 modified: file_meta.modified,
-                language: file_meta.language,
+                kind: file_meta.kind,
                 symbols: file_meta.symbols,
                 refs: file_meta.refs,
                 imports: file_meta.imports,
+                column_usages: file_meta.column_usages,
+                api: file_meta.api,
                 domain: file_meta.domain,
                 tech_stack: file_meta.tech_stack,
                 content: file_meta.content,
@@ -1267,3 +1619,102 @@ pub(crate) fn extract_package_name(import_path: &str, language: SupportedLanguag
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backfill_import_graph() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("a.ts"), "import { b } from './b';\n").unwrap();
+        std::fs::write(root.path().join("b.ts"), "export const b = 1;\n").unwrap();
+        let index = tempfile::tempdir().unwrap();
+        let sqlite = SqliteStorage::new(index.path().join("graph.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.migrate().await.unwrap();
+
+        // Indexed before file_imports existed
+        for path in ["a.ts", "b.ts"] {
+            sqlite.upsert_file(path, 1, "hash").await.unwrap();
+        }
+        backfill_import_graph(root.path(), &sqlite, false).await;
+        let edges = sqlite
+            .get_import_edges(&["a.ts".to_string()], false)
+            .await
+            .unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!((edges[0].to_path.as_str(), edges[0].line), ("b.ts", 0));
+        assert!(sqlite
+            .get_index_meta(IMPORT_GRAPH_KEY)
+            .await
+            .unwrap()
+            .is_some());
+
+        // Runs once: later edges come from the pipeline and the watcher
+        let a = sqlite.get_file("a.ts").await.unwrap().unwrap();
+        sqlite.replace_file_imports(a.id, &[]).await.unwrap();
+        backfill_import_graph(root.path(), &sqlite, false).await;
+        assert!(sqlite
+            .get_import_edges(&["a.ts".to_string()], false)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_chunk_text() {
+        let index = tempfile::tempdir().unwrap();
+        let sqlite = SqliteStorage::new(index.path().join("graph.db").to_str().unwrap())
+            .await
+            .unwrap();
+        sqlite.migrate().await.unwrap();
+        let mut lance = LanceStorage::new(index.path().join("lancedb").to_str().unwrap(), 8)
+            .await
+            .unwrap();
+
+        // Chunks embedded before chunks_fts existed
+        let chunk = CodeChunk {
+            id: "src/cfg.rs:1:3".to_string(),
+            file_path: "src/cfg.rs".to_string(),
+            content: "fn load() { parseConfigFile(p) }".to_string(),
+            line_start: 1,
+            line_end: 3,
+            symbol_name: None,
+            symbol_kind: None,
+            symbol_path: None,
+            scopes: Vec::new(),
+        };
+        lance
+            .upsert_chunks(&[chunk], &[vec![0.5; 8]])
+            .await
+            .unwrap();
+        let lance = Arc::new(Mutex::new(lance));
+
+        backfill_chunk_text(&sqlite, &lance, false).await;
+        let hits = sqlite
+            .search_chunk_text("config file", 10, None)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_path, "src/cfg.rs");
+        assert!(sqlite
+            .get_index_meta(CHUNK_TEXT_KEY)
+            .await
+            .unwrap()
+            .is_some());
+
+        // Runs once: later rows come from the pipeline
+        sqlite
+            .replace_chunk_text(&["src/cfg.rs"], &[])
+            .await
+            .unwrap();
+        backfill_chunk_text(&sqlite, &lance, false).await;
+        assert!(sqlite
+            .search_chunk_text("config file", 10, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
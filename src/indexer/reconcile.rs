@@ -0,0 +1,228 @@
+//! Startup reconciliation against git.
+//!
+//! Activating a project used to stat and hash every file to find what changed
+//! while the daemon was down. In a git repository the answer is mostly known:
+//! files that differ between the commit indexed last time and `HEAD`, plus
+//! whatever `git status` reports as modified or untracked now or at the last
+//! sync. Only those candidates go through the mtime/size/hash check; projects
+//! outside git, or whose last synced commit is gone, fall back to a full scan.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use git2::{Oid, Repository, StatusOptions};
+
+use super::parser::DocumentKind;
+
+/// `index_metadata` key: `HEAD` commit at the end of the last full sync
+pub const SYNCED_HEAD_KEY: &str = "synced_head";
+/// `index_metadata` key: JSON list of root-relative paths that were dirty then
+pub const SYNCED_DIRTY_KEY: &str = "synced_dirty";
+/// `index_metadata` key: what decided which files are indexed (gofer version,
+/// document types and ignore patterns). A change in any needs a full scan.
+pub const SYNCED_SCOPE_KEY: &str = "synced_scope";
+
+/// Value stored under `SYNCED_SCOPE_KEY`.
+pub fn scope_fingerprint(extra_ignores: &[String]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    for kind in DocumentKind::ALL {
+        hasher.update(kind.as_str().as_bytes());
+    }
+    for pattern in extra_ignores {
+        hasher.update(b"\n");
+        hasher.update(pattern.as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Files that may have changed since the last sync.
+#[derive(Debug, Default)]
+pub struct GitCandidates {
+    /// Current `HEAD`, to store once the sync completes
+    pub head: String,
+    /// Root-relative paths `git status` reports as dirty right now
+    pub dirty: Vec<String>,
+    /// Absolute paths to check
+    pub paths: Vec<PathBuf>,
+}
+
+/// The repository's `HEAD` and dirty paths, for recording after a full scan.
+/// `None` outside git, for bare repositories and before the first commit.
+pub fn git_state(root: &Path) -> Option<(String, Vec<String>)> {
+    let (repo, prefix) = open(root)?;
+    let head = repo.head().ok()?.target()?;
+    let dirty = status_paths(&repo)?
+        .into_iter()
+        .filter_map(|p| relative(&prefix, &p))
+        .collect();
+    Some((head.to_string(), dirty))
+}
+
+/// Candidate files for an incremental startup sync, or `None` when a full scan
+/// is needed.
+pub fn git_candidates(
+    root: &Path,
+    synced_head: Option<&str>,
+    synced_dirty: &[String],
+) -> Option<GitCandidates> {
+    let (repo, prefix) = open(root)?;
+    let head = repo.head().ok()?.target()?;
+    let synced = Oid::from_str(synced_head?).ok()?;
+    let old_tree = repo.find_commit(synced).ok()?.tree().ok()?;
+    let new_tree = repo.find_commit(head).ok()?.tree().ok()?;
+
+    let mut changed: BTreeSet<String> = BTreeSet::new();
+    let diff = repo
+        .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+        .ok()?;
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path().and_then(|p| p.to_str()) {
+                changed.insert(path.to_string());
+            }
+        }
+    }
+
+    let dirty: Vec<String> = status_paths(&repo)?
+        .into_iter()
+        .filter_map(|p| relative(&prefix, &p))
+        .collect();
+
+    let mut paths: BTreeSet<PathBuf> = changed
+        .iter()
+        .filter_map(|p| relative(&prefix, p))
+        .map(|rel| root.join(rel))
+        .collect();
+    // Dirty then (maybe reverted since) and dirty now
+    paths.extend(synced_dirty.iter().chain(&dirty).map(|rel| root.join(rel)));
+
+    Some(GitCandidates {
+        head: head.to_string(),
+        dirty,
+        paths: paths.into_iter().collect(),
+    })
+}
+
+/// Repository containing `root` and the root's path relative to its workdir
+/// (empty when the project is the repository root).
+fn open(root: &Path) -> Option<(Repository, String)> {
+    let repo = Repository::discover(root).ok()?;
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+    let prefix = root.strip_prefix(&workdir).ok()?;
+    let prefix = prefix.to_str()?.replace('\\', "/");
+    Some((repo, prefix))
+}
+
+/// Workdir-relative paths of modified, deleted, renamed and untracked files.
+fn status_paths(repo: &Repository) -> Option<Vec<String>> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false)
+        .exclude_submodules(true)
+        .renames_head_to_index(true);
+    let statuses = repo.statuses(Some(&mut opts)).ok()?;
+
+    let mut paths = BTreeSet::new();
+    for entry in statuses.iter() {
+        for delta in [entry.head_to_index(), entry.index_to_workdir()]
+            .into_iter()
+            .flatten()
+        {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path().and_then(|p| p.to_str()) {
+                    paths.insert(path.to_string());
+                }
+            }
+        }
+        if let Some(path) = entry.path() {
+            paths.insert(path.to_string());
+        }
+    }
+    Some(paths.into_iter().collect())
+}
+
+/// Workdir-relative path to root-relative, dropping files outside the project.
+fn relative(prefix: &str, path: &str) -> Option<String> {
+    if prefix.is_empty() {
+        return Some(path.to_string());
+    }
+    path.strip_prefix(prefix)?
+        .strip_prefix('/')
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_all(repo: &Repository, message: &str) -> Oid {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("test", "test@example.com").unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_candidates_cover_commits_and_worktree_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+        for name in ["a.rs", "b.rs", "c.rs", "d.rs"] {
+            std::fs::write(root.join(name), "fn f() {}").unwrap();
+        }
+        let synced = commit_all(&repo, "first").to_string();
+
+        std::fs::write(root.join("a.rs"), "fn a() {}").unwrap();
+        commit_all(&repo, "second");
+        std::fs::write(root.join("b.rs"), "fn b() {}").unwrap();
+        std::fs::write(root.join("e.rs"), "fn e() {}").unwrap();
+
+        let previously_dirty = vec!["c.rs".to_string()];
+        let found = git_candidates(&root, Some(&synced), &previously_dirty).unwrap();
+        let names: Vec<_> = found
+            .paths
+            .iter()
+            .map(|p| p.strip_prefix(&root).unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a.rs", "b.rs", "c.rs", "e.rs"]);
+        assert_eq!(found.dirty, vec!["b.rs", "e.rs"]);
+
+        // Unknown last sync: full scan
+        assert!(git_candidates(&root, None, &[]).is_none());
+        assert!(git_candidates(&root, Some("0123456789abcdef"), &[]).is_none());
+    }
+
+    #[test]
+    fn test_subdirectory_project_paths_are_relative_to_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let top = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&top).unwrap();
+        std::fs::create_dir(top.join("app")).unwrap();
+        std::fs::write(top.join("app/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(top.join("other.rs"), "fn o() {}").unwrap();
+        let synced = commit_all(&repo, "first").to_string();
+        std::fs::write(top.join("app/main.rs"), "fn main() { }").unwrap();
+        std::fs::write(top.join("other.rs"), "fn o2() {}").unwrap();
+
+        let root = top.join("app");
+        let found = git_candidates(&root, Some(&synced), &[]).unwrap();
+        assert_eq!(found.paths, vec![root.join("main.rs")]);
+        assert_eq!(found.dirty, vec!["main.rs"]);
+    }
+}
@@ -0,0 +1,1584 @@
+//! Routes registered through router builders, handler attributes and the file
+//! system: actix-web, rocket, warp, poem and nested axum routers in Rust;
+//! net/http, chi, gin, echo and gorilla/mux in Go; Django `urls.py`; Next.js
+//! and Nuxt API routes.
+//!
+//! Registrations are found with tree-sitter queries. Their prefix is resolved
+//! by walking up the tree through the `scope`/`nest`/`Group`/`Route`/`include`
+//! calls around them, and through the call sites of the helper function that
+//! builds the router, so multi-line builders and nested routers yield the full
+//! path. Handlers are stored by name for linking to their symbols.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Parser, Query, QueryCursor};
+
+use super::domains::ParsedEndpoint;
+use super::parser::SupportedLanguage;
+
+pub(super) const HTTP_METHODS: &[&str] =
+    &["get", "post", "put", "delete", "patch", "head", "options"];
+
+/// Method of a route that accepts every method, as for Express `app.all`
+const ANY: &str = "ALL";
+
+/// How many helper functions or variables are followed to find where a router
+/// is mounted
+const MAX_DEPTH: usize = 6;
+
+const RUST_ROUTES_QUERY: &str = r#"
+; Router builders: .route("/x", get(h)), poem .at("/x", get(h)),
+; actix .route("/x", web::get().to(h)) and web::resource("/x").route(web::get().to(h))
+(call_expression
+  function: (field_expression field: (field_identifier) @name)
+  (#any-of? @name "route" "at")) @builder
+
+; Handler attributes: actix/rocket #[get("/x")], actix #[route("/x", method = "GET")]
+(attribute_item
+  (attribute
+    [(identifier) @name (scoped_identifier name: (identifier) @name)]
+    arguments: (token_tree))
+  (#any-of? @name "get" "post" "put" "delete" "patch" "head" "options" "route")) @attribute
+
+; warp filter chains ending in a handler
+(call_expression
+  function: (field_expression field: (field_identifier) @name)
+  (#any-of? @name "and_then" "map" "then")) @filter
+"#;
+
+const GO_ROUTES_QUERY: &str = r#"
+; net/http, chi, gin, echo and gorilla/mux registrations
+(call_expression
+  function: (selector_expression field: (field_identifier) @name)
+  (#any-of? @name
+    "Get" "Post" "Put" "Delete" "Patch" "Head" "Options"
+    "GET" "POST" "PUT" "DELETE" "PATCH" "HEAD" "OPTIONS"
+    "Any" "Handle" "HandleFunc" "Method" "MethodFunc")) @route
+"#;
+
+const DJANGO_ROUTES_QUERY: &str = r#"
+; path("users/<int:pk>/", views.detail), re_path(r"^feed/$", ...), DRF router.register(r"users", ...)
+(call
+  function: [(identifier) @name (attribute attribute: (identifier) @name)]
+  arguments: (argument_list . (string))
+  (#any-of? @name "path" "re_path" "url" "register")) @route
+"#;
+
+const TS_EXPORTS_QUERY: &str = r#"
+(export_statement
+  declaration: [
+    (function_declaration name: (identifier) @name)
+    (lexical_declaration (variable_declarator name: (identifier) @name))
+  ]) @export
+
+(export_statement
+  value: [
+    (identifier) @name
+    (function_expression name: (identifier) @name)
+  ]) @default
+"#;
+
+/// Routes of the frameworks above declared in one file. Line-based parsers in
+/// `domains` cover the rest; `merge_routes` combines both.
+pub fn parse_framework_routes(
+    path: &str,
+    content: &str,
+    language: SupportedLanguage,
+) -> Vec<ParsedEndpoint> {
+    let file_route = match language {
+        SupportedLanguage::TypeScript | SupportedLanguage::JavaScript => match file_route(path) {
+            Some(route) => Some(route),
+            None => return Vec::new(),
+        },
+        SupportedLanguage::Rust | SupportedLanguage::Go if !mentions_router(content) => {
+            return Vec::new()
+        }
+        SupportedLanguage::Python if !is_django_urls(path, content) => return Vec::new(),
+        SupportedLanguage::Vue => return Vec::new(),
+        _ => None,
+    };
+
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&language.tree_sitter_language())
+        .is_err()
+    {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+    let root = tree.root_node();
+
+    let mut routes = match (language, file_route) {
+        (_, Some(route)) => route.endpoints(root, content),
+        (SupportedLanguage::Rust, _) => rust_routes(root, content),
+        (SupportedLanguage::Go, _) => go_routes(root, content),
+        (SupportedLanguage::Python, _) => django_routes(root, content),
+        _ => Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    routes.retain(|r| seen.insert((r.method.clone(), r.path.clone(), r.line)));
+    routes
+}
+
+/// Add `found` to the endpoints of the line-based parsers. A route both report
+/// (same method, line and handler) keeps the line-based types and takes the
+/// prefixed path.
+pub fn merge_routes(endpoints: &mut Vec<ParsedEndpoint>, found: Vec<ParsedEndpoint>) {
+    for route in found {
+        let existing = endpoints.iter_mut().find(|e| {
+            e.method == route.method && e.line == route.line && e.handler == route.handler
+        });
+        match existing {
+            Some(endpoint) => endpoint.path = route.path,
+            None => endpoints.push(route),
+        }
+    }
+}
+
+fn mentions_router(content: &str) -> bool {
+    [
+        "actix",
+        "rocket",
+        "warp",
+        "poem",
+        "axum",
+        "net/http",
+        "go-chi",
+        "gin-gonic",
+        "echo",
+        "gorilla/mux",
+    ]
+    .iter()
+    .any(|marker| content.contains(marker))
+}
+
+fn is_django_urls(path: &str, content: &str) -> bool {
+    path.rsplit(['/', '\\']).next() == Some("urls.py") || content.contains("urlpatterns")
+}
+
+// === Rust: axum, actix-web, rocket, poem, warp ===
+
+fn rust_routes(root: Node<'_>, src: &str) -> Vec<ParsedEndpoint> {
+    static QUERY: OnceLock<Query> = OnceLock::new();
+    let query = compile(&QUERY, SupportedLanguage::Rust, RUST_ROUTES_QUERY);
+    let references = References::new(root, src);
+
+    let mut routes = Vec::new();
+    for (capture, node) in captures(query, root, src) {
+        match capture {
+            "builder" => builder_routes(node, src, &references, &mut routes),
+            "attribute" => attribute_routes(node, src, &references, &mut routes),
+            "filter" if src.contains("warp") => warp_routes(node, src, &mut routes),
+            _ => {}
+        }
+    }
+    routes
+}
+
+/// `.route("/x", get(list).post(create))`, `.at("/x", get(h))`,
+/// `.route("/x", web::get().to(h))`, `web::resource("/x").route(web::post().to(h))`
+fn builder_routes(
+    call: Node<'_>,
+    src: &str,
+    references: &References<'_>,
+    routes: &mut Vec<ParsedEndpoint>,
+) {
+    let args = arguments(call);
+    let (path, router) = match args.as_slice() {
+        [path, router] => match string_value(*path, src) {
+            Some(path) => (path, *router),
+            None => return,
+        },
+        [router] if !chain_scope(call, src).is_empty() => (String::new(), *router),
+        _ => return,
+    };
+    let methods = method_handlers(router, src);
+    if methods.is_empty() {
+        return;
+    }
+    let line = call
+        .child_by_field_name("function")
+        .and_then(|f| f.child_by_field_name("field"))
+        .map(line)
+        .unwrap_or_default();
+    for prefix in rust_prefixes(call, src, references, 0) {
+        let full = join(&prefix, &path);
+        for (method, handler) in &methods {
+            routes.push(endpoint(method, full.clone(), handler.clone(), line));
+        }
+    }
+}
+
+/// Methods and handlers of a method router: `get(a).post(b)`,
+/// `web::get().to(h)`, or a bare handler.
+fn method_handlers(router: Node<'_>, src: &str) -> Vec<(String, Option<String>)> {
+    if router.kind() != "call_expression" {
+        return handler_name(router, src)
+            .map(|h| vec![(ANY.to_string(), Some(h))])
+            .unwrap_or_default();
+    }
+    let mut chain = vec![router];
+    while let Some((Some(receiver), _)) = callee(chain[chain.len() - 1], src) {
+        if receiver.kind() != "call_expression" {
+            break;
+        }
+        chain.push(receiver);
+    }
+
+    let mut methods = Vec::new();
+    let mut pending = Vec::new();
+    for call in chain.into_iter().rev() {
+        let Some((_, name)) = callee(call, src) else {
+            continue;
+        };
+        let handler = arguments(call)
+            .first()
+            .and_then(|arg| handler_name(*arg, src));
+        match name {
+            "to" => {
+                if pending.is_empty() {
+                    pending.push(ANY.to_string());
+                }
+                for method in pending.drain(..) {
+                    methods.push((method, handler.clone()));
+                }
+            }
+            "any" => methods.push((ANY.to_string(), handler)),
+            m if HTTP_METHODS.contains(&m) => match handler {
+                Some(_) => methods.push((m.to_uppercase(), handler)),
+                None => pending.push(m.to_uppercase()),
+            },
+            _ => {}
+        }
+    }
+    methods
+}
+
+/// `#[get("/users/<id>")] fn user(..)`, mounted by `.service(user)` or
+/// `.mount("/api", routes![user])`
+fn attribute_routes(
+    item: Node<'_>,
+    src: &str,
+    references: &References<'_>,
+    routes: &mut Vec<ParsedEndpoint>,
+) {
+    let Some(attribute) = item.named_child(0) else {
+        return;
+    };
+    let Some(name) = attribute.named_child(0).map(|n| match n.kind() {
+        "scoped_identifier" => n
+            .child_by_field_name("name")
+            .map(|n| text(n, src))
+            .unwrap_or_default(),
+        _ => text(n, src),
+    }) else {
+        return;
+    };
+    let Some(arguments) = attribute.child_by_field_name("arguments") else {
+        return;
+    };
+    let mut cursor = arguments.walk();
+    let strings: Vec<String> = arguments
+        .named_children(&mut cursor)
+        .filter_map(|n| string_value(n, src))
+        .collect();
+    let Some(path) = strings.first().filter(|p| p.starts_with('/')) else {
+        return;
+    };
+    // Rocket query segments are not part of the route: `/search?<q>`
+    let path = path.split('?').next().unwrap_or_default();
+
+    let methods: Vec<String> = if name == "route" {
+        let listed: Vec<String> = strings[1..]
+            .iter()
+            .filter(|m| HTTP_METHODS.contains(&m.to_lowercase().as_str()))
+            .map(|m| m.to_uppercase())
+            .collect();
+        if listed.is_empty() {
+            vec![ANY.to_string()]
+        } else {
+            listed
+        }
+    } else {
+        vec![name.to_uppercase()]
+    };
+
+    let mut next = item.next_named_sibling();
+    while let Some(node) = next.filter(|n| n.kind() != "function_item") {
+        if !matches!(
+            node.kind(),
+            "attribute_item" | "line_comment" | "block_comment"
+        ) {
+            return;
+        }
+        next = node.next_named_sibling();
+    }
+    let Some(handler) = next
+        .and_then(|f| f.child_by_field_name("name"))
+        .map(|n| text(n, src))
+    else {
+        return;
+    };
+
+    let mut prefixes: Vec<String> = references
+        .of(handler)
+        .iter()
+        .filter(|n| {
+            matches!(
+                n.parent().map(|p| p.kind()),
+                Some("arguments" | "token_tree")
+            )
+        })
+        .flat_map(|site| rust_prefixes(*site, src, references, 0))
+        .collect();
+    if prefixes.is_empty() {
+        prefixes.push(String::new());
+    }
+    for prefix in prefixes {
+        for method in &methods {
+            routes.push(endpoint(
+                method,
+                join(&prefix, path),
+                Some(handler.to_string()),
+                line(item),
+            ));
+        }
+    }
+}
+
+/// Prefixes a Rust router node is mounted under: the `web::scope`/`resource`
+/// its chain starts from, the `.nest("/x", ..)`/`.mount("/x", ..)`/`.service(..)`
+/// calls it is an argument of, and the same for each call site of the helper
+/// function it is built in.
+fn rust_prefixes(
+    node: Node<'_>,
+    src: &str,
+    references: &References<'_>,
+    depth: usize,
+) -> Vec<String> {
+    let mut prefix = chain_scope(node, src);
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        match parent.kind() {
+            "arguments" => {
+                let Some(call) = parent.parent() else { break };
+                if let Some((Some(_), "nest" | "nest_service" | "mount")) = callee(call, src) {
+                    let path = arguments(call)
+                        .first()
+                        .filter(|a| a.id() != child.id())
+                        .and_then(|a| string_value(*a, src));
+                    if let Some(path) = path {
+                        prefix = join(&path, &prefix);
+                    }
+                }
+                prefix = join(&chain_scope(call, src), &prefix);
+                child = call;
+            }
+            "function_item" if depth < MAX_DEPTH => {
+                let Some(name) = parent.child_by_field_name("name") else {
+                    break;
+                };
+                let sites: Vec<Node<'_>> = references
+                    .of(text(name, src))
+                    .iter()
+                    .filter_map(|n| {
+                        let parent = n.parent()?;
+                        match parent.kind() {
+                            "call_expression" => Some(parent),
+                            "arguments" => Some(*n),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                if sites.is_empty() {
+                    break;
+                }
+                return sites
+                    .into_iter()
+                    .flat_map(|site| rust_prefixes(site, src, references, depth + 1))
+                    .map(|outer| join(&outer, &prefix))
+                    .collect();
+            }
+            _ => child = parent,
+        }
+    }
+    vec![prefix]
+}
+
+/// Path of the `web::scope("/x")` or `web::resource("/x")` a call chain starts from
+fn chain_scope(node: Node<'_>, src: &str) -> String {
+    let mut current = node;
+    while current.kind() == "call_expression" {
+        match callee(current, src) {
+            Some((Some(receiver), _)) => current = receiver,
+            Some((None, "scope" | "resource")) => {
+                return arguments(current)
+                    .first()
+                    .and_then(|a| string_value(*a, src))
+                    .unwrap_or_default()
+            }
+            _ => break,
+        }
+    }
+    String::new()
+}
+
+#[derive(Default)]
+struct WarpFilter {
+    segments: Vec<String>,
+    method: Option<String>,
+    handler: Option<String>,
+    is_warp: bool,
+}
+
+/// `warp::path!("users" / u32).and(warp::get()).and_then(get_user)`
+fn warp_routes(call: Node<'_>, src: &str, routes: &mut Vec<ParsedEndpoint>) {
+    // The outermost handler call of a chain reports it
+    let outer = call
+        .parent()
+        .filter(|p| p.kind() == "field_expression")
+        .and_then(|p| p.parent())
+        .and_then(|p| callee(p, src));
+    if let Some((_, "and_then" | "map" | "then")) = outer {
+        return;
+    }
+    let mut filter = WarpFilter::default();
+    warp_filter(call, src, &mut filter, 0);
+    if !filter.is_warp || (filter.segments.is_empty() && filter.method.is_none()) {
+        return;
+    }
+    routes.push(endpoint(
+        filter.method.as_deref().unwrap_or(ANY),
+        join("", &filter.segments.join("/")),
+        filter.handler,
+        line(call),
+    ));
+}
+
+fn warp_filter(node: Node<'_>, src: &str, filter: &mut WarpFilter, depth: usize) {
+    match node.kind() {
+        "call_expression" => {
+            let Some((receiver, name)) = callee(node, src) else {
+                return;
+            };
+            let args = arguments(node);
+            match receiver {
+                Some(receiver) => {
+                    // Receiver first, so segments stay in path order
+                    warp_filter(receiver, src, filter, depth);
+                    match name {
+                        "and" => {
+                            if let Some(arg) = args.first() {
+                                warp_filter(*arg, src, filter, depth);
+                            }
+                        }
+                        "and_then" | "map" | "then" if filter.handler.is_none() => {
+                            filter.handler = args.first().and_then(|a| handler_name(*a, src));
+                        }
+                        _ => {}
+                    }
+                }
+                None => {
+                    if let Some(function) = node.child_by_field_name("function") {
+                        filter.is_warp |= text(function, src).starts_with("warp::");
+                    }
+                    match name {
+                        "path" => {
+                            if let Some(segment) = args.first().and_then(|a| string_value(*a, src))
+                            {
+                                filter.segments.push(segment);
+                            }
+                        }
+                        "param" => filter.segments.push("{param}".to_string()),
+                        m if HTTP_METHODS.contains(&m) && args.is_empty() => {
+                            filter.method = Some(m.to_uppercase());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        "macro_invocation" => {
+            let Some(name) = node.child_by_field_name("macro") else {
+                return;
+            };
+            let name = text(name, src);
+            if name != "path" && name != "warp::path" {
+                return;
+            }
+            filter.is_warp = true;
+            let Some(tokens) = node.named_child(node.named_child_count().saturating_sub(1)) else {
+                return;
+            };
+            let mut cursor = tokens.walk();
+            for token in tokens.named_children(&mut cursor) {
+                match string_value(token, src) {
+                    Some(segment) => filter.segments.push(segment),
+                    None => filter.segments.push(format!("{{{}}}", text(token, src))),
+                }
+            }
+        }
+        "identifier" if depth < MAX_DEPTH => {
+            if let Some(value) = let_value(node, src) {
+                warp_filter(value, src, filter, depth + 1);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Value of the `let` binding an identifier refers to, in its function
+fn let_value<'t>(ident: Node<'t>, src: &str) -> Option<Node<'t>> {
+    let mut scope = ident;
+    while let Some(parent) = scope.parent() {
+        scope = parent;
+        if scope.kind() == "function_item" {
+            break;
+        }
+    }
+    let mut lets = Vec::new();
+    collect(scope, &["let_declaration"], &mut lets);
+    lets.into_iter()
+        .rev()
+        .filter(|l| l.end_byte() <= ident.start_byte())
+        .find(|l| {
+            l.child_by_field_name("pattern")
+                .is_some_and(|p| text(p, src) == text(ident, src))
+        })
+        .and_then(|l| l.child_by_field_name("value"))
+}
+
+// === Go: net/http, chi, gin, echo, gorilla/mux ===
+
+fn go_routes(root: Node<'_>, src: &str) -> Vec<ParsedEndpoint> {
+    static QUERY: OnceLock<Query> = OnceLock::new();
+    let query = compile(&QUERY, SupportedLanguage::Go, GO_ROUTES_QUERY);
+    let references = References::new(root, src);
+
+    let mut routes = Vec::new();
+    for (_, call) in captures(query, root, src) {
+        let Some((Some(receiver), name)) = callee(call, src) else {
+            continue;
+        };
+        let args = arguments(call);
+        let string = |i: usize| args.get(i).and_then(|a| string_value(*a, src));
+        let (methods, pattern) = match name {
+            "Method" | "MethodFunc" => match (string(0), string(1)) {
+                (Some(method), Some(path)) => (vec![method.to_uppercase()], path),
+                _ => continue,
+            },
+            "Handle" | "HandleFunc" => {
+                let Some(pattern) = string(0) else { continue };
+                // Go 1.22 patterns: "GET /users/{id}"
+                match pattern.split_once(' ') {
+                    Some((method, path)) if method.chars().all(|c| c.is_ascii_uppercase()) => {
+                        (vec![method.to_string()], path.trim().to_string())
+                    }
+                    _ => (gorilla_methods(call, src), pattern),
+                }
+            }
+            "Any" => match string(0) {
+                Some(path) => (vec![ANY.to_string()], path),
+                None => continue,
+            },
+            method => match string(0) {
+                Some(path) => (vec![method.to_uppercase()], path),
+                None => continue,
+            },
+        };
+        if !pattern.starts_with('/') {
+            continue;
+        }
+        let handler = args[1..].last().and_then(|h| handler_name(*h, src));
+        let line = call
+            .child_by_field_name("function")
+            .and_then(|f| f.child_by_field_name("field"))
+            .map(line)
+            .unwrap_or_default();
+        for prefix in go_prefixes(receiver, src, &references, 0) {
+            for method in &methods {
+                routes.push(endpoint(
+                    method,
+                    join(&prefix, &pattern),
+                    handler.clone(),
+                    line,
+                ));
+            }
+        }
+    }
+    routes
+}
+
+/// gorilla/mux `r.HandleFunc("/x", h).Methods("GET", "POST")`
+fn gorilla_methods(call: Node<'_>, src: &str) -> Vec<String> {
+    let methods = call
+        .parent()
+        .filter(|p| p.kind() == "selector_expression")
+        .and_then(|p| p.parent())
+        .filter(|p| matches!(callee(*p, src), Some((_, "Methods"))))
+        .map(|p| {
+            arguments(p)
+                .into_iter()
+                .filter_map(|a| string_value(a, src))
+                .map(|m| m.to_uppercase())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if methods.is_empty() {
+        vec![ANY.to_string()]
+    } else {
+        methods
+    }
+}
+
+/// Prefixes of the router a Go expression evaluates to: `Group`/`PathPrefix`
+/// paths along it, the variable or parameter it came from, chi
+/// `Route("/x", func(r chi.Router) {..})` closures and `Mount("/x", newRouter())`.
+fn go_prefixes(
+    expr: Node<'_>,
+    src: &str,
+    references: &References<'_>,
+    depth: usize,
+) -> Vec<String> {
+    if depth > MAX_DEPTH {
+        return vec![String::new()];
+    }
+    match expr.kind() {
+        "call_expression" => match callee(expr, src) {
+            Some((Some(receiver), "Group" | "Route" | "PathPrefix")) => {
+                let path = arguments(expr)
+                    .first()
+                    .and_then(|a| string_value(*a, src))
+                    .unwrap_or_default();
+                go_prefixes(receiver, src, references, depth + 1)
+                    .into_iter()
+                    .map(|p| join(&p, &path))
+                    .collect()
+            }
+            Some((Some(receiver), "With" | "Subrouter")) => {
+                go_prefixes(receiver, src, references, depth + 1)
+            }
+            _ => mounted_prefixes(expr, src, references, depth),
+        },
+        "identifier" => match go_definition(expr, src) {
+            Some(GoDefinition::Value(value)) => go_prefixes(value, src, references, depth + 1),
+            Some(GoDefinition::Param(function, index)) => {
+                param_prefixes(function, index, src, references, depth + 1)
+            }
+            None => vec![String::new()],
+        },
+        _ => vec![String::new()],
+    }
+}
+
+/// A router created in a function: the prefixes it is mounted under by
+/// `r.Mount("/x", thatFunction())`.
+fn mounted_prefixes(
+    expr: Node<'_>,
+    src: &str,
+    references: &References<'_>,
+    depth: usize,
+) -> Vec<String> {
+    let prefixes: Vec<String> = enclosing_function_name(expr, src)
+        .map(|name| references.of(name))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|n| call_of(*n))
+        .filter_map(|site| {
+            let mount = site.parent()?.parent()?;
+            let (receiver, _) = callee(mount, src).filter(|(_, name)| *name == "Mount")?;
+            let path = arguments(mount)
+                .first()
+                .and_then(|a| string_value(*a, src))?;
+            Some((receiver?, path))
+        })
+        .flat_map(|(receiver, path)| {
+            go_prefixes(receiver, src, references, depth + 1)
+                .into_iter()
+                .map(move |p| join(&p, &path))
+        })
+        .collect();
+    if prefixes.is_empty() {
+        vec![String::new()]
+    } else {
+        prefixes
+    }
+}
+
+/// Prefixes a router parameter receives: the arguments at the function's call
+/// sites, or the chi `Route("/x", ..)`/`Group(..)` the function is passed to.
+fn param_prefixes(
+    function: Node<'_>,
+    index: usize,
+    src: &str,
+    references: &References<'_>,
+    depth: usize,
+) -> Vec<String> {
+    let passed_to_route = |node: Node<'_>| -> Option<Vec<String>> {
+        let call = node
+            .parent()
+            .filter(|p| p.kind() == "argument_list")?
+            .parent()?;
+        let (receiver, name) = callee(call, src)?;
+        let path = match name {
+            "Route" => arguments(call)
+                .first()
+                .and_then(|a| string_value(*a, src))?,
+            "Group" => String::new(),
+            _ => return None,
+        };
+        Some(
+            go_prefixes(receiver?, src, references, depth)
+                .into_iter()
+                .map(|p| join(&p, &path))
+                .collect(),
+        )
+    };
+
+    let prefixes: Vec<String> = if function.kind() == "func_literal" {
+        passed_to_route(function).unwrap_or_default()
+    } else {
+        let Some(name) = function.child_by_field_name("name") else {
+            return vec![String::new()];
+        };
+        references
+            .of(text(name, src))
+            .iter()
+            .filter(|n| n.id() != name.id())
+            .flat_map(|n| match call_of(*n) {
+                Some(site) => arguments(site)
+                    .get(index)
+                    .map(|arg| go_prefixes(*arg, src, references, depth))
+                    .unwrap_or_default(),
+                None => {
+                    let reference = match n.parent() {
+                        Some(p) if p.kind() == "selector_expression" => p,
+                        _ => *n,
+                    };
+                    passed_to_route(reference).unwrap_or_default()
+                }
+            })
+            .collect()
+    };
+    if prefixes.is_empty() {
+        vec![String::new()]
+    } else {
+        prefixes
+    }
+}
+
+enum GoDefinition<'t> {
+    Value(Node<'t>),
+    /// Function node and parameter index
+    Param(Node<'t>, usize),
+}
+
+/// The assignment or parameter a Go identifier refers to: the last assignment
+/// before it in the innermost enclosing function that has one, else a
+/// parameter of that function.
+fn go_definition<'t>(ident: Node<'t>, src: &str) -> Option<GoDefinition<'t>> {
+    let name = text(ident, src);
+    let mut scope = ident.parent();
+    while let Some(function) = scope {
+        scope = function.parent();
+        if !matches!(
+            function.kind(),
+            "func_literal" | "function_declaration" | "method_declaration" | "source_file"
+        ) {
+            continue;
+        }
+
+        let mut assignments = Vec::new();
+        collect(
+            function,
+            &["short_var_declaration", "assignment_statement", "var_spec"],
+            &mut assignments,
+        );
+        let value = assignments
+            .into_iter()
+            .rev()
+            .filter(|a| a.end_byte() <= ident.start_byte())
+            .find_map(|a| {
+                let (names, values): (Vec<Node<'t>>, Vec<Node<'t>>) = if a.kind() == "var_spec" {
+                    let mut cursor = a.walk();
+                    let names = a.children_by_field_name("name", &mut cursor).collect();
+                    (names, named(a.child_by_field_name("value")?))
+                } else {
+                    (
+                        named(a.child_by_field_name("left")?),
+                        named(a.child_by_field_name("right")?),
+                    )
+                };
+                let index = names.iter().position(|n| text(*n, src) == name)?;
+                values.get(index).copied()
+            });
+        if let Some(value) = value {
+            return Some(GoDefinition::Value(value));
+        }
+
+        if let Some(parameters) = function.child_by_field_name("parameters") {
+            let mut index = 0;
+            for declaration in named(parameters) {
+                let mut cursor = declaration.walk();
+                for param in declaration.children_by_field_name("name", &mut cursor) {
+                    if text(param, src) == name {
+                        return Some(GoDefinition::Param(function, index));
+                    }
+                    index += 1;
+                }
+            }
+        }
+    }
+    None
+}
+
+fn enclosing_function_name<'s>(node: Node<'_>, src: &'s str) -> Option<&'s str> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        match n.kind() {
+            "function_declaration" | "method_declaration" => {
+                return n.child_by_field_name("name").map(|name| text(name, src))
+            }
+            "func_literal" => return None,
+            _ => current = n.parent(),
+        }
+    }
+    None
+}
+
+/// The call an identifier is the callee of: `newRouter()` or `h.Routes()`
+fn call_of(name: Node<'_>) -> Option<Node<'_>> {
+    let function = match name.parent()? {
+        p if p.kind() == "selector_expression" => p,
+        _ => name,
+    };
+    let call = function
+        .parent()
+        .filter(|p| p.kind() == "call_expression")?;
+    (call.child_by_field_name("function")?.id() == function.id()).then_some(call)
+}
+
+// === Python: Django urls.py ===
+
+fn django_routes(root: Node<'_>, src: &str) -> Vec<ParsedEndpoint> {
+    static QUERY: OnceLock<Query> = OnceLock::new();
+    let query = compile(&QUERY, SupportedLanguage::Python, DJANGO_ROUTES_QUERY);
+    let references = References::new(root, src);
+
+    let mut routes = Vec::new();
+    for (_, call) in captures(query, root, src) {
+        let Some((receiver, name)) = callee(call, src) else {
+            continue;
+        };
+        let args: Vec<Node<'_>> = arguments(call)
+            .into_iter()
+            .filter(|a| a.kind() != "keyword_argument")
+            .collect();
+        let (Some(path), Some(target)) = (
+            args.first().and_then(|a| string_value(*a, src)),
+            args.get(1),
+        ) else {
+            continue;
+        };
+        if matches!(callee(*target, src), Some((_, "include"))) {
+            continue;
+        }
+
+        let prefixes = match (name, receiver) {
+            // DRF: router.register(r"users", UserViewSet), included as router.urls
+            ("register", Some(router)) => {
+                let prefixes: Vec<String> = references
+                    .of("urls")
+                    .iter()
+                    .filter_map(|n| n.parent())
+                    .filter(|attr| {
+                        attr.child_by_field_name("object")
+                            .is_some_and(|o| text(o, src) == text(router, src))
+                    })
+                    .flat_map(|attr| django_prefixes(attr, src, &references, 0))
+                    .collect();
+                if prefixes.is_empty() {
+                    vec![String::new()]
+                } else {
+                    prefixes
+                }
+            }
+            ("register", None) => continue,
+            _ => django_prefixes(call, src, &references, 0),
+        };
+        let path = django_path(&path, name);
+        for prefix in prefixes {
+            routes.push(endpoint(
+                ANY,
+                join(&prefix, &path),
+                handler_name(*target, src),
+                line(call),
+            ));
+        }
+    }
+    routes
+}
+
+/// Prefixes of the `path("api/", include([...]))` calls around a pattern, and
+/// of the includes of the pattern list variable it belongs to.
+fn django_prefixes(
+    node: Node<'_>,
+    src: &str,
+    references: &References<'_>,
+    depth: usize,
+) -> Vec<String> {
+    let mut prefix = String::new();
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        match parent.kind() {
+            "argument_list" => {
+                let Some(call) = parent.parent() else { break };
+                if let Some((_, name @ ("path" | "re_path" | "url"))) = callee(call, src) {
+                    let path = arguments(call)
+                        .first()
+                        .filter(|a| a.id() != child.id())
+                        .and_then(|a| string_value(*a, src));
+                    if let Some(path) = path {
+                        prefix = join(&django_path(&path, name), &prefix);
+                    }
+                }
+                child = call;
+            }
+            "assignment" if depth < MAX_DEPTH => {
+                let Some(variable) = parent.child_by_field_name("left") else {
+                    break;
+                };
+                let sites: Vec<Node<'_>> = references
+                    .of(text(variable, src))
+                    .iter()
+                    .filter(|n| n.parent().is_some_and(|p| p.kind() == "argument_list"))
+                    .copied()
+                    .collect();
+                if sites.is_empty() {
+                    break;
+                }
+                return sites
+                    .into_iter()
+                    .flat_map(|site| django_prefixes(site, src, references, depth + 1))
+                    .map(|outer| join(&outer, &prefix))
+                    .collect();
+            }
+            _ => child = parent,
+        }
+    }
+    vec![prefix]
+}
+
+/// `re_path(r"^users/(?P<pk>\d+)/$")` -> `users/{pk}/`; `path()` patterns as is
+fn django_path(path: &str, function: &str) -> String {
+    if function == "path" || function == "register" {
+        return path.to_string();
+    }
+    static GROUP: OnceLock<Regex> = OnceLock::new();
+    let group = GROUP.get_or_init(|| Regex::new(r"\(\?P<(\w+)>[^)]*\)").unwrap());
+    group
+        .replace_all(path.trim_start_matches('^').trim_end_matches('$'), "{$1}")
+        .to_string()
+}
+
+// === Next.js / Nuxt file-system routes ===
+
+/// Route a Next.js or Nuxt server file serves, derived from its path
+struct FileRoute {
+    path: String,
+    /// Nuxt `users.get.ts`
+    method: Option<String>,
+    /// Next.js app router `route.ts`: one endpoint per exported `GET`, `POST`, ...
+    exported_methods: bool,
+}
+
+fn file_route(path: &str) -> Option<FileRoute> {
+    let parts: Vec<&str> = path.split(['/', '\\']).collect();
+    let (file, dirs) = parts.split_last()?;
+    let (stem, ext) = file.rsplit_once('.')?;
+    if !matches!(ext, "ts" | "js" | "tsx" | "jsx" | "mjs") {
+        return None;
+    }
+
+    // app/api/users/[id]/route.ts
+    if stem == "route" {
+        if let Some(app) = dirs.iter().rposition(|d| *d == "app") {
+            return Some(FileRoute {
+                path: route_segments(&dirs[app + 1..], None),
+                method: None,
+                exported_methods: true,
+            });
+        }
+    }
+    // pages/api/users/[id].ts
+    if let Some(pages) = dirs.windows(2).position(|w| w == ["pages", "api"]) {
+        return Some(FileRoute {
+            path: route_segments(&dirs[pages + 1..], Some(stem)),
+            method: None,
+            exported_methods: false,
+        });
+    }
+    // server/api/users/[id].get.ts, server/routes/feed.ts
+    let server = dirs
+        .windows(2)
+        .position(|w| w[0] == "server" && matches!(w[1], "api" | "routes"))?;
+    let start = if dirs[server + 1] == "api" {
+        server + 1
+    } else {
+        server + 2
+    };
+    let (name, method) = match stem.rsplit_once('.') {
+        Some((name, method)) if HTTP_METHODS.contains(&method) => {
+            (name, Some(method.to_uppercase()))
+        }
+        _ => (stem, None),
+    };
+    Some(FileRoute {
+        path: route_segments(&dirs[start..], Some(name)),
+        method,
+        exported_methods: false,
+    })
+}
+
+/// `["api", "(admin)", "users", "[id]"]` + `"[...slug]"` -> `/api/users/{id}/{slug}`
+fn route_segments(dirs: &[&str], file: Option<&str>) -> String {
+    let segments: Vec<String> = dirs
+        .iter()
+        .copied()
+        .chain(file.filter(|f| *f != "index"))
+        .filter(|s| !(s.starts_with('@') || s.starts_with('(') && s.ends_with(')')))
+        .map(|s| {
+            let param = s.trim_start_matches('[').trim_end_matches(']');
+            if param.len() < s.len() {
+                format!("{{{}}}", param.trim_start_matches("..."))
+            } else {
+                s.to_string()
+            }
+        })
+        .collect();
+    join("", &segments.join("/"))
+}
+
+impl FileRoute {
+    fn endpoints(&self, root: Node<'_>, src: &str) -> Vec<ParsedEndpoint> {
+        static QUERY: OnceLock<Query> = OnceLock::new();
+        let query = compile(&QUERY, SupportedLanguage::TypeScript, TS_EXPORTS_QUERY);
+        let mut exports = Vec::new();
+        let mut default = None;
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, root, src.as_bytes());
+        while let Some(m) = matches.next() {
+            let name = m
+                .captures
+                .iter()
+                .find(|c| query.capture_names()[c.index as usize] == "name");
+            let statement = m
+                .captures
+                .iter()
+                .find(|c| query.capture_names()[c.index as usize] != "name");
+            let (Some(name), Some(statement)) = (name, statement) else {
+                continue;
+            };
+            let export = (text(name.node, src).to_string(), line(statement.node));
+            if query.capture_names()[statement.index as usize] == "default"
+                || text(statement.node, src).starts_with("export default")
+            {
+                default = Some(export);
+            } else {
+                exports.push(export);
+            }
+        }
+
+        if self.exported_methods {
+            return exports
+                .into_iter()
+                .filter(|(name, _)| HTTP_METHODS.contains(&name.to_lowercase().as_str()))
+                .map(|(name, line)| endpoint(&name, self.path.clone(), Some(name.clone()), line))
+                .collect();
+        }
+        let (handler, line) = default.map(|(n, l)| (Some(n), l)).unwrap_or((None, 0));
+        vec![endpoint(
+            self.method.as_deref().unwrap_or(ANY),
+            self.path.clone(),
+            handler,
+            line,
+        )]
+    }
+}
+
+// === Tree helpers ===
+
+fn compile(
+    lock: &'static OnceLock<Query>,
+    language: SupportedLanguage,
+    source: &str,
+) -> &'static Query {
+    lock.get_or_init(|| {
+        Query::new(&language.tree_sitter_language(), source)
+            .expect("Failed to compile tree-sitter route query")
+    })
+}
+
+/// Nodes of every capture except `@name`, with their capture name
+fn captures<'t>(query: &'static Query, root: Node<'t>, src: &str) -> Vec<(&'static str, Node<'t>)> {
+    let names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, root, src.as_bytes());
+    let mut nodes = Vec::new();
+    while let Some(m) = matches.next() {
+        nodes.extend(
+            m.captures
+                .iter()
+                .map(|c| (names[c.index as usize], c.node))
+                .filter(|(name, _)| *name != "name"),
+        );
+    }
+    nodes
+}
+
+/// Identifier nodes by name, for finding where a helper function or handler is
+/// called or passed
+struct References<'t> {
+    by_name: HashMap<String, Vec<Node<'t>>>,
+}
+
+impl<'t> References<'t> {
+    fn new(root: Node<'t>, src: &str) -> Self {
+        let mut nodes = Vec::new();
+        collect(root, &["identifier", "field_identifier"], &mut nodes);
+        let mut by_name: HashMap<String, Vec<Node<'t>>> = HashMap::new();
+        for node in nodes {
+            by_name
+                .entry(text(node, src).to_string())
+                .or_default()
+                .push(node);
+        }
+        Self { by_name }
+    }
+
+    fn of(&self, name: &str) -> &[Node<'t>] {
+        self.by_name
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+pub(super) fn collect<'t>(node: Node<'t>, kinds: &[&str], out: &mut Vec<Node<'t>>) {
+    if kinds.contains(&node.kind()) {
+        out.push(node);
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(child, kinds, out);
+    }
+}
+
+pub(super) fn named(node: Node<'_>) -> Vec<Node<'_>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|n| !n.kind().contains("comment"))
+        .collect()
+}
+
+pub(super) fn arguments(call: Node<'_>) -> Vec<Node<'_>> {
+    call.child_by_field_name("arguments")
+        .map(named)
+        .unwrap_or_default()
+}
+
+/// Receiver and name of a call: `recv.name(..)`, `path::name(..)` or `name(..)`
+fn callee<'t, 's>(call: Node<'t>, src: &'s str) -> Option<(Option<Node<'t>>, &'s str)> {
+    if !matches!(call.kind(), "call_expression" | "call") {
+        return None;
+    }
+    let mut function = call.child_by_field_name("function")?;
+    if function.kind() == "generic_function" {
+        function = function.child_by_field_name("function")?;
+    }
+    let (receiver, name) = match function.kind() {
+        "field_expression" => (
+            function.child_by_field_name("value"),
+            function.child_by_field_name("field")?,
+        ),
+        "selector_expression" => (
+            function.child_by_field_name("operand"),
+            function.child_by_field_name("field")?,
+        ),
+        "attribute" => (
+            function.child_by_field_name("object"),
+            function.child_by_field_name("attribute")?,
+        ),
+        "scoped_identifier" => (None, function.child_by_field_name("name")?),
+        "identifier" => (None, function),
+        _ => return None,
+    };
+    Some((receiver, text(name, src)))
+}
+
+/// Function a handler expression names: `list`, `handlers::list`, `h.List`,
+/// `views.detail`, `UserView.as_view()`
+fn handler_name(node: Node<'_>, src: &str) -> Option<String> {
+    match node.kind() {
+        "identifier" | "field_identifier" => Some(text(node, src).to_string()),
+        "scoped_identifier" | "generic_function" => {
+            let inner = node
+                .child_by_field_name("name")
+                .or_else(|| node.child_by_field_name("function"))?;
+            handler_name(inner, src)
+        }
+        "field_expression" | "selector_expression" => {
+            handler_name(node.child_by_field_name("field")?, src)
+        }
+        "attribute" => handler_name(node.child_by_field_name("attribute")?, src),
+        "call" => match callee(node, src)? {
+            (Some(view), "as_view") => handler_name(view, src),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Contents of a Rust, Go, Python or TypeScript string literal
+pub(super) fn string_value(node: Node<'_>, src: &str) -> Option<String> {
+    if !matches!(
+        node.kind(),
+        "string_literal" | "raw_string_literal" | "interpreted_string_literal" | "string"
+    ) {
+        return None;
+    }
+    let raw = text(node, src)
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .trim_matches('#');
+    let quote = raw
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    Some(raw.trim_matches(quote).to_string())
+}
+
+pub(super) fn text<'s>(node: Node<'_>, src: &'s str) -> &'s str {
+    &src[node.byte_range()]
+}
+
+pub(super) fn line(node: Node<'_>) -> u32 {
+    node.start_position().row as u32
+}
+
+/// `/api` + `users/` -> `/api/users`
+fn join(prefix: &str, path: &str) -> String {
+    let parts: Vec<&str> = [prefix, path]
+        .iter()
+        .map(|p| p.trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .collect();
+    format!("/{}", parts.join("/"))
+}
+
+fn endpoint(method: &str, path: String, handler: Option<String>, line: u32) -> ParsedEndpoint {
+    ParsedEndpoint {
+        method: method.to_uppercase(),
+        path,
+        handler,
+        request_type: None,
+        response_type: None,
+        line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes(path: &str, content: &str, language: SupportedLanguage) -> Vec<String> {
+        let mut routes: Vec<String> = parse_framework_routes(path, content, language)
+            .into_iter()
+            .map(|r| {
+                format!(
+                    "{} {} {} {}",
+                    r.method,
+                    r.path,
+                    r.handler.unwrap_or_default(),
+                    r.line + 1
+                )
+            })
+            .collect();
+        routes.sort();
+        routes
+    }
+
+    #[test]
+    fn test_rust_builders_and_nested_routers() {
+        let axum = r#"
+use axum::{routing::{get, post}, Router};
+
+pub fn app() -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .nest("/api", api())
+}
+
+fn api() -> Router {
+    Router::new()
+        .route(
+            "/users",
+            get(handlers::list_users).post(create_user),
+        )
+        .nest("/admin", Router::new().route("/stats", get(stats)))
+}
+"#;
+        assert_eq!(
+            routes("src/app.rs", axum, SupportedLanguage::Rust),
+            vec![
+                "GET /api/admin/stats stats 16",
+                "GET /api/users list_users 12",
+                "GET /health health 6",
+                "POST /api/users create_user 12",
+            ]
+        );
+
+        let actix = r#"
+use actix_web::{get, post, web, App, HttpServer};
+
+#[get("/{id}")]
+async fn get_user(path: web::Path<u32>) -> String { todo!() }
+
+#[route("/bulk", method = "POST", method = "PUT")]
+async fn bulk() -> String { todo!() }
+
+fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/users")
+            .service(get_user)
+            .service(bulk)
+            .route("", web::post().to(create_user)),
+    );
+}
+
+#[actix_web::main]
+async fn main() {
+    HttpServer::new(|| {
+        App::new()
+            .service(web::scope("/api").configure(config))
+            .service(web::resource("/ping").route(web::get().to(ping)))
+    });
+}
+"#;
+        assert_eq!(
+            routes("src/main.rs", actix, SupportedLanguage::Rust),
+            vec![
+                "GET /api/users/{id} get_user 4",
+                "GET /ping ping 24",
+                "POST /api/users create_user 15",
+                "POST /api/users/bulk bulk 7",
+                "PUT /api/users/bulk bulk 7",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rocket_poem_and_warp() {
+        let rocket = r#"
+#[macro_use] extern crate rocket;
+
+#[get("/<id>")]
+fn user(id: u32) -> String { todo!() }
+
+#[post("/search?<q>", data = "<body>")]
+fn search(q: &str, body: String) -> String { todo!() }
+
+#[launch]
+fn rocket() -> _ {
+    rocket::build().mount("/users", routes![user, search])
+}
+"#;
+        assert_eq!(
+            routes("src/main.rs", rocket, SupportedLanguage::Rust),
+            vec!["GET /users/<id> user 4", "POST /users/search search 7"]
+        );
+
+        let poem = r#"
+use poem::{get, Route};
+
+fn app() -> Route {
+    Route::new().nest("/api", Route::new().at("/hello/:name", get(hello).post(greet)))
+}
+"#;
+        assert_eq!(
+            routes("src/main.rs", poem, SupportedLanguage::Rust),
+            vec![
+                "GET /api/hello/:name hello 5",
+                "POST /api/hello/:name greet 5"
+            ]
+        );
+
+        let warp = r#"
+use warp::Filter;
+
+async fn main() {
+    let api = warp::path("api");
+    let user = api
+        .and(warp::path!("users" / u32))
+        .and(warp::get())
+        .and_then(get_user)
+        .map(|reply| reply);
+    warp::serve(user.or(health)).run(([0, 0, 0, 0], 3030)).await;
+}
+"#;
+        assert_eq!(
+            routes("src/main.rs", warp, SupportedLanguage::Rust),
+            vec!["GET /api/users/{u32} get_user 6"]
+        );
+    }
+
+    #[test]
+    fn test_go_routers() {
+        let chi = r#"
+package main
+
+import "github.com/go-chi/chi/v5"
+
+func main() {
+	r := chi.NewRouter()
+	r.Get("/health", health)
+	r.Route("/api", func(r chi.Router) {
+		r.Get("/users/{id}", h.GetUser)
+		r.Route("/orders", orderRoutes)
+	})
+	r.Mount("/admin", adminRouter())
+	http.HandleFunc("GET /ping", ping)
+}
+
+func orderRoutes(r chi.Router) {
+	r.Post("/", createOrder)
+}
+
+func adminRouter() chi.Router {
+	r := chi.NewRouter()
+	r.Method("DELETE", "/cache", flushCache)
+	return r
+}
+"#;
+        assert_eq!(
+            routes("cmd/main.go", chi, SupportedLanguage::Go),
+            vec![
+                "DELETE /admin/cache flushCache 23",
+                "GET /api/users/{id} GetUser 10",
+                "GET /health health 8",
+                "GET /ping ping 14",
+                "POST /api/orders createOrder 18",
+            ]
+        );
+
+        let gin = r#"
+package main
+
+import "github.com/gin-gonic/gin"
+
+func main() {
+	r := gin.Default()
+	v1 := r.Group("/v1")
+	{
+		users := v1.Group("/users")
+		users.GET("/:id", auth, getUser)
+		registerAdmin(v1.Group("/admin"))
+	}
+	e := echo.New()
+	e.POST("/login", login)
+}
+
+func registerAdmin(g *gin.RouterGroup) {
+	g.DELETE("/users/:id", func(c *gin.Context) {})
+}
+"#;
+        assert_eq!(
+            routes("main.go", gin, SupportedLanguage::Go),
+            vec![
+                "DELETE /v1/admin/users/:id  19",
+                "GET /v1/users/:id getUser 11",
+                "POST /login login 15",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_django_urls() {
+        let urls = r#"
+from django.urls import include, path, re_path
+from rest_framework import routers
+from . import views
+
+router = routers.DefaultRouter()
+router.register(r"accounts", views.AccountViewSet)
+
+user_patterns = [
+    path("<int:pk>/", views.user_detail, name="user-detail"),
+    re_path(r"^(?P<pk>\d+)/avatar/$", views.AvatarView.as_view()),
+]
+
+urlpatterns = [
+    path("", views.index),
+    path("api/", include([
+        path("users/", include(user_patterns)),
+        path("", include(router.urls)),
+    ])),
+]
+"#;
+        assert_eq!(
+            routes("app/urls.py", urls, SupportedLanguage::Python),
+            vec![
+                "ALL / index 15",
+                "ALL /api/accounts AccountViewSet 7",
+                "ALL /api/users/<int:pk> user_detail 10",
+                "ALL /api/users/{pk}/avatar AvatarView 11",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_and_nuxt_file_routes() {
+        let app_route = r#"
+export async function GET(request: Request) {}
+export const POST = async (request: Request) => {};
+export const dynamic = "force-dynamic";
+"#;
+        assert_eq!(
+            routes(
+                "src/app/(shop)/api/orders/[id]/route.ts",
+                app_route,
+                SupportedLanguage::TypeScript
+            ),
+            vec!["GET /api/orders/{id} GET 2", "POST /api/orders/{id} POST 3"]
+        );
+        assert_eq!(
+            routes(
+                "pages/api/docs/[...slug].ts",
+                "export default function handler(req, res) {}\n",
+                SupportedLanguage::TypeScript
+            ),
+            vec!["ALL /api/docs/{slug} handler 1"]
+        );
+        assert_eq!(
+            routes(
+                "server/api/users/[id].get.ts",
+                "export default defineEventHandler(() => {})\n",
+                SupportedLanguage::TypeScript
+            ),
+            vec!["GET /api/users/{id}  1"]
+        );
+        assert_eq!(
+            routes(
+                "server/routes/index.ts",
+                "export default defineEventHandler(() => {})\n",
+                SupportedLanguage::TypeScript
+            ),
+            vec!["ALL /  1"]
+        );
+        assert!(routes("src/pages/users.ts", "", SupportedLanguage::TypeScript).is_empty());
+    }
+}
@@ -0,0 +1,717 @@
+//! Machine-checkable project conventions from `.gofer/rules.toml`: forbidden
+//! imports between domains or directories, naming patterns per symbol kind,
+//! banned APIs and file/function size limits.
+//!
+//! ```toml
+//! [[imports]]
+//! from = "frontend"              # domain, path glob or directory
+//! to = ["src/db/**", "sqlx"]     # the same, or a package name
+//! message = "UI code goes through the API"
+//!
+//! [[naming]]
+//! kind = "function"
+//! pattern = "^[a-z][a-z0-9_]*$"
+//! paths = ["**/*.rs"]
+//!
+//! [[banned]]
+//! pattern = '\.unwrap\(\)'
+//! exclude = ["**/tests/**"]
+//! severity = "warning"
+//!
+//! [[limits]]
+//! max_file_lines = 800
+//! max_function_lines = 80
+//! ```
+//!
+//! Every rule takes optional `name`, `message`, `severity` (`error` or
+//! `warning`), `paths` and `exclude` (globs of the files it applies to).
+//! Layering between modules lives under `[architecture]`, see
+//! [`super::architecture`].
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+use super::architecture::Architecture;
+use super::context::ImportResolver;
+use super::domains::{detect_domain, DomainConfig};
+use super::parser::{CodeParser, SupportedLanguage};
+use crate::models::{Symbol, SymbolKind};
+
+pub const RULES_FILE: &str = "rules.toml";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// Fields shared by every rule
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Scope {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub paths: Vec<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub exclude: Vec<String>,
+}
+
+impl Scope {
+    pub(super) fn applies_to(&self, path: &str) -> bool {
+        (self.paths.is_empty() || self.paths.iter().any(|p| path_matches(p, path)))
+            && !self.exclude.iter().any(|p| path_matches(p, path))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportRule {
+    #[serde(flatten)]
+    pub scope: Scope,
+    #[serde(deserialize_with = "one_or_many")]
+    pub from: Vec<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NamingRule {
+    #[serde(flatten)]
+    pub scope: Scope,
+    #[serde(alias = "kinds", deserialize_with = "one_or_many")]
+    pub kind: Vec<String>,
+    #[serde(deserialize_with = "regex")]
+    pub pattern: Regex,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BannedApi {
+    #[serde(flatten)]
+    pub scope: Scope,
+    #[serde(deserialize_with = "regex")]
+    pub pattern: Regex,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Limits {
+    #[serde(flatten)]
+    pub scope: Scope,
+    #[serde(default)]
+    pub max_file_lines: Option<usize>,
+    #[serde(default)]
+    pub max_function_lines: Option<usize>,
+}
+
+/// Contents of `.gofer/rules.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RulesFile {
+    #[serde(default)]
+    pub imports: Vec<ImportRule>,
+    #[serde(default)]
+    pub naming: Vec<NamingRule>,
+    #[serde(default)]
+    pub banned: Vec<BannedApi>,
+    #[serde(default)]
+    pub limits: Vec<Limits>,
+    #[serde(default)]
+    pub architecture: Architecture,
+}
+
+impl RulesFile {
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty()
+            && self.naming.is_empty()
+            && self.banned.is_empty()
+            && self.limits.is_empty()
+            && self.architecture.is_empty()
+    }
+}
+
+/// Rules of the project at `root`; empty when it has no rules file.
+pub fn load_rules(root: &Path) -> anyhow::Result<RulesFile> {
+    let path = root.join(".gofer").join(RULES_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(RulesFile::default()),
+        Err(e) => return Err(e.into()),
+    };
+    toml::from_str(&content).map_err(|e| anyhow::anyhow!("Invalid .gofer/{}: {}", RULES_FILE, e))
+}
+
+/// An import of a file and the project files it resolves to
+#[derive(Debug, Clone)]
+pub struct FileImport {
+    /// 0-based
+    pub line: u32,
+    pub specifier: String,
+    pub targets: Vec<String>,
+}
+
+/// Domains of project files: the one the index stored, else detected from the
+/// path. Import-based detection needs a file's content, which a target of an
+/// import is not read for.
+#[derive(Debug, Clone, Default)]
+pub struct Domains {
+    pub config: DomainConfig,
+    pub stored: HashMap<String, String>,
+}
+
+impl Domains {
+    pub fn of(&self, path: &str) -> &str {
+        match self.stored.get(path) {
+            Some(domain) => domain,
+            None => detect_domain(path, "", &self.config).0.as_str(),
+        }
+    }
+}
+
+/// What the rules look at in one file
+#[derive(Debug, Clone)]
+pub struct FileFacts {
+    /// Relative to the project root
+    pub path: String,
+    /// Detected from `content`
+    pub domain: String,
+    pub content: String,
+    pub symbols: Vec<Symbol>,
+    pub imports: Vec<FileImport>,
+}
+
+impl FileFacts {
+    /// Parse `content` of `path`; `symbols` are taken from the index when
+    /// given, otherwise parsed too. `None` for files that are not code.
+    pub fn parse(
+        root: &Path,
+        resolver: &ImportResolver,
+        config: &DomainConfig,
+        path: &str,
+        content: String,
+        symbols: Option<Vec<Symbol>>,
+    ) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?;
+        let language = SupportedLanguage::from_extension(ext)?;
+        let symbols = match symbols {
+            Some(symbols) => symbols,
+            None => CodeParser::new()
+                .parse_symbols(&content, language)
+                .unwrap_or_default(),
+        };
+        let imports = parse_imports(root, resolver, path, &content)?;
+        let domain = detect_domain(path, &content, config).0.as_str().to_string();
+        Some(Self {
+            path: path.to_string(),
+            domain,
+            content,
+            symbols,
+            imports,
+        })
+    }
+
+    /// Project files the file imports
+    pub fn targets(&self) -> impl Iterator<Item = &String> {
+        self.imports.iter().flat_map(|import| &import.targets)
+    }
+}
+
+/// Imports of `content` with the project files each resolves to; `None` for
+/// files that are not code.
+pub fn parse_imports(
+    root: &Path,
+    resolver: &ImportResolver,
+    path: &str,
+    content: &str,
+) -> Option<Vec<FileImport>> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    let language = SupportedLanguage::from_extension(ext)?;
+    let abs_path = root.join(path);
+    Some(
+        CodeParser::new()
+            .parse_imports(content, language)
+            .into_iter()
+            .map(|import| FileImport {
+                line: import.line,
+                targets: resolver.resolve(&import, &abs_path, language),
+                specifier: import.path,
+            })
+            .collect(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub path: String,
+    /// 1-based
+    pub line: usize,
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{} [{}] {}: {}",
+            self.path,
+            self.line,
+            self.severity.as_str(),
+            self.rule,
+            self.message
+        )
+    }
+}
+
+/// Every rule `file` breaks, in line order.
+pub fn check_file(rules: &RulesFile, file: &FileFacts, domains: &Domains) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut report = |scope: &Scope, default_name: &str, line: usize, what: String| {
+        let message = match &scope.message {
+            Some(message) => format!("{} ({})", what, message),
+            None => what,
+        };
+        violations.push(Violation {
+            path: file.path.clone(),
+            line,
+            rule: scope
+                .name
+                .clone()
+                .unwrap_or_else(|| default_name.to_string()),
+            severity: scope.severity,
+            message,
+        });
+    };
+
+    let domain = file.domain.as_str();
+    for rule in rules
+        .imports
+        .iter()
+        .filter(|r| r.scope.applies_to(&file.path))
+    {
+        if !rule.from.iter().any(|s| selects(s, &file.path, domain)) {
+            continue;
+        }
+        for import in &file.imports {
+            let hit = if import.targets.is_empty() {
+                rule.to.iter().find(|s| names_package(s, &import.specifier))
+            } else {
+                rule.to
+                    .iter()
+                    .find(|s| import.targets.iter().any(|t| selects(s, t, domains.of(t))))
+            };
+            if let Some(selector) = hit {
+                let target = import.targets.first().unwrap_or(&import.specifier);
+                report(
+                    &rule.scope,
+                    "forbidden-import",
+                    import.line as usize + 1,
+                    format!(
+                        "imports {} (forbidden: {} -> {})",
+                        target,
+                        rule.from.join("|"),
+                        selector
+                    ),
+                );
+            }
+        }
+    }
+
+    for import in &file.imports {
+        let layering = import.targets.iter().find_map(|target| {
+            rules
+                .architecture
+                .check_edge(&file.path, domain, target, domains.of(target))
+                .map(|why| (target, why))
+        });
+        if let Some((target, why)) = layering {
+            report(
+                &rules.architecture.scope,
+                "layering",
+                import.line as usize + 1,
+                format!("imports {} ({})", target, why),
+            );
+        }
+    }
+
+    for rule in rules
+        .naming
+        .iter()
+        .filter(|r| r.scope.applies_to(&file.path))
+    {
+        for symbol in &file.symbols {
+            if rule.kind.iter().any(|k| k == symbol.kind.as_str())
+                && !rule.pattern.is_match(&symbol.name)
+            {
+                report(
+                    &rule.scope,
+                    "naming",
+                    symbol.line_start as usize + 1,
+                    format!(
+                        "{} `{}` does not match {}",
+                        symbol.kind.as_str(),
+                        symbol.name,
+                        rule.pattern.as_str()
+                    ),
+                );
+            }
+        }
+    }
+
+    for rule in rules
+        .banned
+        .iter()
+        .filter(|r| r.scope.applies_to(&file.path))
+    {
+        for (i, line) in file.content.lines().enumerate() {
+            if let Some(m) = rule.pattern.find(line) {
+                report(
+                    &rule.scope,
+                    "banned-api",
+                    i + 1,
+                    format!("`{}` is banned", m.as_str().trim()),
+                );
+            }
+        }
+    }
+
+    for rule in rules
+        .limits
+        .iter()
+        .filter(|r| r.scope.applies_to(&file.path))
+    {
+        if let Some(max) = rule.max_file_lines {
+            let lines = file.content.lines().count();
+            if lines > max {
+                report(
+                    &rule.scope,
+                    "max-file-lines",
+                    1,
+                    format!("file has {} lines (max {})", lines, max),
+                );
+            }
+        }
+        if let Some(max) = rule.max_function_lines {
+            for symbol in file
+                .symbols
+                .iter()
+                .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
+            {
+                let lines = (symbol.line_end - symbol.line_start + 1).max(0) as usize;
+                if lines > max {
+                    report(
+                        &rule.scope,
+                        "max-function-lines",
+                        symbol.line_start as usize + 1,
+                        format!("`{}` has {} lines (max {})", symbol.name, lines, max),
+                    );
+                }
+            }
+        }
+    }
+
+    violations.sort_by_key(|v| v.line);
+    violations
+}
+
+/// Whether `selector` names the domain of `path`, matches it as a glob or is
+/// one of its parent directories.
+pub(super) fn selects(selector: &str, path: &str, domain: &str) -> bool {
+    selector == domain || path_matches(selector, path)
+}
+
+/// `sqlx` names `sqlx::query`, `lodash` names `lodash/fp`, `os` names `os.path`
+fn names_package(selector: &str, specifier: &str) -> bool {
+    specifier == selector
+        || [specifier.strip_prefix(selector)]
+            .into_iter()
+            .flatten()
+            .any(|rest| rest.starts_with('/') || rest.starts_with("::") || rest.starts_with('.'))
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    if glob::Pattern::new(pattern).is_ok_and(|p| p.matches(path)) {
+        return true;
+    }
+    let dir = pattern.trim_end_matches('/');
+    !dir.is_empty() && (path == dir || path.starts_with(&format!("{}/", dir)))
+}
+
+pub(super) fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(s) => vec![s],
+        OneOrMany::Many(v) => v,
+    })
+}
+
+fn regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, kind: SymbolKind, line_start: i32, line_end: i32) -> Symbol {
+        Symbol {
+            id: 0,
+            file_id: 0,
+            name: name.to_string(),
+            kind,
+            line_start,
+            line_end,
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn test_check_file() {
+        let rules: RulesFile = toml::from_str(
+            r#"
+[[imports]]
+name = "ui-no-db"
+from = "web"
+to = ["src/db/**", "sqlx"]
+message = "go through the API"
+
+[[naming]]
+kind = ["function", "method"]
+pattern = "^[a-z][a-zA-Z0-9]*$"
+severity = "warning"
+
+[[banned]]
+pattern = 'console\.log\('
+exclude = "web/dev/**"
+
+[[limits]]
+max_file_lines = 5
+max_function_lines = 3
+paths = ["web/**"]
+"#,
+        )
+        .unwrap();
+        let file = FileFacts {
+            path: "web/pages/users.ts".to_string(),
+            domain: "frontend".to_string(),
+            content: "import { pool } from '../../src/db/pool';\nimport sqlx from 'sqlx';\nimport { api } from './api';\n\nfunction load_users() {\n  console.log('x');\n  return api.get('/users');\n}\n".to_string(),
+            symbols: vec![
+                symbol("load_users", SymbolKind::Function, 4, 7),
+                symbol("render", SymbolKind::Function, 8, 9),
+            ],
+            imports: vec![
+                FileImport {
+                    line: 0,
+                    specifier: "../../src/db/pool".to_string(),
+                    targets: vec!["src/db/pool.ts".to_string()],
+                },
+                FileImport {
+                    line: 1,
+                    specifier: "sqlx".to_string(),
+                    targets: Vec::new(),
+                },
+                FileImport {
+                    line: 2,
+                    specifier: "./api".to_string(),
+                    targets: vec!["web/pages/api.ts".to_string()],
+                },
+            ],
+        };
+
+        let found: Vec<String> = check_file(&rules, &file, &Domains::default())
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "web/pages/users.ts:1 [error] ui-no-db: imports src/db/pool.ts (forbidden: web -> src/db/**) (go through the API)",
+                "web/pages/users.ts:1 [error] max-file-lines: file has 8 lines (max 5)",
+                "web/pages/users.ts:2 [error] ui-no-db: imports sqlx (forbidden: web -> sqlx) (go through the API)",
+                "web/pages/users.ts:5 [warning] naming: function `load_users` does not match ^[a-z][a-zA-Z0-9]*$",
+                "web/pages/users.ts:5 [error] max-function-lines: `load_users` has 4 lines (max 3)",
+                "web/pages/users.ts:6 [error] banned-api: `console.log(` is banned",
+            ]
+        );
+
+        let dev = FileFacts {
+            path: "web/dev/debug.ts".to_string(),
+            ..file
+        };
+        assert!(check_file(&rules, &dev, &Domains::default())
+            .iter()
+            .all(|v| v.rule != "banned-api"));
+    }
+
+    fn facts(path: &str, content: &str, symbols: Vec<Symbol>) -> FileFacts {
+        FileFacts {
+            path: path.to_string(),
+            domain: "frontend".to_string(),
+            content: content.to_string(),
+            symbols,
+            imports: Vec::new(),
+        }
+    }
+
+    fn check(rules: &str, file: &FileFacts) -> Vec<String> {
+        let rules: RulesFile = toml::from_str(rules).unwrap();
+        check_file(&rules, file, &Domains::default())
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_import_rule_uses_stored_target_domain() {
+        let rules: RulesFile = toml::from_str(
+            r#"
+[[imports]]
+from = "frontend"
+to = "backend"
+"#,
+        )
+        .unwrap();
+        let mut file = facts(
+            "web/app.ts",
+            "import { pool } from '../lib/db';\n",
+            Vec::new(),
+        );
+        file.imports.push(FileImport {
+            line: 0,
+            specifier: "../lib/db".to_string(),
+            targets: vec!["lib/db.ts".to_string()],
+        });
+
+        // The path alone says nothing about lib/db.ts; its imports made it backend
+        assert!(check_file(&rules, &file, &Domains::default()).is_empty());
+        let domains = Domains {
+            stored: HashMap::from([("lib/db.ts".to_string(), "backend".to_string())]),
+            ..Default::default()
+        };
+        let found = check_file(&rules, &file, &domains);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rule, "forbidden-import");
+        assert_eq!(found[0].line, 1);
+    }
+
+    #[test]
+    fn test_naming_rules() {
+        let rules = r#"
+[[naming]]
+name = "pascal-types"
+kinds = ["struct", "enum"]
+pattern = "^[A-Z][A-Za-z0-9]*$"
+
+[[naming]]
+kind = "function"
+pattern = "^[a-z_][a-z0-9_]*$"
+paths = "src/**"
+"#;
+        let symbols = vec![
+            symbol("user_row", SymbolKind::Struct, 0, 3),
+            symbol("Role", SymbolKind::Enum, 4, 6),
+            symbol("loadUser", SymbolKind::Function, 7, 9),
+            symbol("load_user", SymbolKind::Function, 10, 12),
+            symbol("fetchAll", SymbolKind::Method, 13, 14),
+        ];
+        assert_eq!(
+            check(rules, &facts("src/users.rs", "", symbols.clone())),
+            vec![
+                "src/users.rs:1 [error] pascal-types: struct `user_row` does not match ^[A-Z][A-Za-z0-9]*$",
+                "src/users.rs:8 [error] naming: function `loadUser` does not match ^[a-z_][a-z0-9_]*$",
+            ]
+        );
+        // The function rule only covers src/
+        assert_eq!(check(rules, &facts("tools/gen.rs", "", symbols)).len(), 1);
+    }
+
+    #[test]
+    fn test_banned_api() {
+        let rules = r#"
+[[banned]]
+pattern = '\.unwrap\(\)'
+severity = "warning"
+exclude = ["**/tests/**"]
+message = "use ? or expect"
+
+[[banned]]
+name = "no-dbg"
+pattern = 'dbg!'
+"#;
+        let content =
+            "fn main() {\n    let x = read().unwrap();\n    dbg!(x);\n    ok().unwrap();\n}\n";
+        assert_eq!(
+            check(rules, &facts("src/main.rs", content, Vec::new())),
+            vec![
+                "src/main.rs:2 [warning] banned-api: `.unwrap()` is banned (use ? or expect)",
+                "src/main.rs:3 [error] no-dbg: `dbg!` is banned",
+                "src/main.rs:4 [warning] banned-api: `.unwrap()` is banned (use ? or expect)",
+            ]
+        );
+        assert_eq!(
+            check(rules, &facts("src/tests/io.rs", content, Vec::new())),
+            vec!["src/tests/io.rs:3 [error] no-dbg: `dbg!` is banned"]
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let rules = r#"
+[[limits]]
+max_file_lines = 4
+max_function_lines = 2
+"#;
+        let symbols = vec![
+            symbol("short", SymbolKind::Function, 0, 1),
+            symbol("long", SymbolKind::Method, 2, 4),
+            symbol("Big", SymbolKind::Struct, 0, 9),
+        ];
+        // At the limit is fine
+        assert!(
+            check(rules, &facts("a.rs", "1\n2\n3\n4\n", symbols.clone()))
+                .iter()
+                .all(|v| !v.contains("max-file-lines"))
+        );
+        assert_eq!(
+            check(rules, &facts("a.rs", "1\n2\n3\n4\n5\n", symbols)),
+            vec![
+                "a.rs:1 [error] max-file-lines: file has 5 lines (max 4)",
+                "a.rs:3 [error] max-function-lines: `long` has 3 lines (max 2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selectors() {
+        assert!(selects("frontend", "web/app.ts", "frontend"));
+        assert!(selects("src/db", "src/db/pool.rs", "backend"));
+        assert!(selects("src/**/models.rs", "src/api/models.rs", "backend"));
+        assert!(!selects("src/db", "src/dbx/pool.rs", "backend"));
+        assert!(names_package("sqlx", "sqlx::query"));
+        assert!(names_package("lodash", "lodash/fp"));
+        assert!(!names_package("react", "react-dom"));
+    }
+}
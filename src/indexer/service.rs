@@ -1,14 +1,18 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use tokio::sync::{mpsc, Mutex, Semaphore};
 use tokio_util::sync::CancellationToken;
 
+use super::api_contracts::extract_api;
+use super::column_usages::extract_column_usages;
+use super::context::{ImportResolver, MAX_CACHED_BUNDLE_DEPTH};
+use super::db_schema;
 use super::domains::{
     parse_backend_routes, parse_frontend_api_calls, paths_match, run_structural_fingerprinting,
 };
 use super::embedder::EmbedderPool;
-use super::parser::{smart_chunk_file, CodeParser, SupportedLanguage};
+use super::parser::{parse_document, smart_chunk_file, CodeParser, SourceKind, SupportedLanguage};
 use super::pipeline::{self, ParsedFileMetadata};
 use super::watcher::IndexTask;
 use crate::cache::CacheManager;
@@ -19,25 +23,35 @@ use crate::storage::{LanceStorage, SqliteStorage};
 /// Indexer service that processes files and updates storage
 #[derive(Clone)]
 pub struct IndexerService {
+    /// Project root; index keys are stored relative to it
+    root: PathBuf,
     sqlite: SqliteStorage,
     lance: Arc<Mutex<LanceStorage>>,
     embedder: Arc<EmbedderPool>,
     cache: Option<Arc<CacheManager>>,
+    /// Receives the index key of every file reindexed or deleted by `run`
+    updates: Option<mpsc::UnboundedSender<String>>,
+    /// Git syncs from the watcher are reported here
+    progress: Option<Arc<SyncProgress>>,
     parallel_workers: usize,
 }
 
 impl IndexerService {
     pub fn new(
+        root: PathBuf,
         sqlite: SqliteStorage,
         lance: Arc<Mutex<LanceStorage>>,
         embedder: Arc<EmbedderPool>,
         parallel_workers: usize,
     ) -> Self {
         Self {
+            root,
             sqlite,
             lance,
             embedder,
             cache: None,
+            updates: None,
+            progress: None,
             parallel_workers,
         }
     }
@@ -48,6 +62,18 @@ impl IndexerService {
         self
     }
 
+    /// Report changed files (index keys) to `tx`, e.g. for resource subscriptions
+    pub fn with_updates(mut self, tx: mpsc::UnboundedSender<String>) -> Self {
+        self.updates = Some(tx);
+        self
+    }
+
+    /// Report watcher-triggered git syncs in `progress`
+    pub fn with_progress(mut self, progress: Arc<SyncProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
     /// Run the indexer worker that processes tasks from the channel
     /// Uses bounded parallelism to process multiple files concurrently without overloading resources.
     pub async fn run(self, mut rx: mpsc::Receiver<IndexTask>) {
@@ -80,6 +106,9 @@ impl IndexerService {
                             tracing::error!("Failed to delete {:?}: {}", path, e);
                         }
                     }
+                    IndexTask::GitSync { reason, paths } => {
+                        service.sync_paths(&reason, paths).await;
+                    }
                 }
                 // Permit is dropped here, allowing the next task to start
                 drop(permit);
@@ -93,7 +122,7 @@ impl IndexerService {
     pub async fn index_file(&self, path: &Path) -> anyhow::Result<()> {
         let content = tokio::fs::read_to_string(path).await?;
         let hash = blake3::hash(content.as_bytes()).to_hex().to_string();
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = pipeline::relative_key(&self.root, path);
 
         if !self.sqlite.needs_reindex(&path_str, &hash).await? {
             tracing::debug!("Skipping {:?} (unchanged)", path);
@@ -102,28 +131,36 @@ impl IndexerService {
 
         tracing::info!("Indexing: {:?}", path);
 
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        let Some(language) = SupportedLanguage::from_extension(ext) else {
+        let Some(kind) = SourceKind::from_path(path) else {
             return Ok(());
         };
 
-        let mut parser = CodeParser::new();
-        let symbols = parser.parse_symbols(&content, language)?;
-        let chunks = smart_chunk_file(&content, &path_str, language).unwrap_or_else(|e| {
-            tracing::debug!(
-                "smart_chunk_file failed for {}: {}, falling back to parse_chunks",
-                path_str,
-                e
-            );
-            parser
-                .parse_chunks(&content, &path_str, language)
-                .unwrap_or_else(|e2| {
-                    tracing::warn!("parse_chunks also failed for {}: {}", path_str, e2);
-                    Vec::new()
-                })
-        });
-        let all_refs = parser.parse_references(&content, language)?;
-        let imports = parser.parse_imports(&content, language);
+        let (symbols, chunks, all_refs, imports) = match kind {
+            SourceKind::Code(language) => {
+                let mut parser = CodeParser::new();
+                let symbols = parser.parse_symbols(&content, language)?;
+                let chunks = smart_chunk_file(&content, &path_str, language).unwrap_or_else(|e| {
+                    tracing::debug!(
+                        "smart_chunk_file failed for {}: {}, falling back to parse_chunks",
+                        path_str,
+                        e
+                    );
+                    parser
+                        .parse_chunks(&content, &path_str, language)
+                        .unwrap_or_else(|e2| {
+                            tracing::warn!("parse_chunks also failed for {}: {}", path_str, e2);
+                            Vec::new()
+                        })
+                });
+                let all_refs = parser.parse_references(&content, language)?;
+                let imports = parser.parse_imports(&content, language);
+                (symbols, chunks, all_refs, imports)
+            }
+            SourceKind::Document(doc) => {
+                let parsed = parse_document(&content, &path_str, doc);
+                (parsed.symbols, parsed.chunks, Vec::new(), Vec::new())
+            }
+        };
 
         let modified = tokio::fs::metadata(path)
             .await?
@@ -152,46 +189,71 @@ impl IndexerService {
             .await?;
         self.sqlite.clear_dependency_usage(file_id).await?;
 
-        let ecosystem = match language {
-            SupportedLanguage::Rust => "cargo",
-            SupportedLanguage::TypeScript
-            | SupportedLanguage::JavaScript
-            | SupportedLanguage::Vue => "npm",
-            SupportedLanguage::Python => "pip",
-            SupportedLanguage::Go => "go",
-        };
-
-        for import in &imports {
-            if !import.is_relative {
-                let pkg_name = pipeline::extract_package_name(&import.path, language);
-                let items_json = if !import.items.is_empty() {
-                    Some(serde_json::to_string(&import.items).unwrap_or_default())
-                } else {
-                    None
-                };
-                let usage_type = match language {
-                    SupportedLanguage::Rust => "use",
-                    _ => "import",
-                };
+        if let Some(language) = kind.language() {
+            let ecosystem = match language {
+                SupportedLanguage::Rust => "cargo",
+                SupportedLanguage::TypeScript
+                | SupportedLanguage::JavaScript
+                | SupportedLanguage::Vue => "npm",
+                SupportedLanguage::Python => "pip",
+                SupportedLanguage::Go => "go",
+            };
 
-                if let Err(e) = self
-                    .sqlite
-                    .record_dependency_usage(
-                        file_id,
-                        &pkg_name,
-                        ecosystem,
-                        import.line as i32,
-                        usage_type,
-                        &import.path,
-                        items_json.as_deref(),
-                    )
-                    .await
-                {
-                    tracing::warn!("Failed to record dependency usage: {}", e);
+            for import in &imports {
+                if !import.is_relative {
+                    let pkg_name = pipeline::extract_package_name(&import.path, language);
+                    let items_json = if !import.items.is_empty() {
+                        Some(serde_json::to_string(&import.items).unwrap_or_default())
+                    } else {
+                        None
+                    };
+                    let usage_type = match language {
+                        SupportedLanguage::Rust => "use",
+                        _ => "import",
+                    };
+
+                    if let Err(e) = self
+                        .sqlite
+                        .record_dependency_usage(
+                            file_id,
+                            &pkg_name,
+                            ecosystem,
+                            import.line as i32,
+                            usage_type,
+                            &import.path,
+                            items_json.as_deref(),
+                        )
+                        .await
+                    {
+                        tracing::warn!("Failed to record dependency usage: {}", e);
+                    }
                 }
             }
         }
 
+        let edges = kind
+            .language()
+            .map(|language| ImportResolver::new(&self.root).edges(&path_str, &imports, language))
+            .unwrap_or_default();
+        self.sqlite.replace_file_imports(file_id, &edges).await?;
+
+        let column_usages = kind
+            .language()
+            .map(|language| extract_column_usages(&content, language))
+            .unwrap_or_default();
+        self.sqlite
+            .replace_column_usages(file_id, &column_usages)
+            .await?;
+
+        let api = extract_api(&path_str, &content, kind);
+        self.sqlite
+            .replace_api_endpoints(file_id, api.source, &api.endpoints)
+            .await?;
+        self.sqlite
+            .replace_frontend_api_calls(file_id, &api.calls)
+            .await?;
+        self.sqlite.replace_api_bases(file_id, &api.bases).await?;
+
         let stored_symbols = self.sqlite.get_file_symbols(file_id).await?;
 
         for symbol in &stored_symbols {
@@ -217,6 +279,10 @@ impl IndexerService {
             tracing::debug!("Resolved {} references", resolved);
         }
 
+        self.sqlite
+            .replace_chunk_text(&[&path_str], &chunks)
+            .await?;
+
         if !chunks.is_empty() {
             let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
             let embeddings = self.embedder.embed(texts).await?;
@@ -232,36 +298,116 @@ impl IndexerService {
             all_refs.len()
         );
 
+        if db_schema::is_migration(&path_str) {
+            self.refresh_db_schema().await;
+        }
+
         // Feature 012: Invalidate cache for changed file
         if let Some(ref cache) = self.cache {
             cache.invalidate_file(&path_str).await;
             cache.invalidate_all_searches().await;
+            self.invalidate_importers(cache, &path_str).await;
             tracing::debug!("Invalidated cache for {:?}", path);
         }
+        if let Some(ref updates) = self.updates {
+            let _ = updates.send(path_str);
+        }
 
         Ok(())
     }
 
+    /// Drop cached context bundles of every file that (transitively) imports
+    /// `path`, since they embed its content. One extra hop covers definitions
+    /// a bundle pulls in through re-exports.
+    async fn invalidate_importers(&self, cache: &CacheManager, path: &str) {
+        match self
+            .sqlite
+            .get_import_closure(path, MAX_CACHED_BUNDLE_DEPTH + 1, true)
+            .await
+        {
+            Ok(edges) => {
+                let importers: Vec<String> = edges.into_iter().map(|(e, _)| e.from_path).collect();
+                cache.invalidate_bundles(&importers).await;
+            }
+            Err(e) => tracing::warn!("Failed to load importers of {}: {}", path, e),
+        }
+    }
+
     /// Delete a file from indices
     async fn delete_file(&self, path: &Path) -> anyhow::Result<()> {
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = pipeline::relative_key(&self.root, path);
         self.sqlite.delete_file(&path_str).await?;
         {
             let lance = self.lance.lock().await;
             lance.delete_file(&path_str).await?;
         }
+        if db_schema::is_migration(&path_str) {
+            self.refresh_db_schema().await;
+        }
 
         // Feature 012: Invalidate cache for deleted file
         if let Some(ref cache) = self.cache {
             cache.invalidate_file(&path_str).await;
             cache.invalidate_all_searches().await;
+            self.invalidate_importers(cache, &path_str).await;
             tracing::debug!("Invalidated cache for deleted file {:?}", path);
         }
 
         tracing::info!("Deleted: {:?}", path);
+        if let Some(ref updates) = self.updates {
+            let _ = updates.send(path_str);
+        }
         Ok(())
     }
 
+    /// Index the files changed by one git operation as a single sync.
+    pub async fn sync_paths(&self, reason: &str, paths: Vec<PathBuf>) {
+        use futures::StreamExt;
+        use std::sync::atomic::Ordering;
+
+        tracing::info!("{}: syncing {} changed files", reason, paths.len());
+        if let Some(ref p) = self.progress {
+            p.reset();
+            *p.stage.lock().await = reason.to_string();
+            p.files_total.store(paths.len(), Ordering::Relaxed);
+        }
+
+        futures::stream::iter(paths)
+            .for_each_concurrent(self.parallel_workers.max(1), |path| async move {
+                let result = if path.exists() {
+                    self.index_file(&path).await
+                } else {
+                    self.delete_file(&path).await
+                };
+                if let Err(e) = result {
+                    tracing::error!("Git sync failed for {:?}: {}", path, e);
+                }
+                if let Some(ref p) = self.progress {
+                    p.files_scanned.fetch_add(1, Ordering::Relaxed);
+                    p.files_parsed.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .await;
+
+        // Single-file updates keep mined synonyms; a branch switch may move many files
+        if let Some(ref cache) = self.cache {
+            cache.invalidate_mined_synonyms().await;
+        }
+        if let Some(ref p) = self.progress {
+            p.finish();
+        }
+        tracing::info!("{}: sync complete", reason);
+    }
+
+    /// Rebuild the database schema model if the migration files changed.
+    async fn refresh_db_schema(&self) {
+        match db_schema::refresh(&self.sqlite, &self.root).await {
+            Ok(Some(tables)) => tracing::info!("Database schema: {} tables", tables),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Database schema rebuild failed: {}", e),
+        }
+    }
+
     /// Perform initial full sync using SEDA pipeline
     pub async fn full_sync(
         &self,
@@ -291,6 +437,20 @@ impl IndexerService {
         )
         .await?;
 
+        // Bundles inline the content of many files, searches and mined synonyms
+        // span all of them; drop them after a sync
+        if !metadata.is_empty() {
+            if let Some(ref cache) = self.cache {
+                cache.invalidate_all_bundles().await;
+                cache.invalidate_all_searches().await;
+                cache.invalidate_mined_synonyms().await;
+            }
+        }
+
+        // Deleted migrations change the schema too, so this runs before the
+        // no-changes shortcut
+        self.refresh_db_schema().await;
+
         let changed_count = metadata.len();
         if changed_count == 0 {
             tracing::info!("No changes detected");
@@ -306,11 +466,14 @@ impl IndexerService {
 
         let backend_files: Vec<&ParsedFileMetadata> = metadata
             .iter()
+            .filter(|f| f.kind.language().is_some())
             .filter(|f| f.domain == "rust" && f.path.contains("api"))
             .collect();
 
-        let frontend_files: Vec<&ParsedFileMetadata> =
-            metadata.iter().filter(|f| f.domain == "frontend").collect();
+        let frontend_files: Vec<&ParsedFileMetadata> = metadata
+            .iter()
+            .filter(|f| f.kind.language().is_some() && f.domain == "frontend")
+            .collect();
 
         for backend_file in &backend_files {
             let ext = std::path::Path::new(&backend_file.path)
@@ -364,7 +527,7 @@ impl IndexerService {
 
         let fp_files: Vec<(String, String, SupportedLanguage)> = metadata
             .iter()
-            .map(|f| (f.path.clone(), (*f.content).clone(), f.language))
+            .filter_map(|f| Some((f.path.clone(), (*f.content).clone(), f.kind.language()?)))
             .collect();
 
         let fingerprint_links = run_structural_fingerprinting(&fp_files, &self.sqlite)
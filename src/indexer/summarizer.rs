@@ -177,6 +177,7 @@ pub async fn summarize_with_ollama(
 /// Runs until the cancellation token is triggered.
 pub async fn summary_worker(
     config: SummarizerConfig,
+    root: std::path::PathBuf,
     sqlite: crate::storage::SqliteStorage,
     cancel: tokio_util::sync::CancellationToken,
 ) {
@@ -220,7 +221,7 @@ pub async fn summary_worker(
         };
 
         // Read file content
-        let content = match tokio::fs::read_to_string(&file_record.path).await {
+        let content = match tokio::fs::read_to_string(root.join(&file_record.path)).await {
             Ok(c) => c,
             Err(e) => {
                 let _ = sqlite.fail_summary_queue(item.id, &e.to_string()).await;
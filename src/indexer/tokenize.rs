@@ -0,0 +1,141 @@
+//! Code-aware tokenization for the chunk full-text index.
+//!
+//! FTS5's `unicode61` tokenizer keeps `parseConfigFile` as one token, so a search for
+//! "config file" never reaches it. Chunk text is therefore expanded before indexing:
+//! every identifier is emitted whole (lowercased) followed by its camelCase/snake_case
+//! parts. Queries go through the same expansion so both sides agree on the terms.
+
+/// Split an identifier into lowercase words: `parseHTTPRequest_v2` → `parse http request v2`.
+pub fn split_identifier(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' || c == '$' {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let boundary = if let Some(&prev) = i.checked_sub(1).and_then(|p| chars.get(p)) {
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // fooBar | HTTPRequest (split before the last capital of an acronym) | v2x
+            (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_uppercase() && c.is_uppercase() && next_lower)
+                || (prev.is_alphabetic() && c.is_ascii_digit())
+                || (prev.is_ascii_digit() && c.is_alphabetic())
+        } else {
+            false
+        };
+
+        if boundary && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Expand source text into the term stream indexed by `chunks_fts`.
+///
+/// Each identifier-like run (`[A-Za-z0-9_$]`) yields the whole word lowercased and,
+/// if it is compound, its parts. Non-identifier text (punctuation, operators) is dropped.
+pub fn code_terms(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 4);
+    for word in text
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|w| !w.is_empty())
+    {
+        let whole = word.to_lowercase();
+        let parts = split_identifier(word);
+
+        push_term(&mut out, &whole);
+        if parts.len() > 1 {
+            for part in &parts {
+                push_term(&mut out, part);
+            }
+        }
+    }
+    out
+}
+
+/// Build an FTS5 MATCH expression for a free-text query against `chunks_fts`.
+///
+/// Compound identifiers become `"whole" OR ("part1" AND "part2")`, other words are
+/// quoted literally; all groups are OR-ed so BM25 ranks chunks matching more terms higher.
+pub fn fts_match_query(query: &str) -> String {
+    let mut groups: Vec<String> = Vec::new();
+    for word in query
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|w| !w.is_empty())
+    {
+        let whole = quote(&word.to_lowercase());
+        let parts = split_identifier(word);
+        if parts.len() > 1 {
+            let all_parts = parts
+                .iter()
+                .map(|p| quote(p))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            groups.push(format!("({} OR ({}))", whole, all_parts));
+        } else {
+            groups.push(whole);
+        }
+    }
+    groups.join(" OR ")
+}
+
+fn push_term(out: &mut String, term: &str) {
+    if !out.is_empty() {
+        out.push(' ');
+    }
+    out.push_str(term);
+}
+
+fn quote(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_camel_and_snake() {
+        assert_eq!(
+            split_identifier("parseConfigFile"),
+            vec!["parse", "config", "file"]
+        );
+        assert_eq!(
+            split_identifier("load_user_by_id"),
+            vec!["load", "user", "by", "id"]
+        );
+        assert_eq!(split_identifier("HTTPServer"), vec!["http", "server"]);
+        assert_eq!(split_identifier("sha256Sum"), vec!["sha", "256", "sum"]);
+        assert_eq!(split_identifier("plain"), vec!["plain"]);
+    }
+
+    #[test]
+    fn test_code_terms_keeps_whole_identifier() {
+        let terms = code_terms("let cfg = parseConfigFile(path); // \"not found\"");
+        assert_eq!(
+            terms,
+            "let cfg parseconfigfile parse config file path not found"
+        );
+    }
+
+    #[test]
+    fn test_fts_match_query() {
+        assert_eq!(fts_match_query("timeout"), "\"timeout\"");
+        assert_eq!(
+            fts_match_query("retry_count exceeded"),
+            "(\"retry_count\" OR (\"retry\" AND \"count\")) OR \"exceeded\""
+        );
+        assert_eq!(fts_match_query("  ()  "), "");
+    }
+}
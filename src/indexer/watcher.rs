@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
 use std::time::Duration;
@@ -8,13 +9,21 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
-use super::parser::SupportedLanguage;
+use super::domains::DomainConfig;
+use super::git_watch::{Flush, GitWatch};
+use super::parser::{DocumentKind, SourceKind};
 
 /// Task for the indexer worker
 #[derive(Debug, Clone)]
 pub enum IndexTask {
     Reindex(PathBuf),
     Delete(PathBuf),
+    /// Files changed by one git operation (checkout, rebase, stash pop),
+    /// indexed as a single sync
+    GitSync {
+        reason: String,
+        paths: Vec<PathBuf>,
+    },
 }
 
 /// gofer configuration from config.toml
@@ -26,6 +35,24 @@ pub struct GoferConfig {
     pub embedding: EmbeddingConfig,
     #[serde(default)]
     pub summarizer: SummarizerTomlConfig,
+    #[serde(default)]
+    pub feedback: FeedbackConfig,
+    #[serde(default)]
+    pub ask: AskConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// Path prefixes per domain; see [`GoferConfig::domain_config`]
+    #[serde(default)]
+    pub domains: DomainConfig,
+}
+
+impl GoferConfig {
+    /// Domain detection with the project's `[domains]` applied to the defaults
+    pub fn domain_config(&self) -> DomainConfig {
+        DomainConfig::with_overrides(&self.domains)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
@@ -34,6 +61,80 @@ pub struct IndexerConfig {
     pub ignore: Vec<String>,
     #[serde(default)]
     pub parallel_workers: Option<usize>,
+    /// Non-code files indexed next to source code
+    #[serde(default)]
+    pub documents: DocumentsConfig,
+}
+
+impl IndexerConfig {
+    /// `ignore` plus globs for document types switched off in `[indexer.documents]`
+    pub fn ignore_patterns(&self) -> Vec<String> {
+        let mut patterns = self.ignore.clone();
+        for kind in DocumentKind::ALL {
+            if !self.documents.enabled(kind) {
+                patterns.extend(kind.extensions().iter().map(|ext| format!("*.{}", ext)));
+            }
+        }
+        patterns
+    }
+}
+
+/// `[indexer.documents]`: which document types become symbols and chunks
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DocumentsConfig {
+    /// Headings of `.md`/`.markdown`/`.mdx` files
+    #[serde(default = "default_true")]
+    pub markdown: bool,
+    /// Tables and key paths of `.toml` files
+    #[serde(default = "default_true")]
+    pub toml: bool,
+    /// Key paths of `.yml`/`.yaml` files
+    #[serde(default = "default_true")]
+    pub yaml: bool,
+    /// Key paths of `.json` files (lock files are always skipped)
+    #[serde(default = "default_true")]
+    pub json: bool,
+    /// Statements of `.sql` files; tables and columns become symbols
+    #[serde(default = "default_true")]
+    pub sql: bool,
+    /// Types and root operation fields of `.graphql`/`.gql` schemas
+    #[serde(default = "default_true")]
+    pub graphql: bool,
+    /// Messages, enums, services and rpcs of `.proto` files
+    #[serde(default = "default_true")]
+    pub proto: bool,
+}
+
+impl DocumentsConfig {
+    pub fn enabled(&self, kind: DocumentKind) -> bool {
+        match kind {
+            DocumentKind::Markdown => self.markdown,
+            DocumentKind::Toml => self.toml,
+            DocumentKind::Yaml => self.yaml,
+            DocumentKind::Json => self.json,
+            DocumentKind::Sql => self.sql,
+            DocumentKind::GraphQl => self.graphql,
+            DocumentKind::Proto => self.proto,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DocumentsConfig {
+    fn default() -> Self {
+        Self {
+            markdown: true,
+            toml: true,
+            yaml: true,
+            json: true,
+            sql: true,
+            graphql: true,
+            proto: true,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -118,6 +219,138 @@ impl Default for SummarizerTomlConfig {
     }
 }
 
+/// Implicit relevance feedback: files read after a search rank higher next time
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FeedbackConfig {
+    /// Set to false to stop recording clicks and ignore learned boosts
+    #[serde(default = "default_feedback_enabled")]
+    pub enabled: bool,
+    /// A read counts as a click if it follows the search within this many seconds
+    #[serde(default = "default_feedback_window_secs")]
+    pub window_secs: u64,
+    /// Click scores halve after this many days without reinforcement
+    #[serde(default = "default_feedback_half_life_days")]
+    pub half_life_days: f64,
+}
+
+fn default_feedback_enabled() -> bool {
+    true
+}
+fn default_feedback_window_secs() -> u64 {
+    300
+}
+fn default_feedback_half_life_days() -> f64 {
+    14.0
+}
+
+impl Default for FeedbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_feedback_enabled(),
+            window_secs: default_feedback_window_secs(),
+            half_life_days: default_feedback_half_life_days(),
+        }
+    }
+}
+
+/// Chat endpoint used by the `ask` tool
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AskConfig {
+    /// `openai` (any OpenAI-compatible server, including Ollama's /v1) or `ollama` (native /api/chat)
+    #[serde(default = "default_ask_api")]
+    pub api: String,
+    /// Base URL: `http://localhost:11434/v1` for OpenAI-style, `http://localhost:11434` for native Ollama
+    #[serde(default = "default_ask_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_ask_model")]
+    pub model: String,
+    /// Environment variable holding a bearer token (optional)
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    /// Token budget for retrieved code in the prompt
+    #[serde(default = "default_ask_context_tokens")]
+    pub max_context_tokens: usize,
+    #[serde(default = "default_ask_answer_tokens")]
+    pub max_answer_tokens: usize,
+    #[serde(default = "default_ask_temperature")]
+    pub temperature: f64,
+    #[serde(default = "default_ask_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_ask_api() -> String {
+    "openai".to_string()
+}
+fn default_ask_endpoint() -> String {
+    "http://localhost:11434/v1".to_string()
+}
+fn default_ask_model() -> String {
+    "qwen2.5-coder:7b".to_string()
+}
+fn default_ask_context_tokens() -> usize {
+    6000
+}
+fn default_ask_answer_tokens() -> usize {
+    512
+}
+fn default_ask_temperature() -> f64 {
+    0.2
+}
+fn default_ask_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for AskConfig {
+    fn default() -> Self {
+        Self {
+            api: default_ask_api(),
+            endpoint: default_ask_endpoint(),
+            model: default_ask_model(),
+            api_key_env: None,
+            max_context_tokens: default_ask_context_tokens(),
+            max_answer_tokens: default_ask_answer_tokens(),
+            temperature: default_ask_temperature(),
+            timeout_secs: default_ask_timeout_secs(),
+        }
+    }
+}
+
+/// MCP over HTTP (Streamable HTTP + legacy SSE), read from the global ~/.gofer/config.toml
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Listen address; keep it on loopback unless the port is otherwise protected
+    #[serde(default = "default_http_bind")]
+    pub bind: String,
+    /// Bearer token clients must send; generated into ~/.gofer/http_token if unset
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+fn default_http_bind() -> String {
+    "127.0.0.1:10988".to_string()
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_http_bind(),
+            token: None,
+        }
+    }
+}
+
+/// Frontend API call detection
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct ApiConfig {
+    /// Functions that make HTTP requests but are defined outside the project
+    /// or not recognized as wrappers, e.g. `["apiFetch", "request"]`
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+}
+
 /// Load gofer configuration from .gofer/config.toml
 pub fn load_config(gofer_dir: &Path) -> GoferConfig {
     let config_path = gofer_dir.join("config.toml");
@@ -161,12 +394,9 @@ fn build_gitignore(root: &Path, extra_ignores: &[String]) -> Gitignore {
     builder.build().unwrap_or_else(|_| Gitignore::empty())
 }
 
-/// Check if a file should be indexed based on extension
+/// Check if a file should be indexed: source code or a document type
 fn should_index(path: &Path) -> bool {
-    path.extension()
-        .and_then(|e| e.to_str())
-        .and_then(SupportedLanguage::from_extension)
-        .is_some()
+    SourceKind::from_path(path).is_some()
 }
 
 pub fn find_watchable_dirs(root: &Path, extra_ignores: &[String]) -> Vec<PathBuf> {
@@ -242,8 +472,39 @@ pub async fn start_watcher(
             }
         }
 
+        // Git metadata is watched separately so branch switches and rebases can be
+        // indexed as one sync instead of a flood of single-file tasks
+        let mut git = GitWatch::open(&root_path);
+        // Git reports paths under the canonical workdir, which differs from a
+        // symlinked project root
+        let canonical_root = root_path
+            .canonicalize()
+            .unwrap_or_else(|_| root_path.clone());
+        if let Some(ref g) = git {
+            for (dir, recursive) in g.watch_targets() {
+                let mode = if recursive {
+                    RecursiveMode::Recursive
+                } else {
+                    RecursiveMode::NonRecursive
+                };
+                if let Err(e) = debouncer.watcher().watch(&dir, mode) {
+                    tracing::debug!("Watcher failed to register git dir {:?}: {}", dir, e);
+                }
+            }
+        }
+
         tracing::info!("File watcher started for: {:?}", root_path);
 
+        let send = |task: IndexTask| {
+            tracing::debug!("File event: {:?}", task);
+            // Use blocking_send inside spawn_blocking
+            let sent = task_tx.blocking_send(task).is_ok();
+            if !sent {
+                tracing::warn!("Indexer channel closed");
+            }
+            sent
+        };
+
         // Process events in a loop
         loop {
             if cancel.is_cancelled() {
@@ -251,12 +512,30 @@ pub async fn start_watcher(
                 break;
             }
 
+            // Poll faster while a git operation holds file events
+            let wait = if git.as_ref().is_some_and(|g| g.is_paused()) {
+                Duration::from_millis(250)
+            } else {
+                Duration::from_secs(1)
+            };
+
             // Use recv_timeout so we can check cancellation periodically
-            match rx.recv_timeout(Duration::from_secs(1)) {
+            match rx.recv_timeout(wait) {
                 Ok(Ok(events)) => {
+                    // Git events first, so worktree changes in the same batch are held
+                    if let Some(g) = git.as_mut() {
+                        for event in events.iter().filter(|e| g.is_git_path(&e.path)) {
+                            g.observe_git(&event.path);
+                        }
+                    }
+
                     for event in events {
                         let path = event.path;
 
+                        if git.as_ref().is_some_and(|g| g.is_git_path(&path)) {
+                            continue;
+                        }
+
                         // Skip ignored files
                         if gitignore.matched(&path, path.is_dir()).is_ignore() {
                             continue;
@@ -275,18 +554,11 @@ pub async fn start_watcher(
                             continue;
                         }
 
-                        // Determine task type based on file existence
-                        let task = if path.exists() {
-                            IndexTask::Reindex(path.clone())
-                        } else {
-                            IndexTask::Delete(path.clone())
-                        };
-
-                        tracing::debug!("File event: {:?}", task);
+                        if git.as_mut().is_some_and(|g| g.hold(&path)) {
+                            continue;
+                        }
 
-                        // Use blocking_send inside spawn_blocking
-                        if task_tx.blocking_send(task).is_err() {
-                            tracing::warn!("Indexer channel closed");
+                        if !send(file_task(path)) {
                             return;
                         }
                     }
@@ -300,10 +572,52 @@ pub async fn start_watcher(
                     break;
                 }
             }
+
+            match git.as_mut().and_then(|g| g.poll()) {
+                Some(Flush::Replay(paths)) => {
+                    for path in paths {
+                        if !send(file_task(path)) {
+                            return;
+                        }
+                    }
+                }
+                Some(Flush::Sync { reason, paths }) => {
+                    let paths: BTreeSet<PathBuf> = paths
+                        .into_iter()
+                        .filter_map(|p| under_root(p, &root_path, &canonical_root))
+                        .filter(|p| should_index(p) && !gitignore.matched(p, false).is_ignore())
+                        .collect();
+                    let paths: Vec<PathBuf> = paths.into_iter().collect();
+                    tracing::info!("{}: {} indexable files changed", reason, paths.len());
+                    if !paths.is_empty() && !send(IndexTask::GitSync { reason, paths }) {
+                        return;
+                    }
+                }
+                None => {}
+            }
         }
     }); // No await, spawn_blocking detaches. We just let it run until cancelled.
 }
 
+/// `path` under the project root as the indexer sees it, also when it was
+/// reported under the root's canonical form; `None` outside the project.
+fn under_root(path: PathBuf, root: &Path, canonical_root: &Path) -> Option<PathBuf> {
+    if path.starts_with(root) {
+        return Some(path);
+    }
+    let relative = path.strip_prefix(canonical_root).ok()?;
+    Some(root.join(relative))
+}
+
+/// Reindex or delete depending on whether the file still exists.
+fn file_task(path: PathBuf) -> IndexTask {
+    if path.exists() {
+        IndexTask::Reindex(path)
+    } else {
+        IndexTask::Delete(path)
+    }
+}
+
 /// Scan directory and return all indexable files.
 /// Uses `ignore::WalkBuilder` for parallel traversal with built-in .gitignore support.
 pub fn scan_directory(root: &Path, extra_ignores: &[String]) -> Vec<PathBuf> {
@@ -321,22 +635,7 @@ pub fn scan_directory(root: &Path, extra_ignores: &[String]) -> Vec<PathBuf> {
                 .unwrap_or(4),
         );
 
-    // Добавить пользовательские ignore-паттерны через overrides (см. выше)
-    // builder.add_custom_ignore_filename(pattern); // Удалено - неправильное использование
-
-    // Также добавить через Override для glob-паттернов
-    let mut overrides = ignore::overrides::OverrideBuilder::new(root);
-    for pattern in extra_ignores {
-        // OverrideBuilder treats normal patterns as allow-lists.
-        // To ignore, we need to prefix the pattern with '!'
-        let ignore_pattern = if pattern.starts_with('!') {
-            pattern.clone()
-        } else {
-            format!("!{}", pattern)
-        };
-        let _ = overrides.add(&ignore_pattern);
-    }
-    if let Ok(ov) = overrides.build() {
+    if let Some(ov) = build_overrides(root, extra_ignores) {
         builder.overrides(ov);
     }
 
@@ -352,4 +651,49 @@ pub fn scan_directory(root: &Path, extra_ignores: &[String]) -> Vec<PathBuf> {
     files
 }
 
+/// Пользовательские ignore-паттерны из конфига в виде Override.
+fn build_overrides(root: &Path, extra_ignores: &[String]) -> Option<ignore::overrides::Override> {
+    let mut overrides = ignore::overrides::OverrideBuilder::new(root);
+    for pattern in extra_ignores {
+        // OverrideBuilder treats normal patterns as allow-lists.
+        // To ignore, we need to prefix the pattern with '!'
+        let ignore_pattern = if pattern.starts_with('!') {
+            pattern.clone()
+        } else {
+            format!("!{}", pattern)
+        };
+        let _ = overrides.add(&ignore_pattern);
+    }
+    overrides.build().ok()
+}
+
+/// Отфильтровать готовый список путей (например, кандидатов из git status)
+/// теми же правилами, что и `scan_directory`: скрытые файлы, пользовательские
+/// ignore-паттерны и `should_index`. `.gitignore` здесь не нужен — git status
+/// уже не возвращает игнорируемые файлы.
+pub fn filter_scannable(
+    root: &Path,
+    extra_ignores: &[String],
+    paths: Vec<PathBuf>,
+) -> Vec<PathBuf> {
+    let overrides = build_overrides(root, extra_ignores);
+    paths
+        .into_iter()
+        .filter(|path| {
+            let Ok(rel) = path.strip_prefix(root) else {
+                return false;
+            };
+            let hidden = rel
+                .components()
+                .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+            !hidden
+                && path.is_file()
+                && should_index(path)
+                && overrides
+                    .as_ref()
+                    .is_none_or(|ov| !ov.matched(rel, false).is_ignore())
+        })
+        .collect()
+}
+
 // scan_recursive больше не используется — заменён на ignore::WalkBuilder
@@ -0,0 +1,840 @@
+//! MCP over HTTP — Streamable HTTP (`/mcp`) and the legacy HTTP+SSE transport
+//! (`GET /sse` + `POST /messages`) for clients that cannot spawn the stdio bridge.
+//!
+//! Uses raw `TcpListener` like the metrics endpoint: one request per connection,
+//! SSE streams stay open. Every request needs `Authorization: Bearer <token>` and
+//! browser origins other than localhost are rejected. A session is bound to one
+//! registered project (`?project=/path` or `X-Gofer-Project` on `initialize` /
+//! `GET /sse`); its MCP requests go through the same handler as the Unix socket,
+//! daemon management methods are refused, and broadcast notifications are queued
+//! for the session's SSE stream.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+
+use super::protocol::{DaemonRequest, DaemonResponse};
+use super::server::{run_request, InFlight};
+use crate::daemon::resources;
+use crate::daemon::state::DaemonState;
+use crate::error::GoferError;
+use crate::indexer::HttpConfig;
+
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+/// Time a client gets to send the whole request, body included
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Sessions without requests or an open stream for this long are dropped
+const SESSION_IDLE: Duration = Duration::from_secs(3600);
+const KEEPALIVE: Duration = Duration::from_secs(15);
+const SESSION_HEADER: &str = "mcp-session-id";
+
+struct Session {
+    id: String,
+    project_path: String,
+    /// Resource-subscription identity, shared with Unix socket connections
+    conn_id: u64,
+    outbox_tx: mpsc::Sender<String>,
+    /// Taken by the SSE stream while one is attached
+    outbox_rx: Mutex<Option<mpsc::Receiver<String>>>,
+    cancel: CancellationToken,
+    /// Requests running on behalf of this session, for `notifications/cancelled`
+    inflight: InFlight,
+    last_seen: std::sync::Mutex<Instant>,
+}
+
+impl Session {
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+}
+
+#[derive(Default)]
+struct Sessions(RwLock<HashMap<String, Arc<Session>>>);
+
+impl Sessions {
+    async fn get(&self, id: &str) -> Option<Arc<Session>> {
+        self.0.read().await.get(id).cloned()
+    }
+
+    async fn remove(&self, id: &str, state: &DaemonState) -> bool {
+        let session = self.0.write().await.remove(id);
+        if let Some(ref s) = session {
+            s.cancel.cancel();
+            s.inflight.cancel_all();
+            state.resource_subscriptions.disconnect(s.conn_id);
+        }
+        session.is_some()
+    }
+
+    async fn prune_idle(&self, state: &DaemonState) {
+        let idle: Vec<String> = self
+            .0
+            .read()
+            .await
+            .values()
+            .filter(|s| s.idle_for() > SESSION_IDLE)
+            .map(|s| s.id.clone())
+            .collect();
+        for id in idle {
+            tracing::info!("MCP HTTP: dropping idle session {}", id);
+            self.remove(&id, state).await;
+        }
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Lowercased header names
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    /// Project a new session is bound to.
+    fn project(&self) -> Option<String> {
+        self.query
+            .get("project")
+            .map(String::as_str)
+            .or(self.header("x-gofer-project"))
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+    }
+}
+
+/// Serve MCP over HTTP on `config.bind` until daemon shutdown.
+pub async fn serve_mcp_http(state: Arc<DaemonState>, config: HttpConfig) {
+    let token = match resolve_token(&state.gofer_home, &config) {
+        Ok(t) => Arc::new(t),
+        Err(e) => {
+            tracing::error!("MCP HTTP: cannot set up auth token: {}", e);
+            return;
+        }
+    };
+    let listener = match TcpListener::bind(&config.bind).await {
+        Ok(l) => l,
+        Err(e) => {
+            tracing::warn!("MCP HTTP: failed to bind {}: {}", config.bind, e);
+            return;
+        }
+    };
+    if listener
+        .local_addr()
+        .map(|a| !a.ip().is_loopback())
+        .unwrap_or(false)
+    {
+        tracing::warn!(
+            "MCP HTTP: listening on non-loopback address {}; only the bearer token protects it",
+            config.bind
+        );
+    }
+    tracing::info!(
+        "MCP HTTP: listening on http://{}/mcp (legacy SSE at /sse)",
+        config.bind
+    );
+
+    let sessions = Arc::new(Sessions::default());
+    let shutdown = state.shutdown_token.clone();
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                tracing::info!("MCP HTTP: shutting down");
+                break;
+            }
+            accept = listener.accept() => {
+                let Ok((stream, _)) = accept else { continue };
+                let state = state.clone();
+                let sessions = sessions.clone();
+                let token = token.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_http(stream, state, sessions, &token).await {
+                        tracing::debug!("MCP HTTP: connection error: {}", e);
+                    }
+                });
+            }
+        }
+    }
+
+    let ids: Vec<String> = sessions.0.read().await.keys().cloned().collect();
+    for id in ids {
+        sessions.remove(&id, &state).await;
+    }
+}
+
+/// The configured token, else the one in ~/.gofer/http_token (created on first use).
+fn resolve_token(gofer_home: &Path, config: &HttpConfig) -> Result<String> {
+    if let Some(token) = config.token.as_ref().filter(|t| !t.is_empty()) {
+        return Ok(token.clone());
+    }
+    let path = gofer_home.join("http_token");
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        let existing = existing.trim();
+        if !existing.is_empty() {
+            return Ok(existing.to_string());
+        }
+    }
+
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(token.as_bytes())?;
+    tracing::info!("MCP HTTP: generated bearer token in {:?}", path);
+    Ok(token)
+}
+
+async fn handle_http(
+    stream: TcpStream,
+    state: Arc<DaemonState>,
+    sessions: Arc<Sessions>,
+    token: &str,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let req = match tokio::time::timeout(READ_TIMEOUT, read_request(BufReader::new(reader))).await {
+        Ok(Ok(r)) => r,
+        Ok(Err(e)) => {
+            return respond(
+                &mut writer,
+                "400 Bad Request",
+                &[],
+                "text/plain",
+                &e.to_string(),
+            )
+            .await
+        }
+        Err(_) => {
+            return respond(
+                &mut writer,
+                "408 Request Timeout",
+                &[],
+                "text/plain",
+                "Request not received in time",
+            )
+            .await
+        }
+    };
+
+    // DNS rebinding protection: browsers always send Origin
+    if let Some(origin) = req.header("origin") {
+        if !origin_allowed(origin) {
+            return respond(
+                &mut writer,
+                "403 Forbidden",
+                &[],
+                "text/plain",
+                "Origin not allowed",
+            )
+            .await;
+        }
+    }
+    let authorized = req
+        .header("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|t| constant_time_eq(t.trim().as_bytes(), token.as_bytes()));
+    if !authorized {
+        return respond(
+            &mut writer,
+            "401 Unauthorized",
+            &[("WWW-Authenticate", "Bearer")],
+            "text/plain",
+            "Missing or invalid bearer token",
+        )
+        .await;
+    }
+
+    match (req.method.as_str(), req.path.as_str()) {
+        ("POST", "/mcp") => post_mcp(&req, &mut writer, &state, &sessions).await,
+        ("GET", "/mcp") => {
+            let Some(session) = session_from(&req, &sessions).await else {
+                return respond(
+                    &mut writer,
+                    "404 Not Found",
+                    &[],
+                    "text/plain",
+                    "Unknown session",
+                )
+                .await;
+            };
+            open_stream(&mut writer, &session, &state, None).await
+        }
+        ("DELETE", "/mcp") => {
+            let id = req.header(SESSION_HEADER).unwrap_or_default();
+            if sessions.remove(id, &state).await {
+                respond(&mut writer, "204 No Content", &[], "text/plain", "").await
+            } else {
+                respond(
+                    &mut writer,
+                    "404 Not Found",
+                    &[],
+                    "text/plain",
+                    "Unknown session",
+                )
+                .await
+            }
+        }
+        ("GET", "/sse") => legacy_sse(&req, &mut writer, &state, &sessions).await,
+        ("POST", "/messages") => legacy_post(&req, &mut writer, &state, &sessions).await,
+        _ => respond(&mut writer, "404 Not Found", &[], "text/plain", "Not found").await,
+    }
+}
+
+/// Streamable HTTP: JSON-RPC message(s) in, JSON response(s) out.
+async fn post_mcp(
+    req: &HttpRequest,
+    writer: &mut OwnedWriteHalf,
+    state: &Arc<DaemonState>,
+    sessions: &Arc<Sessions>,
+) -> Result<()> {
+    let (messages, is_batch) = match serde_json::from_slice::<Value>(&req.body) {
+        Ok(Value::Array(batch)) => (batch, true),
+        Ok(single) => (vec![single], false),
+        Err(e) => {
+            let (code, msg) = GoferError::ParseError(e.to_string()).into_rpc();
+            let body = serde_json::to_string(&DaemonResponse::error(Value::Null, code, msg))?;
+            return respond(writer, "400 Bad Request", &[], "application/json", &body).await;
+        }
+    };
+
+    let session = match req.header(SESSION_HEADER) {
+        Some(id) => match sessions.get(id).await {
+            Some(s) => s,
+            None => {
+                return respond(
+                    writer,
+                    "404 Not Found",
+                    &[],
+                    "text/plain",
+                    "Unknown session",
+                )
+                .await
+            }
+        },
+        None => {
+            if !messages.iter().any(|m| m["method"] == "initialize") {
+                return respond(
+                    writer,
+                    "400 Bad Request",
+                    &[],
+                    "text/plain",
+                    "Missing Mcp-Session-Id header",
+                )
+                .await;
+            }
+            match create_session(req, state, sessions).await {
+                Ok(s) => s,
+                Err(e) => {
+                    return respond(writer, "400 Bad Request", &[], "text/plain", &e.to_string())
+                        .await
+                }
+            }
+        }
+    };
+    session.touch();
+
+    let responses = process_messages(messages, &session, state).await;
+    let headers = [("Mcp-Session-Id", session.id.as_str())];
+    if responses.is_empty() {
+        return respond(writer, "202 Accepted", &headers, "text/plain", "").await;
+    }
+    let body = if is_batch {
+        serde_json::to_string(&responses)?
+    } else {
+        serde_json::to_string(&responses[0])?
+    };
+    respond(writer, "200 OK", &headers, "application/json", &body).await
+}
+
+/// Legacy transport: the stream announces where to POST, responses arrive as events.
+async fn legacy_sse(
+    req: &HttpRequest,
+    writer: &mut OwnedWriteHalf,
+    state: &Arc<DaemonState>,
+    sessions: &Arc<Sessions>,
+) -> Result<()> {
+    let session = match create_session(req, state, sessions).await {
+        Ok(s) => s,
+        Err(e) => {
+            return respond(writer, "400 Bad Request", &[], "text/plain", &e.to_string()).await
+        }
+    };
+    let endpoint = format!("/messages?session_id={}", session.id);
+    let result = open_stream(writer, &session, state, Some(&endpoint)).await;
+    // Legacy sessions live exactly as long as their stream
+    sessions.remove(&session.id, state).await;
+    result
+}
+
+async fn legacy_post(
+    req: &HttpRequest,
+    writer: &mut OwnedWriteHalf,
+    state: &Arc<DaemonState>,
+    sessions: &Arc<Sessions>,
+) -> Result<()> {
+    let Some(session) = session_from(req, sessions).await else {
+        return respond(
+            writer,
+            "404 Not Found",
+            &[],
+            "text/plain",
+            "Unknown session",
+        )
+        .await;
+    };
+    let messages = match serde_json::from_slice::<Value>(&req.body) {
+        Ok(Value::Array(batch)) => batch,
+        Ok(single) => vec![single],
+        Err(e) => {
+            return respond(writer, "400 Bad Request", &[], "text/plain", &e.to_string()).await
+        }
+    };
+    session.touch();
+    for response in process_messages(messages, &session, state).await {
+        let _ = session.outbox_tx.send(response.to_string()).await;
+    }
+    respond(writer, "202 Accepted", &[], "text/plain", "").await
+}
+
+async fn session_from(req: &HttpRequest, sessions: &Sessions) -> Option<Arc<Session>> {
+    let id = req
+        .header(SESSION_HEADER)
+        .or(req.query.get("session_id").map(String::as_str))?;
+    sessions.get(id).await
+}
+
+/// Activate an already registered project and start queueing broadcast
+/// notifications for the new session. Registering stays with `gofer init`.
+async fn create_session(
+    req: &HttpRequest,
+    state: &Arc<DaemonState>,
+    sessions: &Arc<Sessions>,
+) -> Result<Arc<Session>> {
+    sessions.prune_idle(state).await;
+
+    let project = req.project().ok_or_else(|| {
+        anyhow::anyhow!("Missing project: pass ?project=/path or an X-Gofer-Project header")
+    })?;
+    if !Path::new(&project).is_dir() {
+        anyhow::bail!("Project directory not found: {}", project);
+    }
+    if state.registry.get_by_path(&project).await?.is_none() {
+        anyhow::bail!(
+            "Project not registered: {}. Run 'gofer init' in the project directory first.",
+            project
+        );
+    }
+    state.activate_project(&project, true, true).await?;
+
+    let (outbox_tx, outbox_rx) = mpsc::channel::<String>(256);
+    let session = Arc::new(Session {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        project_path: project,
+        conn_id: state.resource_subscriptions.connect(),
+        outbox_tx,
+        outbox_rx: Mutex::new(Some(outbox_rx)),
+        cancel: CancellationToken::new(),
+        inflight: InFlight::default(),
+        last_seen: std::sync::Mutex::new(Instant::now()),
+    });
+
+    let mut notify_rx = state.notify_tx.subscribe();
+    let subscriptions = state.resource_subscriptions.clone();
+    let forward = session.clone();
+    tokio::spawn(async move {
+        loop {
+            let msg = tokio::select! {
+                _ = forward.cancel.cancelled() => break,
+                msg = notify_rx.recv() => msg,
+            };
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            };
+            if let Some(msg) = resources::route_notification(msg, forward.conn_id, &subscriptions) {
+                // Dropped if no stream drains the queue
+                let _ = forward.outbox_tx.try_send(msg);
+            }
+        }
+    });
+
+    tracing::info!(
+        "MCP HTTP: session {} -> {}",
+        session.id,
+        session.project_path
+    );
+    sessions
+        .0
+        .write()
+        .await
+        .insert(session.id.clone(), session.clone());
+    Ok(session)
+}
+
+/// Run JSON-RPC messages through the daemon handler; returns the responses.
+async fn process_messages(
+    messages: Vec<Value>,
+    session: &Session,
+    state: &Arc<DaemonState>,
+) -> Vec<Value> {
+    let mut responses = Vec::new();
+    for mut msg in messages {
+        // Client responses (e.g. to roots/list) need no answer
+        if msg.get("method").is_none() {
+            continue;
+        }
+        match msg.get_mut("params") {
+            Some(Value::Object(params)) => {
+                params.insert(
+                    "project_path".to_string(),
+                    Value::String(session.project_path.clone()),
+                );
+            }
+            _ => msg["params"] = json!({ "project_path": session.project_path }),
+        }
+        let response = match serde_json::from_value::<DaemonRequest>(msg) {
+            Ok(req) if !mcp_method(&req.method) => {
+                // Daemon management stays on the local socket
+                let Some(id) = req.id else {
+                    continue;
+                };
+                let (code, msg) = GoferError::MethodNotFound(req.method).into_rpc();
+                DaemonResponse::error(id, code, msg)
+            }
+            Ok(req) => {
+                // Progress notifications go out on the session's stream
+                let scope = session
+                    .inflight
+                    .scope(&req, Some(session.outbox_tx.clone()));
+                match run_request(req, state, session.conn_id, scope, &session.inflight).await {
+                    Some(resp) => resp,
+                    None => continue,
+                }
+            }
+            Err(e) => {
+                let (code, msg) = GoferError::ParseError(e.to_string()).into_rpc();
+                DaemonResponse::error(Value::Null, code, msg)
+            }
+        };
+        if let Ok(value) = serde_json::to_value(&response) {
+            responses.push(value);
+        }
+    }
+    responses
+}
+
+/// Methods HTTP sessions may call: the MCP protocol, not the daemon's own API.
+fn mcp_method(method: &str) -> bool {
+    matches!(method, "initialize" | "ping")
+        || ["notifications/", "tools/", "resources/", "prompts/"]
+            .iter()
+            .any(|prefix| method.starts_with(prefix))
+}
+
+/// Hold an SSE stream open, writing queued messages as `message` events.
+async fn open_stream(
+    writer: &mut OwnedWriteHalf,
+    session: &Session,
+    state: &DaemonState,
+    endpoint: Option<&str>,
+) -> Result<()> {
+    let Some(mut rx) = session.outbox_rx.lock().await.take() else {
+        return respond(
+            writer,
+            "409 Conflict",
+            &[],
+            "text/plain",
+            "A stream is already open for this session",
+        )
+        .await;
+    };
+
+    let head = format!(
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/event-stream\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: keep-alive\r\n\
+         Mcp-Session-Id: {}\r\n\
+         \r\n",
+        session.id
+    );
+    let mut result = writer.write_all(head.as_bytes()).await;
+    if let (Ok(()), Some(endpoint)) = (&result, endpoint) {
+        result = write_event(writer, "endpoint", endpoint).await;
+    }
+
+    let mut keepalive = tokio::time::interval(KEEPALIVE);
+    while result.is_ok() {
+        tokio::select! {
+            _ = session.cancel.cancelled() => break,
+            _ = state.shutdown_token.cancelled() => break,
+            msg = rx.recv() => match msg {
+                Some(msg) => result = write_event(writer, "message", &msg).await,
+                None => break,
+            },
+            _ = keepalive.tick() => {
+                session.touch();
+                result = async {
+                    writer.write_all(b": ping\n\n").await?;
+                    writer.flush().await
+                }
+                .await;
+            }
+        }
+    }
+
+    *session.outbox_rx.lock().await = Some(rx);
+    Ok(())
+}
+
+async fn write_event(writer: &mut OwnedWriteHalf, event: &str, data: &str) -> std::io::Result<()> {
+    writer
+        .write_all(format!("event: {}\ndata: {}\n\n", event, data).as_bytes())
+        .await?;
+    writer.flush().await
+}
+
+async fn respond(
+    writer: &mut OwnedWriteHalf,
+    status: &str,
+    headers: &[(&str, &str)],
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    let mut out = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    for (name, value) in headers {
+        out.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    out.push_str("\r\n");
+    out.push_str(body);
+    writer.write_all(out.as_bytes()).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+async fn read_request<R: tokio::io::AsyncRead + Unpin>(
+    mut reader: BufReader<R>,
+) -> Result<HttpRequest> {
+    let mut line = String::new();
+    let mut header_bytes = read_header_line(&mut reader, &mut line, 0).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+    if method.is_empty() || target.is_empty() {
+        anyhow::bail!("Malformed request line");
+    }
+
+    let mut headers = HashMap::new();
+    loop {
+        let n = read_header_line(&mut reader, &mut line, header_bytes).await?;
+        header_bytes += n;
+        if n == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    if headers
+        .get("transfer-encoding")
+        .is_some_and(|te| te.contains("chunked"))
+    {
+        anyhow::bail!("Chunked request bodies are not supported; send Content-Length");
+    }
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        anyhow::bail!("Body too large");
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query(query)),
+        None => (target, HashMap::new()),
+    };
+    Ok(HttpRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    })
+}
+
+/// Read one line of the request head into `line`; `used` bytes of the head are
+/// read already, and a line that would exceed `MAX_HEADER_BYTES` is an error
+/// before it is buffered.
+async fn read_header_line<R: tokio::io::AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    line: &mut String,
+    used: usize,
+) -> Result<usize> {
+    line.clear();
+    let limit = MAX_HEADER_BYTES.saturating_sub(used);
+    let n = (&mut *reader).take(limit as u64).read_line(line).await?;
+    if n == limit && !line.ends_with('\n') {
+        anyhow::bail!("Headers too large");
+    }
+    Ok(n)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (escaped, bytes[i]) {
+            (Some(b), _) => {
+                out.push(b);
+                i += 3;
+            }
+            (None, b'+') => {
+                out.push(b' ');
+                i += 1;
+            }
+            (None, b) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Only pages served from this machine may call the server.
+fn origin_allowed(origin: &str) -> bool {
+    let authority = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let authority = authority.split('/').next().unwrap_or_default();
+    let host = if authority.starts_with('[') {
+        authority.split_inclusive(']').next().unwrap_or_default()
+    } else {
+        authority.split(':').next().unwrap_or_default()
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_allowed_only_for_localhost() {
+        assert!(origin_allowed("http://localhost:3000"));
+        assert!(origin_allowed("http://127.0.0.1"));
+        assert!(origin_allowed("http://[::1]:8080"));
+        assert!(!origin_allowed("https://evil.example"));
+        assert!(!origin_allowed("http://localhost.evil.example"));
+    }
+
+    #[test]
+    fn test_only_mcp_methods_over_http() {
+        for method in [
+            "initialize",
+            "ping",
+            "notifications/initialized",
+            "tools/call",
+            "resources/read",
+            "prompts/get",
+        ] {
+            assert!(mcp_method(method), "{method} should be allowed");
+        }
+        for method in [
+            "daemon/shutdown",
+            "daemon/import_index",
+            "daemon/relocate_project",
+            "daemon/activate_project",
+            "reindex",
+        ] {
+            assert!(!mcp_method(method), "{method} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_parse_query_decodes_paths() {
+        let query = parse_query("project=%2Fhome%2Fdev%2Fmy%20app&session_id=abc&bad=%zz");
+        assert_eq!(query["project"], "/home/dev/my app");
+        assert_eq!(query["session_id"], "abc");
+        assert_eq!(query["bad"], "%zz");
+    }
+
+    #[tokio::test]
+    async fn test_read_request_parses_headers_and_body() {
+        let raw = b"POST /mcp?project=%2Ftmp HTTP/1.1\r\nAuthorization: Bearer t\r\nContent-Length: 2\r\n\r\n{}";
+        let req = read_request(BufReader::new(&raw[..])).await.unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/mcp");
+        assert_eq!(req.project().as_deref(), Some("/tmp"));
+        assert_eq!(req.header("authorization"), Some("Bearer t"));
+        assert_eq!(req.body, b"{}");
+    }
+
+    #[tokio::test]
+    async fn test_read_request_bounds_header_lines() {
+        // An endless line fails at the limit instead of buffering forever
+        let err = read_request(BufReader::new(tokio::io::repeat(b'a')))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Headers too large");
+
+        let mut raw = b"GET /sse HTTP/1.1\r\n".to_vec();
+        for i in 0..MAX_HEADER_BYTES / 32 {
+            raw.extend(format!("X-Filler-{:05}: {}\r\n", i, "a".repeat(40)).as_bytes());
+        }
+        raw.extend(b"\r\n");
+        let err = read_request(BufReader::new(&raw[..])).await.err().unwrap();
+        assert_eq!(err.to_string(), "Headers too large");
+    }
+}
@@ -1,4 +1,5 @@
 pub mod bridge;
 pub mod client;
+pub mod http;
 pub mod protocol;
 pub mod server;
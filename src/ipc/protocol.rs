@@ -88,16 +88,16 @@ impl DaemonNotification {
         }
     }
 
-    /// Create an MCP progress notification.
-    pub fn progress(token: &str, progress: usize, total: usize, message: &str) -> Self {
-        Self::new(
-            "notifications/progress",
-            serde_json::json!({
-                "progressToken": token,
-                "progress": progress,
-                "total": total,
-                "message": message
-            }),
-        )
+    /// Create an MCP progress notification. `total` is omitted when unknown.
+    pub fn progress(token: &Value, progress: usize, total: Option<usize>, message: &str) -> Self {
+        let mut params = serde_json::json!({
+            "progressToken": token,
+            "progress": progress,
+            "message": message
+        });
+        if let Some(total) = total {
+            params["total"] = total.into();
+        }
+        Self::new("notifications/progress", params)
     }
 }
@@ -1,5 +1,6 @@
 //! Unix socket daemon server — accepts connections and routes JSON-RPC requests.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::error::GoferError;
@@ -8,8 +9,15 @@ use anyhow::Result;
 use serde_json::{json, Value};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
 use tokio::net::UnixListener;
-
-use super::protocol::{DaemonNotification, DaemonRequest, DaemonResponse};
+use tokio::sync::{mpsc, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+use super::protocol::{DaemonRequest, DaemonResponse};
+use crate::daemon::handlers::common::ProgressReporter;
+use crate::daemon::handlers::feedback;
+use crate::daemon::index_bundle;
+use crate::daemon::prompts;
+use crate::daemon::resources::{self, ResourceUri};
 use crate::daemon::state::DaemonState;
 use crate::daemon::tools;
 
@@ -50,9 +58,11 @@ pub async fn run_daemon(state: Arc<DaemonState>) -> Result<()> {
                     let conn_start = std::time::Instant::now();
                     tracing::debug!("New connection accepted (active connections: {})", 256 - state.connection_semaphore.available_permits());
 
-                    if let Err(e) = handle_connection(stream, state.clone()).await {
+                    let conn_id = state.resource_subscriptions.connect();
+                    if let Err(e) = handle_connection(stream, state.clone(), conn_id).await {
                         tracing::error!("Connection error: {}", e);
                     }
+                    state.resource_subscriptions.disconnect(conn_id);
 
                     let duration = conn_start.elapsed();
                     tracing::debug!(
@@ -76,7 +86,11 @@ pub async fn run_daemon(state: Arc<DaemonState>) -> Result<()> {
     Ok(())
 }
 
-async fn handle_connection(stream: tokio::net::UnixStream, state: Arc<DaemonState>) -> Result<()> {
+async fn handle_connection(
+    stream: tokio::net::UnixStream,
+    state: Arc<DaemonState>,
+    conn_id: u64,
+) -> Result<()> {
     let (reader, writer) = stream.into_split();
     let mut reader = BufReader::new(reader);
     let writer = BufWriter::new(writer);
@@ -88,8 +102,21 @@ async fn handle_connection(stream: tokio::net::UnixStream, state: Arc<DaemonStat
     // Subscribe to broadcast notifications (e.g. tools/list_changed)
     let mut notify_rx = state.notify_tx.subscribe();
     let notify_write_tx = write_tx.clone();
+    let subscriptions = state.resource_subscriptions.clone();
     tokio::spawn(async move {
-        while let Ok(msg) = notify_rx.recv().await {
+        loop {
+            let msg = match notify_rx.recv().await {
+                Ok(msg) => msg,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Notification receiver lagged, skipped {}", skipped);
+                    continue;
+                }
+                Err(_) => break,
+            };
+            // Resource updates only go to connections subscribed to the URI
+            let Some(msg) = resources::route_notification(msg, conn_id, &subscriptions) else {
+                continue;
+            };
             if notify_write_tx.send(msg).await.is_err() {
                 break;
             }
@@ -122,6 +149,9 @@ async fn handle_connection(stream: tokio::net::UnixStream, state: Arc<DaemonStat
     let mut window_start = std::time::Instant::now();
     let mut window_count: u32 = 0;
 
+    let inflight = Arc::new(InFlight::default());
+    let tool_calls = Arc::new(Semaphore::new(MAX_CONCURRENT_TOOL_CALLS));
+
     loop {
         line.clear();
 
@@ -179,16 +209,17 @@ async fn handle_connection(stream: tokio::net::UnixStream, state: Arc<DaemonStat
                     .into_iter()
                     .map(|v| {
                         let st = state_clone.clone();
+                        let inflight = inflight.clone();
+                        let request = serde_json::from_value::<DaemonRequest>(v).map(|req| {
+                            let scope = inflight.scope(&req, Some(write_tx.clone()));
+                            (req, scope)
+                        });
+                        let tool_calls = tool_calls.clone();
                         tokio::spawn(async move {
-                            match serde_json::from_value::<DaemonRequest>(v) {
-                                Ok(req) => {
-                                    let is_notification = req.id.is_none();
-                                    let resp = handle_request(req, &st).await;
-                                    if is_notification {
-                                        None
-                                    } else {
-                                        Some(resp)
-                                    }
+                            let _permit = tool_calls.acquire_owned().await.ok();
+                            match request {
+                                Ok((req, scope)) => {
+                                    run_request(req, &st, conn_id, scope, &inflight).await
                                 }
                                 Err(e) => {
                                     let (code, msg) =
@@ -200,113 +231,214 @@ async fn handle_connection(stream: tokio::net::UnixStream, state: Arc<DaemonStat
                     })
                     .collect();
 
-                // Await all in parallel and collect results
-                let mut responses = Vec::with_capacity(handles.len());
-                for handle in handles {
-                    if let Ok(Some(resp)) = handle.await {
-                        responses.push(resp);
+                // Await all in the background so cancellations can still be read
+                let batch_tx = write_tx.clone();
+                tokio::spawn(async move {
+                    let mut responses = Vec::with_capacity(handles.len());
+                    for handle in handles {
+                        if let Ok(Some(resp)) = handle.await {
+                            responses.push(resp);
+                        }
                     }
-                }
 
-                // Send batch response as JSON array
-                if !responses.is_empty() {
-                    let out = simd_json::to_string(&responses)
-                        .map_err(|e| anyhow::anyhow!("simd_json: {}", e))?;
-                    let _ = write_tx.send(out).await;
-                }
+                    // Send batch response as JSON array
+                    if !responses.is_empty() {
+                        if let Ok(out) = simd_json::to_string(&responses) {
+                            let _ = batch_tx.send(out).await;
+                        }
+                    }
+                });
             }
             _ => {
-                // Single request (original logic)
+                // Single request
                 let mut single_bytes = trimmed.as_bytes().to_vec();
-                let response = match simd_json::from_slice::<DaemonRequest>(&mut single_bytes) {
+                match simd_json::from_slice::<DaemonRequest>(&mut single_bytes) {
                     Ok(req) => {
-                        let is_notification = req.id.is_none();
-
-                        // Check for progress token in _meta
-                        let progress_token = req
-                            .params
-                            .get("_meta")
-                            .and_then(|m| m.get("progressToken"))
-                            .and_then(|t| t.as_str())
-                            .map(String::from);
-
-                        // If this is a reindex request with a progress token, spawn progress reporter
-                        let _progress_guard = if let Some(token) = progress_token {
-                            if req.method == "reindex" || req.method == "daemon/activate_project" {
-                                let notif_tx = write_tx.clone();
-                                let progress = state.sync_progress.clone();
-                                let cancel = tokio_util::sync::CancellationToken::new();
-                                let cancel_clone = cancel.clone();
-                                tokio::spawn(async move {
-                                    let mut interval = tokio::time::interval(
-                                        std::time::Duration::from_millis(500),
-                                    );
-                                    loop {
-                                        tokio::select! {
-                                            _ = interval.tick() => {}
-                                            _ = cancel_clone.cancelled() => { break; }
-                                        }
-                                        let snap = progress.snapshot();
-                                        if !snap.active {
-                                            continue;
-                                        }
-                                        let stage = progress.stage.lock().await.clone();
-                                        let notif = DaemonNotification::progress(
-                                            &token,
-                                            snap.files_parsed,
-                                            snap.files_total,
-                                            &stage,
-                                        );
-                                        if let Ok(msg) = simd_json::to_string(&notif) {
-                                            if notif_tx.send(msg).await.is_err() {
-                                                break;
-                                            }
-                                        }
-                                    }
-                                });
-                                Some(cancel)
-                            } else {
-                                None
+                        // Registered before running so a cancellation that
+                        // follows right behind the request still finds it
+                        let scope = inflight.scope(&req, Some(write_tx.clone()));
+                        let concurrent = runs_concurrently(&req);
+                        let state = state.clone();
+                        let inflight = inflight.clone();
+                        let response_tx = write_tx.clone();
+                        let run = async move {
+                            let Some(resp) =
+                                run_request(req, &state, conn_id, scope, &inflight).await
+                            else {
+                                return;
+                            };
+                            match simd_json::to_string(&resp) {
+                                Ok(out) => {
+                                    let _ = response_tx.send(out).await;
+                                }
+                                Err(e) => tracing::error!("simd_json: {}", e),
                             }
-                        } else {
-                            None
                         };
-
-                        let resp = handle_request(req, &state).await;
-
-                        // Stop progress reporter
-                        if let Some(cancel) = _progress_guard {
-                            cancel.cancel();
-                        }
-
-                        if is_notification {
-                            None
-                        } else {
-                            Some(resp)
-                        }
+                        schedule(run, concurrent, &tool_calls).await;
                     }
                     Err(e) => {
                         let (code, msg) = GoferError::ParseError(e.to_string()).into_rpc();
-                        Some(DaemonResponse::error(Value::Null, code, msg))
+                        let resp = DaemonResponse::error(Value::Null, code, msg);
+                        let out = simd_json::to_string(&resp)
+                            .map_err(|e| anyhow::anyhow!("simd_json: {}", e))?;
+                        let _ = write_tx.send(out).await;
                     }
-                };
-
-                if let Some(resp) = response {
-                    let out = simd_json::to_string(&resp)
-                        .map_err(|e| anyhow::anyhow!("simd_json: {}", e))?;
-                    let _ = write_tx.send(out).await;
                 }
             }
         }
     }
 
+    // The client is gone: stop whatever it was still waiting for
+    inflight.cancel_all();
     drop(write_tx);
     let _ = writer_handle.await;
 
     Ok(())
 }
 
-async fn handle_request(req: DaemonRequest, state: &Arc<DaemonState>) -> DaemonResponse {
+/// Tool calls one connection may run at once, batch entries included
+const MAX_CONCURRENT_TOOL_CALLS: usize = 16;
+
+/// Only `tools/call` leaves the read loop. Everything else runs in arrival
+/// order, so a request can rely on the ones sent before it: the stdio bridge
+/// sends `daemon/activate_project` right behind `daemon/register_project`.
+fn runs_concurrently(req: &DaemonRequest) -> bool {
+    req.method == "tools/call"
+}
+
+/// Run a single request of a connection: concurrent ones in their own task once
+/// a permit is free, the others before the next line is read.
+async fn schedule<F>(run: F, concurrent: bool, permits: &Arc<Semaphore>)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    if !concurrent {
+        run.await;
+        return;
+    }
+    let Ok(permit) = permits.clone().acquire_owned().await else {
+        return;
+    };
+    tokio::spawn(async move {
+        run.await;
+        drop(permit);
+    });
+}
+
+/// What a request's handlers get from its connection: a cancellation token and
+/// the outgoing queue for `notifications/progress`.
+#[derive(Clone, Default)]
+pub(crate) struct RequestScope {
+    pub cancel: CancellationToken,
+    pub notify: Option<mpsc::Sender<String>>,
+}
+
+impl RequestScope {
+    /// Progress reporter for the request's `_meta.progressToken`, if it has one.
+    fn progress(&self, req: &DaemonRequest) -> ProgressReporter {
+        let token = req
+            .params
+            .get("_meta")
+            .and_then(|m| m.get("progressToken"))
+            .filter(|t| t.is_string() || t.is_number())
+            .cloned();
+        ProgressReporter::new(token, self.notify.clone())
+    }
+}
+
+/// Requests running on one connection, keyed by JSON-RPC id, so that
+/// `notifications/cancelled` can reach them.
+#[derive(Default)]
+pub(crate) struct InFlight(std::sync::Mutex<HashMap<String, CancellationToken>>);
+
+impl InFlight {
+    /// Scope for a request about to run; requests with an id are registered.
+    pub(crate) fn scope(
+        &self,
+        req: &DaemonRequest,
+        notify: Option<mpsc::Sender<String>>,
+    ) -> RequestScope {
+        let cancel = CancellationToken::new();
+        if let Some(id) = &req.id {
+            self.lock().insert(id.to_string(), cancel.clone());
+        }
+        RequestScope { cancel, notify }
+    }
+
+    fn finish(&self, id: &Value) {
+        self.lock().remove(&id.to_string());
+    }
+
+    /// Handle `notifications/cancelled`. Unknown or finished ids are ignored.
+    pub(crate) fn cancel(&self, params: &Value) {
+        let Some(id) = params.get("requestId") else {
+            return;
+        };
+        if let Some(token) = self.lock().get(&id.to_string()) {
+            tracing::info!(
+                "Request {} cancelled by client: {}",
+                id,
+                params
+                    .get("reason")
+                    .and_then(|r| r.as_str())
+                    .unwrap_or("no reason")
+            );
+            token.cancel();
+        }
+    }
+
+    pub(crate) fn cancel_all(&self) {
+        for token in self.lock().values() {
+            token.cancel();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run one request under its scope. Returns `None` when there is nothing to send
+/// back: notifications, and requests the client cancelled.
+pub(crate) async fn run_request(
+    req: DaemonRequest,
+    state: &Arc<DaemonState>,
+    conn_id: u64,
+    scope: RequestScope,
+    inflight: &InFlight,
+) -> Option<DaemonResponse> {
+    if req.method == "notifications/cancelled" {
+        inflight.cancel(&req.params);
+        return None;
+    }
+    let id = req.id.clone();
+
+    // Indexing requests relay the daemon-wide sync progress
+    let relay_done = CancellationToken::new();
+    if matches!(req.method.as_str(), "reindex" | "daemon/activate_project") {
+        let reporter = scope.progress(&req);
+        let sync = state.sync_progress.clone();
+        let done = relay_done.clone();
+        tokio::spawn(async move { reporter.relay_sync(sync, done).await });
+    }
+
+    let cancel = scope.cancel.clone();
+    let resp = handle_request(req, state, conn_id, scope).await;
+    relay_done.cancel();
+
+    inflight.finish(id.as_ref()?);
+    if cancel.is_cancelled() {
+        return None;
+    }
+    Some(resp)
+}
+
+pub(crate) async fn handle_request(
+    req: DaemonRequest,
+    state: &Arc<DaemonState>,
+    conn_id: u64,
+    scope: RequestScope,
+) -> DaemonResponse {
     let id = req.id.clone().unwrap_or(Value::Null);
 
     match req.method.as_str() {
@@ -314,13 +446,16 @@ async fn handle_request(req: DaemonRequest, state: &Arc<DaemonState>) -> DaemonR
         "daemon/register_project" => handle_register_project(id, &req.params, state).await,
         "daemon/activate_project" => handle_activate_project(id, &req.params, state).await,
         "daemon/deactivate_project" => handle_deactivate_project(id, &req.params, state).await,
-        "daemon/sync_progress" => handle_sync_progress(id, state).await,
+        "daemon/sync_progress" => handle_sync_progress(id, &req.params, state).await,
         "daemon/status" => handle_status(id, state).await,
         "daemon/health" => handle_health(id, state).await,
         "daemon/summary_stats" => handle_summary_stats(id, &req.params, state).await,
         "daemon/metrics" => handle_metrics(id, state).await,
         "daemon/shutdown" => handle_shutdown(id, state).await,
         "reindex" => handle_reindex(id, &req.params, state).await,
+        "daemon/export_index" => handle_export_index(id, &req.params, state).await,
+        "daemon/import_index" => handle_import_index(id, &req.params, state).await,
+        "daemon/relocate_project" => handle_relocate_project(id, &req.params, state).await,
 
         // === MCP protocol methods ===
         "initialize" => DaemonResponse::success(
@@ -333,19 +468,26 @@ async fn handle_request(req: DaemonRequest, state: &Arc<DaemonState>) -> DaemonR
                 },
                 "capabilities": {
                     "tools": { "listChanged": true },
-                    "resources": { "subscribe": false, "listChanged": false },
-                    "prompts": { "listChanged": false }
+                    "resources": { "subscribe": true, "listChanged": false },
+                    "prompts": { "listChanged": true }
                 }
             }),
         ),
         "initialized" | "notifications/initialized" => DaemonResponse::success(id, json!({})),
         "ping" => DaemonResponse::success(id, json!({})),
         "tools/list" => handle_tools_list(id, &req, state).await,
-        "tools/call" => handle_tools_call(id, &req, state).await,
+        "tools/call" => handle_tools_call(id, &req, state, &scope).await,
         "resources/list" => handle_resources_list(id, &req, state).await,
-        "resources/read" => handle_resources_read(id, &req, state).await,
-        "prompts/list" => handle_prompts_list(id).await,
-        "prompts/get" => handle_prompts_get(id, &req, state).await,
+        "resources/read" => handle_resources_read(id, &req, state, &scope).await,
+        "resources/templates/list" => {
+            DaemonResponse::success(id, json!({ "resourceTemplates": resources::templates() }))
+        }
+        "resources/subscribe" => handle_resources_subscribe(id, &req, state, conn_id, true).await,
+        "resources/unsubscribe" => {
+            handle_resources_subscribe(id, &req, state, conn_id, false).await
+        }
+        "prompts/list" => handle_prompts_list(id, &req, state).await,
+        "prompts/get" => handle_prompts_get(id, &req, state, &scope).await,
 
         _ => {
             let (code, msg) = GoferError::MethodNotFound(req.method.clone()).into_rpc();
@@ -422,9 +564,34 @@ async fn handle_deactivate_project(
     }
 }
 
-async fn handle_sync_progress(id: Value, state: &Arc<DaemonState>) -> DaemonResponse {
-    let snap = state.sync_progress.snapshot();
-    let stage = state.sync_progress.stage.lock().await.clone();
+async fn handle_sync_progress(
+    id: Value,
+    params: &Value,
+    state: &Arc<DaemonState>,
+) -> DaemonResponse {
+    // With a project_path, report that project's git syncs; otherwise the
+    // daemon-wide full sync
+    let progress = match params.get("project_path").and_then(|v| v.as_str()) {
+        Some(project_path) => {
+            let projects = state.projects.read().await;
+            match projects
+                .values()
+                .find(|ps| ps.path == std::path::Path::new(project_path))
+            {
+                Some(ps) => ps.sync_progress.clone(),
+                None => {
+                    return DaemonResponse::error(
+                        id,
+                        -32000,
+                        format!("Project not loaded: {}", project_path),
+                    )
+                }
+            }
+        }
+        None => state.sync_progress.clone(),
+    };
+    let snap = progress.snapshot();
+    let stage = progress.stage.lock().await.clone();
     DaemonResponse::success(
         id,
         json!({
@@ -556,6 +723,7 @@ async fn handle_reindex(id: Value, params: &Value, state: &Arc<DaemonState>) ->
                 .execute(pool)
                 .await;
             let _ = sqlx::query("DELETE FROM files").execute(pool).await;
+            let _ = project.sqlite.clear_chunk_text().await;
             tracing::info!("Force reindex: cleared SQLite data for {}", project_path);
         }
         DaemonResponse::success(id, json!({ "message": "Data cleared, ready for resync" }))
@@ -564,12 +732,15 @@ async fn handle_reindex(id: Value, params: &Value, state: &Arc<DaemonState>) ->
         match state.get_or_load_project(project_path).await {
             Ok(project) => {
                 let indexer = crate::indexer::service::IndexerService::new(
+                    project.path.clone(),
                     project.sqlite.clone(),
                     project.lance.clone(),
                     state.embedder.clone(),
                     1,
-                );
-                match indexer.index_file(std::path::Path::new(file_path)).await {
+                )
+                .with_cache(project.cache.clone());
+                let abs_path = project.path.join(file_path);
+                match indexer.index_file(&abs_path).await {
                     Ok(_) => DaemonResponse::success(
                         id,
                         json!({ "message": format!("Reindexed: {}", file_path) }),
@@ -584,6 +755,169 @@ async fn handle_reindex(id: Value, params: &Value, state: &Arc<DaemonState>) ->
     }
 }
 
+async fn handle_export_index(
+    id: Value,
+    params: &Value,
+    state: &Arc<DaemonState>,
+) -> DaemonResponse {
+    let project_path = match params.get("project_path").and_then(|v| v.as_str()) {
+        Some(p) => p,
+        None => return DaemonResponse::error(id, -32602, "Missing project_path".into()),
+    };
+    let output = match params.get("output").and_then(|v| v.as_str()) {
+        Some(o) => std::path::PathBuf::from(o),
+        None => return DaemonResponse::error(id, -32602, "Missing output".into()),
+    };
+
+    let project = match state.get_or_load_project(project_path).await {
+        Ok(p) => p,
+        Err(e) => return DaemonResponse::error(id, -32000, format!("Project load failed: {}", e)),
+    };
+    let index_dir = state.gofer_home.join("indices").join(&project.id);
+
+    // Hold the Lance lock so the indexer can't write chunks while the table is copied
+    let _lance_guard = project.lance.lock().await;
+    match index_bundle::export_index(
+        &project.sqlite,
+        &index_dir,
+        &project.path,
+        &state.embedder.cache_version_key(),
+        &output,
+    )
+    .await
+    {
+        Ok(manifest) => DaemonResponse::success(
+            id,
+            json!({
+                "message": format!("Exported index to {}", output.display()),
+                "output": output.to_string_lossy(),
+                "manifest": manifest,
+            }),
+        ),
+        Err(e) => DaemonResponse::error(id, -32000, format!("Export failed: {}", e)),
+    }
+}
+
+async fn handle_import_index(
+    id: Value,
+    params: &Value,
+    state: &Arc<DaemonState>,
+) -> DaemonResponse {
+    let project_path = match params.get("project_path").and_then(|v| v.as_str()) {
+        Some(p) => p,
+        None => return DaemonResponse::error(id, -32602, "Missing project_path".into()),
+    };
+    let input = match params.get("input").and_then(|v| v.as_str()) {
+        Some(i) => std::path::PathBuf::from(i),
+        None => return DaemonResponse::error(id, -32602, "Missing input".into()),
+    };
+
+    let uuid = match state.registry.register(project_path).await {
+        Ok(uuid) => uuid,
+        Err(e) => return DaemonResponse::error(id, -32000, format!("Registration failed: {}", e)),
+    };
+
+    // The index files are replaced underneath the project, so it must be unloaded first
+    let watching = match state.projects.read().await.get(&uuid) {
+        Some(ps) => Some(*ps.watcher_active.lock().await),
+        None => None,
+    };
+    if let Err(e) = state.deactivate_project(project_path).await {
+        return DaemonResponse::error(id, -32000, format!("Deactivation failed: {}", e));
+    }
+
+    let index_dir = state.gofer_home.join("indices").join(&uuid);
+    let report = match index_bundle::import_index(
+        &input,
+        &index_dir,
+        std::path::Path::new(project_path),
+        &state.embedder.cache_version_key(),
+        state.embedder.dimension(),
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            // The previous index is back in place; bring the project online again
+            if let Some(watch) = watching {
+                if let Err(e) = state.activate_project(project_path, watch, true).await {
+                    tracing::error!("Reactivating {} after failed import: {}", project_path, e);
+                }
+            }
+            return DaemonResponse::error(id, -32000, format!("Import failed: {}", e));
+        }
+    };
+
+    // Incremental sync picks up whatever changed since the bundle was built
+    match state.activate_project(project_path, false, false).await {
+        Ok(msg) => DaemonResponse::success(
+            id,
+            json!({
+                "message": format!("Imported {} — {}", input.display(), msg),
+                "report": report,
+            }),
+        ),
+        Err(e) => DaemonResponse::error(id, -32000, format!("Post-import sync failed: {}", e)),
+    }
+}
+
+async fn handle_relocate_project(
+    id: Value,
+    params: &Value,
+    state: &Arc<DaemonState>,
+) -> DaemonResponse {
+    let old_path = match params.get("old_path").and_then(|v| v.as_str()) {
+        Some(p) => p,
+        None => return DaemonResponse::error(id, -32602, "Missing old_path".into()),
+    };
+    let new_path = match params.get("new_path").and_then(|v| v.as_str()) {
+        Some(p) => p,
+        None => return DaemonResponse::error(id, -32602, "Missing new_path".into()),
+    };
+
+    let record = match state.registry.get_by_path(old_path).await {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return DaemonResponse::error(
+                id,
+                -32000,
+                format!("Project not registered: {}", old_path),
+            )
+        }
+        Err(e) => return DaemonResponse::error(id, -32000, format!("Registry error: {}", e)),
+    };
+    match state.registry.get_by_path(new_path).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return DaemonResponse::error(
+                id,
+                -32000,
+                format!("{} is already registered as a separate project", new_path),
+            )
+        }
+        Err(e) => return DaemonResponse::error(id, -32000, format!("Registry error: {}", e)),
+    }
+
+    if let Err(e) = state.deactivate_project(old_path).await {
+        return DaemonResponse::error(id, -32000, format!("Deactivation failed: {}", e));
+    }
+    if let Err(e) = state.registry.update_path(&record.id, new_path).await {
+        return DaemonResponse::error(id, -32000, format!("Relocation failed: {}", e));
+    }
+
+    // Stored paths are relative to the root, so the existing index is reused as-is
+    match state.activate_project(new_path, false, false).await {
+        Ok(msg) => DaemonResponse::success(
+            id,
+            json!({
+                "message": format!("Relocated {} -> {} — {}", old_path, new_path, msg),
+                "project_id": record.id,
+            }),
+        ),
+        Err(e) => DaemonResponse::error(id, -32000, format!("Post-relocate sync failed: {}", e)),
+    }
+}
+
 // === MCP tool routing ===
 
 async fn handle_tools_list(
@@ -618,6 +952,7 @@ async fn handle_tools_call(
     id: Value,
     req: &DaemonRequest,
     state: &Arc<DaemonState>,
+    scope: &RequestScope,
 ) -> DaemonResponse {
     let name = req
         .params
@@ -625,6 +960,12 @@ async fn handle_tools_call(
         .and_then(|v| v.as_str())
         .unwrap_or("");
     let args = req.params.get("arguments").cloned().unwrap_or(json!({}));
+    // `gofer eval` opts out so its searches and reads stay out of implicit feedback
+    let record_feedback = req
+        .params
+        .get("record_feedback")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
     let project_path = req.project_path();
 
     let Some(pp) = project_path else {
@@ -654,6 +995,9 @@ async fn handle_tools_call(
         vector_circuit: Arc::clone(&state.vector_circuit),
         rust_analyzer: Arc::clone(&project.rust_analyzer),
         language_services: Arc::clone(&project.language_services),
+        cancel: scope.cancel.clone(),
+        progress: scope.progress(req),
+        feedback: project.feedback.clone(),
     };
 
     // Try language services first
@@ -688,7 +1032,14 @@ async fn handle_tools_call(
     let success = result.is_ok();
     let err_msg = result.as_ref().err().map(|e: &anyhow::Error| e.to_string());
     let args_str = serde_json::to_string(&args).ok();
+    let result_paths = result
+        .as_ref()
+        .ok()
+        .filter(|_| record_feedback)
+        .and_then(|v| feedback::result_paths(name, v));
     let sqlite_clone = project.sqlite.clone();
+    let root = project.path.clone();
+    let feedback_config = project.feedback.clone();
     let tool_name = name.to_string();
     tokio::spawn(async move {
         let _ = sqlite_clone
@@ -698,8 +1049,14 @@ async fn handle_tools_call(
                 latency_ms,
                 success,
                 err_msg.as_deref(),
+                result_paths.as_deref(),
             )
             .await;
+        // Implicit feedback: a read of a path a recent search returned is a click
+        if success && record_feedback {
+            feedback::record_follow_up(&sqlite_clone, &root, &feedback_config, &tool_name, &args)
+                .await;
+        }
     });
 
     match result {
@@ -762,6 +1119,7 @@ async fn handle_resources_read(
     id: Value,
     req: &DaemonRequest,
     state: &Arc<DaemonState>,
+    scope: &RequestScope,
 ) -> DaemonResponse {
     let uri = req.params.get("uri").and_then(|v| v.as_str()).unwrap_or("");
     let project_path = req.project_path();
@@ -785,6 +1143,9 @@ async fn handle_resources_read(
         vector_circuit: Arc::clone(&state.vector_circuit),       // Feature 016
         rust_analyzer: Arc::clone(&project.rust_analyzer),
         language_services: Arc::clone(&project.language_services),
+        cancel: scope.cancel.clone(),
+        progress: ProgressReporter::default(),
+        feedback: project.feedback.clone(),
     };
 
     let result = match uri {
@@ -793,7 +1154,22 @@ async fn handle_resources_read(
         "project://stats" => resource_project_stats(&ctx).await,
         "project://config" => tools::dispatch("get_config_keys", json!({}), &ctx).await,
         _ => {
-            return DaemonResponse::error(id, -32602, format!("Unknown resource URI: {}", uri));
+            let Some(parsed) = ResourceUri::parse(uri, &project.path) else {
+                return DaemonResponse::error(id, -32602, format!("Unknown resource URI: {}", uri));
+            };
+            return match resources::read(&parsed, &ctx).await {
+                Ok((mime_type, text)) => DaemonResponse::success(
+                    id,
+                    json!({
+                        "contents": [{
+                            "uri": uri,
+                            "mimeType": mime_type,
+                            "text": text
+                        }]
+                    }),
+                ),
+                Err(e) => DaemonResponse::error(id, -32000, format!("Resource read error: {}", e)),
+            };
         }
     };
 
@@ -815,6 +1191,44 @@ async fn handle_resources_read(
     }
 }
 
+/// `resources/subscribe` and `resources/unsubscribe` for templated URIs.
+async fn handle_resources_subscribe(
+    id: Value,
+    req: &DaemonRequest,
+    state: &Arc<DaemonState>,
+    conn_id: u64,
+    subscribe: bool,
+) -> DaemonResponse {
+    let uri = req.params.get("uri").and_then(|v| v.as_str()).unwrap_or("");
+    let Some(pp) = req.project_path() else {
+        return DaemonResponse::error(id, -32602, "Missing project_path".into());
+    };
+    let project = match state.get_or_load_project(pp).await {
+        Ok(p) => p,
+        Err(e) => return DaemonResponse::error(id, -32000, format!("Project load failed: {}", e)),
+    };
+    let Some(parsed) = ResourceUri::parse(uri, &project.path) else {
+        return DaemonResponse::error(
+            id,
+            -32602,
+            format!("Resource does not support subscriptions: {}", uri),
+        );
+    };
+
+    let project_key = project.path.to_string_lossy();
+    let canonical = parsed.canonical();
+    if subscribe {
+        state
+            .resource_subscriptions
+            .subscribe(conn_id, &project_key, &canonical, uri);
+    } else {
+        state
+            .resource_subscriptions
+            .unsubscribe(conn_id, &project_key, &canonical);
+    }
+    DaemonResponse::success(id, json!({}))
+}
+
 async fn resource_project_context(ctx: &tools::ToolContext) -> anyhow::Result<Value> {
     use crate::models::chunk::Rule;
 
@@ -877,41 +1291,57 @@ async fn resource_project_stats(ctx: &tools::ToolContext) -> anyhow::Result<Valu
 
 // === MCP Prompts ===
 
-async fn handle_prompts_list(id: Value) -> DaemonResponse {
-    DaemonResponse::success(
-        id,
+async fn handle_prompts_list(
+    id: Value,
+    req: &DaemonRequest,
+    state: &Arc<DaemonState>,
+) -> DaemonResponse {
+    let mut prompt_list = builtin_prompts();
+
+    // Project prompts from .gofer/prompts/ replace built-ins of the same name
+    if let Some(pp) = req.project_path() {
+        if let Ok(project) = state.get_or_load_project(pp).await {
+            for prompt in prompts::load_prompts(&project.path) {
+                prompt_list.retain(|p| p["name"] != prompt.name.as_str());
+                prompt_list.push(prompt.to_list_entry());
+            }
+        }
+    }
+
+    DaemonResponse::success(id, json!({ "prompts": prompt_list }))
+}
+
+fn builtin_prompts() -> Vec<Value> {
+    vec![
         json!({
-            "prompts": [
-                {
-                    "name": "review_code",
-                    "description": "Generate a code review prompt for a given file, including context bundle and project rules.",
-                    "arguments": [
-                        { "name": "file", "description": "File to review", "required": true }
-                    ]
-                },
-                {
-                    "name": "explain_module",
-                    "description": "Generate a prompt to explain a module/file's purpose, dependencies, and architecture.",
-                    "arguments": [
-                        { "name": "file", "description": "File to explain", "required": true }
-                    ]
-                },
-                {
-                    "name": "find_related",
-                    "description": "Generate a prompt to find files/code related to a given concept or feature.",
-                    "arguments": [
-                        { "name": "query", "description": "Concept or feature to find related code for", "required": true }
-                    ]
-                }
+            "name": "review_code",
+            "description": "Generate a code review prompt for a given file, including context bundle and project rules.",
+            "arguments": [
+                { "name": "file", "description": "File to review", "required": true }
             ]
         }),
-    )
+        json!({
+            "name": "explain_module",
+            "description": "Generate a prompt to explain a module/file's purpose, dependencies, and architecture.",
+            "arguments": [
+                { "name": "file", "description": "File to explain", "required": true }
+            ]
+        }),
+        json!({
+            "name": "find_related",
+            "description": "Generate a prompt to find files/code related to a given concept or feature.",
+            "arguments": [
+                { "name": "query", "description": "Concept or feature to find related code for", "required": true }
+            ]
+        }),
+    ]
 }
 
 async fn handle_prompts_get(
     id: Value,
     req: &DaemonRequest,
     state: &Arc<DaemonState>,
+    scope: &RequestScope,
 ) -> DaemonResponse {
     let name = req
         .params
@@ -940,12 +1370,20 @@ async fn handle_prompts_get(
         vector_circuit: Arc::clone(&state.vector_circuit),       // Feature 016
         rust_analyzer: Arc::clone(&project.rust_analyzer),
         language_services: Arc::clone(&project.language_services),
+        cancel: scope.cancel.clone(),
+        progress: ProgressReporter::default(),
+        feedback: project.feedback.clone(),
     };
 
-    let result = match name {
-        "review_code" => prompt_review_code(&args, &ctx).await,
-        "explain_module" => prompt_explain_module(&args, &ctx).await,
-        "find_related" => prompt_find_related(&args, &ctx).await,
+    let project_prompt = prompts::load_prompts(&project.path)
+        .into_iter()
+        .find(|p| p.name == name);
+
+    let result = match (name, project_prompt) {
+        (_, Some(prompt)) => prompt.render(&args, &ctx).await,
+        ("review_code", None) => prompt_review_code(&args, &ctx).await,
+        ("explain_module", None) => prompt_explain_module(&args, &ctx).await,
+        ("find_related", None) => prompt_find_related(&args, &ctx).await,
         _ => {
             return DaemonResponse::error(id, -32602, format!("Unknown prompt: {}", name));
         }
@@ -971,16 +1409,7 @@ async fn prompt_review_code(args: &Value, ctx: &tools::ToolContext) -> anyhow::R
     )
     .await?;
 
-    let rules: Vec<crate::models::chunk::Rule> = ctx.sqlite.get_rules().await?;
-    let rules_text = if rules.is_empty() {
-        "No project rules defined.".to_string()
-    } else {
-        rules
-            .iter()
-            .map(|r| format!("- [{}] (p={}) {}", r.category, r.priority, r.rule))
-            .collect::<Vec<_>>()
-            .join("\n")
-    };
+    let rules_text = prompts::rules_text(ctx).await?;
 
     let bundle_text = serde_json::to_string_pretty(&bundle)?;
 
@@ -1055,3 +1484,122 @@ async fn prompt_find_related(args: &Value, ctx: &tools::ToolContext) -> anyhow::
         }
     })])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: Value, params: Value) -> DaemonRequest {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": params
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cancel_reaches_only_matching_request() {
+        let inflight = InFlight::default();
+        let a = inflight.scope(&request(json!(1), json!({})), None);
+        let b = inflight.scope(&request(json!("1"), json!({})), None);
+
+        inflight.cancel(&json!({ "requestId": 1, "reason": "user" }));
+        assert!(a.cancel.is_cancelled());
+        assert!(!b.cancel.is_cancelled());
+
+        inflight.finish(&json!("1"));
+        inflight.cancel(&json!({ "requestId": "1" }));
+        assert!(!b.cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_progress_uses_request_token() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let scope = RequestScope {
+            cancel: CancellationToken::new(),
+            notify: Some(tx),
+        };
+        let req = request(json!(7), json!({ "_meta": { "progressToken": 42 } }));
+        scope.progress(&req).report(3, Some(10), "parsing");
+
+        let msg: Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["method"], "notifications/progress");
+        assert_eq!(msg["params"]["progressToken"], 42);
+        assert_eq!(msg["params"]["total"], 10);
+
+        let silent = scope.progress(&request(json!(8), json!({})));
+        silent.report(1, None, "ignored");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_and_activate_run_in_order() {
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_TOOL_CALLS));
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        // Sent back to back, as the stdio bridge does
+        for (method, delay) in [
+            ("daemon/register_project", 50),
+            ("daemon/activate_project", 0),
+            ("tools/call", 100),
+            ("daemon/status", 0),
+        ] {
+            let req: DaemonRequest = serde_json::from_value(json!({
+                "jsonrpc": "2.0", "id": 1, "method": method, "params": {}
+            }))
+            .unwrap();
+            let log = log.clone();
+            let run = async move {
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                log.lock().unwrap().push(method);
+            };
+            schedule(run, runs_concurrently(&req), &permits).await;
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "daemon/register_project",
+                "daemon/activate_project",
+                "daemon/status"
+            ]
+        );
+
+        // The tool call did not hold up the read loop, and still finishes
+        assert_eq!(permits.available_permits(), MAX_CONCURRENT_TOOL_CALLS - 1);
+        let _all = permits
+            .acquire_many(MAX_CONCURRENT_TOOL_CALLS as u32)
+            .await
+            .unwrap();
+        assert_eq!(log.lock().unwrap().last(), Some(&"tools/call"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_reactivates_project() {
+        let home = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().to_str().unwrap();
+        let state = Arc::new(
+            DaemonState::with_embedder(
+                home.path().to_path_buf(),
+                crate::indexer::embedder::EmbedderPool::unloaded(),
+            )
+            .await
+            .unwrap(),
+        );
+        state.registry.register(path).await.unwrap();
+        state.get_or_load_project(path).await.unwrap();
+
+        let params = json!({
+            "project_path": path,
+            "input": project.path().join("missing.tar.gz"),
+        });
+        let response = handle_import_index(json!(1), &params, &state).await;
+        assert!(response.error.is_some());
+
+        // Back online with its old index instead of waiting for a manual activate
+        let projects = state.projects.read().await;
+        assert!(projects.values().any(|ps| ps.path == project.path()));
+    }
+}
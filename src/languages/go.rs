@@ -127,7 +127,7 @@ impl LanguageService for GoService {
         let root = ctx.root_path.as_path();
         match name {
             "go_project_info" => self.tool_project_info(root).await,
-            "go_explain_struct" => self.tool_explain_struct(args).await,
+            "go_explain_struct" => self.tool_explain_struct(args, root).await,
             "go_find_interface_impls" => self.tool_find_interface_impls(args).await,
             "go_vet" => self.tool_vet(args, root).await,
             "go_build" => self.tool_build(args, root).await,
@@ -238,7 +238,7 @@ impl GoService {
     }
 
     /// `go_explain_struct` — struct fields, methods from index + tree-sitter AST
-    async fn tool_explain_struct(&self, args: Value) -> Result<String> {
+    async fn tool_explain_struct(&self, args: Value, root: &Path) -> Result<String> {
         let struct_name = args
             .get("struct_name")
             .and_then(|v| v.as_str())
@@ -267,7 +267,7 @@ impl GoService {
         }
 
         // tree-sitter AST analysis: extract fields and methods from source
-        let source = tokio::fs::read_to_string(&file.path).await.ok();
+        let source = tokio::fs::read_to_string(root.join(&file.path)).await.ok();
         if let Some(ref src) = source {
             let (fields, methods) = go_analyze_struct(src, struct_name);
 
@@ -738,6 +738,14 @@ fn node_text<'a>(node: &tree_sitter::Node, code: &'a str) -> &'a str {
 // Import resolution
 // ---------------------------------------------------------------------------
 
+/// Resolve an import to a file inside the project; stdlib and third-party modules
+/// (resolved to `<stdlib>` / `<site-packages>` placeholders) yield `None`.
+pub fn resolve_project_import(import_path: &str, from_file: &Path, root: &Path) -> Option<PathBuf> {
+    resolve_python_import(import_path, from_file, root)
+        .map(|(path, _)| path)
+        .filter(|path| path.exists())
+}
+
 fn resolve_python_import(
     import_path: &str,
     from_file: &Path,
@@ -318,6 +318,45 @@ struct DiagnosticEntry {
     message: String,
 }
 
+/// Files a `crate::a::b` module path can live in: `src/a/b.rs` and/or `src/a/b/mod.rs`
+/// (`crate` alone maps to `src/lib.rs` / `src/main.rs`).
+pub fn resolve_module_path(root: &Path, module_path: &str) -> Vec<PathBuf> {
+    let src_dir = root.join("src");
+    let segments: Vec<&str> = module_path.split("::").skip(1).collect();
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    // Build all possible paths
+    if segments.is_empty() {
+        // "crate" alone → src/lib.rs or src/main.rs
+        let lib = src_dir.join("lib.rs");
+        let main = src_dir.join("main.rs");
+        if lib.exists() {
+            candidates.push(lib);
+        }
+        if main.exists() {
+            candidates.push(main);
+        }
+    } else {
+        // crate::a::b::c  →  try src/a/b/c.rs  and  src/a/b/c/mod.rs
+        let mut rel = PathBuf::new();
+        for seg in &segments {
+            rel.push(seg);
+        }
+
+        let file_variant = src_dir.join(&rel).with_extension("rs");
+        let mod_variant = src_dir.join(&rel).join("mod.rs");
+
+        if file_variant.exists() {
+            candidates.push(file_variant);
+        }
+        if mod_variant.exists() {
+            candidates.push(mod_variant);
+        }
+    }
+    candidates
+}
+
 fn parse_cargo_diagnostics(stdout: &str, file_filter: Option<&str>) -> Vec<DiagnosticEntry> {
     let mut entries = Vec::new();
 
@@ -673,39 +712,8 @@ impl RustService {
             ));
         }
 
-        let src_dir = root.join("src");
         let segments = &parts[1..]; // skip "crate"
-
-        let mut candidates: Vec<PathBuf> = Vec::new();
-
-        // Build all possible paths
-        if segments.is_empty() {
-            // "crate" alone → src/lib.rs or src/main.rs
-            let lib = src_dir.join("lib.rs");
-            let main = src_dir.join("main.rs");
-            if lib.exists() {
-                candidates.push(lib);
-            }
-            if main.exists() {
-                candidates.push(main);
-            }
-        } else {
-            // crate::a::b::c  →  try src/a/b/c.rs  and  src/a/b/c/mod.rs
-            let mut rel = PathBuf::new();
-            for seg in segments {
-                rel.push(seg);
-            }
-
-            let file_variant = src_dir.join(&rel).with_extension("rs");
-            let mod_variant = src_dir.join(&rel).join("mod.rs");
-
-            if file_variant.exists() {
-                candidates.push(file_variant);
-            }
-            if mod_variant.exists() {
-                candidates.push(mod_variant);
-            }
-        }
+        let candidates = resolve_module_path(root, module_path);
 
         let mut out = format!("# Module: `{}`\n\n", module_path);
 
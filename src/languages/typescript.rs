@@ -316,6 +316,28 @@ impl LanguageService for TypeScriptService {
 
 static TS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "d.ts", "vue"];
 
+/// tsconfig-aware import resolution for callers outside the service (context bundles).
+pub struct TsImportResolver {
+    root: PathBuf,
+    aliases: Vec<PathAlias>,
+}
+
+impl TsImportResolver {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            aliases: load_tsconfig_aliases(root),
+        }
+    }
+
+    /// Resolve a relative or aliased import to a workspace file; packages in
+    /// `node_modules` are not followed.
+    pub fn resolve(&self, import_path: &str, from_file: &Path) -> Option<PathBuf> {
+        resolve_import_path(import_path, from_file, &self.root, &self.aliases)
+            .filter(|p| !p.components().any(|c| c.as_os_str() == "node_modules"))
+    }
+}
+
 fn resolve_import_path(
     import_path: &str,
     from_file: &Path,
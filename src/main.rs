@@ -6,6 +6,7 @@ mod commit;
 mod daemon;
 mod error;
 mod error_recovery; // Feature 016: graceful error handling & recovery
+mod eval; // search quality evaluation (gofer eval)
 mod indexer;
 mod ipc;
 mod languages;
@@ -108,11 +109,56 @@ enum Commands {
         action: Option<ConfigAction>,
     },
 
+    /// Export or import a portable index bundle
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+
+    /// Re-point a registered project at the current directory (after a move or re-clone)
+    Relocate {
+        /// Previous project location as registered with the daemon
+        from: String,
+    },
+
+    /// Measure search quality against a golden query set (recall@k, MRR, nDCG)
+    Eval {
+        /// Golden query file (.yaml/.yml or .json)
+        golden: String,
+        /// Tool argument overrides to evaluate (defaults to the built-in settings)
+        #[arg(long)]
+        config: Option<String>,
+        /// Second config to compare against the first
+        #[arg(long)]
+        against: Option<String>,
+        /// Rank cutoff (overrides `k` from the golden file)
+        #[arg(short, long)]
+        k: Option<usize>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// (internal) Run as daemon process
     #[command(hide = true)]
     Daemon,
 }
 
+#[derive(Subcommand)]
+enum IndexAction {
+    /// Write the current project's index to a .tar.gz bundle
+    Export {
+        /// Output file (defaults to ./<project>.gofer-index.tar.gz)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Load an index bundle into the current project and sync changes since it was built
+    Import {
+        /// Bundle produced by `gofer index export`
+        bundle: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Create default config.toml in .gofer/ directory
@@ -155,6 +201,15 @@ fn main() -> anyhow::Result<()> {
         Commands::Stop => handle_stop(),
         Commands::Logs { lines, follow, err } => handle_logs(lines, follow, err),
         Commands::Config { action } => handle_config(action),
+        Commands::Index { action } => handle_index(action),
+        Commands::Relocate { from } => handle_relocate(&from),
+        Commands::Eval {
+            golden,
+            config,
+            against,
+            k,
+            json,
+        } => handle_eval(&golden, config.as_deref(), against.as_deref(), k, json),
     }
 }
 
@@ -221,6 +276,15 @@ fn run_daemon() -> anyhow::Result<()> {
                     .await;
                 });
 
+                // MCP over HTTP for clients without stdio (off unless enabled in ~/.gofer/config.toml)
+                let http_config = indexer::watcher::load_config(&state.gofer_home).http;
+                if http_config.enabled {
+                    let http_state = state.clone();
+                    tokio::spawn(async move {
+                        ipc::http::serve_mcp_http(http_state, http_config).await;
+                    });
+                }
+
                 // Register signal handlers to trigger graceful shutdown
                 let token = state.shutdown_token.clone();
                 tokio::spawn(async move {
@@ -851,6 +915,167 @@ fn handle_config(action: Option<ConfigAction>) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn handle_index(action: IndexAction) -> anyhow::Result<()> {
+    let rt = cli_runtime()?;
+    rt.block_on(async {
+        ensure_daemon_running().await?;
+
+        let cwd = std::env::current_dir()?.canonicalize()?;
+        let cwd_str = cwd.to_string_lossy().to_string();
+        let sock = socket_path();
+        let mut client = DaemonClient::connect(&sock).await?;
+
+        match action {
+            IndexAction::Export { output } => {
+                let output = match output {
+                    Some(o) => cwd.join(o),
+                    None => {
+                        let name = cwd
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("project");
+                        cwd.join(format!("{}.gofer-index.tar.gz", name))
+                    }
+                };
+                println!("Exporting index to {}...", output.display());
+                let result = client
+                    .call(
+                        "daemon/export_index",
+                        json!({
+                            "project_path": cwd_str,
+                            "output": output.to_string_lossy(),
+                        }),
+                    )
+                    .await?;
+                if let Some(manifest) = result.get("manifest") {
+                    println!("{}", serde_json::to_string_pretty(manifest)?);
+                }
+                let msg = result
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Exported");
+                println!("{}", msg);
+            }
+            IndexAction::Import { bundle } => {
+                let bundle = cwd.join(bundle).canonicalize()?;
+                println!("Importing {} into {}...", bundle.display(), cwd_str);
+                let result = client
+                    .call(
+                        "daemon/import_index",
+                        json!({
+                            "project_path": cwd_str,
+                            "input": bundle.to_string_lossy(),
+                        }),
+                    )
+                    .await?;
+                if let Some(report) = result.get("report") {
+                    let bundle_head = report
+                        .pointer("/manifest/git_head")
+                        .and_then(|v| v.as_str());
+                    let current_head = report.get("current_git_head").and_then(|v| v.as_str());
+                    if bundle_head.is_some() && bundle_head != current_head {
+                        println!(
+                            "Note: bundle was built at {}, working tree is at {}",
+                            bundle_head.unwrap_or("?"),
+                            current_head.unwrap_or("?")
+                        );
+                    }
+                }
+                let msg = result
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Imported");
+                println!("{}", msg);
+            }
+        }
+
+        anyhow::Ok(())
+    })
+}
+
+fn handle_relocate(from: &str) -> anyhow::Result<()> {
+    let rt = cli_runtime()?;
+    rt.block_on(async {
+        ensure_daemon_running().await?;
+
+        let cwd = std::env::current_dir()?.canonicalize()?;
+        // The old location usually no longer exists, so it can't be canonicalized
+        let old_path = cwd.join(from);
+        let old_str = old_path.to_string_lossy().trim_end_matches('/').to_string();
+        let cwd_str = cwd.to_string_lossy().to_string();
+
+        println!("Relocating {} -> {}...", old_str, cwd_str);
+        let sock = socket_path();
+        let mut client = DaemonClient::connect(&sock).await?;
+        let result = client
+            .call(
+                "daemon/relocate_project",
+                json!({
+                    "old_path": old_str,
+                    "new_path": cwd_str,
+                }),
+            )
+            .await?;
+        let msg = result
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Relocated");
+        println!("{}", msg);
+
+        anyhow::Ok(())
+    })
+}
+
+fn handle_eval(
+    golden: &str,
+    config: Option<&str>,
+    against: Option<&str>,
+    k: Option<usize>,
+    as_json: bool,
+) -> anyhow::Result<()> {
+    let cwd = std::env::current_dir()?.canonicalize()?;
+    let set: eval::GoldenSet = eval::load_file(&cwd.join(golden))?;
+    let baseline: eval::EvalConfig = match config {
+        Some(path) => eval::load_file(&cwd.join(path))?,
+        None => eval::EvalConfig::default(),
+    };
+    let candidate: Option<eval::EvalConfig> = against
+        .map(|path| eval::load_file(&cwd.join(path)))
+        .transpose()?;
+    let k = k.or(set.k).unwrap_or(eval::DEFAULT_K);
+
+    let rt = cli_runtime()?;
+    rt.block_on(async {
+        ensure_daemon_running().await?;
+
+        let cwd_str = cwd.to_string_lossy().to_string();
+        let sock = socket_path();
+        let mut client = DaemonClient::connect(&sock).await?;
+
+        let report = eval::run_eval(&mut client, &cwd_str, &set, &baseline, k).await?;
+        match candidate {
+            Some(candidate) => {
+                let other = eval::run_eval(&mut client, &cwd_str, &set, &candidate, k).await?;
+                let diff = eval::diff_reports(report, other);
+                if as_json {
+                    println!("{}", serde_json::to_string_pretty(&diff)?);
+                } else {
+                    print!("{}", eval::format_diff(&diff));
+                }
+            }
+            None => {
+                if as_json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    print!("{}", eval::format_report(&report));
+                }
+            }
+        }
+
+        anyhow::Ok(())
+    })
+}
+
 const DEFAULT_CONFIG: &str = r#"# gofer configuration
 # See: gofer config --help
 
@@ -882,6 +1107,15 @@ ignore = [
 ]
 parallel_workers = 4
 
+# Non-code files indexed as symbols and chunks: Markdown headings, TOML/YAML/JSON
+# key paths, SQL tables and columns. Set a type to false to skip it.
+[indexer.documents]
+markdown = true
+toml = true
+yaml = true
+json = true
+sql = true
+
 [embedding]
 batch_size = 32
 model = "NomicEmbedTextV15"
@@ -897,6 +1131,26 @@ model_id = "qwen2.5-coder:1.5b"
 max_tokens = 150
 temperature = 0.3
 
+# Boost files that agents open after a search (learned from the audit log)
+[feedback]
+enabled = true
+window_secs = 300
+half_life_days = 14
+
+# Chat model for the `ask` tool (OpenAI-compatible endpoint; api = "ollama" for native /api/chat)
+[ask]
+api = "openai"
+endpoint = "http://localhost:11434/v1"
+model = "qwen2.5-coder:7b"
+max_context_tokens = 6000
+max_answer_tokens = 512
+
+# MCP over HTTP for clients that cannot use stdio (global ~/.gofer/config.toml only).
+# Clients send "Authorization: Bearer <token>"; without `token` one is written to ~/.gofer/http_token
+[http]
+enabled = false
+bind = "127.0.0.1:10988"
+
 [domains]
 rs_paths = []
 py_paths = []
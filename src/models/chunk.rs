@@ -66,6 +66,18 @@ pub enum SymbolKind {
     Method,
     #[serde(rename = "local_var")]
     LocalVar,
+    /// Markdown heading
+    #[serde(rename = "section")]
+    Section,
+    /// TOML/YAML/JSON key path
+    #[serde(rename = "key")]
+    Key,
+    /// SQL table or view
+    #[serde(rename = "table")]
+    Table,
+    /// SQL column, named `table.column`
+    #[serde(rename = "column")]
+    Column,
 }
 
 impl SymbolKind {
@@ -85,6 +97,10 @@ impl SymbolKind {
             "class" => SymbolKind::Class,
             "method" => SymbolKind::Method,
             "local_var" => SymbolKind::LocalVar,
+            "section" => SymbolKind::Section,
+            "key" => SymbolKind::Key,
+            "table" => SymbolKind::Table,
+            "column" => SymbolKind::Column,
             _ => SymbolKind::Function, // Default fallback
         }
     }
@@ -105,6 +121,10 @@ impl SymbolKind {
             SymbolKind::Class => "class",
             SymbolKind::Method => "method",
             SymbolKind::LocalVar => "local_var",
+            SymbolKind::Section => "section",
+            SymbolKind::Key => "key",
+            SymbolKind::Table => "table",
+            SymbolKind::Column => "column",
         }
     }
 }
@@ -211,6 +231,17 @@ pub struct ImportInfo {
     pub line: u32,
 }
 
+/// Import of one project file by another, resolved at index time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ImportEdge {
+    pub from_path: String,
+    pub to_path: String,
+    /// 0-based
+    pub line: i32,
+    /// Import path as written
+    pub specifier: String,
+}
+
 /// Bundled context for LLM consumption
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextBundle {
@@ -220,6 +251,18 @@ pub struct ContextBundle {
     pub markdown: String,
     pub total_lines: usize,
     pub total_tokens_estimate: usize,
+    /// Imported items the resolved module does not define (e.g. re-exports)
+    #[serde(default)]
+    pub unresolved_items: Vec<UnresolvedItem>,
+}
+
+/// An imported item left for the symbol index to locate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedItem {
+    pub name: String,
+    /// Module file the import resolved to
+    pub module_file: String,
+    pub depth: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -357,6 +400,18 @@ pub struct SymbolWithPath {
     pub file_path: String,
 }
 
+/// Chunk matched by the full-text index over chunk bodies (`chunks_fts`)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ChunkTextHit {
+    pub chunk_id: String,
+    pub file_path: String,
+    pub line_start: i64,
+    pub line_end: i64,
+    pub content: String,
+    /// Negated BM25 (higher is better)
+    pub score: f64,
+}
+
 /// Reference with file path (for MCP tools)
 #[derive(
     Debug, Clone, Serialize, Deserialize, sqlx::FromRow, Archive, RkyvSerialize, RkyvDeserialize,
@@ -390,7 +445,14 @@ pub struct ApiEndpointInfo {
     pub id: i64,
     pub method: String,
     pub path: String,
+    /// `code`, `openapi`, `graphql` or `proto`
+    pub source: String,
+    pub handler: Option<String>,
+    pub request_type: Option<String>,
+    pub response_type: Option<String>,
     pub file_id: i64,
+    pub file_path: String,
+    /// 0-based
     pub line: Option<i32>,
 }
 
@@ -402,7 +464,24 @@ pub struct FrontendApiCallInfo {
     pub path: String,
     pub path_pattern: Option<String>,
     pub file_id: i64,
+    pub file_path: String,
+    /// 0-based
     pub line: Option<i32>,
+    /// Imported client or wrapper the call goes through, resolved against
+    /// `api_bases`
+    pub client: Option<String>,
+    /// Module specifier `client` was imported from
+    pub client_source: Option<String>,
+    /// Only an API call if `client` resolves to a base or configured wrapper
+    pub needs_client: bool,
+}
+
+/// URL prefix added by a client instance, wrapper or constant
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ApiBaseInfo {
+    pub name: String,
+    pub base_url: String,
+    pub file_path: String,
 }
 
 // === Summarization Types ===
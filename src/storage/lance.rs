@@ -9,13 +9,13 @@ use futures::TryStreamExt;
 use lancedb::{
     connect,
     index::Index,
-    query::{ExecutableQuery, QueryBase},
+    query::{ExecutableQuery, QueryBase, Select},
     table::OptimizeAction,
     Connection, DistanceType, Table,
 };
 use thiserror::Error;
 
-use crate::models::CodeChunk;
+use crate::models::{CodeChunk, SymbolKind};
 
 #[derive(Error, Debug)]
 pub enum LanceError {
@@ -297,6 +297,77 @@ impl LanceStorage {
         Ok(hits)
     }
 
+    /// Every stored chunk, without its vector
+    pub async fn chunks(&self) -> Result<Vec<CodeChunk>> {
+        let Some(table) = &self.table else {
+            return Ok(Vec::new());
+        };
+
+        let batches = table
+            .query()
+            .select(Select::columns(&[
+                "id",
+                "file_path",
+                "content",
+                "line_start",
+                "line_end",
+                "symbol_name",
+                "symbol_kind",
+                "symbol_path",
+            ]))
+            .execute()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut chunks = Vec::new();
+        for batch in batches {
+            let text = |name: &str| {
+                batch
+                    .column_by_name(name)
+                    .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            };
+            let line = |name: &str| {
+                batch
+                    .column_by_name(name)
+                    .and_then(|c| c.as_any().downcast_ref::<UInt32Array>())
+            };
+            let (Some(ids), Some(file_paths), Some(contents), Some(line_starts), Some(line_ends)) = (
+                text("id"),
+                text("file_path"),
+                text("content"),
+                line("line_start"),
+                line("line_end"),
+            ) else {
+                continue;
+            };
+            let optional = |column: Option<&StringArray>, i: usize| {
+                column
+                    .filter(|c| !c.is_null(i))
+                    .map(|c| c.value(i).to_string())
+            };
+            let (symbol_names, symbol_kinds, symbol_paths) = (
+                text("symbol_name"),
+                text("symbol_kind"),
+                text("symbol_path"),
+            );
+            for i in 0..batch.num_rows() {
+                chunks.push(CodeChunk {
+                    id: ids.value(i).to_string(),
+                    file_path: file_paths.value(i).to_string(),
+                    content: contents.value(i).to_string(),
+                    line_start: line_starts.value(i),
+                    line_end: line_ends.value(i),
+                    symbol_name: optional(symbol_names, i),
+                    symbol_kind: optional(symbol_kinds, i).map(|k| SymbolKind::from_str(&k)),
+                    symbol_path: optional(symbol_paths, i),
+                    scopes: Vec::new(),
+                });
+            }
+        }
+        Ok(chunks)
+    }
+
     /// Get total count of chunks (for health checks)
     pub async fn count(&self) -> Result<usize> {
         if let Some(table) = &self.table {
@@ -327,6 +398,45 @@ impl LanceStorage {
         Ok(())
     }
 
+    /// Replace the `old_root` prefix of `file_path` with `new_root` for all chunks.
+    pub async fn rebase_paths(&self, old_root: &str, new_root: &str) -> Result<u64> {
+        let Some(table) = &self.table else {
+            return Ok(0);
+        };
+
+        let old_prefix = format!("{}/", old_root.trim_end_matches('/'));
+        let new_prefix = crate::storage::SqliteStorage::rebase_prefix(new_root);
+        let cut = old_prefix.chars().count() + 1;
+
+        // Chunk ids embed the file path (`path:start:end`), so they move with it
+        let result = table
+            .update()
+            .only_if(format!(
+                "starts_with(file_path, '{}')",
+                escape_filter_string(&old_prefix)
+            ))
+            .column(
+                "file_path",
+                format!(
+                    "concat('{}', substr(file_path, {}))",
+                    escape_filter_string(&new_prefix),
+                    cut
+                ),
+            )
+            .column(
+                "id",
+                format!(
+                    "concat('{}', substr(id, {}))",
+                    escape_filter_string(&new_prefix),
+                    cut
+                ),
+            )
+            .execute()
+            .await?;
+
+        Ok(result.rows_updated)
+    }
+
     /// Create an IVF-PQ vector index for faster ANN search.
     /// Uses incremental logic: only rebuilds if row count grew >= 20% since last build.
     pub async fn create_vector_index_incremental(
@@ -576,6 +686,28 @@ mod tests {
         assert_eq!(storage.count().await.unwrap(), 0);
     }
 
+    #[tokio::test]
+    async fn test_chunks_reads_back_text() {
+        let (mut storage, _temp) = create_test_storage().await;
+        assert!(storage.chunks().await.unwrap().is_empty());
+
+        let chunks = vec![
+            make_chunk("c1", "src/a.rs", "fn a() {}", 1, 3),
+            make_chunk("c2", "src/b.rs", "fn b() {}", 4, 9),
+        ];
+        let embeddings: Vec<_> = (0..2).map(|_| random_vector(TEST_VECTOR_DIM)).collect();
+        storage.upsert_chunks(&chunks, &embeddings).await.unwrap();
+
+        let mut stored = storage.chunks().await.unwrap();
+        stored.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].file_path, "src/b.rs");
+        assert_eq!(stored[1].content, "fn b() {}");
+        assert_eq!((stored[1].line_start, stored[1].line_end), (4, 9));
+        assert_eq!(stored[0].symbol_name.as_deref(), Some("test_symbol"));
+        assert_eq!(stored[0].symbol_path, None);
+    }
+
     // -------------------------------------------------------------------------
     // Search tests
     // -------------------------------------------------------------------------
@@ -714,6 +846,53 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_rebase_paths() {
+        let (mut storage, _temp) = create_test_storage().await;
+
+        let chunks = vec![
+            make_chunk("/old/root/a.rs:1:5", "/old/root/a.rs", "code1", 1, 5),
+            make_chunk("/elsewhere/b.rs:1:5", "/elsewhere/b.rs", "code2", 1, 5),
+        ];
+        let embeddings: Vec<_> = (0..2).map(|_| random_vector(TEST_VECTOR_DIM)).collect();
+        storage.upsert_chunks(&chunks, &embeddings).await.unwrap();
+
+        let updated = storage.rebase_paths("/old/root", "/new").await.unwrap();
+        assert_eq!(updated, 1);
+
+        let hits = storage
+            .search_with_filter(&random_vector(TEST_VECTOR_DIM), 10, Some("/new/"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_path, "/new/a.rs");
+
+        // The rebased chunk is addressable by its new path
+        storage.delete_file("/new/a.rs").await.unwrap();
+        assert_eq!(storage.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rebase_paths_to_relative() {
+        let (mut storage, _temp) = create_test_storage().await;
+
+        let chunk = make_chunk("/proj/src/a.rs:1:5", "/proj/src/a.rs", "code", 1, 5);
+        storage
+            .upsert_chunks(&[chunk], &[random_vector(TEST_VECTOR_DIM)])
+            .await
+            .unwrap();
+
+        let updated = storage.rebase_paths("/proj", "").await.unwrap();
+        assert_eq!(updated, 1);
+
+        let hits = storage
+            .search_with_filter(&random_vector(TEST_VECTOR_DIM), 10, Some("src/"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_path, "src/a.rs");
+    }
+
     // -------------------------------------------------------------------------
     // Edge cases
     // -------------------------------------------------------------------------
@@ -6,11 +6,18 @@ use std::sync::Arc;
 use std::time::Instant;
 use thiserror::Error;
 
+use crate::indexer::api_calls::ApiBase;
+use crate::indexer::column_usages::ColumnUsage;
+use crate::indexer::db_schema::{
+    table_key, ColumnModel, ForeignKeyModel, IndexModel, Location, SchemaModel, TableModel,
+};
+use crate::indexer::domains::{ParsedApiCall, ParsedEndpoint};
+use crate::indexer::tokenize::{code_terms, fts_match_query};
 use crate::models::{
-    ActiveError, ApiEndpointInfo, ConfigKey, CrossStackLink, Dependency, DependencyUsage,
-    DependencyUsageInfo, FileSummary, FileSummaryWithPath, FrontendApiCallInfo, IndexedFile,
-    ReferenceWithPath, Rule, SummaryQueueItem, Symbol, SymbolReference, SymbolWithPath,
-    TypeFingerprint, VueTree,
+    ActiveError, ApiBaseInfo, ApiEndpointInfo, ChunkTextHit, CodeChunk, ConfigKey, CrossStackLink,
+    Dependency, DependencyUsage, DependencyUsageInfo, FileSummary, FileSummaryWithPath,
+    FrontendApiCallInfo, ImportEdge, IndexedFile, ReferenceWithPath, Rule, SummaryQueueItem,
+    Symbol, SymbolReference, SymbolWithPath, TypeFingerprint, VueTree,
 };
 
 #[derive(Error, Debug)]
@@ -42,6 +49,14 @@ fn sanitize_fts5_query(query: &str) -> String {
     format!("\"{}\"", escaped)
 }
 
+/// Escape `%`, `_` and `\` for a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 /// Query performance metrics
 #[derive(Debug, Clone, Default)]
 pub struct QueryMetrics {
@@ -262,12 +277,37 @@ impl SqliteStorage {
         Ok(rows.into_iter().map(|(p, h, m)| (p, (h, m))).collect())
     }
 
+    /// Stored hash, mtime and size of every file, for the pipeline scanner
+    pub async fn get_file_stats(&self) -> Result<std::collections::HashMap<String, FileStat>> {
+        let rows: Vec<(String, String, i64, i64)> =
+            sqlx::query_as("SELECT path, content_hash, last_modified, size FROM files")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(path, hash, modified, size)| {
+                (
+                    path,
+                    FileStat {
+                        hash,
+                        modified,
+                        size,
+                    },
+                )
+            })
+            .collect())
+    }
+
     /// Delete file and its symbols
     pub async fn delete_file(&self, path: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query("DELETE FROM files WHERE path = ?")
             .bind(path)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+        Self::delete_chunk_text(&mut *tx, path).await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -400,6 +440,163 @@ impl SqliteStorage {
         Ok(symbols)
     }
 
+    // === Chunk Full-Text Operations ===
+
+    /// Replace the full-text rows of `file_paths` with `chunks` (which must all belong to them).
+    pub async fn replace_chunk_text(
+        &self,
+        file_paths: &[&str],
+        chunks: &[CodeChunk],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for path in file_paths {
+            Self::delete_chunk_text(&mut *tx, path).await?;
+        }
+
+        for c in chunks {
+            let id = sqlx::query(
+                "INSERT INTO chunk_text (chunk_id, file_path, line_start, line_end, body) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(&c.id)
+            .bind(&c.file_path)
+            .bind(c.line_start as i64)
+            .bind(c.line_end as i64)
+            .bind(&c.content)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+            sqlx::query("INSERT INTO chunks_fts (rowid, body) VALUES (?, ?)")
+                .bind(id)
+                .bind(code_terms(&c.content))
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop the full-text rows of one file. The index holds `code_terms` of each row
+    /// rather than its text, so entries are removed with those terms, found by rowid.
+    async fn delete_chunk_text(conn: &mut sqlx::SqliteConnection, path: &str) -> Result<()> {
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, body FROM chunk_text WHERE file_path = ?")
+                .bind(path)
+                .fetch_all(&mut *conn)
+                .await?;
+        for (id, body) in &rows {
+            sqlx::query("INSERT INTO chunks_fts (chunks_fts, rowid, body) VALUES ('delete', ?, ?)")
+                .bind(id)
+                .bind(code_terms(body))
+                .execute(&mut *conn)
+                .await?;
+        }
+        if !rows.is_empty() {
+            sqlx::query("DELETE FROM chunk_text WHERE file_path = ?")
+                .bind(path)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Drop every full-text row (forced reindex).
+    pub async fn clear_chunk_text(&self) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM chunk_text")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO chunks_fts (chunks_fts) VALUES ('delete-all')")
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// BM25 keyword search over chunk bodies, optionally restricted to a path prefix.
+    pub async fn search_chunk_text(
+        &self,
+        query: &str,
+        limit: i32,
+        path_filter: Option<&str>,
+    ) -> Result<Vec<ChunkTextHit>> {
+        let match_expr = fts_match_query(query);
+        if match_expr.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sql = if path_filter.is_some() {
+            r#"
+            SELECT t.chunk_id, t.file_path, t.line_start, t.line_end, t.body AS content,
+                   -bm25(chunks_fts) AS score
+            FROM chunks_fts
+            JOIN chunk_text t ON t.id = chunks_fts.rowid
+            WHERE chunks_fts MATCH ? AND t.file_path LIKE ? ESCAPE '\'
+            ORDER BY bm25(chunks_fts)
+            LIMIT ?
+            "#
+        } else {
+            r#"
+            SELECT t.chunk_id, t.file_path, t.line_start, t.line_end, t.body AS content,
+                   -bm25(chunks_fts) AS score
+            FROM chunks_fts
+            JOIN chunk_text t ON t.id = chunks_fts.rowid
+            WHERE chunks_fts MATCH ?
+            ORDER BY bm25(chunks_fts)
+            LIMIT ?
+            "#
+        };
+
+        let mut q = sqlx::query_as::<_, ChunkTextHit>(sql).bind(&match_expr);
+        if let Some(prefix) = path_filter {
+            q = q.bind(format!("{}%", escape_like(prefix)));
+        }
+        Ok(q.bind(limit).fetch_all(&self.pool).await?)
+    }
+
+    /// Names of definitions that co-occur with `term`: symbols living in files whose path
+    /// or symbol names contain the term, ranked by the share of their files that do, so a
+    /// helper used everywhere ranks below one that only appears next to the term.
+    /// Used to mine project-specific synonyms ("auth" → `verify_token`); the term match is
+    /// a scan, so callers cache the result.
+    pub async fn co_occurring_symbols(&self, term: &str, limit: i32) -> Result<Vec<String>> {
+        let pattern = format!("%{}%", escape_like(&term.to_lowercase()));
+
+        let names: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            WITH matching AS (
+                SELECT s.file_id FROM symbols s WHERE lower(s.name) LIKE ?1 ESCAPE '\'
+                UNION
+                SELECT f.id FROM files f WHERE lower(f.path) LIKE ?1 ESCAPE '\'
+            ),
+            together AS (
+                SELECT s2.name, COUNT(DISTINCT s2.file_id) AS n
+                FROM symbols s2
+                WHERE s2.file_id IN matching
+                AND lower(s2.name) NOT LIKE ?1 ESCAPE '\'
+                AND s2.kind IN ('function', 'method', 'struct', 'class', 'trait', 'interface')
+                AND length(s2.name) > 3
+                GROUP BY s2.name
+            ),
+            overall AS (
+                SELECT name, COUNT(DISTINCT file_id) AS n
+                FROM symbols
+                WHERE name IN (SELECT name FROM together)
+                GROUP BY name
+            )
+            SELECT t.name, t.n
+            FROM together t JOIN overall o ON o.name = t.name
+            ORDER BY CAST(t.n AS REAL) / o.n DESC, t.n DESC, length(t.name) ASC
+            LIMIT ?2
+            "#,
+        )
+        .bind(&pattern)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names.into_iter().map(|(name, _)| name).collect())
+    }
+
     /// Get all symbols for a file
     pub async fn get_file_symbols(&self, file_id: i64) -> Result<Vec<Symbol>> {
         let symbols = sqlx::query_as::<_, Symbol>(
@@ -424,6 +621,56 @@ impl SqliteStorage {
         Ok(symbols)
     }
 
+    /// Functions and methods with any of the given names, with their file
+    pub async fn get_functions_by_names(&self, names: &[String]) -> Result<Vec<SymbolWithPath>> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut builder = sqlx::QueryBuilder::new(
+            r#"
+            SELECT s.id, s.name, s.kind, s.line_start AS line, s.line_end AS end_line,
+                   s.signature, f.path AS file_path
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.kind IN ('function', 'method') AND s.name IN (
+            "#,
+        );
+        let mut separated = builder.separated(", ");
+        for name in names {
+            separated.push_bind(name);
+        }
+        separated.push_unseparated(") ORDER BY f.path, s.line_start");
+        Ok(builder
+            .build_query_as::<SymbolWithPath>()
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    /// Definitions (anything but local variables) with any of the given names, with their file
+    pub async fn get_definitions_by_names(&self, names: &[String]) -> Result<Vec<SymbolWithPath>> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut builder = sqlx::QueryBuilder::new(
+            r#"
+            SELECT s.id, s.name, s.kind, s.line_start AS line, s.line_end AS end_line,
+                   s.signature, f.path AS file_path
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.kind != 'local_var' AND s.name IN (
+            "#,
+        );
+        let mut separated = builder.separated(", ");
+        for name in names {
+            separated.push_bind(name);
+        }
+        separated.push_unseparated(") ORDER BY f.path, s.line_start");
+        Ok(builder
+            .build_query_as::<SymbolWithPath>()
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
     /// Get symbol by id
     pub async fn get_symbol_by_id(&self, id: i64) -> Result<Option<Symbol>> {
         let symbol = sqlx::query_as::<_, Symbol>(
@@ -1152,30 +1399,89 @@ impl SqliteStorage {
         Ok(())
     }
 
-    /// Insert API endpoint
-    pub async fn insert_api_endpoint(
+    /// Replace the API endpoints a file declares (`source` is one of the
+    /// `api_contracts::SOURCE_*` values)
+    pub async fn replace_api_endpoints(
         &self,
-        method: &str,
-        path: &str,
         file_id: i64,
-        line: i32,
+        source: &str,
+        endpoints: &[ParsedEndpoint],
     ) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO api_endpoints (method, path, file_id, line)
-            VALUES (?, ?, ?, ?)
-            ON CONFLICT(method, path) DO UPDATE SET
-                file_id = excluded.file_id,
-                line = excluded.line
-            "#,
-        )
-        .bind(method)
-        .bind(path)
-        .bind(file_id)
-        .bind(line)
-        .execute(&self.pool)
-        .await?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM api_endpoints WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        for chunk in endpoints.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO api_endpoints (method, path, source, handler, request_type, response_type, file_id, line) ",
+            );
+            builder.push_values(chunk, |mut b, e| {
+                b.push_bind(&e.method)
+                    .push_bind(&e.path)
+                    .push_bind(source)
+                    .push_bind(&e.handler)
+                    .push_bind(&e.request_type)
+                    .push_bind(&e.response_type)
+                    .push_bind(file_id)
+                    .push_bind(e.line as i32);
+            });
+            builder.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace the API calls a file makes
+    pub async fn replace_frontend_api_calls(
+        &self,
+        file_id: i64,
+        calls: &[ParsedApiCall],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM frontend_api_calls WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        for chunk in calls.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO frontend_api_calls (method, path, path_pattern, file_id, line, client, client_source, needs_client) ",
+            );
+            builder.push_values(chunk, |mut b, c| {
+                b.push_bind(&c.method)
+                    .push_bind(&c.path)
+                    .push_bind(&c.path_pattern)
+                    .push_bind(file_id)
+                    .push_bind(c.line as i32)
+                    .push_bind(&c.client)
+                    .push_bind(&c.client_source)
+                    .push_bind(c.needs_client);
+            });
+            builder.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
 
+    /// Replace the API client bases defined by a file
+    pub async fn replace_api_bases(&self, file_id: i64, bases: &[ApiBase]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM api_bases WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        for chunk in bases.chunks(100) {
+            let mut builder =
+                sqlx::QueryBuilder::new("INSERT INTO api_bases (name, base_url, file_id, line) ");
+            builder.push_values(chunk, |mut b, base| {
+                b.push_bind(&base.name)
+                    .push_bind(&base.base_url)
+                    .push_bind(file_id)
+                    .push_bind(base.line as i32);
+            });
+            builder.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
         Ok(())
     }
 
@@ -1386,13 +1692,40 @@ impl SqliteStorage {
         Ok(stats)
     }
 
+    /// Domain the index detected for each of `paths` that has one
+    pub async fn get_file_domains(
+        &self,
+        paths: &[String],
+    ) -> Result<std::collections::HashMap<String, String>> {
+        let mut domains = std::collections::HashMap::new();
+        for chunk in paths.chunks(500) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "SELECT path, domain FROM files WHERE domain IS NOT NULL AND path IN (",
+            );
+            let mut separated = builder.separated(", ");
+            for path in chunk {
+                separated.push_bind(path);
+            }
+            separated.push_unseparated(")");
+            domains.extend(
+                builder
+                    .build_query_as::<(String, String)>()
+                    .fetch_all(&self.pool)
+                    .await?,
+            );
+        }
+        Ok(domains)
+    }
+
     /// Get all API endpoints
     pub async fn get_api_endpoints(&self) -> Result<Vec<ApiEndpointInfo>> {
         let endpoints = sqlx::query_as::<_, ApiEndpointInfo>(
             r#"
-            SELECT id, method, path, file_id, line
-            FROM api_endpoints
-            ORDER BY path, method
+            SELECT e.id, e.method, e.path, e.source, e.handler, e.request_type,
+                   e.response_type, e.file_id, f.path AS file_path, e.line
+            FROM api_endpoints e
+            JOIN files f ON f.id = e.file_id
+            ORDER BY e.path, e.method
             "#,
         )
         .fetch_all(&self.pool)
@@ -1405,9 +1738,11 @@ impl SqliteStorage {
     pub async fn get_frontend_api_calls(&self) -> Result<Vec<FrontendApiCallInfo>> {
         let calls = sqlx::query_as::<_, FrontendApiCallInfo>(
             r#"
-            SELECT id, method, path, path_pattern, file_id, line
-            FROM frontend_api_calls
-            ORDER BY path
+            SELECT c.id, c.method, c.path, c.path_pattern, c.file_id, f.path AS file_path, c.line,
+                   c.client, c.client_source, c.needs_client
+            FROM frontend_api_calls c
+            JOIN files f ON f.id = c.file_id
+            ORDER BY c.path
             "#,
         )
         .fetch_all(&self.pool)
@@ -1416,6 +1751,132 @@ impl SqliteStorage {
         Ok(calls)
     }
 
+    /// Get the client bases calls in other files are resolved against
+    pub async fn get_api_bases(&self) -> Result<Vec<ApiBaseInfo>> {
+        let bases = sqlx::query_as::<_, ApiBaseInfo>(
+            r#"
+            SELECT b.name, b.base_url, f.path AS file_path
+            FROM api_bases b
+            JOIN files f ON f.id = b.file_id
+            ORDER BY f.path, b.line
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(bases)
+    }
+
+    // === Import Graph Operations ===
+
+    /// Replace the import edges of a file
+    pub async fn replace_file_imports(&self, file_id: i64, edges: &[ImportEdge]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM file_imports WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        for chunk in edges.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO file_imports (file_id, target_path, line, specifier) ",
+            );
+            builder.push_values(chunk, |mut b, e| {
+                b.push_bind(file_id)
+                    .push_bind(&e.to_path)
+                    .push_bind(e.line)
+                    .push_bind(&e.specifier);
+            });
+            builder.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Import edges leaving `paths`, or arriving at them when `reverse`
+    pub async fn get_import_edges(
+        &self,
+        paths: &[String],
+        reverse: bool,
+    ) -> Result<Vec<ImportEdge>> {
+        let mut edges = Vec::new();
+        for chunk in paths.chunks(500) {
+            let mut builder = sqlx::QueryBuilder::new(
+                r#"
+                SELECT f.path AS from_path, fi.target_path AS to_path, fi.line, fi.specifier
+                FROM file_imports fi
+                JOIN files f ON f.id = fi.file_id
+                WHERE "#,
+            );
+            builder.push(if reverse { "fi.target_path" } else { "f.path" });
+            builder.push(" IN (");
+            let mut separated = builder.separated(", ");
+            for path in chunk {
+                separated.push_bind(path);
+            }
+            separated.push_unseparated(") ORDER BY f.path, fi.line");
+            edges.extend(
+                builder
+                    .build_query_as::<ImportEdge>()
+                    .fetch_all(&self.pool)
+                    .await?,
+            );
+        }
+        Ok(edges)
+    }
+
+    /// Edges reachable from `path` within `depth` hops, each with the hop it
+    /// was found at (1 = direct). `reverse` follows importers instead.
+    pub async fn get_import_closure(
+        &self,
+        path: &str,
+        depth: u32,
+        reverse: bool,
+    ) -> Result<Vec<(ImportEdge, u32)>> {
+        let mut seen: std::collections::HashSet<String> = [path.to_string()].into();
+        let mut frontier = vec![path.to_string()];
+        let mut closure = Vec::new();
+        for hop in 1..=depth {
+            if frontier.is_empty() {
+                break;
+            }
+            let edges = self.get_import_edges(&frontier, reverse).await?;
+            frontier.clear();
+            for edge in edges {
+                let next = if reverse {
+                    &edge.from_path
+                } else {
+                    &edge.to_path
+                };
+                if seen.insert(next.clone()) {
+                    frontier.push(next.clone());
+                }
+                closure.push((edge, hop));
+            }
+        }
+        Ok(closure)
+    }
+
+    /// Number of files importing each of `paths` (absent when none do)
+    pub async fn get_importer_counts(
+        &self,
+        paths: &[String],
+    ) -> Result<std::collections::HashMap<String, i64>> {
+        let mut counts = std::collections::HashMap::new();
+        for chunk in paths.chunks(500) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "SELECT target_path, COUNT(DISTINCT file_id) FROM file_imports WHERE target_path IN (",
+            );
+            let mut separated = builder.separated(", ");
+            for path in chunk {
+                separated.push_bind(path);
+            }
+            separated.push_unseparated(") GROUP BY target_path");
+            let rows: Vec<(String, i64)> = builder.build_query_as().fetch_all(&self.pool).await?;
+            counts.extend(rows);
+        }
+        Ok(counts)
+    }
+
     // === Summary Operations ===
 
     /// Insert or update a file summary
@@ -1855,6 +2316,80 @@ impl SqliteStorage {
         Ok(())
     }
 
+    /// Highest applied migration version (0 for a fresh database).
+    pub async fn schema_version(&self) -> Result<i64> {
+        let version = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success = 1",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(version)
+    }
+
+    /// Latest migration version shipped with this binary.
+    pub fn latest_schema_version() -> i64 {
+        sqlx::migrate!("./migrations")
+            .iter()
+            .map(|m| m.version)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Write a consistent copy of the database to `dest` (VACUUM INTO).
+    pub async fn snapshot_to(&self, dest: &Path) -> Result<()> {
+        if dest.exists() {
+            tokio::fs::remove_file(dest).await?;
+        }
+        sqlx::query("VACUUM INTO ?")
+            .bind(dest.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replace the `old_root` prefix of every stored file path with `new_root`.
+    /// An empty `new_root` strips the prefix, turning absolute paths into root-relative ones.
+    pub async fn rebase_paths(&self, old_root: &str, new_root: &str) -> Result<u64> {
+        let old_prefix = format!("{}/", old_root.trim_end_matches('/'));
+        let new_prefix = Self::rebase_prefix(new_root);
+        let cut = old_prefix.chars().count() as i64 + 1;
+
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0u64;
+        for (table, column) in [
+            ("files", "path"),
+            ("active_errors", "file_path"),
+            ("cross_stack_links", "source_file"),
+            ("cross_stack_links", "target_file"),
+            ("chunk_text", "chunk_id"),
+            ("chunk_text", "file_path"),
+        ] {
+            let sql = format!(
+                "UPDATE {table} SET {column} = ? || substr({column}, ?) WHERE substr({column}, 1, ?) = ?"
+            );
+            let res = sqlx::query(&sql)
+                .bind(&new_prefix)
+                .bind(cut)
+                .bind(cut - 1)
+                .bind(&old_prefix)
+                .execute(&mut *tx)
+                .await?;
+            updated += res.rows_affected();
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    /// Prefix that replaces the old root in [`Self::rebase_paths`]; empty for relative paths.
+    pub(crate) fn rebase_prefix(new_root: &str) -> String {
+        let trimmed = new_root.trim_end_matches('/');
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", trimmed)
+        }
+    }
+
     // === Chunk Embedding Cache ===
 
     /// Look up cached embeddings by content hashes. Returns a map of hash → embedding.
@@ -2009,6 +2544,7 @@ impl SqliteStorage {
 
 impl SqliteStorage {
     /// Record a tool call in the audit log.
+    /// `result_paths` is a JSON array of files returned by search-type tools.
     pub async fn log_tool_call(
         &self,
         tool_name: &str,
@@ -2016,49 +2552,693 @@ impl SqliteStorage {
         latency_ms: u64,
         success: bool,
         error_msg: Option<&str>,
+        result_paths: Option<&str>,
     ) -> Result<()> {
         sqlx::query(
-            "INSERT INTO audit_log (tool_name, args_json, latency_ms, success, error_msg) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO audit_log (tool_name, args_json, latency_ms, success, error_msg, result_paths) VALUES (?, ?, ?, ?, ?, ?)",
         )
         .bind(tool_name)
         .bind(args_json)
         .bind(latency_ms as i64)
         .bind(success as i32)
         .bind(error_msg)
+        .bind(result_paths)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
+
+    /// Most recent successful search-type call since `since` (unix seconds) whose results
+    /// included `file_path`. Returns the audit row id and its arguments.
+    pub async fn find_search_returning(
+        &self,
+        file_path: &str,
+        since: i64,
+    ) -> Result<Option<(i64, Option<String>)>> {
+        let row: Option<(i64, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT id, args_json FROM audit_log
+            WHERE result_paths IS NOT NULL
+              AND success = 1
+              AND created_at >= ?
+              AND EXISTS (SELECT 1 FROM json_each(audit_log.result_paths) WHERE json_each.value = ?)
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(since)
+        .bind(file_path)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
 }
 
-#[derive(Debug, Clone, sqlx::FromRow)]
-#[allow(dead_code)]
-pub struct SubprojectRecord {
-    pub id: i64,
-    pub name: String,
-    pub path: String,
-    pub kind: String,
-    pub parent_path: Option<String>,
+/// What the index last saw of a file (see `get_file_stats`).
+#[derive(Debug, Clone)]
+pub struct FileStat {
+    pub hash: String,
+    pub modified: i64,
+    /// Bytes; -1 when unknown
+    pub size: i64,
 }
 
-// =============================================================================
-// Unit Tests
-// =============================================================================
+// === Search Feedback ===
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+/// Decayed click count for a (normalized query, file) pair.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SearchFeedbackRow {
+    pub query: String,
+    pub file_path: String,
+    pub score: f64,
+    pub updated_at: i64,
+}
 
-    async fn create_test_storage() -> (SqliteStorage, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("test.db");
-        let storage = SqliteStorage::new(db_path.to_str().unwrap()).await.unwrap();
-        storage.migrate().await.unwrap();
-        (storage, temp_dir)
+impl SearchFeedbackRow {
+    /// Score as of `now`, halving every `half_life_secs`.
+    pub fn decayed(&self, now: i64, half_life_secs: f64) -> f64 {
+        decay_score(self.score, (now - self.updated_at) as f64, half_life_secs)
     }
+}
 
-    // -------------------------------------------------------------------------
+fn decay_score(score: f64, elapsed_secs: f64, half_life_secs: f64) -> f64 {
+    if half_life_secs <= 0.0 {
+        return score;
+    }
+    score * 0.5f64.powf(elapsed_secs.max(0.0) / half_life_secs)
+}
+
+impl SqliteStorage {
+    /// Count a follow-up read of `file_path` after the search with audit id `search_id`.
+    /// Repeated reads after the same search count once.
+    pub async fn record_search_click(
+        &self,
+        query: &str,
+        file_path: &str,
+        search_id: i64,
+        half_life_secs: f64,
+        now: i64,
+    ) -> Result<()> {
+        let existing: Option<(f64, i64, Option<i64>)> = sqlx::query_as(
+            "SELECT score, updated_at, last_search_id FROM search_feedback WHERE query = ? AND file_path = ?",
+        )
+        .bind(query)
+        .bind(file_path)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let score = match existing {
+            Some((_, _, Some(last))) if last == search_id => return Ok(()),
+            Some((score, updated_at, _)) => {
+                decay_score(score, (now - updated_at) as f64, half_life_secs) + 1.0
+            }
+            None => 1.0,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO search_feedback (query, file_path, score, updated_at, last_search_id)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(query, file_path) DO UPDATE SET
+                score = excluded.score,
+                updated_at = excluded.updated_at,
+                last_search_id = excluded.last_search_id
+            "#,
+        )
+        .bind(query)
+        .bind(file_path)
+        .bind(score)
+        .bind(now)
+        .bind(search_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// All feedback rows for the given files (any query).
+    pub async fn get_search_feedback(
+        &self,
+        file_paths: &[String],
+    ) -> Result<Vec<SearchFeedbackRow>> {
+        if file_paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; file_paths.len()].join(", ");
+        let sql = format!(
+            "SELECT query, file_path, score, updated_at FROM search_feedback WHERE file_path IN ({})",
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, SearchFeedbackRow>(&sql);
+        for path in file_paths {
+            query = query.bind(path);
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+}
+
+/// Saved agent workspace state (see `save_workspace_state`).
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WorkspaceStateRow {
+    pub state_id: String,
+    pub task: Option<String>,
+    /// JSON object
+    pub metadata: String,
+    /// JSON array of paths
+    pub touched_files: String,
+    /// JSON array of `{transaction_id, operations}`
+    pub transactions: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// TODO note anchored to a file and optionally a symbol.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TodoNoteRow {
+    pub id: i64,
+    pub file_path: String,
+    pub symbol_name: Option<String>,
+    pub line: Option<i64>,
+    pub anchor_text: Option<String>,
+    pub text: String,
+    pub priority: String,
+    pub state_id: Option<String>,
+    pub created_at: i64,
+    pub resolved_at: Option<i64>,
+}
+
+/// Filter for [`SqliteStorage::list_todo_notes`]; `None` fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct TodoFilter {
+    pub file_path: Option<String>,
+    pub priority: Option<String>,
+    pub state_id: Option<String>,
+    pub include_resolved: bool,
+}
+
+impl SqliteStorage {
+    /// Insert or replace a workspace state, keeping its original `created_at`.
+    pub async fn save_workspace_state(
+        &self,
+        state_id: &str,
+        task: Option<&str>,
+        metadata: &str,
+        touched_files: &str,
+        transactions: &str,
+        now: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workspace_states
+                (state_id, task, metadata, touched_files, transactions, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(state_id) DO UPDATE SET
+                task = excluded.task,
+                metadata = excluded.metadata,
+                touched_files = excluded.touched_files,
+                transactions = excluded.transactions,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(state_id)
+        .bind(task)
+        .bind(metadata)
+        .bind(touched_files)
+        .bind(transactions)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_workspace_state(&self, state_id: &str) -> Result<Option<WorkspaceStateRow>> {
+        Ok(sqlx::query_as::<_, WorkspaceStateRow>(
+            "SELECT state_id, task, metadata, touched_files, transactions, created_at, updated_at FROM workspace_states WHERE state_id = ?",
+        )
+        .bind(state_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// All workspace states, most recently updated first.
+    pub async fn list_workspace_states(&self) -> Result<Vec<WorkspaceStateRow>> {
+        Ok(sqlx::query_as::<_, WorkspaceStateRow>(
+            "SELECT state_id, task, metadata, touched_files, transactions, created_at, updated_at FROM workspace_states ORDER BY updated_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Delete a state; its notes are kept but detached. Returns false if it did not exist.
+    pub async fn delete_workspace_state(&self, state_id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE todo_notes SET state_id = NULL WHERE state_id = ?")
+            .bind(state_id)
+            .execute(&mut *tx)
+            .await?;
+        let deleted = sqlx::query("DELETE FROM workspace_states WHERE state_id = ?")
+            .bind(state_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(deleted > 0)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_todo_note(
+        &self,
+        file_path: &str,
+        symbol_name: Option<&str>,
+        line: Option<i64>,
+        anchor_text: Option<&str>,
+        text: &str,
+        priority: &str,
+        state_id: Option<&str>,
+        now: i64,
+    ) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO todo_notes
+                (file_path, symbol_name, line, anchor_text, text, priority, state_id, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(file_path)
+        .bind(symbol_name)
+        .bind(line)
+        .bind(anchor_text)
+        .bind(text)
+        .bind(priority)
+        .bind(state_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn list_todo_notes(&self, filter: &TodoFilter) -> Result<Vec<TodoNoteRow>> {
+        let mut sql = String::from(
+            "SELECT id, file_path, symbol_name, line, anchor_text, text, priority, state_id, created_at, resolved_at FROM todo_notes WHERE 1 = 1",
+        );
+        if filter.file_path.is_some() {
+            sql.push_str(" AND file_path = ?");
+        }
+        if filter.priority.is_some() {
+            sql.push_str(" AND priority = ?");
+        }
+        if filter.state_id.is_some() {
+            sql.push_str(" AND state_id = ?");
+        }
+        if !filter.include_resolved {
+            sql.push_str(" AND resolved_at IS NULL");
+        }
+        sql.push_str(
+            " ORDER BY CASE priority WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END, id",
+        );
+
+        let mut query = sqlx::query_as::<_, TodoNoteRow>(&sql);
+        for value in [&filter.file_path, &filter.priority, &filter.state_id]
+            .into_iter()
+            .flatten()
+        {
+            query = query.bind(value);
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    /// Store a re-resolved anchor position.
+    pub async fn update_todo_anchor(
+        &self,
+        id: i64,
+        file_path: &str,
+        line: Option<i64>,
+        anchor_text: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE todo_notes SET file_path = ?, line = ?, anchor_text = ? WHERE id = ?")
+            .bind(file_path)
+            .bind(line)
+            .bind(anchor_text)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark a note done. Returns false if it does not exist or was already resolved.
+    pub async fn resolve_todo_note(&self, id: i64, now: i64) -> Result<bool> {
+        let updated = sqlx::query(
+            "UPDATE todo_notes SET resolved_at = ? WHERE id = ? AND resolved_at IS NULL",
+        )
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(updated > 0)
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+#[allow(dead_code)]
+pub struct SubprojectRecord {
+    pub id: i64,
+    pub name: String,
+    pub path: String,
+    pub kind: String,
+    pub parent_path: Option<String>,
+}
+
+// === Database Schema Model ===
+
+/// A code reference to a table or column (see `column_usages` in migration 021).
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ColumnUsageRow {
+    pub file_path: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub line: i64,
+    pub kind: String,
+    pub ambiguous: bool,
+    pub context: Option<String>,
+}
+
+/// Field names of a struct/class/interface from `type_fingerprints`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TypeFieldsRow {
+    pub type_name: String,
+    pub fields_json: String,
+    pub file_path: String,
+    /// 1-based line of the type, 0 when its symbol is gone
+    pub line: i64,
+}
+
+impl SqliteStorage {
+    /// Path and content hash of every indexed file of one `files.language`.
+    pub async fn get_files_by_language(&self, language: &str) -> Result<Vec<(String, String)>> {
+        Ok(
+            sqlx::query_as("SELECT path, content_hash FROM files WHERE language = ? ORDER BY path")
+                .bind(language)
+                .fetch_all(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Replace the stored schema model.
+    pub async fn replace_db_schema(&self, model: &SchemaModel) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["db_foreign_keys", "db_indexes", "db_columns", "db_tables"] {
+            sqlx::query(&format!("DELETE FROM {}", table))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for table in &model.tables {
+            let table_id = sqlx::query(
+                r#"
+                INSERT INTO db_tables (name, file_path, line, dropped_file, dropped_line, renamed_to)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&table.name)
+            .bind(&table.defined.file)
+            .bind(table.defined.line as i64)
+            .bind(table.dropped.as_ref().map(|d| &d.file))
+            .bind(table.dropped.as_ref().map(|d| d.line as i64))
+            .bind(&table.renamed_to)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+
+            for (position, column) in table.columns.iter().enumerate() {
+                sqlx::query(
+                    r#"
+                    INSERT INTO db_columns
+                        (table_id, name, data_type, not_null, primary_key, position,
+                         file_path, line, dropped_file, dropped_line, renamed_to)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(table_id)
+                .bind(&column.name)
+                .bind(&column.data_type)
+                .bind(column.not_null)
+                .bind(column.primary_key)
+                .bind(position as i64)
+                .bind(&column.defined.file)
+                .bind(column.defined.line as i64)
+                .bind(column.dropped.as_ref().map(|d| &d.file))
+                .bind(column.dropped.as_ref().map(|d| d.line as i64))
+                .bind(&column.renamed_to)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for index in &table.indexes {
+                sqlx::query(
+                    "INSERT INTO db_indexes (table_id, name, columns, is_unique, file_path, line) VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(table_id)
+                .bind(&index.name)
+                .bind(serde_json::to_string(&index.columns).unwrap_or_default())
+                .bind(index.unique)
+                .bind(&index.defined.file)
+                .bind(index.defined.line as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for fk in &table.foreign_keys {
+                sqlx::query(
+                    "INSERT INTO db_foreign_keys (table_id, columns, ref_table, ref_columns, file_path, line) VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(table_id)
+                .bind(serde_json::to_string(&fk.columns).unwrap_or_default())
+                .bind(&fk.ref_table)
+                .bind(serde_json::to_string(&fk.ref_columns).unwrap_or_default())
+                .bind(&fk.defined.file)
+                .bind(fk.defined.line as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Load the stored schema model, live tables first.
+    pub async fn get_db_schema(&self) -> Result<SchemaModel> {
+        type TableRow = (
+            i64,
+            String,
+            String,
+            i64,
+            Option<String>,
+            Option<i64>,
+            Option<String>,
+        );
+        type ColumnRow = (
+            i64,
+            String,
+            String,
+            bool,
+            bool,
+            String,
+            i64,
+            Option<String>,
+            Option<i64>,
+            Option<String>,
+        );
+        type ListRow = (i64, String, String, bool, String, i64);
+
+        let location = |file: Option<String>, line: Option<i64>| {
+            file.map(|file| Location {
+                file,
+                line: line.unwrap_or(0) as usize,
+            })
+        };
+        let list = |json: &str| serde_json::from_str::<Vec<String>>(json).unwrap_or_default();
+
+        let tables: Vec<TableRow> = sqlx::query_as(
+            "SELECT id, name, file_path, line, dropped_file, dropped_line, renamed_to FROM db_tables ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let columns: Vec<ColumnRow> = sqlx::query_as(
+            r#"
+            SELECT table_id, name, data_type, not_null, primary_key, file_path, line,
+                   dropped_file, dropped_line, renamed_to
+            FROM db_columns ORDER BY table_id, position
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let indexes: Vec<ListRow> = sqlx::query_as(
+            "SELECT table_id, name, columns, is_unique, file_path, line FROM db_indexes ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let foreign_keys: Vec<(i64, String, String, String, String, i64)> = sqlx::query_as(
+            "SELECT table_id, columns, ref_table, ref_columns, file_path, line FROM db_foreign_keys ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut model = SchemaModel::default();
+        for (id, name, file, line, dropped_file, dropped_line, renamed_to) in tables {
+            model.tables.push(TableModel {
+                name,
+                defined: Location {
+                    file,
+                    line: line as usize,
+                },
+                dropped: location(dropped_file, dropped_line),
+                renamed_to,
+                columns: columns
+                    .iter()
+                    .filter(|c| c.0 == id)
+                    .map(|c| ColumnModel {
+                        name: c.1.clone(),
+                        data_type: c.2.clone(),
+                        not_null: c.3,
+                        primary_key: c.4,
+                        defined: Location {
+                            file: c.5.clone(),
+                            line: c.6 as usize,
+                        },
+                        dropped: location(c.7.clone(), c.8),
+                        renamed_to: c.9.clone(),
+                    })
+                    .collect(),
+                indexes: indexes
+                    .iter()
+                    .filter(|i| i.0 == id)
+                    .map(|i| IndexModel {
+                        name: i.1.clone(),
+                        columns: list(&i.2),
+                        unique: i.3,
+                        defined: Location {
+                            file: i.4.clone(),
+                            line: i.5 as usize,
+                        },
+                    })
+                    .collect(),
+                foreign_keys: foreign_keys
+                    .iter()
+                    .filter(|fk| fk.0 == id)
+                    .map(|fk| ForeignKeyModel {
+                        columns: list(&fk.1),
+                        ref_table: fk.2.clone(),
+                        ref_columns: list(&fk.3),
+                        defined: Location {
+                            file: fk.4.clone(),
+                            line: fk.5 as usize,
+                        },
+                    })
+                    .collect(),
+            });
+        }
+        model.tables.sort_by_key(|t| t.dropped.is_some());
+        Ok(model)
+    }
+
+    /// Replace the table/column usages recorded for a file.
+    pub async fn replace_column_usages(&self, file_id: i64, usages: &[ColumnUsage]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM column_usages WHERE file_id = ?")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        for chunk in usages.chunks(100) {
+            let mut builder = sqlx::QueryBuilder::new(
+                "INSERT INTO column_usages (file_id, table_name, column_name, line, kind, ambiguous, context) ",
+            );
+            builder.push_values(chunk, |mut b, u| {
+                b.push_bind(file_id)
+                    .push_bind(table_key(&u.table))
+                    .push_bind(&u.column)
+                    .push_bind(u.line as i64)
+                    .push_bind(u.kind)
+                    .push_bind(u.ambiguous)
+                    .push_bind(&u.context);
+            });
+            builder.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Usages of the given tables (lookup keys, see `table_key`), or of all
+    /// tables when `tables` is empty.
+    pub async fn get_column_usages(&self, tables: &[String]) -> Result<Vec<ColumnUsageRow>> {
+        let mut builder = sqlx::QueryBuilder::new(
+            r#"
+            SELECT f.path AS file_path, cu.table_name, cu.column_name, cu.line, cu.kind,
+                   cu.ambiguous, cu.context
+            FROM column_usages cu
+            JOIN files f ON cu.file_id = f.id
+            "#,
+        );
+        if !tables.is_empty() {
+            builder.push(" WHERE cu.table_name IN (");
+            let mut separated = builder.separated(", ");
+            for table in tables {
+                separated.push_bind(table);
+            }
+            separated.push_unseparated(")");
+        }
+        builder.push(" ORDER BY f.path, cu.line");
+        Ok(builder
+            .build_query_as::<ColumnUsageRow>()
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    /// Field lists of the named types (case-insensitive).
+    pub async fn get_type_fields_by_names(&self, names: &[String]) -> Result<Vec<TypeFieldsRow>> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut builder = sqlx::QueryBuilder::new(
+            r#"
+            SELECT tf.type_name, tf.fields_json, f.path AS file_path,
+                   COALESCE(s.line_start + 1, 0) AS line
+            FROM type_fingerprints tf
+            JOIN files f ON tf.file_id = f.id
+            LEFT JOIN symbols s ON tf.symbol_id = s.id
+            WHERE lower(tf.type_name) IN (
+            "#,
+        );
+        let mut separated = builder.separated(", ");
+        for name in names {
+            separated.push_bind(name.to_ascii_lowercase());
+        }
+        separated.push_unseparated(") ORDER BY f.path");
+        Ok(builder
+            .build_query_as::<TypeFieldsRow>()
+            .fetch_all(&self.pool)
+            .await?)
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn create_test_storage() -> (SqliteStorage, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let storage = SqliteStorage::new(db_path.to_str().unwrap()).await.unwrap();
+        storage.migrate().await.unwrap();
+        (storage, temp_dir)
+    }
+
+    // -------------------------------------------------------------------------
     // Basic connectivity tests
     // -------------------------------------------------------------------------
 
@@ -2359,6 +3539,102 @@ mod tests {
         assert_eq!(value, Some("new_value".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_schema_version() {
+        let (storage, _temp) = create_test_storage().await;
+        assert!(storage.schema_version().await.unwrap() >= 16);
+    }
+
+    #[tokio::test]
+    async fn test_rebase_paths() {
+        let (storage, _temp) = create_test_storage().await;
+
+        storage
+            .upsert_file("/old/root/src/main.rs", 1, "h1")
+            .await
+            .unwrap();
+        storage
+            .upsert_file("/old/rootless/lib.rs", 1, "h2")
+            .await
+            .unwrap();
+
+        let updated = storage
+            .rebase_paths("/old/root", "/new/place")
+            .await
+            .unwrap();
+        assert_eq!(updated, 1);
+
+        assert!(storage
+            .get_file("/new/place/src/main.rs")
+            .await
+            .unwrap()
+            .is_some());
+        // Sibling directory sharing the prefix must not be touched
+        assert!(storage
+            .get_file("/old/rootless/lib.rs")
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rebase_paths_to_relative() {
+        let (storage, _temp) = create_test_storage().await;
+
+        storage
+            .upsert_file("/home/me/proj/src/lib.rs", 1, "h1")
+            .await
+            .unwrap();
+
+        let updated = storage.rebase_paths("/home/me/proj/", "").await.unwrap();
+        assert_eq!(updated, 1);
+        assert!(storage.get_file("src/lib.rs").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_co_occurring_symbols() {
+        let (storage, _temp) = create_test_storage().await;
+        let function = |file_id, name: &str| Symbol {
+            id: 0,
+            file_id,
+            name: name.to_string(),
+            kind: crate::models::chunk::SymbolKind::Function,
+            line_start: 1,
+            line_end: 2,
+            signature: None,
+        };
+        // `log_event` is everywhere, `verify_token` only next to auth code
+        for (path, names) in [
+            ("src/auth/session.rs", vec!["verify_token", "log_event"]),
+            (
+                "src/auth/login.rs",
+                vec!["verify_token", "log_event", "hash_password"],
+            ),
+            ("src/db/pool.rs", vec!["log_event", "connect"]),
+            ("src/db/query.rs", vec!["log_event", "hash_password"]),
+            ("src/api/users.rs", vec!["log_event", "auth_header"]),
+        ] {
+            let file_id = storage.upsert_file(path, 1, "h").await.unwrap();
+            let symbols: Vec<Symbol> = names.iter().map(|n| function(file_id, n)).collect();
+            storage.insert_symbols(file_id, &symbols).await.unwrap();
+        }
+
+        let names = storage.co_occurring_symbols("auth", 3).await.unwrap();
+        assert_eq!(names, vec!["verify_token", "log_event", "hash_password"]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_to() {
+        let (storage, temp) = create_test_storage().await;
+        storage.upsert_file("/a.rs", 1, "h").await.unwrap();
+
+        let dest = temp.path().join("copy.db");
+        storage.snapshot_to(&dest).await.unwrap();
+
+        let copy = SqliteStorage::new(dest.to_str().unwrap()).await.unwrap();
+        assert!(copy.get_file("/a.rs").await.unwrap().is_some());
+    }
+
     // -------------------------------------------------------------------------
     // Rules tests
     // -------------------------------------------------------------------------
@@ -2389,6 +3665,130 @@ mod tests {
         storage.clear_active_errors().await.unwrap();
     }
 
+    // -------------------------------------------------------------------------
+    // Chunk full-text tests
+    // -------------------------------------------------------------------------
+
+    fn text_chunk(file_path: &str, content: &str, line_start: u32) -> CodeChunk {
+        CodeChunk {
+            id: format!("{}:{}:{}", file_path, line_start, line_start + 5),
+            file_path: file_path.to_string(),
+            content: content.to_string(),
+            line_start,
+            line_end: line_start + 5,
+            symbol_name: None,
+            symbol_kind: None,
+            symbol_path: None,
+            scopes: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_chunk_text_splits_identifiers() {
+        let (storage, _temp) = create_test_storage().await;
+
+        let chunks = vec![
+            text_chunk("src/cfg.rs", "fn load() { parseConfigFile(p) }", 1),
+            text_chunk("src/log.rs", "error!(\"retry_count exceeded\");", 10),
+        ];
+        storage
+            .replace_chunk_text(&["src/cfg.rs", "src/log.rs"], &chunks)
+            .await
+            .unwrap();
+
+        // camelCase body identifier reachable by its words
+        let hits = storage
+            .search_chunk_text("config file", 10, None)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_path, "src/cfg.rs");
+
+        // exact snake_case identifier inside a string literal
+        let hits = storage
+            .search_chunk_text("retry_count", 10, None)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_start, 10);
+
+        // path prefix filter
+        let hits = storage
+            .search_chunk_text("exceeded", 10, Some("src/cfg"))
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+
+        // `_` in the prefix is literal, not a wildcard
+        let hits = storage
+            .search_chunk_text("exceeded", 10, Some("src_log"))
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+        let hits = storage
+            .search_chunk_text("exceeded", 10, Some("src/log"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_file_drops_chunk_text() {
+        let (storage, _temp) = create_test_storage().await;
+        storage.upsert_file("a.rs", 0, "a").await.unwrap();
+        let chunks = vec![
+            text_chunk("a.rs", "fn parseConfig() {}", 1),
+            text_chunk("b.rs", "fn parseConfig() {}", 1),
+        ];
+        storage
+            .replace_chunk_text(&["a.rs", "b.rs"], &chunks)
+            .await
+            .unwrap();
+
+        storage.delete_file("a.rs").await.unwrap();
+
+        let hits = storage
+            .search_chunk_text("parse config", 10, None)
+            .await
+            .unwrap();
+        let paths: Vec<&str> = hits.iter().map(|h| h.file_path.as_str()).collect();
+        assert_eq!(paths, vec!["b.rs"]);
+        assert_eq!(hits[0].content, "fn parseConfig() {}");
+
+        // The index keeps no entries for removed rows
+        let (indexed,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM chunks_fts WHERE chunks_fts MATCH 'parseconfig'")
+                .fetch_one(storage.pool())
+                .await
+                .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_replace_chunk_text_drops_stale_rows() {
+        let (storage, _temp) = create_test_storage().await;
+
+        let old = vec![text_chunk("a.rs", "fn legacyHandler() {}", 1)];
+        storage.replace_chunk_text(&["a.rs"], &old).await.unwrap();
+
+        let new = vec![text_chunk("a.rs", "fn modernHandler() {}", 1)];
+        storage.replace_chunk_text(&["a.rs"], &new).await.unwrap();
+
+        assert!(storage
+            .search_chunk_text("legacy", 10, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            storage
+                .search_chunk_text("modern", 10, None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
     // -------------------------------------------------------------------------
     // Golden samples tests
     // -------------------------------------------------------------------------
@@ -2411,14 +3811,231 @@ mod tests {
         let (storage, _temp) = create_test_storage().await;
 
         storage
-            .log_tool_call("search", Some(r#"{"query":"test"}"#), 150, true, None)
+            .log_tool_call("search", Some(r#"{"query":"test"}"#), 150, true, None, None)
             .await
             .unwrap();
         storage
-            .log_tool_call("get_symbols", None, 50, false, Some("error occurred"))
+            .log_tool_call("get_symbols", None, 50, false, Some("error occurred"), None)
             .await
             .unwrap();
 
         // Just verify no errors - audit log reading would need additional method
     }
+
+    #[tokio::test]
+    async fn test_workspace_state_and_todo_notes() {
+        let (storage, _temp) = create_test_storage().await;
+
+        storage
+            .save_workspace_state("auth", Some("Refactor auth"), "{}", "[]", "[]", 100)
+            .await
+            .unwrap();
+        storage
+            .save_workspace_state(
+                "auth",
+                Some("Refactor auth"),
+                r#"{"progress":40}"#,
+                r#"["src/auth.rs"]"#,
+                "[]",
+                200,
+            )
+            .await
+            .unwrap();
+        let state = storage.get_workspace_state("auth").await.unwrap().unwrap();
+        assert_eq!(state.created_at, 100);
+        assert_eq!(state.updated_at, 200);
+        assert_eq!(state.touched_files, r#"["src/auth.rs"]"#);
+        assert_eq!(storage.list_workspace_states().await.unwrap().len(), 1);
+
+        let id = storage
+            .add_todo_note(
+                "src/auth.rs",
+                Some("verify_token"),
+                Some(12),
+                Some("pub fn verify_token(t: &str) -> bool {"),
+                "Add rate limiting",
+                "high",
+                Some("auth"),
+                300,
+            )
+            .await
+            .unwrap();
+        storage
+            .add_todo_note(
+                "src/api.rs",
+                None,
+                Some(3),
+                None,
+                "Log errors",
+                "low",
+                None,
+                300,
+            )
+            .await
+            .unwrap();
+
+        let filter = TodoFilter {
+            state_id: Some("auth".into()),
+            ..Default::default()
+        };
+        let notes = storage.list_todo_notes(&filter).await.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].symbol_name.as_deref(), Some("verify_token"));
+
+        storage
+            .update_todo_anchor(id, "src/auth/token.rs", Some(40), None)
+            .await
+            .unwrap();
+        assert!(storage.resolve_todo_note(id, 400).await.unwrap());
+        assert!(!storage.resolve_todo_note(id, 500).await.unwrap());
+
+        let open = storage
+            .list_todo_notes(&TodoFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].file_path, "src/api.rs");
+
+        // Deleting the state keeps its notes
+        assert!(storage.delete_workspace_state("auth").await.unwrap());
+        let all = storage
+            .list_todo_notes(&TodoFilter {
+                include_resolved: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].file_path, "src/auth/token.rs");
+        assert!(all.iter().all(|n| n.state_id.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_search_click_feedback() {
+        let (storage, _temp) = create_test_storage().await;
+
+        storage
+            .log_tool_call(
+                "search",
+                Some(r#"{"query":"load config"}"#),
+                10,
+                true,
+                None,
+                Some(r#"["src/config.rs","src/main.rs"]"#),
+            )
+            .await
+            .unwrap();
+
+        let (search_id, args) = storage
+            .find_search_returning("src/config.rs", 0)
+            .await
+            .unwrap()
+            .expect("search returned src/config.rs");
+        assert!(args.unwrap().contains("load config"));
+        assert!(storage
+            .find_search_returning("src/other.rs", 0)
+            .await
+            .unwrap()
+            .is_none());
+
+        let day = 86_400.0;
+        let now = 1_000_000;
+        storage
+            .record_search_click("config load", "src/config.rs", search_id, day, now)
+            .await
+            .unwrap();
+        // Second read after the same search is not a new click
+        storage
+            .record_search_click("config load", "src/config.rs", search_id, day, now + 10)
+            .await
+            .unwrap();
+        // A later search decays the old score by one half-life before adding
+        storage
+            .record_search_click(
+                "config load",
+                "src/config.rs",
+                search_id + 1,
+                day,
+                now + 86_400,
+            )
+            .await
+            .unwrap();
+
+        let rows = storage
+            .get_search_feedback(&["src/config.rs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!((rows[0].score - 1.5).abs() < 1e-9);
+        assert!((rows[0].decayed(now + 2 * 86_400, day) - 0.75).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_import_closure() {
+        let (storage, _temp) = create_test_storage().await;
+        let edge = |from: &str, to: &str, line: i32| ImportEdge {
+            from_path: from.to_string(),
+            to_path: to.to_string(),
+            line,
+            specifier: format!("./{}", to),
+        };
+        for (path, edges) in [
+            ("src/api.rs", vec![edge("src/api.rs", "src/service.rs", 2)]),
+            (
+                "src/service.rs",
+                vec![
+                    edge("src/service.rs", "src/repo.rs", 0),
+                    edge("src/service.rs", "src/api.rs", 5),
+                ],
+            ),
+            ("src/jobs.rs", vec![edge("src/jobs.rs", "src/repo.rs", 1)]),
+        ] {
+            let file_id = storage.upsert_file(path, 0, "hash").await.unwrap();
+            storage.replace_file_imports(file_id, &edges).await.unwrap();
+        }
+
+        let imports = storage
+            .get_import_closure("src/api.rs", 3, false)
+            .await
+            .unwrap();
+        let hops: Vec<(&str, u32)> = imports
+            .iter()
+            .map(|(e, hop)| (e.to_path.as_str(), *hop))
+            .collect();
+        assert_eq!(
+            hops,
+            vec![("src/service.rs", 1), ("src/repo.rs", 2), ("src/api.rs", 2)]
+        );
+
+        let importers = storage
+            .get_import_closure("src/repo.rs", 2, true)
+            .await
+            .unwrap();
+        let mut from: Vec<&str> = importers
+            .iter()
+            .map(|(e, _)| e.from_path.as_str())
+            .collect();
+        from.sort();
+        assert_eq!(from, vec!["src/api.rs", "src/jobs.rs", "src/service.rs"]);
+
+        let counts = storage
+            .get_importer_counts(&["src/repo.rs".to_string(), "src/jobs.rs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(counts.get("src/repo.rs"), Some(&2));
+        assert_eq!(counts.get("src/jobs.rs"), None);
+
+        // Reindexing a file replaces its edges; deleting it drops them
+        let file_id = storage
+            .upsert_file("src/jobs.rs", 1, "hash2")
+            .await
+            .unwrap();
+        storage.replace_file_imports(file_id, &[]).await.unwrap();
+        storage.delete_file("src/service.rs").await.unwrap();
+        let importers = storage
+            .get_import_edges(&["src/repo.rs".to_string()], true)
+            .await
+            .unwrap();
+        assert!(importers.is_empty());
+    }
 }